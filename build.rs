@@ -0,0 +1,18 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "grpc")]
+    grpc();
+}
+
+/// Generate the gRPC service and messages from proto/rbn_vfd.proto
+#[cfg(feature = "grpc")]
+fn grpc() {
+    // protoc from crates.io, so builds don't need it installed
+    let protoc = protoc_bin_vendored::protoc_bin_path().expect("No bundled protoc for this host");
+    std::env::set_var("PROTOC", protoc);
+    println!("cargo:rerun-if-changed=proto/rbn_vfd.proto");
+    tonic_build::configure()
+        .build_client(false)
+        .compile_protos(&["proto/rbn_vfd.proto"], &["proto"])
+        .expect("Failed to compile proto/rbn_vfd.proto");
+}
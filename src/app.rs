@@ -1,6 +1,29 @@
 use crate::config::Config;
-use crate::services::radio::{self, RadioController, RadioMode};
-use crate::services::{RbnClient, RbnMessage, SpotStore, VfdDisplay};
+use crate::logging;
+use crate::models::{
+    find_spot, format_offset, nearest_spot, normalize_callsign, AggregatedSpot, Band, LatLon,
+    SpotSort,
+};
+use crate::paths;
+use crate::services::display;
+use crate::services::display::simulator::{self, SimulatedScreen};
+#[cfg(feature = "grpc")]
+use crate::services::grpc::{GrpcCommand, GrpcServer};
+use crate::services::input::{keypad_action, parse_bindings, ButtonAction};
+#[cfg(target_os = "linux")]
+use crate::services::input::{InputEvent, InputHandler};
+#[cfg(feature = "mqtt")]
+use crate::services::mqtt::MqttPublisher;
+use crate::services::radio::{self, RadioEvent, RadioManager, RadioMode, RadioState};
+#[cfg(feature = "web")]
+use crate::services::web::{WebCommand, WebServer};
+use crate::services::{
+    detail_lines, AppCommand, Callbook, CommandSource, Control, DisplayStatus, HotplugEvent,
+    HotplugWatcher, Integrations, LoggerLink, LotwUsers, RbnClient, RbnMessage, SelfSpotter,
+    SpotAlerts, SpotStore, VfdDisplay,
+};
+#[cfg(target_os = "linux")]
+use crate::services::{AlertSound, LedIndicators};
 use eframe::egui;
 use std::time::{Duration, Instant};
 
@@ -10,6 +33,8 @@ const RAW_DATA_LOG_MAX_LINES: usize = 500;
 /// Main application state
 pub struct RbnVfdApp {
     config: Config,
+    /// Settings changes from the controls, buttons and web clients
+    control: Control,
     spot_store: SpotStore,
     vfd_display: VfdDisplay,
     rbn_client: Option<RbnClient>,
@@ -23,67 +48,234 @@ pub struct RbnVfdApp {
     /// Raw telnet data log for debugging
     raw_data_log: Vec<String>,
     /// Currently selected spot for tuning
-    selected_spot: Option<crate::models::AggregatedSpot>,
-    /// Radio controller for CAT control
-    radio_controller: Box<dyn RadioController>,
+    selected_spot: Option<AggregatedSpot>,
+    /// Radio worker for CAT control
+    radio: RadioManager,
+    /// Cached radio state, refreshed once per frame
+    radio_state: RadioState,
     /// Error message to show in popup
     radio_error: Option<String>,
     /// Whether to show radio settings dialog
     show_radio_settings: bool,
     /// Temporary radio config for settings dialog
     temp_radio_config: Option<crate::config::RadioConfig>,
+    /// Watches the VFD's USB adapter when a VID:PID is configured
+    vfd_hotplug: Option<HotplugWatcher>,
+    /// VFD was open when its adapter was unplugged, reopen on return
+    vfd_unplugged: bool,
+    /// Watches the CAT adapter when a VID:PID is configured
+    cat_hotplug: Option<HotplugWatcher>,
+    /// Web dashboard, when enabled
+    #[cfg(feature = "web")]
+    web_server: Option<WebServer>,
+    /// gRPC service, when enabled
+    #[cfg(feature = "grpc")]
+    grpc_server: Option<GrpcServer>,
+    /// MQTT spot and state publisher, when enabled
+    #[cfg(feature = "mqtt")]
+    mqtt: Option<MqttPublisher>,
+    /// Front-panel rotary encoder, when configured
+    #[cfg(target_os = "linux")]
+    input: Option<InputHandler>,
+    /// Watch-list and new-DXCC checks on incoming spots
+    alerts: SpotAlerts,
+    /// Alert rules, logbooks and spot sharing
+    integrations: Integrations,
+    /// QRZ or HamQTH operator lookups for the detail page, when enabled
+    callbook: Option<Callbook>,
+    /// LoTW users list, when enabled
+    lotw: Option<LotwUsers>,
+    /// Logging program selected spots are looked up in, when enabled
+    logger: Option<LoggerLink>,
+    /// SOTA/POTA self-spots of the `[activation]`
+    self_spotter: SelfSpotter,
+    /// Spot whose detail page was last put up, refreshed by callbook answers
+    detail_spot: Option<AggregatedSpot>,
+    /// Alert LEDs, when configured
+    #[cfg(target_os = "linux")]
+    leds: Option<LedIndicators>,
+    /// Piezo or sound card alerts, when configured
+    #[cfg(target_os = "linux")]
+    sound: Option<AlertSound>,
 }
 
 impl RbnVfdApp {
     /// Create a new application instance
     pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
         let config = Config::load();
-        let radio_controller = radio::create_controller(&config.radio);
+        logging::init(&config.logging, true);
+        let radio = RadioManager::new(config.radio.clone());
+        let radio_state = radio.state();
         let spot_store = SpotStore::new();
         let mut vfd_display = VfdDisplay::new();
         vfd_display.set_scroll_interval(config.scroll_interval_seconds);
         vfd_display.set_random_char_percent(config.random_char_percent);
+        vfd_display.set_driver_config(&config.display);
+        vfd_display.set_region(config.region);
+        vfd_display.set_units(config.units);
+        for extra in &config.extra_displays {
+            if let Err(e) = vfd_display.open_mirror(&extra.settings, &extra.port()) {
+                log::warn!("Display {}: {}", extra.settings.driver, e);
+            }
+        }
+
+        let vfd_hotplug = config.display_usb_id.map(HotplugWatcher::new);
+        let cat_hotplug = config.radio.cat_usb_id.map(HotplugWatcher::new);
+
+        #[cfg(feature = "web")]
+        let web_server = if config.web.enabled {
+            match WebServer::start(
+                &config.web.bind,
+                config.web.port,
+                config.web.api_token.expose(),
+                spot_store.clone(),
+                config.spot_limits(),
+            ) {
+                Ok(server) => Some(server),
+                Err(e) => {
+                    log::error!("{}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        #[cfg(feature = "grpc")]
+        let grpc_server = if config.grpc.enabled {
+            GrpcServer::start(&config.grpc, spot_store.clone())
+                .map_err(|e| log::error!("{}", e))
+                .ok()
+        } else {
+            None
+        };
+
+        // Web and MQTT mirror the display alongside the hardware
+        #[cfg(feature = "web")]
+        if let Some(ref server) = web_server {
+            let (columns, rows) = vfd_display.dimensions();
+            vfd_display.add_output(
+                server.display_output(columns, rows),
+                Duration::from_millis(250),
+            );
+        }
+        #[cfg(feature = "mqtt")]
+        let mqtt = if config.mqtt.enabled {
+            let mqtt = MqttPublisher::start(&config.mqtt, spot_store.clone());
+            let (columns, rows) = vfd_display.dimensions();
+            vfd_display.add_output(mqtt.display_output(columns, rows), Duration::from_secs(1));
+            Some(mqtt)
+        } else {
+            None
+        };
+
+        #[cfg(target_os = "linux")]
+        let input = InputHandler::start(&config.input).unwrap_or_else(|e| {
+            log::warn!("{}", e);
+            None
+        });
+
+        let mut alerts = SpotAlerts::new(&config.alerts);
+        alerts.set_callsign(&config.callsign);
+        if let Err(e) = alerts.load_dxcc(&config.alerts) {
+            log::warn!("{}", e);
+        }
+        let (integrations, errors) = Integrations::start(&config, &spot_store, &alerts);
+        for e in errors {
+            log::error!("{}", e);
+        }
+        let callbook = Callbook::start(&config.qrz, &config.hamqth);
+        let lotw = LotwUsers::start(&config.lotw);
+        let logger = LoggerLink::start(&config.logger);
+        #[cfg(target_os = "linux")]
+        let leds = LedIndicators::start(&config.leds).unwrap_or_else(|e| {
+            log::warn!("{}", e);
+            None
+        });
+        #[cfg(target_os = "linux")]
+        let sound = AlertSound::start(&config.sound, config.units.timezone).unwrap_or_else(|e| {
+            log::warn!("{}", e);
+            None
+        });
 
         let available_ports = VfdDisplay::available_ports();
-        let selected_port = if available_ports.contains(&config.serial_port) {
+        let selected_port = if let Some(port) = vfd_hotplug.as_ref().and_then(|w| w.port()) {
+            port.to_string()
+        } else if available_ports.contains(&config.serial_port) {
             config.serial_port.clone()
         } else {
             available_ports.first().cloned().unwrap_or_default()
         };
 
+        // Only the first settings problem fits; the rest are on stderr
+        let status_message = match config.issues.first() {
+            Some(issue) => format!("Settings: {}", issue),
+            None => "Ready".to_string(),
+        };
+
         Self {
             callsign_input: config.callsign.clone(),
             config,
+            control: Control::new(),
             spot_store,
             vfd_display,
             rbn_client: None,
             selected_port,
             available_ports,
-            status_message: "Ready".to_string(),
+            status_message,
             is_connected: false,
             last_purge: Instant::now(),
             last_port_refresh: Instant::now(),
             raw_data_log: Vec::new(),
             selected_spot: None,
-            radio_controller,
+            radio,
+            radio_state,
             radio_error: None,
             show_radio_settings: false,
             temp_radio_config: None,
+            vfd_hotplug,
+            vfd_unplugged: false,
+            cat_hotplug,
+            #[cfg(feature = "web")]
+            web_server,
+            #[cfg(feature = "grpc")]
+            grpc_server,
+            #[cfg(feature = "mqtt")]
+            mqtt,
+            #[cfg(target_os = "linux")]
+            input,
+            alerts,
+            integrations,
+            callbook,
+            lotw,
+            logger,
+            self_spotter: SelfSpotter::new(),
+            detail_spot: None,
+            #[cfg(target_os = "linux")]
+            leds,
+            #[cfg(target_os = "linux")]
+            sound,
         }
     }
 
     /// Connect to RBN server
     fn connect_rbn(&mut self) {
-        if self.callsign_input.trim().is_empty() {
-            self.status_message = "Please enter a callsign".to_string();
-            return;
-        }
-
-        let callsign = self.callsign_input.trim().to_uppercase();
+        let callsign = match normalize_callsign(&self.callsign_input) {
+            Ok(callsign) => callsign,
+            Err(e) => {
+                self.status_message = e;
+                return;
+            }
+        };
+        self.callsign_input = callsign.clone();
         self.config.callsign = callsign.clone();
+        self.alerts.set_callsign(&callsign);
 
         let client = RbnClient::new();
-        client.connect(callsign);
+        match &self.config.replay_file {
+            Some(path) => client.replay(path.clone()),
+            None => client.connect(callsign, self.config.feed.server.clone()),
+        }
 
         self.rbn_client = Some(client);
         self.is_connected = true;
@@ -102,7 +294,7 @@ impl RbnVfdApp {
 
     /// Open VFD on selected port
     fn open_vfd(&mut self) {
-        if self.selected_port.is_empty() {
+        if self.selected_port.is_empty() && display::uses_serial(&self.config.display.driver) {
             self.status_message = "No serial port selected".to_string();
             return;
         }
@@ -124,6 +316,36 @@ impl RbnVfdApp {
         self.status_message = "VFD closed".to_string();
     }
 
+    /// Show the simulator driver's output in its own window while it is
+    /// open; closing the window closes the display
+    fn show_simulator(&mut self, ctx: &egui::Context) {
+        let Some(screen) = simulator::simulated_screen() else {
+            return;
+        };
+        let size = egui::vec2(
+            screen.columns as f32 * 6.0 * SIM_PITCH,
+            screen.rows as f32 * 10.0 * SIM_PITCH,
+        ) + egui::Vec2::splat(2.0 * SIM_MARGIN);
+
+        let mut closed = false;
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("vfd_simulator"),
+            egui::ViewportBuilder::default()
+                .with_title("VFD Simulator")
+                .with_inner_size(size)
+                .with_resizable(false),
+            |ctx, _class| {
+                egui::CentralPanel::default()
+                    .frame(egui::Frame::new().fill(egui::Color32::from_rgb(4, 10, 9)))
+                    .show(ctx, |ui| draw_simulated_vfd(ui, &screen));
+                closed = ctx.input(|i| i.viewport().close_requested());
+            },
+        );
+        if closed {
+            self.close_vfd();
+        }
+    }
+
     /// Tune the radio to the selected spot
     fn tune_to_selected(&mut self) {
         let Some(spot) = &self.selected_spot else {
@@ -131,21 +353,275 @@ impl RbnVfdApp {
         };
 
         let mode = RadioMode::from_rbn_mode(&spot.mode);
+        if self.config.radio.use_sub_receiver {
+            self.radio.tune_sub(spot.frequency_khz, mode);
+        } else {
+            self.radio.tune(spot.frequency_khz, mode);
+        }
+    }
 
-        match self.radio_controller.tune(spot.frequency_khz, mode) {
-            Ok(()) => {
-                self.status_message = format!(
-                    "Tuned to {:.1} kHz {}",
-                    spot.frequency_khz,
-                    mode.to_rigctld_mode()
-                );
+    /// Put the detail page for a spot up on the display
+    fn show_spot_detail(&mut self, spot: &AggregatedSpot) {
+        let lines = self.spot_detail_lines(spot);
+        self.vfd_display.show_detail(spot, lines);
+        self.detail_spot = Some(spot.clone());
+    }
+
+    fn spot_detail_lines(&mut self, spot: &AggregatedSpot) -> Vec<String> {
+        let home = LatLon::from_grid(&self.config.grid);
+        let operator = self
+            .callbook
+            .as_mut()
+            .and_then(|callbook| callbook.get(&spot.callsign));
+        let lotw = self
+            .lotw
+            .as_ref()
+            .and_then(|lotw| lotw.last_upload(&spot.callsign));
+        detail_lines(spot, &self.alerts, operator, lotw, home, &self.config.units)
+    }
+
+    /// Add callbook answers to the detail page still showing their spot
+    fn process_callbook_answers(&mut self) {
+        let Some(ref mut callbook) = self.callbook else {
+            return;
+        };
+        let answered = callbook.poll();
+        let Some(spot) = self.detail_spot.clone().filter(|spot| {
+            answered
+                .iter()
+                .any(|call| call.eq_ignore_ascii_case(&spot.callsign))
+        }) else {
+            return;
+        };
+        let lines = self.spot_detail_lines(&spot);
+        self.vfd_display.update_detail(&spot.callsign, lines);
+    }
+
+    /// Process events from the radio worker
+    fn process_radio_events(&mut self) {
+        while let Some(event) = self.radio.try_recv() {
+            match event {
+                RadioEvent::Status(s) => {
+                    self.status_message = s;
+                }
+                RadioEvent::Error(e) => {
+                    self.radio_error = Some(e);
+                }
+                RadioEvent::Tuned {
+                    frequency_khz,
+                    mode,
+                } => self.integrations.tuned(frequency_khz, mode),
             }
-            Err(e) => {
-                self.radio_error = Some(e.to_string());
+        }
+        self.radio_state = self.radio.state();
+    }
+
+    /// Handle tune requests from the web dashboard (showing the spot's detail
+    /// page) and keep its filters current
+    #[cfg(feature = "web")]
+    fn process_web_commands(&mut self) {
+        let Some(ref mut server) = self.web_server else {
+            return;
+        };
+        server.set_state(&self.config, self.is_connected);
+        let commands: Vec<WebCommand> = std::iter::from_fn(|| server.try_recv()).collect();
+        for command in commands {
+            match command {
+                WebCommand::Tune {
+                    frequency_khz,
+                    mode,
+                } => self.tune_remote(frequency_khz, &mode),
+                WebCommand::Control(command) => self.control.send(CommandSource::Web, command),
+            }
+        }
+    }
+
+    /// Handle tune and filter requests from gRPC clients
+    #[cfg(feature = "grpc")]
+    fn process_grpc_commands(&mut self) {
+        let Some(ref mut server) = self.grpc_server else {
+            return;
+        };
+        let commands: Vec<GrpcCommand> = std::iter::from_fn(|| server.try_recv()).collect();
+        for command in commands {
+            match command {
+                GrpcCommand::Tune {
+                    frequency_khz,
+                    mode,
+                } => self.tune_remote(frequency_khz, &mode),
+                GrpcCommand::Control(command) => self.control.send(CommandSource::Grpc, command),
+            }
+        }
+    }
+
+    /// Tune to a spot picked from the dashboard or an API: show its detail
+    /// page and tune the radio
+    #[cfg(any(feature = "web", feature = "grpc"))]
+    fn tune_remote(&mut self, frequency_khz: f64, mode: &str) {
+        let spots = self.visible_spots();
+        if let Some((spot, _)) = nearest_spot(&spots, frequency_khz) {
+            self.show_spot_detail(spot);
+        }
+        let mode = RadioMode::from_rbn_mode(mode);
+        if self.config.radio.use_sub_receiver {
+            self.radio.tune_sub(frequency_khz, mode);
+        } else {
+            self.radio.tune(frequency_khz, mode);
+        }
+    }
+
+    /// Scroll through the spots with the front-panel encoder; pressing opens
+    /// the selected one's detail page, pressing again tunes it
+    #[cfg(target_os = "linux")]
+    fn process_input(&mut self) {
+        let Some(ref input) = self.input else {
+            return;
+        };
+        let events: Vec<InputEvent> = std::iter::from_fn(|| input.try_recv()).collect();
+        if events.is_empty() {
+            return;
+        }
+
+        let spots = self.visible_spots();
+        for event in events {
+            // Input while the display is off only wakes it
+            if self.vfd_display.wake() {
+                continue;
             }
+            match event {
+                InputEvent::Turn(detents) => {
+                    if spots.is_empty() {
+                        continue;
+                    }
+                    let current = self
+                        .selected_spot
+                        .as_ref()
+                        .and_then(|s| find_spot(&spots, &s.callsign, s.frequency_khz));
+                    let index = match current {
+                        Some(index) => {
+                            (index as i64 + detents as i64).clamp(0, spots.len() as i64 - 1)
+                        }
+                        None => 0,
+                    } as usize;
+                    self.selected_spot = Some(spots[index].clone());
+                    self.vfd_display.select_spot(Some(&spots[index]));
+                    if let Some(ref mut logger) = self.logger {
+                        logger.select(&spots[index]);
+                    }
+                }
+                // First press opens the detail page, the next one tunes
+                InputEvent::Press if self.vfd_display.showing_detail() => {
+                    if let Some(ref spot) = self.selected_spot {
+                        self.status_message = format!(
+                            "Tuning {} on {}",
+                            spot.callsign,
+                            self.config.units.frequency_with_unit(spot.frequency_khz)
+                        );
+                    }
+                    self.tune_to_selected();
+                    self.vfd_display.select_spot(None);
+                }
+                InputEvent::Press => {
+                    if let Some(spot) = self.selected_spot.clone() {
+                        self.show_spot_detail(&spot);
+                    }
+                }
+                InputEvent::Action(action) => self.apply_action(action, CommandSource::Button),
+            }
+        }
+    }
+
+    /// Run a front-panel button or keypad action (settings changes are
+    /// queued like any other command)
+    fn apply_action(&mut self, action: ButtonAction, source: CommandSource) {
+        if let Some(command) = action.command() {
+            self.control.send(source, command);
+            return;
+        }
+        self.status_message = match action {
+            ButtonAction::PageUp | ButtonAction::PageDown => {
+                let step = if action == ButtonAction::PageDown {
+                    1
+                } else {
+                    -1
+                };
+                self.vfd_display.step_page(step);
+                format!("Display page: {}", self.vfd_display.page_label())
+            }
+            ButtonAction::SelfSpot => self.self_spot(),
+            // NextView; settings actions were queued above
+            _ => {
+                self.vfd_display.step_view(1);
+                format!("Display view: {}", self.vfd_display.view_name())
+            }
+        };
+    }
+
+    /// Post a self-spot of the activation, returning the status line
+    fn self_spot(&mut self) -> String {
+        self.self_spotter
+            .post(&self.config, &self.radio_state)
+            .unwrap_or_else(|e| e)
+    }
+
+    /// Apply queued settings changes
+    fn process_commands(&mut self) {
+        while let Some((command, result)) = self.control.try_apply(&mut self.config) {
+            match result {
+                Ok(status) => {
+                    if command.changes_profile() {
+                        self.profile_changed();
+                    }
+                    if command.changes_radio() {
+                        self.radio_changed();
+                    }
+                    self.status_message = status;
+                }
+                Err(e) => self.status_message = e,
+            }
+        }
+    }
+
+    /// Hand a new profile's alert settings to the services using them
+    fn profile_changed(&mut self) {
+        self.alerts.set_lists(&self.config.alerts);
+        self.integrations.set_lists(&self.config.alerts);
+        self.vfd_display
+            .set_attention(&self.config.display.attention);
+        #[cfg(target_os = "linux")]
+        if let Some(ref mut sound) = self.sound {
+            sound.set_alerts(&self.config.sound.alerts);
         }
     }
 
+    /// Rebuild the radio worker and CAT hotplug watch for the radio in use
+    fn radio_changed(&mut self) {
+        self.radio.reconfigure(self.config.radio.clone());
+        self.cat_hotplug = self.config.radio.cat_usb_id.map(HotplugWatcher::new);
+    }
+
+    /// Write the received lines of the raw log to a new capture file
+    fn save_capture(&self) -> Result<String, String> {
+        let path = paths::new_capture_file().ok_or("Could not determine data directory")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let lines: Vec<&str> = self
+            .raw_data_log
+            .iter()
+            .filter_map(|line| line.strip_prefix("<< "))
+            .collect();
+        std::fs::write(&path, lines.join("\n") + "\n")
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(format!("Saved {} lines to {}", lines.len(), path.display()))
+    }
+
+    /// Spots passing the SNR, age, band and mode filters, by frequency
+    fn visible_spots(&self) -> Vec<AggregatedSpot> {
+        self.config.filter_spots(&self.spot_store, &self.alerts)
+    }
+
     /// Process incoming RBN messages
     fn process_rbn_messages(&mut self) {
         // Collect messages first to avoid borrow conflicts
@@ -167,8 +643,25 @@ impl RbnVfdApp {
                     self.status_message = s;
                 }
                 RbnMessage::Spot(raw) => {
+                    if let Some(alert) = self.integrations.spot(&raw, &mut self.alerts) {
+                        self.status_message = alert.to_string();
+                        #[cfg(target_os = "linux")]
+                        if let Some(ref leds) = self.leds {
+                            leds.alert(&alert);
+                        }
+                        #[cfg(target_os = "linux")]
+                        if let Some(ref sound) = self.sound {
+                            sound.alert(&alert);
+                        }
+                        self.vfd_display.alert(&alert);
+                    }
+                    self.vfd_display.count_spot();
                     self.spot_store.add_spot(raw);
                 }
+                RbnMessage::Announcement(announcement) => {
+                    self.integrations.announcement(&announcement);
+                    self.vfd_display.add_announcement(announcement);
+                }
                 RbnMessage::Disconnected => {
                     self.is_connected = false;
                     should_disconnect = true;
@@ -190,6 +683,56 @@ impl RbnVfdApp {
         }
     }
 
+    /// Carry out the LED and beep actions of alert rules that fired
+    fn process_rule_matches(&mut self) {
+        while let Some(fired) = self.integrations.try_recv_match() {
+            self.status_message = fired.alert.to_string();
+            #[cfg(target_os = "linux")]
+            if let Some(ref leds) = self.leds {
+                leds.flash(&fired.led);
+            }
+            #[cfg(target_os = "linux")]
+            if let Some(sound) = self.sound.as_ref().filter(|_| fired.beep) {
+                sound.beep(&fired.alert);
+            }
+            self.vfd_display.alert(&fired.alert);
+        }
+    }
+
+    /// React to the VFD or CAT USB adapters being unplugged or plugged back in
+    fn poll_hotplug(&mut self) {
+        match self.vfd_hotplug.as_mut().and_then(|w| w.poll()) {
+            Some(HotplugEvent::Removed(path)) => {
+                if self.vfd_display.is_open() {
+                    self.vfd_display.close();
+                    self.vfd_unplugged = true;
+                }
+                self.status_message = format!("VFD adapter removed from {}", path);
+            }
+            Some(HotplugEvent::Arrived(path)) => {
+                let reopen = self.vfd_display.is_open() || self.vfd_unplugged;
+                self.selected_port = path;
+                if reopen {
+                    self.vfd_unplugged = false;
+                    self.open_vfd();
+                }
+            }
+            None => {}
+        }
+
+        match self.cat_hotplug.as_mut().and_then(|w| w.poll()) {
+            Some(HotplugEvent::Removed(path)) => {
+                self.radio.disconnect();
+                self.status_message = format!("CAT adapter removed from {}", path);
+            }
+            Some(HotplugEvent::Arrived(path)) if self.config.radio.enabled => {
+                self.radio.connect();
+                self.status_message = format!("CAT adapter on {}, reconnecting radio", path);
+            }
+            _ => {}
+        }
+    }
+
     /// Perform periodic updates
     fn update_periodic(&mut self) {
         let now = Instant::now();
@@ -203,15 +746,109 @@ impl RbnVfdApp {
         // Refresh available ports every 5 seconds
         if now.duration_since(self.last_port_refresh) >= Duration::from_secs(5) {
             self.available_ports = VfdDisplay::available_ports();
+            self.poll_hotplug();
             self.last_port_refresh = now;
         }
 
+        #[cfg(target_os = "linux")]
+        if let Some(ref mut leds) = self.leds {
+            leds.set_connected(self.is_connected);
+        }
+
         // Update VFD display
-        let max_age = Duration::from_secs(self.config.max_age_minutes as u64 * 60);
-        let spots = self
-            .spot_store
-            .get_filtered_spots(self.config.min_snr, max_age);
+        let spots = self.visible_spots();
+        self.vfd_display.set_vfo(self.radio_state.frequency_khz);
+        let tints = spots
+            .iter()
+            .filter_map(|spot| Some((spot.callsign.clone(), self.alerts.tint(spot)?)))
+            .collect();
+        self.vfd_display.set_tints(tints);
+        let locations = spots
+            .iter()
+            .filter_map(|spot| Some((spot.callsign.clone(), self.alerts.location(&spot.callsign)?)))
+            .collect();
+        self.vfd_display
+            .set_locations(LatLon::from_grid(&self.config.grid), locations);
+        self.vfd_display.set_status(DisplayStatus {
+            connected: self.is_connected,
+            filters: self.config.filter_summary(),
+            radio: if self.config.radio.enabled {
+                self.radio_state.summary()
+            } else {
+                String::new()
+            },
+        });
         self.vfd_display.update(&spots);
+        self.integrations.update(&spots, &self.alerts);
+        if let Some(ref mut logger) = self.logger {
+            logger.update();
+        }
+        #[cfg(feature = "mqtt")]
+        if let Some(ref mut mqtt) = self.mqtt {
+            mqtt.publish_state(&self.config, self.is_connected, &self.radio_state);
+            while let Some(announcement) = mqtt.try_recv() {
+                self.status_message = announcement.to_string();
+                self.vfd_display.add_announcement(announcement);
+            }
+        }
+
+        if let Some(result) = self.self_spotter.try_recv() {
+            self.status_message = result.unwrap_or_else(|e| e);
+        }
+
+        // Keypad on the display (Matrix Orbital): keys bound in
+        // `[input] keypad` run their action, others step the display view
+        let keys = self.vfd_display.poll_keys();
+        if !keys.is_empty() {
+            let bindings = parse_bindings(&self.config.input.keypad);
+            for key in keys {
+                let action = keypad_action(&bindings, key).unwrap_or(ButtonAction::NextView);
+                self.apply_action(action, CommandSource::Keypad);
+            }
+        }
+    }
+}
+
+/// Size of one dot and the distance between dots in the simulator window
+const SIM_DOT: f32 = 4.0;
+const SIM_PITCH: f32 = 5.0;
+const SIM_MARGIN: f32 = 12.0;
+
+/// Paint a simulated VFD: lit dots glow blue-green over a faint dot grid
+fn draw_simulated_vfd(ui: &mut egui::Ui, screen: &SimulatedScreen) {
+    let lit =
+        egui::Color32::from_rgb(90, 255, 210).gamma_multiply(screen.brightness as f32 / 100.0);
+    let glow = lit.gamma_multiply(0.15);
+    let unlit = egui::Color32::from_rgb(10, 28, 24);
+
+    let painter = ui.painter();
+    let origin = ui.max_rect().min + egui::vec2(SIM_MARGIN, SIM_MARGIN);
+    for (row, cells) in screen.cells.iter().enumerate() {
+        if row > 0 && screen.large[row - 1] {
+            continue;
+        }
+        // Large rows draw each dot as a 2x2 block over this row and the next
+        let scale = if screen.large[row] { 2.0 } else { 1.0 };
+        for (column, c) in cells.iter().enumerate() {
+            let dots = screen.dots(*c);
+            // 5x8 dots per cell, one dot of space between cells and two
+            // between rows
+            let cell =
+                origin + egui::vec2(column as f32 * 6.0 * scale, row as f32 * 10.0) * SIM_PITCH;
+            for (y, bits) in dots.iter().enumerate() {
+                for x in 0..5 {
+                    let offset = egui::vec2(x as f32, y as f32) * SIM_PITCH * scale;
+                    let size = egui::Vec2::splat(SIM_DOT + (scale - 1.0) * SIM_PITCH);
+                    let dot = egui::Rect::from_min_size(cell + offset, size);
+                    if bits & (0x10 >> x) != 0 {
+                        painter.circle_filled(dot.center(), size.x * 1.5, glow);
+                        painter.rect_filled(dot, 1.0, lit);
+                    } else {
+                        painter.rect_filled(dot, 1.0, unlit);
+                    }
+                }
+            }
+        }
     }
 }
 
@@ -265,6 +902,17 @@ impl eframe::App for RbnVfdApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // Process messages and periodic updates
         self.process_rbn_messages();
+        self.integrations.poll(&mut self.alerts);
+        self.process_callbook_answers();
+        self.process_rule_matches();
+        self.process_radio_events();
+        #[cfg(feature = "web")]
+        self.process_web_commands();
+        #[cfg(feature = "grpc")]
+        self.process_grpc_commands();
+        #[cfg(target_os = "linux")]
+        self.process_input();
+        self.process_commands();
         self.update_periodic();
 
         // Request repaint for continuous updates
@@ -291,6 +939,8 @@ impl eframe::App for RbnVfdApp {
                 {
                     self.connect_rbn();
                 }
+                ui.label("Grid:");
+                ui.add(egui::TextEdit::singleline(&mut self.config.grid).desired_width(60.0));
 
                 if self.is_connected {
                     if ui.button("Disconnect").clicked() {
@@ -307,6 +957,24 @@ impl eframe::App for RbnVfdApp {
             ui.horizontal(|ui| {
                 ui.label("VFD Port:");
 
+                ui.add_enabled_ui(!self.vfd_display.is_open(), |ui| {
+                    let before = self.config.display.driver.clone();
+                    egui::ComboBox::from_id_salt("driver_selector")
+                        .selected_text(&self.config.display.driver)
+                        .show_ui(ui, |ui| {
+                            for name in display::registered_drivers() {
+                                ui.selectable_value(
+                                    &mut self.config.display.driver,
+                                    name.clone(),
+                                    name,
+                                );
+                            }
+                        });
+                    if self.config.display.driver != before {
+                        self.vfd_display.set_driver_config(&self.config.display);
+                    }
+                });
+
                 egui::ComboBox::from_id_salt("port_selector")
                     .selected_text(&self.selected_port)
                     .show_ui(ui, |ui| {
@@ -333,13 +1001,28 @@ impl eframe::App for RbnVfdApp {
             // Radio settings button
             ui.horizontal(|ui| {
                 ui.label("Radio:");
-                ui.label(if self.radio_controller.is_connected() {
-                    format!("{} connected", self.radio_controller.backend_name())
+                ui.label(if self.radio_state.connected {
+                    format!("{} connected", self.radio_state.backend)
                 } else if self.config.radio.enabled {
-                    format!("{} disconnected", self.radio_controller.backend_name())
+                    format!("{} disconnected", self.radio_state.backend)
                 } else {
                     "Not configured".to_string()
                 });
+                if let (Some(freq), Some(mode)) =
+                    (self.radio_state.frequency_khz, self.radio_state.mode)
+                {
+                    ui.label(format!(
+                        "{} {}",
+                        self.config.units.frequency_with_unit(freq),
+                        mode.to_rigctld_mode()
+                    ));
+                }
+                if self.radio_state.split == Some(true) {
+                    ui.label("SPLIT");
+                }
+                if let Some(sub) = self.radio_state.sub_frequency_khz {
+                    ui.label(format!("Sub {}", self.config.units.frequency(sub)));
+                }
                 if ui.button("Settings...").clicked() {
                     self.show_radio_settings = true;
                 }
@@ -353,10 +1036,39 @@ impl eframe::App for RbnVfdApp {
                 ui.label(&self.status_message);
             });
 
+            #[cfg(feature = "web")]
+            if let Some(ref server) = self.web_server {
+                ui.horizontal(|ui| {
+                    ui.label("Web:");
+                    ui.label(format!("http://{}/", server.address()));
+                });
+            }
+            #[cfg(feature = "grpc")]
+            if let Some(ref server) = self.grpc_server {
+                ui.horizontal(|ui| {
+                    ui.label("gRPC:");
+                    ui.label(server.address());
+                });
+            }
+            if let Some(server) = self.integrations.telnet() {
+                ui.horizontal(|ui| {
+                    ui.label("Telnet:");
+                    ui.label(format!(
+                        "{} ({} connected)",
+                        server.address(),
+                        server.clients()
+                    ));
+                });
+            }
+
             if self.vfd_display.is_open() {
                 ui.horizontal(|ui| {
                     ui.label("VFD:");
-                    ui.label(format!("Open on {}", self.vfd_display.port_name()));
+                    ui.label(format!(
+                        "{} open on {}",
+                        self.vfd_display.driver_name().unwrap_or_default(),
+                        self.vfd_display.port_name()
+                    ));
                 });
             }
 
@@ -364,6 +1076,55 @@ impl eframe::App for RbnVfdApp {
 
             // Filter controls
             ui.collapsing("Filters", |ui| {
+                // Named profiles from settings.toml (also on a button)
+                if !self.config.profiles.is_empty() {
+                    ui.horizontal(|ui| {
+                        ui.label("Profile:");
+                        let mut selected = self.config.active_profile.clone();
+                        egui::ComboBox::from_id_salt("profile")
+                            .selected_text(self.config.profile_label())
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut selected, String::new(), "main");
+                                for name in self.config.profiles.keys() {
+                                    ui.selectable_value(&mut selected, name.clone(), name);
+                                }
+                            });
+                        if selected != self.config.active_profile {
+                            self.control
+                                .send(CommandSource::Gui, AppCommand::SetProfile(selected));
+                        }
+                    });
+
+                    ui.add_space(4.0);
+                }
+
+                // Filter presets from settings.toml, standing in for the
+                // filters below while one is picked
+                if !self.config.presets.is_empty() {
+                    ui.horizontal(|ui| {
+                        ui.label("Preset:");
+                        let mut selected = self.config.active_preset;
+                        egui::ComboBox::from_id_salt("preset")
+                            .selected_text(self.config.preset_label())
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut selected, None, "none");
+                                for (index, preset) in self.config.presets.iter().enumerate() {
+                                    ui.selectable_value(&mut selected, Some(index), &preset.name);
+                                }
+                            });
+                        if selected != self.config.active_preset {
+                            let name = selected
+                                .and_then(|index| self.config.presets.get(index))
+                                .map(|preset| preset.name.clone())
+                                .unwrap_or_default();
+                            self.control
+                                .send(CommandSource::Gui, AppCommand::SetPreset(name));
+                        }
+                    });
+
+                    ui.add_space(4.0);
+                }
+
                 // Min SNR slider
                 ui.horizontal(|ui| {
                     ui.label("Min SNR:");
@@ -372,7 +1133,33 @@ impl eframe::App for RbnVfdApp {
                         .add(egui::Slider::new(&mut snr, 0..=50).suffix(" dB"))
                         .changed()
                     {
-                        self.config.min_snr = snr;
+                        self.control
+                            .send(CommandSource::Gui, AppCommand::SetMinSnr(snr));
+                    }
+                });
+
+                ui.add_space(4.0);
+
+                // Band and mode filters (also on front-panel buttons)
+                ui.horizontal(|ui| {
+                    ui.label("Band:");
+                    let mut band_filter = self.config.band_filter;
+                    egui::ComboBox::from_id_salt("band_filter")
+                        .selected_text(band_filter.map_or("All", |band| band.name()))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut band_filter, None, "All");
+                            for band in Band::ALL {
+                                ui.selectable_value(&mut band_filter, Some(band), band.name());
+                            }
+                        });
+                    if band_filter != self.config.band_filter {
+                        self.control
+                            .send(CommandSource::Gui, AppCommand::SetBand(band_filter));
+                    }
+                    let mut cw_only = self.config.cw_only;
+                    if ui.checkbox(&mut cw_only, "CW only").changed() {
+                        self.control
+                            .send(CommandSource::Gui, AppCommand::SetCwOnly(cw_only));
                     }
                 });
 
@@ -387,7 +1174,21 @@ impl eframe::App for RbnVfdApp {
                             .radio(self.config.max_age_minutes == age, format!("{} min", age))
                             .clicked()
                         {
-                            self.config.max_age_minutes = age;
+                            self.control
+                                .send(CommandSource::Gui, AppCommand::SetMaxAge(age));
+                        }
+                    }
+                });
+
+                ui.add_space(4.0);
+
+                // Sort order radio buttons
+                ui.horizontal(|ui| {
+                    ui.label("Sort:");
+                    for sort in SpotSort::ALL {
+                        if ui.radio(self.config.sort == sort, sort.name()).clicked() {
+                            self.control
+                                .send(CommandSource::Gui, AppCommand::SetSort(sort));
                         }
                     }
                 });
@@ -468,19 +1269,11 @@ impl eframe::App for RbnVfdApp {
                             Some(egui::Color32::from_rgb(0, 255, 0));
 
                         // Use monospace font
-                        let line1 = if preview[0].is_empty() {
-                            " ".repeat(20)
-                        } else {
-                            format!("{:20}", preview[0])
-                        };
-                        let line2 = if preview[1].is_empty() {
-                            " ".repeat(20)
-                        } else {
-                            format!("{:20}", preview[1])
-                        };
-
-                        ui.label(egui::RichText::new(&line1).monospace().size(16.0));
-                        ui.label(egui::RichText::new(&line2).monospace().size(16.0));
+                        let (width, _) = self.vfd_display.dimensions();
+                        for line in &preview {
+                            let line = format!("{:width$}", line, width = width);
+                            ui.label(egui::RichText::new(&line).monospace().size(16.0));
+                        }
                     });
             });
 
@@ -493,6 +1286,15 @@ impl eframe::App for RbnVfdApp {
                     if ui.button("Clear").clicked() {
                         self.raw_data_log.clear();
                     }
+                    if ui
+                        .button("Save")
+                        .on_hover_text("Save received lines for --replay")
+                        .clicked()
+                    {
+                        self.status_message = match self.save_capture() {
+                            Ok(message) | Err(message) => message,
+                        };
+                    }
                 });
 
                 egui::ScrollArea::vertical()
@@ -533,7 +1335,7 @@ impl eframe::App for RbnVfdApp {
             // Tune controls
             ui.horizontal(|ui| {
                 // Connection indicator
-                let connected = self.radio_controller.is_connected();
+                let connected = self.radio_state.connected;
                 let indicator_color = if connected {
                     egui::Color32::from_rgb(0, 200, 0)
                 } else {
@@ -544,6 +1346,26 @@ impl eframe::App for RbnVfdApp {
                 ui.painter()
                     .circle_filled(rect.center(), 5.0, indicator_color);
 
+                // `[[radio]]` entries to tune with
+                if self.config.radios.len() > 1 {
+                    let mut selected = self.config.radio.name.clone();
+                    egui::ComboBox::from_id_salt("radio")
+                        .selected_text(self.config.radio_label())
+                        .show_ui(ui, |ui| {
+                            for radio in &self.config.radios {
+                                ui.selectable_value(
+                                    &mut selected,
+                                    radio.name.clone(),
+                                    radio.name.as_str(),
+                                );
+                            }
+                        });
+                    if selected != self.config.radio.name {
+                        self.control
+                            .send(CommandSource::Gui, AppCommand::SetRadio(selected));
+                    }
+                }
+
                 // Tune button
                 let can_tune = connected && self.selected_spot.is_some();
                 if ui
@@ -552,20 +1374,52 @@ impl eframe::App for RbnVfdApp {
                 {
                     self.tune_to_selected();
                 }
+                if ui
+                    .add_enabled(self.selected_spot.is_some(), egui::Button::new("Details"))
+                    .on_hover_text("Show who heard the spot, where it is and if it's worked")
+                    .clicked()
+                {
+                    if let Some(spot) = self.selected_spot.clone() {
+                        self.show_spot_detail(&spot);
+                    }
+                }
+
+                ui.checkbox(&mut self.config.radio.use_sub_receiver, "Sub RX")
+                    .on_hover_text("Tune the sub receiver instead of the main VFO");
+
+                // Back button returns to the frequency before the last tune
+                let can_undo = connected && self.radio_state.qsy_history_len > 0;
+                if ui
+                    .add_enabled(can_undo, egui::Button::new("Back"))
+                    .on_hover_text("Return to the previous frequency")
+                    .clicked()
+                {
+                    self.radio.undo_qsy();
+                }
+
+                let activating = !self.config.activation.reference.trim().is_empty();
+                if ui
+                    .add_enabled(connected && activating, egui::Button::new("Self-spot"))
+                    .on_hover_text("Spot your activation on SOTA or POTA at this frequency")
+                    .clicked()
+                {
+                    self.status_message = self.self_spot();
+                }
 
                 // Show selected spot info
                 if let Some(spot) = &self.selected_spot {
-                    ui.label(format!("{} @ {:.1} kHz", spot.callsign, spot.frequency_khz));
+                    ui.label(format!(
+                        "{} @ {}",
+                        spot.callsign,
+                        self.config.units.frequency_with_unit(spot.frequency_khz)
+                    ));
                 }
             });
 
             egui::ScrollArea::vertical()
                 .auto_shrink([false, false])
                 .show(ui, |ui| {
-                    let max_age = Duration::from_secs(self.config.max_age_minutes as u64 * 60);
-                    let spots = self
-                        .spot_store
-                        .get_filtered_spots(self.config.min_snr, max_age);
+                    let spots = self.visible_spots();
                     if spots.is_empty() {
                         ui.label("No spots yet. Connect to RBN to receive spots.");
                     } else {
@@ -601,10 +1455,23 @@ impl eframe::App for RbnVfdApp {
                                     .monospace()
                                     .strong(),
                             );
+                            ui.label(
+                                egui::RichText::new(format!("{:>5}", "Want"))
+                                    .monospace()
+                                    .strong(),
+                            )
+                            .on_hover_text(
+                                "Club Log most-wanted rank, + when the entity or band slot is needed",
+                            );
                         });
 
                         ui.separator();
 
+                        let nearest = self
+                            .radio_state
+                            .frequency_khz
+                            .and_then(|vfo| nearest_spot(&spots, vfo));
+                        let limits = self.config.spot_limits();
                         for spot in &spots {
                             let is_selected = self
                                 .selected_spot
@@ -622,26 +1489,33 @@ impl eframe::App for RbnVfdApp {
                             } else {
                                 format!("{:>3}m", age_secs / 60)
                             };
-                            let row_text = format!(
-                                "{:>10.1} {:<10} {:>4} {:>5} {:>5} {}",
-                                spot.frequency_khz,
+                            let mut row_text = format!(
+                                "{:>10} {:<10} {:>4} {:>5} {:>5} {} {:>5}",
+                                self.config.units.frequency(spot.frequency_khz),
                                 spot.callsign,
                                 spot.highest_snr,
                                 spot.average_speed.round() as i32,
                                 spot.spot_count,
-                                age_text
+                                age_text,
+                                self.alerts.want_label(spot)
                             );
+                            // Highlight the spot the radio is tuned to, with its offset
+                            let is_nearest =
+                                nearest.is_some_and(|(near, _)| std::ptr::eq(near, spot));
+                            if let Some((_, offset)) = nearest.filter(|_| is_nearest) {
+                                row_text = format!("{} {}", row_text, format_offset(offset));
+                            }
+                            let mut row_label = egui::RichText::new(&row_text).monospace();
+                            if is_nearest {
+                                row_label = row_label.color(egui::Color32::from_rgb(255, 200, 0));
+                            }
 
                             // Use selectable_label for proper click handling
                             let response = ui.horizontal(|ui| {
-                                let response = ui.selectable_label(
-                                    is_selected,
-                                    egui::RichText::new(&row_text).monospace(),
-                                );
+                                let response = ui.selectable_label(is_selected, row_label);
 
                                 // Ring indicator
-                                let max_age =
-                                    Duration::from_secs(self.config.max_age_minutes as u64 * 60);
+                                let (_, max_age) = limits.for_khz(spot.frequency_khz);
                                 let fraction = spot.age_fraction(max_age);
                                 draw_age_ring(ui, fraction);
 
@@ -651,6 +1525,9 @@ impl eframe::App for RbnVfdApp {
                             // Handle click to select
                             if response.inner.clicked() {
                                 self.selected_spot = Some(spot.clone());
+                                if let Some(ref mut logger) = self.logger {
+                                    logger.select(spot);
+                                }
                             }
 
                             // Handle double-click to tune
@@ -705,12 +1582,29 @@ impl eframe::App for RbnVfdApp {
                             ui.horizontal(|ui| {
                                 ui.radio_value(&mut temp.backend, "omnirig".to_string(), "OmniRig");
                                 ui.radio_value(&mut temp.backend, "rigctld".to_string(), "rigctld");
+                                // Out-of-tree backends added with register_backend()
+                                for name in radio::registered_backends()
+                                    .into_iter()
+                                    .filter(|name| name != "omnirig" && name != "rigctld")
+                                {
+                                    ui.radio_value(&mut temp.backend, name.clone(), name);
+                                }
                             });
                         }
 
                         #[cfg(not(target_os = "windows"))]
                         {
-                            ui.label("Backend: rigctld");
+                            let backends = radio::registered_backends();
+                            if backends.len() > 1 {
+                                ui.label("Backend:");
+                                ui.horizontal(|ui| {
+                                    for name in backends {
+                                        ui.radio_value(&mut temp.backend, name.clone(), name);
+                                    }
+                                });
+                            } else {
+                                ui.label("Backend: rigctld");
+                            }
                         }
 
                         ui.add_space(8.0);
@@ -755,6 +1649,154 @@ impl eframe::App for RbnVfdApp {
                             });
                         }
 
+                        if !cfg!(target_os = "windows") || temp.backend == "rigctld" {
+                            ui.add_space(8.0);
+                            ui.checkbox(&mut temp.manage_rigctld, "Start rigctld automatically");
+                            if temp.manage_rigctld {
+                                egui::Grid::new("rigctld_launch_grid").num_columns(2).show(
+                                    ui,
+                                    |ui| {
+                                        ui.label("Program:");
+                                        ui.text_edit_singleline(&mut temp.rigctld_path);
+                                        ui.end_row();
+                                        ui.label("Rig model:");
+                                        ui.add(egui::DragValue::new(&mut temp.rigctld_model));
+                                        ui.end_row();
+                                        ui.label("Device:");
+                                        ui.text_edit_singleline(&mut temp.rigctld_device);
+                                        ui.end_row();
+                                        ui.label("Extra args:");
+                                        ui.text_edit_singleline(&mut temp.rigctld_args);
+                                        ui.end_row();
+                                    },
+                                );
+                            }
+                        }
+
+                        ui.add_space(8.0);
+
+                        ui.horizontal(|ui| {
+                            ui.label("Calibration offset:");
+                            ui.add(
+                                egui::DragValue::new(&mut temp.calibration_offset_hz)
+                                    .range(-5000..=5000)
+                                    .suffix(" Hz"),
+                            )
+                            .on_hover_text("Positive if the rig reads high");
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.label("Transverter offset:");
+                            ui.add(
+                                egui::DragValue::new(&mut temp.transverter_offset_khz)
+                                    .range(0.0..=10_000_000.0)
+                                    .suffix(" kHz"),
+                            )
+                            .on_hover_text("Local oscillator, e.g. 116000 for 2 m on a 10 m rig");
+                        });
+
+                        ui.add_space(8.0);
+
+                        ui.checkbox(&mut temp.smooth_qsy, "Smooth QSY (step to new frequency)");
+                        if temp.smooth_qsy {
+                            ui.horizontal(|ui| {
+                                ui.label("Step:");
+                                ui.add(
+                                    egui::DragValue::new(&mut temp.smooth_qsy_step_hz)
+                                        .range(10..=100000)
+                                        .suffix(" Hz"),
+                                );
+                                ui.label("Delay:");
+                                ui.add(
+                                    egui::DragValue::new(&mut temp.smooth_qsy_delay_ms)
+                                        .range(0..=1000)
+                                        .suffix(" ms"),
+                                );
+                            });
+                        }
+
+                        ui.add_space(8.0);
+
+                        ui.collapsing("Filter Widths", |ui| {
+                            ui.label("Filter width set after tuning (0 = rig default)");
+                            egui::Grid::new("filter_width_grid")
+                                .num_columns(2)
+                                .show(ui, |ui| {
+                                    ui.label("CW:");
+                                    ui.add(
+                                        egui::DragValue::new(&mut temp.cw_filter_hz)
+                                            .range(0..=10000)
+                                            .suffix(" Hz"),
+                                    );
+                                    ui.end_row();
+                                    ui.label("SSB:");
+                                    ui.add(
+                                        egui::DragValue::new(&mut temp.ssb_filter_hz)
+                                            .range(0..=10000)
+                                            .suffix(" Hz"),
+                                    );
+                                    ui.end_row();
+                                    ui.label("Digital:");
+                                    ui.add(
+                                        egui::DragValue::new(&mut temp.data_filter_hz)
+                                            .range(0..=10000)
+                                            .suffix(" Hz"),
+                                    );
+                                    ui.end_row();
+                                });
+                        });
+
+                        ui.add_space(8.0);
+
+                        ui.collapsing("Tune Hooks", |ui| {
+                            ui.label("Shell commands get TUNE_FREQ_KHZ and TUNE_MODE");
+                            egui::Grid::new("tune_hooks_grid")
+                                .num_columns(2)
+                                .show(ui, |ui| {
+                                    ui.label("Before tune:");
+                                    ui.text_edit_singleline(&mut temp.pre_tune_command);
+                                    ui.end_row();
+                                    ui.label("After tune:");
+                                    ui.text_edit_singleline(&mut temp.post_tune_command);
+                                    ui.end_row();
+                                    ui.label("CAT before:");
+                                    ui.text_edit_singleline(&mut temp.pre_tune_cat);
+                                    ui.end_row();
+                                    ui.label("CAT after:");
+                                    ui.text_edit_singleline(&mut temp.post_tune_cat);
+                                    ui.end_row();
+                                });
+                        });
+
+                        ui.add_space(8.0);
+
+                        ui.collapsing("Band Power Presets", |ui| {
+                            ui.label("TX power set after tuning to a new band (0 = unchanged)");
+                            egui::Grid::new("band_power_grid")
+                                .num_columns(2)
+                                .show(ui, |ui| {
+                                    for band in Band::ALL {
+                                        ui.label(band.name());
+                                        let mut percent =
+                                            temp.band_power.get(&band).copied().unwrap_or(0);
+                                        if ui
+                                            .add(
+                                                egui::Slider::new(&mut percent, 0..=100)
+                                                    .suffix("%"),
+                                            )
+                                            .changed()
+                                        {
+                                            if percent == 0 {
+                                                temp.band_power.remove(&band);
+                                            } else {
+                                                temp.band_power.insert(band, percent);
+                                            }
+                                        }
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+
                         ui.add_space(8.0);
 
                         // Test connection button
@@ -793,10 +1835,7 @@ impl eframe::App for RbnVfdApp {
             if apply_settings {
                 if let Some(temp) = self.temp_radio_config.take() {
                     self.config.radio = temp;
-                    self.radio_controller = radio::create_controller(&self.config.radio);
-                    if self.config.radio.enabled {
-                        let _ = self.radio_controller.connect();
-                    }
+                    self.radio_changed();
                 }
                 self.show_radio_settings = false;
             }
@@ -806,6 +1845,8 @@ impl eframe::App for RbnVfdApp {
                 self.temp_radio_config = None;
             }
         }
+
+        self.show_simulator(ctx);
     }
 
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
@@ -819,7 +1860,7 @@ impl eframe::App for RbnVfdApp {
 
         // Save config
         if let Err(e) = self.config.save() {
-            eprintln!("Failed to save config: {}", e);
+            log::error!("Failed to save config: {}", e);
         }
     }
 }
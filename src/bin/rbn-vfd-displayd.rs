@@ -0,0 +1,7 @@
+fn main() {
+    let listen = std::env::args().nth(1);
+    if let Err(e) = rbn_vfd::display_daemon::run(listen) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
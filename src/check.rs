@@ -0,0 +1,132 @@
+//! Settings check (`rbn-vfd check-config`)
+//!
+//! Loads the settings as a start would, without changing anything on disk,
+//! and reports each problem found. Unless told not to, it then probes what
+//! the settings point at: each enabled radio is connected to and let go
+//! (starting a managed rigctld for the probe), each display opened and
+//! closed, and the spot server's name resolved.
+//! Ends with an error when anything failed, so scripts and CI can rely on
+//! the exit status.
+
+use crate::config::Config;
+use crate::services::display;
+use crate::services::radio::{self, RigctldProcess};
+use crate::services::{display_port, server_address};
+use std::net::ToSocketAddrs;
+
+/// Check the settings, probing devices and the server unless `offline`
+pub fn run(offline: bool) -> Result<(), String> {
+    let path = Config::config_path().ok_or("Could not determine config path")?;
+    println!("Checking {}", path.display());
+    let config = Config::check()?;
+    let mut failures = 0;
+    for issue in &config.issues {
+        println!("  problem  {}", issue);
+        failures += 1;
+    }
+    if config.callsign.trim().is_empty() {
+        println!("  problem  station.callsign: needed to log in to the spot server");
+        failures += 1;
+    }
+    if offline {
+        return finish(failures);
+    }
+
+    for radio in config.radios.iter().filter(|radio| radio.enabled) {
+        let label = if radio.name.is_empty() {
+            format!("radio {}", radio.backend)
+        } else {
+            format!("radio {} ({})", radio.name, radio.backend)
+        };
+        // Nothing listens until rbn-vfd starts rigctld itself, so start it
+        // for the probe; it's stopped again when dropped
+        let rigctld = match radio::rigctld_launch(radio).map(RigctldProcess::new) {
+            Some(mut process) => match process.start() {
+                Ok(()) => Some(process),
+                Err(e) => {
+                    report(&label, Err(e), &mut failures);
+                    continue;
+                }
+            },
+            None => None,
+        };
+        let mut controller = radio::create_controller(radio);
+        let result = controller.connect().map_err(|e| e.to_string()).map(|()| {
+            let answer = match controller.get_frequency() {
+                Ok(khz) => format!("answers, VFO at {:.1} kHz", khz),
+                Err(_) => "connects".to_string(),
+            };
+            controller.disconnect();
+            match rigctld {
+                Some(_) => format!("{} through a started rigctld", answer),
+                None => answer,
+            }
+        });
+        report(&label, result, &mut failures);
+    }
+
+    let mut displays = vec![(
+        config.display.clone(),
+        display_port(&config.serial_port, config.display_usb_id),
+    )];
+    displays.extend(
+        config
+            .extra_displays
+            .iter()
+            .map(|extra| (extra.settings.clone(), extra.port())),
+    );
+    for (settings, port) in displays {
+        let label = match port.as_str() {
+            "" => format!("display {}", settings.driver),
+            port => format!("display {} on {}", settings.driver, port),
+        };
+        let result = if display::uses_serial(&settings.driver) && port.is_empty() {
+            Err("no serial port set or found".to_string())
+        } else {
+            // Dropped straight away, closing the device
+            display::create_driver(&settings, &port)
+                .map(|driver| {
+                    let (columns, rows) = driver.dimensions();
+                    format!("opens, {}x{}", columns, rows)
+                })
+                .map_err(|e| e.to_string())
+        };
+        report(&label, result, &mut failures);
+    }
+
+    let address = server_address(&config.feed.server);
+    let result = address
+        .to_socket_addrs()
+        .map_err(|e| e.to_string())
+        .and_then(|mut addrs| {
+            addrs
+                .next()
+                .map(|addr| format!("resolves to {}", addr.ip()))
+                .ok_or_else(|| "no addresses".to_string())
+        });
+    report(&format!("server {}", address), result, &mut failures);
+
+    finish(failures)
+}
+
+/// One probe's line of the report
+fn report(label: &str, result: Result<String, String>, failures: &mut usize) {
+    match result {
+        Ok(detail) => println!("  ok       {}: {}", label, detail),
+        Err(e) => {
+            println!("  FAILED   {}: {}", label, e);
+            *failures += 1;
+        }
+    }
+}
+
+fn finish(failures: usize) -> Result<(), String> {
+    match failures {
+        0 => {
+            println!("No problems found");
+            Ok(())
+        }
+        1 => Err("1 problem found".to_string()),
+        count => Err(format!("{} problems found", count)),
+    }
+}
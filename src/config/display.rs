@@ -0,0 +1,380 @@
+//! `[[display]]` entries: several displays, each naming its driver and
+//! the options that driver takes
+//!
+//! A plain `[display]` section is a single entry and takes every key, as
+//! before. In the array form each entry is split into the settings all
+//! drivers share and a typed `DriverOptions` chosen by `driver`; keys that
+//! neither takes are reported and dropped, and saving writes each entry
+//! back with only its own keys. The first entry is the main display, the
+//! rest mirror its frames.
+
+use super::validate::key_line;
+use super::{blank_none, DisplayConfig, DisplaySection, Issue};
+use crate::services::{display_port, UsbId};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// A `[[display]]` entry after the first, mirroring the main display
+#[derive(Debug, Clone)]
+pub struct ExtraDisplay {
+    pub serial_port: String,
+    pub usb_id: Option<UsbId>,
+    pub settings: DisplayConfig,
+}
+
+impl ExtraDisplay {
+    /// Port to open: `serial_port`, or wherever `usb_id` is plugged in
+    pub fn port(&self) -> String {
+        display_port(&self.serial_port, self.usb_id)
+    }
+}
+
+/// Serial character displays (VFDs, Matrix Orbital LCDs)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SerialOptions {
+    pub serial_port: String,
+    /// USB VID:PID of the adapter, used to find it when its path changes
+    #[serde(with = "blank_none")]
+    pub usb_id: Option<UsbId>,
+    /// Baud rate (0 = the driver's default)
+    pub baud_rate: u32,
+    /// GPO switched on while spots are shown (0 = unused)
+    pub activity_output: u8,
+}
+
+/// Displays on an I2C bus
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct I2cOptions {
+    pub i2c_bus: u8,
+    pub i2c_address: u8,
+}
+
+/// HD44780 wired straight to GPIOs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GpioOptions {
+    /// "rs,e,d4,d5,d6,d7"
+    pub gpio_pins: String,
+}
+
+/// Displays on an SPI bus, with a data/command and reset line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpiOptions {
+    pub spi_bus: u8,
+    pub spi_chip_select: u8,
+    /// Clock in Hz (0 = the spidev default)
+    pub spi_speed_hz: u32,
+    pub dc_pin: u32,
+    #[serde(with = "blank_none")]
+    pub reset_pin: Option<u32>,
+}
+
+/// SSD1306 and SH1106 OLED panels
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OledOptions {
+    /// 32 or 64
+    pub pixel_height: u32,
+}
+
+/// Waveshare e-paper panels
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EpaperOptions {
+    pub busy_pin: u32,
+    /// Minutes between full redraws
+    pub full_refresh_minutes: u32,
+}
+
+/// ST7789 and ILI9341 TFT panels
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TftOptions {
+    #[serde(with = "blank_none")]
+    pub backlight_pin: Option<u32>,
+}
+
+/// Linux framebuffer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FramebufferOptions {
+    pub fb_device: String,
+}
+
+/// `rbn-vfd-displayd` on another machine
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RemoteOptions {
+    /// "host[:port]"
+    pub remote_address: String,
+}
+
+impl Default for SerialOptions {
+    fn default() -> Self {
+        Self {
+            serial_port: String::new(),
+            usb_id: None,
+            baud_rate: DisplayConfig::default().baud_rate,
+            activity_output: DisplayConfig::default().activity_output,
+        }
+    }
+}
+
+impl Default for I2cOptions {
+    fn default() -> Self {
+        let config = DisplayConfig::default();
+        Self {
+            i2c_bus: config.i2c_bus,
+            i2c_address: config.i2c_address,
+        }
+    }
+}
+
+impl Default for GpioOptions {
+    fn default() -> Self {
+        Self {
+            gpio_pins: DisplayConfig::default().gpio_pins,
+        }
+    }
+}
+
+impl Default for SpiOptions {
+    fn default() -> Self {
+        let config = DisplayConfig::default();
+        Self {
+            spi_bus: config.spi_bus,
+            spi_chip_select: config.spi_chip_select,
+            spi_speed_hz: config.spi_speed_hz,
+            dc_pin: config.dc_pin,
+            reset_pin: config.reset_pin,
+        }
+    }
+}
+
+impl Default for OledOptions {
+    fn default() -> Self {
+        Self {
+            pixel_height: DisplayConfig::default().pixel_height,
+        }
+    }
+}
+
+impl Default for EpaperOptions {
+    fn default() -> Self {
+        let config = DisplayConfig::default();
+        Self {
+            busy_pin: config.busy_pin,
+            full_refresh_minutes: config.full_refresh_minutes,
+        }
+    }
+}
+
+impl Default for TftOptions {
+    fn default() -> Self {
+        Self {
+            backlight_pin: DisplayConfig::default().backlight_pin,
+        }
+    }
+}
+
+impl Default for FramebufferOptions {
+    fn default() -> Self {
+        Self {
+            fb_device: DisplayConfig::default().fb_device,
+        }
+    }
+}
+
+impl Default for RemoteOptions {
+    fn default() -> Self {
+        Self {
+            remote_address: DisplayConfig::default().remote_address,
+        }
+    }
+}
+
+/// Options of one driver, by the driver family `[display] driver` names
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DriverOptions {
+    /// elo, futaba, matrix_orbital, noritake
+    Serial(SerialOptions),
+    Hd44780I2c(I2cOptions),
+    Hd44780Gpio(GpioOptions),
+    /// ssd1306_i2c, sh1106_i2c
+    OledI2c(I2cOptions, OledOptions),
+    /// ssd1306_spi, sh1106_spi
+    OledSpi(SpiOptions, OledOptions),
+    /// waveshare_2in13, waveshare_2in9
+    Epaper(SpiOptions, EpaperOptions),
+    /// st7789, ili9341
+    Tft(SpiOptions, TftOptions),
+    Framebuffer(FramebufferOptions),
+    Remote(RemoteOptions),
+    /// The simulator, which has no options
+    Simulator,
+}
+
+impl DriverOptions {
+    /// Options for a built-in driver, read from an entry's keys (None for
+    /// drivers registered elsewhere, which may read any key)
+    fn parse(driver: &str, entry: &toml::Table) -> Result<Option<Self>, String> {
+        let value = toml::Value::Table(entry.clone());
+        let read = || value.clone();
+        Ok(Some(match driver {
+            "elo" | "futaba" | "matrix_orbital" | "noritake" => {
+                DriverOptions::Serial(typed(read())?)
+            }
+            "hd44780_i2c" => DriverOptions::Hd44780I2c(typed(read())?),
+            "hd44780_gpio" => DriverOptions::Hd44780Gpio(typed(read())?),
+            "ssd1306_i2c" | "sh1106_i2c" => DriverOptions::OledI2c(typed(read())?, typed(read())?),
+            "ssd1306_spi" | "sh1106_spi" => DriverOptions::OledSpi(typed(read())?, typed(read())?),
+            "waveshare_2in13" | "waveshare_2in9" => {
+                DriverOptions::Epaper(typed(read())?, typed(read())?)
+            }
+            "st7789" | "ili9341" => DriverOptions::Tft(typed(read())?, typed(read())?),
+            "framebuffer" => DriverOptions::Framebuffer(typed(read())?),
+            "remote" => DriverOptions::Remote(typed(read())?),
+            "simulator" => DriverOptions::Simulator,
+            _ => return Ok(None),
+        }))
+    }
+
+    /// settings.toml keys the options are read from
+    fn keys(&self) -> BTreeSet<String> {
+        let parts = match self {
+            DriverOptions::Serial(serial) => vec![toml::Value::try_from(serial)],
+            DriverOptions::Hd44780I2c(i2c) => vec![toml::Value::try_from(i2c)],
+            DriverOptions::Hd44780Gpio(gpio) => vec![toml::Value::try_from(gpio)],
+            DriverOptions::OledI2c(i2c, oled) => {
+                vec![toml::Value::try_from(i2c), toml::Value::try_from(oled)]
+            }
+            DriverOptions::OledSpi(spi, oled) => {
+                vec![toml::Value::try_from(spi), toml::Value::try_from(oled)]
+            }
+            DriverOptions::Epaper(spi, epaper) => {
+                vec![toml::Value::try_from(spi), toml::Value::try_from(epaper)]
+            }
+            DriverOptions::Tft(spi, tft) => {
+                vec![toml::Value::try_from(spi), toml::Value::try_from(tft)]
+            }
+            DriverOptions::Framebuffer(framebuffer) => vec![toml::Value::try_from(framebuffer)],
+            DriverOptions::Remote(remote) => vec![toml::Value::try_from(remote)],
+            DriverOptions::Simulator => Vec::new(),
+        };
+        parts
+            .into_iter()
+            .filter_map(|part| match part {
+                Ok(toml::Value::Table(table)) => Some(table.into_iter().map(|(key, _)| key)),
+                _ => None,
+            })
+            .flatten()
+            .collect()
+    }
+}
+
+fn typed<T: serde::de::DeserializeOwned>(value: toml::Value) -> Result<T, String> {
+    value.try_into().map_err(|e: toml::de::Error| e.to_string())
+}
+
+/// Keys of a settings struct, from its defaults
+fn keys_of<T: Serialize + Default>() -> Vec<String> {
+    match toml::Value::try_from(T::default()) {
+        Ok(toml::Value::Table(table)) => table.into_iter().map(|(key, _)| key).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Keys that belong to one driver family or another
+fn driver_keys() -> BTreeSet<String> {
+    [
+        keys_of::<SerialOptions>(),
+        keys_of::<I2cOptions>(),
+        keys_of::<GpioOptions>(),
+        keys_of::<SpiOptions>(),
+        keys_of::<OledOptions>(),
+        keys_of::<EpaperOptions>(),
+        keys_of::<TftOptions>(),
+        keys_of::<FramebufferOptions>(),
+        keys_of::<RemoteOptions>(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Keys of an entry that its driver doesn't take (none for drivers
+/// registered elsewhere)
+fn foreign_keys(entry: &toml::Table) -> Result<Vec<String>, String> {
+    let driver = entry
+        .get("driver")
+        .and_then(toml::Value::as_str)
+        .map_or_else(|| DisplayConfig::default().driver, str::to_string);
+    let Some(options) = DriverOptions::parse(&driver, entry)? else {
+        return Ok(Vec::new());
+    };
+    let own = options.keys();
+    let specific = driver_keys();
+    Ok(entry
+        .keys()
+        .filter(|key| specific.contains(*key) && !own.contains(*key))
+        .cloned()
+        .collect())
+}
+
+/// Reduce each `[[display]]` entry of a settings file to the keys its
+/// driver takes, reporting the rest by their line in `text`
+pub(super) fn load_entries(table: &mut toml::Table, text: &str, issues: &mut Vec<Issue>) {
+    let Some(toml::Value::Array(entries)) = table.get_mut("display") else {
+        return;
+    };
+    let specific = driver_keys();
+    let shared: BTreeSet<String> = keys_of::<DisplaySection>()
+        .into_iter()
+        .filter(|key| !specific.contains(key))
+        .collect();
+    for (index, entry) in entries.iter_mut().enumerate() {
+        let Some(entry) = entry.as_table_mut() else {
+            continue;
+        };
+        // A value of the wrong type fails deserializing, reported there
+        let Ok(foreign) = foreign_keys(entry) else {
+            continue;
+        };
+        let driver = entry
+            .get("driver")
+            .and_then(toml::Value::as_str)
+            .map_or_else(|| DisplayConfig::default().driver, str::to_string);
+        let unknown = entry
+            .keys()
+            .filter(|key| !shared.contains(*key) && !specific.contains(*key));
+        let problems: Vec<(String, String)> = foreign
+            .iter()
+            .map(|name| (name.clone(), format!("not used by the {} driver", driver)))
+            .chain(unknown.map(|name| (name.clone(), "no such display setting".to_string())))
+            .collect();
+        for (name, message) in problems {
+            let key = format!("display.{}.{}", index + 1, name);
+            issues.push(Issue {
+                line: key_line(text, &key),
+                key,
+                message,
+            });
+            entry.remove(&name);
+        }
+    }
+}
+
+/// Trim `[[display]]` entries about to be saved to their driver's keys
+pub(super) fn save_entries(table: &mut toml::Table) {
+    let Some(toml::Value::Array(entries)) = table.get_mut("display") else {
+        return;
+    };
+    for entry in entries.iter_mut().filter_map(toml::Value::as_table_mut) {
+        for name in foreign_keys(entry).unwrap_or_default() {
+            entry.remove(&name);
+        }
+    }
+}
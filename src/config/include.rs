@@ -0,0 +1,136 @@
+//! Settings split across files (`include = ["hardware.toml", ...]`)
+//!
+//! The top-level `include` list names files, relative to the one listing
+//! them, read in order beneath it: tables merge key by key, while any other
+//! value, arrays of tables like `[[radio]]` included, comes whole from the
+//! last file setting it, the including file last of all. Included files may
+//! include others. Only settings.toml is upgraded by `migrate` and written
+//! on save; values still matching the included ones are left out of it, so
+//! wiring kept in hardware.toml stays there.
+
+use super::Issue;
+use std::path::{Path, PathBuf};
+
+/// Top-level key listing the included files
+pub(super) const KEY: &str = "include";
+
+/// `table` laid over the files it includes (read relative to `path`)
+pub(super) fn beneath(path: &Path, table: toml::Table, issues: &mut Vec<Issue>) -> toml::Table {
+    let mut merged = load(path, &table, issues);
+    if merged.is_empty() {
+        return table;
+    }
+    merge(&mut merged, table);
+    merged
+}
+
+/// Settings from the files `table` includes, merged in order
+pub(super) fn load(path: &Path, table: &toml::Table, issues: &mut Vec<Issue>) -> toml::Table {
+    let mut merged = toml::Table::new();
+    let mut open = vec![canonical(path)];
+    include_into(&mut merged, path, table, &mut open, issues);
+    merged
+}
+
+/// Each setting of an included table, by key, for `restore_keys`
+pub(super) fn settings(table: &toml::Table) -> Vec<(Vec<String>, toml::Value)> {
+    let mut settings = Vec::new();
+    collect(table, &mut Vec::new(), &mut settings);
+    settings.retain(|(key, _)| key.first().is_some_and(|first| first != KEY));
+    settings
+}
+
+/// Merge the files `table` (read from `from`) includes into `merged`;
+/// `open` holds the files being read, to catch one including itself
+fn include_into(
+    merged: &mut toml::Table,
+    from: &Path,
+    table: &toml::Table,
+    open: &mut Vec<PathBuf>,
+    issues: &mut Vec<Issue>,
+) {
+    let dir = from.parent().unwrap_or(Path::new("."));
+    for name in names(table, from, issues) {
+        let path = dir.join(&name);
+        let canonical = canonical(&path);
+        if open.contains(&canonical) {
+            report(issues, format!("{} includes itself", path.display()));
+            continue;
+        }
+        let included = match super::read_table(&path) {
+            Ok(included) => included,
+            Err(e) => {
+                report(issues, e);
+                continue;
+            }
+        };
+        open.push(canonical);
+        include_into(merged, &path, &included, open, issues);
+        open.pop();
+        merge(merged, included);
+    }
+}
+
+/// `include` as a list of file names, a single name also allowed
+fn names(table: &toml::Table, from: &Path, issues: &mut Vec<Issue>) -> Vec<String> {
+    let items = match table.get(KEY) {
+        None => return Vec::new(),
+        Some(toml::Value::Array(items)) => items.clone(),
+        Some(item) => vec![item.clone()],
+    };
+    items
+        .into_iter()
+        .filter_map(|item| match item {
+            toml::Value::String(name) if !name.trim().is_empty() => Some(name.trim().to_string()),
+            item => {
+                report(
+                    issues,
+                    format!("{}: '{}' is not a file name", from.display(), item),
+                );
+                None
+            }
+        })
+        .collect()
+}
+
+/// Lay `over` on `base`, merging tables and replacing everything else
+fn merge(base: &mut toml::Table, over: toml::Table) {
+    for (key, value) in over {
+        if key == KEY {
+            continue;
+        }
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(over)) => merge(base, over),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn collect(
+    table: &toml::Table,
+    prefix: &mut Vec<String>,
+    settings: &mut Vec<(Vec<String>, toml::Value)>,
+) {
+    for (name, value) in table {
+        prefix.push(name.clone());
+        match value {
+            toml::Value::Table(table) => collect(table, prefix, settings),
+            value => settings.push((prefix.clone(), value.clone())),
+        }
+        prefix.pop();
+    }
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn report(issues: &mut Vec<Issue>, message: String) {
+    issues.push(Issue {
+        key: KEY.to_string(),
+        line: None,
+        message,
+    });
+}
@@ -0,0 +1,115 @@
+//! Upgrades of settings files written by older versions
+//!
+//! Every settings.toml carries a top-level `version`. When a release
+//! renames or moves keys it bumps `VERSION` and appends a step to
+//! `STEPS`; files with an older version (or none, version 1) go through the
+//! missing steps on load and are rewritten, after a copy of the original
+//! is kept as settings.toml.v<N>.bak.
+
+use std::path::Path;
+
+/// Layout this build writes
+pub(super) const VERSION: u32 = 2;
+
+/// Upgrades from each version to the next, starting at version 1
+const STEPS: &[fn(&mut toml::Table)] = &[station_callsign];
+
+/// Version 1 to 2: `[connection] callsign` moved to `[station]`
+fn station_callsign(table: &mut toml::Table) {
+    let Some(toml::Value::Table(mut connection)) = table.remove("connection") else {
+        return;
+    };
+    let Some(callsign) = connection.remove("callsign") else {
+        return;
+    };
+    let station = table
+        .entry("station")
+        .or_insert(toml::Value::Table(toml::Table::new()));
+    if let toml::Value::Table(station) = station {
+        station.entry("callsign").or_insert(callsign);
+    }
+}
+
+/// Version a file was written with (1 before versions were recorded)
+fn version_of(table: &toml::Table) -> u32 {
+    table
+        .get("version")
+        .and_then(toml::Value::as_integer)
+        .map_or(1, |version| version.clamp(1, u32::MAX as i64) as u32)
+}
+
+/// Note for a file written by a newer build than this one
+pub(super) fn newer(table: &toml::Table) -> Option<String> {
+    let version = version_of(table);
+    (version > VERSION).then(|| {
+        format!(
+            "written by a newer version (settings version {}, this build reads {}); \
+             keys it doesn't know are ignored",
+            version, VERSION
+        )
+    })
+}
+
+/// Bring a parsed settings file up to `VERSION` in memory. Returns the
+/// version it was written with, or None when there was nothing to do.
+pub(super) fn apply(table: &mut toml::Table) -> Option<u32> {
+    let version = version_of(table);
+    if version >= VERSION {
+        return None;
+    }
+    for step in &STEPS[(version - 1) as usize..] {
+        step(table);
+    }
+    // Kept first so it's the first thing in the file
+    let mut upgraded = toml::Table::new();
+    upgraded.insert("version".to_string(), toml::Value::Integer(VERSION as i64));
+    for (key, value) in std::mem::take(table) {
+        if key != "version" {
+            upgraded.insert(key, value);
+        }
+    }
+    *table = upgraded;
+    Some(version)
+}
+
+/// Bring a parsed settings file up to `VERSION`, rewriting it (and keeping
+/// the original) when anything changed. Returns the text now on disk, for
+/// locating problems by line, or None when the file was left alone.
+pub(super) fn upgrade(path: &Path, table: &mut toml::Table) -> Option<String> {
+    if let Some(problem) = newer(table) {
+        eprintln!("{}: {}", path.display(), problem);
+        return None;
+    }
+    let version = apply(table)?;
+
+    let backup = path.with_extension(format!("toml.v{}.bak", version));
+    let text = match toml::to_string_pretty(table) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("Failed to upgrade {}: {}", path.display(), e);
+            return None;
+        }
+    };
+    let written = std::fs::copy(path, &backup)
+        .map_err(|e| format!("Failed to keep a copy at {}: {}", backup.display(), e))
+        .and_then(|_| {
+            std::fs::write(path, &text).map_err(|e| format!("Failed to write config: {}", e))
+        });
+    match written {
+        Ok(()) => {
+            eprintln!(
+                "Upgraded {} from settings version {} to {} (original kept as {})",
+                path.display(),
+                version,
+                VERSION,
+                backup.display()
+            );
+            Some(text)
+        }
+        // Still used in memory; the next save writes the new layout
+        Err(e) => {
+            eprintln!("{}", e);
+            None
+        }
+    }
+}
@@ -0,0 +1,2807 @@
+use crate::models::{
+    parse_timezone, AggregatedSpot, Band, Clocks, DateStyle, DistanceUnit, FrequencyUnit, Region,
+    SpotSort, Units,
+};
+use crate::paths;
+use crate::services::display::color::{self, Rgb};
+use crate::services::radio::RadioMode;
+use crate::services::{SpotAlerts, SpotStore, UsbId};
+use configparser::ini::Ini;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+mod display;
+mod include;
+mod migrate;
+mod sample;
+mod secret;
+mod state;
+mod validate;
+
+pub use display::ExtraDisplay;
+pub use secret::{same_token, Secret};
+pub use validate::Issue;
+
+/// Command-line settings, installed once at startup
+static OVERRIDES: OnceLock<Overrides> = OnceLock::new();
+
+/// Prefix of environment variables overriding settings.toml keys
+const ENV_PREFIX: &str = "RBNVFD_";
+
+/// Application settings
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Own callsign, for logging in to RBN and self-spot alerts
+    pub callsign: String,
+    /// Own Maidenhead locator, for distance and bearing to spotted stations
+    pub grid: String,
+    /// Own CQ and ITU zones (0 = not set)
+    pub cq_zone: u8,
+    pub itu_zone: u8,
+    /// IARU region, choosing the band plan (None = widest band edges)
+    pub region: Option<Region>,
+    pub serial_port: String,
+    /// USB VID:PID of the VFD adapter, used to find it when its path changes
+    pub display_usb_id: Option<UsbId>,
+    pub min_snr: i32,
+    pub max_age_minutes: u32,
+    /// Only show spots on this band (None = all bands)
+    pub band_filter: Option<Band>,
+    /// Only show CW spots
+    pub cw_only: bool,
+    /// Order spot lists are shown in
+    pub sort: SpotSort,
+    /// Keep filter, profile and preset changes made while running in
+    /// state.toml (see `save_state`)
+    pub keep_adjustments: bool,
+    pub scroll_interval_seconds: u32,
+    /// Percentage chance (0-100) to show random character when idle
+    pub random_char_percent: u32,
+    pub display: DisplayConfig,
+    /// `[[display]]` entries after the first, mirroring it
+    pub extra_displays: Vec<ExtraDisplay>,
+    /// Radio in use, one of `radios`
+    pub radio: RadioConfig,
+    /// Every `[[radio]]` entry (one for a plain `[radio]` section); `radio`
+    /// is saved in place of the one in use
+    pub radios: Vec<RadioConfig>,
+    /// Index of `radio` in `radios`
+    active_radio: usize,
+    pub feed: FeedConfig,
+    pub web: WebConfig,
+    pub grpc: GrpcConfig,
+    pub mqtt: MqttConfig,
+    pub cloudlog: CloudlogConfig,
+    pub qrz: CallbookConfig,
+    pub hamqth: CallbookConfig,
+    pub lotw: LotwConfig,
+    pub clublog: ClubLogConfig,
+    pub n1mm: N1mmConfig,
+    pub logger: LoggerConfig,
+    pub wsjtx: WsjtxConfig,
+    pub telnet: TelnetConfig,
+    pub input: InputConfig,
+    pub alerts: AlertConfig,
+    pub telegram: TelegramConfig,
+    pub ntfy: NtfyConfig,
+    pub gotify: GotifyConfig,
+    pub digest: DigestConfig,
+    pub aprs: AprsConfig,
+    pub influx: InfluxConfig,
+    pub dump: DumpConfig,
+    pub activation: ActivationConfig,
+    pub leds: LedConfig,
+    pub sound: SoundConfig,
+    pub logging: LoggingConfig,
+    /// How distances, frequencies, times and dates are shown (`[units]`)
+    pub units: Units,
+    /// Named filter and alert sets, `[profile.<name>]`
+    pub profiles: BTreeMap<String, Profile>,
+    /// `[filters.<band>]` thresholds replacing the global ones on that band
+    pub band_filters: BTreeMap<Band, BandFilter>,
+    /// Profile in use ("" = the main settings)
+    pub active_profile: String,
+    /// Main settings the active profile replaced, saved in their place
+    base_settings: Option<Profile>,
+    /// `[[preset]]` filter sets, in file order
+    pub presets: Vec<FilterPreset>,
+    /// Index of the preset in use in `presets` (None = the filters alone)
+    pub active_preset: Option<usize>,
+    /// Recorded feed played back instead of connecting to RBN (command line
+    /// only, never saved)
+    pub replay_file: Option<PathBuf>,
+    /// Problems found in the settings file when it was loaded
+    pub issues: Vec<Issue>,
+}
+
+/// Filter and alert settings a profile replaces; unset ones keep the main
+/// settings' values
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub min_snr: Option<i32>,
+    pub max_age_minutes: Option<u32>,
+    /// Band name or "all"
+    pub band: Option<String>,
+    pub cw_only: Option<bool>,
+    /// "frequency", "recency" or "snr"
+    pub sort: Option<String>,
+    /// `[alerts]` lists
+    pub watch_list: Option<String>,
+    pub worked_dxcc: Option<String>,
+    pub worked_slots: Option<String>,
+    /// Alert kinds that flash the display (`[display] attention`)
+    pub attention: Option<String>,
+    /// Alert kinds that sound (`[sound] alerts`)
+    pub sound_alerts: Option<String>,
+}
+
+impl Profile {
+    /// Every setting a profile can replace, as it is now
+    fn capture(config: &Config) -> Self {
+        Self {
+            min_snr: Some(config.min_snr),
+            max_age_minutes: Some(config.max_age_minutes),
+            band: Some(
+                config
+                    .band_filter
+                    .map_or("all", |band| band.name())
+                    .to_string(),
+            ),
+            cw_only: Some(config.cw_only),
+            sort: Some(config.sort.name().to_string()),
+            watch_list: Some(config.alerts.watch_list.clone()),
+            worked_dxcc: Some(config.alerts.worked_dxcc.clone()),
+            worked_slots: Some(config.alerts.worked_slots.clone()),
+            attention: Some(config.display.attention.clone()),
+            sound_alerts: Some(config.sound.alerts.clone()),
+        }
+    }
+
+    fn apply(&self, config: &mut Config) {
+        if let Some(min_snr) = self.min_snr {
+            config.min_snr = min_snr;
+        }
+        if let Some(max_age) = self.max_age_minutes {
+            config.max_age_minutes = max_age;
+        }
+        if let Some(band) = &self.band {
+            config.band_filter = Band::from_name(band);
+        }
+        if let Some(cw_only) = self.cw_only {
+            config.cw_only = cw_only;
+        }
+        if let Some(sort) = self.sort.as_deref().and_then(SpotSort::from_name) {
+            config.sort = sort;
+        }
+        if let Some(list) = &self.watch_list {
+            config.alerts.watch_list = list.clone();
+        }
+        if let Some(list) = &self.worked_dxcc {
+            config.alerts.worked_dxcc = list.clone();
+        }
+        if let Some(list) = &self.worked_slots {
+            config.alerts.worked_slots = list.clone();
+        }
+        if let Some(kinds) = &self.attention {
+            config.display.attention = kinds.clone();
+        }
+        if let Some(kinds) = &self.sound_alerts {
+            config.sound.alerts = kinds.clone();
+        }
+    }
+}
+
+/// Filters for one band (`[filters.20m]`); unset ones use the global
+/// `[filters]` values
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BandFilter {
+    pub min_snr: Option<i32>,
+    pub max_age_minutes: Option<u32>,
+    pub cw_only: Option<bool>,
+}
+
+/// Filter set switched at runtime (`[[preset]]`); while one is in use its
+/// settings stand in for the `[filters]` ones, and unset ones keep them
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterPreset {
+    pub name: String,
+    pub min_snr: Option<i32>,
+    pub max_age_minutes: Option<u32>,
+    pub cw_only: Option<bool>,
+    /// Bands to show, e.g. "20m,17m,15m" ("" keeps the band filter)
+    pub bands: String,
+    /// Continents of the spotted stations, e.g. "EU,AF" ("" for all; needs
+    /// the country file)
+    pub continents: String,
+    /// Only entities (or band slots, when listed) not in the `[alerts]`
+    /// worked lists
+    pub new_only: bool,
+}
+
+impl FilterPreset {
+    /// Bands listed in `bands` (empty for none)
+    fn band_list(&self) -> Vec<Band> {
+        self.bands.split(',').filter_map(Band::from_name).collect()
+    }
+
+    /// Whether a spot passes the continent and new-only settings
+    fn allows(&self, spot: &AggregatedSpot, alerts: &SpotAlerts) -> bool {
+        let continent_match = self.continents.trim().is_empty()
+            || alerts.continent(&spot.callsign).is_some_and(|continent| {
+                self.continents
+                    .split(',')
+                    .any(|listed| listed.trim().eq_ignore_ascii_case(continent))
+            });
+        continent_match && (!self.new_only || alerts.is_new(spot))
+    }
+}
+
+/// SNR and age limits in effect, with per-band exceptions
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SpotLimits {
+    pub min_snr: i32,
+    pub max_age: Duration,
+    /// Bands with their own limits
+    pub bands: BTreeMap<Band, (i32, Duration)>,
+}
+
+impl SpotLimits {
+    /// Minimum SNR and maximum age for a frequency
+    pub fn for_khz(&self, frequency_khz: f64) -> (i32, Duration) {
+        Band::from_khz(frequency_khz)
+            .and_then(|band| self.bands.get(&band).copied())
+            .unwrap_or((self.min_snr, self.max_age))
+    }
+
+    /// Lowest SNR and longest age any band accepts, for fetching spots
+    /// before `allows`
+    pub fn loosest(&self) -> (i32, Duration) {
+        self.bands.values().fold(
+            (self.min_snr, self.max_age),
+            |(snr, age), (band_snr, band_age)| (snr.min(*band_snr), age.max(*band_age)),
+        )
+    }
+
+    /// Whether a spot is strong and recent enough for its band
+    pub fn allows(&self, spot: &AggregatedSpot) -> bool {
+        let (min_snr, max_age) = self.for_khz(spot.frequency_khz);
+        spot.highest_snr >= min_snr && spot.last_spotted.elapsed() <= max_age
+    }
+}
+
+/// Settings given on the command line, applied over the file by every
+/// `Config::load`
+///
+/// An overridden value is not written back by `save` unless it was changed
+/// in the app, so trying something from the command line leaves the file as
+/// it was.
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+    /// Settings file to use instead of the platform default
+    pub config_path: Option<PathBuf>,
+    /// Profile to start in ("" for the main settings)
+    pub profile: Option<String>,
+    pub callsign: Option<String>,
+    pub min_snr: Option<i32>,
+    /// Band filter (Some(None) = all bands)
+    pub band_filter: Option<Option<Band>>,
+    pub replay_file: Option<PathBuf>,
+}
+
+impl Overrides {
+    fn apply(&self, config: &mut Config) {
+        if let Some(name) = &self.profile {
+            if let Err(e) = config.select_profile(name) {
+                eprintln!("{}", e);
+            }
+        }
+        if let Some(callsign) = &self.callsign {
+            config.callsign = callsign.clone();
+        }
+        if let Some(min_snr) = self.min_snr {
+            config.min_snr = min_snr;
+        }
+        if let Some(band) = self.band_filter {
+            config.band_filter = band;
+        }
+        if let Some(path) = &self.replay_file {
+            config.replay_file = Some(path.clone());
+        }
+    }
+
+    /// Put back the file's values for overrides the app left unchanged
+    fn restore(&self, config: &mut Config, file: &Config) {
+        if self.callsign.as_ref() == Some(&config.callsign) {
+            config.callsign = file.callsign.clone();
+        }
+        if self.min_snr == Some(config.min_snr) {
+            config.min_snr = file.min_snr;
+        }
+        if self.band_filter == Some(config.band_filter) {
+            config.band_filter = file.band_filter;
+        }
+        if self.profile.as_ref() == Some(&config.active_profile) {
+            let _ = config.select_profile(&file.active_profile);
+        }
+    }
+
+    /// state.toml keys whose value is still the override's, left out of it
+    fn unchanged_keys(&self, config: &Config) -> Vec<&'static str> {
+        let mut keys = Vec::new();
+        if self.min_snr == Some(config.min_snr) {
+            keys.push("filters.min_snr");
+        }
+        if self.band_filter == Some(config.band_filter) {
+            keys.push("filters.band");
+        }
+        if self.profile.as_ref() == Some(&config.active_profile) {
+            keys.push("filters.profile");
+        }
+        keys
+    }
+}
+
+/// MQTT publishing settings (needs the `mqtt` feature)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MqttConfig {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub client_id: String,
+    pub username: String,
+    pub password: Secret,
+    /// Topics are `<prefix>/spots`, `<prefix>/display`, `<prefix>/radio`,
+    /// `<prefix>/app`, `<prefix>/activity`, `<prefix>/notify` and
+    /// `<prefix>/status` unless set below
+    pub topic_prefix: String,
+    pub spots_topic: String,
+    pub display_topic: String,
+    pub radio_topic: String,
+    pub app_topic: String,
+    /// Spots per minute and spots per band, every 30 seconds
+    pub activity_topic: String,
+    /// Messages sent here are shown like cluster announcements
+    pub notify_topic: String,
+    /// "online" while connected; the broker sets "offline" (the last
+    /// will) when the connection drops
+    pub status_topic: String,
+    /// QoS (0-2) for spots
+    pub spot_qos: u8,
+    /// QoS (0-2) for the retained display, radio, app and status messages
+    pub state_qos: u8,
+    /// Connect with TLS (brokers usually listen on 8883 for it)
+    pub tls: bool,
+    /// PEM CA certificate for the broker ("" for the system's roots)
+    pub ca_file: String,
+    /// Announce sensors and a notify target to Home Assistant
+    pub discovery: bool,
+    /// Home Assistant's discovery prefix
+    pub discovery_prefix: String,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: "localhost".to_string(),
+            port: 1883,
+            client_id: "rbn-vfd".to_string(),
+            username: String::new(),
+            password: Secret::default(),
+            topic_prefix: "rbn-vfd".to_string(),
+            spots_topic: String::new(),
+            display_topic: String::new(),
+            radio_topic: String::new(),
+            app_topic: String::new(),
+            activity_topic: String::new(),
+            notify_topic: String::new(),
+            status_topic: String::new(),
+            spot_qos: 0,
+            state_qos: 1,
+            tls: false,
+            ca_file: String::new(),
+            discovery: false,
+            discovery_prefix: "homeassistant".to_string(),
+        }
+    }
+}
+
+/// Cloudlog or Wavelog logbook for worked-before lookups and QSY reports
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CloudlogConfig {
+    pub enabled: bool,
+    /// Base URL of the API, e.g. "https://log.example.com/index.php"
+    pub url: String,
+    /// Read/write API key
+    pub api_key: Secret,
+    /// Count only confirmed QSOs as worked (Wavelog only)
+    pub confirmed: bool,
+    /// Radio name to report tuned spots under ("" for no QSY reports)
+    pub radio: String,
+}
+
+/// Callbook login for detail page lookups: QRZ.com (`[qrz]`) or HamQTH
+/// (`[hamqth]`); with both enabled HamQTH is the fallback
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CallbookConfig {
+    pub enabled: bool,
+    pub username: String,
+    pub password: Secret,
+}
+
+/// ARRL's LoTW users list, for the detail page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LotwConfig {
+    pub enabled: bool,
+    /// How old the cached list may get before it is downloaded again (0
+    /// keeps the cached copy)
+    pub refresh_hours: u32,
+}
+
+impl Default for LotwConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            refresh_hours: 24,
+        }
+    }
+}
+
+/// Club Log most-wanted ranking, and your Club Log log when a login is set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClubLogConfig {
+    pub enabled: bool,
+    /// How old the cached copies may get before they are downloaded again
+    /// (0 keeps the cached copies)
+    pub refresh_hours: u32,
+    /// Log to fetch ("" for `[station] callsign`)
+    pub callsign: String,
+    /// Club Log login; without one only the ranking is fetched
+    pub email: String,
+    pub password: Secret,
+    /// API key from Club Log's helpdesk, needed for the log
+    pub api_key: Secret,
+}
+
+impl Default for ClubLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            refresh_hours: 24,
+            callsign: String::new(),
+            email: String::new(),
+            password: Secret::default(),
+            api_key: Secret::default(),
+        }
+    }
+}
+
+/// Spots sent to contest loggers as N1MM+ UDP packets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct N1mmConfig {
+    pub enabled: bool,
+    /// "host:port" to send to; the broadcast address reaches every logger
+    /// on the LAN
+    pub address: String,
+}
+
+impl Default for N1mmConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            address: "255.255.255.255:12060".to_string(),
+        }
+    }
+}
+
+/// Logging program that selected spots are sent to for lookup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggerConfig {
+    pub enabled: bool,
+    /// "log4om" or "dxkeeper"
+    pub program: String,
+    /// "host:port" the program listens on ("" for its default)
+    pub address: String,
+}
+
+impl Default for LoggerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            program: "log4om".to_string(),
+            address: String::new(),
+        }
+    }
+}
+
+/// Watched and needed FT8/FT4 stations highlighted in WSJT-X
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WsjtxConfig {
+    pub enabled: bool,
+    /// "host:port" to listen on: WSJT-X's "UDP Server" setting
+    pub address: String,
+    /// Background for watch-list calls
+    pub watch_color: Rgb,
+    /// Background for new entities or slots
+    pub needed_color: Rgb,
+}
+
+impl Default for WsjtxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            address: "127.0.0.1:2237".to_string(),
+            watch_color: color::WATCH,
+            needed_color: Rgb(0, 170, 255),
+        }
+    }
+}
+
+/// Cluster server passing the filtered spots on to other programs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TelnetConfig {
+    pub enabled: bool,
+    /// Address to listen on ("0.0.0.0" for the whole LAN)
+    pub bind: String,
+    pub port: u16,
+}
+
+impl Default for TelnetConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: "0.0.0.0".to_string(),
+            port: 7300,
+        }
+    }
+}
+
+/// Where spots come from
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeedConfig {
+    /// Telnet "host[:port]" of a DX cluster node passing on skimmer spots
+    /// ("" for the RBN server)
+    pub server: String,
+}
+
+/// Web dashboard settings (needs the `web` feature)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebConfig {
+    pub enabled: bool,
+    /// Address to listen on ("0.0.0.0" to allow phones on the LAN)
+    pub bind: String,
+    pub port: u16,
+    /// Bearer token for the REST API under /api ("" leaves it off)
+    pub api_token: Secret,
+}
+
+impl Default for WebConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: "0.0.0.0".to_string(),
+            port: 8080,
+            api_token: Secret::default(),
+        }
+    }
+}
+
+/// gRPC spot stream and control service (needs the `grpc` feature)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GrpcConfig {
+    pub enabled: bool,
+    /// Address to listen on ("0.0.0.0" for other machines)
+    pub bind: String,
+    pub port: u16,
+    /// Bearer token clients send as `authorization` metadata ("" for
+    /// none)
+    pub token: Secret,
+}
+
+impl Default for GrpcConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: "127.0.0.1".to_string(),
+            port: 50051,
+            token: Secret::default(),
+        }
+    }
+}
+
+/// Front-panel controls (Linux)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputConfig {
+    /// Rotary encoder source: "none", "evdev" or "gpio"
+    pub encoder: String,
+    /// Comma separated /dev/input event devices for the encoder and its
+    /// button (e.g. the rotary-encoder and gpio-keys overlays)
+    pub evdev_devices: String,
+    /// GPIO encoder pins as "a,b,button" sysfs numbers (button optional)
+    pub encoder_pins: String,
+    /// GPIO buttons (active low) as "pin=action,...", e.g. "5=band_up,6=band_down"
+    pub buttons: String,
+    /// Keys on `evdev_devices` as "code=action,..." with Linux key codes,
+    /// e.g. "59=snr_up" (F1)
+    pub keys: String,
+    /// Matrix Orbital keypad keys as "key=action,...", e.g. "A=cw_toggle";
+    /// unbound keys step the display view
+    pub keypad: String,
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        Self {
+            encoder: "none".to_string(),
+            evdev_devices: String::new(),
+            encoder_pins: String::new(),
+            buttons: String::new(),
+            keys: String::new(),
+            keypad: String::new(),
+        }
+    }
+}
+
+/// Spot alert rules
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertConfig {
+    /// Comma separated callsigns to alert on; `*` matches any characters
+    pub watch_list: String,
+    /// Country file (cty.dat) used to find DXCC entities; empty uses
+    /// cty.dat in the cache directory if there is one (`--print-paths`),
+    /// else new-DXCC alerts are off
+    pub cty_file: String,
+    /// Comma separated primary prefixes of worked entities, e.g. "K,VE,DL"
+    pub worked_dxcc: String,
+    /// Comma separated worked "entity/band" slots, e.g. "DL/20m,DL/40m";
+    /// empty disables new-slot alerts
+    pub worked_slots: String,
+    /// Logger's ADIF file, watched for QSOs that count as worked on top of
+    /// the lists above ("" for none)
+    pub adif_file: String,
+    /// Most webhooks, pushes and Telegram messages the rules send in an
+    /// hour, so a pileup can't flood the phone (0 for no limit)
+    pub notify_limit: u32,
+    /// `[[alerts.rule]]` entries, checked by `AlertEngine`
+    #[serde(rename = "rule", skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<AlertRule>,
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        Self {
+            watch_list: String::new(),
+            cty_file: String::new(),
+            worked_dxcc: String::new(),
+            worked_slots: String::new(),
+            adif_file: String::new(),
+            notify_limit: 20,
+            rules: Vec::new(),
+        }
+    }
+}
+
+/// Alert rule (`[[alerts.rule]]`): a spot meeting every condition set
+/// fires the actions set. Conditions left out match anything.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertRule {
+    /// Shown with the alert and sent with webhooks and pushes
+    pub name: String,
+    /// Comma separated callsigns; `*` matches any characters
+    pub calls: String,
+    /// Comma separated bands, e.g. "20m,17m"
+    pub bands: String,
+    /// Only entities (or band slots, when listed) not in the worked lists
+    pub new_dxcc: bool,
+    pub min_snr: Option<i32>,
+    /// Distance from `[station] grid`, which needs the country file too
+    pub min_km: Option<u32>,
+    pub max_km: Option<u32>,
+    /// Active period as "HH:MM-HH:MM" in `[units] timezone`, may wrap
+    /// midnight ("" for always)
+    pub hours: String,
+    /// `[leds]` LED to blink: "watch" or "dxcc"
+    pub led: String,
+    /// Beep on the `[sound]` output, outside quiet hours
+    pub beep: bool,
+    /// URL the spot is POSTed to as JSON
+    pub webhook: String,
+    /// ntfy topic URL for a push notification, e.g.
+    /// "https://ntfy.sh/my-rbn-alerts"
+    pub push: String,
+    /// Message the `[telegram]` chat
+    pub telegram: bool,
+    /// Send to the `[gotify]` server
+    pub gotify: bool,
+    /// Push priority from 1 (min) to 5 (urgent), ntfy's scale; Gotify
+    /// gets 0, 2, 5, 8 or 10. Left out, the server's default is used.
+    pub priority: Option<u8>,
+    /// Only entities in Club Log's top this many most wanted (needs
+    /// `[clublog]`)
+    pub most_wanted: Option<u32>,
+}
+
+/// Telegram bot that alert rules with `telegram = true` message
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TelegramConfig {
+    /// Token from @BotFather
+    pub bot_token: Secret,
+    /// Chat to send to: your user ID, or a group's (negative) ID
+    pub chat_id: String,
+}
+
+/// Access to an ntfy server for alert rule `push` URLs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NtfyConfig {
+    /// Access token for protected topics ("" for open ones)
+    pub token: Secret,
+}
+
+/// Email digest of watch-list and new-entity spots (needs the `email`
+/// feature)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DigestConfig {
+    pub enabled: bool,
+    /// "daily" or "weekly"
+    pub period: String,
+    /// Time it goes out, "HH:MM" in `[units] timezone`
+    pub send_at: String,
+    /// Day a weekly digest goes out, e.g. "mon"
+    pub weekday: String,
+    /// Alert kinds it lists: "watch", "dxcc", "slot"
+    pub alerts: String,
+    /// Send one even when nothing was heard
+    pub send_empty: bool,
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    /// "starttls", "tls" or "none"
+    pub security: String,
+    /// SMTP login ("" for none)
+    pub username: String,
+    pub password: Secret,
+    pub from: String,
+    /// Comma separated addresses
+    pub to: String,
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            period: "daily".to_string(),
+            send_at: "07:00".to_string(),
+            weekday: "mon".to_string(),
+            alerts: "watch,dxcc,slot".to_string(),
+            send_empty: false,
+            smtp_host: String::new(),
+            smtp_port: 587,
+            security: "starttls".to_string(),
+            username: String::new(),
+            password: Secret::default(),
+            from: String::new(),
+            to: String::new(),
+        }
+    }
+}
+
+/// Alerting spots sent to APRS-IS as objects and messages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AprsConfig {
+    pub enabled: bool,
+    /// APRS-IS server, "host:port"
+    pub server: String,
+    /// Call-SSID it logs in and sends as, e.g. "N0CALL-10" ("" for
+    /// `[station] callsign`)
+    pub callsign: String,
+    /// Alert kinds it sends: "self", "watch", "dxcc", "slot"
+    pub alerts: String,
+    /// Put each station on the map as an object at its entity's location
+    pub objects: bool,
+    /// Call-SSID messaged about each one, e.g. your HT ("" for none)
+    pub message_to: String,
+}
+
+impl Default for AprsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            server: "rotate.aprs2.net:14580".to_string(),
+            callsign: String::new(),
+            alerts: "watch".to_string(),
+            objects: true,
+            message_to: String::new(),
+        }
+    }
+}
+
+/// Spot counts, SNRs and solar indices written to InfluxDB as line protocol
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InfluxConfig {
+    pub enabled: bool,
+    /// Write endpoint, e.g. "http://localhost:8086/api/v2/write?org=home&bucket=rbn"
+    /// or "http://localhost:8086/write?db=rbn" ("" to send over UDP)
+    pub url: String,
+    /// InfluxDB 2 API token ("" for none)
+    pub token: Secret,
+    /// "host:port" of a UDP line protocol listener, used when `url` is ""
+    pub udp_address: String,
+    /// Start of the measurement names, e.g. "rbn" for "rbn_snr"
+    pub prefix: String,
+    /// Seconds between writes, each with the band activity since the last
+    pub interval_secs: u64,
+    /// Write each spot's SNR, not only the counts
+    pub spots: bool,
+}
+
+impl Default for InfluxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: String::new(),
+            token: Secret::default(),
+            udp_address: String::new(),
+            prefix: "rbn".to_string(),
+            interval_secs: 60,
+            spots: true,
+        }
+    }
+}
+
+/// Spots on hand and session counts written to files on a schedule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DumpConfig {
+    pub enabled: bool,
+    /// Cron-style "minute hour day month weekday" in `[units] timezone`,
+    /// e.g. "0 * * * *" for hourly
+    pub schedule: String,
+    /// "json", "csv" or "both"
+    pub format: String,
+    /// Directory the files go in ("" for dumps/ in the data directory)
+    pub dir: String,
+    /// Days files are kept (0 keeps them all)
+    pub keep_days: u32,
+}
+
+impl Default for DumpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            schedule: "0 * * * *".to_string(),
+            format: "json".to_string(),
+            dir: String::new(),
+            keep_days: 14,
+        }
+    }
+}
+
+/// Gotify server that alert rules with `gotify = true` send to
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GotifyConfig {
+    /// Server URL, e.g. "https://gotify.example.com"
+    pub url: String,
+    /// Application token
+    pub token: Secret,
+}
+
+/// SOTA or POTA activation the `self_spot` button spots
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActivationConfig {
+    /// "pota" or "sota"
+    pub program: String,
+    /// Park or summit being activated, e.g. "US-1234" or "W7W/LC-001"
+    /// ("" = not activating)
+    pub reference: String,
+    /// Comment sent with the spot
+    pub comment: String,
+    /// Activating callsign ("" for `[station] callsign`)
+    pub callsign: String,
+    /// SOTAwatch access token, needed for SOTA spots
+    pub sota_token: Secret,
+}
+
+impl Default for ActivationConfig {
+    fn default() -> Self {
+        Self {
+            program: "pota".to_string(),
+            reference: String::new(),
+            comment: "QRV".to_string(),
+            callsign: String::new(),
+            sota_token: Secret::default(),
+        }
+    }
+}
+
+/// Audible alerts from a GPIO piezo or the sound card
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoundConfig {
+    /// "none", "gpio" (active piezo buzzer) or "alsa" (via `aplay`)
+    pub output: String,
+    /// Sysfs pin driving the piezo
+    pub piezo_pin: u32,
+    /// ALSA device for `aplay -D`
+    pub alsa_device: String,
+    /// Tone pitch for the sound card
+    pub tone_hz: u32,
+    /// Send the alert's callsign in Morse instead of a beep pattern
+    pub morse: bool,
+    /// Morse speed in words per minute
+    pub morse_wpm: u32,
+    /// Alert kinds that sound: "self", "watch", "dxcc", "slot", "rule"
+    pub alerts: String,
+    /// Silent period as "HH:MM-HH:MM" local time, may wrap midnight
+    pub quiet_hours: String,
+}
+
+impl Default for SoundConfig {
+    fn default() -> Self {
+        Self {
+            output: "none".to_string(),
+            piezo_pin: 18,
+            alsa_device: "default".to_string(),
+            tone_hz: 800,
+            morse: false,
+            morse_wpm: 20,
+            alerts: "watch,dxcc,slot".to_string(),
+            quiet_hours: String::new(),
+        }
+    }
+}
+
+/// Log levels and where log lines go (see `logging`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// "error", "warn", "info", "debug" or "trace"
+    pub level: String,
+    /// Levels for some modules, e.g. "rbn_client=debug,radio=warn"
+    pub modules: String,
+    /// "stderr", "file" and/or "journald", comma separated
+    pub output: String,
+    /// Log file ("" for rbn-vfd.log in the data directory)
+    pub file: String,
+    /// Size at which the file is rotated (0 = never)
+    pub max_file_kb: u32,
+    /// Rotated files kept as <file>.1, <file>.2, ...
+    pub keep_files: u32,
+    /// Log every line sent to and received from the RBN server
+    pub raw_lines: bool,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            modules: String::new(),
+            output: "stderr".to_string(),
+            file: String::new(),
+            max_file_kb: 1024,
+            keep_files: 3,
+            raw_lines: false,
+        }
+    }
+}
+
+/// Alert LEDs on GPIOs (Linux), by sysfs pin number
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LedConfig {
+    /// Lit while connected to RBN
+    #[serde(with = "blank_none")]
+    pub connected_pin: Option<u32>,
+    /// Blinks after a watch-list hit
+    #[serde(with = "blank_none")]
+    pub watch_pin: Option<u32>,
+    /// Blinks fast after a new-DXCC spot
+    #[serde(with = "blank_none")]
+    pub dxcc_pin: Option<u32>,
+    /// How long an alert keeps its LED blinking
+    pub alert_seconds: u32,
+}
+
+impl Default for LedConfig {
+    fn default() -> Self {
+        Self {
+            connected_pin: None,
+            watch_pin: None,
+            dxcc_pin: None,
+            alert_seconds: 60,
+        }
+    }
+}
+
+/// Template equivalent to the built-in list layout
+const DEFAULT_TEMPLATE: &str = "{freq:>7} {wpm:>2} {call}";
+
+/// Display hardware settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplayConfig {
+    /// Driver name ("elo", "framebuffer", "futaba", "hd44780_i2c", "hd44780_gpio",
+    /// "ili9341", "matrix_orbital", "noritake", "remote", "simulator", "ssd1306_i2c",
+    /// "ssd1306_spi", "sh1106_i2c", "sh1106_spi", "st7789", "waveshare_2in13",
+    /// "waveshare_2in9")
+    pub driver: String,
+    /// Serial baud rate (0 = the driver's default)
+    pub baud_rate: u32,
+    /// Text grid size
+    pub columns: usize,
+    pub rows: usize,
+    /// Brightness percentage applied on open, where the driver supports it
+    pub brightness: u8,
+    /// Display GPO switched on while spots are shown (0 = unused)
+    pub activity_output: u8,
+    /// I2C bus number and device address for I2C displays
+    pub i2c_bus: u8,
+    pub i2c_address: u8,
+    /// GPIO pins for directly wired displays, "rs,e,d4,d5,d6,d7"
+    pub gpio_pins: String,
+    /// SPI device (/dev/spidevB.C) and control pins for SPI displays
+    pub spi_bus: u8,
+    pub spi_chip_select: u8,
+    /// SPI clock in Hz (0 = the spidev default)
+    pub spi_speed_hz: u32,
+    pub dc_pin: u32,
+    #[serde(with = "blank_none")]
+    pub reset_pin: Option<u32>,
+    pub busy_pin: u32,
+    /// Backlight enable pin for TFT panels (13 on the Display HAT Mini)
+    #[serde(with = "blank_none")]
+    pub backlight_pin: Option<u32>,
+    /// Panel height in pixels for graphic displays (32 or 64)
+    pub pixel_height: u32,
+    /// Spot layout: "list" (one spot per line), "icons" (list with band/mode
+    /// glyphs), "compact" (one spot per page) or "template"
+    pub layout: String,
+    /// Line template for the template layout, e.g. "{mhz:>6} {wpm:>2} {call}"
+    /// (`|` separates the lines of a one-spot-per-page template, a line
+    /// starting with `^` is double size, and `{utc}`, `{local}`, `{session}`
+    /// and `{received}` show the clock and session counters)
+    pub template: String,
+    /// Show the selected spot at double size on displays that can
+    pub large_highlight: bool,
+    /// Lists longer than the display: "scroll" (one line per interval) or
+    /// "page" (a screenful per interval, with a "Page 2/5" indicator)
+    pub list_mode: String,
+    /// Minutes between full redraws on e-paper panels
+    pub full_refresh_minutes: u32,
+    /// Framebuffer device for the "framebuffer" driver
+    pub fb_device: String,
+    /// "host[:port]" of the `rbn-vfd-displayd` for the "remote" driver
+    pub remote_address: String,
+    /// Marquee speed for fields too long for their line, in characters per
+    /// second (0 truncates instead)
+    pub marquee_speed: u32,
+    /// How long a marquee holds at each end, in milliseconds
+    pub marquee_pause_ms: u32,
+    /// Comma separated views to rotate through: "spots", "bands", "stats",
+    /// "watch" (watch-list spots), "ladder", "clock", "cluster" (WWV lines
+    /// and announcements), "prop" (solar flux and A/K indices)
+    pub views: String,
+    /// Alert kinds that flash the display (pulse brightness, invert, a toast
+    /// on the web dashboard or a partial refresh on e-paper): "self",
+    /// "watch", "dxcc", "slot", "rule"
+    pub attention: String,
+    /// Comma separated items for a status footer on the last row: "feed",
+    /// "spots", "filters", "radio", "prop", "clock" (UTC/local time),
+    /// "session" (session length and spots received) (empty for no footer)
+    pub footer: String,
+    /// Seconds each view stays up before rotating to the next
+    pub view_seconds: u32,
+    /// Time-of-day brightness, "HH:MM=percent" entries separated by commas
+    /// (empty uses `brightness` all day)
+    pub brightness_schedule: String,
+    /// Local time range the display is switched off, e.g. "01:00-06:00"
+    /// (empty keeps it on)
+    pub off_hours: String,
+    /// Minutes a button press or watch-list hit keeps the display on during
+    /// `off_hours`
+    pub wake_minutes: u32,
+    /// I2C bus of the light sensor (None = the display's `i2c_bus`)
+    #[serde(with = "blank_none")]
+    pub light_sensor_bus: Option<u8>,
+    /// I2C address of a BH1750 light sensor on `light_sensor_bus` for ambient
+    /// dimming (0 = none, usually 0x23)
+    pub light_sensor_address: u8,
+    /// Character substitutions, "from=to" entries separated by commas (e.g.
+    /// "Ä=AE,Ö=OE"), applied before the built-in transliteration
+    pub charset_map: String,
+}
+
+impl DisplayConfig {
+    /// Configured baud rate, or the driver's default when unset
+    pub fn baud_rate_or(&self, default: u32) -> u32 {
+        if self.baud_rate == 0 {
+            default
+        } else {
+            self.baud_rate
+        }
+    }
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        Self {
+            driver: "elo".to_string(),
+            baud_rate: 0,
+            columns: 20,
+            rows: 2,
+            brightness: 100,
+            activity_output: 0,
+            i2c_bus: 1,
+            i2c_address: 0x27,
+            gpio_pins: "25,24,23,17,18,22".to_string(),
+            spi_bus: 0,
+            spi_chip_select: 0,
+            spi_speed_hz: 0,
+            dc_pin: 25,
+            reset_pin: Some(17),
+            busy_pin: 24,
+            backlight_pin: Some(13),
+            pixel_height: 64,
+            layout: "list".to_string(),
+            template: DEFAULT_TEMPLATE.to_string(),
+            large_highlight: false,
+            list_mode: "scroll".to_string(),
+            full_refresh_minutes: 10,
+            fb_device: "/dev/fb0".to_string(),
+            remote_address: String::new(),
+            marquee_speed: 4,
+            marquee_pause_ms: 1500,
+            views: "spots".to_string(),
+            attention: "watch,dxcc".to_string(),
+            footer: String::new(),
+            view_seconds: 15,
+            brightness_schedule: String::new(),
+            off_hours: String::new(),
+            wake_minutes: 5,
+            light_sensor_bus: None,
+            light_sensor_address: 0,
+            charset_map: String::new(),
+        }
+    }
+}
+
+/// Radio control settings, one `[[radio]]` entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RadioConfig {
+    /// Name to pick the radio by, e.g. "ic7300" (needed with several)
+    pub name: String,
+    /// Radio used on start (else the first entry)
+    pub default: bool,
+    pub enabled: bool,
+    pub backend: String,
+    pub rigctld_host: String,
+    pub rigctld_port: u16,
+    pub omnirig_rig: u8,
+    /// TX power (percent) applied after tuning onto a different band
+    pub band_power: BTreeMap<Band, u8>,
+    /// Filter widths in Hz applied after tuning (0 = leave the rig's default)
+    pub cw_filter_hz: u32,
+    pub ssb_filter_hz: u32,
+    pub data_filter_hz: u32,
+    /// USB VID:PID of the CAT adapter, watched for unplug/replug
+    #[serde(with = "blank_none")]
+    pub cat_usb_id: Option<UsbId>,
+    /// Hz the rig reads high (positive) or low (negative), added when tuning
+    pub calibration_offset_hz: i32,
+    /// Transverter local oscillator in kHz, subtracted from spot frequencies
+    /// when tuning, e.g. 116000 for 144 MHz on a 28 MHz rig (0 = none)
+    pub transverter_offset_khz: f64,
+    /// Launch and supervise rigctld from the app
+    pub manage_rigctld: bool,
+    pub rigctld_path: String,
+    /// Hamlib rig model number
+    pub rigctld_model: u32,
+    /// Rig serial device (empty = use the CAT USB ID, if set)
+    pub rigctld_device: String,
+    /// Extra rigctld arguments, e.g. "-s 38400"
+    pub rigctld_args: String,
+    /// Shell commands run before/after each tune (empty = none)
+    pub pre_tune_command: String,
+    pub post_tune_command: String,
+    /// Raw CAT commands sent before/after each tune, ';'-separated
+    pub pre_tune_cat: String,
+    pub post_tune_cat: String,
+    /// Step the VFO toward the target instead of jumping
+    pub smooth_qsy: bool,
+    pub smooth_qsy_step_hz: u32,
+    pub smooth_qsy_delay_ms: u32,
+    /// Send spots to the sub receiver instead of the main VFO
+    pub use_sub_receiver: bool,
+}
+
+impl RadioConfig {
+    /// Configured filter width for a mode, if any
+    pub fn filter_width_hz(&self, mode: RadioMode) -> Option<u32> {
+        let width = match mode {
+            RadioMode::Cw | RadioMode::CwReverse => self.cw_filter_hz,
+            RadioMode::Usb | RadioMode::Lsb | RadioMode::Am | RadioMode::Fm => self.ssb_filter_hz,
+            RadioMode::Rtty | RadioMode::RttyReverse | RadioMode::Data => self.data_filter_hz,
+        };
+        (width > 0).then_some(width)
+    }
+}
+
+impl Default for RadioConfig {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            default: false,
+            enabled: false,
+            backend: if cfg!(target_os = "windows") {
+                "omnirig".to_string()
+            } else {
+                "rigctld".to_string()
+            },
+            rigctld_host: "localhost".to_string(),
+            rigctld_port: 4532,
+            omnirig_rig: 1,
+            band_power: BTreeMap::new(),
+            cw_filter_hz: 500,
+            ssb_filter_hz: 2400,
+            data_filter_hz: 3000,
+            cat_usb_id: None,
+            calibration_offset_hz: 0,
+            transverter_offset_khz: 0.0,
+            manage_rigctld: false,
+            rigctld_path: "rigctld".to_string(),
+            rigctld_model: 1,
+            rigctld_device: String::new(),
+            rigctld_args: String::new(),
+            pre_tune_command: String::new(),
+            post_tune_command: String::new(),
+            pre_tune_cat: String::new(),
+            post_tune_cat: String::new(),
+            smooth_qsy: false,
+            smooth_qsy_step_hz: 1000,
+            smooth_qsy_delay_ms: 20,
+            use_sub_receiver: false,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            callsign: String::new(),
+            grid: String::new(),
+            cq_zone: 0,
+            itu_zone: 0,
+            region: None,
+            serial_port: String::new(),
+            display_usb_id: None,
+            min_snr: 10,
+            max_age_minutes: 10,
+            band_filter: None,
+            cw_only: false,
+            sort: SpotSort::default(),
+            keep_adjustments: false,
+            scroll_interval_seconds: 3,
+            random_char_percent: 20,
+            display: DisplayConfig::default(),
+            extra_displays: Vec::new(),
+            radio: RadioConfig::default(),
+            radios: vec![RadioConfig::default()],
+            active_radio: 0,
+            feed: FeedConfig::default(),
+            web: WebConfig::default(),
+            grpc: GrpcConfig::default(),
+            mqtt: MqttConfig::default(),
+            cloudlog: CloudlogConfig::default(),
+            qrz: CallbookConfig::default(),
+            hamqth: CallbookConfig::default(),
+            lotw: LotwConfig::default(),
+            clublog: ClubLogConfig::default(),
+            n1mm: N1mmConfig::default(),
+            logger: LoggerConfig::default(),
+            wsjtx: WsjtxConfig::default(),
+            telnet: TelnetConfig::default(),
+            input: InputConfig::default(),
+            alerts: AlertConfig::default(),
+            telegram: TelegramConfig::default(),
+            ntfy: NtfyConfig::default(),
+            gotify: GotifyConfig::default(),
+            digest: DigestConfig::default(),
+            aprs: AprsConfig::default(),
+            influx: InfluxConfig::default(),
+            dump: DumpConfig::default(),
+            activation: ActivationConfig::default(),
+            leds: LedConfig::default(),
+            sound: SoundConfig::default(),
+            logging: LoggingConfig::default(),
+            units: Units::default(),
+            profiles: BTreeMap::new(),
+            band_filters: BTreeMap::new(),
+            active_profile: String::new(),
+            base_settings: None,
+            presets: Vec::new(),
+            active_preset: None,
+            replay_file: None,
+            issues: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Use command-line settings for every later `load` and `save` (only the
+    /// first call takes effect)
+    pub fn set_overrides(overrides: Overrides) {
+        let _ = OVERRIDES.set(overrides);
+    }
+
+    /// Get the config file path (`--config`, or settings.toml in the
+    /// platform config directory)
+    pub fn config_path() -> Option<PathBuf> {
+        if let Some(path) = OVERRIDES.get().and_then(|o| o.config_path.clone()) {
+            return Some(path);
+        }
+        paths::config_file()
+    }
+
+    /// Commented settings.toml with every key at its default and the display
+    /// drivers and radio backends in this build
+    pub fn sample_toml() -> Result<String, String> {
+        sample::generate()
+    }
+
+    /// Load config from file, or return defaults if file doesn't exist, with
+    /// any command-line overrides applied
+    pub fn load() -> Self {
+        let mut config = Self::load_file();
+        if let Some(overrides) = OVERRIDES.get() {
+            overrides.apply(&mut config);
+        }
+        config
+    }
+
+    /// Settings as stored in the file
+    ///
+    /// A settings.ini from an older version is read once and rewritten as
+    /// settings.toml next to it.
+    fn load_file() -> Self {
+        let path = Self::config_path();
+        if let Some(ref path) = path {
+            let legacy = path.with_file_name("settings.ini");
+            let custom_path = OVERRIDES.get().is_some_and(|o| o.config_path.is_some());
+            if !path.exists() && !custom_path && legacy.exists() {
+                let config = Self::from_ini(&legacy);
+                // Written directly: `save` reads the file back for overrides
+                match config.to_toml().and_then(|text| {
+                    std::fs::write(path, text).map_err(|e| format!("Failed to write config: {}", e))
+                }) {
+                    Ok(()) => eprintln!(
+                        "Moved settings from {} to {}",
+                        legacy.display(),
+                        path.display()
+                    ),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        return config;
+                    }
+                }
+            }
+        }
+
+        let path = path.filter(|path| path.exists());
+        Self::read_file(path.as_deref(), false).unwrap_or_default()
+    }
+
+    /// Load and check the settings file without changing anything on disk
+    /// (`check-config`): problems found are in `issues`, and a file that
+    /// can't be read or parsed is an error
+    pub fn check() -> Result<Self, String> {
+        let path = Self::config_path().ok_or("Could not determine config path")?;
+        if !path.exists() {
+            return Err(format!("{} not found", path.display()));
+        }
+        let mut config = Self::read_file(Some(&path), true)?;
+        if let Some(overrides) = OVERRIDES.get() {
+            overrides.apply(&mut config);
+        }
+        Ok(config)
+    }
+
+    /// Settings from the file at `path` (None for the defaults), with
+    /// secrets, environment and state laid over them and checked
+    ///
+    /// A load upgrades an old layout on disk, reports problems on stderr
+    /// and, given a file it can't use, keeps a copy and goes on with the
+    /// defaults. A `dry_run` leaves the file alone, prints nothing and
+    /// fails instead; only a dry run returns an error.
+    fn read_file(path: Option<&Path>, dry_run: bool) -> Result<Self, String> {
+        let mut text = match path {
+            Some(path) => match std::fs::read_to_string(path) {
+                Ok(text) => text,
+                Err(e) if dry_run => {
+                    return Err(format!("Failed to read {}: {}", path.display(), e))
+                }
+                Err(e) => {
+                    eprintln!("Failed to read {}: {}", path.display(), e);
+                    String::new()
+                }
+            },
+            None => String::new(),
+        };
+        let mut issues = Vec::new();
+        let mut table = match toml::from_str::<toml::Table>(&text) {
+            Ok(mut table) => {
+                if dry_run {
+                    issues.extend(migrate::newer(&table).map(|message| Issue {
+                        key: "version".to_string(),
+                        line: None,
+                        message,
+                    }));
+                    migrate::apply(&mut table);
+                } else if let Some(upgraded) =
+                    path.and_then(|path| migrate::upgrade(path, &mut table))
+                {
+                    text = upgraded;
+                }
+                match path {
+                    Some(path) => include::beneath(path, table, &mut issues),
+                    None => table,
+                }
+            }
+            Err(e) if dry_run => return Err(e.to_string()),
+            Err(e) => {
+                keep_invalid_file(path, &e.to_string());
+                toml::Table::new()
+            }
+        };
+        if let Some(path) = path {
+            for (key, value) in secret::load(&secret::file_for(path), &mut issues) {
+                set_key(&mut table, &key, value);
+            }
+        }
+        for (key, value) in env_overrides() {
+            set_key(&mut table, key, value.clone());
+        }
+        if let Some(path) = path.filter(|_| keeps_adjustments(&table)) {
+            for (key, value) in state::load(&state::file_for(path)) {
+                set_key(&mut table, &key, value);
+            }
+        }
+        display::load_entries(&mut table, &text, &mut issues);
+        match toml::Value::Table(table).try_into::<ConfigFile>() {
+            Ok(file) => {
+                issues.extend(validate::check(&file, &text));
+                for issue in issues.iter().filter(|_| !dry_run) {
+                    match path {
+                        Some(path) => eprintln!("{}: {}", path.display(), issue),
+                        None => eprintln!("{}", issue),
+                    }
+                }
+                let mut config = Self::from(file);
+                config.clamp();
+                config.issues = issues;
+                Ok(config)
+            }
+            Err(e) => {
+                // Parsing the text again locates the bad value by line
+                let error = toml::from_str::<ConfigFile>(&text)
+                    .err()
+                    .map_or_else(|| e.to_string(), |e| e.to_string());
+                if dry_run {
+                    return Err(error);
+                }
+                keep_invalid_file(path, &error);
+                Ok(Self::default())
+            }
+        }
+    }
+
+    /// Bring loaded values into range
+    fn clamp(&mut self) {
+        let extras = self
+            .extra_displays
+            .iter_mut()
+            .map(|extra| &mut extra.settings);
+        for display in std::iter::once(&mut self.display).chain(extras) {
+            display.columns = display.columns.clamp(1, 80);
+            display.rows = display.rows.clamp(1, 8);
+            display.brightness = display.brightness.min(100);
+            display.full_refresh_minutes = display.full_refresh_minutes.max(1);
+            display.view_seconds = display.view_seconds.max(1);
+            display.wake_minutes = display.wake_minutes.max(1);
+        }
+        for radio in self.radios.iter_mut().chain([&mut self.radio]) {
+            for percent in radio.band_power.values_mut() {
+                *percent = (*percent).min(100);
+            }
+        }
+        self.sound.tone_hz = self.sound.tone_hz.clamp(100, 4000);
+    }
+
+    /// Read a settings.ini written by versions before settings.toml
+    fn from_ini(path: &Path) -> Self {
+        let mut ini = Ini::new();
+        if ini.load(path).is_err() {
+            return Self::default();
+        }
+
+        let display = DisplayConfig {
+            driver: ini
+                .get("display", "driver")
+                .unwrap_or_else(|| "elo".to_string()),
+            baud_rate: ini
+                .getint("display", "baud_rate")
+                .ok()
+                .flatten()
+                .unwrap_or(0) as u32,
+            columns: ini
+                .getint("display", "columns")
+                .ok()
+                .flatten()
+                .unwrap_or(20)
+                .clamp(1, 80) as usize,
+            rows: ini
+                .getint("display", "rows")
+                .ok()
+                .flatten()
+                .unwrap_or(2)
+                .clamp(1, 8) as usize,
+            brightness: ini
+                .getint("display", "brightness")
+                .ok()
+                .flatten()
+                .unwrap_or(100)
+                .clamp(0, 100) as u8,
+            activity_output: ini
+                .getint("display", "activity_output")
+                .ok()
+                .flatten()
+                .unwrap_or(0)
+                .clamp(0, 255) as u8,
+            i2c_bus: ini.getint("display", "i2c_bus").ok().flatten().unwrap_or(1) as u8,
+            i2c_address: ini
+                .get("display", "i2c_address")
+                .and_then(|address| {
+                    u8::from_str_radix(address.trim().trim_start_matches("0x"), 16).ok()
+                })
+                .unwrap_or(0x27),
+            gpio_pins: ini
+                .get("display", "gpio_pins")
+                .unwrap_or_else(|| "25,24,23,17,18,22".to_string()),
+            spi_bus: ini.getint("display", "spi_bus").ok().flatten().unwrap_or(0) as u8,
+            spi_chip_select: ini
+                .getint("display", "spi_chip_select")
+                .ok()
+                .flatten()
+                .unwrap_or(0) as u8,
+            spi_speed_hz: ini
+                .getint("display", "spi_speed_hz")
+                .ok()
+                .flatten()
+                .unwrap_or(0)
+                .max(0) as u32,
+            dc_pin: ini.getint("display", "dc_pin").ok().flatten().unwrap_or(25) as u32,
+            reset_pin: match ini.get("display", "reset_pin") {
+                Some(pin) => pin.trim().parse().ok(),
+                None => Some(17),
+            },
+            busy_pin: ini
+                .getint("display", "busy_pin")
+                .ok()
+                .flatten()
+                .unwrap_or(24) as u32,
+            backlight_pin: match ini.get("display", "backlight_pin") {
+                Some(pin) => pin.trim().parse().ok(),
+                None => Some(13),
+            },
+            pixel_height: ini
+                .getint("display", "pixel_height")
+                .ok()
+                .flatten()
+                .unwrap_or(64) as u32,
+            layout: ini
+                .get("display", "layout")
+                .unwrap_or_else(|| "list".to_string()),
+            template: ini
+                .get("display", "template")
+                .unwrap_or_else(|| DEFAULT_TEMPLATE.to_string()),
+            large_highlight: ini
+                .getbool("display", "large_highlight")
+                .ok()
+                .flatten()
+                .unwrap_or(false),
+            list_mode: ini
+                .get("display", "list_mode")
+                .unwrap_or_else(|| "scroll".to_string()),
+            full_refresh_minutes: ini
+                .getint("display", "full_refresh_minutes")
+                .ok()
+                .flatten()
+                .unwrap_or(10)
+                .max(1) as u32,
+            fb_device: ini
+                .get("display", "fb_device")
+                .unwrap_or_else(|| "/dev/fb0".to_string()),
+            remote_address: ini.get("display", "remote_address").unwrap_or_default(),
+            marquee_speed: ini
+                .getint("display", "marquee_speed")
+                .ok()
+                .flatten()
+                .unwrap_or(4)
+                .max(0) as u32,
+            marquee_pause_ms: ini
+                .getint("display", "marquee_pause_ms")
+                .ok()
+                .flatten()
+                .unwrap_or(1500)
+                .max(0) as u32,
+            views: ini
+                .get("display", "views")
+                .unwrap_or_else(|| "spots".to_string()),
+            attention: ini
+                .get("display", "attention")
+                .unwrap_or_else(|| "watch,dxcc".to_string()),
+            footer: ini.get("display", "footer").unwrap_or_default(),
+            view_seconds: ini
+                .getint("display", "view_seconds")
+                .ok()
+                .flatten()
+                .unwrap_or(15)
+                .max(1) as u32,
+            brightness_schedule: ini
+                .get("display", "brightness_schedule")
+                .unwrap_or_default(),
+            off_hours: ini.get("display", "off_hours").unwrap_or_default(),
+            wake_minutes: ini
+                .getint("display", "wake_minutes")
+                .ok()
+                .flatten()
+                .unwrap_or(5)
+                .max(1) as u32,
+            light_sensor_bus: ini
+                .get("display", "light_sensor_bus")
+                .and_then(|bus| bus.trim().parse().ok()),
+            light_sensor_address: ini
+                .get("display", "light_sensor_address")
+                .and_then(|address| {
+                    u8::from_str_radix(address.trim().trim_start_matches("0x"), 16).ok()
+                })
+                .unwrap_or(0),
+            charset_map: ini.get("display", "charset_map").unwrap_or_default(),
+        };
+
+        let radio = RadioConfig {
+            name: String::new(),
+            default: false,
+            enabled: ini
+                .getbool("radio", "enabled")
+                .ok()
+                .flatten()
+                .unwrap_or(false),
+            backend: ini.get("radio", "backend").unwrap_or_else(|| {
+                if cfg!(target_os = "windows") {
+                    "omnirig".to_string()
+                } else {
+                    "rigctld".to_string()
+                }
+            }),
+            rigctld_host: ini
+                .get("radio", "rigctld_host")
+                .unwrap_or_else(|| "localhost".to_string()),
+            rigctld_port: ini
+                .getint("radio", "rigctld_port")
+                .ok()
+                .flatten()
+                .unwrap_or(4532) as u16,
+            omnirig_rig: ini
+                .getint("radio", "omnirig_rig")
+                .ok()
+                .flatten()
+                .unwrap_or(1) as u8,
+            band_power: Band::ALL
+                .into_iter()
+                .filter_map(|band| {
+                    ini.getint("radio", &format!("power_{}", band.name()))
+                        .ok()
+                        .flatten()
+                        .map(|percent| (band, percent.clamp(0, 100) as u8))
+                })
+                .collect(),
+            cw_filter_hz: ini
+                .getint("radio", "cw_filter_hz")
+                .ok()
+                .flatten()
+                .unwrap_or(500) as u32,
+            ssb_filter_hz: ini
+                .getint("radio", "ssb_filter_hz")
+                .ok()
+                .flatten()
+                .unwrap_or(2400) as u32,
+            data_filter_hz: ini
+                .getint("radio", "data_filter_hz")
+                .ok()
+                .flatten()
+                .unwrap_or(3000) as u32,
+            cat_usb_id: ini
+                .get("radio", "cat_usb_id")
+                .and_then(|id| id.parse().ok()),
+            calibration_offset_hz: ini
+                .getint("radio", "calibration_offset_hz")
+                .ok()
+                .flatten()
+                .unwrap_or(0) as i32,
+            transverter_offset_khz: 0.0,
+            manage_rigctld: ini
+                .getbool("radio", "manage_rigctld")
+                .ok()
+                .flatten()
+                .unwrap_or(false),
+            rigctld_path: ini
+                .get("radio", "rigctld_path")
+                .unwrap_or_else(|| "rigctld".to_string()),
+            rigctld_model: ini
+                .getint("radio", "rigctld_model")
+                .ok()
+                .flatten()
+                .unwrap_or(1) as u32,
+            rigctld_device: ini.get("radio", "rigctld_device").unwrap_or_default(),
+            rigctld_args: ini.get("radio", "rigctld_args").unwrap_or_default(),
+            pre_tune_command: ini.get("radio", "pre_tune_command").unwrap_or_default(),
+            post_tune_command: ini.get("radio", "post_tune_command").unwrap_or_default(),
+            pre_tune_cat: ini.get("radio", "pre_tune_cat").unwrap_or_default(),
+            post_tune_cat: ini.get("radio", "post_tune_cat").unwrap_or_default(),
+            smooth_qsy: ini
+                .getbool("radio", "smooth_qsy")
+                .ok()
+                .flatten()
+                .unwrap_or(false),
+            smooth_qsy_step_hz: ini
+                .getint("radio", "smooth_qsy_step_hz")
+                .ok()
+                .flatten()
+                .unwrap_or(1000) as u32,
+            smooth_qsy_delay_ms: ini
+                .getint("radio", "smooth_qsy_delay_ms")
+                .ok()
+                .flatten()
+                .unwrap_or(20) as u32,
+            use_sub_receiver: ini
+                .getbool("radio", "use_sub_receiver")
+                .ok()
+                .flatten()
+                .unwrap_or(false),
+        };
+
+        Self {
+            callsign: ini.get("connection", "callsign").unwrap_or_default(),
+            grid: ini.get("station", "grid").unwrap_or_default(),
+            serial_port: ini.get("display", "serial_port").unwrap_or_default(),
+            display_usb_id: ini.get("display", "usb_id").and_then(|id| id.parse().ok()),
+            min_snr: ini
+                .getint("filters", "min_snr")
+                .ok()
+                .flatten()
+                .unwrap_or(10) as i32,
+            max_age_minutes: ini
+                .getint("filters", "max_age_minutes")
+                .ok()
+                .flatten()
+                .unwrap_or(10) as u32,
+            band_filter: ini
+                .get("filters", "band")
+                .and_then(|band| Band::from_name(&band)),
+            cw_only: ini
+                .getbool("filters", "cw_only")
+                .ok()
+                .flatten()
+                .unwrap_or(false),
+            scroll_interval_seconds: ini
+                .getint("filters", "scroll_interval_seconds")
+                .ok()
+                .flatten()
+                .unwrap_or(3) as u32,
+            random_char_percent: ini
+                .getint("display", "random_char_percent")
+                .ok()
+                .flatten()
+                .unwrap_or(20) as u32,
+            display,
+            radios: vec![radio.clone()],
+            radio,
+            web: WebConfig {
+                enabled: ini
+                    .getbool("web", "enabled")
+                    .ok()
+                    .flatten()
+                    .unwrap_or(false),
+                bind: ini
+                    .get("web", "bind")
+                    .unwrap_or_else(|| "0.0.0.0".to_string()),
+                port: ini.getint("web", "port").ok().flatten().unwrap_or(8080) as u16,
+                api_token: Secret::default(),
+            },
+            mqtt: MqttConfig {
+                enabled: ini
+                    .getbool("mqtt", "enabled")
+                    .ok()
+                    .flatten()
+                    .unwrap_or(false),
+                host: ini
+                    .get("mqtt", "host")
+                    .unwrap_or_else(|| "localhost".to_string()),
+                port: ini.getint("mqtt", "port").ok().flatten().unwrap_or(1883) as u16,
+                client_id: ini
+                    .get("mqtt", "client_id")
+                    .unwrap_or_else(|| "rbn-vfd".to_string()),
+                username: ini.get("mqtt", "username").unwrap_or_default(),
+                password: ini.get("mqtt", "password").unwrap_or_default().into(),
+                topic_prefix: ini
+                    .get("mqtt", "topic_prefix")
+                    .unwrap_or_else(|| "rbn-vfd".to_string()),
+                ..MqttConfig::default()
+            },
+            input: InputConfig {
+                encoder: ini
+                    .get("input", "encoder")
+                    .unwrap_or_else(|| "none".to_string()),
+                evdev_devices: ini.get("input", "evdev_devices").unwrap_or_default(),
+                encoder_pins: ini.get("input", "encoder_pins").unwrap_or_default(),
+                buttons: ini.get("input", "buttons").unwrap_or_default(),
+                keys: ini.get("input", "keys").unwrap_or_default(),
+                keypad: ini.get("input", "keypad").unwrap_or_default(),
+            },
+            alerts: AlertConfig {
+                watch_list: ini.get("alerts", "watch_list").unwrap_or_default(),
+                cty_file: ini.get("alerts", "cty_file").unwrap_or_default(),
+                worked_dxcc: ini.get("alerts", "worked_dxcc").unwrap_or_default(),
+                worked_slots: ini.get("alerts", "worked_slots").unwrap_or_default(),
+                adif_file: String::new(),
+                rules: Vec::new(),
+                ..AlertConfig::default()
+            },
+            leds: LedConfig {
+                connected_pin: ini
+                    .get("leds", "connected_pin")
+                    .and_then(|pin| pin.trim().parse().ok()),
+                watch_pin: ini
+                    .get("leds", "watch_pin")
+                    .and_then(|pin| pin.trim().parse().ok()),
+                dxcc_pin: ini
+                    .get("leds", "dxcc_pin")
+                    .and_then(|pin| pin.trim().parse().ok()),
+                alert_seconds: ini
+                    .getint("leds", "alert_seconds")
+                    .ok()
+                    .flatten()
+                    .unwrap_or(60) as u32,
+            },
+            sound: SoundConfig {
+                output: ini
+                    .get("sound", "output")
+                    .unwrap_or_else(|| "none".to_string()),
+                piezo_pin: ini
+                    .getint("sound", "piezo_pin")
+                    .ok()
+                    .flatten()
+                    .unwrap_or(18) as u32,
+                alsa_device: ini
+                    .get("sound", "alsa_device")
+                    .unwrap_or_else(|| "default".to_string()),
+                tone_hz: ini
+                    .getint("sound", "tone_hz")
+                    .ok()
+                    .flatten()
+                    .unwrap_or(800)
+                    .clamp(100, 4000) as u32,
+                alerts: ini
+                    .get("sound", "alerts")
+                    .unwrap_or_else(|| "watch,dxcc,slot".to_string()),
+                quiet_hours: ini.get("sound", "quiet_hours").unwrap_or_default(),
+                ..SoundConfig::default()
+            },
+            ..Self::default()
+        }
+    }
+
+    /// Save config to file
+    pub fn save(&self) -> Result<(), String> {
+        let Some(path) = Self::config_path() else {
+            return Err("Could not determine config path".to_string());
+        };
+
+        // Create config directory if it doesn't exist
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+
+        let mut config = self.clone();
+        if let Some(overrides) = OVERRIDES.get() {
+            overrides.restore(&mut config, &Self::load_file());
+        }
+        let mut table = config.to_table()?;
+        let file = read_table(&path).unwrap_or_default();
+        let included = include::load(&path, &file, &mut Vec::new());
+        restore_keys(&mut table, &file, &include::settings(&included));
+        if let Some(names) = file.get(include::KEY) {
+            // Top-level values go before the sections
+            let mut front = toml::Table::new();
+            front.insert(include::KEY.to_string(), names.clone());
+            front.extend(table);
+            table = front;
+        }
+        let secrets = secret::load(&secret::file_for(&path), &mut Vec::new());
+        restore_keys(&mut table, &file, &secrets);
+        restore_keys(&mut table, &file, env_overrides());
+        if config.keep_adjustments {
+            restore_keys(&mut table, &file, &state::load(&state::file_for(&path)));
+        }
+        let text = toml::to_string_pretty(&table)
+            .map_err(|e| format!("Failed to encode config: {}", e))?;
+        std::fs::write(&path, text).map_err(|e| format!("Failed to write config: {}", e))
+    }
+
+    /// Write the filters, profile and preset to state.toml, for
+    /// `keep_adjustments`; command-line values the app left unchanged keep
+    /// what state.toml had
+    pub fn save_state(&self) -> Result<(), String> {
+        let Some(path) = Self::config_path() else {
+            return Err("Could not determine config path".to_string());
+        };
+        let skip = OVERRIDES
+            .get()
+            .map(|overrides| overrides.unchanged_keys(self))
+            .unwrap_or_default();
+        state::save(&state::file_for(&path), &self.to_table()?, &skip)
+    }
+
+    /// Settings in the settings.toml format
+    pub fn to_toml(&self) -> Result<String, String> {
+        toml::to_string_pretty(&self.to_table()?)
+            .map_err(|e| format!("Failed to encode config: {}", e))
+    }
+
+    /// Settings as settings.toml tables, `[[display]]` entries trimmed to
+    /// their drivers' keys
+    fn to_table(&self) -> Result<toml::Table, String> {
+        let mut table = match toml::Value::try_from(ConfigFile::from(self)) {
+            Ok(toml::Value::Table(table)) => table,
+            _ => return Err("Failed to encode config".to_string()),
+        };
+        display::save_entries(&mut table);
+        Ok(table)
+    }
+
+    /// Copy safe to print or log: secrets given in the file, secrets.toml
+    /// or the environment are hidden (`env:` references stay)
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        config.mqtt.password = config.mqtt.password.redacted();
+        config.cloudlog.api_key = config.cloudlog.api_key.redacted();
+        config.qrz.password = config.qrz.password.redacted();
+        config.hamqth.password = config.hamqth.password.redacted();
+        config.clublog.password = config.clublog.password.redacted();
+        config.clublog.api_key = config.clublog.api_key.redacted();
+        config.web.api_token = config.web.api_token.redacted();
+        config.grpc.token = config.grpc.token.redacted();
+        config.telegram.bot_token = config.telegram.bot_token.redacted();
+        config.ntfy.token = config.ntfy.token.redacted();
+        config.gotify.token = config.gotify.token.redacted();
+        config.digest.password = config.digest.password.redacted();
+        config.activation.sota_token = config.activation.sota_token.redacted();
+        config.influx.token = config.influx.token.redacted();
+        config
+    }
+
+    /// Switch to `[profile.<name>]` ("" for the main settings). While a
+    /// profile is active the main settings are saved as they were before it.
+    pub fn select_profile(&mut self, name: &str) -> Result<(), String> {
+        let name = name.trim();
+        let profile = match name {
+            "" => None,
+            name => Some(
+                self.profiles
+                    .get(name)
+                    .cloned()
+                    .ok_or_else(|| format!("No profile named '{}'", name))?,
+            ),
+        };
+        if let Some(base) = self.base_settings.take() {
+            base.apply(self);
+        }
+        if let Some(profile) = profile {
+            self.base_settings = Some(Profile::capture(self));
+            profile.apply(self);
+        }
+        self.active_profile = name.to_string();
+        Ok(())
+    }
+
+    /// Step to the next profile by name, passing through the main settings
+    /// after the last one
+    pub fn next_profile(&mut self) {
+        let next = self
+            .profiles
+            .keys()
+            .find(|name| name.as_str() > self.active_profile.as_str())
+            .cloned()
+            .unwrap_or_default();
+        let _ = self.select_profile(&next);
+    }
+
+    /// Active profile name, or "main"
+    pub fn profile_label(&self) -> &str {
+        if self.active_profile.is_empty() {
+            "main"
+        } else {
+            &self.active_profile
+        }
+    }
+
+    /// Switch to the `[[preset]]` with this name ("" for the filters alone)
+    pub fn select_preset(&mut self, name: &str) -> Result<(), String> {
+        let name = name.trim();
+        self.active_preset = match name {
+            "" => None,
+            name => Some(
+                self.presets
+                    .iter()
+                    .position(|preset| preset.name.trim().eq_ignore_ascii_case(name))
+                    .ok_or_else(|| format!("No preset named '{}'", name))?,
+            ),
+        };
+        Ok(())
+    }
+
+    /// Step to the next preset in file order, passing through the filters
+    /// alone after the last one
+    pub fn next_preset(&mut self) {
+        self.active_preset = match self.active_preset {
+            None if !self.presets.is_empty() => Some(0),
+            Some(index) if index + 1 < self.presets.len() => Some(index + 1),
+            _ => None,
+        };
+    }
+
+    /// Preset in use, if any
+    pub fn preset(&self) -> Option<&FilterPreset> {
+        self.active_preset.and_then(|index| self.presets.get(index))
+    }
+
+    /// Name of the preset in use, or "none"
+    pub fn preset_label(&self) -> &str {
+        self.preset().map_or("none", |preset| preset.name.as_str())
+    }
+
+    /// Switch to the `[[radio]]` entry with this name, keeping the changes
+    /// made to the one in use
+    pub fn select_radio(&mut self, name: &str) -> Result<(), String> {
+        let name = name.trim();
+        let index = self
+            .radios
+            .iter()
+            .position(|radio| radio.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("No radio named '{}'", name))?;
+        self.use_radio(index);
+        Ok(())
+    }
+
+    /// Step to the next `[[radio]]` entry, wrapping to the first
+    pub fn next_radio(&mut self) {
+        if !self.radios.is_empty() {
+            self.use_radio((self.active_radio + 1) % self.radios.len());
+        }
+    }
+
+    fn use_radio(&mut self, index: usize) {
+        if let Some(current) = self.radios.get_mut(self.active_radio) {
+            *current = self.radio.clone();
+        }
+        self.active_radio = index;
+        self.radio = self.radios[index].clone();
+    }
+
+    /// Name of the radio in use, or its backend when it has none
+    pub fn radio_label(&self) -> &str {
+        if self.radio.name.is_empty() {
+            &self.radio.backend
+        } else {
+            &self.radio.name
+        }
+    }
+
+    /// Whether a spot passes the band and CW-only filters (its band's
+    /// `cw_only`, if set, else the preset's)
+    pub fn band_mode_match(&self, spot: &AggregatedSpot) -> bool {
+        let band = Band::from_khz(spot.frequency_khz);
+        let preset = self.preset();
+        let cw_only = band
+            .and_then(|band| self.band_filters.get(&band))
+            .and_then(|filter| filter.cw_only)
+            .or(preset.and_then(|preset| preset.cw_only))
+            .unwrap_or(self.cw_only);
+        let preset_bands = preset.map(FilterPreset::band_list).unwrap_or_default();
+        let band_match = if preset_bands.is_empty() {
+            self.band_filter
+                .is_none_or(|selected| band == Some(selected))
+        } else {
+            band.is_some_and(|band| preset_bands.contains(&band))
+        };
+        band_match && (!cw_only || spot.mode.eq_ignore_ascii_case("CW"))
+    }
+
+    /// Whether a spot passes the preset's continent and new-only settings
+    /// (always, without a preset)
+    pub fn preset_match(&self, spot: &AggregatedSpot, alerts: &SpotAlerts) -> bool {
+        self.preset()
+            .is_none_or(|preset| preset.allows(spot, alerts))
+    }
+
+    /// SNR and age limits, global (or the preset's) and from
+    /// `[filters.<band>]`
+    pub fn spot_limits(&self) -> SpotLimits {
+        let minutes = |minutes: u32| Duration::from_secs(minutes as u64 * 60);
+        let preset = self.preset();
+        let min_snr = preset
+            .and_then(|preset| preset.min_snr)
+            .unwrap_or(self.min_snr);
+        let max_age_minutes = preset
+            .and_then(|preset| preset.max_age_minutes)
+            .unwrap_or(self.max_age_minutes);
+        SpotLimits {
+            min_snr,
+            max_age: minutes(max_age_minutes),
+            bands: self
+                .band_filters
+                .iter()
+                .map(|(band, filter)| {
+                    let limits = (
+                        filter.min_snr.unwrap_or(min_snr),
+                        minutes(filter.max_age_minutes.unwrap_or(max_age_minutes)),
+                    );
+                    (*band, limits)
+                })
+                .collect(),
+        }
+    }
+
+    /// Spots passing every filter (SNR and age per band, band, mode, and
+    /// the preset's continents and new-only), in the `sort` order
+    pub fn filter_spots(&self, spot_store: &SpotStore, alerts: &SpotAlerts) -> Vec<AggregatedSpot> {
+        let limits = self.spot_limits();
+        let (min_snr, max_age) = limits.loosest();
+        let mut spots = spot_store.get_filtered_spots(min_snr, max_age);
+        spots.retain(|spot| {
+            limits.allows(spot) && self.band_mode_match(spot) && self.preset_match(spot, alerts)
+        });
+        self.sort.sort(&mut spots);
+        spots
+    }
+
+    /// Short description of the active spot filters, e.g. "CW ≥10dB 20m",
+    /// or the preset's name while one is in use
+    pub fn filter_summary(&self) -> String {
+        if let Some(preset) = self.preset() {
+            return preset.name.clone();
+        }
+        let mut parts = Vec::new();
+        if self.cw_only {
+            parts.push("CW".to_string());
+        }
+        if self.min_snr > 0 {
+            parts.push(format!("≥{}dB", self.min_snr));
+        }
+        if let Some(band) = self.band_filter {
+            parts.push(band.name().to_string());
+        }
+        parts.join(" ")
+    }
+
+    /// Reset to defaults
+    pub fn reset_to_defaults(&mut self) {
+        let defaults = Self::default();
+        self.min_snr = defaults.min_snr;
+        self.max_age_minutes = defaults.max_age_minutes;
+        self.band_filter = defaults.band_filter;
+        self.cw_only = defaults.cw_only;
+        self.sort = defaults.sort;
+        self.scroll_interval_seconds = defaults.scroll_interval_seconds;
+        self.random_char_percent = defaults.random_char_percent;
+        // Keep callsign and serial_port as-is
+    }
+}
+
+/// Parse a settings file into its tables
+fn read_table(path: &Path) -> Result<toml::Table, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    toml::from_str(&text).map_err(|e| e.to_string())
+}
+
+/// Whether `[filters] keep_adjustments` is on in a parsed settings file
+fn keeps_adjustments(table: &toml::Table) -> bool {
+    let key = ["filters".to_string(), "keep_adjustments".to_string()];
+    get_key(table, &key)
+        .and_then(toml::Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// Report a settings file that can't be used and, since settings are saved
+/// on exit, keep a copy of it
+fn keep_invalid_file(path: Option<&Path>, error: &str) {
+    match path {
+        Some(path) => {
+            let backup = path.with_extension("toml.bak");
+            let _ = std::fs::copy(path, &backup);
+            eprintln!(
+                "Invalid {} (copied to {}), using defaults: {}",
+                path.display(),
+                backup.display(),
+                error
+            );
+        }
+        None => eprintln!("Invalid settings, using defaults: {}", error),
+    }
+}
+
+/// `RBNVFD_*` environment variables, as settings.toml keys (split on `__`,
+/// e.g. `RBNVFD_DISPLAY__DRIVER` for `[display] driver`) and values
+fn env_overrides() -> &'static [(Vec<String>, toml::Value)] {
+    static ENV: OnceLock<Vec<(Vec<String>, toml::Value)>> = OnceLock::new();
+    ENV.get_or_init(|| {
+        let defaults = toml::Value::try_from(ConfigFile::default())
+            .unwrap_or_else(|_| toml::Value::Table(toml::Table::new()));
+        std::env::vars()
+            .filter_map(|(name, text)| {
+                let key: Vec<String> = name
+                    .strip_prefix(ENV_PREFIX)?
+                    .split("__")
+                    .map(str::to_lowercase)
+                    .collect();
+                match env_value(&defaults, &key, &text) {
+                    Some(value) => Some((key, value)),
+                    None => {
+                        eprintln!("Ignoring {}: no such setting", name);
+                        None
+                    }
+                }
+            })
+            .collect()
+    })
+}
+
+/// Setting value from environment text, typed like the setting's default
+/// (None when there is no such setting)
+fn env_value(defaults: &toml::Value, key: &[String], text: &str) -> Option<toml::Value> {
+    let mut default = defaults;
+    for part in key {
+        match default {
+            // Open-ended tables like `[radio.band_power]` and `[profile.<name>]`
+            toml::Value::Table(table) if table.is_empty() => return Some(guess_value(text)),
+            toml::Value::Table(table) => default = table.get(part)?,
+            _ => return None,
+        }
+    }
+    let string = || toml::Value::String(text.to_string());
+    Some(match default {
+        toml::Value::Integer(_) => text
+            .trim()
+            .parse()
+            .map_or_else(|_| string(), toml::Value::Integer),
+        toml::Value::Float(_) => text
+            .trim()
+            .parse()
+            .map_or_else(|_| string(), toml::Value::Float),
+        toml::Value::Boolean(_) => match text.trim().to_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => toml::Value::Boolean(true),
+            "false" | "no" | "off" | "0" => toml::Value::Boolean(false),
+            _ => string(),
+        },
+        toml::Value::Table(_) => return None,
+        _ => string(),
+    })
+}
+
+/// Environment text read as a TOML value, or else as a string
+fn guess_value(text: &str) -> toml::Value {
+    toml::from_str::<toml::Table>(&format!("value = {}", text))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(text.to_string()))
+}
+
+fn get_key<'a>(table: &'a toml::Table, key: &[String]) -> Option<&'a toml::Value> {
+    let (first, rest) = key.split_first()?;
+    rest.iter()
+        .try_fold(table.get(first)?, |value, part| value.as_table()?.get(part))
+}
+
+/// Set a value, creating the tables on its way
+fn set_key(table: &mut toml::Table, key: &[String], value: toml::Value) {
+    let Some((last, parents)) = key.split_last() else {
+        return;
+    };
+    let mut table = table;
+    for part in parents {
+        let entry = table
+            .entry(part.clone())
+            .or_insert(toml::Value::Table(toml::Table::new()));
+        let toml::Value::Table(next) = entry else {
+            return;
+        };
+        table = next;
+    }
+    table.insert(last.clone(), value);
+}
+
+fn remove_key(table: &mut toml::Table, key: &[String]) {
+    let Some((last, parents)) = key.split_last() else {
+        return;
+    };
+    let mut table = table;
+    for part in parents {
+        let Some(toml::Value::Table(next)) = table.get_mut(part) else {
+            return;
+        };
+        table = next;
+    }
+    table.remove(last);
+}
+
+/// Put back the file's values for keys laid over it (environment
+/// overrides, secrets.toml) that the app left unchanged, so they stay out of
+/// the saved file
+fn restore_keys(table: &mut toml::Table, file: &toml::Table, keys: &[(Vec<String>, toml::Value)]) {
+    for (key, value) in keys {
+        if get_key(table, key) != Some(value) {
+            continue;
+        }
+        match get_key(file, key) {
+            Some(original) => set_key(table, key, original.clone()),
+            None => remove_key(table, key),
+        }
+    }
+}
+
+/// Layout of settings.toml, keeping the sections of the old settings.ini:
+/// the top-level `Config` fields live in `[station]`, `[filters]` and
+/// `[display]`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+struct ConfigFile {
+    /// Layout version, for `migrate` (always written as the current one)
+    version: u32,
+    station: StationSection,
+    filters: FilterSection,
+    /// `[display]`, or `[[display]]` entries for several
+    #[serde(with = "one_or_many")]
+    display: Vec<DisplaySection>,
+    /// `[radio]`, or `[[radio]]` entries for several
+    #[serde(with = "one_or_many")]
+    radio: Vec<RadioConfig>,
+    feed: FeedConfig,
+    web: WebConfig,
+    grpc: GrpcConfig,
+    mqtt: MqttConfig,
+    cloudlog: CloudlogConfig,
+    qrz: CallbookConfig,
+    hamqth: CallbookConfig,
+    lotw: LotwConfig,
+    clublog: ClubLogConfig,
+    n1mm: N1mmConfig,
+    logger: LoggerConfig,
+    wsjtx: WsjtxConfig,
+    telnet: TelnetConfig,
+    input: InputConfig,
+    alerts: AlertConfig,
+    telegram: TelegramConfig,
+    ntfy: NtfyConfig,
+    gotify: GotifyConfig,
+    digest: DigestConfig,
+    aprs: AprsConfig,
+    influx: InfluxConfig,
+    dump: DumpConfig,
+    activation: ActivationConfig,
+    leds: LedConfig,
+    sound: SoundConfig,
+    logging: LoggingConfig,
+    units: UnitsSection,
+    profile: BTreeMap<String, Profile>,
+    /// `[[preset]]` entries
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    preset: Vec<FilterPreset>,
+}
+
+impl Default for ConfigFile {
+    fn default() -> Self {
+        Self::from(&Config::default())
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct StationSection {
+    callsign: String,
+    grid: String,
+    cq_zone: u8,
+    itu_zone: u8,
+    /// IARU region 1-3 (0 = not set)
+    region: u8,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+struct FilterSection {
+    min_snr: i32,
+    max_age_minutes: u32,
+    /// Band name or "all"
+    band: String,
+    cw_only: bool,
+    /// "frequency", "recency" or "snr"
+    sort: String,
+    keep_adjustments: bool,
+    scroll_interval_seconds: u32,
+    /// Active `[profile.<name>]` ("" for none)
+    profile: String,
+    /// Active `[[preset]]` ("" for none)
+    preset: String,
+    /// `[filters.<band>]` sections
+    #[serde(flatten)]
+    bands: BTreeMap<Band, BandFilter>,
+}
+
+impl Default for FilterSection {
+    fn default() -> Self {
+        ConfigFile::default().filters
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+struct DisplaySection {
+    serial_port: String,
+    #[serde(with = "blank_none")]
+    usb_id: Option<UsbId>,
+    random_char_percent: u32,
+    #[serde(flatten)]
+    settings: DisplayConfig,
+}
+
+impl Default for DisplaySection {
+    fn default() -> Self {
+        ConfigFile::default().display.remove(0)
+    }
+}
+
+/// `[units]`, by name; `Config` holds them as `Units`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+struct UnitsSection {
+    /// "km" or "mi"
+    distance: String,
+    /// "khz" or "mhz"
+    frequency: String,
+    /// Decimal places of frequencies ("" for the unit's usual 1 or 3)
+    #[serde(with = "blank_none")]
+    frequency_decimals: Option<u8>,
+    /// "utc" or "local"
+    clock: String,
+    /// IANA zone of local time, e.g. "Europe/Berlin" ("" for the system's)
+    timezone: String,
+    /// Clock view lines: "utc", "local" or "both"
+    clocks: String,
+    /// 24 or 12
+    hours: u8,
+    /// "iso", "dmy" or "mdy"
+    date: String,
+}
+
+impl Default for UnitsSection {
+    fn default() -> Self {
+        ConfigFile::default().units
+    }
+}
+
+impl From<&Units> for UnitsSection {
+    fn from(units: &Units) -> Self {
+        Self {
+            distance: units.distance.name().to_string(),
+            frequency: units.frequency.name().to_string(),
+            // Left blank at the unit's usual places so changing unit changes them too
+            frequency_decimals: (units.decimals != units.frequency.default_decimals())
+                .then_some(units.decimals as u8),
+            clock: if units.local_time { "local" } else { "utc" }.to_string(),
+            timezone: units
+                .timezone
+                .map(|zone| zone.name().to_string())
+                .unwrap_or_default(),
+            clocks: units.clocks.name().to_string(),
+            hours: if units.twelve_hour { 12 } else { 24 },
+            date: units.date.name().to_string(),
+        }
+    }
+}
+
+impl From<&UnitsSection> for Units {
+    /// Unknown names (reported by `validate`) keep the defaults
+    fn from(section: &UnitsSection) -> Self {
+        let frequency = FrequencyUnit::from_name(&section.frequency).unwrap_or_default();
+        Self {
+            distance: DistanceUnit::from_name(&section.distance).unwrap_or_default(),
+            frequency,
+            decimals: section
+                .frequency_decimals
+                .map_or(frequency.default_decimals(), usize::from),
+            local_time: section.clock.trim().eq_ignore_ascii_case("local"),
+            timezone: parse_timezone(&section.timezone),
+            clocks: Clocks::from_name(&section.clocks).unwrap_or_default(),
+            twelve_hour: section.hours == 12,
+            date: DateStyle::from_name(&section.date).unwrap_or_default(),
+        }
+    }
+}
+
+impl From<&Config> for ConfigFile {
+    fn from(config: &Config) -> Self {
+        // Store the main settings, not the active profile's
+        let mut config = config.clone();
+        if let Some(base) = config.base_settings.take() {
+            base.apply(&mut config);
+        }
+        Self {
+            version: migrate::VERSION,
+            station: StationSection {
+                callsign: config.callsign.clone(),
+                grid: config.grid.clone(),
+                cq_zone: config.cq_zone,
+                itu_zone: config.itu_zone,
+                region: config.region.map_or(0, Region::number),
+            },
+            filters: FilterSection {
+                min_snr: config.min_snr,
+                max_age_minutes: config.max_age_minutes,
+                band: config
+                    .band_filter
+                    .map_or("all", |band| band.name())
+                    .to_string(),
+                cw_only: config.cw_only,
+                sort: config.sort.name().to_string(),
+                keep_adjustments: config.keep_adjustments,
+                scroll_interval_seconds: config.scroll_interval_seconds,
+                profile: config.active_profile.clone(),
+                preset: config
+                    .preset()
+                    .map(|preset| preset.name.clone())
+                    .unwrap_or_default(),
+                bands: config.band_filters.clone(),
+            },
+            display: std::iter::once(DisplaySection {
+                serial_port: config.serial_port.clone(),
+                usb_id: config.display_usb_id,
+                random_char_percent: config.random_char_percent,
+                settings: config.display.clone(),
+            })
+            .chain(config.extra_displays.iter().map(|extra| DisplaySection {
+                serial_port: extra.serial_port.clone(),
+                usb_id: extra.usb_id,
+                random_char_percent: config.random_char_percent,
+                settings: extra.settings.clone(),
+            }))
+            .collect(),
+            radio: {
+                let mut radios = config.radios.clone();
+                match radios.get_mut(config.active_radio) {
+                    Some(radio) => *radio = config.radio.clone(),
+                    None => radios = vec![config.radio.clone()],
+                }
+                radios
+            },
+            feed: config.feed.clone(),
+            web: config.web.clone(),
+            grpc: config.grpc.clone(),
+            mqtt: config.mqtt.clone(),
+            cloudlog: config.cloudlog.clone(),
+            qrz: config.qrz.clone(),
+            hamqth: config.hamqth.clone(),
+            lotw: config.lotw.clone(),
+            clublog: config.clublog.clone(),
+            n1mm: config.n1mm.clone(),
+            logger: config.logger.clone(),
+            wsjtx: config.wsjtx.clone(),
+            telnet: config.telnet.clone(),
+            input: config.input.clone(),
+            alerts: config.alerts.clone(),
+            telegram: config.telegram.clone(),
+            ntfy: config.ntfy.clone(),
+            gotify: config.gotify.clone(),
+            digest: config.digest.clone(),
+            aprs: config.aprs.clone(),
+            influx: config.influx.clone(),
+            dump: config.dump.clone(),
+            activation: config.activation.clone(),
+            leds: config.leds.clone(),
+            sound: config.sound.clone(),
+            logging: config.logging.clone(),
+            units: UnitsSection::from(&config.units),
+            profile: config.profiles.clone(),
+            preset: config.presets.clone(),
+        }
+    }
+}
+
+impl From<ConfigFile> for Config {
+    fn from(file: ConfigFile) -> Self {
+        let profile = file.filters.profile;
+        let preset = file.filters.preset;
+        let radios = match file.radio {
+            radios if radios.is_empty() => vec![RadioConfig::default()],
+            radios => radios,
+        };
+        let active_radio = radios.iter().position(|radio| radio.default).unwrap_or(0);
+        let mut displays = file.display.into_iter();
+        let display = displays.next().unwrap_or_default();
+        let extra_displays = displays
+            .map(|entry| ExtraDisplay {
+                serial_port: entry.serial_port,
+                usb_id: entry.usb_id,
+                settings: entry.settings,
+            })
+            .collect();
+        let mut config = Self {
+            callsign: file.station.callsign.trim().to_uppercase(),
+            grid: file.station.grid,
+            cq_zone: file.station.cq_zone,
+            itu_zone: file.station.itu_zone,
+            region: Region::from_number(file.station.region),
+            serial_port: display.serial_port,
+            display_usb_id: display.usb_id,
+            min_snr: file.filters.min_snr,
+            max_age_minutes: file.filters.max_age_minutes,
+            band_filter: Band::from_name(&file.filters.band),
+            cw_only: file.filters.cw_only,
+            sort: SpotSort::from_name(&file.filters.sort).unwrap_or_default(),
+            keep_adjustments: file.filters.keep_adjustments,
+            scroll_interval_seconds: file.filters.scroll_interval_seconds,
+            random_char_percent: display.random_char_percent,
+            display: display.settings,
+            extra_displays,
+            radio: radios[active_radio].clone(),
+            radios,
+            active_radio,
+            feed: file.feed,
+            web: file.web,
+            grpc: file.grpc,
+            mqtt: file.mqtt,
+            cloudlog: file.cloudlog,
+            qrz: file.qrz,
+            hamqth: file.hamqth,
+            lotw: file.lotw,
+            clublog: file.clublog,
+            n1mm: file.n1mm,
+            logger: file.logger,
+            wsjtx: file.wsjtx,
+            telnet: file.telnet,
+            input: file.input,
+            alerts: file.alerts,
+            telegram: file.telegram,
+            ntfy: file.ntfy,
+            gotify: file.gotify,
+            digest: file.digest,
+            aprs: file.aprs,
+            influx: file.influx,
+            dump: file.dump,
+            activation: file.activation,
+            leds: file.leds,
+            sound: file.sound,
+            logging: file.logging,
+            units: Units::from(&file.units),
+            profiles: file.profile,
+            band_filters: file.filters.bands,
+            active_profile: String::new(),
+            base_settings: None,
+            presets: file.preset,
+            active_preset: None,
+            replay_file: None,
+            issues: Vec::new(),
+        };
+        if let Err(e) = config.select_profile(&profile) {
+            eprintln!("{}", e);
+        }
+        // An unknown name is reported by `validate`
+        let _ = config.select_preset(&preset);
+        config
+    }
+}
+
+/// A list written as a plain table when it has one entry (`[radio]`) and
+/// as an array of tables otherwise (`[[radio]]`); either form reads
+mod one_or_many {
+    use serde::de::{DeserializeOwned, Error};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<T: Serialize, S: Serializer>(
+        items: &[T],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match items {
+            [one] => one.serialize(serializer),
+            items => items.serialize(serializer),
+        }
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        T: DeserializeOwned,
+        D: Deserializer<'de>,
+    {
+        match toml::Value::deserialize(deserializer)? {
+            toml::Value::Array(items) => items
+                .into_iter()
+                .map(|item| item.try_into().map_err(D::Error::custom))
+                .collect(),
+            one => one
+                .try_into()
+                .map(|one| vec![one])
+                .map_err(D::Error::custom),
+        }
+    }
+}
+
+/// Optional settings written as "" when unset, so one that defaults to a
+/// value (like `reset_pin`) can still be switched off
+mod blank_none {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::str::FromStr;
+
+    pub fn serialize<T: Serialize, S: Serializer>(
+        value: &Option<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => value.serialize(serializer),
+            None => serializer.serialize_str(""),
+        }
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        T: Deserialize<'de> + FromStr,
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Value<T> {
+            Set(T),
+            Text(String),
+        }
+        Ok(match Value::<T>::deserialize(deserializer)? {
+            Value::Set(value) => Some(value),
+            Value::Text(text) => text.trim().parse().ok(),
+        })
+    }
+}
@@ -0,0 +1,977 @@
+//! Commented example settings file (`generate-config`)
+//!
+//! Keys and values come from serializing the defaults, so the sample always
+//! matches this build; `COMMENTS` only adds the explanation above each key.
+
+use super::ConfigFile;
+use crate::services::display;
+use crate::services::radio;
+use std::fmt::Write;
+
+/// Wrap comments at this width
+const WIDTH: usize = 78;
+
+/// Explanation for each settings.toml key
+const COMMENTS: &[(&str, &str)] = &[
+    (
+        "version",
+        "Settings layout version; older files are upgraded on start, keeping a copy",
+    ),
+    ("station", "Who and where you are"),
+    (
+        "station.callsign",
+        "Your callsign, to log in to RBN (required) and for self-spot alerts",
+    ),
+    (
+        "station.grid",
+        "Maidenhead locator, for distance and bearing to spotted stations",
+    ),
+    ("station.cq_zone", "Your CQ zone, 1-40 (0 = not set)"),
+    ("station.itu_zone", "Your ITU zone, 1-90 (0 = not set)"),
+    (
+        "station.region",
+        "IARU region 1-3, choosing the band plan edges of the band map (0 = widest edges)",
+    ),
+    ("filters", "Which spots are shown"),
+    ("filters.min_snr", "Minimum SNR in dB (0-50)"),
+    ("filters.max_age_minutes", "Oldest spot shown, in minutes"),
+    ("filters.band", "Band name such as \"20m\", or \"all\""),
+    ("filters.cw_only", "Only show CW spots"),
+    (
+        "filters.sort",
+        "Order of the spot lists: \"frequency\", \"recency\" (newest first) or \"snr\" (strongest first)",
+    ),
+    (
+        "filters.keep_adjustments",
+        "Keep filter, profile and preset changes made while running in state.toml next to this file, so they survive a restart (this file keeps its values)",
+    ),
+    (
+        "filters.scroll_interval_seconds",
+        "Seconds between scroll steps when more spots than lines",
+    ),
+    (
+        "filters.profile",
+        "Active [profile.<name>] (\"\" for the settings in this file)",
+    ),
+    (
+        "filters.preset",
+        "Active [[preset]] by name (\"\" for the filters alone)",
+    ),
+    ("display", "The spot display"),
+    (
+        "display.serial_port",
+        "Serial port of a VFD or serial LCD, e.g. \"/dev/ttyUSB0\" or \"COM3\"",
+    ),
+    (
+        "display.usb_id",
+        "USB VID:PID of the display's adapter (e.g. \"0403:6001\"), used to find it when its port changes",
+    ),
+    (
+        "display.random_char_percent",
+        "Percentage chance (0-100) to show a random character when idle",
+    ),
+    ("display.baud_rate", "Serial baud rate (0 = the driver's default)"),
+    ("display.columns", "Text grid columns"),
+    ("display.rows", "Text grid rows"),
+    (
+        "display.brightness",
+        "Brightness percentage applied on open, where the driver supports it",
+    ),
+    (
+        "display.activity_output",
+        "Display GPO switched on while spots are shown (0 = unused)",
+    ),
+    ("display.i2c_bus", "I2C bus number for I2C displays"),
+    ("display.i2c_address", "I2C device address for I2C displays"),
+    (
+        "display.gpio_pins",
+        "GPIO pins for directly wired displays, \"rs,e,d4,d5,d6,d7\"",
+    ),
+    ("display.spi_bus", "SPI device /dev/spidevB.C for SPI displays: B"),
+    ("display.spi_chip_select", "SPI device /dev/spidevB.C: C"),
+    ("display.spi_speed_hz", "SPI clock in Hz (0 = the spidev default)"),
+    (
+        "display.dc_pin",
+        "Data/command GPIO of SPI displays (25 on the Waveshare e-paper HAT)",
+    ),
+    (
+        "display.reset_pin",
+        "Reset GPIO of SPI displays (17 on the Waveshare e-paper HAT, \"\" = none)",
+    ),
+    (
+        "display.busy_pin",
+        "Busy GPIO of e-paper panels (24 on the Waveshare e-paper HAT)",
+    ),
+    (
+        "display.backlight_pin",
+        "Backlight enable pin for TFT panels (13 on the Display HAT Mini, \"\" = none)",
+    ),
+    (
+        "display.pixel_height",
+        "Panel height in pixels for graphic displays (32 or 64)",
+    ),
+    (
+        "display.layout",
+        "Spot layout: \"list\" (one spot per line), \"icons\" (list with band/mode glyphs), \"compact\" (one spot per page) or \"template\"",
+    ),
+    (
+        "display.template",
+        "Line template for the template layout, e.g. \"{mhz:>6} {wpm:>2} {call}\" (| separates the lines of a one-spot-per-page template, a line starting with ^ is double size, and {utc}, {local}, {session} and {received} show the clock and session counters)",
+    ),
+    (
+        "display.large_highlight",
+        "Show the selected spot at double size on displays that can",
+    ),
+    (
+        "display.list_mode",
+        "Lists longer than the display: \"scroll\" (one line per interval) or \"page\" (a screenful per interval)",
+    ),
+    (
+        "display.full_refresh_minutes",
+        "Minutes between full redraws on e-paper panels",
+    ),
+    (
+        "display.fb_device",
+        "Framebuffer device for the \"framebuffer\" driver",
+    ),
+    (
+        "display.remote_address",
+        "\"host[:port]\" of the rbn-vfd-displayd for the \"remote\" driver",
+    ),
+    (
+        "display.marquee_speed",
+        "Marquee speed for fields too long for their line, in characters per second (0 truncates instead)",
+    ),
+    (
+        "display.marquee_pause_ms",
+        "How long a marquee holds at each end, in milliseconds",
+    ),
+    (
+        "display.views",
+        "Views to rotate through: \"spots\", \"bands\", \"stats\", \"watch\" (watch-list spots), \"ladder\", \"clock\", \"cluster\" (WWV lines and announcements), \"prop\" (solar flux and A/K indices)",
+    ),
+    (
+        "display.attention",
+        "Alert kinds that flash the display: \"self\", \"watch\", \"dxcc\", \"slot\", \"rule\"",
+    ),
+    (
+        "display.footer",
+        "Status footer on the last row: \"feed\", \"spots\", \"filters\", \"radio\", \"prop\", \"clock\" (UTC/local time), \"session\" (session length and spots received) (\"\" for none)",
+    ),
+    (
+        "display.view_seconds",
+        "Seconds each view stays up before rotating to the next",
+    ),
+    (
+        "display.brightness_schedule",
+        "Time-of-day brightness as \"HH:MM=percent\" entries, e.g. \"07:00=100,22:00=20\" (\"\" uses brightness all day)",
+    ),
+    (
+        "display.off_hours",
+        "Local time range the display is off, e.g. \"01:00-06:00\" (\"\" keeps it on)",
+    ),
+    (
+        "display.wake_minutes",
+        "Minutes a button press or watch-list hit keeps the display on during off_hours",
+    ),
+    (
+        "display.light_sensor_bus",
+        "I2C bus of the light sensor (\"\" = i2c_bus)",
+    ),
+    (
+        "display.light_sensor_address",
+        "I2C address of a BH1750 light sensor for ambient dimming (0 = none, usually 0x23)",
+    ),
+    (
+        "display.charset_map",
+        "Character substitutions as \"from=to\" entries, e.g. \"Ä=AE,Ö=OE\"",
+    ),
+    (
+        "radio",
+        "CAT control: clicking a spot tunes the radio. For several radios, write [[radio]] entries instead (see the end of the file)",
+    ),
+    (
+        "radio.name",
+        "Name to pick the radio by (needed with several [[radio]] entries)",
+    ),
+    (
+        "radio.default",
+        "Use this radio on start (else the first [[radio]] entry)",
+    ),
+    ("radio.enabled", "Tune the radio to selected spots"),
+    ("radio.rigctld_host", "Hamlib rigctld address"),
+    ("radio.rigctld_port", "Hamlib rigctld port"),
+    ("radio.omnirig_rig", "OmniRig rig number (1 or 2, Windows)"),
+    (
+        "radio.band_power",
+        "TX power percent applied after tuning onto a band, e.g. 20m = 50",
+    ),
+    (
+        "radio.cw_filter_hz",
+        "CW filter width in Hz applied after tuning (0 = leave the rig's setting)",
+    ),
+    ("radio.ssb_filter_hz", "SSB filter width in Hz (0 = leave it)"),
+    ("radio.data_filter_hz", "Data filter width in Hz (0 = leave it)"),
+    (
+        "radio.cat_usb_id",
+        "USB VID:PID of the CAT adapter, watched for unplug/replug",
+    ),
+    (
+        "radio.calibration_offset_hz",
+        "Hz the rig reads high (positive) or low (negative), added when tuning",
+    ),
+    (
+        "radio.transverter_offset_khz",
+        "Transverter local oscillator in kHz, subtracted when tuning, e.g. 116000 for 2 m on a 10 m rig (0 = none)",
+    ),
+    (
+        "radio.manage_rigctld",
+        "Launch and supervise rigctld from the app",
+    ),
+    ("radio.rigctld_path", "rigctld executable"),
+    ("radio.rigctld_model", "Hamlib rig model number (rigctld -l)"),
+    (
+        "radio.rigctld_device",
+        "Rig serial device (\"\" = use cat_usb_id, if set)",
+    ),
+    ("radio.rigctld_args", "Extra rigctld arguments, e.g. \"-s 38400\""),
+    (
+        "radio.pre_tune_command",
+        "Shell command run before each tune (\"\" = none)",
+    ),
+    (
+        "radio.post_tune_command",
+        "Shell command run after each tune (\"\" = none)",
+    ),
+    (
+        "radio.pre_tune_cat",
+        "Raw CAT commands sent before each tune, ';'-separated",
+    ),
+    ("radio.post_tune_cat", "Raw CAT commands sent after each tune"),
+    (
+        "radio.smooth_qsy",
+        "Step the VFO toward the target instead of jumping",
+    ),
+    ("radio.smooth_qsy_step_hz", "Smooth QSY step size in Hz"),
+    ("radio.smooth_qsy_delay_ms", "Pause between smooth QSY steps"),
+    (
+        "radio.use_sub_receiver",
+        "Send spots to the sub receiver instead of the main VFO",
+    ),
+    ("feed", "Where spots come from"),
+    (
+        "feed.server",
+        "Telnet \"host[:port]\" of a DX cluster node passing on skimmer spots (\"\" = RBN)",
+    ),
+    ("web", "Band map dashboard in a browser"),
+    ("web.enabled", "Serve the dashboard"),
+    (
+        "web.bind",
+        "Address to listen on (\"0.0.0.0\" to allow phones on the LAN)",
+    ),
+    ("web.port", "Port to listen on"),
+    (
+        "web.api_token",
+        "Bearer token for the REST API under /api (\"\" = no API)",
+    ),
+    (
+        "grpc",
+        "gRPC spot stream and control service (needs the grpc feature)",
+    ),
+    ("grpc.enabled", "Serve it"),
+    (
+        "grpc.bind",
+        "Address to listen on (\"0.0.0.0\" for other machines)",
+    ),
+    ("grpc.port", "Port to listen on"),
+    (
+        "grpc.token",
+        "Bearer token clients send as authorization metadata (\"\" = none)",
+    ),
+    ("mqtt", "Publish spots and display frames to an MQTT broker"),
+    ("mqtt.enabled", "Connect to the broker"),
+    ("mqtt.host", "Broker host"),
+    ("mqtt.port", "Broker port"),
+    ("mqtt.client_id", "MQTT client ID"),
+    ("mqtt.username", "Broker login (\"\" for none)"),
+    (
+        "mqtt.password",
+        "Broker password",
+    ),
+    (
+        "mqtt.topic_prefix",
+        "Topics are <prefix>/spots, /display, /radio, /app and /status unless set below",
+    ),
+    ("mqtt.spots_topic", "Each spot as JSON (\"\" = <prefix>/spots)"),
+    (
+        "mqtt.display_topic",
+        "Lines on the display, retained (\"\" = <prefix>/display)",
+    ),
+    (
+        "mqtt.radio_topic",
+        "Radio frequency and mode, retained (\"\" = <prefix>/radio)",
+    ),
+    (
+        "mqtt.app_topic",
+        "Feed connection, profile and filters, retained (\"\" = <prefix>/app)",
+    ),
+    (
+        "mqtt.activity_topic",
+        "Spots per minute and per band every 30 seconds, retained (\"\" = <prefix>/activity)",
+    ),
+    (
+        "mqtt.notify_topic",
+        "Messages to show like cluster announcements (\"\" = <prefix>/notify)",
+    ),
+    (
+        "mqtt.status_topic",
+        "\"online\", or \"offline\" from the broker once the connection drops (\"\" = <prefix>/status)",
+    ),
+    ("mqtt.spot_qos", "QoS for spots: 0, 1 or 2"),
+    (
+        "mqtt.state_qos",
+        "QoS for the retained display, radio, app and status messages",
+    ),
+    ("mqtt.tls", "Connect with TLS (usually port 8883)"),
+    (
+        "mqtt.ca_file",
+        "PEM CA certificate for the broker (\"\" = the system's roots)",
+    ),
+    (
+        "mqtt.discovery",
+        "Add sensors and a notify target to Home Assistant through MQTT discovery",
+    ),
+    ("mqtt.discovery_prefix", "Home Assistant's discovery prefix"),
+    (
+        "cloudlog",
+        "Cloudlog or Wavelog logbook for worked-before lookups and QSY reports",
+    ),
+    ("cloudlog.enabled", "Look up spotted stations in the logbook"),
+    (
+        "cloudlog.url",
+        "API base URL, e.g. \"https://log.example.com/index.php\"",
+    ),
+    (
+        "cloudlog.api_key",
+        "Read/write API key",
+    ),
+    (
+        "cloudlog.confirmed",
+        "Count only confirmed QSOs as worked (Wavelog only)",
+    ),
+    (
+        "cloudlog.radio",
+        "Radio name to report tuned spots under, so the logbook follows the rig (\"\" = no reports)",
+    ),
+    ("qrz", "QRZ.com lookups for the spot detail page"),
+    (
+        "qrz.enabled",
+        "Show the operator's name, grid and country from QRZ",
+    ),
+    ("qrz.username", "QRZ login"),
+    (
+        "qrz.password",
+        "QRZ password",
+    ),
+    (
+        "hamqth",
+        "HamQTH lookups for the spot detail page (free; after QRZ when both are on)",
+    ),
+    (
+        "hamqth.enabled",
+        "Show the operator's name, grid and country from HamQTH",
+    ),
+    ("hamqth.username", "HamQTH login"),
+    (
+        "hamqth.password",
+        "HamQTH password",
+    ),
+    (
+        "lotw",
+        "ARRL's LoTW users list, showing a spotted station's last upload on the detail page",
+    ),
+    (
+        "lotw.enabled",
+        "Download the list and keep it in the cache directory",
+    ),
+    (
+        "lotw.refresh_hours",
+        "Download again once the cached list is this old (0 = keep the cached copy)",
+    ),
+    (
+        "clublog",
+        "Club Log most-wanted ranking for the detail page, TUI and rules, and your Club Log log as worked QSOs",
+    ),
+    (
+        "clublog.enabled",
+        "Download the ranking and keep it in the cache directory",
+    ),
+    (
+        "clublog.refresh_hours",
+        "Download again once the cached copies are this old (0 = keep the cached copies)",
+    ),
+    ("clublog.callsign", "Log to fetch (\"\" = [station] callsign)"),
+    (
+        "clublog.email",
+        "Club Log login; without one only the ranking is fetched",
+    ),
+    (
+        "clublog.password",
+        "Club Log password",
+    ),
+    (
+        "clublog.api_key",
+        "API key from the Club Log helpdesk, needed for the log",
+    ),
+    (
+        "n1mm",
+        "Filtered spots sent to contest loggers (N1MM+, DXLog) as N1MM+ UDP spot packets",
+    ),
+    ("n1mm.enabled", "Fill the loggers' band maps from this feed"),
+    (
+        "n1mm.address",
+        "\"host:port\" to send to (\"255.255.255.255:12060\" = every logger on the LAN)",
+    ),
+    (
+        "logger",
+        "Logging program (Log4OM or DXKeeper) that a selected spot's call is sent to for lookup",
+    ),
+    ("logger.enabled", "Send it when a spot is selected"),
+    ("logger.program", "\"log4om\" or \"dxkeeper\""),
+    (
+        "logger.address",
+        "\"host:port\" it listens on (\"\" = 127.0.0.1:2241 for Log4OM, 127.0.0.1:52001 for DXKeeper)",
+    ),
+    (
+        "wsjtx",
+        "Watched and needed stations heard on FT8/FT4 highlighted in WSJT-X's band activity window",
+    ),
+    (
+        "wsjtx.enabled",
+        "Send WSJT-X HighlightCallsign requests for them",
+    ),
+    (
+        "wsjtx.address",
+        "\"host:port\" to listen on; set WSJT-X's Reporting > UDP Server to the same",
+    ),
+    ("wsjtx.watch_color", "Background for watch-list calls (\"#rrggbb\")"),
+    (
+        "wsjtx.needed_color",
+        "Background for new DXCC entities or slots (\"#rrggbb\")",
+    ),
+    (
+        "telnet",
+        "Cluster server re-sending the filtered spots as \"DX de\" lines to loggers and band maps",
+    ),
+    ("telnet.enabled", "Accept telnet clients"),
+    (
+        "telnet.bind",
+        "Address to listen on (\"0.0.0.0\" = the whole LAN, \"127.0.0.1\" = this machine)",
+    ),
+    ("telnet.port", "Port to listen on"),
+    ("input", "Front-panel encoder and buttons"),
+    ("input.encoder", "Rotary encoder source: \"none\", \"evdev\" or \"gpio\""),
+    (
+        "input.evdev_devices",
+        "Comma separated /dev/input event devices for the encoder and its button",
+    ),
+    (
+        "input.encoder_pins",
+        "GPIO encoder pins as \"a,b,button\" (button optional)",
+    ),
+    (
+        "input.buttons",
+        "GPIO buttons (active low) as \"pin=action,...\", e.g. \"5=band_up,6=band_down\"; actions are band_up, band_down, cw_toggle, snr_up, snr_down, next_view, page_up, page_down, next_profile, next_radio, next_preset, next_sort, self_spot",
+    ),
+    (
+        "input.keys",
+        "Keys on evdev_devices as \"code=action,...\" with Linux key codes, e.g. \"59=snr_up\" (F1)",
+    ),
+    (
+        "input.keypad",
+        "Matrix Orbital keypad keys as \"key=action,...\", e.g. \"A=cw_toggle\"; unbound keys step the display view",
+    ),
+    ("alerts", "Spots worth noticing"),
+    (
+        "alerts.watch_list",
+        "Comma separated callsigns to alert on; * matches any characters",
+    ),
+    (
+        "alerts.cty_file",
+        "Country file (cty.dat) for DXCC entities (\"\" = cty.dat in the cache directory, see --print-paths)",
+    ),
+    (
+        "alerts.worked_dxcc",
+        "Comma separated prefixes of worked entities, e.g. \"K,VE,DL\"",
+    ),
+    (
+        "alerts.worked_slots",
+        "Worked \"entity/band\" slots, e.g. \"DL/20m,DL/40m\" (\"\" disables new-slot alerts)",
+    ),
+    (
+        "alerts.adif_file",
+        "Logger's ADIF file; QSOs in it, including ones logged while running, count as worked (\"\" = none)",
+    ),
+    (
+        "alerts.notify_limit",
+        "Most webhooks, pushes and Telegram messages rules send per hour (0 = no limit)",
+    ),
+    ("telegram", "Telegram bot for alert rules with telegram = true"),
+    (
+        "telegram.bot_token",
+        "Bot token from @BotFather",
+    ),
+    (
+        "telegram.chat_id",
+        "Chat to message: your user ID or a group's negative ID",
+    ),
+    ("ntfy", "ntfy server access for alert rule push URLs"),
+    (
+        "ntfy.token",
+        "Access token for protected topics (\"\" = none)",
+    ),
+    (
+        "digest",
+        "Email digest of watch-list and new-entity spots (needs the email feature)",
+    ),
+    ("digest.enabled", "Send it"),
+    ("digest.period", "\"daily\" or \"weekly\""),
+    ("digest.send_at", "Time it goes out, \"HH:MM\" in [units] timezone"),
+    ("digest.weekday", "Day a weekly digest goes out, e.g. \"mon\""),
+    (
+        "digest.alerts",
+        "Comma separated alert kinds it lists: watch, dxcc, slot",
+    ),
+    ("digest.send_empty", "Send one even when nothing was heard"),
+    ("digest.smtp_host", "Mail server, e.g. \"smtp.gmail.com\""),
+    ("digest.smtp_port", "Mail server port (587 for starttls, 465 for tls)"),
+    ("digest.security", "\"starttls\", \"tls\" or \"none\""),
+    ("digest.username", "SMTP login (\"\" = none)"),
+    (
+        "digest.password",
+        "SMTP password",
+    ),
+    ("digest.from", "Sender, e.g. \"RBN VFD <pi@example.com>\""),
+    ("digest.to", "Comma separated recipients"),
+    ("aprs", "Alerting spots sent to APRS-IS, for aprs.fi and APRS radios"),
+    ("aprs.enabled", "Send them"),
+    ("aprs.server", "APRS-IS server, \"host:port\""),
+    (
+        "aprs.callsign",
+        "Call-SSID it logs in and sends as, e.g. \"N0CALL-10\" (\"\" = [station] callsign); the passcode is worked out from it",
+    ),
+    (
+        "aprs.alerts",
+        "Comma separated alert kinds it sends: self, watch, dxcc, slot",
+    ),
+    (
+        "aprs.objects",
+        "Put each station on the map as an object at its entity's location",
+    ),
+    (
+        "aprs.message_to",
+        "Call-SSID sent a message about each one, e.g. your HT \"N0CALL-7\" (\"\" = none)",
+    ),
+    (
+        "influx",
+        "Spot counts, SNRs and solar indices written to InfluxDB (or Telegraf) as line protocol",
+    ),
+    ("influx.enabled", "Write them"),
+    (
+        "influx.url",
+        "Write endpoint, e.g. \"http://localhost:8086/api/v2/write?org=home&bucket=rbn\" or \"http://localhost:8086/write?db=rbn\" (\"\" = send over UDP)",
+    ),
+    (
+        "influx.token",
+        "InfluxDB 2 API token (\"\" = none)",
+    ),
+    (
+        "influx.udp_address",
+        "\"host:port\" of a UDP line protocol listener, used when url is \"\"",
+    ),
+    ("influx.prefix", "Start of the measurement names (\"rbn\" gives rbn_snr)"),
+    (
+        "influx.interval_secs",
+        "Seconds between writes, each with the band activity since the last",
+    ),
+    ("influx.spots", "Write each spot's SNR, not only the counts"),
+    (
+        "dump",
+        "Spots on hand and session counts written to JSON or CSV files on a schedule",
+    ),
+    ("dump.enabled", "Write them"),
+    (
+        "dump.schedule",
+        "Cron-style \"minute hour day month weekday\" in [units] timezone, e.g. \"0 * * * *\" hourly or \"*/15 6-22 * * 1-5\"",
+    ),
+    ("dump.format", "\"json\", \"csv\" or \"both\""),
+    (
+        "dump.dir",
+        "Directory the files go in (\"\" = dumps/ in the data directory)",
+    ),
+    ("dump.keep_days", "Days files are kept (0 = keep them all)"),
+    ("gotify", "Gotify server for alert rules with gotify = true"),
+    ("gotify.url", "Server URL, e.g. \"https://gotify.example.com\""),
+    (
+        "gotify.token",
+        "Application token",
+    ),
+    (
+        "activation",
+        "SOTA or POTA activation spotted with the self_spot button",
+    ),
+    ("activation.program", "\"pota\" or \"sota\""),
+    (
+        "activation.reference",
+        "Park or summit, e.g. \"US-1234\" or \"W7W/LC-001\" (\"\" = not activating)",
+    ),
+    ("activation.comment", "Comment sent with the spot"),
+    (
+        "activation.callsign",
+        "Activating callsign (\"\" = [station] callsign)",
+    ),
+    (
+        "activation.sota_token",
+        "SOTAwatch access token for SOTA spots",
+    ),
+    ("leds", "Alert LEDs on GPIO pins (\"\" = none)"),
+    ("leds.connected_pin", "Lit while connected to RBN"),
+    ("leds.watch_pin", "Blinks after a watch-list hit"),
+    ("leds.dxcc_pin", "Blinks fast after a new-DXCC spot"),
+    (
+        "leds.alert_seconds",
+        "How long an alert keeps its LED blinking",
+    ),
+    ("sound", "Alert sounds"),
+    (
+        "sound.output",
+        "\"none\", \"gpio\" (active piezo buzzer) or \"alsa\" (via aplay)",
+    ),
+    ("sound.piezo_pin", "GPIO driving the piezo"),
+    ("sound.alsa_device", "ALSA device for aplay -D"),
+    ("sound.tone_hz", "Tone pitch for the sound card"),
+    (
+        "sound.morse",
+        "Send the alert's callsign in Morse instead of a beep pattern",
+    ),
+    ("sound.morse_wpm", "Morse speed in words per minute"),
+    (
+        "sound.alerts",
+        "Alert kinds that sound: \"self\", \"watch\", \"dxcc\", \"slot\", \"rule\"",
+    ),
+    (
+        "sound.quiet_hours",
+        "Silent period as \"HH:MM-HH:MM\" local time, may wrap midnight",
+    ),
+    ("logging", "Log levels and outputs"),
+    (
+        "logging.level",
+        "off, error, warn, info, debug or trace",
+    ),
+    (
+        "logging.modules",
+        "Levels for some modules as \"module=level,...\", e.g. \"rbn_client=debug,radio=warn\"",
+    ),
+    (
+        "logging.output",
+        "Where log lines go: stderr, file and/or journald, comma separated",
+    ),
+    (
+        "logging.file",
+        "Log file for the file output (\"\" for rbn-vfd.log in the data directory)",
+    ),
+    (
+        "logging.max_file_kb",
+        "Size in kB at which the log file is rotated (0 = never)",
+    ),
+    (
+        "logging.keep_files",
+        "Rotated log files kept as <file>.1, <file>.2, ...",
+    ),
+    (
+        "logging.raw_lines",
+        "Log every line sent to and received from the RBN server",
+    ),
+    (
+        "units",
+        "How the displays and windows show values; JSON, MQTT and web data stay in kHz and UTC",
+    ),
+    ("units.distance", "\"km\" or \"mi\""),
+    ("units.frequency", "\"khz\" or \"mhz\""),
+    (
+        "units.frequency_decimals",
+        "Decimal places of frequencies (\"\" = 1 for kHz, 3 for MHz)",
+    ),
+    ("units.clock", "Times in \"utc\" or \"local\" time"),
+    (
+        "units.timezone",
+        "IANA zone of local time, e.g. \"America/Denver\", daylight saving included (\"\" = the system's)",
+    ),
+    (
+        "units.clocks",
+        "Clocks on the clock view: \"utc\", \"local\" or \"both\"",
+    ),
+    ("units.hours", "24 or 12 (with am/pm)"),
+    (
+        "units.date",
+        "\"iso\" (2024-03-17), \"dmy\" (17/03/2024) or \"mdy\" (03/17/2024)",
+    ),
+];
+
+/// Example include list, commented out
+const INCLUDE_EXAMPLE: &str = "\
+# Settings can be split across files named here, relative to this one and
+# read in order beneath it: sections merge key by key, other values (lists
+# and [[radio]] style entries included) come from the last file setting
+# them, and this file wins. Saving only writes this file, leaving out
+# values that match the included ones.
+#
+# include = [\"hardware.toml\", \"filters.toml\"]
+";
+
+/// Example profile appended after the sections, commented out
+const PROFILE_EXAMPLE: &str = "\
+# Named profiles replace the filters and alert lists when selected with
+# --profile, [filters] profile, the GUI or the next_profile button; keys
+# left out keep the values above.
+#
+# [profile.contest]
+# min_snr = 15
+# band = \"20m\"
+# cw_only = true
+# sort = \"snr\"
+# watch_list = \"\"
+# attention = \"\"
+# sound_alerts = \"\"
+";
+
+/// Example filter presets, commented out
+const PRESETS_EXAMPLE: &str = "\
+# Filter presets stand in for min_snr, max_age_minutes, band and cw_only
+# while one is picked with the GUI, the next_preset button, the TUI 'f'
+# key or the dashboard; keys left out keep the [filters] values.
+# continents and new_only need the country file ([alerts] cty_file).
+#
+# [[preset]]
+# name = \"All CW\"
+# cw_only = true
+#
+# [[preset]]
+# name = \"New ones only\"
+# new_only = true
+#
+# [[preset]]
+# name = \"EU high bands\"
+# bands = \"20m,17m,15m,12m,10m\"
+# continents = \"EU\"
+";
+
+/// Example alert rules, commented out
+const RULES_EXAMPLE: &str = "\
+# Alert rules fire when a spot meets every condition they set: calls
+# (patterns with *), bands, new_dxcc, most_wanted (Club Log's top N),
+# min_snr, min_km/max_km from your grid and hours (HH:MM-HH:MM in [units]
+# timezone). Each then does what it sets:
+# led (\"watch\" or \"dxcc\"), beep, webhook (the spot POSTed as JSON),
+# push (an ntfy topic URL), telegram (a message from the [telegram] bot) or
+# gotify (a message to the [gotify] server), with priority 1-5 for pushes,
+# and flashes the display with \"rule\" in [display] attention. A rule
+# fires once per callsign and band per 30 minutes, and no more than
+# [alerts] notify_limit webhooks, pushes and messages go out per hour.
+#
+# [[alerts.rule]]
+# name = \"Long path JA\"
+# calls = \"JA*,JH*,JR*\"
+# bands = \"40m,30m\"
+# min_snr = 10
+# hours = \"05:00-08:00\"
+# led = \"watch\"
+# beep = true
+#
+# [[alerts.rule]]
+# name = \"New one far away\"
+# new_dxcc = true
+# most_wanted = 100
+# min_km = 8000
+# push = \"https://ntfy.sh/my-rbn-alerts\"
+# priority = 4
+# telegram = true
+";
+
+/// Example of several radios, commented out
+const RADIOS_EXAMPLE: &str = "\
+# Several radios replace [radio] with [[radio]] entries, each taking the
+# keys above; the default one is used on start, and the GUI, the
+# next_radio button or the TUI 'r' key switch between them.
+#
+# [[radio]]
+# name = \"ic7300\"
+# default = true
+# enabled = true
+# rigctld_port = 4532
+#
+# [[radio]]
+# name = \"2m\"
+# enabled = true
+# rigctld_port = 4533
+# transverter_offset_khz = 116000
+";
+
+/// Example of several displays, commented out
+const DISPLAYS_EXAMPLE: &str = "\
+# More displays replace [display] with [[display]] entries. Each takes the
+# shared keys above (driver, columns, brightness and so on) plus only its
+# driver's own keys; others are reported and dropped. The first entry is
+# the main display and the rest show the same frames.
+#
+# [[display]]
+# driver = \"futaba\"
+# serial_port = \"/dev/ttyUSB0\"
+#
+# [[display]]
+# driver = \"ssd1306_i2c\"
+# i2c_address = 60
+# pixel_height = 64
+";
+
+/// Example band filters, commented out
+const BAND_FILTER_EXAMPLE: &str = "\
+# Per-band filters replace min_snr, max_age_minutes and cw_only from
+# [filters] on one band; keys left out keep the [filters] values.
+#
+# [filters.160m]
+# min_snr = 3
+# max_age_minutes = 30
+#
+# [filters.10m]
+# min_snr = 15
+# cw_only = true
+";
+
+/// Every setting at its default, with comments
+pub(super) fn generate() -> Result<String, String> {
+    let table = match toml::Value::try_from(ConfigFile::default())
+        .map_err(|e| format!("Failed to serialize config: {}", e))?
+    {
+        toml::Value::Table(table) => table,
+        _ => return Err("Failed to serialize config".to_string()),
+    };
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "# rbn-vfd {} settings, every key at its default",
+        env!("CARGO_PKG_VERSION")
+    );
+    comment(
+        &mut out,
+        "Keys left out take their defaults. Environment variables such as RBNVFD_STATION__CALLSIGN override the file.",
+    );
+    comment(
+        &mut out,
+        "Passwords, tokens and API keys can be written as \"env:NAME\" to read environment variable NAME, or left out and set in a secrets.toml next to this file.",
+    );
+
+    // Top-level keys (the layout version) come before any section
+    for (key, value) in &table {
+        if !value.is_table() {
+            out.push('\n');
+            if let Some(text) = describe(key) {
+                comment(&mut out, &text);
+            }
+            let _ = writeln!(out, "{} = {}", key, value);
+        }
+    }
+    out.push('\n');
+    out.push_str(INCLUDE_EXAMPLE);
+
+    for (section, value) in &table {
+        let toml::Value::Table(keys) = value else {
+            continue;
+        };
+        // Profiles are empty by default; shown as an example instead
+        if section == "profile" {
+            continue;
+        }
+        out.push('\n');
+        if let Some(text) = lookup(section) {
+            comment(&mut out, text);
+        }
+        let _ = writeln!(out, "[{}]", section);
+        let mut tables = Vec::new();
+        for (key, value) in keys {
+            if let toml::Value::Table(inner) = value {
+                tables.push((key, inner));
+                continue;
+            }
+            let path = format!("{}.{}", section, key);
+            if let Some(text) = describe(&path) {
+                comment(&mut out, &text);
+            }
+            let _ = writeln!(out, "{} = {}", key, value);
+        }
+        for (key, inner) in tables {
+            out.push('\n');
+            if let Some(text) = describe(&format!("{}.{}", section, key)) {
+                comment(&mut out, &text);
+            }
+            let _ = writeln!(out, "[{}.{}]", section, key);
+            for (key, value) in inner {
+                let _ = writeln!(out, "{} = {}", key, value);
+            }
+        }
+    }
+
+    out.push('\n');
+    out.push_str(BAND_FILTER_EXAMPLE);
+    out.push('\n');
+    out.push_str(PRESETS_EXAMPLE);
+    out.push('\n');
+    out.push_str(RULES_EXAMPLE);
+    out.push('\n');
+    out.push_str(RADIOS_EXAMPLE);
+    out.push('\n');
+    out.push_str(DISPLAYS_EXAMPLE);
+    out.push('\n');
+    out.push_str(PROFILE_EXAMPLE);
+    Ok(out)
+}
+
+fn lookup(key: &str) -> Option<&'static str> {
+    COMMENTS
+        .iter()
+        .find(|(name, _)| *name == key)
+        .map(|(_, text)| *text)
+}
+
+/// Comment for a key, listing this build's drivers and backends for the
+/// keys choosing one
+fn describe(key: &str) -> Option<String> {
+    match key {
+        "display.driver" => Some(format!(
+            "Display driver: {}",
+            display::registered_drivers().join(", ")
+        )),
+        "radio.backend" => Some(format!(
+            "Radio backend: {}",
+            radio::registered_backends().join(", ")
+        )),
+        _ => lookup(key).map(str::to_string),
+    }
+}
+
+/// Append `text` as `#` lines wrapped at `WIDTH`
+fn comment(out: &mut String, text: &str) {
+    let mut line = String::from("#");
+    for word in text.split_whitespace() {
+        if line.len() > 1 && line.len() + 1 + word.len() > WIDTH {
+            out.push_str(&line);
+            out.push('\n');
+            line = String::from("#");
+        }
+        line.push(' ');
+        line.push_str(word);
+    }
+    out.push_str(&line);
+    out.push('\n');
+}
@@ -0,0 +1,193 @@
+//! Passwords and API keys kept out of settings.toml
+//!
+//! Any `Secret` setting (those in `KEYS`) can be written as `"env:NAME"` to
+//! read environment variable NAME, or left out of settings.toml and set in
+//! a `secrets.toml` next to it (same sections and keys, readable only by its
+//! owner). The reference is what gets saved, and dumps and `Debug` output
+//! never show the value.
+
+use super::{get_key, Issue};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Prefix of a reference to an environment variable
+const ENV_REFERENCE: &str = "env:";
+
+/// Shown instead of a value in config dumps
+const REDACTED: &str = "<redacted>";
+
+/// Settings keys holding secrets
+pub(super) const KEYS: &[&str] = &[
+    "mqtt.password",
+    "cloudlog.api_key",
+    "qrz.password",
+    "hamqth.password",
+    "clublog.password",
+    "clublog.api_key",
+    "web.api_token",
+    "grpc.token",
+    "telegram.bot_token",
+    "ntfy.token",
+    "gotify.token",
+    "digest.password",
+    "activation.sota_token",
+    "influx.token",
+];
+
+/// A password or key, with how settings.toml gives it
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Secret {
+    /// As written: the value itself or an `env:` reference
+    text: String,
+    /// Value in use ("" when a referenced variable isn't set)
+    value: String,
+}
+
+impl From<String> for Secret {
+    fn from(text: String) -> Self {
+        let value = match text.trim().strip_prefix(ENV_REFERENCE) {
+            Some(name) => std::env::var(name.trim()).unwrap_or_default(),
+            None => text.clone(),
+        };
+        Self { text, value }
+    }
+}
+
+impl Secret {
+    /// The password or key itself, for the service that needs it
+    pub fn expose(&self) -> &str {
+        &self.value
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
+
+    /// Environment variable it refers to, if it's a reference
+    fn variable(&self) -> Option<&str> {
+        self.text.trim().strip_prefix(ENV_REFERENCE).map(str::trim)
+    }
+
+    /// Copy safe to print: references stay, values are hidden
+    pub(super) fn redacted(&self) -> Self {
+        if self.variable().is_some() || self.text.is_empty() {
+            self.clone()
+        } else {
+            Self {
+                text: REDACTED.to_string(),
+                value: String::new(),
+            }
+        }
+    }
+
+    /// Problem with a reference, e.g. an unset variable
+    pub(super) fn problem(&self) -> Option<String> {
+        let name = self.variable()?;
+        if name.is_empty() {
+            Some("\"env:\" needs a variable name".to_string())
+        } else if std::env::var_os(name).is_none() {
+            Some(format!("environment variable {} is not set", name))
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.variable() {
+            Some(name) => write!(f, "Secret(env:{})", name),
+            None if self.text.is_empty() => f.write_str("Secret(\"\")"),
+            None => write!(f, "Secret({})", REDACTED),
+        }
+    }
+}
+
+impl Serialize for Secret {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.text)
+    }
+}
+
+impl<'de> Deserialize<'de> for Secret {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from)
+    }
+}
+
+/// secrets.toml beside a settings file
+/// Compare a token a client sent with the expected one without stopping at
+/// the first difference, so response times don't give it away
+pub fn same_token(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+pub(super) fn file_for(settings: &Path) -> PathBuf {
+    settings.with_file_name("secrets.toml")
+}
+
+/// Secret keys set in secrets.toml, with their values, to lay over the
+/// settings; other keys, and a file others can read, go in `issues`
+pub(super) fn load(path: &Path, issues: &mut Vec<Issue>) -> Vec<(Vec<String>, toml::Value)> {
+    if !path.exists() {
+        return Vec::new();
+    }
+    let table = match super::read_table(path) {
+        Ok(table) => table,
+        Err(e) => {
+            eprintln!("Ignoring {}: {}", path.display(), e);
+            return Vec::new();
+        }
+    };
+    if let Some(message) = readable_by_others(path) {
+        issues.push(Issue {
+            key: path.display().to_string(),
+            line: None,
+            message,
+        });
+    }
+    let mut secrets = Vec::new();
+    for name in KEYS {
+        let key: Vec<String> = name.split('.').map(str::to_string).collect();
+        if let Some(value) = get_key(&table, &key) {
+            secrets.push((key, value.clone()));
+        }
+    }
+    for (section, value) in &table {
+        let keys = value.as_table().into_iter().flat_map(|keys| keys.keys());
+        for key in keys {
+            let name = format!("{}.{}", section, key);
+            if !KEYS.contains(&name.as_str()) {
+                issues.push(Issue {
+                    key: name,
+                    line: None,
+                    message: format!("ignored in {}: not a secret", path.display()),
+                });
+            }
+        }
+    }
+    secrets
+}
+
+#[cfg(unix)]
+fn readable_by_others(path: &Path) -> Option<String> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = std::fs::metadata(path).ok()?.permissions().mode();
+    (mode & 0o077 != 0).then(|| {
+        format!(
+            "readable by other users (mode {:o}); run chmod 600 on it",
+            mode & 0o777
+        )
+    })
+}
+
+#[cfg(not(unix))]
+fn readable_by_others(_path: &Path) -> Option<String> {
+    None
+}
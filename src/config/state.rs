@@ -0,0 +1,66 @@
+//! Runtime adjustments kept across restarts
+//!
+//! With `[filters] keep_adjustments`, filter, profile and preset changes
+//! made while running (buttons, keys, the GUI, web clients) are written to a
+//! `state.toml` next to settings.toml instead of to it, and laid over the
+//! settings on the next start. settings.toml keeps its own values; deleting
+//! state.toml goes back to them.
+
+use super::get_key;
+use std::path::{Path, PathBuf};
+
+/// Settings keys kept in state.toml
+pub(super) const KEYS: &[&str] = &[
+    "filters.min_snr",
+    "filters.max_age_minutes",
+    "filters.band",
+    "filters.cw_only",
+    "filters.sort",
+    "filters.profile",
+    "filters.preset",
+];
+
+/// state.toml beside a settings file
+pub(super) fn file_for(settings: &Path) -> PathBuf {
+    settings.with_file_name("state.toml")
+}
+
+/// Keys set in state.toml, with their values, to lay over the settings
+pub(super) fn load(path: &Path) -> Vec<(Vec<String>, toml::Value)> {
+    if !path.exists() {
+        return Vec::new();
+    }
+    let table = match super::read_table(path) {
+        Ok(table) => table,
+        Err(e) => {
+            eprintln!("Ignoring {}: {}", path.display(), e);
+            return Vec::new();
+        }
+    };
+    KEYS.iter()
+        .filter_map(|name| {
+            let key: Vec<String> = name.split('.').map(str::to_string).collect();
+            let value = get_key(&table, &key)?.clone();
+            Some((key, value))
+        })
+        .collect()
+}
+
+/// Write the `KEYS` values of `settings` to state.toml, except those in
+/// `skip`, which keep what the file had
+pub(super) fn save(path: &Path, settings: &toml::Table, skip: &[&str]) -> Result<(), String> {
+    let mut state = super::read_table(path).unwrap_or_default();
+    for name in KEYS.iter().filter(|name| !skip.contains(name)) {
+        let key: Vec<String> = name.split('.').map(str::to_string).collect();
+        if let Some(value) = get_key(settings, &key) {
+            super::set_key(&mut state, &key, value.clone());
+        }
+    }
+    let text =
+        toml::to_string_pretty(&state).map_err(|e| format!("Failed to encode state: {}", e))?;
+    // Renamed into place so losing power mid-write leaves the old file
+    let partial = path.with_extension("toml.tmp");
+    std::fs::write(&partial, text)
+        .and_then(|_| std::fs::rename(&partial, path))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
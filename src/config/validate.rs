@@ -0,0 +1,1030 @@
+//! Checks on settings that deserialized but can't work as written, each
+//! located by key and, when the file sets it, line
+
+use super::{
+    ActivationConfig, ConfigFile, DigestConfig, DisplaySection, FilterPreset, LoggingConfig,
+    Profile, RadioConfig, UnitsSection,
+};
+use crate::logging::{self, Output};
+use crate::models::{
+    normalize_callsign, parse_timezone, Band, Clocks, DateStyle, DistanceUnit, FrequencyUnit,
+    SpotSort,
+};
+use crate::services::display;
+use crate::services::radio;
+use crate::services::{Cron, DailyRange};
+use chrono::{NaiveTime, Weekday};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::Path;
+
+/// Continent codes used in the country file
+const CONTINENTS: [&str; 7] = ["AF", "AN", "AS", "EU", "NA", "OC", "SA"];
+
+/// Range the SNR controls allow
+const SNR_RANGE: std::ops::RangeInclusive<i32> = 0..=50;
+
+/// A setting that won't do what was meant
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    /// settings.toml key, e.g. "display.driver"
+    pub key: String,
+    /// Line of the key in the settings file, if it's set there
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    /// Example: "line 12: display.driver: unknown driver 'elo2' (elo, ...)"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}: {}", line, self.key, self.message),
+            None => write!(f, "{}: {}", self.key, self.message),
+        }
+    }
+}
+
+/// Problems collected for one file
+struct Checker<'a> {
+    text: &'a str,
+    issues: Vec<Issue>,
+}
+
+impl Checker<'_> {
+    fn report(&mut self, key: &str, message: String) {
+        self.issues.push(Issue {
+            key: key.to_string(),
+            line: key_line(self.text, key),
+            message,
+        });
+    }
+
+    fn band(&mut self, key: &str, name: &str) {
+        let name = name.trim();
+        if !name.is_empty() && !name.eq_ignore_ascii_case("all") && Band::from_name(name).is_none()
+        {
+            let names: Vec<&str> = Band::ALL.iter().map(|band| band.name()).collect();
+            self.report(
+                key,
+                format!("unknown band '{}' ({} or all)", name, names.join(", ")),
+            );
+        }
+    }
+
+    fn sort(&mut self, key: &str, name: &str) {
+        if SpotSort::from_name(name).is_none() {
+            let names: Vec<&str> = SpotSort::ALL.iter().map(|sort| sort.name()).collect();
+            self.report(
+                key,
+                format!("unknown sort '{}' ({})", name.trim(), names.join(", ")),
+            );
+        }
+    }
+
+    fn min_snr(&mut self, key: &str, snr: i32) {
+        if !SNR_RANGE.contains(&snr) {
+            self.report(
+                key,
+                format!(
+                    "{} dB is outside {}-{} dB",
+                    snr,
+                    SNR_RANGE.start(),
+                    SNR_RANGE.end()
+                ),
+            );
+        }
+    }
+
+    fn max_age(&mut self, key: &str, minutes: u32) {
+        if minutes == 0 {
+            self.report(
+                key,
+                "must be at least 1 minute (0 hides every spot)".to_string(),
+            );
+        }
+    }
+
+    fn daily_range(&mut self, key: &str, range: &str) {
+        if !range.trim().is_empty() && DailyRange::parse(range).is_none() {
+            self.report(
+                key,
+                format!("'{}' is not a time range like \"22:00-07:00\"", range),
+            );
+        }
+    }
+
+    fn device(&mut self, key: &str, path: &str, hint: &str) {
+        // COM ports aren't files on Windows
+        if cfg!(target_os = "windows") || path.trim().is_empty() {
+            return;
+        }
+        if !Path::new(path.trim()).exists() {
+            self.report(key, format!("{} not found; {}", path.trim(), hint));
+        }
+    }
+
+    /// "host:port" with a usable port; `example` goes in the hint
+    fn udp_address(&mut self, key: &str, address: &str, example: &str) {
+        match address.trim().rsplit_once(':') {
+            Some((_, port)) if port.parse::<u16>().map_or(true, |port| port == 0) => {
+                self.report(
+                    key,
+                    format!("port '{}' isn't a number from 1 to 65535", port),
+                );
+            }
+            Some(_) => {}
+            None => self.report(key, format!("needs a port, e.g. \"{}\"", example)),
+        }
+    }
+
+    /// One `[display]` or `[[display]]` entry, its keys under `prefix`
+    fn display(&mut self, prefix: &str, entry: &DisplaySection) {
+        let key = |field: &str| format!("{}.{}", prefix, field);
+        let display = &entry.settings;
+        let drivers = display::registered_drivers();
+        if !drivers.contains(&display.driver) {
+            self.report(
+                &key("driver"),
+                format!(
+                    "unknown driver '{}' ({})",
+                    display.driver,
+                    drivers.join(", ")
+                ),
+            );
+        } else if display::uses_serial(&display.driver) {
+            self.device(
+                &key("serial_port"),
+                &entry.serial_port,
+                "plug the display in, pick its port, or set usb_id instead",
+            );
+        }
+        self.daily_range(&key("off_hours"), &display.off_hours);
+
+        // Two devices at one address on the same I2C bus
+        if display.driver.ends_with("_i2c")
+            && display.light_sensor_address != 0
+            && display.light_sensor_bus.unwrap_or(display.i2c_bus) == display.i2c_bus
+            && display.light_sensor_address == display.i2c_address
+        {
+            self.report(
+                &key("light_sensor_address"),
+                format!(
+                    "0x{:02x} on I2C bus {} is the display's address",
+                    display.i2c_address, display.i2c_bus
+                ),
+            );
+        }
+    }
+
+    /// `[radio]`, or each `[[radio]]` entry
+    fn radios(&mut self, radios: &[RadioConfig]) {
+        let several = radios.len() > 1;
+        let backends = radio::registered_backends();
+        let mut names = BTreeSet::new();
+        for (index, radio) in radios.iter().enumerate() {
+            let key = |field: &str| match several {
+                false => format!("radio.{}", field),
+                true => format!("radio.{}.{}", index + 1, field),
+            };
+            if several && radio.name.trim().is_empty() {
+                self.report(
+                    &key("name"),
+                    "needs a name to pick it by when there are several radios".to_string(),
+                );
+            } else if several && !names.insert(radio.name.trim().to_lowercase()) {
+                self.report(&key("name"), format!("'{}' is used twice", radio.name));
+            }
+            if radio.enabled && !backends.contains(&radio.backend) {
+                self.report(
+                    &key("backend"),
+                    format!(
+                        "unknown backend '{}' ({})",
+                        radio.backend,
+                        backends.join(", ")
+                    ),
+                );
+            }
+            if radio.enabled && radio.manage_rigctld {
+                self.device(
+                    &key("rigctld_device"),
+                    &radio.rigctld_device,
+                    "check the rig's serial device, or leave it empty to use cat_usb_id",
+                );
+            }
+        }
+        if radios.iter().filter(|radio| radio.default).count() > 1 {
+            self.report(
+                "radio.default",
+                "more than one radio is the default; the first is used".to_string(),
+            );
+        }
+    }
+
+    /// Each `[[alerts.rule]]` entry, with the `[station] grid` distances
+    /// need and the servers its actions send to
+    fn alert_rules(&mut self, file: &ConfigFile) {
+        let (grid, telegram, gotify) = (&file.station.grid, &file.telegram, &file.gotify);
+        let mut names = BTreeSet::new();
+        for (index, rule) in file.alerts.rules.iter().enumerate() {
+            let key = |field: &str| format!("alerts.rule.{}.{}", index + 1, field);
+            if rule.name.trim().is_empty() {
+                self.report(
+                    &key("name"),
+                    "needs a name to show with its alerts".to_string(),
+                );
+            } else if !names.insert(rule.name.trim().to_lowercase()) {
+                self.report(&key("name"), format!("'{}' is used twice", rule.name));
+            }
+            for band in rule.bands.split(',').filter(|band| !band.trim().is_empty()) {
+                if band.trim().eq_ignore_ascii_case("all") {
+                    self.report(&key("bands"), "leave bands empty for all".to_string());
+                } else {
+                    self.band(&key("bands"), band);
+                }
+            }
+            if let Some(snr) = rule.min_snr {
+                self.min_snr(&key("min_snr"), snr);
+            }
+            if let (Some(min), Some(max)) = (rule.min_km, rule.max_km) {
+                if min > max {
+                    self.report(
+                        &key("max_km"),
+                        format!("{} km is less than min_km ({} km)", max, min),
+                    );
+                }
+            }
+            if (rule.min_km.is_some() || rule.max_km.is_some()) && grid.trim().is_empty() {
+                self.report(
+                    &key(if rule.min_km.is_some() {
+                        "min_km"
+                    } else {
+                        "max_km"
+                    }),
+                    "needs [station] grid to measure from".to_string(),
+                );
+            }
+            self.daily_range(&key("hours"), &rule.hours);
+            let led = rule.led.trim();
+            if !led.is_empty() && !["watch", "dxcc"].contains(&led.to_lowercase().as_str()) {
+                self.report(
+                    &key("led"),
+                    format!("unknown LED '{}' (watch or dxcc)", led),
+                );
+            }
+            for (field, url) in [("webhook", &rule.webhook), ("push", &rule.push)] {
+                let url = url.trim();
+                if !url.is_empty() && !url.starts_with("http://") && !url.starts_with("https://") {
+                    self.report(
+                        &key(field),
+                        format!("'{}' is not an http:// or https:// URL", url),
+                    );
+                }
+            }
+            if rule.telegram
+                && (telegram.bot_token.is_empty() || telegram.chat_id.trim().is_empty())
+            {
+                self.report(
+                    &key("telegram"),
+                    "needs [telegram] bot_token and chat_id".to_string(),
+                );
+            }
+            if rule.gotify && (gotify.url.trim().is_empty() || gotify.token.is_empty()) {
+                self.report(&key("gotify"), "needs [gotify] url and token".to_string());
+            }
+            if let Some(priority) = rule.priority {
+                if !(1..=5).contains(&priority) {
+                    self.report(
+                        &key("priority"),
+                        format!("{} isn't from 1 (min) to 5 (urgent)", priority),
+                    );
+                }
+            }
+            if rule.most_wanted == Some(0) {
+                self.report(
+                    &key("most_wanted"),
+                    "0 lets no entity through; leave it out for all".to_string(),
+                );
+            } else if rule.most_wanted.is_some() && !file.clublog.enabled {
+                self.report(&key("most_wanted"), "needs [clublog] enabled".to_string());
+            }
+        }
+    }
+
+    /// `[digest]` schedule and mail settings, when it's enabled
+    fn digest(&mut self, digest: &DigestConfig) {
+        let period = digest.period.trim().to_lowercase();
+        if period != "daily" && period != "weekly" {
+            self.report(
+                "digest.period",
+                format!("unknown period '{}' (daily or weekly)", digest.period),
+            );
+        }
+        if NaiveTime::parse_from_str(digest.send_at.trim(), "%H:%M").is_err() {
+            self.report(
+                "digest.send_at",
+                format!("'{}' is not a time like \"07:00\"", digest.send_at),
+            );
+        }
+        if period == "weekly" && digest.weekday.trim().parse::<Weekday>().is_err() {
+            self.report(
+                "digest.weekday",
+                format!("'{}' is not a day like \"mon\"", digest.weekday),
+            );
+        }
+        for kind in digest.alerts.split(',').map(str::trim) {
+            if !kind.is_empty() && !["watch", "dxcc", "slot"].contains(&kind) {
+                self.report(
+                    "digest.alerts",
+                    format!("unknown alert kind '{}' (watch, dxcc or slot)", kind),
+                );
+            }
+        }
+        if digest.smtp_host.trim().is_empty() {
+            self.report("digest.smtp_host", "needed to send the digest".to_string());
+        }
+        let security = digest.security.trim().to_lowercase();
+        if !["starttls", "tls", "none"].contains(&security.as_str()) {
+            self.report(
+                "digest.security",
+                format!(
+                    "unknown security '{}' (starttls, tls or none)",
+                    digest.security
+                ),
+            );
+        }
+        if let Some(problem) = digest.password.problem() {
+            self.report("digest.password", problem);
+        }
+        if !digest.from.contains('@') {
+            self.report(
+                "digest.from",
+                "needs a sender address, e.g. \"pi@example.com\"".to_string(),
+            );
+        }
+        let to: Vec<&str> = digest
+            .to
+            .split(',')
+            .map(str::trim)
+            .filter(|to| !to.is_empty())
+            .collect();
+        if to.is_empty() {
+            self.report("digest.to", "needs at least one recipient".to_string());
+        }
+        for address in to.iter().filter(|to| !to.contains('@')) {
+            self.report(
+                "digest.to",
+                format!("'{}' is not an email address", address),
+            );
+        }
+    }
+
+    /// `[activation]` program and reference, when one is set
+    fn activation(&mut self, activation: &ActivationConfig, station_callsign: &str) {
+        let reference = activation.reference.trim();
+        match activation.program.trim().to_lowercase().as_str() {
+            "pota" => {
+                if !reference.contains('-') {
+                    self.report(
+                        "activation.reference",
+                        format!("'{}' is not a park like \"US-1234\"", reference),
+                    );
+                }
+            }
+            "sota" => {
+                if !reference.contains('/') || !reference.contains('-') {
+                    self.report(
+                        "activation.reference",
+                        format!("'{}' is not a summit like \"W7W/LC-001\"", reference),
+                    );
+                }
+                match activation.sota_token.problem() {
+                    Some(problem) => self.report("activation.sota_token", problem),
+                    None if activation.sota_token.is_empty() => self.report(
+                        "activation.sota_token",
+                        "needed to post SOTA spots".to_string(),
+                    ),
+                    None => {}
+                }
+            }
+            _ => self.report(
+                "activation.program",
+                format!("unknown program '{}' (pota or sota)", activation.program),
+            ),
+        }
+        if activation.callsign.trim().is_empty() && station_callsign.trim().is_empty() {
+            self.report(
+                "activation.callsign",
+                "needed to spot, as [station] callsign isn't set".to_string(),
+            );
+        }
+    }
+
+    /// Each `[[preset]]` entry, and the one `[filters] preset` picks
+    fn presets(&mut self, presets: &[FilterPreset], active: &str) {
+        let mut names = BTreeSet::new();
+        for (index, preset) in presets.iter().enumerate() {
+            let key = |field: &str| format!("preset.{}.{}", index + 1, field);
+            if preset.name.trim().is_empty() {
+                self.report(&key("name"), "needs a name to pick it by".to_string());
+            } else if !names.insert(preset.name.trim().to_lowercase()) {
+                self.report(&key("name"), format!("'{}' is used twice", preset.name));
+            }
+            if let Some(snr) = preset.min_snr {
+                self.min_snr(&key("min_snr"), snr);
+            }
+            if let Some(minutes) = preset.max_age_minutes {
+                self.max_age(&key("max_age_minutes"), minutes);
+            }
+            for band in preset
+                .bands
+                .split(',')
+                .filter(|band| !band.trim().is_empty())
+            {
+                if band.trim().eq_ignore_ascii_case("all") {
+                    self.report(&key("bands"), "leave bands empty for all".to_string());
+                } else {
+                    self.band(&key("bands"), band);
+                }
+            }
+            for continent in preset.continents.split(',').map(str::trim) {
+                if !continent.is_empty() && !CONTINENTS.contains(&continent.to_uppercase().as_str())
+                {
+                    self.report(
+                        &key("continents"),
+                        format!(
+                            "unknown continent '{}' ({})",
+                            continent,
+                            CONTINENTS.join(", ")
+                        ),
+                    );
+                }
+            }
+        }
+        let active = active.trim();
+        if !active.is_empty() && !names.contains(&active.to_lowercase()) {
+            self.report(
+                "filters.preset",
+                format!("no [[preset]] named '{}'", active),
+            );
+        }
+    }
+
+    fn logging(&mut self, config: &LoggingConfig) {
+        const LEVELS: &str = "off, error, warn, info, debug or trace";
+        if logging::parse_level(&config.level).is_none() {
+            self.report(
+                "logging.level",
+                format!("unknown level '{}' ({})", config.level.trim(), LEVELS),
+            );
+        }
+        for entry in config
+            .modules
+            .split(',')
+            .filter(|entry| !entry.trim().is_empty())
+        {
+            let valid = entry.split_once('=').is_some_and(|(module, level)| {
+                !module.trim().is_empty() && logging::parse_level(level).is_some()
+            });
+            if !valid {
+                self.report(
+                    "logging.modules",
+                    format!("'{}' is not module=level ({})", entry.trim(), LEVELS),
+                );
+            }
+        }
+        for name in config
+            .output
+            .split(',')
+            .filter(|name| !name.trim().is_empty())
+        {
+            if Output::from_name(name).is_none() {
+                let names: Vec<&str> = Output::ALL.iter().map(|output| output.name()).collect();
+                self.report(
+                    "logging.output",
+                    format!("unknown output '{}' ({})", name.trim(), names.join(", ")),
+                );
+            }
+        }
+    }
+
+    fn units(&mut self, units: &UnitsSection) {
+        if DistanceUnit::from_name(&units.distance).is_none() {
+            self.report(
+                "units.distance",
+                format!("unknown unit '{}' (km or mi)", units.distance.trim()),
+            );
+        }
+        if FrequencyUnit::from_name(&units.frequency).is_none() {
+            self.report(
+                "units.frequency",
+                format!("unknown unit '{}' (khz or mhz)", units.frequency.trim()),
+            );
+        }
+        if units.frequency_decimals.is_some_and(|places| places > 6) {
+            self.report(
+                "units.frequency_decimals",
+                "more than 6 decimal places is finer than 1 Hz".to_string(),
+            );
+        }
+        let clock = units.clock.trim();
+        if !clock.eq_ignore_ascii_case("utc") && !clock.eq_ignore_ascii_case("local") {
+            self.report(
+                "units.clock",
+                format!("unknown clock '{}' (utc or local)", clock),
+            );
+        }
+        let timezone = units.timezone.trim();
+        if !timezone.is_empty() && parse_timezone(timezone).is_none() {
+            self.report(
+                "units.timezone",
+                format!(
+                    "unknown time zone '{}' (an IANA name like \"Europe/Berlin\")",
+                    timezone
+                ),
+            );
+        }
+        if Clocks::from_name(&units.clocks).is_none() {
+            self.report(
+                "units.clocks",
+                format!(
+                    "unknown clocks '{}' (utc, local or both)",
+                    units.clocks.trim()
+                ),
+            );
+        }
+        if !matches!(units.hours, 12 | 24) {
+            self.report(
+                "units.hours",
+                format!("{}-hour clock doesn't exist (12 or 24)", units.hours),
+            );
+        }
+        if DateStyle::from_name(&units.date).is_none() {
+            self.report(
+                "units.date",
+                format!(
+                    "unknown date style '{}' (iso, dmy or mdy)",
+                    units.date.trim()
+                ),
+            );
+        }
+    }
+
+    fn profile(&mut self, name: &str, profile: &Profile) {
+        let key = |field: &str| format!("profile.{}.{}", name, field);
+        if let Some(band) = &profile.band {
+            self.band(&key("band"), band);
+        }
+        if let Some(sort) = &profile.sort {
+            self.sort(&key("sort"), sort);
+        }
+        if let Some(snr) = profile.min_snr {
+            self.min_snr(&key("min_snr"), snr);
+        }
+        if let Some(minutes) = profile.max_age_minutes {
+            self.max_age(&key("max_age_minutes"), minutes);
+        }
+    }
+}
+
+/// Check deserialized settings, locating problems in the file's `text`
+pub(super) fn check(file: &ConfigFile, text: &str) -> Vec<Issue> {
+    let mut checker = Checker {
+        text,
+        issues: Vec::new(),
+    };
+    checker.band("filters.band", &file.filters.band);
+    checker.sort("filters.sort", &file.filters.sort);
+    checker.min_snr("filters.min_snr", file.filters.min_snr);
+    checker.max_age("filters.max_age_minutes", file.filters.max_age_minutes);
+    for (name, profile) in &file.profile {
+        checker.profile(name, profile);
+    }
+    checker.presets(&file.preset, &file.filters.preset);
+    checker.alert_rules(file);
+    let rules = &file.alerts.rules;
+    for (used, key, secret) in [
+        (
+            rules.iter().any(|rule| rule.telegram),
+            "telegram.bot_token",
+            &file.telegram.bot_token,
+        ),
+        (
+            rules.iter().any(|rule| !rule.push.trim().is_empty()),
+            "ntfy.token",
+            &file.ntfy.token,
+        ),
+        (
+            rules.iter().any(|rule| rule.gotify),
+            "gotify.token",
+            &file.gotify.token,
+        ),
+    ] {
+        if let Some(problem) = secret.problem().filter(|_| used) {
+            checker.report(key, problem);
+        }
+    }
+    let gotify_url = file.gotify.url.trim();
+    if !gotify_url.is_empty()
+        && !gotify_url.starts_with("http://")
+        && !gotify_url.starts_with("https://")
+    {
+        checker.report(
+            "gotify.url",
+            format!("'{}' is not an http:// or https:// URL", gotify_url),
+        );
+    }
+    let adif_file = file.alerts.adif_file.trim();
+    if !adif_file.is_empty() && !Path::new(adif_file).exists() {
+        checker.report(
+            "alerts.adif_file",
+            format!(
+                "{} not found; check the path your logger writes its ADIF log to",
+                adif_file
+            ),
+        );
+    }
+    for (band, filter) in &file.filters.bands {
+        let key = |field: &str| format!("filters.{}.{}", band.name(), field);
+        if let Some(snr) = filter.min_snr {
+            checker.min_snr(&key("min_snr"), snr);
+        }
+        if let Some(minutes) = filter.max_age_minutes {
+            checker.max_age(&key("max_age_minutes"), minutes);
+        }
+    }
+
+    let station = &file.station;
+    if !station.callsign.trim().is_empty() {
+        if let Err(e) = normalize_callsign(&station.callsign) {
+            checker.report("station.callsign", e);
+        }
+    }
+    if station.region > 3 {
+        checker.report(
+            "station.region",
+            format!(
+                "IARU region {} doesn't exist (1, 2, 3 or 0 for unset)",
+                station.region
+            ),
+        );
+    }
+    if station.cq_zone > 40 {
+        checker.report(
+            "station.cq_zone",
+            format!("CQ zone {} is outside 1-40", station.cq_zone),
+        );
+    }
+    if station.itu_zone > 90 {
+        checker.report(
+            "station.itu_zone",
+            format!("ITU zone {} is outside 1-90", station.itu_zone),
+        );
+    }
+
+    if let Some((_, port)) = file.feed.server.trim().rsplit_once(':') {
+        if port.parse::<u16>().map_or(true, |port| port == 0) {
+            checker.report(
+                "feed.server",
+                format!("port '{}' isn't a number from 1 to 65535", port),
+            );
+        }
+    }
+
+    if file.web.enabled {
+        if let Some(problem) = file.web.api_token.problem() {
+            checker.report("web.api_token", problem);
+        }
+    }
+
+    if file.grpc.enabled {
+        if let Some(problem) = file.grpc.token.problem() {
+            checker.report("grpc.token", problem);
+        }
+    }
+
+    if file.mqtt.enabled {
+        if let Some(problem) = file.mqtt.password.problem() {
+            checker.report("mqtt.password", problem);
+        }
+        for (key, qos) in [
+            ("mqtt.spot_qos", file.mqtt.spot_qos),
+            ("mqtt.state_qos", file.mqtt.state_qos),
+        ] {
+            if qos > 2 {
+                checker.report(key, format!("QoS {} isn't 0, 1 or 2", qos));
+            }
+        }
+        let ca_file = file.mqtt.ca_file.trim();
+        if !ca_file.is_empty() && !Path::new(ca_file).exists() {
+            checker.report("mqtt.ca_file", format!("{} not found", ca_file));
+        }
+        if file.mqtt.discovery && file.mqtt.discovery_prefix.trim().is_empty() {
+            checker.report(
+                "mqtt.discovery_prefix",
+                "needed for discovery, usually \"homeassistant\"".to_string(),
+            );
+        }
+    }
+
+    if file.cloudlog.enabled {
+        if file.cloudlog.url.trim().is_empty() {
+            checker.report(
+                "cloudlog.url",
+                "needed to reach the logbook, e.g. \"https://log.example.com/index.php\""
+                    .to_string(),
+            );
+        }
+        match file.cloudlog.api_key.problem() {
+            Some(problem) => checker.report("cloudlog.api_key", problem),
+            None if file.cloudlog.api_key.is_empty() => checker.report(
+                "cloudlog.api_key",
+                "needed to use the logbook's API".to_string(),
+            ),
+            None => {}
+        }
+    }
+
+    if file.n1mm.enabled {
+        checker.udp_address("n1mm.address", &file.n1mm.address, "255.255.255.255:12060");
+    }
+    if file.logger.enabled {
+        let program = file.logger.program.trim().to_lowercase();
+        if !["log4om", "dxkeeper"].contains(&program.as_str()) {
+            checker.report(
+                "logger.program",
+                format!(
+                    "unknown program '{}' (log4om or dxkeeper)",
+                    file.logger.program
+                ),
+            );
+        }
+        if !file.logger.address.trim().is_empty() {
+            checker.udp_address("logger.address", &file.logger.address, "127.0.0.1:2241");
+        }
+    }
+    if file.wsjtx.enabled {
+        checker.udp_address("wsjtx.address", &file.wsjtx.address, "127.0.0.1:2237");
+    }
+
+    if file.digest.enabled {
+        checker.digest(&file.digest);
+    }
+    if file.aprs.enabled {
+        checker.udp_address("aprs.server", &file.aprs.server, "rotate.aprs2.net:14580");
+        let callsign = match file.aprs.callsign.trim() {
+            "" => file.station.callsign.trim(),
+            callsign => callsign,
+        };
+        if callsign.is_empty() || callsign.len() > 9 {
+            checker.report(
+                "aprs.callsign",
+                format!("'{}' is not a call-SSID like \"N0CALL-10\"", callsign),
+            );
+        }
+        for kind in file.aprs.alerts.split(',').map(str::trim) {
+            if !kind.is_empty() && !["self", "watch", "dxcc", "slot"].contains(&kind) {
+                checker.report(
+                    "aprs.alerts",
+                    format!("unknown alert kind '{}' (self, watch, dxcc or slot)", kind),
+                );
+            }
+        }
+        let to = file.aprs.message_to.trim();
+        if to.len() > 9 {
+            checker.report(
+                "aprs.message_to",
+                format!("'{}' is longer than an APRS addressee (9 characters)", to),
+            );
+        }
+    }
+    if file.influx.enabled {
+        let url = file.influx.url.trim();
+        if url.is_empty() {
+            if file.influx.udp_address.trim().is_empty() {
+                checker.report(
+                    "influx.url",
+                    "needs a url, or a udp_address to send to".to_string(),
+                );
+            } else {
+                checker.udp_address(
+                    "influx.udp_address",
+                    &file.influx.udp_address,
+                    "127.0.0.1:8089",
+                );
+            }
+        } else if !url.starts_with("http://") && !url.starts_with("https://") {
+            checker.report(
+                "influx.url",
+                format!("'{}' is not an http:// or https:// URL", url),
+            );
+        }
+        if let Some(problem) = file.influx.token.problem() {
+            checker.report("influx.token", problem);
+        }
+        if file.influx.interval_secs == 0 {
+            checker.report("influx.interval_secs", "must be at least 1".to_string());
+        }
+    }
+    if file.dump.enabled {
+        if Cron::parse(&file.dump.schedule).is_none() {
+            checker.report(
+                "dump.schedule",
+                format!(
+                    "'{}' is not a schedule like \"0 * * * *\" (minute hour day month weekday)",
+                    file.dump.schedule
+                ),
+            );
+        }
+        let format = file.dump.format.trim().to_lowercase();
+        if !["json", "csv", "both"].contains(&format.as_str()) {
+            checker.report(
+                "dump.format",
+                format!("unknown format '{}' (json, csv or both)", file.dump.format),
+            );
+        }
+    }
+    if !file.activation.reference.trim().is_empty() {
+        checker.activation(&file.activation, &file.station.callsign);
+    }
+
+    for (section, service, login) in [
+        ("qrz", "QRZ", &file.qrz),
+        ("hamqth", "HamQTH", &file.hamqth),
+    ] {
+        if !login.enabled {
+            continue;
+        }
+        let key = |field: &str| format!("{}.{}", section, field);
+        if login.username.trim().is_empty() {
+            checker.report(&key("username"), format!("needed to log in to {}", service));
+        }
+        match login.password.problem() {
+            Some(problem) => checker.report(&key("password"), problem),
+            None if login.password.is_empty() => {
+                checker.report(&key("password"), format!("needed to log in to {}", service))
+            }
+            None => {}
+        }
+    }
+
+    let clublog = &file.clublog;
+    if clublog.enabled && !clublog.email.trim().is_empty() {
+        for (key, secret) in [
+            ("clublog.password", &clublog.password),
+            ("clublog.api_key", &clublog.api_key),
+        ] {
+            match secret.problem() {
+                Some(problem) => checker.report(key, problem),
+                None if secret.is_empty() => {
+                    checker.report(key, "needed to fetch your Club Log log".to_string())
+                }
+                None => {}
+            }
+        }
+        if clublog.callsign.trim().is_empty() && file.station.callsign.trim().is_empty() {
+            checker.report(
+                "clublog.callsign",
+                "needed to fetch your log, as [station] callsign isn't set".to_string(),
+            );
+        }
+    }
+
+    for (prefix, entry) in display_entries(file) {
+        checker.display(&prefix, entry);
+    }
+    checker.radios(&file.radio);
+    checker.daily_range("sound.quiet_hours", &file.sound.quiet_hours);
+    if file.sound.morse && !(5..=60).contains(&file.sound.morse_wpm) {
+        checker.report(
+            "sound.morse_wpm",
+            format!("{} is outside 5 to 60 WPM", file.sound.morse_wpm),
+        );
+    }
+    checker.logging(&file.logging);
+    checker.units(&file.units);
+
+    // One GPIO claimed twice
+    let mut pins: BTreeMap<u32, String> = BTreeMap::new();
+    for (key, pin) in gpio_pins(file) {
+        match pins.get(&pin) {
+            Some(owner) => checker.report(&key, format!("GPIO {} is also {}", pin, owner)),
+            None => {
+                pins.insert(pin, key);
+            }
+        }
+    }
+
+    checker.issues
+}
+
+/// Display entries with the prefix of their keys: "display" for a plain
+/// `[display]`, "display.2" for the second `[[display]]`
+fn display_entries(file: &ConfigFile) -> Vec<(String, &DisplaySection)> {
+    let several = file.display.len() > 1;
+    file.display
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| match several {
+            false => ("display".to_string(), entry),
+            true => (format!("display.{}", index + 1), entry),
+        })
+        .collect()
+}
+
+/// GPIOs the settings put to use, by the key claiming them
+fn gpio_pins(file: &ConfigFile) -> Vec<(String, u32)> {
+    let mut pins = Vec::new();
+    let list = |text: &str| -> Vec<u32> {
+        text.split(',')
+            .filter_map(|pin| pin.trim().parse().ok())
+            .collect()
+    };
+
+    for (prefix, entry) in display_entries(file) {
+        let display = &entry.settings;
+        let driver = display.driver.as_str();
+        let key = |field: &str| format!("{}.{}", prefix, field);
+        if driver == "hd44780_gpio" {
+            for pin in list(&display.gpio_pins) {
+                pins.push((key("gpio_pins"), pin));
+            }
+        }
+        let spi = driver.ends_with("_spi")
+            || driver.starts_with("waveshare_")
+            || matches!(driver, "st7789" | "ili9341");
+        if spi {
+            pins.push((key("dc_pin"), display.dc_pin));
+            if let Some(pin) = display.reset_pin {
+                pins.push((key("reset_pin"), pin));
+            }
+        }
+        if driver.starts_with("waveshare_") {
+            pins.push((key("busy_pin"), display.busy_pin));
+        }
+        if matches!(driver, "st7789" | "ili9341") {
+            if let Some(pin) = display.backlight_pin {
+                pins.push((key("backlight_pin"), pin));
+            }
+        }
+    }
+
+    for (key, pin) in [
+        ("leds.connected_pin", file.leds.connected_pin),
+        ("leds.watch_pin", file.leds.watch_pin),
+        ("leds.dxcc_pin", file.leds.dxcc_pin),
+    ] {
+        if let Some(pin) = pin {
+            pins.push((key.to_string(), pin));
+        }
+    }
+    if file.sound.output.trim().eq_ignore_ascii_case("gpio") {
+        pins.push(("sound.piezo_pin".to_string(), file.sound.piezo_pin));
+    }
+    if file.input.encoder.trim().eq_ignore_ascii_case("gpio") {
+        for pin in list(&file.input.encoder_pins) {
+            pins.push(("input.encoder_pins".to_string(), pin));
+        }
+    }
+    for entry in file.input.buttons.split(',') {
+        if let Some(pin) = entry
+            .split_once('=')
+            .and_then(|(pin, _)| pin.trim().parse().ok())
+        {
+            pins.push(("input.buttons".to_string(), pin));
+        }
+    }
+    pins
+}
+
+/// Line (1-based) where the file sets a dotted key such as "display.driver"
+/// or "profile.dx.band"; entries of an array of tables count from 1, e.g.
+/// "radio.2.backend"
+pub(super) fn key_line(text: &str, key: &str) -> Option<usize> {
+    let (section, name) = key.rsplit_once('.')?;
+    let mut current = String::new();
+    let mut entries: BTreeMap<String, usize> = BTreeMap::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if let Some(header) = line.strip_prefix('[') {
+            current = header
+                .trim_matches(|c| c == '[' || c == ']')
+                .split('.')
+                .map(|part| part.trim().trim_matches('"'))
+                .collect::<Vec<_>>()
+                .join(".");
+            if header.starts_with('[') {
+                let count = entries.entry(current.clone()).or_insert(0);
+                *count += 1;
+                current = format!("{}.{}", current, count);
+            }
+            continue;
+        }
+        let Some((found, _)) = line.split_once('=') else {
+            continue;
+        };
+        if current == section && found.trim().trim_matches('"') == name {
+            return Some(index + 1);
+        }
+    }
+    None
+}
@@ -0,0 +1,213 @@
+//! Network display daemon (`rbn-vfd-displayd [listen address]`)
+//!
+//! Runs on the machine the display is attached to: opens the display from
+//! that machine's own settings and shows the frames the app sends with its
+//! "remote" driver (see `services::display::remote`). The newest connection
+//! that has sent anything wins: messages from older ones are dropped until
+//! it closes, and frames older than the last one shown are dropped too.
+
+use crate::config::{Config, DisplayConfig};
+use crate::services::display::charset::Charset;
+use crate::services::display::remote::{self, Hello, Message};
+use crate::services::display::{self, DisplayDriver, DisplayResult};
+use crate::services::display_port;
+use std::collections::BTreeSet;
+use std::io::BufReader;
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+/// Listen for the app and show its frames until killed
+pub fn run(listen: Option<String>) -> Result<(), String> {
+    let config = Config::load();
+    if config.display.driver == "remote" {
+        return Err("The daemon needs a local display driver, not 'remote'".to_string());
+    }
+    let port = display_port(&config.serial_port, config.display_usb_id);
+    if display::uses_serial(&config.display.driver) && port.is_empty() {
+        return Err("No display port configured".to_string());
+    }
+    let mut screen = Screen::open(config.display.clone(), port)?;
+    let hello = screen.hello();
+
+    let listen = listen.unwrap_or_else(|| format!("0.0.0.0:{}", remote::DEFAULT_PORT));
+    let listener =
+        TcpListener::bind(&listen).map_err(|e| format!("Failed to listen on {}: {}", listen, e))?;
+    println!(
+        "Showing frames received on {} on the {} display ({}x{})",
+        listen, config.display.driver, hello.columns, hello.rows
+    );
+
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        // Numbered in the order they were accepted
+        for (connection, stream) in (0u64..).zip(listener.incoming()) {
+            match stream {
+                Ok(stream) => {
+                    let tx = tx.clone();
+                    thread::spawn(move || client_session(connection, stream, hello, tx));
+                }
+                Err(e) => eprintln!("Accept failed: {}", e),
+            }
+        }
+    });
+
+    // Open connections that have sent a message
+    let mut live: BTreeSet<u64> = BTreeSet::new();
+    // (session, seq) of the last message shown
+    let mut latest: Option<(u64, u64)> = None;
+    for event in rx {
+        let (connection, message) = match event {
+            Event::Message(connection, message) => (connection, message),
+            Event::Closed(connection) => {
+                live.remove(&connection);
+                continue;
+            }
+        };
+        live.insert(connection);
+        if live.last() != Some(&connection) {
+            continue;
+        }
+        let Some((session, seq)) = message.sequence() else {
+            continue;
+        };
+        if latest.is_some_and(|(last_session, last_seq)| last_session == session && seq <= last_seq)
+        {
+            continue;
+        }
+        latest = Some((session, seq));
+        screen.show(message);
+    }
+    Ok(())
+}
+
+/// What a connection tells the display loop
+enum Event {
+    Message(u64, Message),
+    Closed(u64),
+}
+
+/// Greet one connection and forward its messages to the display loop
+fn client_session(connection: u64, stream: TcpStream, hello: Hello, tx: Sender<Event>) {
+    let peer = stream
+        .peer_addr()
+        .map(|addr| addr.to_string())
+        .unwrap_or_default();
+    println!("{} connected", peer);
+
+    let result = (|| -> std::io::Result<()> {
+        let mut writer = stream.try_clone()?;
+        remote::write_message(&mut writer, &Message::Hello(hello))?;
+        let mut reader = BufReader::new(stream);
+        while let Some(message) = remote::read_message(&mut reader)? {
+            if tx.send(Event::Message(connection, message)).is_err() {
+                break;
+            }
+        }
+        Ok(())
+    })();
+    let _ = tx.send(Event::Closed(connection));
+    match result {
+        Ok(()) => println!("{} disconnected", peer),
+        Err(e) => println!("{} disconnected: {}", peer, e),
+    }
+}
+
+/// The local display, reopened after a write fails
+struct Screen {
+    config: DisplayConfig,
+    port: String,
+    driver: Option<Box<dyn DisplayDriver>>,
+    charset: Charset,
+    hello: Hello,
+}
+
+impl Screen {
+    fn open(config: DisplayConfig, port: String) -> Result<Self, String> {
+        let driver = display::create_driver(&config, &port)
+            .map_err(|e| format!("Failed to open display: {}", e))?;
+        let (columns, rows) = driver.dimensions();
+        let capabilities = driver.capabilities();
+        let mut screen = Self {
+            charset: Charset::parse(&config.charset_map),
+            hello: Hello {
+                columns,
+                rows,
+                brightness: capabilities.brightness,
+                color: capabilities.color,
+            },
+            config,
+            port,
+            driver: Some(driver),
+        };
+        screen.restore_brightness();
+        Ok(screen)
+    }
+
+    fn hello(&self) -> Hello {
+        self.hello
+    }
+
+    fn restore_brightness(&mut self) {
+        if let Some(driver) = self.driver.as_mut() {
+            if driver.capabilities().brightness {
+                let _ = driver.set_brightness(self.config.brightness);
+            }
+        }
+    }
+
+    /// Show a message, reopening the display first if it was lost
+    fn show(&mut self, message: Message) {
+        if self.driver.is_none() {
+            match display::create_driver(&self.config, &self.port) {
+                Ok(driver) => {
+                    println!("Display reopened");
+                    self.driver = Some(driver);
+                    self.restore_brightness();
+                }
+                Err(e) => {
+                    eprintln!("Failed to reopen display: {}", e);
+                    return;
+                }
+            }
+        }
+        let Some(driver) = self.driver.as_mut() else {
+            return;
+        };
+
+        let result = match message {
+            Message::Frame { lines, colors, .. } => {
+                write_frame(driver.as_mut(), &self.charset, &lines, &colors)
+            }
+            Message::Brightness { percent, .. } => driver.set_brightness(percent),
+            Message::Hello(_) => Ok(()),
+        };
+        if let Err(e) = result {
+            eprintln!("Display error: {}", e);
+            self.driver = None;
+        }
+    }
+}
+
+/// Translate, fit and write every row, then flush
+fn write_frame(
+    driver: &mut dyn DisplayDriver,
+    charset: &Charset,
+    lines: &[String],
+    colors: &[Vec<display::color::ColorSpan>],
+) -> DisplayResult<()> {
+    let (width, rows) = driver.dimensions();
+    let color = driver.capabilities().color;
+    for row in 0..rows {
+        let line = charset.translate(lines.get(row).map_or("", String::as_str), driver);
+        let line: String = format!("{:width$}", line, width = width)
+            .chars()
+            .take(width)
+            .collect();
+        match colors.get(row) {
+            Some(colors) if color => driver.write_colored_line(row, &line, colors)?,
+            _ => driver.write_line(row, &line)?,
+        }
+    }
+    driver.flush()
+}
@@ -0,0 +1,182 @@
+//! Display self-test (`--display-test [port]`)
+//!
+//! Opens each configured display in turn (the main one, then any further
+//! `[[display]]` entries) and steps through patterns that make wiring and
+//! configuration faults obvious without waiting for spots: every cell
+//! filled, row/column addressing, the character set, brightness levels and
+//! custom glyphs. Each step is timed and reported on stdout.
+
+use crate::config::{Config, DisplayConfig};
+use crate::models::Band;
+use crate::services::display::glyphs::Glyph;
+use crate::services::display::{self, DisplayDriver, DisplayResult};
+use crate::services::display_port;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long each pattern stays up
+const HOLD: Duration = Duration::from_secs(2);
+
+/// Brightness levels stepped through, in percent
+const BRIGHTNESS_STEPS: [u8; 4] = [100, 50, 10, 0];
+
+/// Frames written back to back for the refresh timing
+const TIMING_FRAMES: u32 = 10;
+
+/// One glyph per custom character slot
+const TEST_GLYPHS: [Glyph; 8] = [
+    Glyph::Cw,
+    Glyph::Rtty,
+    Glyph::Digital,
+    Glyph::NewDxcc,
+    Glyph::TrendUp,
+    Glyph::TrendDown,
+    Glyph::Band(Band::B20m),
+    Glyph::Band(Band::B160m),
+];
+
+/// Run the self-test on every configured display; `port` replaces the
+/// main display's configured port (or USB adapter)
+pub fn run(port: Option<String>) -> Result<(), String> {
+    let config = Config::load();
+    let port = port.unwrap_or_else(|| display_port(&config.serial_port, config.display_usb_id));
+    let mut displays = vec![(config.display.clone(), port)];
+    displays.extend(
+        config
+            .extra_displays
+            .iter()
+            .map(|extra| (extra.settings.clone(), extra.port())),
+    );
+
+    let mut failures = 0;
+    for (index, (settings, port)) in displays.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        if let Err(e) = test_display(settings, port) {
+            println!("  {}", e);
+            failures += 1;
+        }
+    }
+    match failures {
+        0 => Ok(()),
+        1 => Err("A display failed to open".to_string()),
+        n => Err(format!("{} displays failed to open", n)),
+    }
+}
+
+/// Step one display through the patterns
+fn test_display(settings: &DisplayConfig, port: &str) -> Result<(), String> {
+    let name = &settings.driver;
+    println!("Display test: {} {}", name, port);
+    if display::uses_serial(name) && port.is_empty() {
+        return Err("No display port configured; pass one after display-test".to_string());
+    }
+
+    let started = Instant::now();
+    let mut driver = display::create_driver(settings, port)
+        .map_err(|e| format!("Failed to open display: {}", e))?;
+    let (columns, rows) = driver.dimensions();
+    let capabilities = driver.capabilities();
+    println!("  {:<24} {}", "Open and init", millis(started.elapsed()));
+    println!("  {:<24} {}x{}", "Grid", columns, rows);
+    println!("  {:<24} {:?}", "Capabilities", capabilities);
+
+    let driver = driver.as_mut();
+    step("Fill every cell", || {
+        show(driver, &vec!["#".repeat(columns); rows])
+    });
+    step("Row and column ruler", || {
+        let lines: Vec<String> = (0..rows)
+            .map(|row| {
+                let ruler: String = (0..columns)
+                    .map(|column| char::from(b'0' + (column % 10) as u8))
+                    .collect();
+                format!("{}{}", row + 1, &ruler[1..])
+            })
+            .collect();
+        show(driver, &lines)
+    });
+    step("Character set", || {
+        let chars: Vec<char> = (b' '..=b'~').map(char::from).collect();
+        let lines: Vec<String> = chars
+            .chunks(columns.max(1))
+            .take(rows)
+            .map(|chunk| chunk.iter().collect())
+            .collect();
+        show(driver, &lines)
+    });
+    if capabilities.partial_writes {
+        step("Partial write", || {
+            show(driver, &vec![String::new(); rows])?;
+            driver.write_at(rows - 1, columns.saturating_sub(4), "<-->")?;
+            driver.flush()
+        });
+    }
+    if capabilities.custom_glyphs {
+        step("Custom glyphs", || {
+            for (slot, glyph) in TEST_GLYPHS.iter().enumerate() {
+                driver.set_custom_glyph(slot as u8, &glyph.bitmap())?;
+            }
+            let glyphs: String = (0..TEST_GLYPHS.len() as u8).map(char::from).collect();
+            show(driver, &[format!("Glyphs {}", glyphs)])
+        });
+    }
+    if capabilities.brightness {
+        let _ = show(driver, &["Brightness".to_string()]);
+        for level in BRIGHTNESS_STEPS {
+            step(&format!("Brightness {}%", level), || {
+                driver.set_brightness(level)
+            });
+        }
+        let _ = driver.set_brightness(settings.brightness);
+    }
+
+    let patterns = [
+        vec!["#".repeat(columns); rows],
+        vec![" ".repeat(columns); rows],
+    ];
+    let started = Instant::now();
+    let mut result = Ok(());
+    for frame in 0..TIMING_FRAMES {
+        result = result.and_then(|_| show(driver, &patterns[frame as usize % 2]));
+    }
+    match result {
+        Ok(()) => println!(
+            "  {:<24} {} per frame",
+            "Full redraw",
+            millis(started.elapsed() / TIMING_FRAMES)
+        ),
+        Err(e) => println!("  {:<24} FAILED: {}", "Full redraw", e),
+    }
+
+    let _ = show(driver, &["Display test done".to_string()]);
+    thread::sleep(HOLD);
+    let _ = driver.clear();
+    println!("Done");
+    Ok(())
+}
+
+/// Run one step, report how long it took, and leave the result up
+fn step(name: &str, action: impl FnOnce() -> DisplayResult<()>) {
+    let started = Instant::now();
+    match action() {
+        Ok(()) => println!("  {:<24} {}", name, millis(started.elapsed())),
+        Err(e) => println!("  {:<24} FAILED: {}", name, e),
+    }
+    thread::sleep(HOLD);
+}
+
+/// Write a whole frame, blanking rows past the end of `lines`
+fn show(driver: &mut dyn DisplayDriver, lines: &[String]) -> DisplayResult<()> {
+    let (columns, rows) = driver.dimensions();
+    for row in 0..rows {
+        let line = lines.get(row).map_or("", String::as_str);
+        driver.write_line(row, &format!("{:columns$}", line, columns = columns))?;
+    }
+    driver.flush()
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.1} ms", duration.as_secs_f64() * 1000.0)
+}
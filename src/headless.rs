@@ -0,0 +1,315 @@
+//! Headless mode (`--headless [json|text]`)
+//!
+//! Runs the RBN client, alerts (with the webhooks and pushes of alert
+//! rules) and radio manager without any display, writing each spot that
+//! passes the configured filters to stdout as one JSON object (or
+//! `key=value` text) per line, for piping into other tools.
+//! Status and errors go to the log (stderr unless `[logging] output` says
+//! otherwise) so stdout stays machine-readable. With
+//! the web dashboard enabled, its tune clicks still reach the radio, so the
+//! binary can run as a plain RBN-to-radio bridge, and its filter commands
+//! change what is written; gRPC clients can do the same. With `--replay`
+//! the run ends when the recording does.
+
+use crate::config::Config;
+use crate::logging;
+use crate::models::{normalize_callsign, AggregatedSpot, Band, RawSpot};
+#[cfg(feature = "grpc")]
+use crate::services::grpc::{GrpcCommand, GrpcServer};
+#[cfg(feature = "mqtt")]
+use crate::services::mqtt::MqttPublisher;
+#[cfg(any(feature = "web", feature = "grpc"))]
+use crate::services::radio::RadioMode;
+use crate::services::radio::{RadioEvent, RadioManager};
+#[cfg(feature = "web")]
+use crate::services::web::{WebCommand, WebServer};
+#[cfg(any(feature = "web", feature = "grpc"))]
+use crate::services::CommandSource;
+use crate::services::{Alert, Control, Integrations, RbnClient, RbnMessage, SpotAlerts, SpotStore};
+use chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Pause between polls of the RBN and radio channels
+const TICK: Duration = Duration::from_millis(250);
+
+/// Wait before reconnecting after the feed drops
+const RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Output line format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// One JSON object per line
+    Json,
+    /// Space separated `key=value` pairs
+    Text,
+}
+
+impl Format {
+    /// "json" or "text" (None for anything else)
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "json" => Some(Format::Json),
+            "text" => Some(Format::Text),
+            _ => None,
+        }
+    }
+}
+
+/// One spot as written to stdout
+#[derive(Debug, Serialize)]
+struct SpotRecord<'a> {
+    /// UTC, RFC 3339
+    time: String,
+    call: &'a str,
+    spotter: &'a str,
+    khz: f64,
+    band: Option<&'static str>,
+    mode: &'a str,
+    snr: i32,
+    wpm: i32,
+    /// Alert kind ("self", "watch", "dxcc", "slot", "rule"), if the spot raised one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alert: Option<&'static str>,
+}
+
+impl<'a> SpotRecord<'a> {
+    fn new(raw: &'a RawSpot, alert: Option<&Alert>) -> Self {
+        Self {
+            time: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            call: &raw.spotted_callsign,
+            spotter: &raw.spotter_callsign,
+            khz: raw.frequency_khz,
+            band: Band::from_khz(raw.frequency_khz).map(Band::name),
+            mode: &raw.mode,
+            snr: raw.snr,
+            wpm: raw.speed_wpm,
+            alert: alert.map(Alert::kind),
+        }
+    }
+
+    fn to_line(&self, format: Format) -> String {
+        match format {
+            Format::Json => serde_json::to_string(self).unwrap_or_default(),
+            Format::Text => {
+                let mut line = format!(
+                    "time={} call={} spotter={} khz={:.1} band={} mode={} snr={} wpm={}",
+                    self.time,
+                    self.call,
+                    self.spotter,
+                    self.khz,
+                    self.band.unwrap_or("-"),
+                    self.mode,
+                    self.snr,
+                    self.wpm
+                );
+                if let Some(alert) = self.alert {
+                    line.push_str(&format!(" alert={}", alert));
+                }
+                line
+            }
+        }
+    }
+}
+
+/// Run without a display until the output pipe closes
+pub fn run(format: Format) -> Result<(), String> {
+    let mut config = Config::load();
+    logging::init(&config.logging, true);
+    let callsign = normalize_callsign(&config.callsign)
+        .map_err(|e| format!("{}; set one in settings.toml to connect", e))?;
+
+    let spot_store = SpotStore::new();
+    let radio = RadioManager::new(config.radio.clone());
+    let mut alerts = SpotAlerts::new(&config.alerts);
+    alerts.set_callsign(&config.callsign);
+    if let Err(e) = alerts.load_dxcc(&config.alerts) {
+        log::warn!("{}", e);
+    }
+    // Rules send their webhooks and pushes; there are no LEDs or sound here
+    let (mut integrations, errors) = Integrations::start(&config, &spot_store, &alerts);
+    for e in errors {
+        log::error!("{}", e);
+    }
+    // Spots and state only; there's no display to mirror
+    #[cfg(feature = "mqtt")]
+    let mut mqtt = config
+        .mqtt
+        .enabled
+        .then(|| MqttPublisher::start(&config.mqtt, spot_store.clone()));
+    #[cfg(feature = "web")]
+    let mut web_server = if config.web.enabled {
+        WebServer::start(
+            &config.web.bind,
+            config.web.port,
+            config.web.api_token.expose(),
+            spot_store.clone(),
+            config.spot_limits(),
+        )
+        .map_err(|e| log::error!("{}", e))
+        .ok()
+    } else {
+        None
+    };
+
+    #[cfg(feature = "grpc")]
+    let mut grpc_server = if config.grpc.enabled {
+        GrpcServer::start(&config.grpc, spot_store.clone())
+            .map_err(|e| log::error!("{}", e))
+            .ok()
+    } else {
+        None
+    };
+
+    let control = Control::new();
+    let mut client: Option<RbnClient> = None;
+    let mut reconnect_at = Instant::now();
+    let mut last_purge = Instant::now();
+    let stdout = io::stdout();
+    loop {
+        if client.is_none() && Instant::now() >= reconnect_at {
+            let rbn = RbnClient::new();
+            match &config.replay_file {
+                Some(path) => rbn.replay(path.clone()),
+                None => rbn.connect(callsign.clone(), config.feed.server.clone()),
+            }
+            client = Some(rbn);
+        }
+
+        integrations.poll(&mut alerts);
+
+        let mut disconnected = false;
+        if let Some(ref mut rbn) = client {
+            while let Some(msg) = rbn.try_recv() {
+                match msg {
+                    RbnMessage::Spot(raw) => {
+                        let alert = integrations.spot(&raw, &mut alerts);
+                        if passes_filters(&config, &raw, &alerts) {
+                            let line = SpotRecord::new(&raw, alert.as_ref()).to_line(format);
+                            let mut out = stdout.lock();
+                            // A closed pipe (e.g. `| head`) ends the run
+                            if writeln!(out, "{}", line).and_then(|_| out.flush()).is_err() {
+                                return Ok(());
+                            }
+                        }
+                        spot_store.add_spot(raw);
+                    }
+                    RbnMessage::Announcement(announcement) => {
+                        integrations.announcement(&announcement);
+                        log::info!("{}", announcement);
+                    }
+                    RbnMessage::Disconnected => disconnected = true,
+                    // Logged by the client
+                    RbnMessage::Status(_) | RbnMessage::RawData { .. } => {}
+                }
+            }
+        }
+        if disconnected && config.replay_file.is_some() {
+            return Ok(());
+        }
+        if disconnected {
+            log::warn!(
+                "RBN connection lost, reconnecting in {} s",
+                RECONNECT_DELAY.as_secs()
+            );
+            client = None;
+            reconnect_at = Instant::now() + RECONNECT_DELAY;
+        }
+
+        // Events are logged by the manager
+        while let Some(event) = radio.try_recv() {
+            if let RadioEvent::Tuned {
+                frequency_khz,
+                mode,
+            } = event
+            {
+                integrations.tuned(frequency_khz, mode);
+            }
+        }
+        // Logged by the engine
+        while integrations.try_recv_match().is_some() {}
+
+        #[cfg(feature = "web")]
+        if let Some(ref mut server) = web_server {
+            while let Some(command) = server.try_recv() {
+                match command {
+                    WebCommand::Tune {
+                        frequency_khz,
+                        mode,
+                    } => tune(&config, &radio, frequency_khz, &mode),
+                    WebCommand::Control(command) => control.send(CommandSource::Web, command),
+                }
+            }
+        }
+        #[cfg(feature = "grpc")]
+        if let Some(ref mut server) = grpc_server {
+            while let Some(command) = server.try_recv() {
+                match command {
+                    GrpcCommand::Tune {
+                        frequency_khz,
+                        mode,
+                    } => tune(&config, &radio, frequency_khz, &mode),
+                    GrpcCommand::Control(command) => control.send(CommandSource::Grpc, command),
+                }
+            }
+        }
+
+        // Settings changes are logged as they apply
+        while let Some((command, result)) = control.try_apply(&mut config) {
+            if result.is_ok() && command.changes_profile() {
+                alerts.set_lists(&config.alerts);
+                integrations.set_lists(&config.alerts);
+            }
+            if result.is_ok() && command.changes_radio() {
+                radio.reconfigure(config.radio.clone());
+            }
+        }
+        #[cfg(feature = "web")]
+        if let Some(ref server) = web_server {
+            server.set_state(&config, client.is_some());
+        }
+        let spots = if integrations.wants_spots() {
+            config.filter_spots(&spot_store, &alerts)
+        } else {
+            Vec::new()
+        };
+        integrations.update(&spots, &alerts);
+        #[cfg(feature = "mqtt")]
+        if let Some(ref mut mqtt) = mqtt {
+            mqtt.publish_state(&config, client.is_some(), &radio.state());
+            while let Some(announcement) = mqtt.try_recv() {
+                log::info!("{}", announcement);
+            }
+        }
+
+        if last_purge.elapsed() >= Duration::from_secs(5) {
+            spot_store.purge_old_spots();
+            last_purge = Instant::now();
+        }
+        thread::sleep(TICK);
+    }
+}
+
+/// Tune requested by a dashboard click or an API client; the logbook hears
+/// of it once the rig takes it
+#[cfg(any(feature = "web", feature = "grpc"))]
+fn tune(config: &Config, radio: &RadioManager, frequency_khz: f64, mode: &str) {
+    let mode = RadioMode::from_rbn_mode(mode);
+    if config.radio.use_sub_receiver {
+        radio.tune_sub(frequency_khz, mode);
+    } else {
+        radio.tune(frequency_khz, mode);
+    }
+}
+
+/// The GUI's spot filters (SNR per band, band, CW only, preset), applied
+/// to a single report
+fn passes_filters(config: &Config, raw: &RawSpot, alerts: &SpotAlerts) -> bool {
+    let spot = AggregatedSpot::from_raw(raw);
+    let (min_snr, _) = config.spot_limits().for_khz(spot.frequency_khz);
+    spot.highest_snr >= min_snr
+        && config.band_mode_match(&spot)
+        && config.preset_match(&spot, alerts)
+}
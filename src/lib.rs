@@ -0,0 +1,42 @@
+//! RBN VFD Display
+//!
+//! The binary is a thin wrapper around [`run`]. Downstream builds can
+//! register extra radio backends (and display drivers, with
+//! `services::display::register_driver`) before starting the app:
+//!
+//! ```no_run
+//! use rbn_vfd::services::radio::{register_backend, NoOpController};
+//!
+//! register_backend("my-rig", |_config| Box::new(NoOpController::new()));
+//! rbn_vfd::run().unwrap();
+//! ```
+
+pub mod app;
+pub mod check;
+pub mod config;
+pub mod display_daemon;
+pub mod display_test;
+pub mod headless;
+pub mod logging;
+pub mod models;
+pub mod paths;
+pub mod services;
+pub mod setup;
+#[cfg(feature = "tui")]
+pub mod tui;
+
+/// Start the GUI application
+pub fn run() -> eframe::Result<()> {
+    let options = eframe::NativeOptions {
+        viewport: eframe::egui::ViewportBuilder::default()
+            .with_inner_size([500.0, 600.0])
+            .with_min_inner_size([400.0, 400.0]),
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "RBN VFD Display",
+        options,
+        Box::new(|cc| Ok(Box::new(app::RbnVfdApp::new(cc)))),
+    )
+}
@@ -0,0 +1,322 @@
+//! Log output (`[logging]`)
+//!
+//! Messages go through the `log` macros; `init` installs a logger that
+//! filters them by module and writes them to stderr, a log file rotated by
+//! size and/or the systemd journal. Problems found while loading the
+//! settings are printed before the logger exists, so they always reach
+//! stderr.
+
+use crate::config::LoggingConfig;
+use crate::paths;
+use chrono::{SecondsFormat, Utc};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Target of the raw RBN telnet lines, written only with `raw_lines`
+pub const RAW_TARGET: &str = "rbn_raw";
+
+/// Where log lines go
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Output {
+    Stderr,
+    File,
+    Journald,
+}
+
+impl Output {
+    pub const ALL: [Output; 3] = [Output::Stderr, Output::File, Output::Journald];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Output::Stderr => "stderr",
+            Output::File => "file",
+            Output::Journald => "journald",
+        }
+    }
+
+    /// Output by name (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|output| output.name().eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+/// Level by name: "off", "error", "warn", "info", "debug" or "trace"
+pub fn parse_level(name: &str) -> Option<LevelFilter> {
+    name.trim().parse().ok()
+}
+
+/// "module=level" entries of `[logging] modules`, skipping bad ones
+pub fn parse_modules(modules: &str) -> Vec<(String, LevelFilter)> {
+    modules
+        .split(',')
+        .filter_map(|entry| {
+            let (module, level) = entry.split_once('=')?;
+            let module = module.trim().trim_matches(':');
+            (!module.is_empty()).then_some((module.to_string(), parse_level(level)?))
+        })
+        .collect()
+}
+
+/// Install the logger for `config`; `stderr` = false drops the stderr
+/// output (the terminal UI, which stderr would draw over). Only the first
+/// call takes effect.
+pub fn init(config: &LoggingConfig, stderr: bool) {
+    let outputs: Vec<Output> = config
+        .output
+        .split(',')
+        .filter_map(Output::from_name)
+        .collect();
+    let file = if outputs.contains(&Output::File) {
+        match RotatingFile::open(config) {
+            Ok(file) => Some(Mutex::new(file)),
+            Err(e) => {
+                eprintln!("Logging to a file: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    #[cfg(target_os = "linux")]
+    let journal = if outputs.contains(&Output::Journald) {
+        journal::Journal::open()
+            .map_err(|e| eprintln!("Logging to the journal: {}", e))
+            .ok()
+    } else {
+        None
+    };
+    #[cfg(not(target_os = "linux"))]
+    if outputs.contains(&Output::Journald) {
+        eprintln!("Logging to the journal needs Linux");
+    }
+
+    let mut modules = parse_modules(&config.modules);
+    // Most specific first
+    modules.sort_by_key(|(module, _)| std::cmp::Reverse(module.matches("::").count()));
+    let level = parse_level(&config.level).unwrap_or(LevelFilter::Info);
+    let max_level = modules
+        .iter()
+        .map(|(_, level)| *level)
+        .chain([level])
+        .chain(config.raw_lines.then_some(LevelFilter::Info))
+        .max()
+        .unwrap_or(LevelFilter::Info);
+    let logger = Logger {
+        level,
+        modules,
+        raw_lines: config.raw_lines,
+        stderr: stderr && outputs.contains(&Output::Stderr),
+        file,
+        #[cfg(target_os = "linux")]
+        journal,
+    };
+    if log::set_boxed_logger(Box::new(logger)).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+struct Logger {
+    /// Level for modules not in `modules`
+    level: LevelFilter,
+    /// Module names with their levels, most specific first
+    modules: Vec<(String, LevelFilter)>,
+    raw_lines: bool,
+    stderr: bool,
+    file: Option<Mutex<RotatingFile>>,
+    #[cfg(target_os = "linux")]
+    journal: Option<journal::Journal>,
+}
+
+impl Logger {
+    /// Level for a target such as "rbn_vfd::services::radio::hamlib": that
+    /// of the most specific listed module it is in, e.g. "radio"
+    fn level_for(&self, target: &str) -> LevelFilter {
+        let path = format!("::{}::", target);
+        self.modules
+            .iter()
+            .find(|(module, _)| path.contains(&format!("::{}::", module)))
+            .map_or(self.level, |(_, level)| *level)
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        if metadata.target() == RAW_TARGET {
+            return self.raw_lines;
+        }
+        metadata.level() <= self.level_for(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        if self.stderr {
+            match record.level() {
+                Level::Debug | Level::Trace => {
+                    eprintln!("[{}] {}", record.target(), record.args())
+                }
+                _ => eprintln!("{}", record.args()),
+            }
+        }
+        if let Some(file) = &self.file {
+            let line = format!(
+                "{} {:<5} {}: {}\n",
+                Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+                record.level(),
+                record.target(),
+                record.args()
+            );
+            if let Ok(mut file) = file.lock() {
+                file.write(&line);
+            }
+        }
+        #[cfg(target_os = "linux")]
+        if let Some(journal) = &self.journal {
+            journal.send(record);
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(file) = &self.file {
+            if let Ok(mut file) = file.lock() {
+                let _ = file.file.flush();
+            }
+        }
+    }
+}
+
+/// Log file moved to `<name>.1` (and older ones up to `keep_files`) when it
+/// grows past `max_file_kb`
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_bytes: u64,
+    keep: u32,
+}
+
+impl RotatingFile {
+    fn open(config: &LoggingConfig) -> Result<Self, String> {
+        let path = match config.file.trim() {
+            "" => paths::log_file().ok_or("no data directory on this system")?,
+            path => PathBuf::from(path),
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let file = Self::append(&path)?;
+        let size = file.metadata().map(|meta| meta.len()).unwrap_or_default();
+        Ok(Self {
+            path,
+            file,
+            size,
+            max_bytes: config.max_file_kb as u64 * 1024,
+            keep: config.keep_files,
+        })
+    }
+
+    fn append(path: &Path) -> Result<File, String> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))
+    }
+
+    /// Older copy number `n`, e.g. rbn-vfd.log.2
+    fn numbered(&self, n: u32) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    }
+
+    fn write(&mut self, line: &str) {
+        if self.max_bytes > 0 && self.size + line.len() as u64 > self.max_bytes {
+            self.rotate();
+        }
+        if self.file.write_all(line.as_bytes()).is_ok() {
+            self.size += line.len() as u64;
+        }
+    }
+
+    fn rotate(&mut self) {
+        let _ = std::fs::remove_file(self.numbered(self.keep.max(1)));
+        for n in (1..self.keep).rev() {
+            let _ = std::fs::rename(self.numbered(n), self.numbered(n + 1));
+        }
+        if self.keep > 0 {
+            let _ = std::fs::rename(&self.path, self.numbered(1));
+        } else {
+            let _ = std::fs::remove_file(&self.path);
+        }
+        if let Ok(file) = Self::append(&self.path) {
+            self.file = file;
+            self.size = 0;
+        }
+    }
+}
+
+/// systemd journal, through its native socket protocol
+#[cfg(target_os = "linux")]
+mod journal {
+    use log::{Level, Record};
+    use std::os::unix::net::UnixDatagram;
+
+    const SOCKET: &str = "/run/systemd/journal/socket";
+
+    pub struct Journal {
+        socket: UnixDatagram,
+    }
+
+    impl Journal {
+        pub fn open() -> Result<Self, String> {
+            let socket = UnixDatagram::unbound().map_err(|e| e.to_string())?;
+            socket
+                .connect(SOCKET)
+                .map_err(|e| format!("{}: {}", SOCKET, e))?;
+            Ok(Self { socket })
+        }
+
+        pub fn send(&self, record: &Record) {
+            let priority = match record.level() {
+                Level::Error => "3",
+                Level::Warn => "4",
+                Level::Info => "6",
+                Level::Debug | Level::Trace => "7",
+            };
+            let mut entry = Vec::new();
+            field(&mut entry, "MESSAGE", &record.args().to_string());
+            field(&mut entry, "PRIORITY", priority);
+            field(&mut entry, "SYSLOG_IDENTIFIER", "rbn-vfd");
+            field(&mut entry, "TARGET", record.target());
+            if let Some(file) = record.file() {
+                field(&mut entry, "CODE_FILE", file);
+            }
+            if let Some(line) = record.line() {
+                field(&mut entry, "CODE_LINE", &line.to_string());
+            }
+            let _ = self.socket.send(&entry);
+        }
+    }
+
+    /// `NAME=value` line, or for values with newlines the length-prefixed
+    /// binary form
+    fn field(entry: &mut Vec<u8>, name: &str, value: &str) {
+        entry.extend_from_slice(name.as_bytes());
+        if value.contains('\n') {
+            entry.push(b'\n');
+            entry.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        } else {
+            entry.push(b'=');
+        }
+        entry.extend_from_slice(value.as_bytes());
+        entry.push(b'\n');
+    }
+}
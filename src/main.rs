@@ -1,19 +1,196 @@
-mod app;
-mod config;
-mod models;
-mod services;
+use clap::{Parser, Subcommand};
+use rbn_vfd::config::{Config, Overrides};
+use rbn_vfd::headless::Format;
+use rbn_vfd::models::{normalize_callsign, Band};
+use rbn_vfd::paths;
+use std::path::PathBuf;
+
+/// RBN spots on a VFD, LCD or OLED
+///
+/// Options given here apply over settings.toml for this run only.
+#[derive(Debug, Parser)]
+#[command(version)]
+struct Cli {
+    /// Settings file to use instead of the platform default
+    #[arg(long, value_name = "PATH", global = true)]
+    config: Option<PathBuf>,
+
+    /// Start in this `[profile.<name>]` ("" for the main settings)
+    #[arg(long, value_name = "NAME", global = true)]
+    profile: Option<String>,
+
+    /// Callsign to log in to RBN with
+    #[arg(long, value_parser = normalize_callsign, global = true)]
+    callsign: Option<String>,
+
+    /// Minimum SNR in dB
+    #[arg(long, value_name = "DB", global = true)]
+    min_snr: Option<i32>,
+
+    /// Only show spots on this band, e.g. "20m" ("all" for every band)
+    #[arg(long, value_parser = parse_band, global = true)]
+    band: Option<String>,
+
+    /// Play back a recorded feed instead of connecting to RBN
+    #[arg(long, value_name = "FILE", global = true)]
+    replay: Option<PathBuf>,
+
+    /// Run without a display, writing filtered spots to stdout
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        num_args = 0..=1,
+        default_missing_value = "json",
+        value_parser = parse_format
+    )]
+    headless: Option<Format>,
+
+    /// Print where settings, captures and cached files are kept, then exit
+    #[arg(long)]
+    print_paths: bool,
+
+    /// Full-screen terminal UI (needs the 'tui' cargo feature)
+    #[arg(long, conflicts_with = "headless")]
+    tui: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Diagnostics
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Step each configured display through test patterns and time them
+    DisplayTest {
+        /// Serial port of the main display (default: the configured one)
+        port: Option<String>,
+    },
+    /// List serial ports with their USB IDs
+    Ports,
+    /// List the display drivers and radio backends in this build
+    Drivers,
+    /// Print the settings in effect, options included (secrets hidden)
+    Config,
+    /// Print a commented settings.toml with every default, to start from
+    GenerateConfig,
+    /// Ask for callsign, spot source, radio and display, and save them
+    Setup,
+    /// Check the settings and probe the radio, displays and spot server
+    /// they name, changing nothing; exits with status 1 on any problem
+    CheckConfig {
+        /// Only check the settings, without touching devices or the network
+        #[arg(long)]
+        offline: bool,
+    },
+}
+
+fn parse_band(name: &str) -> Result<String, String> {
+    if name.eq_ignore_ascii_case("all") || Band::from_name(name).is_some() {
+        Ok(name.to_string())
+    } else {
+        Err(format!("unknown band '{}'", name))
+    }
+}
+
+fn parse_format(name: &str) -> Result<Format, String> {
+    Format::parse(name).ok_or_else(|| format!("unknown output format '{}' (json or text)", name))
+}
 
 fn main() -> eframe::Result<()> {
-    let options = eframe::NativeOptions {
-        viewport: eframe::egui::ViewportBuilder::default()
-            .with_inner_size([500.0, 600.0])
-            .with_min_inner_size([400.0, 400.0]),
-        ..Default::default()
+    let cli = Cli::parse();
+    Config::set_overrides(Overrides {
+        config_path: cli.config,
+        profile: cli.profile,
+        callsign: cli.callsign,
+        min_snr: cli.min_snr,
+        band_filter: cli.band.as_deref().map(Band::from_name),
+        replay_file: cli.replay,
+    });
+
+    if cli.print_paths {
+        print_paths();
+        return Ok(());
+    }
+
+    // First start at a terminal: ask for the basics before anything opens
+    if cli.command.is_none() && rbn_vfd::setup::wanted() {
+        if let Err(e) = rbn_vfd::setup::run(true) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+
+    let result = match cli.command {
+        Some(Command::DisplayTest { port }) => rbn_vfd::display_test::run(port),
+        Some(Command::Ports) => {
+            for (port, id) in rbn_vfd::services::list_ports() {
+                match id {
+                    Some(id) => println!("{}  {}", port, id),
+                    None => println!("{}", port),
+                }
+            }
+            Ok(())
+        }
+        Some(Command::Drivers) => {
+            println!(
+                "Displays: {}",
+                rbn_vfd::services::display::registered_drivers().join(", ")
+            );
+            println!(
+                "Radios: {}",
+                rbn_vfd::services::radio::registered_backends().join(", ")
+            );
+            Ok(())
+        }
+        Some(Command::Config) => {
+            if let Some(path) = Config::config_path() {
+                println!("# {}", path.display());
+            }
+            Config::load()
+                .redacted()
+                .to_toml()
+                .map(|text| print!("{}", text))
+        }
+        Some(Command::GenerateConfig) => Config::sample_toml().map(|text| print!("{}", text)),
+        Some(Command::Setup) => rbn_vfd::setup::run(false),
+        Some(Command::CheckConfig { offline }) => rbn_vfd::check::run(offline),
+        None => match cli.headless {
+            Some(format) => rbn_vfd::headless::run(format),
+            None if cli.tui => run_tui(),
+            None => return rbn_vfd::run(),
+        },
     };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn print_paths() {
+    let show = |label: &str, path: Option<PathBuf>| match path {
+        Some(path) if path.exists() => println!("{:<9} {}", label, path.display()),
+        Some(path) => println!("{:<9} {} (missing)", label, path.display()),
+        None => println!("{:<9} unknown", label),
+    };
+    show("config", Config::config_path());
+    show("data", paths::data_dir());
+    show("captures", paths::captures_dir());
+    show("dumps", paths::dumps_dir());
+    show("log", paths::log_file());
+    show("cache", paths::cache_dir());
+    show("cty.dat", paths::cty_file());
+    show("lotw", paths::lotw_file());
+    show("wanted", paths::clublog_most_wanted_file());
+    show("clublog", paths::clublog_log_file());
+}
+
+#[cfg(feature = "tui")]
+fn run_tui() -> Result<(), String> {
+    rbn_vfd::tui::run().map_err(|e| e.to_string())
+}
 
-    eframe::run_native(
-        "RBN VFD Display",
-        options,
-        Box::new(|cc| Ok(Box::new(app::RbnVfdApp::new(cc)))),
-    )
+#[cfg(not(feature = "tui"))]
+fn run_tui() -> Result<(), String> {
+    Err("--tui needs the 'tui' cargo feature".to_string())
 }
@@ -0,0 +1,136 @@
+use chrono::{DateTime, Utc};
+use std::fmt;
+
+/// What kind of non-spot cluster line an announcement came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnouncementKind {
+    /// "WWV de ..." solar flux / A / K index report
+    Wwv,
+    /// "WCY de ..." propagation report from DK0WCY
+    Wcy,
+    /// "To ALL de ..." announcement to every user
+    Announce,
+    /// "To <call> de ..." talk message to one user
+    Talk,
+}
+
+/// WWV/WCY line or announcement captured from the feed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Announcement {
+    pub kind: AnnouncementKind,
+    /// Station that sent it
+    pub from: String,
+    /// Recipient of a talk message ("ALL" for announcements)
+    pub to: String,
+    /// Message with runs of spaces collapsed
+    pub text: String,
+    pub received: DateTime<Utc>,
+}
+
+impl Announcement {
+    /// Parse a cluster line such as
+    /// "WWV de W0MU <18>:   SFI=150, A=8, K=2, No Storms -> No Storms" or
+    /// "To ALL de K1ABC: QRV 20m CW from FO" (None for spots and anything
+    /// else)
+    pub fn parse(line: &str) -> Option<Self> {
+        let (head, text) = line.trim().split_once(':')?;
+        let mut words = head.split_whitespace();
+        let (kind, to) = match words.next()?.to_ascii_uppercase().as_str() {
+            "WWV" => (AnnouncementKind::Wwv, "ALL".to_string()),
+            "WCY" => (AnnouncementKind::Wcy, "ALL".to_string()),
+            "TO" => {
+                let to = words.next()?.to_ascii_uppercase();
+                if to.starts_with("ALL") || to.starts_with("LOCAL") {
+                    (AnnouncementKind::Announce, to)
+                } else {
+                    (AnnouncementKind::Talk, to)
+                }
+            }
+            _ => return None,
+        };
+        if !words.next()?.eq_ignore_ascii_case("de") {
+            return None;
+        }
+        let from = words.next()?.to_ascii_uppercase();
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if text.is_empty() {
+            return None;
+        }
+        Some(Self {
+            kind,
+            from,
+            to,
+            text,
+            received: Utc::now(),
+        })
+    }
+
+    /// Indices from a WWV or WCY report (None for other announcements)
+    pub fn propagation(&self) -> Option<Propagation> {
+        match self.kind {
+            AnnouncementKind::Wwv | AnnouncementKind::Wcy => Propagation::parse(&self.text),
+            AnnouncementKind::Announce | AnnouncementKind::Talk => None,
+        }
+    }
+}
+
+/// Solar flux and geomagnetic indices from a WWV/WCY report
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Propagation {
+    /// Solar flux index
+    pub sfi: Option<u32>,
+    pub a_index: Option<u32>,
+    pub k_index: Option<u32>,
+}
+
+impl Propagation {
+    /// Indices from "SFI=150, A=8, K=2, ..." style text (None when it has
+    /// none of them)
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut propagation = Self::default();
+        for token in text.split(|c: char| c.is_whitespace() || c == ',') {
+            let Some((key, value)) = token.split_once('=') else {
+                continue;
+            };
+            let value = value.trim().parse().ok();
+            match key.trim().to_ascii_uppercase().as_str() {
+                "SFI" => propagation.sfi = value,
+                "A" => propagation.a_index = value,
+                "K" => propagation.k_index = value,
+                _ => {}
+            }
+        }
+        (propagation != Self::default()).then_some(propagation)
+    }
+
+    /// Fill indices this report lacks from an earlier one
+    pub fn or(self, earlier: Self) -> Self {
+        Self {
+            sfi: self.sfi.or(earlier.sfi),
+            a_index: self.a_index.or(earlier.a_index),
+            k_index: self.k_index.or(earlier.k_index),
+        }
+    }
+
+    /// Example: "SFI 142 A 8 K 2"
+    pub fn summary(&self) -> String {
+        [("SFI", self.sfi), ("A", self.a_index), ("K", self.k_index)]
+            .iter()
+            .filter_map(|(name, value)| value.map(|value| format!("{} {}", name, value)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl fmt::Display for Announcement {
+    /// Example: "WWV SFI=150, A=8, K=2" / "K1ABC: QRV 20m" /
+    /// "W1XYZ>K1ABC: tnx QSO"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            AnnouncementKind::Wwv => write!(f, "WWV {}", self.text),
+            AnnouncementKind::Wcy => write!(f, "WCY {}", self.text),
+            AnnouncementKind::Announce => write!(f, "{}: {}", self.from, self.text),
+            AnnouncementKind::Talk => write!(f, "{}>{}: {}", self.from, self.to, self.text),
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Amateur HF/6m bands covered by RBN skimmers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Band {
+    B160m,
+    B80m,
+    B60m,
+    B40m,
+    B30m,
+    B20m,
+    B17m,
+    B15m,
+    B12m,
+    B10m,
+    B6m,
+}
+
+impl Band {
+    /// All bands, lowest frequency first
+    pub const ALL: [Band; 11] = [
+        Band::B160m,
+        Band::B80m,
+        Band::B60m,
+        Band::B40m,
+        Band::B30m,
+        Band::B20m,
+        Band::B17m,
+        Band::B15m,
+        Band::B12m,
+        Band::B10m,
+        Band::B6m,
+    ];
+
+    /// Determine the band for a frequency in kHz (None if out of band)
+    pub fn from_khz(frequency_khz: f64) -> Option<Self> {
+        Self::ALL.into_iter().find(|band| {
+            let (low, high) = band.edges_khz();
+            frequency_khz >= low && frequency_khz <= high
+        })
+    }
+
+    /// Band from its short name, e.g. "20m" (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|band| band.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Step a band filter up (+1) or down (-1), wrapping through None
+    /// (all bands) between 6m and 160m
+    pub fn cycle(band: Option<Band>, step: isize) -> Option<Band> {
+        let positions = Self::ALL.len() as isize + 1;
+        let current = match band {
+            None => 0,
+            Some(band) => Self::ALL.iter().position(|b| *b == band).unwrap_or(0) as isize + 1,
+        };
+        let next = (current + step).rem_euclid(positions);
+        (next > 0).then(|| Self::ALL[next as usize - 1])
+    }
+
+    /// Short band name, e.g. "20m"
+    pub fn name(self) -> &'static str {
+        match self {
+            Band::B160m => "160m",
+            Band::B80m => "80m",
+            Band::B60m => "60m",
+            Band::B40m => "40m",
+            Band::B30m => "30m",
+            Band::B20m => "20m",
+            Band::B17m => "17m",
+            Band::B15m => "15m",
+            Band::B12m => "12m",
+            Band::B10m => "10m",
+            Band::B6m => "6m",
+        }
+    }
+
+    /// Band edges in a region's band plan, where it is narrower than the
+    /// widest allocation
+    pub fn edges_in(self, region: Option<Region>) -> (f64, f64) {
+        match (self, region) {
+            (Band::B160m, Some(Region::One)) => (1810.0, 2000.0),
+            (Band::B80m, Some(Region::One)) => (3500.0, 3800.0),
+            (Band::B80m, Some(Region::Three)) => (3500.0, 3900.0),
+            (Band::B40m, Some(Region::One | Region::Three)) => (7000.0, 7200.0),
+            _ => self.edges_khz(),
+        }
+    }
+
+    /// Lower and upper band edges in kHz (widest IARU allocation)
+    pub fn edges_khz(self) -> (f64, f64) {
+        match self {
+            Band::B160m => (1800.0, 2000.0),
+            Band::B80m => (3500.0, 4000.0),
+            Band::B60m => (5330.0, 5410.0),
+            Band::B40m => (7000.0, 7300.0),
+            Band::B30m => (10100.0, 10150.0),
+            Band::B20m => (14000.0, 14350.0),
+            Band::B17m => (18068.0, 18168.0),
+            Band::B15m => (21000.0, 21450.0),
+            Band::B12m => (24890.0, 24990.0),
+            Band::B10m => (28000.0, 29700.0),
+            Band::B6m => (50000.0, 54000.0),
+        }
+    }
+}
+
+/// IARU region, choosing the band plan
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Region {
+    /// Europe, Africa, Middle East, northern Asia
+    One,
+    /// The Americas
+    Two,
+    /// Asia-Pacific
+    Three,
+}
+
+impl Region {
+    /// Region from its number, 1-3 (None for anything else)
+    pub fn from_number(number: u8) -> Option<Self> {
+        match number {
+            1 => Some(Region::One),
+            2 => Some(Region::Two),
+            3 => Some(Region::Three),
+            _ => None,
+        }
+    }
+
+    pub fn number(self) -> u8 {
+        match self {
+            Region::One => 1,
+            Region::Two => 2,
+            Region::Three => 3,
+        }
+    }
+}
+
+impl std::fmt::Display for Band {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Written as its short name ("20m") in settings.toml
+impl Serialize for Band {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Band {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Band::from_name(&name).ok_or_else(|| D::Error::custom(format!("unknown band '{}'", name)))
+    }
+}
@@ -0,0 +1,90 @@
+use super::{AggregatedSpot, Band, Region};
+
+/// Narrowest span shown on a band map, in kHz
+const MIN_SPAN_KHZ: f64 = 10.0;
+
+/// Frequency range of a band map ladder, fitted around the spots and VFO
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BandMap {
+    pub band: Band,
+    pub low_khz: f64,
+    pub high_khz: f64,
+}
+
+impl BandMap {
+    /// Band to map: the VFO's band, else the band with the most spots
+    pub fn current_band(spots: &[AggregatedSpot], vfo_khz: Option<f64>) -> Option<Band> {
+        if let Some(band) = vfo_khz.and_then(Band::from_khz) {
+            return Some(band);
+        }
+        Band::ALL
+            .into_iter()
+            .map(|band| (Self::on_band(spots, band).count(), band))
+            .filter(|(count, _)| *count > 0)
+            .max_by_key(|(count, _)| *count)
+            .map(|(_, band)| band)
+    }
+
+    /// Fit a map of `band` around its spots and the VFO, within the band
+    /// edges of the region's band plan
+    pub fn fit(
+        band: Band,
+        spots: &[AggregatedSpot],
+        vfo_khz: Option<f64>,
+        region: Option<Region>,
+    ) -> Self {
+        let (edge_low, edge_high) = band.edges_in(region);
+        let frequencies = Self::on_band(spots, band)
+            .map(|spot| spot.frequency_khz)
+            .chain(vfo_khz.filter(|khz| Band::from_khz(*khz) == Some(band)));
+        let (low, high) = frequencies.fold((f64::MAX, f64::MIN), |(low, high), khz| {
+            (low.min(khz), high.max(khz))
+        });
+        if low > high {
+            return Self {
+                band,
+                low_khz: edge_low,
+                high_khz: edge_high,
+            };
+        }
+
+        // Pad by a tenth either side and keep at least the minimum span
+        let span = ((high - low) * 1.2).max(MIN_SPAN_KHZ);
+        let center = (low + high) / 2.0;
+        let low_khz = (center - span / 2.0).max(edge_low);
+        let high_khz = (low_khz + span).min(edge_high);
+        Self {
+            band,
+            low_khz: (high_khz - span).max(edge_low),
+            high_khz,
+        }
+    }
+
+    /// Spots on this map's band
+    pub fn spots<'a>(
+        &self,
+        spots: &'a [AggregatedSpot],
+    ) -> impl Iterator<Item = &'a AggregatedSpot> + 'a {
+        Self::on_band(spots, self.band)
+    }
+
+    /// Cell (0 = low end) for a frequency on a ladder `cells` long
+    pub fn cell(&self, frequency_khz: f64, cells: usize) -> Option<usize> {
+        if cells == 0 || frequency_khz < self.low_khz || frequency_khz > self.high_khz {
+            return None;
+        }
+        let fraction = (frequency_khz - self.low_khz) / (self.high_khz - self.low_khz);
+        Some(((fraction * cells as f64) as usize).min(cells - 1))
+    }
+
+    /// Frequency at the low edge of a cell
+    pub fn frequency_at(&self, cell: usize, cells: usize) -> f64 {
+        self.low_khz + (self.high_khz - self.low_khz) * cell as f64 / cells.max(1) as f64
+    }
+
+    fn on_band(spots: &[AggregatedSpot], band: Band) -> impl Iterator<Item = &AggregatedSpot> {
+        spots
+            .iter()
+            .filter(move |spot| Band::from_khz(spot.frequency_khz) == Some(band))
+    }
+}
@@ -0,0 +1,28 @@
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// An optional "prefix/" (e.g. "VE3/"), the call itself (prefix, a digit,
+/// then a suffix ending in a letter), an optional "/suffix" (e.g. "/P",
+/// "/QRP", "/7") and an optional "-SSID" (e.g. "-1")
+const PATTERN: &str =
+    r"^([A-Z0-9]{1,4}/)?[A-Z0-9]{1,3}[0-9][A-Z0-9]{0,4}[A-Z](/[A-Z0-9]{1,4})?(-[0-9]{1,2})?$";
+
+/// A callsign as typed, trimmed and upper-cased; an error says what's
+/// wrong with anything that can't be one, so RBN doesn't turn down the login
+/// without a word
+pub fn normalize_callsign(text: &str) -> Result<String, String> {
+    static CALLSIGN: OnceLock<Regex> = OnceLock::new();
+    let callsign = text.trim().to_uppercase();
+    if callsign.is_empty() {
+        return Err("No callsign set".to_string());
+    }
+    let pattern = CALLSIGN.get_or_init(|| Regex::new(PATTERN).expect("valid callsign pattern"));
+    if pattern.is_match(&callsign) {
+        Ok(callsign)
+    } else {
+        Err(format!(
+            "'{}' doesn't look like a callsign (e.g. W6JSV, VE3/W6JSV, W6JSV/P or W6JSV-1)",
+            callsign
+        ))
+    }
+}
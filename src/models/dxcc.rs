@@ -0,0 +1,146 @@
+use super::LatLon;
+use std::collections::HashMap;
+
+/// Callsign suffixes that don't change the entity
+const PORTABLE_SUFFIXES: [&str; 6] = ["P", "M", "MM", "AM", "QRP", "A"];
+
+/// Callsign to DXCC entity lookup from a country file in the `cty.dat`
+/// format (https://www.country-files.com/)
+#[derive(Debug, Clone, Default)]
+pub struct DxccTable {
+    /// Prefix -> entity primary prefix
+    prefixes: HashMap<String, String>,
+    /// Full callsigns listed with `=` -> entity primary prefix
+    exact: HashMap<String, String>,
+    /// Entity primary prefix -> details
+    entities: HashMap<String, DxccEntity>,
+}
+
+/// Header details of a country file entity
+#[derive(Debug, Clone, PartialEq)]
+pub struct DxccEntity {
+    pub name: String,
+    pub cq_zone: u8,
+    /// Two-letter code: AF, AN, AS, EU, NA, OC or SA
+    pub continent: String,
+    pub location: LatLon,
+}
+
+impl DxccTable {
+    /// Read a country file from disk
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read country file {}: {}", path, e))?;
+        Ok(Self::parse(&text))
+    }
+
+    /// Parse `cty.dat` records: a header line of colon separated fields
+    /// ending with the primary prefix, then comma separated prefixes up to
+    /// a `;`. WAE-only entities (primary prefix starting with `*`) are
+    /// skipped so their calls fall back to the DXCC entity.
+    pub fn parse(text: &str) -> Self {
+        let mut table = Self::default();
+        let mut entity: Option<String> = None;
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let Some(current) = entity.clone() else {
+                let fields: Vec<&str> = line.split(':').map(str::trim).collect();
+                if fields.len() >= 8 {
+                    let number = |field: &str| field.parse::<f64>().unwrap_or_default();
+                    let details = DxccEntity {
+                        name: fields[0].to_string(),
+                        cq_zone: fields[1].parse().unwrap_or_default(),
+                        continent: fields[3].to_uppercase(),
+                        // The file has longitude positive west
+                        location: LatLon {
+                            lat: number(fields[4]),
+                            lon: -number(fields[5]),
+                        },
+                    };
+                    table.entities.insert(fields[7].to_string(), details);
+                    entity = Some(fields[7].to_string());
+                }
+                continue;
+            };
+            let (list, done) = match line.strip_suffix(';') {
+                Some(list) => (list, true),
+                None => (line, false),
+            };
+            if !current.starts_with('*') {
+                for prefix in list.split(',').map(strip_overrides) {
+                    if let Some(call) = prefix.strip_prefix('=') {
+                        table.exact.insert(call.to_string(), current.clone());
+                    } else if !prefix.is_empty() {
+                        table.prefixes.insert(prefix.to_string(), current.clone());
+                    }
+                }
+            }
+            if done {
+                entity = None;
+            }
+        }
+        table
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// Primary prefix of the entity a callsign belongs to, e.g. "VP8" for
+    /// "VP8/SQ1SGB/MM"'s operating location
+    pub fn entity(&self, callsign: &str) -> Option<&str> {
+        let callsign = callsign.trim().to_uppercase();
+        if let Some(entity) = self.exact.get(&callsign) {
+            return Some(entity.as_str());
+        }
+        let base = location_part(&callsign);
+        if !base.is_ascii() {
+            return None;
+        }
+        (1..=base.len())
+            .rev()
+            .find_map(|len| self.prefixes.get(&base[..len]))
+            .map(String::as_str)
+    }
+
+    /// Entity name for a primary prefix
+    pub fn name(&self, entity: &str) -> Option<&str> {
+        self.details(entity).map(|details| details.name.as_str())
+    }
+
+    /// Name, CQ zone and location for a primary prefix
+    pub fn details(&self, entity: &str) -> Option<&DxccEntity> {
+        self.entities.get(entity)
+    }
+}
+
+/// Drop the CQ/ITU zone and other per-prefix overrides: `(5)`, `[8]`,
+/// `<lat/lon>`, `{continent}`, `~offset~`
+fn strip_overrides(prefix: &str) -> &str {
+    let prefix = prefix.trim();
+    let end = prefix
+        .find(['(', '[', '<', '{', '~'])
+        .unwrap_or(prefix.len());
+    &prefix[..end]
+}
+
+/// Part of a callsign that says where it operates from: the prefix side of
+/// "VP8/SQ1SGB" or "SQ1SGB/VP8", ignoring suffixes like /P, /M, /QRP
+fn location_part(callsign: &str) -> &str {
+    let parts: Vec<&str> = callsign
+        .split('/')
+        .filter(|part| !part.is_empty() && !PORTABLE_SUFFIXES.contains(part))
+        // Call area changes like "/4" keep the home prefix
+        .filter(|part| !(part.len() == 1 && part.chars().all(|c| c.is_ascii_digit())))
+        .collect();
+    match parts[..] {
+        [] => callsign,
+        [call] => call,
+        // Shorter part is the prefix
+        [first, second, ..] if second.len() < first.len() => second,
+        [first, ..] => first,
+    }
+}
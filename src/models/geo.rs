@@ -0,0 +1,109 @@
+use chrono::{DateTime, Days, NaiveDate, TimeDelta, Utc};
+
+/// Mean earth radius in km
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// Sun altitude at sunrise/sunset, allowing for refraction and the disc
+const SUNRISE_ALTITUDE: f64 = -0.833;
+
+/// Axial tilt of the earth in degrees
+const OBLIQUITY: f64 = 23.4397;
+
+/// Julian day of the Unix epoch and of J2000
+const JULIAN_UNIX_EPOCH: f64 = 2440587.5;
+const JULIAN_2000: f64 = 2451545.0;
+
+/// How close to sunrise or sunset counts as the grey line
+const GREYLINE_WINDOW: TimeDelta = TimeDelta::minutes(30);
+
+/// Latitude and longitude in degrees (north and east positive)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LatLon {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+impl LatLon {
+    /// Center of a 4 or 6 character Maidenhead locator, e.g. "CM87" or
+    /// "CM87wj"
+    pub fn from_grid(grid: &str) -> Option<Self> {
+        let grid: Vec<char> = grid.trim().to_ascii_uppercase().chars().collect();
+        if !matches!(grid.len(), 4 | 6) {
+            return None;
+        }
+        let letter = |c: char, last: char| ('A'..=last).position(|l| l == c);
+        let digit = |c: char| c.to_digit(10);
+
+        let mut lon = letter(grid[0], 'R')? as f64 * 20.0 - 180.0 + digit(grid[2])? as f64 * 2.0;
+        let mut lat = letter(grid[1], 'R')? as f64 * 10.0 - 90.0 + digit(grid[3])? as f64;
+        if grid.len() == 6 {
+            lon += letter(grid[4], 'X')? as f64 / 12.0 + 1.0 / 24.0;
+            lat += letter(grid[5], 'X')? as f64 / 24.0 + 1.0 / 48.0;
+        } else {
+            lon += 1.0;
+            lat += 0.5;
+        }
+        Some(Self { lat, lon })
+    }
+
+    /// Great-circle distance in km and initial bearing in degrees (0-360)
+    /// from here to `other`
+    pub fn distance_bearing(&self, other: &LatLon) -> (f64, f64) {
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let dlon = (other.lon - self.lon).to_radians();
+        let dlat = lat2 - lat1;
+
+        let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+        let distance = 2.0 * EARTH_RADIUS_KM * a.sqrt().atan2((1.0 - a).sqrt());
+
+        let y = dlon.sin() * lat2.cos();
+        let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlon.cos();
+        let bearing = y.atan2(x).to_degrees().rem_euclid(360.0);
+        (distance, bearing)
+    }
+
+    /// Sunrise and sunset here on a UTC date (None during polar day or
+    /// night), from the NOAA sunrise equation
+    pub fn sun_times(&self, date: NaiveDate) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let midnight = date.and_hms_opt(0, 0, 0)?.and_utc().timestamp() as f64 / 86400.0;
+        // Days from J2000 to local solar noon
+        let day = midnight + JULIAN_UNIX_EPOCH + 0.5 - JULIAN_2000 + 0.0008 - self.lon / 360.0;
+        let anomaly = (357.5291 + 0.98560028 * day).rem_euclid(360.0).to_radians();
+        let center = 1.9148 * anomaly.sin()
+            + 0.0200 * (2.0 * anomaly).sin()
+            + 0.0003 * (3.0 * anomaly).sin();
+        let ecliptic = (anomaly.to_degrees() + center + 180.0 + 102.9372)
+            .rem_euclid(360.0)
+            .to_radians();
+        let transit = JULIAN_2000 + day + 0.0053 * anomaly.sin() - 0.0069 * (2.0 * ecliptic).sin();
+        let declination = (ecliptic.sin() * OBLIQUITY.to_radians().sin()).asin();
+
+        let lat = self.lat.to_radians();
+        let cos_hour_angle = (SUNRISE_ALTITUDE.to_radians().sin() - lat.sin() * declination.sin())
+            / (lat.cos() * declination.cos());
+        if !(-1.0..=1.0).contains(&cos_hour_angle) {
+            return None;
+        }
+        let half_day = cos_hour_angle.acos().to_degrees() / 360.0;
+        let to_utc = |julian: f64| {
+            DateTime::from_timestamp(((julian - JULIAN_UNIX_EPOCH) * 86400.0).round() as i64, 0)
+        };
+        Some((to_utc(transit - half_day)?, to_utc(transit + half_day)?))
+    }
+
+    /// Whether `time` is within `GREYLINE_WINDOW` of sunrise or sunset here
+    pub fn in_greyline(&self, time: DateTime<Utc>) -> bool {
+        let today = time.date_naive();
+        // Neighbouring dates too, as a UTC date can miss a local sunset
+        [
+            today.checked_sub_days(Days::new(1)),
+            Some(today),
+            today.checked_add_days(Days::new(1)),
+        ]
+        .into_iter()
+        .flatten()
+        .filter_map(|date| self.sun_times(date))
+        .flat_map(|(rise, set)| [rise, set])
+        .any(|event| (time - event).abs() <= GREYLINE_WINDOW)
+    }
+}
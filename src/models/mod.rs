@@ -1,3 +1,21 @@
+mod announcement;
+mod band;
+mod band_map;
+mod callsign;
+mod dxcc;
+mod geo;
 mod spot;
+mod spot_record;
+mod units;
 
-pub use spot::{AggregatedSpot, RawSpot};
+pub use announcement::{Announcement, AnnouncementKind, Propagation};
+pub use band::{Band, Region};
+pub use band_map::BandMap;
+pub use callsign::normalize_callsign;
+pub use dxcc::{DxccEntity, DxccTable};
+pub use geo::LatLon;
+pub use spot::{find_spot, format_offset, nearest_spot, AggregatedSpot, RawSpot, SpotSort};
+pub use spot_record::SpotRecord;
+pub use units::{
+    local_minute_of_day, parse_timezone, Clocks, DateStyle, DistanceUnit, FrequencyUnit, Units,
+};
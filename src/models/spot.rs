@@ -1,9 +1,13 @@
+use super::Units;
+use serde::Deserialize;
 use std::time::Instant;
 
+/// Spots further than this from the VFO aren't the one being listened to
+const NEAREST_SPOT_MAX_KHZ: f64 = 3.0;
+
 /// Raw spot data as received from RBN telnet
 #[derive(Debug, Clone)]
 pub struct RawSpot {
-    #[allow(dead_code)]
     pub spotter_callsign: String,
     pub spotted_callsign: String,
     pub frequency_khz: f64,
@@ -46,8 +50,12 @@ pub struct AggregatedSpot {
     pub highest_snr: i32,
     pub average_speed: f64,
     pub spot_count: u32,
+    pub first_spotted: Instant,
     pub last_spotted: Instant,
     pub mode: String,
+    /// Skimmers that heard it, with the best SNR from each, in the order
+    /// they first reported
+    pub skimmers: Vec<(String, i32)>,
 }
 
 impl AggregatedSpot {
@@ -60,8 +68,10 @@ impl AggregatedSpot {
             highest_snr: raw.snr,
             average_speed: raw.speed_wpm as f64,
             spot_count: 1,
+            first_spotted: Instant::now(),
             last_spotted: Instant::now(),
             mode: raw.mode.clone(),
+            skimmers: vec![(raw.spotter_callsign.clone(), raw.snr)],
         }
     }
 
@@ -75,6 +85,14 @@ impl AggregatedSpot {
         }
         self.last_spotted = Instant::now();
         self.mode = raw.mode.clone();
+        match self
+            .skimmers
+            .iter_mut()
+            .find(|(skimmer, _)| *skimmer == raw.spotter_callsign)
+        {
+            Some((_, snr)) => *snr = (*snr).max(raw.snr),
+            None => self.skimmers.push((raw.spotter_callsign.clone(), raw.snr)),
+        }
     }
 
     /// Generate the unique key for this spot (callsign + center frequency)
@@ -114,4 +132,91 @@ impl AggregatedSpot {
             call
         )
     }
+
+    /// Format as a page of lines for the compact one-spot-per-page layout
+    /// Example: ["WO6W", "14033.0 kHz", "SNR 23 dB 22 WPM", "Age 1m05s"]
+    pub fn to_compact_lines(&self, units: &Units) -> [String; 4] {
+        let age = self.age_seconds();
+        [
+            self.callsign.clone(),
+            units.frequency_with_unit(self.frequency_khz),
+            format!(
+                "SNR {} dB {} WPM",
+                self.highest_snr,
+                self.average_speed.round() as i32
+            ),
+            format!("Age {}m{:02}s", age / 60, age % 60),
+        ]
+    }
+}
+
+/// Index of the spot with this callsign within half a kHz of `khz`, to
+/// find a selected spot again in a fresh list
+pub fn find_spot(spots: &[AggregatedSpot], callsign: &str, khz: f64) -> Option<usize> {
+    spots
+        .iter()
+        .position(|spot| spot.callsign == callsign && (spot.frequency_khz - khz).abs() < 0.5)
+}
+
+/// Spot closest to the VFO (within a few kHz) and its offset from the VFO
+/// in kHz (positive when the spot is above)
+pub fn nearest_spot(spots: &[AggregatedSpot], vfo_khz: f64) -> Option<(&AggregatedSpot, f64)> {
+    spots
+        .iter()
+        .map(|spot| (spot, spot.frequency_khz - vfo_khz))
+        .filter(|(_, offset)| offset.abs() <= NEAREST_SPOT_MAX_KHZ)
+        .min_by(|(_, a), (_, b)| a.abs().total_cmp(&b.abs()))
+}
+
+/// Short signed offset, e.g. "+0.3" or "-2.5"
+pub fn format_offset(offset_khz: f64) -> String {
+    format!("{:+.1}", offset_khz)
+}
+
+/// Order spot lists are shown in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SpotSort {
+    /// Lowest frequency first
+    #[default]
+    Frequency,
+    /// Most recently spotted first
+    Recency,
+    /// Strongest first
+    Snr,
+}
+
+impl SpotSort {
+    pub const ALL: [SpotSort; 3] = [SpotSort::Frequency, SpotSort::Recency, SpotSort::Snr];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SpotSort::Frequency => "frequency",
+            SpotSort::Recency => "recency",
+            SpotSort::Snr => "snr",
+        }
+    }
+
+    /// Order by name (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|sort| sort.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// The order after this one, wrapping
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|sort| *sort == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Put spots in this order; ties keep their frequency order
+    pub fn sort(self, spots: &mut [AggregatedSpot]) {
+        spots.sort_by(|a, b| a.frequency_khz.total_cmp(&b.frequency_khz));
+        match self {
+            SpotSort::Frequency => {}
+            SpotSort::Recency => spots.sort_by_key(|spot| std::cmp::Reverse(spot.last_spotted)),
+            SpotSort::Snr => spots.sort_by_key(|spot| std::cmp::Reverse(spot.highest_snr)),
+        }
+    }
 }
@@ -0,0 +1,30 @@
+use super::{AggregatedSpot, Band};
+use serde::Serialize;
+
+/// Serializable snapshot of an aggregated spot, for JSON outputs
+#[derive(Debug, Clone, Serialize)]
+pub struct SpotRecord {
+    pub callsign: String,
+    pub frequency_khz: f64,
+    pub band: Option<&'static str>,
+    pub snr: i32,
+    pub wpm: i32,
+    pub mode: String,
+    pub age_seconds: u64,
+    pub spot_count: u32,
+}
+
+impl From<&AggregatedSpot> for SpotRecord {
+    fn from(spot: &AggregatedSpot) -> Self {
+        Self {
+            callsign: spot.callsign.clone(),
+            frequency_khz: spot.frequency_khz,
+            band: Band::from_khz(spot.frequency_khz).map(Band::name),
+            snr: spot.highest_snr,
+            wpm: spot.average_speed.round() as i32,
+            mode: spot.mode.clone(),
+            age_seconds: spot.age_seconds(),
+            spot_count: spot.spot_count,
+        }
+    }
+}
@@ -0,0 +1,287 @@
+use chrono::{DateTime, Local, Timelike, Utc};
+use chrono_tz::Tz;
+
+/// Kilometres per statute mile
+const KM_PER_MILE: f64 = 1.609344;
+
+/// Distance unit for paths to spotted stations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DistanceUnit {
+    #[default]
+    Km,
+    Mi,
+}
+
+impl DistanceUnit {
+    pub const ALL: [DistanceUnit; 2] = [DistanceUnit::Km, DistanceUnit::Mi];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DistanceUnit::Km => "km",
+            DistanceUnit::Mi => "mi",
+        }
+    }
+
+    /// Unit by name (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|unit| unit.name().eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+/// Unit frequencies are shown in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FrequencyUnit {
+    #[default]
+    Khz,
+    Mhz,
+}
+
+impl FrequencyUnit {
+    pub const ALL: [FrequencyUnit; 2] = [FrequencyUnit::Khz, FrequencyUnit::Mhz];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FrequencyUnit::Khz => "khz",
+            FrequencyUnit::Mhz => "mhz",
+        }
+    }
+
+    /// Unit by name (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|unit| unit.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Decimal places shown when the settings don't say: 100 Hz steps
+    /// either way, "14025.3" or "14.025"
+    pub fn default_decimals(self) -> usize {
+        match self {
+            FrequencyUnit::Khz => 1,
+            FrequencyUnit::Mhz => 3,
+        }
+    }
+
+    /// Label after a value, "kHz" or "MHz"
+    pub fn label(self) -> &'static str {
+        match self {
+            FrequencyUnit::Khz => "kHz",
+            FrequencyUnit::Mhz => "MHz",
+        }
+    }
+}
+
+/// Order of day, month and year in dates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DateStyle {
+    /// 2024-03-17
+    #[default]
+    Iso,
+    /// 17/03/2024
+    Dmy,
+    /// 03/17/2024
+    Mdy,
+}
+
+impl DateStyle {
+    pub const ALL: [DateStyle; 3] = [DateStyle::Iso, DateStyle::Dmy, DateStyle::Mdy];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DateStyle::Iso => "iso",
+            DateStyle::Dmy => "dmy",
+            DateStyle::Mdy => "mdy",
+        }
+    }
+
+    /// Style by name (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|style| style.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    fn pattern(self) -> &'static str {
+        match self {
+            DateStyle::Iso => "%Y-%m-%d",
+            DateStyle::Dmy => "%d/%m/%Y",
+            DateStyle::Mdy => "%m/%d/%Y",
+        }
+    }
+}
+
+/// Clocks the clock view shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Clocks {
+    Utc,
+    Local,
+    #[default]
+    Both,
+}
+
+impl Clocks {
+    pub const ALL: [Clocks; 3] = [Clocks::Utc, Clocks::Local, Clocks::Both];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Clocks::Utc => "utc",
+            Clocks::Local => "local",
+            Clocks::Both => "both",
+        }
+    }
+
+    /// Clocks by name (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|clocks| clocks.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    pub fn utc(self) -> bool {
+        self != Clocks::Local
+    }
+
+    pub fn local(self) -> bool {
+        self != Clocks::Utc
+    }
+}
+
+/// Time zone by IANA name, e.g. "America/Denver"
+pub fn parse_timezone(name: &str) -> Option<Tz> {
+    name.trim().parse().ok()
+}
+
+/// Minutes since midnight in `zone` (None = the system's zone), for daily
+/// schedules
+pub fn local_minute_of_day(time: DateTime<Utc>, zone: Option<Tz>) -> u32 {
+    let (hour, minute) = match zone {
+        Some(zone) => {
+            let local = time.with_timezone(&zone);
+            (local.hour(), local.minute())
+        }
+        None => {
+            let local = time.with_timezone(&Local);
+            (local.hour(), local.minute())
+        }
+    };
+    hour * 60 + minute
+}
+
+/// How distances, frequencies, times and dates are written for people
+/// (`[units]`); machine-readable outputs stay in kHz and UTC
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Units {
+    pub distance: DistanceUnit,
+    pub frequency: FrequencyUnit,
+    /// Decimal places of frequencies
+    pub decimals: usize,
+    /// Local time instead of UTC
+    pub local_time: bool,
+    /// Zone of local time (None = the system's), so a Pi kept on UTC can
+    /// still show the operator's time, daylight saving included
+    pub timezone: Option<Tz>,
+    /// Clocks the clock view shows
+    pub clocks: Clocks,
+    /// 12-hour clock with am/pm
+    pub twelve_hour: bool,
+    pub date: DateStyle,
+}
+
+impl Default for Units {
+    fn default() -> Self {
+        Self {
+            distance: DistanceUnit::Km,
+            frequency: FrequencyUnit::Khz,
+            decimals: FrequencyUnit::Khz.default_decimals(),
+            local_time: false,
+            timezone: None,
+            clocks: Clocks::Both,
+            twelve_hour: false,
+            date: DateStyle::Iso,
+        }
+    }
+}
+
+impl Units {
+    /// Frequency without its unit, e.g. "14025.3" or "14.025"
+    pub fn frequency(&self, khz: f64) -> String {
+        let value = match self.frequency {
+            FrequencyUnit::Khz => khz,
+            FrequencyUnit::Mhz => khz / 1000.0,
+        };
+        format!("{:.*}", self.decimals, value)
+    }
+
+    /// Frequency with its unit, e.g. "14025.3 kHz"
+    pub fn frequency_with_unit(&self, khz: f64) -> String {
+        format!("{} {}", self.frequency(khz), self.frequency.label())
+    }
+
+    /// Whole distance with its unit, e.g. "8123km" or "5047mi"
+    pub fn distance(&self, km: f64) -> String {
+        let value = match self.distance {
+            DistanceUnit::Km => km,
+            DistanceUnit::Mi => km / KM_PER_MILE,
+        };
+        format!("{:.0}{}", value, self.distance.name())
+    }
+
+    /// Time of day, e.g. "14:23" or "2:23pm", without a zone marker (see
+    /// `zone`)
+    pub fn time(&self, time: DateTime<Utc>) -> String {
+        self.format_time(time, "%H:%M", "%-I:%M%P")
+    }
+
+    /// Time of day with seconds, e.g. "14:23:05" or "2:23:05pm"
+    pub fn time_seconds(&self, time: DateTime<Utc>) -> String {
+        self.format_time(time, "%H:%M:%S", "%-I:%M:%S%P")
+    }
+
+    /// Time of day for narrow columns, e.g. "1423" or "223p"
+    pub fn time_compact(&self, time: DateTime<Utc>) -> String {
+        let mut text = self.format_time(time, "%H%M", "%-I%M%P");
+        if self.twelve_hour {
+            // "pm" to "p"
+            text.pop();
+        }
+        text
+    }
+
+    /// Marker after a time: "Z" for UTC, nothing for local time
+    pub fn zone(&self) -> &'static str {
+        if self.local_time {
+            ""
+        } else {
+            "Z"
+        }
+    }
+
+    /// Date in the configured style and zone, e.g. "2024-03-17"
+    pub fn date(&self, time: DateTime<Utc>) -> String {
+        self.format(time, self.date.pattern())
+    }
+
+    /// Name of the local zone for labels: its abbreviation at `time` when
+    /// one is set ("MST", "MDT"), else "Local"
+    pub fn local_label(&self, time: DateTime<Utc>) -> String {
+        match self.timezone {
+            Some(zone) => time.with_timezone(&zone).format("%Z").to_string(),
+            None => "Local".to_string(),
+        }
+    }
+
+    fn format_time(&self, time: DateTime<Utc>, hours24: &str, hours12: &str) -> String {
+        self.format(time, if self.twelve_hour { hours12 } else { hours24 })
+    }
+
+    /// `time` formatted in UTC or the local zone
+    fn format(&self, time: DateTime<Utc>, pattern: &str) -> String {
+        match (self.local_time, self.timezone) {
+            (false, _) => time.format(pattern).to_string(),
+            (true, Some(zone)) => time.with_timezone(&zone).format(pattern).to_string(),
+            (true, None) => time.with_timezone(&Local).format(pattern).to_string(),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! Where settings and data live: XDG directories on Linux, Known Folders on
+//! Windows and ~/Library on macOS, all through the `directories` crate
+
+use chrono::Utc;
+use directories::ProjectDirs;
+use std::path::PathBuf;
+
+fn project_dirs() -> Option<ProjectDirs> {
+    ProjectDirs::from("com", "w6jsv", "rbn-vfd-display")
+}
+
+/// Default settings file, e.g. ~/.config/rbn-vfd-display/settings.toml
+pub fn config_file() -> Option<PathBuf> {
+    project_dirs().map(|dirs| dirs.config_dir().join("settings.toml"))
+}
+
+/// Files kept between runs, e.g. ~/.local/share/rbn-vfd-display
+pub fn data_dir() -> Option<PathBuf> {
+    project_dirs().map(|dirs| dirs.data_dir().to_path_buf())
+}
+
+/// Files that can be fetched again, e.g. ~/.cache/rbn-vfd-display
+pub fn cache_dir() -> Option<PathBuf> {
+    project_dirs().map(|dirs| dirs.cache_dir().to_path_buf())
+}
+
+/// Country file used when `[alerts] cty_file` is empty
+pub fn cty_file() -> Option<PathBuf> {
+    cache_dir().map(|dir| dir.join("cty.dat"))
+}
+
+/// Cached LoTW users list (`[lotw]`)
+pub fn lotw_file() -> Option<PathBuf> {
+    cache_dir().map(|dir| dir.join("lotw-user-activity.csv"))
+}
+
+/// Cached Club Log most-wanted ranking (`[clublog]`)
+pub fn clublog_most_wanted_file() -> Option<PathBuf> {
+    cache_dir().map(|dir| dir.join("clublog-mostwanted.json"))
+}
+
+/// Cached copy of your Club Log log (`[clublog]` with a login)
+pub fn clublog_log_file() -> Option<PathBuf> {
+    cache_dir().map(|dir| dir.join("clublog-log.adi"))
+}
+
+/// Log file used when `[logging] file` is empty, e.g.
+/// ~/.local/share/rbn-vfd-display/rbn-vfd.log
+pub fn log_file() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("rbn-vfd.log"))
+}
+
+/// Saved feed captures, which `--replay` plays back
+pub fn captures_dir() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("captures"))
+}
+
+/// Scheduled spot and session dumps (`[dump]`) when `dir` is empty
+pub fn dumps_dir() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("dumps"))
+}
+
+/// Name for a new capture, e.g. captures/rbn-20260114-193012.log (UTC)
+pub fn new_capture_file() -> Option<PathBuf> {
+    captures_dir().map(|dir| dir.join(format!("rbn-{}.log", Utc::now().format("%Y%m%d-%H%M%S"))))
+}
@@ -0,0 +1,204 @@
+//! Worked-before state from a logger's ADIF file (`[alerts] adif_file`)
+//!
+//! The whole log is read on start, then the file is polled and QSOs
+//! appended to it are passed on as they arrive, so a station just worked
+//! stops showing as needed within seconds. A file that shrinks (rewritten
+//! or rotated by the logger) is read again from the start.
+
+use crate::config::AlertConfig;
+use crate::models::Band;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+/// How often the file is checked for new QSOs
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Longest field value believed; a longer length is a corrupt tag
+const MAX_FIELD_LENGTH: usize = 64 * 1024;
+
+/// The parts of a logged QSO the worked-before state needs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Qso {
+    /// Upper case
+    pub call: String,
+    pub band: Option<Band>,
+}
+
+/// Reads the log on a background thread and hands over its QSOs
+pub struct AdifWatcher {
+    receiver: Receiver<Vec<Qso>>,
+}
+
+impl AdifWatcher {
+    /// Start reading and watching `[alerts] adif_file`, or None without one
+    pub fn start(config: &AlertConfig) -> Option<Self> {
+        let path = PathBuf::from(config.adif_file.trim());
+        if path.as_os_str().is_empty() {
+            return None;
+        }
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut reader = LogReader::default();
+            let mut failing = false;
+            loop {
+                match reader.read_new(&path) {
+                    Ok(qsos) => {
+                        failing = false;
+                        if !qsos.is_empty() {
+                            log::info!("Read {} QSOs from {}", qsos.len(), path.display());
+                            if sender.send(qsos).is_err() {
+                                break;
+                            }
+                        }
+                    }
+                    // Reported once until the file can be read again
+                    Err(e) => {
+                        if !failing {
+                            log::warn!("{}", e);
+                        }
+                        failing = true;
+                    }
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+        });
+        Some(Self { receiver })
+    }
+
+    /// QSOs read since the last call, if any
+    pub fn try_recv(&self) -> Option<Vec<Qso>> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+/// Position in the log and any record not finished yet
+#[derive(Default)]
+struct LogReader {
+    offset: u64,
+    /// Text after the last complete record (or the header, at the start)
+    pending: String,
+}
+
+impl LogReader {
+    /// QSOs written since the last read
+    fn read_new(&mut self, path: &Path) -> Result<Vec<Qso>, String> {
+        let mut file =
+            File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        let len = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+        if len < self.offset {
+            *self = Self::default();
+        }
+        if len == self.offset {
+            return Ok(Vec::new());
+        }
+        let mut bytes = Vec::new();
+        file.seek(SeekFrom::Start(self.offset))
+            .and_then(|_| file.read_to_end(&mut bytes))
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        self.offset += bytes.len() as u64;
+        // Loggers write Latin-1 as often as UTF-8; calls and bands are ASCII
+        self.pending.push_str(&String::from_utf8_lossy(&bytes));
+        let (qsos, rest) = parse_records(&self.pending);
+        self.pending = rest.to_string();
+        Ok(qsos)
+    }
+}
+
+/// QSOs in ADIF text and whatever follows the last `<EOR>`. Everything up
+/// to `<EOH>` is the header, when there is one.
+pub(super) fn parse_records(text: &str) -> (Vec<Qso>, &str) {
+    let mut rest = text;
+    let mut qsos = Vec::new();
+    let mut call = None;
+    let mut band = None;
+    let mut freq_band = None;
+    while let Some(start) = rest.find('<') {
+        let Some(end) = rest[start..].find('>').map(|end| start + end) else {
+            break;
+        };
+        let tag = &rest[start + 1..end];
+        let mut parts = tag.split(':');
+        let name = parts.next().unwrap_or_default().trim().to_ascii_uppercase();
+        let length = parts
+            .next()
+            .and_then(|length| length.trim().parse::<usize>().ok());
+        match (name.as_str(), length) {
+            ("EOH", _) => {
+                call = None;
+                band = None;
+                freq_band = None;
+                rest = &rest[end + 1..];
+            }
+            ("EOR", _) => {
+                if let Some(call) = call.take() {
+                    qsos.push(Qso {
+                        call,
+                        band: band.or(freq_band),
+                    });
+                }
+                band = None;
+                freq_band = None;
+                rest = &rest[end + 1..];
+            }
+            (_, Some(length)) => {
+                // Lengths count bytes; a value cut off waits for more text,
+                // unless the length is past any real field or the record
+                // has already ended, when the tag is skipped
+                let value_start = end + 1;
+                let value_end = value_start.saturating_add(length);
+                let corrupt = length > MAX_FIELD_LENGTH
+                    || (rest.len() < value_end
+                        && rest[value_start..].to_ascii_uppercase().contains("<EOR>"));
+                if corrupt {
+                    log::warn!("Skipping ADIF field <{}> with a bad length", tag);
+                    rest = &rest[value_start..];
+                    continue;
+                }
+                let Some(value) = rest.get(value_start..value_end) else {
+                    if rest.len() < value_end {
+                        break;
+                    }
+                    // Length lands inside a multi-byte character
+                    rest = &rest[value_start..];
+                    continue;
+                };
+                let value = value.trim();
+                match name.as_str() {
+                    "CALL" => call = Some(value.to_uppercase()),
+                    "BAND" => band = Band::from_name(value),
+                    "FREQ" => {
+                        freq_band = value
+                            .parse::<f64>()
+                            .ok()
+                            .and_then(|mhz| Band::from_khz(mhz * 1000.0));
+                    }
+                    _ => {}
+                }
+                rest = &rest[value_end..];
+            }
+            _ => rest = &rest[end + 1..],
+        }
+    }
+    // Start of the unfinished record, so it is parsed whole next time
+    let unfinished = if call.is_some() || band.is_some() || freq_band.is_some() {
+        last_record_start(text)
+    } else {
+        rest
+    };
+    (qsos, unfinished)
+}
+
+/// Text after the last `<EOR>` or `<EOH>`, or all of it
+fn last_record_start(text: &str) -> &str {
+    let upper = text.to_ascii_uppercase();
+    let end = [upper.rfind("<EOR>"), upper.rfind("<EOH>")]
+        .into_iter()
+        .flatten()
+        .max()
+        .map_or(0, |at| at + "<EOR>".len());
+    &text[end..]
+}
@@ -0,0 +1,415 @@
+//! Alert rules (`[[alerts.rule]]`)
+//!
+//! Each new or updated spot from the `SpotStore` is checked against the
+//! rules on a background thread. A rule fires when every condition it sets
+//! holds (call pattern, band, new DXCC, SNR, distance, time of day), then
+//! stays quiet for that callsign and band for a while like the built-in
+//! alerts. Webhooks, ntfy and Gotify pushes and Telegram messages are sent
+//! from the thread, no more than `[alerts] notify_limit` an hour; LED and
+//! beep actions come back through `try_recv` for the owner of those
+//! outputs.
+
+use crate::config::{AlertConfig, AlertRule, Config, GotifyConfig, NtfyConfig, TelegramConfig};
+use crate::models::{AggregatedSpot, Band, DxccTable, LatLon, SpotRecord, Units};
+use crate::services::adif::Qso;
+use crate::services::alerts::{glob_match, split_list, Alert, SpotAlerts, REALERT_AFTER};
+use crate::services::schedule::DailyRange;
+use crate::services::SpotStore;
+use chrono::{SecondsFormat, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
+
+/// How long a webhook or push server gets to answer
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+/// Period `[alerts] notify_limit` counts over
+const LIMIT_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// A rule that fired, with the actions left to the caller
+#[derive(Debug, Clone)]
+pub struct RuleMatch {
+    pub alert: Alert,
+    /// `[leds]` LED to blink ("watch", "dxcc" or "" for none)
+    pub led: String,
+    pub beep: bool,
+}
+
+/// Checks spots against the alert rules on a background thread
+pub struct AlertEngine {
+    matches: Receiver<RuleMatch>,
+    updates: Sender<Update>,
+}
+
+/// Worked-before changes for the rule thread's `SpotAlerts`
+enum Update {
+    Lists(AlertConfig),
+    Logged(Vec<Qso>),
+    MostWanted(HashMap<String, u32>),
+}
+
+impl AlertEngine {
+    /// Start checking the store's spots, or None without rules. `dxcc` is
+    /// the country file already loaded for the built-in alerts.
+    pub fn start(config: &Config, spot_store: &SpotStore, dxcc: DxccTable) -> Option<Self> {
+        if config.alerts.rules.is_empty() {
+            return None;
+        }
+        let rules: Vec<Rule> = config.alerts.rules.iter().map(Rule::new).collect();
+        let mut alerts = SpotAlerts::new(&config.alerts);
+        alerts.set_dxcc(dxcc);
+        let home = LatLon::from_grid(&config.grid);
+        let units = config.units;
+        let sinks = Sinks {
+            telegram: config.telegram.clone(),
+            ntfy: config.ntfy.clone(),
+            gotify: config.gotify.clone(),
+        };
+        let mut limit = NotifyLimit::new(config.alerts.notify_limit);
+        let mut spots = spot_store.subscribe();
+        let (match_tx, matches) = mpsc::channel();
+        let (update_tx, update_rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            let mut fired: HashMap<String, Instant> = HashMap::new();
+            loop {
+                let spot = match spots.blocking_recv() {
+                    Ok(spot) => spot,
+                    Err(RecvError::Lagged(missed)) => {
+                        log::warn!("Alert rules fell behind, skipped {} spots", missed);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                while let Ok(update) = update_rx.try_recv() {
+                    match update {
+                        Update::Lists(config) => alerts.set_lists(&config),
+                        Update::Logged(qsos) => alerts.add_logged(&qsos),
+                        Update::MostWanted(ranks) => alerts.set_most_wanted(ranks),
+                    }
+                }
+
+                let now = Instant::now();
+                fired.retain(|_, last| now.duration_since(*last) < REALERT_AFTER);
+                for rule in &rules {
+                    if !rule.matches(&spot, &alerts, home, units.timezone) {
+                        continue;
+                    }
+                    let band = Band::from_khz(spot.frequency_khz).map_or("", Band::name);
+                    let key = format!("{}|{}|{}", rule.rule.name, spot.callsign, band);
+                    if fired.insert(key, now).is_some() {
+                        continue;
+                    }
+                    let alert = Alert::Rule {
+                        rule: rule.rule.name.clone(),
+                        callsign: spot.callsign.to_uppercase(),
+                    };
+                    log::info!(
+                        "{} on {}",
+                        alert,
+                        units.frequency_with_unit(spot.frequency_khz)
+                    );
+                    if rule.notifies() && limit.allow(now) {
+                        rule.notify(&spot, &units, &sinks);
+                    }
+                    let _ = match_tx.send(RuleMatch {
+                        alert,
+                        led: rule.rule.led.trim().to_lowercase(),
+                        beep: rule.rule.beep,
+                    });
+                }
+            }
+        });
+
+        Some(Self {
+            matches,
+            updates: update_tx,
+        })
+    }
+
+    /// Next rule that fired, if any
+    pub fn try_recv(&self) -> Option<RuleMatch> {
+        self.matches.try_recv().ok()
+    }
+
+    /// Take new worked lists for the new-DXCC condition (e.g. after a
+    /// profile switch)
+    pub fn set_lists(&self, config: &AlertConfig) {
+        let _ = self.updates.send(Update::Lists(config.clone()));
+    }
+
+    /// Count QSOs from the `[alerts] adif_file` log as worked
+    pub fn add_logged(&self, qsos: Vec<Qso>) {
+        let _ = self.updates.send(Update::Logged(qsos));
+    }
+
+    /// Take a new Club Log ranking for the `most_wanted` condition
+    pub fn set_most_wanted(&self, ranks: HashMap<String, u32>) {
+        let _ = self.updates.send(Update::MostWanted(ranks));
+    }
+}
+
+/// Where rules send messages other than webhooks
+struct Sinks {
+    telegram: TelegramConfig,
+    ntfy: NtfyConfig,
+    gotify: GotifyConfig,
+}
+
+/// A rule with its lists parsed
+struct Rule {
+    rule: AlertRule,
+    /// Upper-case callsign patterns
+    calls: Vec<String>,
+    bands: Vec<Band>,
+    hours: Option<DailyRange>,
+}
+
+impl Rule {
+    fn new(rule: &AlertRule) -> Self {
+        Self {
+            calls: split_list(&rule.calls),
+            bands: rule.bands.split(',').filter_map(Band::from_name).collect(),
+            hours: DailyRange::parse(&rule.hours),
+            rule: rule.clone(),
+        }
+    }
+
+    /// Whether the spot meets every condition the rule sets
+    fn matches(
+        &self,
+        spot: &AggregatedSpot,
+        alerts: &SpotAlerts,
+        home: Option<LatLon>,
+        zone: Option<Tz>,
+    ) -> bool {
+        let callsign = spot.callsign.to_uppercase();
+        if !self.calls.is_empty()
+            && !self
+                .calls
+                .iter()
+                .any(|pattern| glob_match(pattern, &callsign))
+        {
+            return false;
+        }
+        if !self.bands.is_empty()
+            && !Band::from_khz(spot.frequency_khz).is_some_and(|band| self.bands.contains(&band))
+        {
+            return false;
+        }
+        if self.rule.new_dxcc && !alerts.is_new(spot) {
+            return false;
+        }
+        if let Some(top) = self.rule.most_wanted {
+            // Unranked (or no ranking loaded yet) doesn't pass
+            if alerts.rank(&callsign).is_none_or(|rank| rank > top) {
+                return false;
+            }
+        }
+        if self.rule.min_snr.is_some_and(|min| spot.highest_snr < min) {
+            return false;
+        }
+        if self.rule.min_km.is_some() || self.rule.max_km.is_some() {
+            // Without both ends of the path the distance can't pass
+            let Some(km) = home
+                .zip(alerts.location(&callsign))
+                .map(|(home, there)| home.distance_bearing(&there).0)
+            else {
+                return false;
+            };
+            if self.rule.min_km.is_some_and(|min| km < min as f64)
+                || self.rule.max_km.is_some_and(|max| km > max as f64)
+            {
+                return false;
+            }
+        }
+        self.hours.is_none_or(|hours| hours.contains_now(zone))
+    }
+
+    /// Whether it sends anything off the machine
+    fn notifies(&self) -> bool {
+        !self.rule.webhook.trim().is_empty()
+            || !self.rule.push.trim().is_empty()
+            || self.rule.telegram
+            || self.rule.gotify
+    }
+
+    /// Send the webhook, pushes and Telegram message, each from its own
+    /// thread
+    fn notify(&self, spot: &AggregatedSpot, units: &Units, sinks: &Sinks) {
+        let webhook = self.rule.webhook.trim().to_string();
+        if !webhook.is_empty() {
+            let body = serde_json::to_string(&Notice {
+                rule: &self.rule.name,
+                time: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+                spot: SpotRecord::from(spot),
+            })
+            .unwrap_or_default();
+            thread::spawn(move || {
+                let result = agent()
+                    .post(&webhook)
+                    .set("Content-Type", "application/json")
+                    .send_string(&body);
+                if let Err(e) = result {
+                    log::warn!("Webhook {} failed: {}", webhook, e);
+                }
+            });
+        }
+
+        let message = format!(
+            "{} {} {} dB {} wpm",
+            spot.callsign.to_uppercase(),
+            units.frequency_with_unit(spot.frequency_khz),
+            spot.highest_snr,
+            spot.average_speed.round()
+        );
+        let push = self.rule.push.trim().to_string();
+        if !push.is_empty() {
+            let title = self.rule.name.clone();
+            let message = message.clone();
+            let priority = self.rule.priority.map(|priority| priority.to_string());
+            let token = sinks.ntfy.token.expose().trim().to_string();
+            thread::spawn(move || {
+                // ntfy takes the message as the body, the rest as headers
+                let mut request = agent().post(&push).set("Title", &title);
+                if let Some(ref priority) = priority {
+                    request = request.set("Priority", priority);
+                }
+                if !token.is_empty() {
+                    request = request.set("Authorization", &format!("Bearer {}", token));
+                }
+                let result = request.send_string(&message);
+                if let Err(e) = result {
+                    log::warn!("Push to {} failed: {}", push, e);
+                }
+            });
+        }
+
+        if self.rule.gotify {
+            let url = format!("{}/message", sinks.gotify.url.trim().trim_end_matches('/'));
+            let token = sinks.gotify.token.expose().trim().to_string();
+            let mut body = serde_json::json!({
+                "title": self.rule.name,
+                "message": message,
+            });
+            if let Some(priority) = self.rule.priority {
+                body["priority"] = gotify_priority(priority).into();
+            }
+            let body = body.to_string();
+            thread::spawn(move || {
+                let result = agent()
+                    .post(&url)
+                    .set("X-Gotify-Key", &token)
+                    .set("Content-Type", "application/json")
+                    .send_string(&body);
+                if let Err(e) = result {
+                    log::warn!("Gotify message to {} failed: {}", url, e);
+                }
+            });
+        }
+
+        if self.rule.telegram {
+            let telegram = &sinks.telegram;
+            let url = format!(
+                "https://api.telegram.org/bot{}/sendMessage",
+                telegram.bot_token.expose().trim()
+            );
+            let body = serde_json::json!({
+                "chat_id": telegram.chat_id.trim(),
+                "text": format!("{}: {}", self.rule.name, message),
+            })
+            .to_string();
+            thread::spawn(move || {
+                // The URL holds the token, so errors are logged without it
+                let result = agent()
+                    .post(&url)
+                    .set("Content-Type", "application/json")
+                    .send_string(&body);
+                match result {
+                    Ok(_) => {}
+                    Err(ureq::Error::Status(code, response)) => log::warn!(
+                        "Telegram message failed: {} {}",
+                        code,
+                        response.into_string().unwrap_or_default()
+                    ),
+                    Err(ureq::Error::Transport(e)) => {
+                        log::warn!("Telegram message failed: {}", e.kind())
+                    }
+                }
+            });
+        }
+    }
+}
+
+/// Gotify's 0-10 priority for ntfy's 1 (min) to 5 (urgent)
+fn gotify_priority(priority: u8) -> u8 {
+    match priority {
+        0 | 1 => 0,
+        2 => 2,
+        3 => 5,
+        4 => 8,
+        _ => 10,
+    }
+}
+
+/// Notifications sent in the last hour, held to `[alerts] notify_limit`
+struct NotifyLimit {
+    limit: usize,
+    sent: VecDeque<Instant>,
+    /// Held back since the limit was last reached, for the log
+    held: u32,
+}
+
+impl NotifyLimit {
+    fn new(limit: u32) -> Self {
+        Self {
+            limit: limit as usize,
+            sent: VecDeque::new(),
+            held: 0,
+        }
+    }
+
+    /// Whether another notification may go out now, counting it if so
+    fn allow(&mut self, now: Instant) -> bool {
+        while self
+            .sent
+            .front()
+            .is_some_and(|sent| now.duration_since(*sent) >= LIMIT_PERIOD)
+        {
+            self.sent.pop_front();
+        }
+        if self.limit > 0 && self.sent.len() >= self.limit {
+            if self.held == 0 {
+                log::warn!(
+                    "{} alert notifications in the last hour; holding back more",
+                    self.limit
+                );
+            }
+            self.held += 1;
+            return false;
+        }
+        if self.held > 0 {
+            log::info!("Alert notifications resumed; {} were held back", self.held);
+            self.held = 0;
+        }
+        self.sent.push_back(now);
+        true
+    }
+}
+
+/// Webhook body: the rule and the spot
+#[derive(Serialize)]
+struct Notice<'a> {
+    rule: &'a str,
+    /// UTC, RFC 3339
+    time: String,
+    #[serde(flatten)]
+    spot: SpotRecord,
+}
+
+fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new().timeout(SEND_TIMEOUT).build()
+}
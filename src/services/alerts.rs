@@ -0,0 +1,332 @@
+//! Spot alerts for your own callsign, watch-list callsigns and DXCC
+//! entities (or entity/band slots) not yet worked
+
+use crate::config::AlertConfig;
+use crate::models::{AggregatedSpot, Band, DxccTable, LatLon, RawSpot};
+use crate::paths;
+use crate::services::adif::Qso;
+use crate::services::display::color::{self, Rgb};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// A callsign doesn't alert again on a band until it has been quiet this
+/// long
+pub(super) const REALERT_AFTER: Duration = Duration::from_secs(30 * 60);
+
+/// Why a spot deserves attention
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Alert {
+    /// A skimmer heard `[station] callsign`
+    SelfSpot { spotter: String, snr: i32 },
+    /// Callsign matched `[alerts] watch_list`
+    Watch { callsign: String },
+    /// Entity not in `[alerts] worked_dxcc`
+    NewDxcc {
+        callsign: String,
+        entity: String,
+        name: Option<String>,
+    },
+    /// Worked entity on a band not in `[alerts] worked_slots`
+    NewSlot {
+        callsign: String,
+        entity: String,
+        band: Band,
+    },
+    /// Matched an `[[alerts.rule]]`
+    Rule { rule: String, callsign: String },
+}
+
+impl Alert {
+    /// Short kind name, as used in `[sound] alerts`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Alert::SelfSpot { .. } => "self",
+            Alert::Watch { .. } => "watch",
+            Alert::NewDxcc { .. } => "dxcc",
+            Alert::NewSlot { .. } => "slot",
+            Alert::Rule { .. } => "rule",
+        }
+    }
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Alert::SelfSpot { spotter, snr } => write!(f, "Spotted by {} {} dB", spotter, snr),
+            Alert::Watch { callsign } => write!(f, "Watch list: {}", callsign),
+            Alert::NewDxcc {
+                callsign,
+                entity,
+                name,
+            } => match name {
+                Some(name) => write!(f, "New DXCC: {} ({}) {}", entity, name, callsign),
+                None => write!(f, "New DXCC: {} {}", entity, callsign),
+            },
+            Alert::NewSlot {
+                callsign,
+                entity,
+                band,
+            } => write!(f, "New slot: {} {} {}", entity, band, callsign),
+            Alert::Rule { rule, callsign } => write!(f, "{}: {}", rule, callsign),
+        }
+    }
+}
+
+/// Checks incoming spots against the watch list and worked entities
+pub struct SpotAlerts {
+    /// Own callsign, upper case ("" = no self-spot alerts)
+    own_call: String,
+    /// Upper-case callsign patterns, `*` matching any run of characters
+    watch: Vec<String>,
+    /// Worked entity primary prefixes, upper case
+    worked: HashSet<String>,
+    /// Worked "entity/band" slots, upper case (e.g. "DL/20M")
+    worked_slots: HashSet<String>,
+    /// Entities and slots from the `[alerts] adif_file` log, kept across
+    /// `set_lists`
+    logged: HashSet<String>,
+    logged_slots: HashSet<String>,
+    dxcc: DxccTable,
+    /// Club Log most-wanted rank by upper-case entity prefix (empty
+    /// without `[clublog]`)
+    most_wanted: HashMap<String, u32>,
+    /// When each callsign last alerted, per band
+    alerted: HashMap<String, Instant>,
+}
+
+impl SpotAlerts {
+    pub fn new(config: &AlertConfig) -> Self {
+        Self {
+            own_call: String::new(),
+            watch: split_list(&config.watch_list),
+            worked: split_list(&config.worked_dxcc).into_iter().collect(),
+            worked_slots: split_list(&config.worked_slots).into_iter().collect(),
+            logged: HashSet::new(),
+            logged_slots: HashSet::new(),
+            dxcc: DxccTable::default(),
+            most_wanted: HashMap::new(),
+            alerted: HashMap::new(),
+        }
+    }
+
+    /// Take new watch and worked lists (e.g. after a profile switch),
+    /// keeping the loaded country file
+    pub fn set_lists(&mut self, config: &AlertConfig) {
+        self.watch = split_list(&config.watch_list);
+        self.worked = split_list(&config.worked_dxcc).into_iter().collect();
+        self.worked_slots = split_list(&config.worked_slots).into_iter().collect();
+    }
+
+    /// Count logged QSOs as worked: their entities, and their slots when
+    /// the band is known. Calls the country file can't place are skipped.
+    pub fn add_logged(&mut self, qsos: &[Qso]) {
+        for qso in qsos {
+            let Some(entity) = self.dxcc.entity(&qso.call) else {
+                continue;
+            };
+            let entity = entity.to_string();
+            if let Some(band) = qso.band {
+                self.logged_slots
+                    .insert(format!("{}/{}", entity, band.name().to_uppercase()));
+            }
+            self.logged.insert(entity);
+        }
+    }
+
+    /// Take a new Club Log most-wanted ranking
+    pub fn set_most_wanted(&mut self, ranks: HashMap<String, u32>) {
+        self.most_wanted = ranks;
+    }
+
+    /// Club Log most-wanted rank of a callsign's entity (1 = most wanted)
+    pub fn rank(&self, callsign: &str) -> Option<u32> {
+        let entity = self.dxcc.entity(callsign)?;
+        self.most_wanted.get(&entity.to_uppercase()).copied()
+    }
+
+    /// Short marker for spot lists: the entity's most-wanted rank, then
+    /// "+" when it or its band slot is needed, e.g. "12+" ("" for neither)
+    pub fn want_label(&self, spot: &AggregatedSpot) -> String {
+        let rank = self.rank(&spot.callsign).map(|rank| rank.to_string());
+        let needed = if self.is_new(spot) { "+" } else { "" };
+        format!("{}{}", rank.unwrap_or_default(), needed)
+    }
+
+    /// Alert when a skimmer hears this callsign (or a portable version of
+    /// it, e.g. "W6JSV/P")
+    pub fn set_callsign(&mut self, callsign: &str) {
+        self.own_call = callsign.trim().to_uppercase();
+    }
+
+    /// Load the country file for new-DXCC alerts, if one is configured or
+    /// cached. Without it only the watch list alerts.
+    pub fn load_dxcc(&mut self, config: &AlertConfig) -> Result<(), String> {
+        let path = match config.cty_file.trim() {
+            "" => match paths::cty_file().filter(|path| path.exists()) {
+                Some(path) => path.to_string_lossy().into_owned(),
+                None => return Ok(()),
+            },
+            path => path.to_string(),
+        };
+        self.dxcc = DxccTable::load(&path)?;
+        Ok(())
+    }
+
+    /// Alert raised by a spot, if any. Your own callsign wins over
+    /// watch-list hits, which win over new DXCC, which wins over a new slot
+    /// (only checked when slots are listed).
+    pub fn check(&mut self, spot: &RawSpot) -> Option<Alert> {
+        let callsign = spot.spotted_callsign.to_uppercase();
+        let own_call = !self.own_call.is_empty()
+            && callsign
+                .split('/')
+                .any(|part| part == self.own_call.as_str());
+        let alert = if own_call {
+            Alert::SelfSpot {
+                spotter: spot.spotter_callsign.to_uppercase(),
+                snr: spot.snr,
+            }
+        } else if self.watched(&callsign) {
+            Alert::Watch {
+                callsign: callsign.clone(),
+            }
+        } else {
+            let entity = self.dxcc.entity(&callsign)?;
+            if !self.is_worked(entity) {
+                Alert::NewDxcc {
+                    callsign: callsign.clone(),
+                    entity: entity.to_string(),
+                    name: self.dxcc.name(entity).map(str::to_string),
+                }
+            } else {
+                let band = Band::from_khz(spot.frequency_khz)?;
+                if self.slot_worked(entity, band) {
+                    return None;
+                }
+                Alert::NewSlot {
+                    callsign: callsign.clone(),
+                    entity: entity.to_string(),
+                    band,
+                }
+            }
+        };
+
+        let now = Instant::now();
+        let band = Band::from_khz(spot.frequency_khz).map_or("", Band::name);
+        let key = format!("{}|{}", callsign, band);
+        let previous = self.alerted.insert(key, now);
+        if previous.is_some_and(|last| now.duration_since(last) < REALERT_AFTER) {
+            return None;
+        }
+        self.alerted
+            .retain(|_, last| now.duration_since(*last) < REALERT_AFTER);
+        Some(alert)
+    }
+
+    /// Color hint for a spot's callsign: watch-list hits stand out, worked
+    /// stations are dimmed
+    pub fn tint(&self, spot: &AggregatedSpot) -> Option<Rgb> {
+        let callsign = spot.callsign.to_uppercase();
+        if self.watched(&callsign) {
+            return Some(color::WATCH);
+        }
+        let entity = self.dxcc.entity(&callsign)?;
+        let band = Band::from_khz(spot.frequency_khz);
+        (self.worked_status(entity, band) == "Worked").then_some(color::WORKED)
+    }
+
+    fn is_worked(&self, entity: &str) -> bool {
+        self.worked.contains(entity) || self.logged.contains(entity)
+    }
+
+    /// Whether an entity was worked on a band; always true when neither
+    /// `worked_slots` nor a log lists any slots
+    fn slot_worked(&self, entity: &str, band: Band) -> bool {
+        if self.worked_slots.is_empty() && self.logged_slots.is_empty() {
+            return true;
+        }
+        let slot = format!("{}/{}", entity, band.name().to_uppercase());
+        self.worked_slots.contains(&slot) || self.logged_slots.contains(&slot)
+    }
+
+    /// Whether an upper-case callsign is on the watch list
+    pub fn watched(&self, callsign: &str) -> bool {
+        self.watch
+            .iter()
+            .any(|pattern| glob_match(pattern, callsign))
+    }
+
+    /// Use a country file already loaded elsewhere
+    pub(super) fn set_dxcc(&mut self, dxcc: DxccTable) {
+        self.dxcc = dxcc;
+    }
+
+    /// Country file loaded by `load_dxcc` (empty without one)
+    pub fn dxcc(&self) -> &DxccTable {
+        &self.dxcc
+    }
+
+    /// Location of a callsign's entity, from the country file
+    pub fn location(&self, callsign: &str) -> Option<LatLon> {
+        let entity = self.dxcc.entity(callsign)?;
+        self.dxcc.details(entity).map(|details| details.location)
+    }
+
+    /// Continent of a callsign's entity, from the country file
+    pub fn continent(&self, callsign: &str) -> Option<&str> {
+        let entity = self.dxcc.entity(callsign)?;
+        self.dxcc
+            .details(entity)
+            .map(|details| details.continent.as_str())
+    }
+
+    /// Whether a spot is a new entity, or a new slot when slots are listed
+    pub fn is_new(&self, spot: &AggregatedSpot) -> bool {
+        self.dxcc.entity(&spot.callsign).is_some_and(|entity| {
+            self.worked_status(entity, Band::from_khz(spot.frequency_khz)) != "Worked"
+        })
+    }
+
+    /// Worked-before state of an entity, on a band when slots are listed:
+    /// "New DXCC", "New slot" or "Worked"
+    pub fn worked_status(&self, entity: &str, band: Option<Band>) -> &'static str {
+        if !self.is_worked(entity) {
+            return "New DXCC";
+        }
+        if band.is_some_and(|band| !self.slot_worked(entity, band)) {
+            "New slot"
+        } else {
+            "Worked"
+        }
+    }
+}
+
+/// Comma separated entries, trimmed and upper-cased
+pub(super) fn split_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(|entry| entry.trim().to_uppercase())
+        .filter(|entry| !entry.is_empty())
+        .collect()
+}
+
+/// Match `text` against a pattern where `*` stands for any characters
+pub(super) fn glob_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No `*`: exact match
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
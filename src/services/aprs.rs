@@ -0,0 +1,293 @@
+//! Alerting spots on APRS-IS (`[aprs]`)
+//!
+//! Spots whose alert kind is listed go out under `[aprs] callsign` as an
+//! object at the station's entity location (so they show on aprs.fi and
+//! on APRS radios that list nearby objects) and/or as a message to
+//! `message_to`. A thread keeps the APRS-IS login, reconnecting after a
+//! drop, and kills each object once it has been up for `OBJECT_LIFETIME`.
+//! The passcode is worked out from the callsign, as APRS-IS expects.
+
+use crate::config::AprsConfig;
+use crate::models::{LatLon, RawSpot};
+use crate::services::alerts::{split_list, Alert};
+use chrono::Utc;
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often the thread sends, reads and checks object ages
+const TICK: Duration = Duration::from_secs(1);
+/// How long the server gets to accept the connection
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Wait before logging in again after a drop
+const RECONNECT_DELAY: Duration = Duration::from_secs(60);
+/// How long an object stays on the map before it's killed
+const OBJECT_LIFETIME: Duration = Duration::from_secs(30 * 60);
+/// Destination of sent packets: APZ is for experimental software
+const TOCALL: &str = "APZRVD";
+/// Longest object comment and message text APRS allows
+const COMMENT_MAX: usize = 43;
+const MESSAGE_MAX: usize = 67;
+
+/// Sends alerting spots to APRS-IS
+pub struct AprsPublisher {
+    packets: Sender<Packet>,
+    /// Lower-case alert kinds sent
+    kinds: Vec<String>,
+    objects: bool,
+    /// Addressee padded to 9 characters ("" for no messages)
+    message_to: String,
+}
+
+/// What goes out for a spot
+enum Packet {
+    Object {
+        /// Spotted callsign, padded to 9 characters
+        name: String,
+        position: LatLon,
+        comment: String,
+    },
+    /// Message payload, addressee included
+    Message(String),
+}
+
+impl AprsPublisher {
+    /// Start the APRS-IS connection, or None when not enabled or there's
+    /// no callsign. `callsign` is `[station] callsign`, used unless
+    /// `[aprs] callsign` is set.
+    pub fn start(config: &AprsConfig, callsign: &str) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let login = match config.callsign.trim() {
+            "" => callsign.trim().to_uppercase(),
+            call => call.to_uppercase(),
+        };
+        if login.is_empty() {
+            log::warn!("APRS-IS needs a callsign");
+            return None;
+        }
+        let (packets, packet_rx) = mpsc::channel::<Packet>();
+        let server = config.server.trim().to_string();
+
+        thread::spawn(move || {
+            let mut connection: Option<TcpStream> = None;
+            let mut retry_at = Instant::now();
+            // Object name to when it was sent, position and comment, until
+            // it's killed
+            let mut live: HashMap<String, (Instant, LatLon, String)> = HashMap::new();
+            loop {
+                let mut lines = Vec::new();
+                match packet_rx.recv_timeout(TICK) {
+                    Ok(packet) => lines.extend(packet_line(packet, &mut live)),
+                    Err(mpsc::RecvTimeoutError::Timeout) => {}
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                }
+                lines.extend(
+                    std::iter::from_fn(|| packet_rx.try_recv().ok())
+                        .filter_map(|packet| packet_line(packet, &mut live)),
+                );
+                live.retain(|name, (sent, position, comment)| {
+                    if sent.elapsed() < OBJECT_LIFETIME {
+                        return true;
+                    }
+                    lines.push(object_payload(name, false, *position, comment));
+                    false
+                });
+
+                if connection.is_none() && Instant::now() >= retry_at {
+                    match connect(&server, &login) {
+                        Ok(stream) => {
+                            log::info!("Logged in to APRS-IS at {} as {}", server, login);
+                            connection = Some(stream);
+                        }
+                        Err(e) => {
+                            log::warn!("APRS-IS connection to {} failed: {}", server, e);
+                            retry_at = Instant::now() + RECONNECT_DELAY;
+                        }
+                    }
+                }
+                let Some(ref mut stream) = connection else {
+                    if !lines.is_empty() {
+                        log::debug!("{} APRS packets dropped while offline", lines.len());
+                    }
+                    continue;
+                };
+                let result = drain(stream).and_then(|()| {
+                    lines.iter().try_for_each(|payload| {
+                        log::debug!("APRS-IS: {}", payload);
+                        write!(stream, "{}>{},TCPIP*:{}\r\n", login, TOCALL, payload)
+                    })
+                });
+                if let Err(e) = result {
+                    log::warn!("APRS-IS connection lost: {}", e);
+                    connection = None;
+                    retry_at = Instant::now() + RECONNECT_DELAY;
+                }
+            }
+        });
+
+        let message_to = config.message_to.trim().to_uppercase();
+        Some(Self {
+            packets,
+            kinds: split_list(&config.alerts)
+                .into_iter()
+                .map(|kind| kind.to_lowercase())
+                .collect(),
+            objects: config.objects,
+            message_to: if message_to.is_empty() {
+                message_to
+            } else {
+                format!("{:<9}", message_to)
+            },
+        })
+    }
+
+    /// Send the spot behind an alert, when its kind is listed; `location`
+    /// is its entity's, for the object
+    pub fn record(&self, alert: &Alert, spot: &RawSpot, location: Option<LatLon>) {
+        if !self.kinds.iter().any(|kind| kind == alert.kind()) {
+            return;
+        }
+        let call = spot.spotted_callsign.to_uppercase();
+        let mode = spot.mode.to_uppercase();
+        if let (true, Some(position)) = (self.objects, location) {
+            // APRS frequency format first, so radios can tune it
+            let comment = format!(
+                "{:07.3}MHz {} {}dB {}",
+                spot.frequency_khz / 1000.0,
+                mode,
+                spot.snr,
+                alert
+            );
+            let _ = self.packets.send(Packet::Object {
+                name: format!("{:<9.9}", call),
+                position,
+                comment: clean(&comment, COMMENT_MAX),
+            });
+        }
+        if !self.message_to.is_empty() {
+            let text = format!(
+                "{} {:.1} {} {}dB {}",
+                call, spot.frequency_khz, mode, spot.snr, alert
+            );
+            let _ = self.packets.send(Packet::Message(format!(
+                ":{}:{}",
+                self.message_to,
+                clean(&text, MESSAGE_MAX)
+            )));
+        }
+    }
+}
+
+/// Payload for a packet, noting objects as live; None for an object
+/// already up with the same comment
+fn packet_line(
+    packet: Packet,
+    live: &mut HashMap<String, (Instant, LatLon, String)>,
+) -> Option<String> {
+    match packet {
+        Packet::Object {
+            name,
+            position,
+            comment,
+        } => {
+            let payload = object_payload(&name, true, position, &comment);
+            let same = live.get(&name).is_some_and(|(_, _, sent)| *sent == comment);
+            live.insert(name, (Instant::now(), position, comment));
+            (!same).then_some(payload)
+        }
+        Packet::Message(payload) => Some(payload),
+    }
+}
+
+/// Object report (`alive` false kills it), with the antenna symbol
+/// Example: ";3Y0J     *142305h5425.00S/00319.00Er014.025MHz CW 23dB New DXCC: 3Y (Bouvet) 3Y0J"
+fn object_payload(name: &str, alive: bool, position: LatLon, comment: &str) -> String {
+    let angle = |degrees: f64, width: usize, positive: char, negative: char| {
+        let hemisphere = if degrees < 0.0 { negative } else { positive };
+        let degrees = degrees.abs();
+        let whole = degrees.trunc();
+        // Rounded minutes reaching 60 carry into the degrees
+        let minutes = ((degrees - whole) * 60.0 * 100.0).round() / 100.0;
+        let (whole, minutes) = if minutes >= 60.0 {
+            (whole + 1.0, 0.0)
+        } else {
+            (whole, minutes)
+        };
+        format!(
+            "{:0width$}{:05.2}{}",
+            whole as u32,
+            minutes,
+            hemisphere,
+            width = width
+        )
+    };
+    format!(
+        ";{}{}{}{}/{}r{}",
+        name,
+        if alive { '*' } else { '_' },
+        Utc::now().format("%H%M%Sh"),
+        angle(position.lat.clamp(-90.0, 90.0), 2, 'N', 'S'),
+        angle(position.lon.clamp(-180.0, 180.0), 3, 'E', 'W'),
+        comment
+    )
+}
+
+/// Printable ASCII APRS allows, cut to `max` characters
+fn clean(text: &str, max: usize) -> String {
+    text.chars()
+        .filter(|c| matches!(c, ' '..='~') && !matches!(c, '|' | '~' | '{'))
+        .take(max)
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+/// Connect and log in
+fn connect(server: &str, login: &str) -> io::Result<TcpStream> {
+    let address = server
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no address"))?;
+    let mut stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)?;
+    write!(
+        stream,
+        "user {} pass {} vers rbn-vfd-display {}\r\n",
+        login,
+        passcode(login),
+        env!("CARGO_PKG_VERSION")
+    )?;
+    stream.set_nonblocking(true)?;
+    Ok(stream)
+}
+
+/// Discard what the server sends (its banner, login reply and keepalive
+/// comments); an error when it has closed the connection
+fn drain(stream: &mut TcpStream) -> io::Result<()> {
+    let mut buffer = [0u8; 4096];
+    loop {
+        match stream.read(&mut buffer) {
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(_) => {}
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// APRS-IS passcode for a callsign: a hash of the call without its SSID
+fn passcode(login: &str) -> u16 {
+    let call = login.split('-').next().unwrap_or_default().to_uppercase();
+    let mut hash: u16 = 0x73e2;
+    for pair in call.as_bytes().chunks(2) {
+        hash ^= u16::from(pair[0]) << 8;
+        if let Some(&low) = pair.get(1) {
+            hash ^= u16::from(low);
+        }
+    }
+    hash & 0x7fff
+}
@@ -0,0 +1,165 @@
+//! Automatic display brightness from a time-of-day schedule or an ambient
+//! light sensor (BH1750 on I2C)
+
+use crate::config::DisplayConfig;
+use crate::models::local_minute_of_day;
+use chrono::Utc;
+use chrono_tz::Tz;
+use std::time::{Duration, Instant};
+
+/// How often the schedule and sensor are re-evaluated
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Brightness used in the dark when following the light sensor
+const MIN_AMBIENT_PERCENT: f64 = 10.0;
+
+/// Illuminance at and above which the display runs at full brightness
+const FULL_BRIGHTNESS_LUX: f64 = 1000.0;
+
+/// Picks the brightness the display should run at
+pub struct BrightnessControl {
+    /// (minute of day, percent), sorted by time
+    schedule: Vec<(u32, u8)>,
+    base: u8,
+    /// Zone the schedule's times are in (None = the system's)
+    timezone: Option<Tz>,
+    #[cfg(target_os = "linux")]
+    sensor: Option<Bh1750>,
+    last_check: Option<Instant>,
+    applied: Option<u8>,
+}
+
+impl BrightnessControl {
+    pub fn new(config: &DisplayConfig) -> Self {
+        Self {
+            schedule: parse_schedule(&config.brightness_schedule),
+            base: config.brightness,
+            timezone: None,
+            #[cfg(target_os = "linux")]
+            sensor: None,
+            last_check: None,
+            applied: None,
+        }
+    }
+
+    /// Follow the schedule in `timezone` instead of the system's zone
+    pub fn set_timezone(&mut self, timezone: Option<Tz>) {
+        self.timezone = timezone;
+        self.last_check = None;
+    }
+
+    /// Open the light sensor, if one is configured. Errors leave the
+    /// schedule (or fixed brightness) in charge.
+    pub fn open_sensor(&mut self, config: &DisplayConfig) -> Result<(), String> {
+        self.applied = None;
+        self.last_check = None;
+        if config.light_sensor_address == 0 {
+            return Ok(());
+        }
+        #[cfg(target_os = "linux")]
+        {
+            let bus = config.light_sensor_bus.unwrap_or(config.i2c_bus);
+            self.sensor = Some(Bh1750::open(bus, config.light_sensor_address)?);
+            Ok(())
+        }
+        #[cfg(not(target_os = "linux"))]
+        Err("Light sensors are only supported on Linux".to_string())
+    }
+
+    /// Brightness to apply now, when it differs from the last one returned
+    pub fn poll(&mut self) -> Option<u8> {
+        let now = Instant::now();
+        if self
+            .last_check
+            .is_some_and(|last| now.duration_since(last) < CHECK_INTERVAL)
+        {
+            return None;
+        }
+        self.last_check = Some(now);
+
+        let level = self
+            .ambient_level()
+            .unwrap_or_else(|| self.scheduled_level());
+        if self.applied == Some(level) {
+            return None;
+        }
+        self.applied = Some(level);
+        Some(level)
+    }
+
+    /// Brightness last returned by `poll` (the fixed setting before that)
+    pub fn level(&self) -> u8 {
+        self.applied.unwrap_or(self.base)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn ambient_level(&mut self) -> Option<u8> {
+        let lux = self.sensor.as_mut()?.read_lux().ok()?;
+        // Eyes respond roughly logarithmically, so map log(lux) onto the range
+        let fraction = ((lux + 1.0).log10() / FULL_BRIGHTNESS_LUX.log10()).clamp(0.0, 1.0);
+        let percent = MIN_AMBIENT_PERCENT + (100.0 - MIN_AMBIENT_PERCENT) * fraction;
+        // Never brighter than the schedule or fixed setting allows
+        Some((percent as u8).min(self.scheduled_level()))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn ambient_level(&mut self) -> Option<u8> {
+        None
+    }
+
+    /// Level from the schedule entry most recently passed today (wrapping to
+    /// yesterday's last entry), or the fixed brightness without a schedule
+    fn scheduled_level(&self) -> u8 {
+        let minute = local_minute_of_day(Utc::now(), self.timezone);
+        self.schedule
+            .iter()
+            .rev()
+            .find(|(start, _)| *start <= minute)
+            .or(self.schedule.last())
+            .map_or(self.base, |(_, percent)| *percent)
+    }
+}
+
+/// Parse "HH:MM=percent" entries separated by commas, skipping bad ones
+fn parse_schedule(schedule: &str) -> Vec<(u32, u8)> {
+    let mut entries: Vec<(u32, u8)> = schedule
+        .split(',')
+        .filter_map(|entry| {
+            let (time, percent) = entry.trim().split_once('=')?;
+            let (hour, minute) = time.trim().split_once(':')?;
+            let hour: u32 = hour.parse().ok().filter(|h| *h < 24)?;
+            let minute: u32 = minute.parse().ok().filter(|m| *m < 60)?;
+            let percent: u8 = percent.trim().parse().ok()?;
+            Some((hour * 60 + minute, percent.min(100)))
+        })
+        .collect();
+    entries.sort_by_key(|(start, _)| *start);
+    entries
+}
+
+/// ROHM BH1750 ambient light sensor in continuous high-resolution mode
+#[cfg(target_os = "linux")]
+struct Bh1750 {
+    device: crate::services::i2c::I2cDevice,
+}
+
+#[cfg(target_os = "linux")]
+impl Bh1750 {
+    const POWER_ON: u8 = 0x01;
+    const CONTINUOUS_HIGH_RES: u8 = 0x10;
+
+    fn open(bus: u8, address: u8) -> Result<Self, String> {
+        let mut device = crate::services::i2c::I2cDevice::open(bus, address)?;
+        device
+            .write(&[Self::POWER_ON])
+            .and_then(|_| device.write(&[Self::CONTINUOUS_HIGH_RES]))
+            .map_err(|e| format!("Failed to start light sensor: {}", e))?;
+        Ok(Self { device })
+    }
+
+    fn read_lux(&mut self) -> std::io::Result<f64> {
+        let mut raw = [0u8; 2];
+        self.device.read(&mut raw)?;
+        Ok(u16::from_be_bytes(raw) as f64 / 1.2)
+    }
+}
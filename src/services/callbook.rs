@@ -0,0 +1,332 @@
+//! Callbook lookups for the spot detail page (`[qrz]`, `[hamqth]`)
+//!
+//! Callsigns are looked up on a background thread only when their detail
+//! page opens, at most one request per `MIN_INTERVAL` to each service, and
+//! each answer (including "not found") is kept for the rest of the run.
+//! With both services enabled QRZ.com is asked first and HamQTH fills in
+//! calls QRZ doesn't know or couldn't answer. Answers arrive after the
+//! page is up, so callers refresh it from `poll`.
+
+use crate::config::CallbookConfig;
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a service gets to answer
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Least time between requests to one service, keeping well inside its
+/// limits
+const MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Answers kept before the cache starts over
+const CACHE_SIZE: usize = 1000;
+
+/// Operator details from a callbook; fields it leaves out (e.g. the grid
+/// without a QRZ subscription) are empty
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Operator {
+    pub name: String,
+    pub grid: String,
+    pub country: String,
+}
+
+/// Looks up callsigns in the enabled callbooks and caches the answers
+pub struct Callbook {
+    requests: Sender<String>,
+    answers: Receiver<Answer>,
+    /// Looked-up callsigns; None while waiting and for calls no callbook
+    /// knows
+    cache: HashMap<String, Option<Operator>>,
+}
+
+/// The callbooks' answer for one callsign
+struct Answer {
+    call: String,
+    operator: Result<Option<Operator>, ()>,
+}
+
+impl Callbook {
+    /// Log in to the enabled services as lookups come in, or None when
+    /// neither is enabled
+    pub fn start(qrz: &CallbookConfig, hamqth: &CallbookConfig) -> Option<Self> {
+        let mut sessions: Vec<Session> = [(Service::Qrz, qrz), (Service::HamQth, hamqth)]
+            .into_iter()
+            .filter(|(_, config)| config.enabled)
+            .filter_map(|(service, config)| {
+                if config.username.trim().is_empty() || config.password.is_empty() {
+                    log::warn!("{} lookups need a username and password", service.name());
+                    return None;
+                }
+                Some(Session {
+                    service,
+                    username: config.username.trim().to_string(),
+                    password: config.password.expose().to_string(),
+                    key: None,
+                    agent: ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build(),
+                    last_request: None,
+                })
+            })
+            .collect();
+        if sessions.is_empty() {
+            return None;
+        }
+        let (requests, request_rx) = mpsc::channel::<String>();
+        let (answer_tx, answers) = mpsc::channel();
+
+        thread::spawn(move || {
+            for call in request_rx {
+                let operator = lookup(&mut sessions, &call);
+                if answer_tx.send(Answer { call, operator }).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Some(Self {
+            requests,
+            answers,
+            cache: HashMap::new(),
+        })
+    }
+
+    /// Details for a callsign if a callbook has answered; otherwise asks
+    /// for them (the answer comes through `poll`)
+    pub fn get(&mut self, callsign: &str) -> Option<&Operator> {
+        let call = callsign.trim().to_uppercase();
+        if !self.cache.contains_key(&call) {
+            if self.cache.len() >= CACHE_SIZE {
+                self.cache.clear();
+            }
+            self.cache.insert(call.clone(), None);
+            let _ = self.requests.send(call.clone());
+        }
+        self.cache.get(&call).and_then(Option::as_ref)
+    }
+
+    /// Callsigns answered since the last call, so a detail page showing
+    /// one can be rebuilt. Failed lookups are asked again next time.
+    pub fn poll(&mut self) -> Vec<String> {
+        let mut answered = Vec::new();
+        while let Ok(answer) = self.answers.try_recv() {
+            match answer.operator {
+                Ok(operator) => {
+                    if operator.is_some() {
+                        answered.push(answer.call.clone());
+                    }
+                    self.cache.insert(answer.call, operator);
+                }
+                Err(()) => {
+                    self.cache.remove(&answer.call);
+                }
+            }
+        }
+        answered
+    }
+}
+
+/// First answer for a call from the sessions in order; an error only when
+/// no service found it and one of them failed
+fn lookup(sessions: &mut [Session], call: &str) -> Result<Option<Operator>, ()> {
+    let mut failed = false;
+    for session in sessions {
+        match session.lookup(call) {
+            Ok(Some(operator)) => return Ok(Some(operator)),
+            Ok(None) => {}
+            Err(e) => {
+                log::warn!(
+                    "{} lookup of {} failed: {}",
+                    session.service.name(),
+                    call,
+                    e
+                );
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        Err(())
+    } else {
+        Ok(None)
+    }
+}
+
+/// A callbook's XML API
+#[derive(Debug, Clone, Copy)]
+enum Service {
+    Qrz,
+    HamQth,
+}
+
+impl Service {
+    fn name(self) -> &'static str {
+        match self {
+            Service::Qrz => "QRZ",
+            Service::HamQth => "HamQTH",
+        }
+    }
+
+    fn url(self) -> &'static str {
+        match self {
+            Service::Qrz => "https://xmldata.qrz.com/xml/current/",
+            Service::HamQth => "https://www.hamqth.com/xml.php",
+        }
+    }
+
+    /// Query parameters asking for a session key
+    fn login_params(self, username: &str, password: &str) -> Vec<(&'static str, String)> {
+        match self {
+            Service::Qrz => vec![
+                ("username", username.to_string()),
+                ("password", password.to_string()),
+                ("agent", program()),
+            ],
+            Service::HamQth => vec![("u", username.to_string()), ("p", password.to_string())],
+        }
+    }
+
+    /// Query parameters looking up a callsign
+    fn lookup_params(self, key: &str, call: &str) -> Vec<(&'static str, String)> {
+        match self {
+            Service::Qrz => vec![("s", key.to_string()), ("callsign", call.to_string())],
+            Service::HamQth => vec![
+                ("id", key.to_string()),
+                ("callsign", call.to_string()),
+                ("prg", program()),
+            ],
+        }
+    }
+
+    /// Session key in a login answer
+    fn key(self, xml: &str) -> Option<String> {
+        match self {
+            Service::Qrz => element(xml, "Key"),
+            Service::HamQth => element(xml, "session_id"),
+        }
+    }
+
+    fn error(self, xml: &str) -> Option<String> {
+        match self {
+            Service::Qrz => element(xml, "Error"),
+            Service::HamQth => element(xml, "error"),
+        }
+    }
+
+    /// Operator in a lookup answer
+    fn operator(self, xml: &str) -> Option<Operator> {
+        let (record, name) = match self {
+            Service::Qrz => {
+                let record = element(xml, "Callsign")?;
+                let field = |name: &str| element(&record, name).unwrap_or_default();
+                let name = format!("{} {}", field("fname"), field("name"));
+                (record, name)
+            }
+            Service::HamQth => {
+                let record = element(xml, "search")?;
+                let name = element(&record, "adr_name")
+                    .or_else(|| element(&record, "nick"))
+                    .unwrap_or_default();
+                (record, name)
+            }
+        };
+        let field = |name: &str| element(&record, name).unwrap_or_default();
+        Some(Operator {
+            name: name.trim().to_string(),
+            grid: field("grid").to_uppercase(),
+            country: field("country"),
+        })
+    }
+}
+
+/// Login state for one service on the lookup thread
+struct Session {
+    service: Service,
+    username: String,
+    password: String,
+    /// Session key, once logged in
+    key: Option<String>,
+    agent: ureq::Agent,
+    last_request: Option<Instant>,
+}
+
+impl Session {
+    /// Look up a callsign, logging in first (or again, when the session
+    /// has expired). Ok(None) when the service doesn't know the call.
+    fn lookup(&mut self, call: &str) -> Result<Option<Operator>, String> {
+        for _ in 0..2 {
+            let key = match &self.key {
+                Some(key) => key.clone(),
+                None => self.login()?,
+            };
+            let xml = self.get(&self.service.lookup_params(&key, call))?;
+            if let Some(operator) = self.service.operator(&xml) {
+                return Ok(Some(operator));
+            }
+            match self.service.error(&xml) {
+                Some(error) if error.to_lowercase().contains("not found") => return Ok(None),
+                // Expired or replaced by another login; log in once more
+                Some(error) if error.to_lowercase().contains("session") => {
+                    self.key = None;
+                }
+                Some(error) => return Err(error),
+                None => return Err("answer without a callsign record".to_string()),
+            }
+        }
+        Err("could not keep a session".to_string())
+    }
+
+    /// New session key
+    fn login(&mut self) -> Result<String, String> {
+        let xml = self.get(&self.service.login_params(&self.username, &self.password))?;
+        match self.service.key(&xml) {
+            Some(key) => {
+                self.key = Some(key.clone());
+                Ok(key)
+            }
+            None => Err(self
+                .service
+                .error(&xml)
+                .unwrap_or_else(|| "login refused".to_string())),
+        }
+    }
+
+    /// GET the API with query parameters, waiting out `MIN_INTERVAL`
+    fn get(&mut self, params: &[(&str, String)]) -> Result<String, String> {
+        if let Some(last) = self.last_request {
+            thread::sleep(MIN_INTERVAL.saturating_sub(last.elapsed()));
+        }
+        self.last_request = Some(Instant::now());
+        let mut request = self.agent.get(self.service.url());
+        for (name, value) in params {
+            request = request.query(name, value);
+        }
+        request
+            .call()
+            .map_err(|e| e.to_string())?
+            .into_string()
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Program name and version sent to the services
+fn program() -> String {
+    format!("rbn-vfd-{}", env!("CARGO_PKG_VERSION"))
+}
+
+/// Text of the first `<name>` element, with entities decoded
+fn element(xml: &str, name: &str) -> Option<String> {
+    let open = format!("<{}>", name);
+    let close = format!("</{}>", name);
+    let start = xml.find(&open)? + open.len();
+    let end = start + xml[start..].find(&close)?;
+    Some(
+        xml[start..end]
+            .trim()
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&amp;", "&"),
+    )
+}
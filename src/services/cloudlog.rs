@@ -0,0 +1,229 @@
+//! Cloudlog / Wavelog logbook (`[cloudlog]`)
+//!
+//! Each callsign spotted on a band and mode is looked up once in the
+//! logbook (`api/private_lookup`, again after `RECHECK_AFTER`) on a
+//! background thread; stations already worked there, or confirmed with
+//! `confirmed = true`, come back through `try_recv` as `Qso`s for the
+//! worked-before state, like ADIF log entries. With a `radio` name set,
+//! tuning a spot also sets that radio's frequency and mode in the logbook
+//! (`api/radio`), so its QSO entry form follows the rig.
+
+use crate::config::CloudlogConfig;
+use crate::models::Band;
+use crate::services::adif::Qso;
+use crate::services::SpotStore;
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, TrySendError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
+
+/// How long the logbook gets to answer
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A callsign, band and mode isn't looked up again for this long
+const RECHECK_AFTER: Duration = Duration::from_secs(30 * 60);
+
+/// Lookups waiting for the logbook; spots beyond this are looked up on a
+/// later spot instead
+const QUEUE_LENGTH: usize = 200;
+
+/// Looks up spotted stations in the logbook and reports QSYs to it
+pub struct Cloudlog {
+    api: Arc<Api>,
+    /// Radio name for QSY reports ("" for none)
+    radio: String,
+    worked: Receiver<Qso>,
+}
+
+impl Cloudlog {
+    /// Start looking up the store's spots, or None when not enabled
+    pub fn start(config: &CloudlogConfig, spot_store: &SpotStore) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        if config.url.trim().is_empty() || config.api_key.is_empty() {
+            log::warn!("Cloudlog needs a url and api_key");
+            return None;
+        }
+        let api = Arc::new(Api {
+            url: config.url.trim().trim_end_matches('/').to_string(),
+            key: config.api_key.expose().to_string(),
+            agent: ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build(),
+        });
+        let confirmed = config.confirmed;
+        let (lookup_tx, lookup_rx) = mpsc::sync_channel::<Lookup>(QUEUE_LENGTH);
+        let (worked_tx, worked) = mpsc::channel();
+        let mut spots = spot_store.subscribe();
+
+        thread::spawn(move || {
+            let mut checked: HashMap<String, Instant> = HashMap::new();
+            loop {
+                let spot = match spots.blocking_recv() {
+                    Ok(spot) => spot,
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                };
+                let Some(band) = Band::from_khz(spot.frequency_khz) else {
+                    continue;
+                };
+                let lookup = Lookup {
+                    call: spot.callsign.to_uppercase(),
+                    band,
+                    mode: spot.mode.to_uppercase(),
+                };
+                let key = format!("{}|{}|{}", lookup.call, band.name(), lookup.mode);
+                let now = Instant::now();
+                if checked
+                    .get(&key)
+                    .is_some_and(|last| now.duration_since(*last) < RECHECK_AFTER)
+                {
+                    continue;
+                }
+                match lookup_tx.try_send(lookup) {
+                    Ok(()) => {
+                        checked.retain(|_, last| now.duration_since(*last) < RECHECK_AFTER);
+                        checked.insert(key, now);
+                    }
+                    Err(TrySendError::Full(_)) => {}
+                    Err(TrySendError::Disconnected(_)) => break,
+                }
+            }
+        });
+
+        let lookup_api = Arc::clone(&api);
+        thread::spawn(move || {
+            let mut failing = false;
+            for lookup in lookup_rx {
+                match lookup_api.worked(&lookup, confirmed) {
+                    Ok(worked) => {
+                        if failing {
+                            log::info!("Cloudlog answering again");
+                        }
+                        failing = false;
+                        let qso = Qso {
+                            call: lookup.call,
+                            band: Some(lookup.band),
+                        };
+                        if worked && worked_tx.send(qso).is_err() {
+                            break;
+                        }
+                    }
+                    // Reported once until it answers again
+                    Err(e) => {
+                        if !failing {
+                            log::warn!("Cloudlog lookup failed: {}", e);
+                        }
+                        failing = true;
+                    }
+                }
+            }
+        });
+
+        Some(Self {
+            api,
+            radio: config.radio.trim().to_string(),
+            worked,
+        })
+    }
+
+    /// Next station the logbook has as worked (or confirmed), if any
+    pub fn try_recv(&self) -> Option<Qso> {
+        self.worked.try_recv().ok()
+    }
+
+    /// Report a tune the rig took as the `radio`'s frequency and mode (a
+    /// rig mode, e.g. "CW" or "PKTUSB"), when a radio name is set
+    pub fn qsy(&self, frequency_khz: f64, mode: &str) {
+        if self.radio.is_empty() {
+            return;
+        }
+        let api = Arc::clone(&self.api);
+        let radio = self.radio.clone();
+        let mode = mode.to_uppercase();
+        thread::spawn(move || {
+            if let Err(e) = api.radio(&radio, frequency_khz, &mode) {
+                log::warn!("Cloudlog QSY failed: {}", e);
+            }
+        });
+    }
+}
+
+/// A spotted station to look up
+struct Lookup {
+    call: String,
+    band: Band,
+    mode: String,
+}
+
+/// The logbook's API
+struct Api {
+    /// Base URL without a trailing slash, e.g.
+    /// "https://log.example.com/index.php"
+    url: String,
+    key: String,
+    agent: ureq::Agent,
+}
+
+impl Api {
+    /// Whether the logbook has the station worked (or confirmed) on the
+    /// band and mode
+    fn worked(&self, lookup: &Lookup, confirmed: bool) -> Result<bool, String> {
+        let answer = self.post(
+            "private_lookup",
+            serde_json::json!({
+                "key": self.key,
+                "callsign": lookup.call,
+                "band": lookup.band.name(),
+                "mode": lookup.mode,
+            }),
+        )?;
+        // Only Wavelog reports confirmations
+        let field = if confirmed {
+            "call_confirmed_band_mode"
+        } else {
+            "workedBefore"
+        };
+        Ok(answer.get(field).is_some_and(truthy))
+    }
+
+    /// Set a radio's frequency and mode, as a CAT program would
+    fn radio(&self, radio: &str, frequency_khz: f64, mode: &str) -> Result<(), String> {
+        self.post(
+            "radio",
+            serde_json::json!({
+                "key": self.key,
+                "radio": radio,
+                "frequency": (frequency_khz * 1000.0).round() as u64,
+                "mode": mode,
+            }),
+        )
+        .map(|_| ())
+    }
+
+    /// POST a JSON request to `api/<endpoint>` and parse the answer
+    fn post(&self, endpoint: &str, body: serde_json::Value) -> Result<serde_json::Value, String> {
+        let url = format!("{}/api/{}", self.url, endpoint);
+        let text = self
+            .agent
+            .post(&url)
+            .set("Content-Type", "application/json")
+            .send_string(&body.to_string())
+            .map_err(|e| format!("{}: {}", url, e))?
+            .into_string()
+            .map_err(|e| format!("{}: {}", url, e))?;
+        // The radio endpoint may answer with nothing to parse
+        Ok(serde_json::from_str(&text).unwrap_or(serde_json::Value::Null))
+    }
+}
+
+/// A flag as the logbook may send it: true, 1 or "1"
+fn truthy(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Bool(flag) => *flag,
+        serde_json::Value::Number(number) => number.as_u64().is_some_and(|n| n > 0),
+        serde_json::Value::String(text) => matches!(text.trim(), "1" | "true" | "Y"),
+        _ => false,
+    }
+}
@@ -0,0 +1,221 @@
+//! Club Log most-wanted ranking and log (`[clublog]`)
+//!
+//! Club Log ranks every DXCC entity by how many of its users still need
+//! it. A background thread loads the ranking cached in the cache directory
+//! and downloads a fresh one when it is older than `refresh_hours`, like
+//! the LoTW users list. With a login and API key, your own log is fetched
+//! the same way and its QSOs count as worked, so entities and band slots
+//! confirmed or worked anywhere you upload stop showing as needed.
+
+use crate::config::ClubLogConfig;
+use crate::paths;
+use crate::services::adif::{self, Qso};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Entities by rank as JSON: {"1": "P5", "2": "3Y/B", ...}
+const MOST_WANTED_URL: &str = "https://clublog.org/mostwanted.php?api=1";
+
+/// A Club Log user's whole log as ADIF
+const LOG_URL: &str = "https://clublog.org/getadif.php";
+
+/// How long a download may take
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Wait before trying a failed download again
+const RETRY_AFTER: Duration = Duration::from_secs(60 * 60);
+
+/// Hands over the most-wanted ranking and logged QSOs as they are loaded
+pub struct ClubLog {
+    ranks: Receiver<HashMap<String, u32>>,
+    qsos: Receiver<Vec<Qso>>,
+}
+
+/// What is fetched and cached
+enum Source {
+    MostWanted,
+    Log {
+        call: String,
+        email: String,
+        password: String,
+        api_key: String,
+    },
+}
+
+impl ClubLog {
+    /// Load the cached ranking (and log) and keep them fresh, or None when
+    /// not enabled. `callsign` is `[station] callsign`, used for the log
+    /// unless `[clublog] callsign` is set.
+    pub fn start(config: &ClubLogConfig, callsign: &str) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let mut sources = vec![(Source::MostWanted, paths::clublog_most_wanted_file())];
+        let call = match config.callsign.trim() {
+            "" => callsign.trim().to_uppercase(),
+            call => call.to_uppercase(),
+        };
+        if !config.email.trim().is_empty() && !config.password.is_empty() {
+            if config.api_key.is_empty() || call.is_empty() {
+                log::warn!("Club Log log download needs an api_key and callsign");
+            } else {
+                let source = Source::Log {
+                    call,
+                    email: config.email.trim().to_string(),
+                    password: config.password.expose().to_string(),
+                    api_key: config.api_key.expose().to_string(),
+                };
+                sources.push((source, paths::clublog_log_file()));
+            }
+        }
+        let Some(sources) = sources
+            .into_iter()
+            .map(|(source, path)| Some((source, path?)))
+            .collect::<Option<Vec<(Source, PathBuf)>>>()
+        else {
+            log::warn!("No cache directory for Club Log data");
+            return None;
+        };
+
+        let (ranks_tx, ranks) = mpsc::channel();
+        let (qsos_tx, qsos) = mpsc::channel();
+        let refresh = Duration::from_secs(u64::from(config.refresh_hours) * 60 * 60);
+        thread::spawn(move || {
+            for (source, path) in &sources {
+                match fs::read_to_string(path) {
+                    Ok(text) => source.pass_on(&text, &ranks_tx, &qsos_tx),
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                    Err(e) => log::warn!("Failed to read {}: {}", path.display(), e),
+                }
+            }
+            // 0 keeps to the cached copies
+            if refresh.is_zero() {
+                return;
+            }
+            loop {
+                let mut wait = refresh;
+                for (source, path) in &sources {
+                    let age = fs::metadata(path)
+                        .and_then(|meta| meta.modified())
+                        .ok()
+                        .and_then(|modified| SystemTime::now().duration_since(modified).ok());
+                    match age {
+                        Some(age) if age < refresh => wait = wait.min(refresh - age),
+                        _ => match source.download(path) {
+                            Ok(text) => source.pass_on(&text, &ranks_tx, &qsos_tx),
+                            Err(e) => {
+                                log::warn!("Club Log {} download failed: {}", source.name(), e);
+                                wait = wait.min(RETRY_AFTER);
+                            }
+                        },
+                    }
+                }
+                thread::sleep(wait);
+            }
+        });
+        Some(Self { ranks, qsos })
+    }
+
+    /// Most-wanted rank by upper-case entity prefix, when a new ranking
+    /// has been loaded
+    pub fn try_recv_ranks(&self) -> Option<HashMap<String, u32>> {
+        self.ranks.try_recv().ok()
+    }
+
+    /// QSOs from the Club Log log, when it has been loaded again
+    pub fn try_recv(&self) -> Option<Vec<Qso>> {
+        self.qsos.try_recv().ok()
+    }
+}
+
+impl Source {
+    /// For log lines
+    fn name(&self) -> &'static str {
+        match self {
+            Source::MostWanted => "most-wanted list",
+            Source::Log { .. } => "log",
+        }
+    }
+
+    /// Parse loaded text and send it on
+    fn pass_on(
+        &self,
+        text: &str,
+        ranks_tx: &Sender<HashMap<String, u32>>,
+        qsos_tx: &Sender<Vec<Qso>>,
+    ) {
+        match self {
+            Source::MostWanted => {
+                let ranks = parse_ranks(text);
+                log::info!("Club Log most-wanted list: {} entities", ranks.len());
+                let _ = ranks_tx.send(ranks);
+            }
+            Source::Log { .. } => {
+                let (qsos, _) = adif::parse_records(text);
+                log::info!("Club Log log: {} QSOs", qsos.len());
+                let _ = qsos_tx.send(qsos);
+            }
+        }
+    }
+
+    /// Fetch a fresh copy and cache it at `path`, replacing the old one
+    /// only once the new one is complete
+    fn download(&self, path: &Path) -> Result<String, String> {
+        log::info!("Downloading the Club Log {}", self.name());
+        let agent = ureq::AgentBuilder::new().timeout(DOWNLOAD_TIMEOUT).build();
+        let response = match self {
+            Source::MostWanted => agent.get(MOST_WANTED_URL).call(),
+            Source::Log {
+                call,
+                email,
+                password,
+                api_key,
+            } => agent.post(LOG_URL).send_form(&[
+                ("email", email),
+                ("password", password),
+                ("call", call),
+                ("api", api_key),
+            ]),
+        }
+        // ureq's errors name the URL, which holds no secrets
+        .map_err(|e| e.to_string())?;
+        // Larger than `into_string` allows
+        let mut text = String::new();
+        response
+            .into_reader()
+            .read_to_string(&mut text)
+            .map_err(|e| e.to_string())?;
+        let empty = match self {
+            Source::MostWanted => parse_ranks(&text).is_empty(),
+            Source::Log { .. } => !text.to_ascii_uppercase().contains("<EOH>"),
+        };
+        if empty {
+            return Err("nothing usable in the answer".to_string());
+        }
+
+        let partial = path.with_extension("part");
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        }
+        fs::write(&partial, &text)
+            .and_then(|()| fs::rename(&partial, path))
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(text)
+    }
+}
+
+/// Ranks by upper-case prefix from the most-wanted JSON; anything else
+/// gives an empty ranking
+fn parse_ranks(text: &str) -> HashMap<String, u32> {
+    let Ok(list) = serde_json::from_str::<HashMap<String, String>>(text) else {
+        return HashMap::new();
+    };
+    list.into_iter()
+        .filter_map(|(rank, prefix)| Some((prefix.trim().to_uppercase(), rank.parse().ok()?)))
+        .collect()
+}
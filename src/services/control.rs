@@ -0,0 +1,187 @@
+//! Runtime settings changes
+//!
+//! Buttons, keys, the GUI and web clients don't change the running settings
+//! themselves: each queues an `AppCommand` on the app's `Control`, and the
+//! main loop applies them in order, logging every change with its source.
+
+use crate::config::Config;
+use crate::models::{Band, SpotSort};
+use serde::Deserialize;
+use std::fmt;
+use std::sync::mpsc;
+
+/// A change to the running settings
+///
+/// Web clients send these as JSON, e.g. `{"set_min_snr": 12}`,
+/// `{"set_band": "20m"}` (`null` for all bands), `{"set_sort": "recency"}`,
+/// `{"set_radio": "ic7300"}`, `{"set_preset": "All CW"}` or `"next_profile"`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AppCommand {
+    /// Minimum SNR in dB
+    SetMinSnr(i32),
+    StepMinSnr(i32),
+    /// Band to show (None for all)
+    SetBand(Option<Band>),
+    /// Step through the bands, wrapping through all
+    StepBand(isize),
+    SetCwOnly(bool),
+    ToggleCwOnly,
+    /// Oldest spot to show, in minutes
+    SetMaxAge(u32),
+    SetSort(SpotSort),
+    /// Step through frequency, recency and SNR order
+    NextSort,
+    /// Switch to a `[profile.<name>]` ("" for the main settings)
+    SetProfile(String),
+    NextProfile,
+    /// Switch to the `[[preset]]` with this name ("" for the filters alone)
+    SetPreset(String),
+    NextPreset,
+    /// Switch to the `[[radio]]` entry with this name
+    SetRadio(String),
+    NextRadio,
+}
+
+impl AppCommand {
+    /// Change `config`, returning a status line such as "Min SNR: 12 dB"
+    pub fn apply(&self, config: &mut Config) -> Result<String, String> {
+        match self {
+            AppCommand::SetMinSnr(snr) => config.min_snr = (*snr).clamp(0, 50),
+            AppCommand::StepMinSnr(step) => config.min_snr = (config.min_snr + step).clamp(0, 50),
+            AppCommand::SetBand(band) => config.band_filter = *band,
+            AppCommand::StepBand(step) => {
+                config.band_filter = Band::cycle(config.band_filter, *step)
+            }
+            AppCommand::SetCwOnly(on) => config.cw_only = *on,
+            AppCommand::ToggleCwOnly => config.cw_only = !config.cw_only,
+            AppCommand::SetMaxAge(minutes) => config.max_age_minutes = (*minutes).max(1),
+            AppCommand::SetSort(sort) => config.sort = *sort,
+            AppCommand::NextSort => config.sort = config.sort.next(),
+            AppCommand::SetProfile(name) => config.select_profile(name)?,
+            AppCommand::NextProfile => config.next_profile(),
+            AppCommand::SetPreset(name) => config.select_preset(name)?,
+            AppCommand::NextPreset => config.next_preset(),
+            AppCommand::SetRadio(name) => config.select_radio(name)?,
+            AppCommand::NextRadio => config.next_radio(),
+        }
+        Ok(match self {
+            AppCommand::SetMinSnr(_) | AppCommand::StepMinSnr(_) => {
+                format!("Min SNR: {} dB", config.min_snr)
+            }
+            AppCommand::SetBand(_) | AppCommand::StepBand(_) => format!(
+                "Band: {}",
+                config.band_filter.map_or("all", |band| band.name())
+            ),
+            AppCommand::SetCwOnly(_) | AppCommand::ToggleCwOnly => {
+                format!("Modes: {}", if config.cw_only { "CW" } else { "all" })
+            }
+            AppCommand::SetMaxAge(_) => format!("Max age: {} min", config.max_age_minutes),
+            AppCommand::SetSort(_) | AppCommand::NextSort => {
+                format!("Sort: {}", config.sort.name())
+            }
+            AppCommand::SetProfile(_) | AppCommand::NextProfile => {
+                format!("Profile: {}", config.profile_label())
+            }
+            AppCommand::SetPreset(_) | AppCommand::NextPreset => {
+                format!("Preset: {}", config.preset_label())
+            }
+            AppCommand::SetRadio(_) | AppCommand::NextRadio => {
+                format!("Radio: {}", config.radio_label())
+            }
+        })
+    }
+
+    /// Whether it switches profile (callers then hand the new alert settings
+    /// to their services)
+    pub fn changes_profile(&self) -> bool {
+        matches!(self, AppCommand::SetProfile(_) | AppCommand::NextProfile)
+    }
+
+    /// Whether it switches radio (callers then reconfigure their
+    /// `RadioManager`)
+    pub fn changes_radio(&self) -> bool {
+        matches!(self, AppCommand::SetRadio(_) | AppCommand::NextRadio)
+    }
+}
+
+/// Where a command came from, for the log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandSource {
+    /// Controls in the settings window
+    Gui,
+    /// Terminal UI keys
+    Keyboard,
+    /// Front-panel buttons and encoder
+    Button,
+    /// Matrix Orbital keypad
+    Keypad,
+    /// Web dashboard clients
+    Web,
+    /// gRPC clients
+    Grpc,
+}
+
+impl fmt::Display for CommandSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CommandSource::Gui => "gui",
+            CommandSource::Keyboard => "keyboard",
+            CommandSource::Button => "button",
+            CommandSource::Keypad => "keypad",
+            CommandSource::Web => "web",
+            CommandSource::Grpc => "grpc",
+        })
+    }
+}
+
+/// Queue of settings changes, drained by the main loop
+pub struct Control {
+    tx: mpsc::Sender<(CommandSource, AppCommand)>,
+    rx: mpsc::Receiver<(CommandSource, AppCommand)>,
+}
+
+impl Default for Control {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Control {
+    /// Queue that logs each change
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self { tx, rx }
+    }
+
+    /// Queue a command for the next `try_apply`
+    pub fn send(&self, source: CommandSource, command: AppCommand) {
+        let _ = self.tx.send((source, command));
+    }
+
+    /// Sender for queueing commands from other threads
+    pub fn sender(&self) -> mpsc::Sender<(CommandSource, AppCommand)> {
+        self.tx.clone()
+    }
+
+    /// Apply the next queued command to `config`, returning it with its
+    /// status line (None when the queue is empty). With `keep_adjustments`
+    /// the new filters are written to state.toml.
+    pub fn try_apply(&self, config: &mut Config) -> Option<(AppCommand, Result<String, String>)> {
+        let (source, command) = self.rx.try_recv().ok()?;
+        let result = command.apply(config).map(|status| {
+            if !config.keep_adjustments || command.changes_radio() {
+                return status;
+            }
+            match config.save_state() {
+                Ok(()) => status,
+                Err(e) => format!("{} (not kept: {})", status, e),
+            }
+        });
+        match &result {
+            Ok(status) => log::info!("[{}] {}", source, status),
+            Err(e) => log::warn!("[{}] {:?} failed: {}", source, command, e),
+        }
+        Some((command, result))
+    }
+}
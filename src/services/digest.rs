@@ -0,0 +1,321 @@
+//! Email digest of rare spots (`email` feature, `[digest]`)
+//!
+//! Watch-list hits and new entities or slots are collected on a thread as
+//! they alert, with the best SNR any later spot of the station on that band
+//! reaches, and mailed over SMTP once a day or week at `send_at`, so the
+//! ones heard while nobody was in the shack aren't missed.
+
+use crate::config::DigestConfig;
+use crate::models::{local_minute_of_day, AggregatedSpot, Band, RawSpot, Units};
+use crate::services::alerts::{split_list, Alert};
+use crate::services::SpotStore;
+use chrono::{DateTime, Datelike, Local, NaiveDate, Utc, Weekday};
+use chrono_tz::Tz;
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::Duration;
+use tokio::sync::broadcast::error::TryRecvError;
+
+/// How often the thread checks the schedule
+const TICK: Duration = Duration::from_secs(1);
+/// How long the mail server gets to answer
+const SEND_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Collects alerting spots for the digest
+pub struct Digest {
+    entries: Sender<Entry>,
+    /// Lower-case alert kinds listed
+    kinds: Vec<String>,
+}
+
+/// A station heard on a band, from its first alert
+#[derive(Debug, Clone)]
+struct Entry {
+    callsign: String,
+    band: Option<Band>,
+    frequency_khz: f64,
+    /// Why it's listed, e.g. "New DXCC: 3Y (Bouvet)"
+    reason: String,
+    first_heard: DateTime<Utc>,
+    last_heard: DateTime<Utc>,
+    best_snr: i32,
+}
+
+impl Entry {
+    /// Callsign and band, as entries are merged
+    fn key(&self) -> String {
+        format!("{}|{}", self.callsign, self.band.map_or("", Band::name))
+    }
+}
+
+impl Digest {
+    /// Start collecting, or None when it isn't enabled
+    pub fn start(config: &DigestConfig, units: Units, spot_store: &SpotStore) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let (entries, entry_rx) = mpsc::channel::<Entry>();
+        let mut schedule = Schedule::new(config, units.timezone);
+        let mut updates = spot_store.subscribe();
+        let kinds = split_list(&config.alerts)
+            .into_iter()
+            .map(|kind| kind.to_lowercase())
+            .collect();
+        let config = config.clone();
+
+        thread::spawn(move || {
+            let mut heard: HashMap<String, Entry> = HashMap::new();
+            loop {
+                match entry_rx.recv_timeout(TICK) {
+                    Ok(entry) => add(&mut heard, entry),
+                    Err(mpsc::RecvTimeoutError::Timeout) => {}
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                }
+                loop {
+                    match updates.try_recv() {
+                        Ok(spot) => better_spot(&mut heard, &spot),
+                        Err(TryRecvError::Lagged(_)) => {}
+                        Err(TryRecvError::Empty | TryRecvError::Closed) => break,
+                    }
+                }
+
+                let now = Utc::now();
+                if !schedule.due(now) || (heard.is_empty() && !config.send_empty) {
+                    continue;
+                }
+                let mut listed: Vec<Entry> = heard.values().cloned().collect();
+                listed.sort_by_key(|entry| entry.first_heard);
+                let (subject, body) = compose(&listed, schedule.weekly.is_some(), &units, now);
+                match send(&config, subject, body) {
+                    Ok(()) => {
+                        log::info!("Digest of {} stations sent", listed.len());
+                        heard.clear();
+                    }
+                    // Kept for the next one
+                    Err(e) => log::warn!("Digest not sent: {}", e),
+                }
+            }
+        });
+
+        Some(Self { entries, kinds })
+    }
+
+    /// List the spot behind an alert, when the digest takes its kind
+    pub fn record(&self, alert: &Alert, spot: &RawSpot) {
+        if !self.kinds.iter().any(|kind| kind == alert.kind()) {
+            return;
+        }
+        let reason = match alert {
+            Alert::Watch { .. } => "Watch list".to_string(),
+            Alert::NewDxcc {
+                entity,
+                name: Some(name),
+                ..
+            } => format!("New DXCC: {} ({})", entity, name),
+            Alert::NewDxcc { entity, .. } => format!("New DXCC: {}", entity),
+            Alert::NewSlot { entity, band, .. } => format!("New slot: {} {}", entity, band),
+            Alert::SelfSpot { .. } | Alert::Rule { .. } => return,
+        };
+        let now = Utc::now();
+        let _ = self.entries.send(Entry {
+            callsign: spot.spotted_callsign.to_uppercase(),
+            band: Band::from_khz(spot.frequency_khz),
+            frequency_khz: spot.frequency_khz,
+            reason,
+            first_heard: now,
+            last_heard: now,
+            best_snr: spot.snr,
+        });
+    }
+}
+
+/// Merge an alert into the list; a station alerting again on a band keeps
+/// its first time and reason
+fn add(heard: &mut HashMap<String, Entry>, entry: Entry) {
+    match heard.get_mut(&entry.key()) {
+        Some(listed) => {
+            listed.last_heard = entry.last_heard;
+            listed.best_snr = listed.best_snr.max(entry.best_snr);
+        }
+        None => {
+            heard.insert(entry.key(), entry);
+        }
+    }
+}
+
+/// Raise a listed station's best SNR from a later spot on the same band
+fn better_spot(heard: &mut HashMap<String, Entry>, spot: &AggregatedSpot) {
+    let key = format!(
+        "{}|{}",
+        spot.callsign.to_uppercase(),
+        Band::from_khz(spot.frequency_khz).map_or("", Band::name)
+    );
+    if let Some(listed) = heard.get_mut(&key) {
+        listed.last_heard = Utc::now();
+        if spot.highest_snr > listed.best_snr {
+            listed.best_snr = spot.highest_snr;
+            listed.frequency_khz = spot.frequency_khz;
+        }
+    }
+}
+
+/// When the next digest is due
+struct Schedule {
+    /// Day of a weekly digest; None for daily
+    weekly: Option<Weekday>,
+    /// Minute of the day it goes out
+    minute: u32,
+    zone: Option<Tz>,
+    /// Local date of the last one, so each goes out once
+    last: Option<NaiveDate>,
+}
+
+impl Schedule {
+    fn new(config: &DigestConfig, zone: Option<Tz>) -> Self {
+        let weekly = config
+            .period
+            .trim()
+            .eq_ignore_ascii_case("weekly")
+            .then(|| config.weekday.trim().parse().unwrap_or(Weekday::Mon));
+        let minute = config
+            .send_at
+            .trim()
+            .split_once(':')
+            .and_then(|(hour, minute)| {
+                Some(hour.parse::<u32>().ok()? * 60 + minute.parse::<u32>().ok()?)
+            })
+            .filter(|minute| *minute < 24 * 60)
+            .unwrap_or(7 * 60);
+        let mut schedule = Self {
+            weekly,
+            minute,
+            zone,
+            last: None,
+        };
+        // Started after today's time: the first one goes out next time
+        let now = Utc::now();
+        if local_minute_of_day(now, zone) >= minute {
+            schedule.last = Some(local_date(now, zone));
+        }
+        schedule
+    }
+
+    /// Whether one should go out now, counting it as sent if so
+    fn due(&mut self, now: DateTime<Utc>) -> bool {
+        let today = local_date(now, self.zone);
+        if self.last == Some(today)
+            || self.weekly.is_some_and(|day| today.weekday() != day)
+            || local_minute_of_day(now, self.zone) < self.minute
+        {
+            return false;
+        }
+        self.last = Some(today);
+        true
+    }
+}
+
+/// Date in `zone` (None = the system's)
+fn local_date(time: DateTime<Utc>, zone: Option<Tz>) -> NaiveDate {
+    match zone {
+        Some(zone) => time.with_timezone(&zone).date_naive(),
+        None => time.with_timezone(&Local).date_naive(),
+    }
+}
+
+/// Subject and plain-text body
+/// Example line: "2024-03-17 14:23Z  20m  14025.3 kHz  3Y0J  New DXCC: 3Y (Bouvet), best 23 dB"
+fn compose(listed: &[Entry], weekly: bool, units: &Units, now: DateTime<Utc>) -> (String, String) {
+    let period = if weekly { "Weekly" } else { "Daily" };
+    let subject = match listed.len() {
+        0 => format!(
+            "{} RBN digest {}: nothing rare heard",
+            period,
+            units.date(now)
+        ),
+        1 => format!("{} RBN digest {}: 1 station", period, units.date(now)),
+        count => format!(
+            "{} RBN digest {}: {} stations",
+            period,
+            units.date(now),
+            count
+        ),
+    };
+    let mut body = String::new();
+    if listed.is_empty() {
+        body.push_str("No watch-list or new-entity spots since the last digest.\n");
+    }
+    for entry in listed {
+        body.push_str(&format!(
+            "{} {}{}  {:<4} {:>12}  {:<10} {}, best {} dB",
+            units.date(entry.first_heard),
+            units.time(entry.first_heard),
+            units.zone(),
+            entry.band.map_or("-", Band::name),
+            units.frequency_with_unit(entry.frequency_khz),
+            entry.callsign,
+            entry.reason,
+            entry.best_snr
+        ));
+        if entry
+            .last_heard
+            .signed_duration_since(entry.first_heard)
+            .num_minutes()
+            >= 1
+        {
+            body.push_str(&format!(
+                ", heard until {}{}",
+                units.time(entry.last_heard),
+                units.zone()
+            ));
+        }
+        body.push('\n');
+    }
+    (subject, body)
+}
+
+/// Mail the digest to every `to` address
+fn send(config: &DigestConfig, subject: String, body: String) -> Result<(), String> {
+    let from = config.from.trim();
+    let mut message = Message::builder()
+        .from(
+            from.parse()
+                .map_err(|e| format!("sender '{}': {}", from, e))?,
+        )
+        .subject(subject)
+        .header(ContentType::TEXT_PLAIN);
+    for to in config
+        .to
+        .split(',')
+        .map(str::trim)
+        .filter(|to| !to.is_empty())
+    {
+        message = message.to(to
+            .parse()
+            .map_err(|e| format!("recipient '{}': {}", to, e))?);
+    }
+    let message = message.body(body).map_err(|e| e.to_string())?;
+
+    let host = config.smtp_host.trim();
+    let builder = match config.security.trim().to_lowercase().as_str() {
+        "tls" => SmtpTransport::relay(host),
+        "none" => Ok(SmtpTransport::builder_dangerous(host)),
+        _ => SmtpTransport::starttls_relay(host),
+    }
+    .map_err(|e| format!("{}: {}", host, e))?;
+    let mut builder = builder.port(config.smtp_port).timeout(Some(SEND_TIMEOUT));
+    if !config.username.trim().is_empty() {
+        builder = builder.credentials(Credentials::new(
+            config.username.trim().to_string(),
+            config.password.expose().to_string(),
+        ));
+    }
+    builder
+        .build()
+        .send(&message)
+        .map(|_| ())
+        .map_err(|e| format!("{}: {}", host, e))
+}
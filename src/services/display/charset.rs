@@ -0,0 +1,109 @@
+//! Character translation for displays with a limited character set
+//!
+//! Text is translated per output before it is written: characters the
+//! driver shows natively (`DisplayDriver::shows_char`) pass through, others
+//! are replaced from the configured table, then the built-in
+//! transliterations, and finally with `?`.
+
+use super::glyphs::Glyph;
+use super::DisplayDriver;
+use std::collections::HashMap;
+
+/// Translation table for one output
+#[derive(Debug, Clone, Default)]
+pub struct Charset {
+    /// Configured substitutions, applied even to characters the driver has
+    overrides: HashMap<char, String>,
+}
+
+impl Charset {
+    /// Parse `[display] charset_map` entries "from=to" separated by commas,
+    /// e.g. "Ä=AE,Ö=OE,Ü=UE,\=/". An empty `to` drops the character.
+    pub fn parse(map: &str) -> Self {
+        let overrides = map
+            .split(',')
+            .filter_map(|entry| {
+                let (from, to) = entry.trim().split_once('=')?;
+                let mut chars = from.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(from), None) => Some((from, to.to_string())),
+                    _ => None,
+                }
+            })
+            .collect();
+        Self { overrides }
+    }
+
+    /// Text the driver can show, with glyph markers left for `GlyphSlots`
+    pub fn translate(&self, text: &str, driver: &dyn DisplayDriver) -> String {
+        let mut out = String::with_capacity(text.len());
+        for c in text.chars() {
+            if let Some(to) = self.overrides.get(&c) {
+                out.push_str(to);
+            } else if Glyph::from_marker(c).is_some() || driver.shows_char(c) {
+                out.push(c);
+            } else {
+                out.push_str(transliterate(c).unwrap_or("?"));
+            }
+        }
+        out
+    }
+}
+
+/// Readable ASCII stand-ins for common non-ASCII characters
+fn transliterate(c: char) -> Option<&'static str> {
+    Some(match c {
+        'À'..='Å' | 'Ā' | 'Ă' | 'Ą' => "A",
+        'à'..='å' | 'ā' | 'ă' | 'ą' => "a",
+        'Æ' => "AE",
+        'æ' => "ae",
+        'Ç' | 'Ć' | 'Č' => "C",
+        'ç' | 'ć' | 'č' => "c",
+        'Ď' | 'Đ' => "D",
+        'ď' | 'đ' => "d",
+        'È'..='Ë' | 'Ē' | 'Ė' | 'Ę' | 'Ě' => "E",
+        'è'..='ë' | 'ē' | 'ė' | 'ę' | 'ě' => "e",
+        'Ğ' => "G",
+        'ğ' => "g",
+        'Ì'..='Ï' | 'İ' => "I",
+        'ì'..='ï' | 'ı' => "i",
+        'Ł' => "L",
+        'ł' => "l",
+        'Ñ' | 'Ń' | 'Ň' => "N",
+        'ñ' | 'ń' | 'ň' => "n",
+        'Ò'..='Ö' | 'Ø' | 'Ő' => "O",
+        'ò'..='ö' | 'ø' | 'ő' => "o",
+        'Œ' => "OE",
+        'œ' => "oe",
+        'Ř' => "R",
+        'ř' => "r",
+        'Ś' | 'Š' | 'Ş' => "S",
+        'ś' | 'š' | 'ş' => "s",
+        'ß' => "ss",
+        'Ť' => "T",
+        'ť' => "t",
+        'Ù'..='Ü' | 'Ů' | 'Ű' => "U",
+        'ù'..='ü' | 'ů' | 'ű' => "u",
+        'Ý' | 'Ÿ' => "Y",
+        'ý' | 'ÿ' => "y",
+        'Ź' | 'Ż' | 'Ž' => "Z",
+        'ź' | 'ż' | 'ž' => "z",
+        'Þ' => "TH",
+        'þ' => "th",
+        '‘' | '’' | '‚' | '′' | '´' => "'",
+        '“' | '”' | '„' | '″' | '«' | '»' => "\"",
+        '‐' | '‑' | '‒' | '–' | '—' | '−' => "-",
+        '…' => "...",
+        '\\' => "/",
+        '~' => "-",
+        '°' => "o",
+        '×' => "x",
+        '÷' => "/",
+        '±' => "+-",
+        '≥' => ">=",
+        '≤' => "<=",
+        'µ' => "u",
+        '\u{a0}' | '\t' => " ",
+        _ => return None,
+    })
+}
@@ -0,0 +1,77 @@
+//! Optional color hints for color-capable outputs
+//!
+//! Frames carry spans of colored characters alongside their text. Drivers
+//! with `Capabilities::color` get them through `write_colored_line`;
+//! monochrome drivers only ever see the text.
+
+use crate::models::Band;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A 24-bit color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    /// CSS style "#rrggbb"
+    pub fn hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+
+    /// Parse "#rrggbb" (the leading '#' is optional)
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.trim().trim_start_matches('#');
+        if hex.len() != 6 {
+            return None;
+        }
+        let channel = |at: usize| u8::from_str_radix(hex.get(at..at + 2)?, 16).ok();
+        Some(Self(channel(0)?, channel(2)?, channel(4)?))
+    }
+}
+
+impl Serialize for Rgb {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for Rgb {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        Rgb::from_hex(&hex)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid color '{}'", hex)))
+    }
+}
+
+/// Callsigns on the watch list
+pub const WATCH: Rgb = Rgb(255, 72, 72);
+
+/// Stations in an entity (and band slot) already worked
+pub const WORKED: Rgb = Rgb(110, 110, 110);
+
+/// Status footer while the feed or radio is down
+pub const OFFLINE: Rgb = Rgb(255, 170, 0);
+
+/// Color for characters `start..end` of a row (counted in chars)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColorSpan {
+    pub start: usize,
+    pub end: usize,
+    pub color: Rgb,
+}
+
+/// Conventional band map colors, warm on the low bands to cool on the high
+pub fn band_color(band: Band) -> Rgb {
+    match band {
+        Band::B160m => Rgb(190, 120, 255),
+        Band::B80m => Rgb(255, 110, 200),
+        Band::B60m => Rgb(255, 150, 150),
+        Band::B40m => Rgb(255, 170, 60),
+        Band::B30m => Rgb(255, 220, 80),
+        Band::B20m => Rgb(120, 230, 90),
+        Band::B17m => Rgb(80, 220, 180),
+        Band::B15m => Rgb(80, 200, 255),
+        Band::B12m => Rgb(110, 150, 255),
+        Band::B10m => Rgb(200, 200, 255),
+        Band::B6m => Rgb(255, 255, 255),
+    }
+}
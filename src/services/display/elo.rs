@@ -0,0 +1,67 @@
+//! ELO customer-facing VFD (simple protocol, no escape sequences)
+
+use super::{open_serial, DisplayDriver, DisplayResult};
+use serialport::SerialPort;
+use std::io::Write;
+
+const CLEAR_DISPLAY: &[u8] = &[0x0C]; // Form feed - clear and home cursor
+
+/// Driver for the ELO 20x2 VFD
+///
+/// The display has no cursor addressing, so lines are buffered and the
+/// whole screen is rewritten on `flush`: clear, then every row in order.
+pub struct EloDriver {
+    port: Box<dyn SerialPort>,
+    columns: usize,
+    lines: Vec<String>,
+}
+
+impl EloDriver {
+    pub fn open(
+        port_name: &str,
+        baud_rate: u32,
+        columns: usize,
+        rows: usize,
+    ) -> DisplayResult<Self> {
+        Ok(Self {
+            port: open_serial(port_name, baud_rate)?,
+            columns,
+            lines: vec![String::new(); rows],
+        })
+    }
+}
+
+impl DisplayDriver for EloDriver {
+    fn name(&self) -> &'static str {
+        "elo"
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        (self.columns, self.lines.len())
+    }
+
+    fn clear(&mut self) -> DisplayResult<()> {
+        self.lines.iter_mut().for_each(String::clear);
+        self.port.write_all(CLEAR_DISPLAY)?;
+        Ok(())
+    }
+
+    fn write_line(&mut self, row: usize, text: &str) -> DisplayResult<()> {
+        if let Some(line) = self.lines.get_mut(row) {
+            *line = text.to_string();
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> DisplayResult<()> {
+        self.port.write_all(CLEAR_DISPLAY)?;
+        for line in &self.lines {
+            let padded: String = format!("{:width$}", line, width = self.columns)
+                .chars()
+                .take(self.columns)
+                .collect();
+            self.port.write_all(padded.as_bytes())?;
+        }
+        Ok(())
+    }
+}
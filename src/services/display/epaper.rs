@@ -0,0 +1,306 @@
+//! Waveshare e-paper panels (SSD1680 controller) as a low-power band monitor
+//!
+//! E-paper is slow to refresh and wears with use, so frames are not pushed
+//! as they change. The panel gets a full redraw every `full_refresh_minutes`,
+//! and a quicker partial refresh in between only after a rare spot is
+//! announced through `notify` (alert kinds in `[display] attention`).
+//! Other changes wait for the next refresh.
+
+use super::{Capabilities, DisplayDriver, DisplayError, DisplayResult};
+use crate::services::gpio::{InputPin, OutputPin};
+use crate::services::spi::SpiDevice;
+use embedded_graphics::mono_font::ascii::FONT_9X15;
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::text::{Baseline, Text};
+use std::time::{Duration, Instant};
+
+const CHAR_WIDTH: u32 = 9;
+const LINE_HEIGHT: u32 = 15;
+
+/// Longest the controller may stay busy before we give up
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Supported panels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Panel {
+    /// 2.13" V3/V4, 250x122
+    Waveshare2in13,
+    /// 2.9" V2, 296x128
+    Waveshare2in9,
+}
+
+impl Panel {
+    /// Native (portrait) size: (source lines, gate lines)
+    fn native_size(self) -> (u32, u32) {
+        match self {
+            Panel::Waveshare2in13 => (122, 250),
+            Panel::Waveshare2in9 => (128, 296),
+        }
+    }
+}
+
+/// Native-orientation 1bpp buffer (MSB first, 1 = white), drawn in landscape
+struct FrameBuffer {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl FrameBuffer {
+    fn new(panel: Panel) -> Self {
+        let (width, height) = panel.native_size();
+        Self {
+            width,
+            height,
+            pixels: vec![0xFF; (width.div_ceil(8) * height) as usize],
+        }
+    }
+
+    fn clear(&mut self) {
+        self.pixels.fill(0xFF);
+    }
+}
+
+impl OriginDimensions for FrameBuffer {
+    /// Landscape size
+    fn size(&self) -> Size {
+        Size::new(self.height, self.width)
+    }
+}
+
+impl DrawTarget for FrameBuffer {
+    type Color = BinaryColor;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            let (Ok(x), Ok(y)) = (u32::try_from(point.x), u32::try_from(point.y)) else {
+                continue;
+            };
+            if x >= self.height || y >= self.width {
+                continue;
+            }
+            // Rotate landscape (x, y) onto the portrait panel
+            let (native_x, native_y) = (y, self.height - 1 - x);
+            let index = (native_y * self.width.div_ceil(8) + native_x / 8) as usize;
+            let bit = 0x80 >> (native_x % 8);
+            if color.is_on() {
+                self.pixels[index] &= !bit;
+            } else {
+                self.pixels[index] |= bit;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Driver for Waveshare SSD1680 e-paper panels on SPI
+pub struct EpaperDriver {
+    panel: Panel,
+    spi: SpiDevice,
+    dc: OutputPin,
+    reset: Option<OutputPin>,
+    busy: InputPin,
+    frame: FrameBuffer,
+    full_refresh_interval: Duration,
+    last_full_refresh: Option<Instant>,
+    /// Text requested for each row
+    lines: Vec<String>,
+    /// Text currently on the panel
+    shown: Vec<String>,
+    /// A rare spot was announced since the last refresh
+    rare_spot: bool,
+}
+
+impl EpaperDriver {
+    pub fn open(
+        panel: Panel,
+        spi: SpiDevice,
+        dc: OutputPin,
+        reset: Option<OutputPin>,
+        busy: InputPin,
+        full_refresh_minutes: u32,
+    ) -> DisplayResult<Self> {
+        let frame = FrameBuffer::new(panel);
+        let rows = (frame.width / LINE_HEIGHT) as usize;
+        Ok(Self {
+            panel,
+            spi,
+            dc,
+            reset,
+            busy,
+            frame,
+            full_refresh_interval: Duration::from_secs(full_refresh_minutes.max(1) as u64 * 60),
+            last_full_refresh: None,
+            lines: vec![String::new(); rows],
+            shown: vec![String::new(); rows],
+            rare_spot: false,
+        })
+    }
+
+    fn command(&mut self, command: u8, data: &[u8]) -> DisplayResult<()> {
+        self.dc.set(false)?;
+        self.spi.write(&[command])?;
+        if !data.is_empty() {
+            self.dc.set(true)?;
+            self.spi.write(data)?;
+        }
+        Ok(())
+    }
+
+    fn wait_busy(&mut self) -> DisplayResult<()> {
+        let deadline = Instant::now() + BUSY_TIMEOUT;
+        while self.busy.is_high()? {
+            if Instant::now() >= deadline {
+                return Err(DisplayError::WriteFailed(
+                    "e-paper panel stayed busy".to_string(),
+                ));
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        Ok(())
+    }
+
+    /// Send the framebuffer and refresh, fully or partially
+    fn refresh(&mut self, full: bool) -> DisplayResult<()> {
+        self.command(0x4E, &[0x00])?;
+        self.command(0x4F, &[0x00, 0x00])?;
+        let pixels = self.frame.pixels.clone();
+        self.command(0x24, &pixels)?;
+        if full {
+            // Base image for later partial refreshes
+            self.command(0x4E, &[0x00])?;
+            self.command(0x4F, &[0x00, 0x00])?;
+            self.command(0x26, &pixels)?;
+        }
+        self.command(0x22, &[if full { 0xF7 } else { 0xFF }])?;
+        self.command(0x20, &[])?;
+        self.wait_busy()?;
+        if full {
+            self.last_full_refresh = Some(Instant::now());
+        }
+        self.rare_spot = false;
+        self.shown = self.lines.clone();
+        Ok(())
+    }
+
+    fn render(&mut self) {
+        self.frame.clear();
+        let style = MonoTextStyle::new(&FONT_9X15, BinaryColor::On);
+        for (row, line) in self.lines.iter().enumerate() {
+            let top = (row as u32 * LINE_HEIGHT) as i32;
+            let _ = Text::with_baseline(line, Point::new(0, top), style, Baseline::Top)
+                .draw(&mut self.frame);
+        }
+    }
+}
+
+impl DisplayDriver for EpaperDriver {
+    fn name(&self) -> &'static str {
+        "epaper"
+    }
+
+    fn init(&mut self) -> DisplayResult<()> {
+        if let Some(reset) = self.reset.as_mut() {
+            reset.set(false)?;
+            std::thread::sleep(Duration::from_millis(10));
+            reset.set(true)?;
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        self.wait_busy()?;
+        self.command(0x12, &[])?; // software reset
+        self.wait_busy()?;
+
+        let (width, height) = self.panel.native_size();
+        let [last_gate_lo, last_gate_hi] = ((height - 1) as u16).to_le_bytes();
+        let last_column = (width.div_ceil(8) - 1) as u8;
+        self.command(0x01, &[last_gate_lo, last_gate_hi, 0x00])?; // driver output
+        self.command(0x11, &[0x03])?; // data entry: x then y increment
+        self.command(0x44, &[0x00, last_column])?; // RAM x range
+        self.command(0x45, &[0x00, 0x00, last_gate_lo, last_gate_hi])?; // RAM y range
+        self.command(0x3C, &[0x05])?; // border waveform
+        self.command(0x21, &[0x00, 0x80])?; // display update control
+        self.command(0x18, &[0x80])?; // internal temperature sensor
+        self.wait_busy()?;
+        self.clear()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            graphic: true,
+            notify: true,
+            ..Capabilities::default()
+        }
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        (
+            (self.frame.height / CHAR_WIDTH) as usize,
+            (self.frame.width / LINE_HEIGHT) as usize,
+        )
+    }
+
+    fn clear(&mut self) -> DisplayResult<()> {
+        self.lines.iter_mut().for_each(String::clear);
+        self.frame.clear();
+        self.refresh(true)
+    }
+
+    fn write_line(&mut self, row: usize, text: &str) -> DisplayResult<()> {
+        if let Some(line) = self.lines.get_mut(row) {
+            *line = text.trim_end().to_string();
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> DisplayResult<()> {
+        if self.lines == self.shown {
+            return Ok(());
+        }
+
+        let full_due = self
+            .last_full_refresh
+            .is_none_or(|last| last.elapsed() >= self.full_refresh_interval);
+        if full_due || self.rare_spot {
+            self.render();
+            self.refresh(full_due)?;
+        }
+        Ok(())
+    }
+
+    /// Show the next frame with a partial refresh rather than waiting for
+    /// the full one; the spot is already in the list
+    fn notify(&mut self, _text: &str) -> DisplayResult<()> {
+        self.rare_spot = true;
+        Ok(())
+    }
+}
+
+impl Drop for EpaperDriver {
+    fn drop(&mut self) {
+        // Deep sleep; the image stays on the panel
+        let _ = self.command(0x10, &[0x01]);
+    }
+}
+
+/// Open an e-paper panel from the display config
+pub fn open_from_config(
+    panel: Panel,
+    config: &crate::config::DisplayConfig,
+) -> DisplayResult<EpaperDriver> {
+    let spi = SpiDevice::open(config.spi_bus, config.spi_chip_select, config.spi_speed_hz)
+        .map_err(DisplayError::ConnectionFailed)?;
+    let dc = OutputPin::open(config.dc_pin).map_err(DisplayError::ConnectionFailed)?;
+    let reset = config
+        .reset_pin
+        .map(OutputPin::open)
+        .transpose()
+        .map_err(DisplayError::ConnectionFailed)?;
+    let busy = InputPin::open(config.busy_pin).map_err(DisplayError::ConnectionFailed)?;
+    EpaperDriver::open(panel, spi, dc, reset, busy, config.full_refresh_minutes)
+}
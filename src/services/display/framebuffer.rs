@@ -0,0 +1,251 @@
+//! Linux framebuffer (/dev/fbN) renderer for small HDMI/DPI screens
+//!
+//! Draws the spot lines with embedded-graphics into an off-screen copy of
+//! the framebuffer and writes changed rows back, so no X server is needed.
+
+use super::color::ColorSpan;
+use super::scaled::Doubled;
+use super::{Capabilities, DisplayDriver, DisplayError, DisplayResult};
+use embedded_graphics::mono_font::ascii::FONT_10X20;
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::Rgb888;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+use embedded_graphics::text::{Baseline, Text};
+use std::fs::{File, OpenOptions};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::Path;
+
+const CHAR_WIDTH: u32 = 10;
+const LINE_HEIGHT: u32 = 20;
+
+/// VFD-style colors
+const FOREGROUND: Rgb888 = Rgb888::new(0, 255, 0);
+const BACKGROUND: Rgb888 = Rgb888::BLACK;
+
+/// Off-screen copy of the framebuffer in its native pixel format
+struct Canvas {
+    width: u32,
+    height: u32,
+    bytes_per_pixel: u32,
+    stride: u32,
+    pixels: Vec<u8>,
+}
+
+impl OriginDimensions for Canvas {
+    fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+}
+
+impl DrawTarget for Canvas {
+    type Color = Rgb888;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            let (Ok(x), Ok(y)) = (u32::try_from(point.x), u32::try_from(point.y)) else {
+                continue;
+            };
+            if x >= self.width || y >= self.height {
+                continue;
+            }
+            let offset = (y * self.stride + x * self.bytes_per_pixel) as usize;
+            match self.bytes_per_pixel {
+                2 => {
+                    let rgb565 = ((color.r() as u16 >> 3) << 11)
+                        | ((color.g() as u16 >> 2) << 5)
+                        | (color.b() as u16 >> 3);
+                    self.pixels[offset..offset + 2].copy_from_slice(&rgb565.to_le_bytes());
+                }
+                _ => {
+                    // XRGB8888, little endian
+                    self.pixels[offset..offset + 4].copy_from_slice(&[
+                        color.b(),
+                        color.g(),
+                        color.r(),
+                        0,
+                    ]);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Path of a sysfs attribute of a framebuffer device
+fn sysfs_path(device: &str, attribute: &str) -> String {
+    let name = Path::new(device)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("fb0");
+    format!("/sys/class/graphics/{}/{}", name, attribute)
+}
+
+/// Read a sysfs attribute of a framebuffer device
+fn sysfs_attribute(device: &str, attribute: &str) -> DisplayResult<String> {
+    let path = sysfs_path(device, attribute);
+    std::fs::read_to_string(&path)
+        .map(|value| value.trim().to_string())
+        .map_err(|e| DisplayError::ConnectionFailed(format!("Failed to read {}: {}", path, e)))
+}
+
+/// Driver that renders text lines onto a Linux framebuffer
+pub struct FramebufferDriver {
+    /// Device path, for its sysfs attributes
+    path: String,
+    device: File,
+    canvas: Canvas,
+    lines: Vec<String>,
+    dirty_rows: Vec<bool>,
+}
+
+impl FramebufferDriver {
+    pub fn open(device: &str) -> DisplayResult<Self> {
+        let invalid = |what: &str| {
+            DisplayError::ConnectionFailed(format!("Unexpected {} for {}", what, device))
+        };
+
+        let size = sysfs_attribute(device, "virtual_size")?;
+        let (width, height) = size
+            .split_once(',')
+            .and_then(|(w, h)| Some((w.parse::<u32>().ok()?, h.parse::<u32>().ok()?)))
+            .ok_or_else(|| invalid("virtual_size"))?;
+        let bits_per_pixel: u32 = sysfs_attribute(device, "bits_per_pixel")?
+            .parse()
+            .map_err(|_| invalid("bits_per_pixel"))?;
+        if bits_per_pixel != 16 && bits_per_pixel != 32 {
+            return Err(DisplayError::ConnectionFailed(format!(
+                "{} uses {} bits per pixel; only 16 and 32 are supported",
+                device, bits_per_pixel
+            )));
+        }
+        let stride: u32 = sysfs_attribute(device, "stride")?
+            .parse()
+            .map_err(|_| invalid("stride"))?;
+
+        let file = OpenOptions::new().write(true).open(device).map_err(|e| {
+            DisplayError::ConnectionFailed(format!("Failed to open {}: {}", device, e))
+        })?;
+
+        let rows = (height / LINE_HEIGHT) as usize;
+        Ok(Self {
+            path: device.to_string(),
+            device: file,
+            canvas: Canvas {
+                width,
+                height,
+                bytes_per_pixel: bits_per_pixel / 8,
+                stride,
+                pixels: vec![0; (stride * height) as usize],
+            },
+            lines: vec![String::new(); rows],
+            dirty_rows: vec![true; rows],
+        })
+    }
+
+    /// Write one text row's band of pixels to the device
+    fn write_row(&mut self, row: usize) -> DisplayResult<()> {
+        let start = row as u32 * LINE_HEIGHT * self.canvas.stride;
+        let end = start + LINE_HEIGHT * self.canvas.stride;
+        self.device.seek(SeekFrom::Start(start as u64))?;
+        self.device
+            .write_all(&self.canvas.pixels[start as usize..end as usize])?;
+        Ok(())
+    }
+
+    /// Blank `height` text rows from `row` down
+    fn clear_rows(&mut self, row: usize, height: u32) {
+        let top = (row as u32 * LINE_HEIGHT) as i32;
+        let _ = Rectangle::new(
+            Point::new(0, top),
+            Size::new(self.canvas.width, LINE_HEIGHT * height),
+        )
+        .into_styled(PrimitiveStyle::with_fill(BACKGROUND))
+        .draw(&mut self.canvas);
+    }
+}
+
+impl DisplayDriver for FramebufferDriver {
+    fn name(&self) -> &'static str {
+        "framebuffer"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            graphic: true,
+            large_text: true,
+            power: true,
+            ..Capabilities::default()
+        }
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        ((self.canvas.width / CHAR_WIDTH) as usize, self.lines.len())
+    }
+
+    fn clear(&mut self) -> DisplayResult<()> {
+        let _ = self.canvas.clear(BACKGROUND);
+        self.lines.iter_mut().for_each(String::clear);
+        self.dirty_rows.fill(true);
+        self.flush()
+    }
+
+    fn write_line(&mut self, row: usize, text: &str) -> DisplayResult<()> {
+        match self.lines.get_mut(row) {
+            Some(line) if line != text => *line = text.to_string(),
+            _ => return Ok(()),
+        }
+        self.dirty_rows[row] = true;
+
+        self.clear_rows(row, 1);
+        let top = (row as u32 * LINE_HEIGHT) as i32;
+        let style = MonoTextStyle::new(&FONT_10X20, FOREGROUND);
+        let _ = Text::with_baseline(text, Point::new(0, top), style, Baseline::Top)
+            .draw(&mut self.canvas);
+        Ok(())
+    }
+
+    fn write_large_line(
+        &mut self,
+        row: usize,
+        text: &str,
+        _colors: &[ColorSpan],
+    ) -> DisplayResult<()> {
+        if row + 1 >= self.lines.len() {
+            return self.write_line(row, text);
+        }
+        // Both rows are redrawn by the next normal write to either of them
+        self.lines[row].clear();
+        self.lines[row + 1].clear();
+        self.dirty_rows[row] = true;
+        self.dirty_rows[row + 1] = true;
+
+        self.clear_rows(row, 2);
+        let top = (row as u32 * LINE_HEIGHT) as i32;
+        let style = MonoTextStyle::new(&FONT_10X20, FOREGROUND);
+        let _ = Text::with_baseline(text, Point::zero(), style, Baseline::Top)
+            .draw(&mut Doubled::new(&mut self.canvas, Point::new(0, top)));
+        Ok(())
+    }
+
+    fn flush(&mut self) -> DisplayResult<()> {
+        for row in 0..self.dirty_rows.len() {
+            if self.dirty_rows[row] {
+                self.write_row(row)?;
+                self.dirty_rows[row] = false;
+            }
+        }
+        Ok(())
+    }
+
+    /// Blank the screen through sysfs (needs write access to `blank`)
+    fn set_power(&mut self, on: bool) -> DisplayResult<()> {
+        let path = sysfs_path(&self.path, "blank");
+        std::fs::write(&path, if on { "0" } else { "1" })
+            .map_err(|e| DisplayError::WriteFailed(format!("{}: {}", path, e)))
+    }
+}
@@ -0,0 +1,112 @@
+//! Futaba character VFD modules (M202MD / M202SD series)
+
+use super::{open_serial, rom_byte, rom_shows_char, Capabilities, DisplayDriver, DisplayResult};
+use serialport::SerialPort;
+use std::io::Write;
+
+const RESET: u8 = 0x1F;
+const DIM: u8 = 0x04;
+const CURSOR_OFF: u8 = 0x14; // DC4
+const OVERWRITE_MODE: u8 = 0x11; // DC1, no auto-scroll at the end of a line
+const ESC: u8 = 0x1B;
+
+/// DIM levels supported by the module (25%, 50%, 75%, 100%)
+const DIM_LEVELS: [u8; 4] = [0x20, 0x40, 0x60, 0xFF];
+
+/// Driver for Futaba 5x7 dot character VFDs
+pub struct FutabaDriver {
+    port: Box<dyn SerialPort>,
+    columns: usize,
+    rows: usize,
+}
+
+impl FutabaDriver {
+    pub fn open(
+        port_name: &str,
+        baud_rate: u32,
+        columns: usize,
+        rows: usize,
+    ) -> DisplayResult<Self> {
+        Ok(Self {
+            port: open_serial(port_name, baud_rate)?,
+            columns,
+            rows,
+        })
+    }
+
+    /// Move the cursor to a character cell (ESC H n, n counts across rows)
+    fn set_cursor(&mut self, column: usize, row: usize) -> DisplayResult<()> {
+        let position = (row * self.columns + column).min(u8::MAX as usize) as u8;
+        self.port.write_all(&[ESC, b'H', position])?;
+        Ok(())
+    }
+}
+
+impl DisplayDriver for FutabaDriver {
+    fn name(&self) -> &'static str {
+        "futaba"
+    }
+
+    fn init(&mut self) -> DisplayResult<()> {
+        self.port.write_all(&[RESET, OVERWRITE_MODE, CURSOR_OFF])?;
+        self.clear()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            brightness: true,
+            partial_writes: true,
+            ..Capabilities::default()
+        }
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        (self.columns, self.rows)
+    }
+
+    fn clear(&mut self) -> DisplayResult<()> {
+        for row in 0..self.rows {
+            self.write_line(row, "")?;
+        }
+        Ok(())
+    }
+
+    fn shows_char(&self, c: char) -> bool {
+        rom_shows_char(c)
+    }
+
+    fn write_line(&mut self, row: usize, text: &str) -> DisplayResult<()> {
+        if row >= self.rows {
+            return Ok(());
+        }
+        self.set_cursor(0, row)?;
+        let bytes: Vec<u8> = format!("{:width$}", text, width = self.columns)
+            .chars()
+            .take(self.columns)
+            .map(rom_byte)
+            .collect();
+        self.port.write_all(&bytes)?;
+        Ok(())
+    }
+
+    fn write_at(&mut self, row: usize, column: usize, text: &str) -> DisplayResult<()> {
+        if row >= self.rows || column >= self.columns {
+            return Ok(());
+        }
+        self.set_cursor(column, row)?;
+        let bytes: Vec<u8> = text
+            .chars()
+            .take(self.columns - column)
+            .map(rom_byte)
+            .collect();
+        self.port.write_all(&bytes)?;
+        Ok(())
+    }
+
+    fn set_brightness(&mut self, percent: u8) -> DisplayResult<()> {
+        let index = (percent.min(100) as usize * DIM_LEVELS.len()).div_ceil(100);
+        let level = DIM_LEVELS[index.clamp(1, DIM_LEVELS.len()) - 1];
+        self.port.write_all(&[DIM, level])?;
+        Ok(())
+    }
+}
@@ -0,0 +1,240 @@
+//! Custom 5x8 glyphs for band, mode and status indicators
+//!
+//! Layouts embed a glyph in a line as a private-use marker character.
+//! Before a frame goes to the hardware, `GlyphSlots` assigns each glyph in
+//! it one of the eight CGRAM slots (sent to the driver as characters
+//! `\0`-`\x07`), reprogramming slots as the set of glyphs on screen
+//! changes. Drivers without custom characters get an ASCII fallback.
+
+use super::{DisplayDriver, DisplayError};
+use crate::models::Band;
+
+/// Number of custom character slots on HD44780-style controllers
+pub const SLOT_COUNT: usize = 8;
+
+/// First private-use code point used for glyph markers
+const MARKER_BASE: u32 = 0xE000;
+
+/// Marker offset of the band glyphs
+const BAND_BASE: u32 = 16;
+
+/// An indicator that can be drawn as a custom character
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Glyph {
+    /// Band in meters as two stacked digits
+    Band(Band),
+    Cw,
+    Rtty,
+    /// FT8/FT4/PSK and other digital modes
+    Digital,
+    /// Station not yet worked (star)
+    NewDxcc,
+    TrendUp,
+    TrendDown,
+    /// Sunrise or sunset at one end of the path (half-lit disc)
+    GreyLine,
+}
+
+/// 3x4 digits for the stacked band labels, one row per byte (low 3 bits)
+const SMALL_DIGITS: [[u8; 4]; 10] = [
+    [0b111, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b111],
+    [0b110, 0b001, 0b010, 0b111],
+    [0b111, 0b011, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001],
+    [0b111, 0b110, 0b001, 0b110],
+    [0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010],
+    [0b111, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001],
+];
+
+impl Glyph {
+    /// Mode indicator for an RBN mode string
+    pub fn for_mode(mode: &str) -> Self {
+        match mode.to_ascii_uppercase().as_str() {
+            "CW" => Glyph::Cw,
+            "RTTY" => Glyph::Rtty,
+            _ => Glyph::Digital,
+        }
+    }
+
+    fn id(self) -> u32 {
+        match self {
+            Glyph::Cw => 0,
+            Glyph::Rtty => 1,
+            Glyph::Digital => 2,
+            Glyph::NewDxcc => 3,
+            Glyph::TrendUp => 4,
+            Glyph::TrendDown => 5,
+            Glyph::GreyLine => 6,
+            Glyph::Band(band) => {
+                BAND_BASE + Band::ALL.iter().position(|b| *b == band).unwrap_or(0) as u32
+            }
+        }
+    }
+
+    /// Marker character that stands for this glyph in layout text
+    pub fn marker(self) -> char {
+        char::from_u32(MARKER_BASE + self.id()).unwrap_or('?')
+    }
+
+    /// The glyph a marker character stands for, if any
+    pub fn from_marker(c: char) -> Option<Self> {
+        let id = (c as u32).checked_sub(MARKER_BASE)?;
+        match id {
+            0 => Some(Glyph::Cw),
+            1 => Some(Glyph::Rtty),
+            2 => Some(Glyph::Digital),
+            3 => Some(Glyph::NewDxcc),
+            4 => Some(Glyph::TrendUp),
+            5 => Some(Glyph::TrendDown),
+            6 => Some(Glyph::GreyLine),
+            _ => Band::ALL
+                .get(id.checked_sub(BAND_BASE)? as usize)
+                .map(|band| Glyph::Band(*band)),
+        }
+    }
+
+    /// Plain character used when the display has no custom characters
+    pub fn fallback(self) -> char {
+        match self {
+            Glyph::Band(_) => ' ',
+            Glyph::Cw => 'c',
+            Glyph::Rtty => 'r',
+            Glyph::Digital => 'd',
+            Glyph::NewDxcc => '*',
+            Glyph::TrendUp => '^',
+            Glyph::TrendDown => 'v',
+            Glyph::GreyLine => 'g',
+        }
+    }
+
+    /// 5x8 bitmap, one row per byte with the leftmost pixel in bit 4
+    pub fn bitmap(self) -> [u8; 8] {
+        match self {
+            Glyph::Band(band) => {
+                // "16" stands in for 160m so every label is two digits
+                let label = band.name().trim_end_matches('m');
+                let digits: Vec<usize> =
+                    label.bytes().take(2).map(|b| (b - b'0') as usize).collect();
+                let mut rows = [0u8; 8];
+                let (top, bottom) = match digits.as_slice() {
+                    [top, bottom] => (Some(*top), *bottom),
+                    [single] => (None, *single),
+                    _ => (None, 0),
+                };
+                if let Some(top) = top {
+                    for (row, bits) in SMALL_DIGITS[top].iter().enumerate() {
+                        rows[row] = bits << 1;
+                    }
+                }
+                for (row, bits) in SMALL_DIGITS[bottom].iter().enumerate() {
+                    rows[row + 4] = bits << 1;
+                }
+                rows
+            }
+            Glyph::Cw => [0, 0, 0, 0b10111, 0b10111, 0, 0, 0],
+            Glyph::Rtty => [0, 0, 0b01110, 0b01010, 0b01010, 0b11011, 0, 0],
+            Glyph::Digital => [0, 0b00011, 0, 0b00110, 0, 0b01100, 0, 0b11000],
+            Glyph::NewDxcc => [
+                0b00100, 0b00100, 0b11111, 0b01110, 0b01110, 0b11011, 0b10001, 0,
+            ],
+            Glyph::TrendUp => [
+                0b00100, 0b01110, 0b10101, 0b00100, 0b00100, 0b00100, 0b00100, 0,
+            ],
+            Glyph::TrendDown => [
+                0b00100, 0b00100, 0b00100, 0b00100, 0b10101, 0b01110, 0b00100, 0,
+            ],
+            Glyph::GreyLine => [
+                0b01110, 0b10011, 0b10011, 0b10011, 0b10011, 0b10011, 0b01110, 0,
+            ],
+        }
+    }
+}
+
+/// Replace glyph markers with their fallback characters
+pub fn strip_glyphs(text: &str) -> String {
+    text.chars()
+        .map(|c| Glyph::from_marker(c).map_or(c, Glyph::fallback))
+        .collect()
+}
+
+/// Which glyph is loaded in each custom character slot of a driver
+#[derive(Debug, Default)]
+pub struct GlyphSlots {
+    slots: [Option<Glyph>; SLOT_COUNT],
+    /// Set once the driver refuses a custom character
+    unsupported: bool,
+}
+
+impl GlyphSlots {
+    /// Forget loaded glyphs, e.g. after opening a new driver
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Load the glyphs used by a frame and translate its markers to slot
+    /// characters, falling back to plain characters when the driver has no
+    /// custom characters or the frame needs more than eight glyphs
+    pub fn prepare(&mut self, driver: &mut dyn DisplayDriver, lines: &[String]) -> Vec<String> {
+        if self.unsupported
+            || !driver.capabilities().custom_glyphs
+            || !lines.iter().any(|l| l.chars().any(is_marker))
+        {
+            return lines.iter().map(|line| strip_glyphs(line)).collect();
+        }
+
+        let mut wanted: Vec<Glyph> = Vec::new();
+        for glyph in lines
+            .iter()
+            .flat_map(|l| l.chars())
+            .filter_map(Glyph::from_marker)
+        {
+            if !wanted.contains(&glyph) {
+                wanted.push(glyph);
+            }
+        }
+
+        // Keep glyphs already loaded; reuse slots whose glyph left the screen
+        for glyph in &wanted {
+            if self.slots.contains(&Some(*glyph)) {
+                continue;
+            }
+            let free = self
+                .slots
+                .iter()
+                .position(|slot| slot.is_none_or(|loaded| !wanted.contains(&loaded)));
+            let Some(free) = free else {
+                break;
+            };
+            match driver.set_custom_glyph(free as u8, &glyph.bitmap()) {
+                Ok(()) => self.slots[free] = Some(*glyph),
+                Err(DisplayError::NotSupported) => {
+                    self.unsupported = true;
+                    return lines.iter().map(|line| strip_glyphs(line)).collect();
+                }
+                Err(_) => self.slots[free] = None,
+            }
+        }
+
+        lines
+            .iter()
+            .map(|line| {
+                line.chars()
+                    .map(|c| match Glyph::from_marker(c) {
+                        Some(glyph) => match self.slots.iter().position(|s| *s == Some(glyph)) {
+                            Some(slot) => slot as u8 as char,
+                            None => glyph.fallback(),
+                        },
+                        None => c,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+fn is_marker(c: char) -> bool {
+    Glyph::from_marker(c).is_some()
+}
@@ -0,0 +1,225 @@
+//! HD44780 character LCDs on a Raspberry Pi, via a PCF8574 I2C backpack
+//! or directly on GPIO pins (4-bit mode)
+
+use super::{rom_byte, rom_shows_char, Capabilities, DisplayDriver, DisplayError, DisplayResult};
+use crate::services::gpio::{parse_pins, OutputPin};
+use crate::services::i2c::I2cDevice;
+use std::time::Duration;
+
+const CLEAR: u8 = 0x01;
+const ENTRY_MODE_INCREMENT: u8 = 0x06;
+const DISPLAY_ON: u8 = 0x0C;
+const DISPLAY_OFF: u8 = 0x08;
+const FUNCTION_SET_4BIT_2LINE: u8 = 0x28;
+const SET_CGRAM_ADDRESS: u8 = 0x40;
+const SET_DDRAM_ADDRESS: u8 = 0x80;
+
+/// PCF8574 backpack wiring: P0=RS, P1=RW, P2=E, P3=backlight, P4-P7=D4-D7
+const PCF_RS: u8 = 0x01;
+const PCF_ENABLE: u8 = 0x04;
+const PCF_BACKLIGHT: u8 = 0x08;
+
+/// How the controller's 4-bit bus is wired
+trait Bus: Send {
+    /// Clock one nibble into the controller
+    fn write_nibble(&mut self, nibble: u8, data: bool) -> DisplayResult<()>;
+
+    fn set_backlight(&mut self, _on: bool) -> DisplayResult<()> {
+        Err(DisplayError::NotSupported)
+    }
+}
+
+struct I2cBus {
+    device: I2cDevice,
+    backlight: u8,
+}
+
+impl Bus for I2cBus {
+    fn write_nibble(&mut self, nibble: u8, data: bool) -> DisplayResult<()> {
+        let byte = (nibble << 4) | self.backlight | if data { PCF_RS } else { 0 };
+        self.device.write(&[byte | PCF_ENABLE, byte])?;
+        Ok(())
+    }
+
+    fn set_backlight(&mut self, on: bool) -> DisplayResult<()> {
+        self.backlight = if on { PCF_BACKLIGHT } else { 0 };
+        self.device.write(&[self.backlight])?;
+        Ok(())
+    }
+}
+
+/// RS, E, D4, D5, D6, D7
+struct GpioBus {
+    rs: OutputPin,
+    enable: OutputPin,
+    data: [OutputPin; 4],
+}
+
+impl Bus for GpioBus {
+    fn write_nibble(&mut self, nibble: u8, data: bool) -> DisplayResult<()> {
+        self.rs.set(data)?;
+        for (bit, pin) in self.data.iter_mut().enumerate() {
+            pin.set(nibble & (1 << bit) != 0)?;
+        }
+        self.enable.set(true)?;
+        self.enable.set(false)?;
+        Ok(())
+    }
+}
+
+/// Driver for HD44780-compatible character LCDs
+pub struct Hd44780Driver {
+    bus: Box<dyn Bus>,
+    columns: usize,
+    rows: usize,
+}
+
+impl Hd44780Driver {
+    /// Open an LCD behind a PCF8574 I2C backpack
+    pub fn open_i2c(bus: u8, address: u8, columns: usize, rows: usize) -> DisplayResult<Self> {
+        let device = I2cDevice::open(bus, address).map_err(DisplayError::ConnectionFailed)?;
+        Ok(Self {
+            bus: Box::new(I2cBus {
+                device,
+                backlight: PCF_BACKLIGHT,
+            }),
+            columns,
+            rows,
+        })
+    }
+
+    /// Open an LCD wired to GPIO pins, given as "rs,e,d4,d5,d6,d7"
+    pub fn open_gpio(pins: &str, columns: usize, rows: usize) -> DisplayResult<Self> {
+        let pins = parse_pins(pins).map_err(DisplayError::ConnectionFailed)?;
+        let [rs, enable, d4, d5, d6, d7] = pins[..] else {
+            return Err(DisplayError::ConnectionFailed(
+                "HD44780 GPIO mode needs six pins: rs,e,d4,d5,d6,d7".to_string(),
+            ));
+        };
+        let open = |pin| OutputPin::open(pin).map_err(DisplayError::ConnectionFailed);
+        let bus = GpioBus {
+            rs: open(rs)?,
+            enable: open(enable)?,
+            data: [open(d4)?, open(d5)?, open(d6)?, open(d7)?],
+        };
+        Ok(Self {
+            bus: Box::new(bus),
+            columns,
+            rows,
+        })
+    }
+
+    fn send(&mut self, byte: u8, data: bool) -> DisplayResult<()> {
+        self.bus.write_nibble(byte >> 4, data)?;
+        self.bus.write_nibble(byte & 0x0F, data)?;
+        std::thread::sleep(Duration::from_micros(50));
+        Ok(())
+    }
+
+    fn command(&mut self, command: u8) -> DisplayResult<()> {
+        self.send(command, false)
+    }
+
+    /// DDRAM address of the first cell of a row (rows 2/3 continue rows 0/1)
+    fn row_address(&self, row: usize) -> u8 {
+        let base = if row.is_multiple_of(2) { 0x00 } else { 0x40 };
+        (base + (row / 2) * self.columns) as u8
+    }
+}
+
+impl DisplayDriver for Hd44780Driver {
+    fn name(&self) -> &'static str {
+        "hd44780"
+    }
+
+    /// Power-on initialization into 4-bit mode
+    fn init(&mut self) -> DisplayResult<()> {
+        std::thread::sleep(Duration::from_millis(50));
+        for delay_us in [4500, 150, 150] {
+            self.bus.write_nibble(0x03, false)?;
+            std::thread::sleep(Duration::from_micros(delay_us));
+        }
+        self.bus.write_nibble(0x02, false)?;
+        self.command(FUNCTION_SET_4BIT_2LINE)?;
+        self.command(DISPLAY_ON)?;
+        self.command(ENTRY_MODE_INCREMENT)?;
+        self.clear()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            brightness: true,
+            custom_glyphs: true,
+            partial_writes: true,
+            power: true,
+            ..Capabilities::default()
+        }
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        (self.columns, self.rows)
+    }
+
+    fn clear(&mut self) -> DisplayResult<()> {
+        self.command(CLEAR)?;
+        std::thread::sleep(Duration::from_millis(2));
+        Ok(())
+    }
+
+    fn shows_char(&self, c: char) -> bool {
+        rom_shows_char(c)
+    }
+
+    fn write_line(&mut self, row: usize, text: &str) -> DisplayResult<()> {
+        if row >= self.rows {
+            return Ok(());
+        }
+        self.command(SET_DDRAM_ADDRESS | self.row_address(row))?;
+        let bytes: Vec<u8> = format!("{:width$}", text, width = self.columns)
+            .chars()
+            .take(self.columns)
+            .map(rom_byte)
+            .collect();
+        for byte in bytes {
+            self.send(byte, true)?;
+        }
+        Ok(())
+    }
+
+    fn write_at(&mut self, row: usize, column: usize, text: &str) -> DisplayResult<()> {
+        if row >= self.rows || column >= self.columns {
+            return Ok(());
+        }
+        self.command(SET_DDRAM_ADDRESS | (self.row_address(row) + column as u8))?;
+        let bytes: Vec<u8> = text
+            .chars()
+            .take(self.columns - column)
+            .map(rom_byte)
+            .collect();
+        for byte in bytes {
+            self.send(byte, true)?;
+        }
+        Ok(())
+    }
+
+    fn set_brightness(&mut self, percent: u8) -> DisplayResult<()> {
+        self.bus.set_backlight(percent > 0)
+    }
+
+    fn set_power(&mut self, on: bool) -> DisplayResult<()> {
+        self.command(if on { DISPLAY_ON } else { DISPLAY_OFF })?;
+        self.bus.set_backlight(on)
+    }
+
+    fn set_custom_glyph(&mut self, slot: u8, rows: &[u8; 8]) -> DisplayResult<()> {
+        if slot >= 8 {
+            return Err(DisplayError::NotSupported);
+        }
+        self.command(SET_CGRAM_ADDRESS | (slot << 3))?;
+        for row in rows {
+            self.send(row & 0x1F, true)?;
+        }
+        // Back to DDRAM so the next write lands on screen
+        self.command(SET_DDRAM_ADDRESS)
+    }
+}
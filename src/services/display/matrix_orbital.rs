@@ -0,0 +1,156 @@
+//! Matrix Orbital serial LCD/VFD modules (0xFE command prefix)
+//!
+//! Crystalfontz modules running Matrix Orbital emulation accept the same
+//! command set.
+
+use super::{open_serial, Capabilities, DisplayDriver, DisplayError, DisplayResult};
+use serialport::SerialPort;
+use std::io::{Read, Write};
+
+const PREFIX: u8 = 0xFE;
+const CLEAR_SCREEN: u8 = 0x58;
+const SET_CURSOR: u8 = 0x47;
+const AUTOSCROLL_OFF: u8 = 0x52;
+const WRAP_OFF: u8 = 0x44;
+const BLOCK_CURSOR_OFF: u8 = 0x54;
+const UNDERLINE_CURSOR_OFF: u8 = 0x4B;
+const BRIGHTNESS: u8 = 0x99;
+const GPO_ON: u8 = 0x57;
+const GPO_OFF: u8 = 0x56;
+const AUTO_TRANSMIT_KEYS: u8 = 0x41;
+const DEFINE_CHARACTER: u8 = 0x4E;
+const DISPLAY_OFF: u8 = 0x46;
+/// Followed by minutes to stay on, 0 for always
+const DISPLAY_ON: u8 = 0x42;
+
+/// Driver for Matrix Orbital LK/VK modules and compatibles
+pub struct MatrixOrbitalDriver {
+    port: Box<dyn SerialPort>,
+    columns: usize,
+    rows: usize,
+}
+
+impl MatrixOrbitalDriver {
+    pub fn open(
+        port_name: &str,
+        baud_rate: u32,
+        columns: usize,
+        rows: usize,
+    ) -> DisplayResult<Self> {
+        Ok(Self {
+            port: open_serial(port_name, baud_rate)?,
+            columns,
+            rows,
+        })
+    }
+
+    fn command(&mut self, bytes: &[u8]) -> DisplayResult<()> {
+        self.port.write_all(&[PREFIX])?;
+        self.port.write_all(bytes)?;
+        Ok(())
+    }
+}
+
+impl DisplayDriver for MatrixOrbitalDriver {
+    fn name(&self) -> &'static str {
+        "matrix_orbital"
+    }
+
+    fn init(&mut self) -> DisplayResult<()> {
+        for command in [
+            AUTOSCROLL_OFF,
+            WRAP_OFF,
+            BLOCK_CURSOR_OFF,
+            UNDERLINE_CURSOR_OFF,
+            AUTO_TRANSMIT_KEYS,
+        ] {
+            self.command(&[command])?;
+        }
+        self.clear()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            brightness: true,
+            custom_glyphs: true,
+            outputs: true,
+            keypad: true,
+            graphic: false,
+            partial_writes: true,
+            color: false,
+            large_text: false,
+            invert: false,
+            notify: false,
+            power: true,
+        }
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        (self.columns, self.rows)
+    }
+
+    fn clear(&mut self) -> DisplayResult<()> {
+        self.command(&[CLEAR_SCREEN])
+    }
+
+    fn write_line(&mut self, row: usize, text: &str) -> DisplayResult<()> {
+        if row >= self.rows {
+            return Ok(());
+        }
+        // Cursor positions are 1-based
+        self.command(&[SET_CURSOR, 1, row as u8 + 1])?;
+        let padded: String = format!("{:width$}", text, width = self.columns)
+            .chars()
+            .take(self.columns)
+            .collect();
+        self.port.write_all(padded.as_bytes())?;
+        Ok(())
+    }
+
+    fn write_at(&mut self, row: usize, column: usize, text: &str) -> DisplayResult<()> {
+        if row >= self.rows || column >= self.columns {
+            return Ok(());
+        }
+        self.command(&[SET_CURSOR, column as u8 + 1, row as u8 + 1])?;
+        let text: String = text.chars().take(self.columns - column).collect();
+        self.port.write_all(text.as_bytes())?;
+        Ok(())
+    }
+
+    fn set_brightness(&mut self, percent: u8) -> DisplayResult<()> {
+        let level = (percent.min(100) as u32 * 255 / 100) as u8;
+        self.command(&[BRIGHTNESS, level])
+    }
+
+    fn set_power(&mut self, on: bool) -> DisplayResult<()> {
+        if on {
+            self.command(&[DISPLAY_ON, 0])
+        } else {
+            self.command(&[DISPLAY_OFF])
+        }
+    }
+
+    fn set_custom_glyph(&mut self, slot: u8, rows: &[u8; 8]) -> DisplayResult<()> {
+        if slot >= 8 {
+            return Err(DisplayError::NotSupported);
+        }
+        let mut command = vec![DEFINE_CHARACTER, slot];
+        command.extend(rows.iter().map(|row| row & 0x1F));
+        self.command(&command)
+    }
+
+    fn set_output(&mut self, index: u8, on: bool) -> DisplayResult<()> {
+        self.command(&[if on { GPO_ON } else { GPO_OFF }, index])
+    }
+
+    fn read_keys(&mut self) -> DisplayResult<Vec<u8>> {
+        let pending = self.port.bytes_to_read().unwrap_or(0) as usize;
+        if pending == 0 {
+            return Ok(Vec::new());
+        }
+        let mut keys = vec![0; pending];
+        let read = self.port.read(&mut keys)?;
+        keys.truncate(read);
+        Ok(keys)
+    }
+}
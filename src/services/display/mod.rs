@@ -0,0 +1,566 @@
+//! Display driver abstraction for VFD/LCD hardware
+
+pub mod charset;
+pub mod color;
+#[cfg(feature = "vfd")]
+mod elo;
+#[cfg(all(target_os = "linux", feature = "epaper"))]
+mod epaper;
+#[cfg(all(target_os = "linux", feature = "framebuffer"))]
+mod framebuffer;
+#[cfg(feature = "vfd")]
+mod futaba;
+pub mod glyphs;
+#[cfg(all(target_os = "linux", feature = "hd44780"))]
+mod hd44780;
+#[cfg(feature = "vfd")]
+mod matrix_orbital;
+#[cfg(feature = "vfd")]
+mod noritake;
+#[cfg(all(target_os = "linux", feature = "oled"))]
+mod oled;
+pub mod remote;
+#[cfg(all(
+    target_os = "linux",
+    any(feature = "oled", feature = "framebuffer", feature = "tft")
+))]
+mod scaled;
+pub mod simulator;
+#[cfg(all(target_os = "linux", feature = "tft"))]
+mod tft;
+
+#[cfg(feature = "vfd")]
+pub use elo::EloDriver;
+#[cfg(all(target_os = "linux", feature = "epaper"))]
+pub use epaper::{EpaperDriver, Panel as EpaperPanel};
+#[cfg(all(target_os = "linux", feature = "framebuffer"))]
+pub use framebuffer::FramebufferDriver;
+#[cfg(feature = "vfd")]
+pub use futaba::FutabaDriver;
+#[cfg(all(target_os = "linux", feature = "hd44780"))]
+pub use hd44780::Hd44780Driver;
+#[cfg(feature = "vfd")]
+pub use matrix_orbital::MatrixOrbitalDriver;
+#[cfg(feature = "vfd")]
+pub use noritake::NoritakeDriver;
+#[cfg(all(target_os = "linux", feature = "oled"))]
+pub use oled::{Controller as OledController, OledDriver};
+pub use remote::RemoteDriver;
+pub use simulator::SimulatorDriver;
+#[cfg(all(target_os = "linux", feature = "tft"))]
+pub use tft::{Controller as TftController, TftDriver};
+
+use crate::config::DisplayConfig;
+#[cfg(feature = "vfd")]
+use serialport::SerialPort;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+#[cfg(feature = "vfd")]
+use std::time::Duration;
+
+/// Result type for display operations
+pub type DisplayResult<T> = Result<T, DisplayError>;
+
+/// Display driver errors
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub enum DisplayError {
+    NotConnected,
+    ConnectionFailed(String),
+    WriteFailed(String),
+    NotSupported,
+}
+
+impl std::fmt::Display for DisplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DisplayError::NotConnected => write!(f, "Display not connected"),
+            DisplayError::ConnectionFailed(msg) => write!(f, "{}", msg),
+            DisplayError::WriteFailed(msg) => write!(f, "Write failed: {}", msg),
+            DisplayError::NotSupported => write!(f, "Not supported by this display"),
+        }
+    }
+}
+
+impl std::error::Error for DisplayError {}
+
+impl From<std::io::Error> for DisplayError {
+    fn from(e: std::io::Error) -> Self {
+        DisplayError::WriteFailed(e.to_string())
+    }
+}
+
+/// Optional features a driver supports, so callers can skip what it lacks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    /// `set_brightness` changes the display's brightness or backlight
+    pub brightness: bool,
+    /// `set_custom_glyph` programs the eight `\0`-`\x07` characters
+    pub custom_glyphs: bool,
+    /// `set_output` switches general purpose outputs on the display board
+    pub outputs: bool,
+    /// `read_keys` returns presses from an attached keypad
+    pub keypad: bool,
+    /// Text is drawn into pixels rather than a character ROM
+    pub graphic: bool,
+    /// `write_at` rewrites part of a row in place
+    pub partial_writes: bool,
+    /// `write_colored_line` shows color hints
+    pub color: bool,
+    /// `write_large_line` draws text at double size
+    pub large_text: bool,
+    /// `set_inverted` swaps lit and dark pixels
+    pub invert: bool,
+    /// `notify` shows a notice of the driver's own
+    pub notify: bool,
+    /// `set_power` switches the display off and on, keeping its contents
+    pub power: bool,
+}
+
+/// Trait for display hardware drivers
+pub trait DisplayDriver: Send {
+    /// Short driver name
+    fn name(&self) -> &'static str;
+
+    /// Bring the hardware into a known state and clear it; `create_driver`
+    /// calls this once after opening, before the first frame
+    fn init(&mut self) -> DisplayResult<()> {
+        self.clear()
+    }
+
+    /// Optional features this driver supports
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default()
+    }
+
+    /// Text grid size as (columns, rows)
+    fn dimensions(&self) -> (usize, usize);
+
+    /// Clear the screen
+    fn clear(&mut self) -> DisplayResult<()>;
+
+    /// Write one row of text (already padded/truncated to the width)
+    fn write_line(&mut self, row: usize, text: &str) -> DisplayResult<()>;
+
+    /// Write one row with color hints; drivers without `color` just write
+    /// the text
+    fn write_colored_line(
+        &mut self,
+        row: usize,
+        text: &str,
+        _colors: &[color::ColorSpan],
+    ) -> DisplayResult<()> {
+        self.write_line(row, text)
+    }
+
+    /// Write one row at twice the height and width, covering the row below
+    /// as well; `text` is padded/truncated to half the width. Drivers
+    /// without `large_text` write it as a normal row.
+    fn write_large_line(
+        &mut self,
+        row: usize,
+        text: &str,
+        colors: &[color::ColorSpan],
+    ) -> DisplayResult<()> {
+        self.write_colored_line(row, text, colors)
+    }
+
+    /// Write text starting at a column, leaving the rest of the row as it is
+    fn write_at(&mut self, _row: usize, _column: usize, _text: &str) -> DisplayResult<()> {
+        Err(DisplayError::NotSupported)
+    }
+
+    /// Whether the display shows this character as-is; anything else goes
+    /// through `charset::Charset` before `write_line`
+    fn shows_char(&self, c: char) -> bool {
+        c.is_ascii() && !c.is_ascii_control()
+    }
+
+    /// Push buffered lines to the hardware, for drivers that redraw whole frames
+    fn flush(&mut self) -> DisplayResult<()> {
+        Ok(())
+    }
+
+    /// Set brightness as a percentage (0-100)
+    fn set_brightness(&mut self, _percent: u8) -> DisplayResult<()> {
+        Err(DisplayError::NotSupported)
+    }
+
+    /// Switch the display (and any backlight) off or back on
+    fn set_power(&mut self, _on: bool) -> DisplayResult<()> {
+        Err(DisplayError::NotSupported)
+    }
+
+    /// Swap lit and dark pixels over the whole screen
+    fn set_inverted(&mut self, _inverted: bool) -> DisplayResult<()> {
+        Err(DisplayError::NotSupported)
+    }
+
+    /// Announce a rare spot in the driver's own way (a toast on the web
+    /// dashboard, a partial refresh on e-paper); other drivers get an
+    /// animation from `VfdDisplay`
+    fn notify(&mut self, _text: &str) -> DisplayResult<()> {
+        Err(DisplayError::NotSupported)
+    }
+
+    /// Program a 5x8 custom character (one byte per row, low 5 bits used)
+    fn set_custom_glyph(&mut self, _slot: u8, _rows: &[u8; 8]) -> DisplayResult<()> {
+        Err(DisplayError::NotSupported)
+    }
+
+    /// Switch a general purpose output (1-based) on the display board
+    fn set_output(&mut self, _index: u8, _on: bool) -> DisplayResult<()> {
+        Err(DisplayError::NotSupported)
+    }
+
+    /// Key codes received from an attached keypad since the last call
+    fn read_keys(&mut self) -> DisplayResult<Vec<u8>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Constructor for a display driver, given the display settings and port name
+pub type DriverFactory =
+    Box<dyn Fn(&DisplayConfig, &str) -> DisplayResult<Box<dyn DisplayDriver>> + Send + Sync>;
+
+struct Registration {
+    /// Whether the driver talks to the selected serial port
+    serial: bool,
+    factory: DriverFactory,
+}
+
+/// Registered drivers by name, built-ins included
+fn registry() -> &'static Mutex<BTreeMap<String, Registration>> {
+    static REGISTRY: OnceLock<Mutex<BTreeMap<String, Registration>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let mut drivers = BTreeMap::new();
+        let mut add = |name: &str, serial: bool, factory: DriverFactory| {
+            drivers.insert(name.to_string(), Registration { serial, factory });
+        };
+        for name in ["elo", "futaba", "matrix_orbital", "noritake"] {
+            add(name, true, Box::new(open_vfd));
+        }
+        for name in ["hd44780_i2c", "hd44780_gpio"] {
+            add(name, false, Box::new(open_hd44780));
+        }
+        for name in ["ssd1306_i2c", "ssd1306_spi", "sh1106_i2c", "sh1106_spi"] {
+            add(name, false, Box::new(open_oled));
+        }
+        for name in ["waveshare_2in13", "waveshare_2in9"] {
+            add(name, false, Box::new(open_epaper));
+        }
+        add("framebuffer", false, Box::new(open_framebuffer));
+        add(
+            "remote",
+            false,
+            Box::new(|config, _port| Ok(Box::new(RemoteDriver::open(&config.remote_address)?))),
+        );
+        add(
+            "simulator",
+            false,
+            Box::new(|config, _port| {
+                Ok(Box::new(SimulatorDriver::open(config.columns, config.rows)))
+            }),
+        );
+        for name in ["st7789", "ili9341"] {
+            add(name, false, Box::new(open_tft));
+        }
+        Mutex::new(drivers)
+    })
+}
+
+/// Register a display driver selectable by name via `[display] driver = <name>`
+///
+/// `serial` says whether the driver uses the port picked in the settings.
+/// Call before the app starts; registering an existing name replaces it.
+pub fn register_driver<F>(name: &str, serial: bool, factory: F)
+where
+    F: Fn(&DisplayConfig, &str) -> DisplayResult<Box<dyn DisplayDriver>> + Send + Sync + 'static,
+{
+    if let Ok(mut drivers) = registry().lock() {
+        drivers.insert(
+            name.to_string(),
+            Registration {
+                serial,
+                factory: Box::new(factory),
+            },
+        );
+    }
+}
+
+/// Names of all registered drivers, for settings UIs
+pub fn registered_drivers() -> Vec<String> {
+    registry()
+        .lock()
+        .map(|drivers| drivers.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Whether a driver talks to a serial port (others ignore the port name)
+pub fn uses_serial(driver: &str) -> bool {
+    registry()
+        .lock()
+        .ok()
+        .and_then(|drivers| drivers.get(driver).map(|r| r.serial))
+        .unwrap_or(true)
+}
+
+/// Characters the HD44780 A00 style character ROM shows as-is: it has a
+/// yen sign and an arrow in place of `\` and `~`, and a degree sign
+#[cfg(any(feature = "vfd", all(target_os = "linux", feature = "hd44780")))]
+pub(crate) fn rom_shows_char(c: char) -> bool {
+    c == '°' || ((c == ' ' || c.is_ascii_graphic()) && !matches!(c, '\\' | '~'))
+}
+
+/// Map a character onto the JIS-based character ROM used by Futaba VFDs
+/// and HD44780 (A00) controllers
+///
+/// The ROM puts a yen sign at 0x5C and arrows at 0x7E/0x7F, so those are
+/// substituted, and anything outside ASCII is folded to a close match.
+#[cfg(any(feature = "vfd", all(target_os = "linux", feature = "hd44780")))]
+pub(crate) fn rom_byte(c: char) -> u8 {
+    match c {
+        // Custom characters programmed with `set_custom_glyph`
+        '\0'..='\x07' => c as u8,
+        '\\' => b'/',
+        '~' => b'-',
+        '°' => 0xDF,
+        'À'..='Å' => b'A',
+        'à'..='å' => b'a',
+        'È'..='Ë' => b'E',
+        'è'..='ë' => b'e',
+        'Ì'..='Ï' => b'I',
+        'ì'..='ï' => b'i',
+        'Ò'..='Ö' | 'Ø' => b'O',
+        'ò'..='ö' | 'ø' => b'o',
+        'Ù'..='Ü' => b'U',
+        'ù'..='ü' => b'u',
+        'Ñ' => b'N',
+        'ñ' => b'n',
+        'Ç' => b'C',
+        'ç' => b'c',
+        c if c.is_ascii() && !c.is_ascii_control() => c as u8,
+        _ => b'?',
+    }
+}
+
+/// Open a serial port at 8N1 for a display
+#[cfg(feature = "vfd")]
+pub(crate) fn open_serial(port_name: &str, baud_rate: u32) -> DisplayResult<Box<dyn SerialPort>> {
+    serialport::new(port_name, baud_rate)
+        .data_bits(serialport::DataBits::Eight)
+        .parity(serialport::Parity::None)
+        .stop_bits(serialport::StopBits::One)
+        .timeout(Duration::from_millis(1000))
+        .open()
+        .map_err(|e| DisplayError::ConnectionFailed(format!("Failed to open {}: {}", port_name, e)))
+}
+
+/// Factory function to open and initialize the configured driver on a port
+pub fn create_driver(
+    config: &DisplayConfig,
+    port_name: &str,
+) -> DisplayResult<Box<dyn DisplayDriver>> {
+    let mut driver = {
+        let drivers = registry().lock().map_err(|_| {
+            DisplayError::ConnectionFailed("Display driver registry unavailable".to_string())
+        })?;
+        let registration = drivers.get(&config.driver).ok_or_else(|| {
+            DisplayError::ConnectionFailed(format!("Unknown display driver '{}'", config.driver))
+        })?;
+        (registration.factory)(config, port_name)?
+    };
+    driver.init()?;
+    Ok(driver)
+}
+
+/// Fastest SPI clock accepted in `spi_speed_hz` (the Pi's core clock)
+#[cfg(all(
+    target_os = "linux",
+    any(feature = "oled", feature = "epaper", feature = "tft")
+))]
+const MAX_SPI_SPEED_HZ: u32 = 125_000_000;
+
+/// Check the I2C settings before opening, so a wrong bus or address says
+/// what to fix instead of failing deep inside the driver
+#[cfg(all(target_os = "linux", any(feature = "hd44780", feature = "oled")))]
+fn check_i2c(config: &DisplayConfig) -> DisplayResult<()> {
+    if !(0x03..=0x77).contains(&config.i2c_address) {
+        return Err(DisplayError::ConnectionFailed(format!(
+            "{}: i2c_address 0x{:02x} is outside the 7-bit range 0x03-0x77",
+            config.driver, config.i2c_address
+        )));
+    }
+    let path = format!("/dev/i2c-{}", config.i2c_bus);
+    if !std::path::Path::new(&path).exists() {
+        return Err(DisplayError::ConnectionFailed(format!(
+            "{}: {} not found; enable I2C bus {} (dtparam=i2c_arm=on on a Pi) \
+             and load the i2c-dev module, or set i2c_bus",
+            config.driver, path, config.i2c_bus
+        )));
+    }
+    Ok(())
+}
+
+/// Check the SPI device, clock and control pins before opening
+#[cfg(all(
+    target_os = "linux",
+    any(feature = "oled", feature = "epaper", feature = "tft")
+))]
+fn check_spi(config: &DisplayConfig) -> DisplayResult<()> {
+    let path = format!("/dev/spidev{}.{}", config.spi_bus, config.spi_chip_select);
+    if !std::path::Path::new(&path).exists() {
+        return Err(DisplayError::ConnectionFailed(format!(
+            "{}: {} not found; enable SPI (dtparam=spi=on on a Pi) or check \
+             spi_bus and spi_chip_select",
+            config.driver, path
+        )));
+    }
+    if config.spi_speed_hz > MAX_SPI_SPEED_HZ {
+        return Err(DisplayError::ConnectionFailed(format!(
+            "{}: spi_speed_hz {} is above the {} Hz maximum",
+            config.driver, config.spi_speed_hz, MAX_SPI_SPEED_HZ
+        )));
+    }
+    if config.reset_pin == Some(config.dc_pin) {
+        return Err(DisplayError::ConnectionFailed(format!(
+            "{}: dc_pin and reset_pin are both GPIO {}",
+            config.driver, config.dc_pin
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "vfd")]
+fn open_vfd(config: &DisplayConfig, port: &str) -> DisplayResult<Box<dyn DisplayDriver>> {
+    let (columns, rows) = (config.columns, config.rows);
+    Ok(match config.driver.as_str() {
+        "elo" => Box::new(EloDriver::open(
+            port,
+            config.baud_rate_or(9600),
+            columns,
+            rows,
+        )?),
+        "futaba" => Box::new(FutabaDriver::open(
+            port,
+            config.baud_rate_or(9600),
+            columns,
+            rows,
+        )?),
+        "matrix_orbital" => Box::new(MatrixOrbitalDriver::open(
+            port,
+            config.baud_rate_or(19200),
+            columns,
+            rows,
+        )?),
+        _ => Box::new(NoritakeDriver::open(
+            port,
+            config.baud_rate_or(38400),
+            columns,
+            rows,
+        )?),
+    })
+}
+
+#[cfg(not(feature = "vfd"))]
+fn open_vfd(_config: &DisplayConfig, _port: &str) -> DisplayResult<Box<dyn DisplayDriver>> {
+    Err(DisplayError::ConnectionFailed(
+        "Serial VFD support needs the 'vfd' cargo feature".to_string(),
+    ))
+}
+
+#[cfg(all(target_os = "linux", feature = "hd44780"))]
+fn open_hd44780(config: &DisplayConfig, _port: &str) -> DisplayResult<Box<dyn DisplayDriver>> {
+    let driver = if config.driver == "hd44780_gpio" {
+        Hd44780Driver::open_gpio(&config.gpio_pins, config.columns, config.rows)?
+    } else {
+        check_i2c(config)?;
+        Hd44780Driver::open_i2c(
+            config.i2c_bus,
+            config.i2c_address,
+            config.columns,
+            config.rows,
+        )?
+    };
+    Ok(Box::new(driver))
+}
+
+#[cfg(not(all(target_os = "linux", feature = "hd44780")))]
+fn open_hd44780(_config: &DisplayConfig, _port: &str) -> DisplayResult<Box<dyn DisplayDriver>> {
+    Err(DisplayError::ConnectionFailed(
+        "HD44780 support needs Linux and the 'hd44780' cargo feature".to_string(),
+    ))
+}
+
+#[cfg(all(target_os = "linux", feature = "oled"))]
+fn open_oled(config: &DisplayConfig, _port: &str) -> DisplayResult<Box<dyn DisplayDriver>> {
+    let controller = if config.driver.starts_with("sh1106") {
+        OledController::Sh1106
+    } else {
+        OledController::Ssd1306
+    };
+    let use_spi = config.driver.ends_with("_spi");
+    if use_spi {
+        check_spi(config)?;
+    } else {
+        check_i2c(config)?;
+    }
+    Ok(Box::new(oled::open_from_config(
+        controller, use_spi, config,
+    )?))
+}
+
+#[cfg(not(all(target_os = "linux", feature = "oled")))]
+fn open_oled(_config: &DisplayConfig, _port: &str) -> DisplayResult<Box<dyn DisplayDriver>> {
+    Err(DisplayError::ConnectionFailed(
+        "OLED support needs Linux and the 'oled' cargo feature".to_string(),
+    ))
+}
+
+#[cfg(all(target_os = "linux", feature = "epaper"))]
+fn open_epaper(config: &DisplayConfig, _port: &str) -> DisplayResult<Box<dyn DisplayDriver>> {
+    let panel = if config.driver == "waveshare_2in9" {
+        EpaperPanel::Waveshare2in9
+    } else {
+        EpaperPanel::Waveshare2in13
+    };
+    check_spi(config)?;
+    Ok(Box::new(epaper::open_from_config(panel, config)?))
+}
+
+#[cfg(not(all(target_os = "linux", feature = "epaper")))]
+fn open_epaper(_config: &DisplayConfig, _port: &str) -> DisplayResult<Box<dyn DisplayDriver>> {
+    Err(DisplayError::ConnectionFailed(
+        "E-paper support needs Linux and the 'epaper' cargo feature".to_string(),
+    ))
+}
+
+#[cfg(all(target_os = "linux", feature = "framebuffer"))]
+fn open_framebuffer(config: &DisplayConfig, _port: &str) -> DisplayResult<Box<dyn DisplayDriver>> {
+    Ok(Box::new(FramebufferDriver::open(&config.fb_device)?))
+}
+
+#[cfg(not(all(target_os = "linux", feature = "framebuffer")))]
+fn open_framebuffer(_config: &DisplayConfig, _port: &str) -> DisplayResult<Box<dyn DisplayDriver>> {
+    Err(DisplayError::ConnectionFailed(
+        "Framebuffer output needs Linux and the 'framebuffer' cargo feature".to_string(),
+    ))
+}
+
+#[cfg(all(target_os = "linux", feature = "tft"))]
+fn open_tft(config: &DisplayConfig, _port: &str) -> DisplayResult<Box<dyn DisplayDriver>> {
+    let controller = if config.driver == "ili9341" {
+        TftController::Ili9341
+    } else {
+        TftController::St7789
+    };
+    check_spi(config)?;
+    Ok(Box::new(tft::open_from_config(controller, config)?))
+}
+
+#[cfg(not(all(target_os = "linux", feature = "tft")))]
+fn open_tft(_config: &DisplayConfig, _port: &str) -> DisplayResult<Box<dyn DisplayDriver>> {
+    Err(DisplayError::ConnectionFailed(
+        "TFT support needs Linux and the 'tft' cargo feature".to_string(),
+    ))
+}
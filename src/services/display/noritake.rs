@@ -0,0 +1,138 @@
+//! Noritake GU-7000 / GU-3000 graphic VFD modules (GU-7000 command set)
+
+use super::color::ColorSpan;
+use super::{open_serial, Capabilities, DisplayDriver, DisplayResult};
+use serialport::SerialPort;
+use std::io::Write;
+
+const INITIALIZE: &[u8] = &[0x1B, 0x40]; // ESC @
+const CLEAR_DISPLAY: &[u8] = &[0x0C];
+
+/// Character magnification, US ( g 40h x y
+const MAGNIFY_2X: &[u8] = &[0x1F, 0x28, 0x67, 0x40, 0x02, 0x02];
+const MAGNIFY_1X: &[u8] = &[0x1F, 0x28, 0x67, 0x40, 0x01, 0x01];
+
+/// Display power, US ( a 40h p (p = 0 off, 1 on)
+const POWER: &[u8] = &[0x1F, 0x28, 0x61, 0x40];
+
+/// Width of one character cell in dots with the default 5x7 font
+const CHAR_WIDTH_DOTS: usize = 6;
+
+/// Driver for Noritake GU-series modules in text mode
+pub struct NoritakeDriver {
+    port: Box<dyn SerialPort>,
+    columns: usize,
+    rows: usize,
+}
+
+impl NoritakeDriver {
+    pub fn open(
+        port_name: &str,
+        baud_rate: u32,
+        columns: usize,
+        rows: usize,
+    ) -> DisplayResult<Self> {
+        Ok(Self {
+            port: open_serial(port_name, baud_rate)?,
+            columns,
+            rows,
+        })
+    }
+
+    /// Move the cursor to a character cell (US $ xL xH yL yH, x in dots, y in 8-dot rows)
+    fn set_cursor(&mut self, column: usize, row: usize) -> DisplayResult<()> {
+        let x = (column * CHAR_WIDTH_DOTS) as u16;
+        let y = row as u16;
+        let [x_lo, x_hi] = x.to_le_bytes();
+        let [y_lo, y_hi] = y.to_le_bytes();
+        self.port.write_all(&[0x1F, 0x24, x_lo, x_hi, y_lo, y_hi])?;
+        Ok(())
+    }
+}
+
+impl DisplayDriver for NoritakeDriver {
+    fn name(&self) -> &'static str {
+        "noritake"
+    }
+
+    fn init(&mut self) -> DisplayResult<()> {
+        self.port.write_all(INITIALIZE)?;
+        self.clear()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            brightness: true,
+            partial_writes: true,
+            large_text: true,
+            power: true,
+            ..Capabilities::default()
+        }
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        (self.columns, self.rows)
+    }
+
+    fn clear(&mut self) -> DisplayResult<()> {
+        self.port.write_all(CLEAR_DISPLAY)?;
+        Ok(())
+    }
+
+    fn write_line(&mut self, row: usize, text: &str) -> DisplayResult<()> {
+        if row >= self.rows {
+            return Ok(());
+        }
+        self.set_cursor(0, row)?;
+        let padded: String = format!("{:width$}", text, width = self.columns)
+            .chars()
+            .take(self.columns)
+            .collect();
+        self.port.write_all(padded.as_bytes())?;
+        Ok(())
+    }
+
+    fn write_large_line(
+        &mut self,
+        row: usize,
+        text: &str,
+        _colors: &[ColorSpan],
+    ) -> DisplayResult<()> {
+        if row + 1 >= self.rows {
+            return self.write_line(row, text);
+        }
+        let columns = self.columns / 2;
+        self.set_cursor(0, row)?;
+        let padded: String = format!("{:width$}", text, width = columns)
+            .chars()
+            .take(columns)
+            .collect();
+        self.port.write_all(MAGNIFY_2X)?;
+        self.port.write_all(padded.as_bytes())?;
+        self.port.write_all(MAGNIFY_1X)?;
+        Ok(())
+    }
+
+    fn write_at(&mut self, row: usize, column: usize, text: &str) -> DisplayResult<()> {
+        if row >= self.rows || column >= self.columns {
+            return Ok(());
+        }
+        self.set_cursor(column, row)?;
+        let text: String = text.chars().take(self.columns - column).collect();
+        self.port.write_all(text.as_bytes())?;
+        Ok(())
+    }
+
+    fn set_brightness(&mut self, percent: u8) -> DisplayResult<()> {
+        // US X n, n = 1 (12.5%) .. 8 (100%)
+        let level = ((percent.min(100) as u32 * 8).div_ceil(100)).max(1) as u8;
+        self.port.write_all(&[0x1F, 0x58, level])?;
+        Ok(())
+    }
+
+    fn set_power(&mut self, on: bool) -> DisplayResult<()> {
+        self.port.write_all(POWER)?;
+        self.port.write_all(&[on as u8])?;
+        Ok(())
+    }
+}
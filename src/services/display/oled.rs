@@ -0,0 +1,339 @@
+//! SSD1306 / SH1106 monochrome OLEDs over I2C or SPI, rendered with
+//! embedded-graphics
+
+use super::color::ColorSpan;
+use super::scaled::Doubled;
+use super::{Capabilities, DisplayDriver, DisplayError, DisplayResult};
+use crate::services::gpio::OutputPin;
+use crate::services::i2c::I2cDevice;
+use crate::services::spi::SpiDevice;
+use embedded_graphics::mono_font::ascii::FONT_6X10;
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+use embedded_graphics::text::{Baseline, Text};
+use std::time::Duration;
+
+const WIDTH: u32 = 128;
+const CHAR_WIDTH: u32 = 6;
+const LINE_HEIGHT: u32 = 10;
+
+/// OLED controller chip
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Controller {
+    Ssd1306,
+    /// 132-column RAM with the 128 visible columns offset by 2
+    Sh1106,
+}
+
+/// How the controller is wired
+pub enum Interface {
+    I2c(I2cDevice),
+    Spi { device: SpiDevice, dc: OutputPin },
+}
+
+impl Interface {
+    fn command(&mut self, bytes: &[u8]) -> DisplayResult<()> {
+        match self {
+            Interface::I2c(device) => {
+                let mut frame = Vec::with_capacity(bytes.len() + 1);
+                frame.push(0x00); // control byte: command stream
+                frame.extend_from_slice(bytes);
+                device.write(&frame)?;
+            }
+            Interface::Spi { device, dc } => {
+                dc.set(false)?;
+                device.write(bytes)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn data(&mut self, bytes: &[u8]) -> DisplayResult<()> {
+        match self {
+            Interface::I2c(device) => {
+                let mut frame = Vec::with_capacity(bytes.len() + 1);
+                frame.push(0x40); // control byte: data stream
+                frame.extend_from_slice(bytes);
+                device.write(&frame)?;
+            }
+            Interface::Spi { device, dc } => {
+                dc.set(true)?;
+                device.write(bytes)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 1bpp framebuffer in controller page order (8 vertical pixels per byte)
+struct FrameBuffer {
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl OriginDimensions for FrameBuffer {
+    fn size(&self) -> Size {
+        Size::new(WIDTH, self.height)
+    }
+}
+
+impl DrawTarget for FrameBuffer {
+    type Color = BinaryColor;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            let (Ok(x), Ok(y)) = (u32::try_from(point.x), u32::try_from(point.y)) else {
+                continue;
+            };
+            if x >= WIDTH || y >= self.height {
+                continue;
+            }
+            let index = ((y / 8) * WIDTH + x) as usize;
+            let bit = 1 << (y % 8);
+            if color.is_on() {
+                self.pixels[index] |= bit;
+            } else {
+                self.pixels[index] &= !bit;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Draw a row of text at `top`, with custom characters as 5x8 sprites one
+/// pixel below the cell top
+fn draw_text<D>(target: &mut D, text: &str, top: i32, glyphs: &[[u8; 8]; 8])
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    let style = MonoTextStyle::new(&FONT_6X10, BinaryColor::On);
+    let plain: String = text
+        .chars()
+        .map(|c| if c < '\x08' { ' ' } else { c })
+        .collect();
+    let _ = Text::with_baseline(&plain, Point::new(0, top), style, Baseline::Top).draw(target);
+
+    for (col, c) in text.chars().enumerate().filter(|(_, c)| *c < '\x08') {
+        let left = col as i32 * CHAR_WIDTH as i32;
+        let pixels = glyphs[c as usize].iter().enumerate().flat_map(|(y, bits)| {
+            (0..5)
+                .filter(move |x| bits & (0x10 >> x) != 0)
+                .map(move |x| Pixel(Point::new(left + x, top + 1 + y as i32), BinaryColor::On))
+        });
+        let _ = target.draw_iter(pixels);
+    }
+}
+
+/// Driver for 128x64 / 128x32 monochrome OLEDs in text mode
+pub struct OledDriver {
+    controller: Controller,
+    interface: Interface,
+    frame: FrameBuffer,
+    /// Text last drawn on each row, so unchanged frames aren't resent
+    lines: Vec<String>,
+    dirty: bool,
+    /// Custom characters drawn for `\0`-`\x07`
+    glyphs: [[u8; 8]; 8],
+}
+
+impl OledDriver {
+    pub fn open(
+        controller: Controller,
+        interface: Interface,
+        height: u32,
+        reset: Option<OutputPin>,
+    ) -> DisplayResult<Self> {
+        if let Some(mut reset) = reset {
+            reset.set(false)?;
+            std::thread::sleep(Duration::from_millis(10));
+            reset.set(true)?;
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        Ok(Self {
+            controller,
+            interface,
+            frame: FrameBuffer {
+                height,
+                pixels: vec![0; (WIDTH * height / 8) as usize],
+            },
+            lines: vec![String::new(); (height / LINE_HEIGHT) as usize],
+            dirty: true,
+            glyphs: [[0; 8]; 8],
+        })
+    }
+}
+
+impl DisplayDriver for OledDriver {
+    fn name(&self) -> &'static str {
+        match self.controller {
+            Controller::Ssd1306 => "ssd1306",
+            Controller::Sh1106 => "sh1106",
+        }
+    }
+
+    fn init(&mut self) -> DisplayResult<()> {
+        let (multiplex, com_pins) = if self.frame.height == 32 {
+            (0x1F, 0x02)
+        } else {
+            (0x3F, 0x12)
+        };
+        // Charge pump on: SSD1306 uses 8D 14, SH1106 uses AD 8B
+        let charge_pump: &[u8] = match self.controller {
+            Controller::Ssd1306 => &[0x8D, 0x14],
+            Controller::Sh1106 => &[0xAD, 0x8B],
+        };
+        self.interface
+            .command(&[0xAE, 0xD5, 0x80, 0xA8, multiplex])?;
+        self.interface.command(&[0xD3, 0x00, 0x40])?;
+        self.interface.command(charge_pump)?;
+        self.interface
+            .command(&[0xA1, 0xC8, 0xDA, com_pins, 0x81, 0xCF])?;
+        self.interface
+            .command(&[0xD9, 0xF1, 0xDB, 0x40, 0xA4, 0xA6, 0xAF])?;
+        self.clear()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            brightness: true,
+            custom_glyphs: true,
+            graphic: true,
+            large_text: true,
+            invert: true,
+            power: true,
+            ..Capabilities::default()
+        }
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        (
+            (WIDTH / CHAR_WIDTH) as usize,
+            (self.frame.height / LINE_HEIGHT) as usize,
+        )
+    }
+
+    fn clear(&mut self) -> DisplayResult<()> {
+        self.frame.pixels.fill(0);
+        self.lines.iter_mut().for_each(String::clear);
+        self.dirty = true;
+        self.flush()
+    }
+
+    fn write_line(&mut self, row: usize, text: &str) -> DisplayResult<()> {
+        match self.lines.get_mut(row) {
+            Some(line) if line != text => *line = text.to_string(),
+            _ => return Ok(()),
+        }
+        self.dirty = true;
+
+        let top = row as i32 * LINE_HEIGHT as i32;
+        let _ = Rectangle::new(Point::new(0, top), Size::new(WIDTH, LINE_HEIGHT))
+            .into_styled(PrimitiveStyle::with_fill(BinaryColor::Off))
+            .draw(&mut self.frame);
+        draw_text(&mut self.frame, text, top, &self.glyphs);
+        Ok(())
+    }
+
+    fn write_large_line(
+        &mut self,
+        row: usize,
+        text: &str,
+        _colors: &[ColorSpan],
+    ) -> DisplayResult<()> {
+        if row + 1 >= self.lines.len() {
+            return self.write_line(row, text);
+        }
+        // Both rows are redrawn by the next normal write to either of them
+        self.lines[row].clear();
+        self.lines[row + 1].clear();
+        self.dirty = true;
+
+        let top = row as i32 * LINE_HEIGHT as i32;
+        let _ = Rectangle::new(Point::new(0, top), Size::new(WIDTH, 2 * LINE_HEIGHT))
+            .into_styled(PrimitiveStyle::with_fill(BinaryColor::Off))
+            .draw(&mut self.frame);
+        let mut doubled = Doubled::new(&mut self.frame, Point::new(0, top));
+        draw_text(&mut doubled, text, 0, &self.glyphs);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> DisplayResult<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        self.dirty = false;
+
+        let column = match self.controller {
+            Controller::Ssd1306 => 0u8,
+            Controller::Sh1106 => 2u8,
+        };
+        let pages = self.frame.pixels.chunks(WIDTH as usize).collect::<Vec<_>>();
+        for (page, bytes) in pages.into_iter().enumerate() {
+            self.interface
+                .command(&[0xB0 | page as u8, column & 0x0F, 0x10 | (column >> 4)])?;
+            self.interface.data(bytes)?;
+        }
+        Ok(())
+    }
+
+    fn set_custom_glyph(&mut self, slot: u8, rows: &[u8; 8]) -> DisplayResult<()> {
+        let Some(glyph) = self.glyphs.get_mut(slot as usize) else {
+            return Err(DisplayError::NotSupported);
+        };
+        *glyph = *rows;
+        // Rows showing the old glyph must be redrawn on the next write
+        let c = slot as char;
+        for line in self.lines.iter_mut().filter(|line| line.contains(c)) {
+            line.clear();
+        }
+        Ok(())
+    }
+
+    fn set_brightness(&mut self, percent: u8) -> DisplayResult<()> {
+        let contrast = (percent.min(100) as u32 * 255 / 100) as u8;
+        self.interface.command(&[0x81, contrast])
+    }
+
+    fn set_inverted(&mut self, inverted: bool) -> DisplayResult<()> {
+        self.interface
+            .command(&[if inverted { 0xA7 } else { 0xA6 }])
+    }
+
+    fn set_power(&mut self, on: bool) -> DisplayResult<()> {
+        self.interface.command(&[if on { 0xAF } else { 0xAE }])
+    }
+}
+
+/// Open an OLED from the display config
+pub fn open_from_config(
+    controller: Controller,
+    use_spi: bool,
+    config: &crate::config::DisplayConfig,
+) -> DisplayResult<OledDriver> {
+    let interface = if use_spi {
+        Interface::Spi {
+            device: SpiDevice::open(config.spi_bus, config.spi_chip_select, config.spi_speed_hz)
+                .map_err(DisplayError::ConnectionFailed)?,
+            dc: OutputPin::open(config.dc_pin).map_err(DisplayError::ConnectionFailed)?,
+        }
+    } else {
+        Interface::I2c(
+            I2cDevice::open(config.i2c_bus, config.i2c_address)
+                .map_err(DisplayError::ConnectionFailed)?,
+        )
+    };
+    let reset = config
+        .reset_pin
+        .map(OutputPin::open)
+        .transpose()
+        .map_err(DisplayError::ConnectionFailed)?;
+    let height = if config.pixel_height <= 32 { 32 } else { 64 };
+    OledDriver::open(controller, interface, height, reset)
+}
@@ -0,0 +1,274 @@
+//! Network display protocol between the app and `rbn-vfd-displayd`
+//!
+//! The "remote" driver sends whole frames to a display daemon running on
+//! another machine, so the display can sit next to the radio. Messages are
+//! JSON objects, one per line, over TCP. The daemon greets each connection
+//! with `Hello` giving its grid size, and the app then sends `Frame` and
+//! `Brightness` messages.
+//!
+//! Every message carries the sender's session (random per app start) and a
+//! sequence number. The daemon drops anything not newer than what it last
+//! showed for that session, so frames still queued on a stale connection
+//! cannot overwrite newer ones after a reconnect.
+
+use super::color::ColorSpan;
+use super::{Capabilities, DisplayDriver, DisplayError, DisplayResult};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+/// Port the daemon listens on unless told otherwise
+pub const DEFAULT_PORT: u16 = 7373;
+
+/// How long to wait for the daemon to accept and greet a connection (the
+/// display thread waits this long on each reconnection attempt)
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Pause between reconnection attempts after the connection drops
+const RETRY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Longest message line read, so a peer can't grow the buffer without end
+const MAX_LINE: u64 = 1024 * 1024;
+
+/// The daemon's display, as announced when a connection opens
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hello {
+    pub columns: usize,
+    pub rows: usize,
+    pub brightness: bool,
+    pub color: bool,
+}
+
+/// Protocol message, sent as one JSON line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Message {
+    /// Daemon to app on connect
+    Hello(Hello),
+    /// App to daemon: every row of the display, with color hints
+    Frame {
+        session: u64,
+        seq: u64,
+        lines: Vec<String>,
+        colors: Vec<Vec<ColorSpan>>,
+    },
+    /// App to daemon: brightness percentage
+    Brightness { session: u64, seq: u64, percent: u8 },
+}
+
+impl Message {
+    /// Session and sequence number of an app message
+    pub fn sequence(&self) -> Option<(u64, u64)> {
+        match self {
+            Message::Hello(_) => None,
+            Message::Frame { session, seq, .. } | Message::Brightness { session, seq, .. } => {
+                Some((*session, *seq))
+            }
+        }
+    }
+}
+
+/// Read one message line (None at end of stream); an error for a line
+/// longer than `MAX_LINE`
+pub fn read_message(reader: &mut impl BufRead) -> std::io::Result<Option<Message>> {
+    let mut line = String::new();
+    let read = reader.take(MAX_LINE).read_line(&mut line)?;
+    if read == 0 {
+        return Ok(None);
+    }
+    if read as u64 == MAX_LINE && !line.ends_with('\n') {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "message too long",
+        ));
+    }
+    serde_json::from_str(&line)
+        .map(Some)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+/// Write one message line
+pub fn write_message(writer: &mut impl Write, message: &Message) -> std::io::Result<()> {
+    let mut line = serde_json::to_string(message)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    line.push('\n');
+    writer.write_all(line.as_bytes())
+}
+
+/// "host" or "host:port", with the default port filled in
+fn with_default_port(address: &str) -> String {
+    let address = address.trim();
+    if address
+        .rsplit_once(':')
+        .is_some_and(|(_, port)| port.parse::<u16>().is_ok())
+    {
+        address.to_string()
+    } else {
+        format!("{}:{}", address, DEFAULT_PORT)
+    }
+}
+
+/// Connect to the daemon and wait for its greeting
+fn connect(address: &str) -> DisplayResult<(TcpStream, Hello)> {
+    let failed = |e: std::io::Error| {
+        DisplayError::ConnectionFailed(format!("Display daemon {}: {}", address, e))
+    };
+    let socket = address
+        .to_socket_addrs()
+        .map_err(failed)?
+        .next()
+        .ok_or_else(|| DisplayError::ConnectionFailed(format!("Cannot resolve {}", address)))?;
+    let stream = TcpStream::connect_timeout(&socket, CONNECT_TIMEOUT).map_err(failed)?;
+    stream
+        .set_read_timeout(Some(CONNECT_TIMEOUT))
+        .map_err(failed)?;
+    stream
+        .set_write_timeout(Some(CONNECT_TIMEOUT))
+        .map_err(failed)?;
+    stream.set_nodelay(true).map_err(failed)?;
+
+    let mut reader = BufReader::new(stream.try_clone().map_err(failed)?);
+    match read_message(&mut reader).map_err(failed)? {
+        Some(Message::Hello(hello)) => Ok((stream, hello)),
+        _ => Err(DisplayError::ConnectionFailed(format!(
+            "{} is not a display daemon",
+            address
+        ))),
+    }
+}
+
+/// Driver that sends frames to `rbn-vfd-displayd` over the network
+pub struct RemoteDriver {
+    address: String,
+    stream: Option<TcpStream>,
+    /// No reconnection attempt before then
+    retry_at: Instant,
+    session: u64,
+    seq: u64,
+    columns: usize,
+    capabilities: Capabilities,
+    lines: Vec<String>,
+    colors: Vec<Vec<ColorSpan>>,
+}
+
+impl RemoteDriver {
+    /// Connect to the daemon at "host[:port]"; its grid size comes from the
+    /// greeting and stays fixed for the life of the driver
+    pub fn open(address: &str) -> DisplayResult<Self> {
+        let address = with_default_port(address);
+        let (stream, hello) = connect(&address)?;
+        Ok(Self {
+            address,
+            stream: Some(stream),
+            retry_at: Instant::now(),
+            session: rand::random(),
+            seq: 0,
+            columns: hello.columns,
+            capabilities: Capabilities {
+                brightness: hello.brightness,
+                color: hello.color,
+                ..Capabilities::default()
+            },
+            lines: vec![String::new(); hello.rows],
+            colors: vec![Vec::new(); hello.rows],
+        })
+    }
+
+    /// Send a message, reconnecting first if the connection was lost
+    ///
+    /// While the daemon is unreachable messages are dropped; every frame
+    /// carries the whole display, so the next one after reconnecting
+    /// brings it up to date.
+    fn send(&mut self, message: impl FnOnce(u64, u64) -> Message) -> DisplayResult<()> {
+        if self.stream.is_none() {
+            let now = Instant::now();
+            if now < self.retry_at {
+                return Err(DisplayError::NotConnected);
+            }
+            self.retry_at = now + RETRY_INTERVAL;
+            match connect(&self.address) {
+                Ok((stream, _)) => self.stream = Some(stream),
+                Err(e) => {
+                    log::warn!("{}", e);
+                    return Err(DisplayError::NotConnected);
+                }
+            }
+        }
+
+        self.seq += 1;
+        let message = message(self.session, self.seq);
+        let Some(stream) = self.stream.as_mut() else {
+            return Err(DisplayError::NotConnected);
+        };
+        if let Err(e) = write_message(stream, &message) {
+            self.stream = None;
+            self.retry_at = Instant::now() + RETRY_INTERVAL;
+            return Err(e.into());
+        }
+        Ok(())
+    }
+}
+
+impl DisplayDriver for RemoteDriver {
+    fn name(&self) -> &'static str {
+        "remote"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        (self.columns, self.lines.len())
+    }
+
+    fn clear(&mut self) -> DisplayResult<()> {
+        self.lines.iter_mut().for_each(String::clear);
+        self.colors.iter_mut().for_each(Vec::clear);
+        self.flush()
+    }
+
+    /// The daemon translates for its own display
+    fn shows_char(&self, c: char) -> bool {
+        !c.is_control()
+    }
+
+    fn write_line(&mut self, row: usize, text: &str) -> DisplayResult<()> {
+        self.write_colored_line(row, text, &[])
+    }
+
+    fn write_colored_line(
+        &mut self,
+        row: usize,
+        text: &str,
+        colors: &[ColorSpan],
+    ) -> DisplayResult<()> {
+        if let Some(line) = self.lines.get_mut(row) {
+            *line = text.to_string();
+            self.colors[row] = colors.to_vec();
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> DisplayResult<()> {
+        let (lines, colors) = (self.lines.clone(), self.colors.clone());
+        self.send(|session, seq| Message::Frame {
+            session,
+            seq,
+            lines,
+            colors,
+        })
+    }
+
+    fn set_brightness(&mut self, percent: u8) -> DisplayResult<()> {
+        if !self.capabilities.brightness {
+            return Err(DisplayError::NotSupported);
+        }
+        self.send(|session, seq| Message::Brightness {
+            session,
+            seq,
+            percent,
+        })
+    }
+}
@@ -0,0 +1,39 @@
+//! Draw target adapter for double-size text on graphic displays
+
+use embedded_graphics::prelude::*;
+
+/// Draws into `target` at twice the size, with (0, 0) at `origin`
+pub struct Doubled<'a, D> {
+    target: &'a mut D,
+    origin: Point,
+}
+
+impl<'a, D> Doubled<'a, D> {
+    pub fn new(target: &'a mut D, origin: Point) -> Self {
+        Self { target, origin }
+    }
+}
+
+impl<D: DrawTarget> OriginDimensions for Doubled<'_, D> {
+    fn size(&self) -> Size {
+        self.target.bounding_box().size / 2
+    }
+}
+
+impl<D: DrawTarget> DrawTarget for Doubled<'_, D> {
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let origin = self.origin;
+        self.target
+            .draw_iter(pixels.into_iter().flat_map(move |Pixel(point, color)| {
+                let corner = origin + point * 2;
+                [(0, 0), (1, 0), (0, 1), (1, 1)]
+                    .map(|(x, y)| Pixel(corner + Point::new(x, y), color))
+            }))
+    }
+}
@@ -0,0 +1,269 @@
+//! On-screen stand-in for a character VFD, for working on layouts and
+//! glyphs without hardware
+//!
+//! The driver keeps a 5x8 dot image of each cell, including programmed
+//! custom characters, and publishes it on every flush. The GUI draws the
+//! latest image in its own window (see `simulated_screen`).
+
+use super::color::ColorSpan;
+use super::{Capabilities, DisplayDriver, DisplayResult};
+use std::sync::Mutex;
+
+/// Latest image published by the open simulator, if any
+static SCREEN: Mutex<Option<SimulatedScreen>> = Mutex::new(None);
+
+/// 5x7 character ROM for ' '..='~', five columns per character with the
+/// top dot in bit 0
+const FONT: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x00, 0x00, 0x5F, 0x00, 0x00], // !
+    [0x00, 0x07, 0x00, 0x07, 0x00], // "
+    [0x14, 0x7F, 0x14, 0x7F, 0x14], // #
+    [0x24, 0x2A, 0x7F, 0x2A, 0x12], // $
+    [0x23, 0x13, 0x08, 0x64, 0x62], // %
+    [0x36, 0x49, 0x55, 0x22, 0x50], // &
+    [0x00, 0x05, 0x03, 0x00, 0x00], // '
+    [0x00, 0x1C, 0x22, 0x41, 0x00], // (
+    [0x00, 0x41, 0x22, 0x1C, 0x00], // )
+    [0x08, 0x2A, 0x1C, 0x2A, 0x08], // *
+    [0x08, 0x08, 0x3E, 0x08, 0x08], // +
+    [0x00, 0x50, 0x30, 0x00, 0x00], // ,
+    [0x08, 0x08, 0x08, 0x08, 0x08], // -
+    [0x00, 0x60, 0x60, 0x00, 0x00], // .
+    [0x20, 0x10, 0x08, 0x04, 0x02], // /
+    [0x3E, 0x51, 0x49, 0x45, 0x3E], // 0
+    [0x00, 0x42, 0x7F, 0x40, 0x00], // 1
+    [0x42, 0x61, 0x51, 0x49, 0x46], // 2
+    [0x21, 0x41, 0x45, 0x4B, 0x31], // 3
+    [0x18, 0x14, 0x12, 0x7F, 0x10], // 4
+    [0x27, 0x45, 0x45, 0x45, 0x39], // 5
+    [0x3C, 0x4A, 0x49, 0x49, 0x30], // 6
+    [0x01, 0x71, 0x09, 0x05, 0x03], // 7
+    [0x36, 0x49, 0x49, 0x49, 0x36], // 8
+    [0x06, 0x49, 0x49, 0x29, 0x1E], // 9
+    [0x00, 0x36, 0x36, 0x00, 0x00], // :
+    [0x00, 0x56, 0x36, 0x00, 0x00], // ;
+    [0x08, 0x14, 0x22, 0x41, 0x00], // <
+    [0x14, 0x14, 0x14, 0x14, 0x14], // =
+    [0x00, 0x41, 0x22, 0x14, 0x08], // >
+    [0x02, 0x01, 0x51, 0x09, 0x06], // ?
+    [0x32, 0x49, 0x79, 0x41, 0x3E], // @
+    [0x7E, 0x11, 0x11, 0x11, 0x7E], // A
+    [0x7F, 0x49, 0x49, 0x49, 0x36], // B
+    [0x3E, 0x41, 0x41, 0x41, 0x22], // C
+    [0x7F, 0x41, 0x41, 0x22, 0x1C], // D
+    [0x7F, 0x49, 0x49, 0x49, 0x41], // E
+    [0x7F, 0x09, 0x09, 0x09, 0x01], // F
+    [0x3E, 0x41, 0x49, 0x49, 0x7A], // G
+    [0x7F, 0x08, 0x08, 0x08, 0x7F], // H
+    [0x00, 0x41, 0x7F, 0x41, 0x00], // I
+    [0x20, 0x40, 0x41, 0x3F, 0x01], // J
+    [0x7F, 0x08, 0x14, 0x22, 0x41], // K
+    [0x7F, 0x40, 0x40, 0x40, 0x40], // L
+    [0x7F, 0x02, 0x0C, 0x02, 0x7F], // M
+    [0x7F, 0x04, 0x08, 0x10, 0x7F], // N
+    [0x3E, 0x41, 0x41, 0x41, 0x3E], // O
+    [0x7F, 0x09, 0x09, 0x09, 0x06], // P
+    [0x3E, 0x41, 0x51, 0x21, 0x5E], // Q
+    [0x7F, 0x09, 0x19, 0x29, 0x46], // R
+    [0x46, 0x49, 0x49, 0x49, 0x31], // S
+    [0x01, 0x01, 0x7F, 0x01, 0x01], // T
+    [0x3F, 0x40, 0x40, 0x40, 0x3F], // U
+    [0x1F, 0x20, 0x40, 0x20, 0x1F], // V
+    [0x3F, 0x40, 0x38, 0x40, 0x3F], // W
+    [0x63, 0x14, 0x08, 0x14, 0x63], // X
+    [0x07, 0x08, 0x70, 0x08, 0x07], // Y
+    [0x61, 0x51, 0x49, 0x45, 0x43], // Z
+    [0x00, 0x7F, 0x41, 0x41, 0x00], // [
+    [0x02, 0x04, 0x08, 0x10, 0x20], // backslash
+    [0x00, 0x41, 0x41, 0x7F, 0x00], // ]
+    [0x04, 0x02, 0x01, 0x02, 0x04], // ^
+    [0x40, 0x40, 0x40, 0x40, 0x40], // _
+    [0x00, 0x01, 0x02, 0x04, 0x00], // `
+    [0x20, 0x54, 0x54, 0x54, 0x78], // a
+    [0x7F, 0x48, 0x44, 0x44, 0x38], // b
+    [0x38, 0x44, 0x44, 0x44, 0x20], // c
+    [0x38, 0x44, 0x44, 0x48, 0x7F], // d
+    [0x38, 0x54, 0x54, 0x54, 0x18], // e
+    [0x08, 0x7E, 0x09, 0x01, 0x02], // f
+    [0x0C, 0x52, 0x52, 0x52, 0x3E], // g
+    [0x7F, 0x08, 0x04, 0x04, 0x78], // h
+    [0x00, 0x44, 0x7D, 0x40, 0x00], // i
+    [0x20, 0x40, 0x44, 0x3D, 0x00], // j
+    [0x7F, 0x10, 0x28, 0x44, 0x00], // k
+    [0x00, 0x41, 0x7F, 0x40, 0x00], // l
+    [0x7C, 0x04, 0x18, 0x04, 0x78], // m
+    [0x7C, 0x08, 0x04, 0x04, 0x78], // n
+    [0x38, 0x44, 0x44, 0x44, 0x38], // o
+    [0x7C, 0x14, 0x14, 0x14, 0x08], // p
+    [0x08, 0x14, 0x14, 0x18, 0x7C], // q
+    [0x7C, 0x08, 0x04, 0x04, 0x08], // r
+    [0x48, 0x54, 0x54, 0x54, 0x20], // s
+    [0x04, 0x3F, 0x44, 0x40, 0x20], // t
+    [0x3C, 0x40, 0x40, 0x20, 0x7C], // u
+    [0x1C, 0x20, 0x40, 0x20, 0x1C], // v
+    [0x3C, 0x40, 0x30, 0x40, 0x3C], // w
+    [0x44, 0x28, 0x10, 0x28, 0x44], // x
+    [0x0C, 0x50, 0x50, 0x50, 0x3C], // y
+    [0x44, 0x64, 0x54, 0x4C, 0x44], // z
+    [0x00, 0x08, 0x36, 0x41, 0x00], // {
+    [0x00, 0x00, 0x7F, 0x00, 0x00], // |
+    [0x00, 0x41, 0x36, 0x08, 0x00], // }
+    [0x08, 0x04, 0x08, 0x10, 0x08], // ~
+];
+
+/// Dot image of the simulated display
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulatedScreen {
+    pub columns: usize,
+    pub rows: usize,
+    /// Characters on screen, `rows` rows of `columns` cells
+    pub cells: Vec<Vec<char>>,
+    /// Rows drawn at double size, covering the row below
+    pub large: Vec<bool>,
+    /// Custom characters `\0`-`\x07`, as programmed with `set_custom_glyph`
+    pub glyphs: [[u8; 8]; 8],
+    /// Brightness percentage
+    pub brightness: u8,
+}
+
+impl SimulatedScreen {
+    fn new(columns: usize, rows: usize) -> Self {
+        Self {
+            columns,
+            rows,
+            cells: vec![vec![' '; columns]; rows],
+            large: vec![false; rows],
+            glyphs: [[0; 8]; 8],
+            brightness: 100,
+        }
+    }
+
+    /// 5x8 dots of a character, one row per byte with the leftmost dot in
+    /// bit 4 (the layout `set_custom_glyph` takes)
+    pub fn dots(&self, c: char) -> [u8; 8] {
+        if let Some(glyph) = self.glyphs.get(c as usize) {
+            return *glyph;
+        }
+        let columns = match c {
+            ' '..='~' => FONT[c as usize - ' ' as usize],
+            _ => FONT['?' as usize - ' ' as usize],
+        };
+        let mut rows = [0u8; 8];
+        for (column, bits) in columns.iter().enumerate() {
+            for (row, dots) in rows.iter_mut().enumerate().take(7) {
+                if bits & (1 << row) != 0 {
+                    *dots |= 0x10 >> column;
+                }
+            }
+        }
+        rows
+    }
+}
+
+/// Latest image from the open simulator driver, for drawing
+pub fn simulated_screen() -> Option<SimulatedScreen> {
+    SCREEN.lock().ok().and_then(|screen| screen.clone())
+}
+
+/// Driver that shows frames in a window instead of on hardware
+pub struct SimulatorDriver {
+    screen: SimulatedScreen,
+}
+
+impl SimulatorDriver {
+    pub fn open(columns: usize, rows: usize) -> Self {
+        Self {
+            screen: SimulatedScreen::new(columns, rows),
+        }
+    }
+}
+
+impl DisplayDriver for SimulatorDriver {
+    fn name(&self) -> &'static str {
+        "simulator"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            brightness: true,
+            custom_glyphs: true,
+            partial_writes: true,
+            large_text: true,
+            ..Capabilities::default()
+        }
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        (self.screen.columns, self.screen.rows)
+    }
+
+    fn clear(&mut self) -> DisplayResult<()> {
+        for row in &mut self.screen.cells {
+            row.fill(' ');
+        }
+        self.screen.large.fill(false);
+        self.flush()
+    }
+
+    fn write_line(&mut self, row: usize, text: &str) -> DisplayResult<()> {
+        if let Some(cells) = self.screen.cells.get_mut(row) {
+            cells.fill(' ');
+        }
+        self.write_at(row, 0, text)
+    }
+
+    fn write_large_line(
+        &mut self,
+        row: usize,
+        text: &str,
+        _colors: &[ColorSpan],
+    ) -> DisplayResult<()> {
+        if row + 1 >= self.screen.rows {
+            return self.write_line(row, text);
+        }
+        self.write_line(row, text)?;
+        self.write_line(row + 1, "")?;
+        self.screen.large[row] = true;
+        Ok(())
+    }
+
+    fn write_at(&mut self, row: usize, column: usize, text: &str) -> DisplayResult<()> {
+        if let Some(large) = self.screen.large.get_mut(row) {
+            *large = false;
+        }
+        if let Some(cells) = self.screen.cells.get_mut(row) {
+            for (cell, c) in cells.iter_mut().skip(column).zip(text.chars()) {
+                *cell = c;
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> DisplayResult<()> {
+        if let Ok(mut screen) = SCREEN.lock() {
+            *screen = Some(self.screen.clone());
+        }
+        Ok(())
+    }
+
+    fn set_brightness(&mut self, percent: u8) -> DisplayResult<()> {
+        self.screen.brightness = percent.min(100);
+        self.flush()
+    }
+
+    fn set_custom_glyph(&mut self, slot: u8, rows: &[u8; 8]) -> DisplayResult<()> {
+        if let Some(glyph) = self.screen.glyphs.get_mut(slot as usize) {
+            *glyph = rows.map(|row| row & 0x1F);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for SimulatorDriver {
+    fn drop(&mut self) {
+        if let Ok(mut screen) = SCREEN.lock() {
+            *screen = None;
+        }
+    }
+}
@@ -0,0 +1,459 @@
+//! ST7789/ILI9341 SPI TFT panels (320x240, e.g. Pimoroni Display HAT Mini)
+//!
+//! The panel is laid out as two 20 character text columns with a color band
+//! map along the bottom. Rows fill the left column first and continue in
+//! the right one. The band map counts lines by the frequency each one starts
+//! with, so it follows the spot list. Changed rows are sent as windows of
+//! RGB565 pixels rather than whole frames.
+
+use super::color::{self, ColorSpan, Rgb};
+use super::scaled::Doubled;
+use super::{Capabilities, DisplayDriver, DisplayError, DisplayResult};
+use crate::models::Band;
+use crate::services::gpio::OutputPin;
+use crate::services::spi::SpiDevice;
+use embedded_graphics::mono_font::ascii::{FONT_6X10, FONT_8X13};
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::raw::RawU16;
+use embedded_graphics::pixelcolor::{Rgb565, Rgb888};
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{Line, PrimitiveStyle, Rectangle};
+use embedded_graphics::text::{Baseline, Text};
+use std::thread;
+use std::time::Duration;
+
+const WIDTH: u32 = 320;
+const HEIGHT: u32 = 240;
+const CHAR_WIDTH: u32 = 8;
+const LINE_HEIGHT: u32 = 13;
+
+/// Text columns side by side, each `COLUMN_CHARS` wide
+const COLUMNS: u32 = 2;
+const COLUMN_CHARS: u32 = WIDTH / COLUMNS / CHAR_WIDTH;
+
+/// Strip along the bottom for the band map
+const BAND_MAP_HEIGHT: u32 = 32;
+const BAR_HEIGHT: u32 = 19;
+const ROWS_PER_COLUMN: u32 = (HEIGHT - BAND_MAP_HEIGHT) / LINE_HEIGHT;
+
+/// VFD-style colors for uncolored text
+const FOREGROUND: Rgb888 = Rgb888::new(0, 255, 0);
+const BACKGROUND: Rgb888 = Rgb888::BLACK;
+const RULE: Rgb888 = Rgb888::new(60, 60, 60);
+
+/// Supported controllers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Controller {
+    St7789,
+    Ili9341,
+}
+
+impl Controller {
+    /// Memory access control for landscape, RGB order
+    fn madctl(self) -> u8 {
+        match self {
+            Controller::St7789 => 0x60,
+            Controller::Ili9341 => 0x28,
+        }
+    }
+}
+
+/// Off-screen RGB565 copy of the panel
+struct Canvas {
+    pixels: Vec<u16>,
+}
+
+impl OriginDimensions for Canvas {
+    fn size(&self) -> Size {
+        Size::new(WIDTH, HEIGHT)
+    }
+}
+
+impl DrawTarget for Canvas {
+    type Color = Rgb565;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            let (Ok(x), Ok(y)) = (u32::try_from(point.x), u32::try_from(point.y)) else {
+                continue;
+            };
+            if x >= WIDTH || y >= HEIGHT {
+                continue;
+            }
+            self.pixels[(y * WIDTH + x) as usize] = RawU16::from(color).into_inner();
+        }
+        Ok(())
+    }
+}
+
+fn rgb565(color: Rgb888) -> Rgb565 {
+    Rgb565::from(color)
+}
+
+fn hint(color: Rgb) -> Rgb888 {
+    Rgb888::new(color.0, color.1, color.2)
+}
+
+/// Draw text with its top left at `origin`, coloring characters covered by
+/// `colors`
+fn draw_text<D>(target: &mut D, origin: Point, text: &str, colors: &[ColorSpan])
+where
+    D: DrawTarget<Color = Rgb565>,
+{
+    let chars: Vec<char> = text.chars().collect();
+    let color_at = |index: usize| {
+        colors
+            .iter()
+            .find(|span| (span.start..span.end).contains(&index))
+            .map(|span| hint(span.color))
+            .unwrap_or(FOREGROUND)
+    };
+    let mut start = 0;
+    while start < chars.len() {
+        let color = color_at(start);
+        let end = (start + 1..chars.len())
+            .find(|&index| color_at(index) != color)
+            .unwrap_or(chars.len());
+        let run: String = chars[start..end].iter().collect();
+        let style = MonoTextStyle::new(&FONT_8X13, rgb565(color));
+        let at = origin + Point::new((start as u32 * CHAR_WIDTH) as i32, 0);
+        let _ = Text::with_baseline(&run, at, style, Baseline::Top).draw(target);
+        start = end;
+    }
+}
+
+/// Spots per band, counted from the frequency (kHz) each line starts with
+fn band_counts(lines: &[(String, Vec<ColorSpan>)]) -> [usize; Band::ALL.len()] {
+    let mut counts = [0; Band::ALL.len()];
+    for (line, _) in lines {
+        let band = line
+            .split_whitespace()
+            .next()
+            .and_then(|word| word.parse::<f64>().ok())
+            .and_then(Band::from_khz);
+        if let Some(band) = band {
+            counts[Band::ALL.iter().position(|b| *b == band).unwrap_or(0)] += 1;
+        }
+    }
+    counts
+}
+
+/// Driver for ST7789/ILI9341 color TFTs on SPI
+pub struct TftDriver {
+    controller: Controller,
+    spi: SpiDevice,
+    dc: OutputPin,
+    reset: Option<OutputPin>,
+    backlight: Option<OutputPin>,
+    canvas: Canvas,
+    /// Text and color hints currently drawn in each row
+    lines: Vec<(String, Vec<ColorSpan>)>,
+    dirty_rows: Vec<bool>,
+    /// Counts drawn in the band map, None until first drawn
+    shown_counts: Option<[usize; Band::ALL.len()]>,
+}
+
+impl TftDriver {
+    pub fn open(
+        controller: Controller,
+        spi: SpiDevice,
+        dc: OutputPin,
+        reset: Option<OutputPin>,
+        backlight: Option<OutputPin>,
+    ) -> DisplayResult<Self> {
+        let rows = (ROWS_PER_COLUMN * COLUMNS) as usize;
+        Ok(Self {
+            controller,
+            spi,
+            dc,
+            reset,
+            backlight,
+            canvas: Canvas {
+                pixels: vec![0; (WIDTH * HEIGHT) as usize],
+            },
+            lines: vec![(String::new(), Vec::new()); rows],
+            dirty_rows: vec![true; rows],
+            shown_counts: None,
+        })
+    }
+
+    fn command(&mut self, command: u8, data: &[u8]) -> DisplayResult<()> {
+        self.dc.set(false)?;
+        self.spi.write(&[command])?;
+        if !data.is_empty() {
+            self.dc.set(true)?;
+            self.spi.write(data)?;
+        }
+        Ok(())
+    }
+
+    /// Pixel area of a text row
+    fn row_area(row: usize) -> Rectangle {
+        let row = row as u32;
+        let column = row / ROWS_PER_COLUMN;
+        Rectangle::new(
+            Point::new(
+                (column * COLUMN_CHARS * CHAR_WIDTH) as i32,
+                ((row % ROWS_PER_COLUMN) * LINE_HEIGHT) as i32,
+            ),
+            Size::new(COLUMN_CHARS * CHAR_WIDTH, LINE_HEIGHT),
+        )
+    }
+
+    fn band_map_area() -> Rectangle {
+        Rectangle::new(
+            Point::new(0, (HEIGHT - BAND_MAP_HEIGHT) as i32),
+            Size::new(WIDTH, BAND_MAP_HEIGHT),
+        )
+    }
+
+    /// Send one area of the canvas to the panel
+    fn send_area(&mut self, area: Rectangle) -> DisplayResult<()> {
+        let (x, y) = (area.top_left.x as u32, area.top_left.y as u32);
+        let (width, height) = (area.size.width, area.size.height);
+        let [x0_hi, x0_lo] = (x as u16).to_be_bytes();
+        let [x1_hi, x1_lo] = ((x + width - 1) as u16).to_be_bytes();
+        let [y0_hi, y0_lo] = (y as u16).to_be_bytes();
+        let [y1_hi, y1_lo] = ((y + height - 1) as u16).to_be_bytes();
+        self.command(0x2A, &[x0_hi, x0_lo, x1_hi, x1_lo])?; // column range
+        self.command(0x2B, &[y0_hi, y0_lo, y1_hi, y1_lo])?; // row range
+
+        let mut bytes = Vec::with_capacity((width * height * 2) as usize);
+        for line in y..y + height {
+            let start = (line * WIDTH + x) as usize;
+            for pixel in &self.canvas.pixels[start..start + width as usize] {
+                bytes.extend_from_slice(&pixel.to_be_bytes());
+            }
+        }
+        self.command(0x2C, &bytes) // memory write
+    }
+
+    /// Draw a text row, at double size over it and the row below when
+    /// `large`
+    fn draw_row(&mut self, row: usize, large: bool) {
+        let mut area = Self::row_area(row);
+        if large {
+            area.size.height *= 2;
+        }
+        let _ = area
+            .into_styled(PrimitiveStyle::with_fill(rgb565(BACKGROUND)))
+            .draw(&mut self.canvas);
+
+        let (text, colors) = &self.lines[row];
+        if large {
+            let mut doubled = Doubled::new(&mut self.canvas, area.top_left);
+            draw_text(&mut doubled, Point::zero(), text, colors);
+        } else {
+            draw_text(&mut self.canvas, area.top_left, text, colors);
+        }
+    }
+
+    /// Draw a bar per band, scaled to the busiest band, with band labels
+    fn draw_band_map(&mut self, counts: &[usize; Band::ALL.len()]) {
+        let area = Self::band_map_area();
+        let _ = area
+            .into_styled(PrimitiveStyle::with_fill(rgb565(BACKGROUND)))
+            .draw(&mut self.canvas);
+        let top = area.top_left.y;
+        let _ = Line::new(Point::new(0, top), Point::new(WIDTH as i32 - 1, top))
+            .into_styled(PrimitiveStyle::with_stroke(rgb565(RULE), 1))
+            .draw(&mut self.canvas);
+
+        let slot = WIDTH / Band::ALL.len() as u32;
+        let busiest = counts.iter().copied().max().unwrap_or(0).max(1);
+        let baseline = top + 2 + BAR_HEIGHT as i32;
+        for (index, (band, count)) in Band::ALL.iter().zip(counts).enumerate() {
+            let color = rgb565(hint(color::band_color(*band)));
+            let left = (index as u32 * slot) as i32;
+            let height = (*count as u32 * BAR_HEIGHT).div_ceil(busiest as u32);
+            if height > 0 {
+                let _ = Rectangle::new(
+                    Point::new(left + 3, baseline - height as i32),
+                    Size::new(slot - 6, height),
+                )
+                .into_styled(PrimitiveStyle::with_fill(color))
+                .draw(&mut self.canvas);
+            }
+            let label = band.name().trim_end_matches('m');
+            let label_left = left + (slot as i32 - 6 * label.len() as i32) / 2;
+            let style = MonoTextStyle::new(&FONT_6X10, color);
+            let _ = Text::with_baseline(
+                label,
+                Point::new(label_left, baseline + 1),
+                style,
+                Baseline::Top,
+            )
+            .draw(&mut self.canvas);
+        }
+    }
+}
+
+impl DisplayDriver for TftDriver {
+    fn name(&self) -> &'static str {
+        "tft"
+    }
+
+    fn init(&mut self) -> DisplayResult<()> {
+        if let Some(reset) = self.reset.as_mut() {
+            reset.set(false)?;
+            thread::sleep(Duration::from_millis(10));
+            reset.set(true)?;
+            thread::sleep(Duration::from_millis(120));
+        }
+        self.command(0x01, &[])?; // software reset
+        thread::sleep(Duration::from_millis(150));
+        self.command(0x11, &[])?; // sleep out
+        thread::sleep(Duration::from_millis(120));
+        self.command(0x3A, &[0x55])?; // 16 bits per pixel
+        self.command(0x36, &[self.controller.madctl()])?;
+        if self.controller == Controller::St7789 {
+            // IPS panels need inverted colors
+            self.command(0x21, &[])?;
+        }
+        self.command(0x13, &[])?; // normal display mode
+        self.command(0x29, &[])?; // display on
+        if let Some(backlight) = self.backlight.as_mut() {
+            backlight.set(true)?;
+        }
+        self.clear()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            brightness: self.backlight.is_some(),
+            graphic: true,
+            color: true,
+            large_text: true,
+            invert: true,
+            power: true,
+            ..Capabilities::default()
+        }
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        (COLUMN_CHARS as usize, self.lines.len())
+    }
+
+    fn clear(&mut self) -> DisplayResult<()> {
+        let _ = self.canvas.clear(rgb565(BACKGROUND));
+        for line in &mut self.lines {
+            line.0.clear();
+            line.1.clear();
+        }
+        self.dirty_rows.fill(true);
+        self.shown_counts = None;
+        self.flush()
+    }
+
+    fn write_line(&mut self, row: usize, text: &str) -> DisplayResult<()> {
+        self.write_colored_line(row, text, &[])
+    }
+
+    fn write_colored_line(
+        &mut self,
+        row: usize,
+        text: &str,
+        colors: &[ColorSpan],
+    ) -> DisplayResult<()> {
+        match self.lines.get_mut(row) {
+            Some(line) if line.0 != text || line.1 != colors => {
+                *line = (text.to_string(), colors.to_vec());
+            }
+            _ => return Ok(()),
+        }
+        self.dirty_rows[row] = true;
+        self.draw_row(row, false);
+        Ok(())
+    }
+
+    fn write_large_line(
+        &mut self,
+        row: usize,
+        text: &str,
+        colors: &[ColorSpan],
+    ) -> DisplayResult<()> {
+        // The row below has to be in the same column
+        if row + 1 >= self.lines.len() || (row as u32 + 1).is_multiple_of(ROWS_PER_COLUMN) {
+            return self.write_colored_line(row, text, colors);
+        }
+        // Half-width text never matches a normal row, and the blanked row
+        // below is redrawn by the next write to it
+        self.lines[row] = (text.to_string(), colors.to_vec());
+        self.lines[row + 1] = (String::new(), Vec::new());
+        self.dirty_rows[row] = true;
+        self.dirty_rows[row + 1] = true;
+        self.draw_row(row, true);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> DisplayResult<()> {
+        for row in 0..self.dirty_rows.len() {
+            if self.dirty_rows[row] {
+                self.send_area(Self::row_area(row))?;
+                self.dirty_rows[row] = false;
+            }
+        }
+
+        let counts = band_counts(&self.lines);
+        if self.shown_counts != Some(counts) {
+            self.draw_band_map(&counts);
+            self.send_area(Self::band_map_area())?;
+            self.shown_counts = Some(counts);
+        }
+        Ok(())
+    }
+
+    /// The backlight is a plain GPIO, so any level above zero is fully on
+    fn set_brightness(&mut self, percent: u8) -> DisplayResult<()> {
+        match self.backlight.as_mut() {
+            Some(backlight) => Ok(backlight.set(percent > 0)?),
+            None => Err(DisplayError::NotSupported),
+        }
+    }
+
+    fn set_power(&mut self, on: bool) -> DisplayResult<()> {
+        self.command(if on { 0x29 } else { 0x28 }, &[])?; // display on / off
+        if let Some(backlight) = self.backlight.as_mut() {
+            backlight.set(on)?;
+        }
+        Ok(())
+    }
+
+    /// ST7789 IPS panels already run inverted, so their inverted state is
+    /// normal mode
+    fn set_inverted(&mut self, inverted: bool) -> DisplayResult<()> {
+        let invert_on = inverted != (self.controller == Controller::St7789);
+        self.command(if invert_on { 0x21 } else { 0x20 }, &[])
+    }
+}
+
+impl Drop for TftDriver {
+    fn drop(&mut self) {
+        let _ = self.command(0x28, &[]); // display off
+        if let Some(backlight) = self.backlight.as_mut() {
+            let _ = backlight.set(false);
+        }
+    }
+}
+
+/// Open a TFT panel from the display config
+pub fn open_from_config(
+    controller: Controller,
+    config: &crate::config::DisplayConfig,
+) -> DisplayResult<TftDriver> {
+    let spi = SpiDevice::open(config.spi_bus, config.spi_chip_select, config.spi_speed_hz)
+        .map_err(DisplayError::ConnectionFailed)?;
+    let dc = OutputPin::open(config.dc_pin).map_err(DisplayError::ConnectionFailed)?;
+    let open_optional = |pin: Option<u32>| {
+        pin.map(OutputPin::open)
+            .transpose()
+            .map_err(DisplayError::ConnectionFailed)
+    };
+    let reset = open_optional(config.reset_pin)?;
+    let backlight = open_optional(config.backlight_pin)?;
+    TftDriver::open(controller, spi, dc, reset, backlight)
+}
@@ -0,0 +1,268 @@
+//! Scheduled spot and session dumps (`[dump]`)
+//!
+//! A thread counts every spot update from `SpotStore` since start and, at
+//! each minute `schedule` matches, writes the spots on hand and those
+//! counts to `spots-<UTC time>` and `stats-<UTC time>` files (JSON, CSV or
+//! both) in `dir`. Files older than `keep_days` are deleted after each
+//! dump.
+
+use crate::config::DumpConfig;
+use crate::models::{AggregatedSpot, Band, SpotRecord};
+use crate::paths;
+use crate::services::schedule::Cron;
+use crate::services::SpotStore;
+use chrono::{DateTime, SecondsFormat, Timelike, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::{Duration, SystemTime};
+use tokio::sync::broadcast::error::TryRecvError;
+
+/// How often the thread counts updates and checks the schedule
+const TICK: Duration = Duration::from_secs(1);
+
+/// Keeps the dump thread running; it stops when this is dropped
+pub struct ActivityDump {
+    _stop: Sender<()>,
+}
+
+/// Counts since start
+#[derive(Default)]
+struct Session {
+    spots: u64,
+    stations: HashSet<String>,
+    /// Spots and stations per band
+    bands: BTreeMap<Band, (u64, HashSet<String>)>,
+}
+
+/// Session counts as written
+#[derive(Serialize)]
+struct Stats {
+    time: String,
+    started: String,
+    uptime_seconds: i64,
+    spots_received: u64,
+    stations_heard: usize,
+    spots_on_hand: usize,
+    busiest_band: Option<&'static str>,
+    /// In band order
+    bands: Vec<BandStats>,
+}
+
+#[derive(Serialize)]
+struct BandStats {
+    band: &'static str,
+    spots: u64,
+    stations: usize,
+}
+
+impl ActivityDump {
+    /// Start counting and dumping, or None when not enabled, the schedule
+    /// doesn't parse or there's no directory. `zone` is `[units] timezone`.
+    pub fn start(config: &DumpConfig, zone: Option<Tz>, spot_store: &SpotStore) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let Some(cron) = Cron::parse(&config.schedule) else {
+            log::warn!("Dump schedule '{}' doesn't parse", config.schedule);
+            return None;
+        };
+        let Some(dir) = (match config.dir.trim() {
+            "" => paths::dumps_dir(),
+            dir => Some(PathBuf::from(dir)),
+        }) else {
+            log::warn!("No data directory for spot dumps");
+            return None;
+        };
+        let format = config.format.trim().to_lowercase();
+        let (json, csv) = (format != "csv", format == "csv" || format == "both");
+        let keep = (config.keep_days > 0)
+            .then(|| Duration::from_secs(u64::from(config.keep_days) * 24 * 60 * 60));
+        let spot_store = spot_store.clone();
+        let mut updates = spot_store.subscribe();
+        let (stop, stop_rx) = mpsc::channel::<()>();
+
+        thread::spawn(move || {
+            let started = Utc::now();
+            let mut session = Session::default();
+            // Minute last checked, so each runs once
+            let mut last_minute = None;
+            while let Err(mpsc::RecvTimeoutError::Timeout) = stop_rx.recv_timeout(TICK) {
+                loop {
+                    match updates.try_recv() {
+                        Ok(spot) => session.count(&spot),
+                        // Missed ones still count
+                        Err(TryRecvError::Lagged(missed)) => session.spots += missed,
+                        Err(TryRecvError::Empty | TryRecvError::Closed) => break,
+                    }
+                }
+
+                let now = Utc::now();
+                let minute = now.with_second(0).and_then(|now| now.with_nanosecond(0));
+                if minute == last_minute || !cron.matches(now, zone) {
+                    last_minute = minute;
+                    continue;
+                }
+                last_minute = minute;
+
+                let spots = spot_store.get_spots_by_frequency();
+                let stats = session.stats(started, now, spots.len());
+                let stamp = now.format("%Y%m%d-%H%M").to_string();
+                let result = fs::create_dir_all(&dir)
+                    .map_err(|e| e.to_string())
+                    .and_then(|()| {
+                        if json {
+                            write_json(&dir, &stamp, &stats, &spots)?;
+                        }
+                        if csv {
+                            write_csv(&dir, &stamp, &stats, &spots)?;
+                        }
+                        Ok(())
+                    });
+                match result {
+                    Ok(()) => log::info!("Dumped {} spots to {}", spots.len(), dir.display()),
+                    Err(e) => log::warn!("Spot dump to {} failed: {}", dir.display(), e),
+                }
+                if let Some(keep) = keep {
+                    remove_old(&dir, keep);
+                }
+            }
+        });
+
+        Some(Self { _stop: stop })
+    }
+}
+
+impl Session {
+    fn count(&mut self, spot: &AggregatedSpot) {
+        let call = spot.callsign.to_uppercase();
+        self.spots += 1;
+        if let Some(band) = Band::from_khz(spot.frequency_khz) {
+            let (spots, stations) = self.bands.entry(band).or_default();
+            *spots += 1;
+            stations.insert(call.clone());
+        }
+        self.stations.insert(call);
+    }
+
+    fn stats(&self, started: DateTime<Utc>, now: DateTime<Utc>, on_hand: usize) -> Stats {
+        Stats {
+            time: now.to_rfc3339_opts(SecondsFormat::Secs, true),
+            started: started.to_rfc3339_opts(SecondsFormat::Secs, true),
+            uptime_seconds: now.signed_duration_since(started).num_seconds(),
+            spots_received: self.spots,
+            stations_heard: self.stations.len(),
+            spots_on_hand: on_hand,
+            busiest_band: self
+                .bands
+                .iter()
+                .max_by_key(|(_, (spots, _))| *spots)
+                .map(|(band, _)| band.name()),
+            bands: self
+                .bands
+                .iter()
+                .map(|(band, (spots, stations))| BandStats {
+                    band: band.name(),
+                    spots: *spots,
+                    stations: stations.len(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// `spots-<stamp>.json` and `stats-<stamp>.json`
+fn write_json(
+    dir: &Path,
+    stamp: &str,
+    stats: &Stats,
+    spots: &[AggregatedSpot],
+) -> Result<(), String> {
+    let records: Vec<SpotRecord> = spots.iter().map(SpotRecord::from).collect();
+    let spots = serde_json::to_string_pretty(&records).map_err(|e| e.to_string())?;
+    let stats = serde_json::to_string_pretty(stats).map_err(|e| e.to_string())?;
+    write(&dir.join(format!("spots-{}.json", stamp)), &spots)?;
+    write(&dir.join(format!("stats-{}.json", stamp)), &stats)
+}
+
+/// `spots-<stamp>.csv`, a row per spot, and `stats-<stamp>.csv`, a row
+/// per band and one for all of them
+fn write_csv(
+    dir: &Path,
+    stamp: &str,
+    stats: &Stats,
+    spots: &[AggregatedSpot],
+) -> Result<(), String> {
+    let mut text =
+        String::from("callsign,frequency_khz,band,snr,wpm,mode,age_seconds,spot_count\n");
+    for record in spots.iter().map(SpotRecord::from) {
+        text.push_str(&format!(
+            "{},{:.1},{},{},{},{},{},{}\n",
+            csv_field(&record.callsign),
+            record.frequency_khz,
+            record.band.unwrap_or_default(),
+            record.snr,
+            record.wpm,
+            csv_field(&record.mode),
+            record.age_seconds,
+            record.spot_count
+        ));
+    }
+    write(&dir.join(format!("spots-{}.csv", stamp)), &text)?;
+
+    let mut text = String::from("time,started,uptime_seconds,band,spots,stations\n");
+    let start = format!("{},{},{}", stats.time, stats.started, stats.uptime_seconds);
+    for band in &stats.bands {
+        text.push_str(&format!(
+            "{},{},{},{}\n",
+            start, band.band, band.spots, band.stations
+        ));
+    }
+    text.push_str(&format!(
+        "{},all,{},{}\n",
+        start, stats.spots_received, stats.stations_heard
+    ));
+    write(&dir.join(format!("stats-{}.csv", stamp)), &text)
+}
+
+/// Quoted when it holds a comma, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn write(path: &Path, text: &str) -> Result<(), String> {
+    fs::write(path, text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Delete dumps in `dir` last written more than `keep` ago
+fn remove_old(dir: &Path, keep: Duration) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let ours = (name.starts_with("spots-") || name.starts_with("stats-"))
+            && (name.ends_with(".json") || name.ends_with(".csv"));
+        let old = entry
+            .metadata()
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_some_and(|age| age > keep);
+        if ours && old {
+            match fs::remove_file(&path) {
+                Ok(()) => log::debug!("Removed old dump {}", path.display()),
+                Err(e) => log::warn!("Failed to remove {}: {}", path.display(), e),
+            }
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! GPIO pins through the Linux sysfs interface
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+const SYSFS_GPIO: &str = "/sys/class/gpio";
+
+/// Export a pin (if needed) and set its direction, returning its directory
+fn export(pin: u32, direction: &str) -> Result<String, String> {
+    let dir = format!("{}/gpio{}", SYSFS_GPIO, pin);
+    if !Path::new(&dir).exists() {
+        std::fs::write(format!("{}/export", SYSFS_GPIO), pin.to_string())
+            .map_err(|e| format!("Failed to export GPIO {}: {}", pin, e))?;
+        // udev needs a moment to fix permissions on the new files
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    std::fs::write(format!("{}/direction", dir), direction)
+        .map_err(|e| format!("Failed to set GPIO {} as {}: {}", pin, direction, e))?;
+    Ok(dir)
+}
+
+/// An exported sysfs GPIO configured as an output
+pub struct OutputPin {
+    value: File,
+}
+
+impl OutputPin {
+    /// Export a pin by its sysfs number and set it as an output
+    pub fn open(pin: u32) -> Result<Self, String> {
+        let dir = export(pin, "out")?;
+        let value = OpenOptions::new()
+            .write(true)
+            .open(format!("{}/value", dir))
+            .map_err(|e| format!("Failed to open GPIO {}: {}", pin, e))?;
+        Ok(Self { value })
+    }
+
+    pub fn set(&mut self, high: bool) -> std::io::Result<()> {
+        self.value.write_all(if high { b"1" } else { b"0" })
+    }
+}
+
+/// An exported sysfs GPIO configured as an input
+pub struct InputPin {
+    value: File,
+}
+
+impl InputPin {
+    /// Export a pin by its sysfs number and set it as an input
+    pub fn open(pin: u32) -> Result<Self, String> {
+        let dir = export(pin, "in")?;
+        let value = File::open(format!("{}/value", dir))
+            .map_err(|e| format!("Failed to open GPIO {}: {}", pin, e))?;
+        Ok(Self { value })
+    }
+
+    pub fn is_high(&mut self) -> std::io::Result<bool> {
+        use std::io::{Read, Seek, SeekFrom};
+
+        let mut level = [0u8; 1];
+        self.value.seek(SeekFrom::Start(0))?;
+        self.value.read_exact(&mut level)?;
+        Ok(level[0] == b'1')
+    }
+}
+
+/// Parse a comma separated pin list, e.g. "25,24,23,17,18,22"
+pub fn parse_pins(list: &str) -> Result<Vec<u32>, String> {
+    list.split(',')
+        .map(|pin| {
+            pin.trim()
+                .parse()
+                .map_err(|_| format!("Invalid GPIO pin '{}'", pin.trim()))
+        })
+        .collect()
+}
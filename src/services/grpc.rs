@@ -0,0 +1,271 @@
+//! gRPC spot stream and control service (`grpc` feature, `[grpc]`)
+//!
+//! Serves `SpotService` from proto/rbn_vfd.proto on its own tokio runtime
+//! thread, like the web dashboard. `WatchSpots` streams the `SpotStore`
+//! subscription; `Tune` and `SetFilter` come back to the main loop as
+//! `GrpcCommand`s. With `[grpc] token` set, calls need
+//! `authorization: Bearer <token>` metadata.
+
+// Status is what tonic's service traits and interceptors return
+#![allow(clippy::result_large_err)]
+
+use crate::config::{same_token, GrpcConfig};
+use crate::models::{AggregatedSpot, Band, SpotRecord, SpotSort};
+use crate::services::control::AppCommand;
+use crate::services::SpotStore;
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tonic::{Request, Response, Status};
+
+/// Messages and service generated from proto/rbn_vfd.proto by build.rs
+#[allow(clippy::all)]
+pub mod proto {
+    tonic::include_proto!("rbnvfd.v1");
+}
+
+use proto::spot_service_server::{SpotService, SpotServiceServer};
+
+/// Spots buffered for a slow `WatchSpots` client before it misses some
+const STREAM_BUFFER: usize = 64;
+
+/// Requests from gRPC clients to the app
+#[derive(Debug, Clone)]
+pub enum GrpcCommand {
+    Tune {
+        frequency_khz: f64,
+        mode: String,
+    },
+    /// Settings change for the app's control queue
+    Control(AppCommand),
+}
+
+/// Handle to the gRPC server thread
+pub struct GrpcServer {
+    address: String,
+    cmd_rx: mpsc::Receiver<GrpcCommand>,
+}
+
+impl GrpcServer {
+    /// Start serving on `[grpc] bind:port`
+    pub fn start(config: &GrpcConfig, spot_store: SpotStore) -> Result<Self, String> {
+        let address = format!("{}:{}", config.bind, config.port);
+        let listener = std::net::TcpListener::bind(&address)
+            .map_err(|e| format!("Failed to listen on {}: {}", address, e))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| format!("Failed to listen on {}: {}", address, e))?;
+
+        let (cmd_tx, cmd_rx) = mpsc::channel(16);
+        let service = Service { spot_store, cmd_tx };
+        let token = config.token.expose().trim().to_string();
+
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Failed to create tokio runtime");
+            rt.block_on(async move {
+                let listener = match tokio::net::TcpListener::from_std(listener) {
+                    Ok(listener) => listener,
+                    Err(e) => {
+                        log::error!("gRPC server failed to start: {}", e);
+                        return;
+                    }
+                };
+                let server = SpotServiceServer::with_interceptor(service, move |request| {
+                    check_token(request, &token)
+                });
+                if let Err(e) = tonic::transport::Server::builder()
+                    .add_service(server)
+                    .serve_with_incoming(TcpListenerStream::new(listener))
+                    .await
+                {
+                    log::error!("gRPC server stopped: {}", e);
+                }
+            });
+        });
+
+        Ok(Self { address, cmd_rx })
+    }
+
+    /// Address the server is listening on
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Try to receive a command from a client (non-blocking)
+    pub fn try_recv(&mut self) -> Option<GrpcCommand> {
+        self.cmd_rx.try_recv().ok()
+    }
+}
+
+/// Let a call through when no token is set or it carries the right one
+fn check_token(request: Request<()>, token: &str) -> Result<Request<()>, Status> {
+    if token.is_empty() {
+        return Ok(request);
+    }
+    let given = request
+        .metadata()
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match given {
+        Some(given) if same_token(given.trim(), token) => Ok(request),
+        _ => Err(Status::unauthenticated("missing or wrong bearer token")),
+    }
+}
+
+impl From<&AggregatedSpot> for proto::Spot {
+    fn from(spot: &AggregatedSpot) -> Self {
+        let record = SpotRecord::from(spot);
+        Self {
+            callsign: record.callsign,
+            frequency_khz: record.frequency_khz,
+            band: record.band.unwrap_or_default().to_string(),
+            snr: record.snr,
+            wpm: record.wpm,
+            mode: record.mode,
+            age_seconds: record.age_seconds,
+            spot_count: record.spot_count,
+        }
+    }
+}
+
+struct Service {
+    spot_store: SpotStore,
+    cmd_tx: mpsc::Sender<GrpcCommand>,
+}
+
+impl Service {
+    async fn send(&self, command: GrpcCommand) -> Result<(), Status> {
+        self.cmd_tx
+            .send(command)
+            .await
+            .map_err(|_| Status::unavailable("the app isn't taking commands"))
+    }
+}
+
+/// Band name, or None for "" and "all"
+fn parse_band(name: &str) -> Result<Option<Band>, Status> {
+    let name = name.trim();
+    if name.is_empty() || name.eq_ignore_ascii_case("all") {
+        return Ok(None);
+    }
+    Band::from_name(name)
+        .map(Some)
+        .ok_or_else(|| Status::invalid_argument(format!("unknown band '{}'", name)))
+}
+
+#[tonic::async_trait]
+impl SpotService for Service {
+    type WatchSpotsStream = ReceiverStream<Result<proto::Spot, Status>>;
+
+    async fn watch_spots(
+        &self,
+        request: Request<proto::WatchSpotsRequest>,
+    ) -> Result<Response<Self::WatchSpotsStream>, Status> {
+        let request = request.into_inner();
+        let band = parse_band(&request.band)?;
+        let min_snr = request.min_snr;
+        let wanted = move |spot: &AggregatedSpot| {
+            spot.highest_snr >= min_snr
+                && (band.is_none() || Band::from_khz(spot.frequency_khz) == band)
+        };
+
+        // Subscribe before the snapshot so nothing falls between them
+        let mut updates = self.spot_store.subscribe();
+        let snapshot = if request.snapshot {
+            self.spot_store.get_spots_by_frequency()
+        } else {
+            Vec::new()
+        };
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+        tokio::spawn(async move {
+            for spot in snapshot.iter().filter(|spot| wanted(spot)) {
+                if tx.send(Ok(proto::Spot::from(spot))).await.is_err() {
+                    return;
+                }
+            }
+            loop {
+                match updates.recv().await {
+                    Ok(spot) => {
+                        if wanted(&spot) && tx.send(Ok(proto::Spot::from(&spot))).await.is_err() {
+                            // Client went away
+                            return;
+                        }
+                    }
+                    // Missed some; carry on with the next
+                    Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => return,
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn tune(
+        &self,
+        request: Request<proto::TuneRequest>,
+    ) -> Result<Response<proto::TuneReply>, Status> {
+        let request = request.into_inner();
+        let (frequency_khz, mode) = match request.target {
+            Some(proto::tune_request::Target::Callsign(callsign)) => {
+                let callsign = callsign.trim();
+                let spot = self
+                    .spot_store
+                    .get_spots_by_recency()
+                    .into_iter()
+                    .find(|spot| spot.callsign.eq_ignore_ascii_case(callsign))
+                    .ok_or_else(|| Status::not_found(format!("no spot of {}", callsign)))?;
+                (spot.frequency_khz, spot.mode)
+            }
+            Some(proto::tune_request::Target::FrequencyKhz(frequency_khz)) => {
+                (frequency_khz, request.mode)
+            }
+            None => {
+                return Err(Status::invalid_argument(
+                    "needs a callsign or frequency_khz",
+                ))
+            }
+        };
+        self.send(GrpcCommand::Tune {
+            frequency_khz,
+            mode: mode.clone(),
+        })
+        .await?;
+        Ok(Response::new(proto::TuneReply {
+            frequency_khz,
+            mode,
+        }))
+    }
+
+    async fn set_filter(
+        &self,
+        request: Request<proto::SetFilterRequest>,
+    ) -> Result<Response<proto::SetFilterReply>, Status> {
+        let request = request.into_inner();
+        let mut commands = Vec::new();
+        if let Some(snr) = request.min_snr {
+            commands.push(AppCommand::SetMinSnr(snr));
+        }
+        if let Some(minutes) = request.max_age_minutes {
+            commands.push(AppCommand::SetMaxAge(minutes));
+        }
+        if let Some(name) = request.band {
+            commands.push(AppCommand::SetBand(parse_band(&name)?));
+        }
+        if let Some(on) = request.cw_only {
+            commands.push(AppCommand::SetCwOnly(on));
+        }
+        if let Some(name) = request.sort {
+            let sort = SpotSort::from_name(&name).ok_or_else(|| {
+                Status::invalid_argument(format!("unknown sort '{}'", name.trim()))
+            })?;
+            commands.push(AppCommand::SetSort(sort));
+        }
+        for command in commands {
+            self.send(GrpcCommand::Control(command)).await?;
+        }
+        Ok(Response::new(proto::SetFilterReply {}))
+    }
+}
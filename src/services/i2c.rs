@@ -0,0 +1,46 @@
+//! Minimal Linux i2c-dev access for display and sensor hardware
+
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::os::unix::io::AsRawFd;
+
+/// ioctl to select the slave address for subsequent reads/writes
+const I2C_SLAVE: u64 = 0x0703;
+
+/// An open I2C device at a fixed address on /dev/i2c-N
+pub struct I2cDevice {
+    file: File,
+}
+
+impl I2cDevice {
+    pub fn open(bus: u8, address: u8) -> Result<Self, String> {
+        let path = format!("/dev/i2c-{}", bus);
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to open {}: {}", path, e))?;
+
+        // SAFETY: fd is a valid open i2c-dev handle and I2C_SLAVE takes an integer argument
+        let result =
+            unsafe { libc::ioctl(file.as_raw_fd(), I2C_SLAVE as _, address as libc::c_ulong) };
+        if result < 0 {
+            return Err(format!(
+                "Failed to select I2C address 0x{:02x} on {}: {}",
+                address,
+                path,
+                std::io::Error::last_os_error()
+            ));
+        }
+
+        Ok(Self { file })
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        self.file.write_all(bytes)
+    }
+
+    pub fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<()> {
+        self.file.read_exact(buffer)
+    }
+}
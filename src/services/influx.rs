@@ -0,0 +1,322 @@
+//! Time-series export to InfluxDB (`[influx]`)
+//!
+//! Every spot's SNR (`<prefix>_snr`, tagged by band, mode, skimmer and the
+//! continents at both ends), the spots and stations heard per band in each
+//! interval (`<prefix>_activity`) and the solar indices from WWV/WCY
+//! reports (`<prefix>_propagation`) are written as line protocol, over
+//! HTTP to InfluxDB 1 or 2 or over UDP to InfluxDB 1 or Telegraf. A thread
+//! buffers them and writes once per `interval_secs`; lines a write didn't
+//! take are kept for the next, up to `MAX_BUFFERED`.
+
+use crate::config::InfluxConfig;
+use crate::models::{Band, Propagation, RawSpot};
+use crate::services::SpotAlerts;
+use chrono::Utc;
+use std::collections::{BTreeMap, HashSet};
+use std::net::UdpSocket;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long the server gets to take a write
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Lines kept while the server is unreachable; the oldest go first
+const MAX_BUFFERED: usize = 50_000;
+/// Largest UDP datagram sent, below a typical MTU
+const MAX_DATAGRAM: usize = 1400;
+
+/// Hands spots and reports to the export thread
+pub struct InfluxExporter {
+    tx: Sender<Point>,
+}
+
+/// What the thread is told about
+enum Point {
+    Spot {
+        band: Option<Band>,
+        mode: String,
+        spotter: String,
+        /// Spotter's and spotted station's continents ("" when unknown)
+        de_continent: String,
+        dx_continent: String,
+        callsign: String,
+        frequency_khz: f64,
+        snr: i32,
+        speed_wpm: i32,
+        /// Nanoseconds since the epoch
+        time: i64,
+    },
+    Propagation(Propagation, i64),
+}
+
+/// Spots heard on a band in the current interval
+#[derive(Default)]
+struct Activity {
+    spots: u32,
+    stations: HashSet<String>,
+    snr_total: i64,
+    max_snr: i32,
+}
+
+/// Where lines are written
+enum Target {
+    Http { url: String, token: String },
+    Udp { socket: UdpSocket, address: String },
+}
+
+impl InfluxExporter {
+    /// Start the export thread, or None when not enabled or there's
+    /// nowhere to write
+    pub fn start(config: &InfluxConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let target = match (config.url.trim(), config.udp_address.trim()) {
+            ("", "") => {
+                log::warn!("InfluxDB export needs a url or udp_address");
+                return None;
+            }
+            ("", address) => match UdpSocket::bind(("0.0.0.0", 0)) {
+                Ok(socket) => Target::Udp {
+                    socket,
+                    address: address.to_string(),
+                },
+                Err(e) => {
+                    log::warn!("Failed to open a UDP socket for InfluxDB: {}", e);
+                    return None;
+                }
+            },
+            (url, _) => Target::Http {
+                url: url.to_string(),
+                token: config.token.expose().trim().to_string(),
+            },
+        };
+        let prefix = config.prefix.trim().to_string();
+        let interval = Duration::from_secs(config.interval_secs.max(1));
+        let write_spots = config.spots;
+        let (tx, rx) = mpsc::channel::<Point>();
+
+        thread::spawn(move || {
+            let mut buffered: Vec<String> = Vec::new();
+            let mut activity: BTreeMap<Band, Activity> = BTreeMap::new();
+            let mut next_write = Instant::now() + interval;
+            loop {
+                let wait = next_write.saturating_duration_since(Instant::now());
+                match rx.recv_timeout(wait) {
+                    Ok(Point::Spot {
+                        band,
+                        mode,
+                        spotter,
+                        de_continent,
+                        dx_continent,
+                        callsign,
+                        frequency_khz,
+                        snr,
+                        speed_wpm,
+                        time,
+                    }) => {
+                        if let Some(band) = band {
+                            let counts = activity.entry(band).or_default();
+                            counts.spots += 1;
+                            counts.snr_total += i64::from(snr);
+                            counts.max_snr = if counts.spots == 1 {
+                                snr
+                            } else {
+                                counts.max_snr.max(snr)
+                            };
+                            counts.stations.insert(callsign.clone());
+                        }
+                        if write_spots {
+                            let mut tags = vec![
+                                ("band", band.map_or("unknown", Band::name).to_string()),
+                                ("mode", mode),
+                                ("spotter", spotter),
+                            ];
+                            if !de_continent.is_empty() {
+                                tags.push(("de_continent", de_continent));
+                            }
+                            if !dx_continent.is_empty() {
+                                tags.push(("dx_continent", dx_continent));
+                            }
+                            buffered.push(line(
+                                &format!("{}_snr", prefix),
+                                &tags,
+                                &[
+                                    ("snr", format!("{}i", snr)),
+                                    ("frequency_khz", format!("{:.1}", frequency_khz)),
+                                    ("wpm", format!("{}i", speed_wpm)),
+                                    ("callsign", string_field(&callsign)),
+                                ],
+                                time,
+                            ));
+                        }
+                    }
+                    Ok(Point::Propagation(report, time)) => {
+                        let fields: Vec<(&str, String)> = [
+                            ("sfi", report.sfi),
+                            ("a_index", report.a_index),
+                            ("k_index", report.k_index),
+                        ]
+                        .into_iter()
+                        .filter_map(|(name, value)| Some((name, format!("{}i", value?))))
+                        .collect();
+                        if !fields.is_empty() {
+                            buffered.push(line(
+                                &format!("{}_propagation", prefix),
+                                &[],
+                                &fields,
+                                time,
+                            ));
+                        }
+                    }
+                    Err(mpsc::RecvTimeoutError::Timeout) => {}
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                }
+                if Instant::now() < next_write {
+                    continue;
+                }
+                next_write = Instant::now() + interval;
+
+                let now = now_nanos();
+                for (band, counts) in std::mem::take(&mut activity) {
+                    buffered.push(line(
+                        &format!("{}_activity", prefix),
+                        &[("band", band.name().to_string())],
+                        &[
+                            ("spots", format!("{}i", counts.spots)),
+                            ("stations", format!("{}i", counts.stations.len())),
+                            ("max_snr", format!("{}i", counts.max_snr)),
+                            (
+                                "mean_snr",
+                                format!("{:.1}", counts.snr_total as f64 / counts.spots as f64),
+                            ),
+                        ],
+                        now,
+                    ));
+                }
+                if buffered.is_empty() {
+                    continue;
+                }
+                match target.write(&buffered) {
+                    Ok(()) => {
+                        log::debug!("Wrote {} lines to InfluxDB", buffered.len());
+                        buffered.clear();
+                    }
+                    Err(e) => {
+                        log::warn!("InfluxDB write failed: {}", e);
+                        let excess = buffered.len().saturating_sub(MAX_BUFFERED);
+                        buffered.drain(..excess);
+                    }
+                }
+            }
+        });
+
+        Some(Self { tx })
+    }
+
+    /// Count a spot, and write its SNR when `spots` is set; `alerts` gives
+    /// the continents
+    pub fn spot(&self, spot: &RawSpot, alerts: &SpotAlerts) {
+        let spotter = spot.spotter_callsign.trim_end_matches("-#").to_uppercase();
+        let _ = self.tx.send(Point::Spot {
+            band: Band::from_khz(spot.frequency_khz),
+            mode: spot.mode.to_uppercase(),
+            de_continent: alerts.continent(&spotter).unwrap_or_default().to_string(),
+            dx_continent: alerts
+                .continent(&spot.spotted_callsign)
+                .unwrap_or_default()
+                .to_string(),
+            spotter,
+            callsign: spot.spotted_callsign.to_uppercase(),
+            frequency_khz: spot.frequency_khz,
+            snr: spot.snr,
+            speed_wpm: spot.speed_wpm,
+            time: now_nanos(),
+        });
+    }
+
+    /// Write the solar indices from a WWV/WCY report
+    pub fn propagation(&self, report: Propagation) {
+        let _ = self.tx.send(Point::Propagation(report, now_nanos()));
+    }
+}
+
+impl Target {
+    fn write(&self, lines: &[String]) -> Result<(), String> {
+        match self {
+            Target::Http { url, token } => {
+                let agent = ureq::AgentBuilder::new().timeout(WRITE_TIMEOUT).build();
+                let mut request = agent
+                    .post(url)
+                    .set("Content-Type", "text/plain; charset=utf-8");
+                if !token.is_empty() {
+                    request = request.set("Authorization", &format!("Token {}", token));
+                }
+                // The token goes in a header, so errors can name the URL
+                request
+                    .send_string(&lines.join("\n"))
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            }
+            Target::Udp { socket, address } => {
+                let mut datagram = String::new();
+                for line in lines {
+                    if !datagram.is_empty() && datagram.len() + line.len() >= MAX_DATAGRAM {
+                        socket
+                            .send_to(datagram.as_bytes(), address.as_str())
+                            .map_err(|e| format!("{}: {}", address, e))?;
+                        datagram.clear();
+                    }
+                    datagram.push_str(line);
+                    datagram.push('\n');
+                }
+                socket
+                    .send_to(datagram.as_bytes(), address.as_str())
+                    .map(|_| ())
+                    .map_err(|e| format!("{}: {}", address, e))
+            }
+        }
+    }
+}
+
+/// One line of line protocol
+/// Example: "rbn_snr,band=20m,mode=CW,spotter=W3LPL,de_continent=NA snr=23i,frequency_khz=14025.3,wpm=28i,callsign=\"DL1ABC\" 1710685380000000000"
+fn line(
+    measurement: &str,
+    tags: &[(&str, String)],
+    fields: &[(&str, String)],
+    time: i64,
+) -> String {
+    let mut line = escape(measurement, &[',', ' ']);
+    for (key, value) in tags {
+        if !value.is_empty() {
+            line.push_str(&format!(",{}={}", key, escape(value, &[',', ' ', '='])));
+        }
+    }
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect();
+    format!("{} {} {}", line, fields.join(","), time)
+}
+
+/// Quoted string field value
+fn string_field(value: &str) -> String {
+    format!("\"{}\"", escape(value, &['"', '\\']))
+}
+
+/// Backslash before each of `special`
+fn escape(text: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if special.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn now_nanos() -> i64 {
+    Utc::now().timestamp_nanos_opt().unwrap_or_default()
+}
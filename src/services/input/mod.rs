@@ -0,0 +1,118 @@
+//! Front-panel controls: a rotary encoder with push button and buttons
+//! bound to actions. Encoder and buttons are read on Linux (`reader`);
+//! Matrix Orbital keypad keys share the same actions on every platform.
+
+#[cfg(target_os = "linux")]
+mod reader;
+
+use crate::services::control::AppCommand;
+
+#[cfg(target_os = "linux")]
+pub use reader::{InputEvent, InputHandler};
+
+/// What a bound button or key does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonAction {
+    BandUp,
+    BandDown,
+    /// Switch between CW only and all modes
+    CwToggle,
+    SnrUp,
+    SnrDown,
+    NextView,
+    /// Previous/next screenful of a list too long for the display
+    PageUp,
+    PageDown,
+    /// Step through the `[profile.<name>]` settings
+    NextProfile,
+    /// Step through the `[[radio]]` entries
+    NextRadio,
+    /// Step through the `[[preset]]` filter sets
+    NextPreset,
+    /// Step through the spot list orders
+    NextSort,
+    /// Spot the `[activation]` on SOTA or POTA at the radio's frequency
+    SelfSpot,
+}
+
+impl ButtonAction {
+    const ALL: [ButtonAction; 13] = [
+        ButtonAction::BandUp,
+        ButtonAction::BandDown,
+        ButtonAction::CwToggle,
+        ButtonAction::SnrUp,
+        ButtonAction::SnrDown,
+        ButtonAction::NextView,
+        ButtonAction::PageUp,
+        ButtonAction::PageDown,
+        ButtonAction::NextProfile,
+        ButtonAction::NextRadio,
+        ButtonAction::NextPreset,
+        ButtonAction::NextSort,
+        ButtonAction::SelfSpot,
+    ];
+
+    /// Name used in the binding maps
+    pub fn name(self) -> &'static str {
+        match self {
+            ButtonAction::BandUp => "band_up",
+            ButtonAction::BandDown => "band_down",
+            ButtonAction::CwToggle => "cw_toggle",
+            ButtonAction::SnrUp => "snr_up",
+            ButtonAction::SnrDown => "snr_down",
+            ButtonAction::NextView => "next_view",
+            ButtonAction::PageUp => "page_up",
+            ButtonAction::PageDown => "page_down",
+            ButtonAction::NextProfile => "next_profile",
+            ButtonAction::NextRadio => "next_radio",
+            ButtonAction::NextPreset => "next_preset",
+            ButtonAction::NextSort => "next_sort",
+            ButtonAction::SelfSpot => "self_spot",
+        }
+    }
+
+    /// Settings change the action makes (None for display navigation and
+    /// self-spots)
+    pub fn command(self) -> Option<AppCommand> {
+        match self {
+            ButtonAction::BandUp => Some(AppCommand::StepBand(1)),
+            ButtonAction::BandDown => Some(AppCommand::StepBand(-1)),
+            ButtonAction::CwToggle => Some(AppCommand::ToggleCwOnly),
+            ButtonAction::SnrUp => Some(AppCommand::StepMinSnr(1)),
+            ButtonAction::SnrDown => Some(AppCommand::StepMinSnr(-1)),
+            ButtonAction::NextProfile => Some(AppCommand::NextProfile),
+            ButtonAction::NextRadio => Some(AppCommand::NextRadio),
+            ButtonAction::NextPreset => Some(AppCommand::NextPreset),
+            ButtonAction::NextSort => Some(AppCommand::NextSort),
+            ButtonAction::NextView
+            | ButtonAction::PageUp
+            | ButtonAction::PageDown
+            | ButtonAction::SelfSpot => None,
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|action| action.name() == name.trim().to_lowercase())
+    }
+}
+
+/// Parse a "key=action,..." binding map, skipping entries with unknown
+/// actions
+pub fn parse_bindings(map: &str) -> Vec<(String, ButtonAction)> {
+    map.split(',')
+        .filter_map(|entry| {
+            let (key, action) = entry.split_once('=')?;
+            Some((key.trim().to_string(), ButtonAction::from_name(action)?))
+        })
+        .collect()
+}
+
+/// Action bound to a Matrix Orbital keypad key in `[input] keypad`
+pub fn keypad_action(bindings: &[(String, ButtonAction)], key: u8) -> Option<ButtonAction> {
+    bindings
+        .iter()
+        .find(|(bound, _)| bound.as_bytes() == [key])
+        .map(|(_, action)| *action)
+}
@@ -0,0 +1,238 @@
+//! Rotary encoder and buttons read from Linux input devices (evdev) or
+//! polled sysfs GPIOs
+
+use super::{parse_bindings, ButtonAction};
+use crate::config::InputConfig;
+use crate::services::gpio::{parse_pins, InputPin};
+use std::fs::File;
+use std::io::Read;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// evdev event types and values used by the encoder overlays
+const EV_KEY: u16 = 0x01;
+const EV_REL: u16 = 0x02;
+const EV_ABS: u16 = 0x03;
+const KEY_PRESSED: i32 = 1;
+
+/// How often GPIO pins are sampled
+const GPIO_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Quadrature transitions per detent on common encoders (KY-040, EC11)
+const TRANSITIONS_PER_DETENT: i32 = 4;
+
+/// A button has to stay put this long before a change counts
+const BUTTON_DEBOUNCE: Duration = Duration::from_millis(30);
+
+/// Something the user did on the front panel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputEvent {
+    /// Encoder turned by this many detents (positive is clockwise)
+    Turn(i32),
+    /// Encoder button pressed
+    Press,
+    /// A bound button or key was pressed
+    Action(ButtonAction),
+}
+
+/// Reads the configured encoder and buttons on background threads
+pub struct InputHandler {
+    receiver: Receiver<InputEvent>,
+}
+
+impl InputHandler {
+    /// Start reading the encoder and buttons, or None when nothing is
+    /// configured
+    pub fn start(config: &InputConfig) -> Result<Option<Self>, String> {
+        let (sender, receiver) = mpsc::channel();
+        let mut started = false;
+
+        let devices: Vec<&str> = config
+            .evdev_devices
+            .split(',')
+            .map(str::trim)
+            .filter(|device| !device.is_empty())
+            .collect();
+        match config.encoder.trim().to_lowercase().as_str() {
+            "" | "none" => {}
+            "evdev" if devices.is_empty() => {
+                return Err("No [input] evdev_devices configured".to_string());
+            }
+            "evdev" => {}
+            "gpio" => {
+                let pins = parse_pins(&config.encoder_pins)?;
+                let (a, b, button) = match pins[..] {
+                    [a, b] => (a, b, None),
+                    [a, b, button] => (a, b, Some(button)),
+                    _ => return Err("Encoder pins must be \"a,b\" or \"a,b,button\"".to_string()),
+                };
+                let a = InputPin::open(a)?;
+                let b = InputPin::open(b)?;
+                let button = button.map(Button::open).transpose()?;
+                let sender = sender.clone();
+                thread::spawn(move || poll_encoder(a, b, button, sender));
+                started = true;
+            }
+            other => return Err(format!("Unknown encoder '{}'", other)),
+        }
+
+        // Devices carry the evdev encoder and any bound keys
+        let keys: Vec<(u16, ButtonAction)> = parse_bindings(&config.keys)
+            .into_iter()
+            .filter_map(|(code, action)| Some((code.parse().ok()?, action)))
+            .collect();
+        for device in devices {
+            let file =
+                File::open(device).map_err(|e| format!("Failed to open {}: {}", device, e))?;
+            let sender = sender.clone();
+            let keys = keys.clone();
+            thread::spawn(move || read_evdev(file, keys, sender));
+            started = true;
+        }
+
+        let buttons = parse_bindings(&config.buttons)
+            .into_iter()
+            .map(|(pin, action)| {
+                let pin = pin
+                    .parse()
+                    .map_err(|_| format!("Invalid GPIO pin '{}'", pin))?;
+                Ok((Button::open(pin)?, action))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        if !buttons.is_empty() {
+            thread::spawn(move || poll_buttons(buttons, sender));
+            started = true;
+        }
+
+        Ok(started.then_some(Self { receiver }))
+    }
+
+    /// Next pending event, if any
+    pub fn try_recv(&self) -> Option<InputEvent> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+/// Forward relative motion and key presses from one input device; keys
+/// bound in `keys` become actions, any other key is the encoder button
+fn read_evdev(mut file: File, keys: Vec<(u16, ButtonAction)>, sender: Sender<InputEvent>) {
+    let mut raw = [0u8; std::mem::size_of::<libc::input_event>()];
+    // Absolute encoders (rotary-encoder without linux,relative-axis)
+    // report a position, so turns are the difference from the last one
+    let mut last_abs: Option<i32> = None;
+    while file.read_exact(&mut raw).is_ok() {
+        // SAFETY: input_event is plain old data and `raw` is exactly its size
+        let event: libc::input_event = unsafe { std::ptr::read_unaligned(raw.as_ptr().cast()) };
+        let input = match event.type_ {
+            EV_REL if event.value != 0 => InputEvent::Turn(event.value),
+            EV_ABS => {
+                let previous = last_abs.replace(event.value);
+                match previous.map(|previous| event.value - previous) {
+                    Some(delta) if delta != 0 => InputEvent::Turn(delta),
+                    _ => continue,
+                }
+            }
+            EV_KEY if event.value == KEY_PRESSED => keys
+                .iter()
+                .find(|(code, _)| *code == event.code)
+                .map_or(InputEvent::Press, |(_, action)| InputEvent::Action(*action)),
+            _ => continue,
+        };
+        if sender.send(input).is_err() {
+            return;
+        }
+    }
+}
+
+/// A debounced push button on a GPIO, active low
+struct Button {
+    pin: InputPin,
+    pressed: bool,
+    changed: Instant,
+}
+
+impl Button {
+    fn open(pin: u32) -> Result<Self, String> {
+        Ok(Self {
+            pin: InputPin::open(pin)?,
+            pressed: false,
+            changed: Instant::now(),
+        })
+    }
+
+    /// Sample the pin, true when it has just been pressed
+    fn poll(&mut self) -> bool {
+        let Ok(high) = self.pin.is_high() else {
+            return false;
+        };
+        let now = Instant::now();
+        if high != self.pressed || now.duration_since(self.changed) < BUTTON_DEBOUNCE {
+            return false;
+        }
+        self.pressed = !high;
+        self.changed = now;
+        self.pressed
+    }
+}
+
+/// Decode a quadrature encoder (and its button) by sampling the pins. The
+/// pins need pull-ups, e.g. from the board or a `gpio=...=pu` line in
+/// config.txt.
+fn poll_encoder(
+    mut a: InputPin,
+    mut b: InputPin,
+    mut button: Option<Button>,
+    sender: Sender<InputEvent>,
+) {
+    let read_state = |a: &mut InputPin, b: &mut InputPin| -> Option<u8> {
+        Some(((a.is_high().ok()? as u8) << 1) | b.is_high().ok()? as u8)
+    };
+    let mut state = read_state(&mut a, &mut b).unwrap_or(0);
+    let mut transitions = 0;
+
+    loop {
+        if let Some(next) = read_state(&mut a, &mut b) {
+            transitions += quadrature_step(state, next);
+            state = next;
+            // Report whole detents once the encoder settles on one
+            if state == 0b11 && transitions.abs() >= TRANSITIONS_PER_DETENT {
+                let detents = transitions / TRANSITIONS_PER_DETENT;
+                transitions = 0;
+                if sender.send(InputEvent::Turn(detents)).is_err() {
+                    return;
+                }
+            } else if state == 0b11 {
+                transitions = 0;
+            }
+        }
+
+        if button.as_mut().is_some_and(Button::poll) && sender.send(InputEvent::Press).is_err() {
+            return;
+        }
+
+        thread::sleep(GPIO_POLL_INTERVAL);
+    }
+}
+
+/// Sample the bound GPIO buttons
+fn poll_buttons(mut buttons: Vec<(Button, ButtonAction)>, sender: Sender<InputEvent>) {
+    loop {
+        for (button, action) in buttons.iter_mut() {
+            if button.poll() && sender.send(InputEvent::Action(*action)).is_err() {
+                return;
+            }
+        }
+        thread::sleep(GPIO_POLL_INTERVAL);
+    }
+}
+
+/// Direction of one quadrature transition between AB states (Gray code):
+/// +1 clockwise, -1 counter-clockwise, 0 for no change or a missed step
+fn quadrature_step(from: u8, to: u8) -> i32 {
+    match (from, to) {
+        (0b00, 0b01) | (0b01, 0b11) | (0b11, 0b10) | (0b10, 0b00) => 1,
+        (0b00, 0b10) | (0b10, 0b11) | (0b11, 0b01) | (0b01, 0b00) => -1,
+        _ => 0,
+    }
+}
@@ -0,0 +1,175 @@
+//! Logbook, alert-rule and spot-sharing services the front-ends share
+//!
+//! The GUI, TUI and headless mode all start the same set from the
+//! settings: alert rules, the ADIF watcher, Cloudlog, Club Log, N1MM+,
+//! WSJT-X, the telnet cluster server, APRS-IS, InfluxDB, the activity dump
+//! and (with `email`) the digest. Each front-end keeps its own
+//! `SpotAlerts` and hands it in; hardware and the callbook stay with the
+//! front-ends that use them.
+
+use crate::config::{AlertConfig, Config};
+use crate::models::{AggregatedSpot, Announcement, RawSpot};
+use crate::services::radio::RadioMode;
+#[cfg(feature = "email")]
+use crate::services::Digest;
+use crate::services::{
+    ActivityDump, AdifWatcher, Alert, AlertEngine, AprsPublisher, Cloudlog, ClubLog,
+    InfluxExporter, N1mmBroadcaster, RuleMatch, SpotAlerts, SpotStore, TelnetServer,
+    WsjtxHighlighter,
+};
+
+/// The configured integrations; each one is `None` when disabled
+pub struct Integrations {
+    /// `[[alerts.rule]]` checks, when any are set
+    alert_engine: Option<AlertEngine>,
+    /// QSOs from `[alerts] adif_file`, when set
+    adif: Option<AdifWatcher>,
+    /// Cloudlog/Wavelog lookups and QSY reports, when enabled
+    cloudlog: Option<Cloudlog>,
+    /// Club Log most-wanted ranking and log, when enabled
+    clublog: Option<ClubLog>,
+    /// N1MM+ spot packets for contest loggers, when enabled
+    n1mm: Option<N1mmBroadcaster>,
+    /// WSJT-X call highlighting, when enabled
+    wsjtx: Option<WsjtxHighlighter>,
+    /// Cluster server passing spots on to other programs, when enabled
+    telnet: Option<TelnetServer>,
+    /// Alerting spots on APRS-IS, when enabled
+    aprs: Option<AprsPublisher>,
+    /// Spot counts, SNRs and solar indices for InfluxDB, when enabled
+    influx: Option<InfluxExporter>,
+    /// Scheduled spot and session files, when enabled; runs on its own
+    _dump: Option<ActivityDump>,
+    /// Email digest of rare spots, when enabled
+    #[cfg(feature = "email")]
+    digest: Option<Digest>,
+}
+
+impl Integrations {
+    /// Start everything the settings enable. Services that fail to start
+    /// are left off and their errors returned for the caller to report.
+    pub fn start(
+        config: &Config,
+        spot_store: &SpotStore,
+        alerts: &SpotAlerts,
+    ) -> (Self, Vec<String>) {
+        let mut errors = Vec::new();
+        let telnet = if config.telnet.enabled {
+            TelnetServer::start(&config.telnet)
+                .map_err(|e| errors.push(e))
+                .ok()
+        } else {
+            None
+        };
+        let integrations = Self {
+            alert_engine: AlertEngine::start(config, spot_store, alerts.dxcc().clone()),
+            adif: AdifWatcher::start(&config.alerts),
+            cloudlog: Cloudlog::start(&config.cloudlog, spot_store),
+            clublog: ClubLog::start(&config.clublog, &config.callsign),
+            n1mm: N1mmBroadcaster::start(&config.n1mm),
+            wsjtx: WsjtxHighlighter::start(&config.wsjtx, spot_store),
+            telnet,
+            aprs: AprsPublisher::start(&config.aprs, &config.callsign),
+            influx: InfluxExporter::start(&config.influx),
+            _dump: ActivityDump::start(&config.dump, config.units.timezone, spot_store),
+            #[cfg(feature = "email")]
+            digest: Digest::start(&config.digest, config.units, spot_store),
+        };
+        (integrations, errors)
+    }
+
+    /// Count QSOs logged or found in the logbooks since the last poll as
+    /// worked, and pass on new Club Log most-wanted ranks
+    pub fn poll(&mut self, alerts: &mut SpotAlerts) {
+        let mut qsos = Vec::new();
+        if let Some(ref adif) = self.adif {
+            qsos.extend(std::iter::from_fn(|| adif.try_recv()).flatten());
+        }
+        if let Some(ref cloudlog) = self.cloudlog {
+            qsos.extend(std::iter::from_fn(|| cloudlog.try_recv()));
+        }
+        if let Some(ref clublog) = self.clublog {
+            qsos.extend(std::iter::from_fn(|| clublog.try_recv()).flatten());
+            while let Some(ranks) = clublog.try_recv_ranks() {
+                if let Some(ref engine) = self.alert_engine {
+                    engine.set_most_wanted(ranks.clone());
+                }
+                alerts.set_most_wanted(ranks);
+            }
+        }
+        if qsos.is_empty() {
+            return;
+        }
+        alerts.add_logged(&qsos);
+        if let Some(ref engine) = self.alert_engine {
+            engine.add_logged(qsos);
+        }
+    }
+
+    /// Check a new spot against the alerts, exporting it and passing any
+    /// alert it raises on to APRS-IS and the digest
+    pub fn spot(&self, raw: &RawSpot, alerts: &mut SpotAlerts) -> Option<Alert> {
+        if let Some(ref influx) = self.influx {
+            influx.spot(raw, alerts);
+        }
+        let alert = alerts.check(raw)?;
+        if let Some(ref aprs) = self.aprs {
+            aprs.record(&alert, raw, alerts.location(&raw.spotted_callsign));
+        }
+        #[cfg(feature = "email")]
+        if let Some(ref digest) = self.digest {
+            digest.record(&alert, raw);
+        }
+        Some(alert)
+    }
+
+    /// Export the solar indices of an RBN announcement
+    pub fn announcement(&self, announcement: &Announcement) {
+        if let (Some(influx), Some(report)) = (&self.influx, announcement.propagation()) {
+            influx.propagation(report);
+        }
+    }
+
+    /// Report a frequency the radio was tuned to
+    pub fn tuned(&self, frequency_khz: f64, mode: RadioMode) {
+        if let Some(ref cloudlog) = self.cloudlog {
+            cloudlog.qsy(frequency_khz, mode.to_rigctld_mode());
+        }
+    }
+
+    /// Next alert rule that fired, if any
+    pub fn try_recv_match(&self) -> Option<RuleMatch> {
+        self.alert_engine.as_ref()?.try_recv()
+    }
+
+    /// Hand a new profile's watch lists to the alert rules
+    pub fn set_lists(&self, config: &AlertConfig) {
+        if let Some(ref engine) = self.alert_engine {
+            engine.set_lists(config);
+        }
+    }
+
+    /// Whether `update` has anything to pass the filtered spots to
+    pub fn wants_spots(&self) -> bool {
+        self.n1mm.is_some() || self.telnet.is_some()
+    }
+
+    /// Pass the filtered spots on to N1MM+ and telnet clients, and the
+    /// alerts to WSJT-X
+    pub fn update(&mut self, spots: &[AggregatedSpot], alerts: &SpotAlerts) {
+        if let Some(ref mut n1mm) = self.n1mm {
+            n1mm.update(spots);
+        }
+        if let Some(ref mut telnet) = self.telnet {
+            telnet.update(spots);
+        }
+        if let Some(ref mut wsjtx) = self.wsjtx {
+            wsjtx.update(alerts);
+        }
+    }
+
+    /// The telnet cluster server, when running
+    pub fn telnet(&self) -> Option<&TelnetServer> {
+        self.telnet.as_ref()
+    }
+}
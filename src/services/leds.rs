@@ -0,0 +1,157 @@
+//! Indicator LEDs on GPIOs: steady while connected to RBN, blinking after
+//! a watch-list hit and blinking fast after a new-DXCC spot (or after an
+//! alert rule naming either LED)
+
+use crate::config::LedConfig;
+use crate::services::alerts::Alert;
+use crate::services::gpio::OutputPin;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often the blink patterns are re-evaluated
+const TICK: Duration = Duration::from_millis(25);
+
+/// Half periods of the blink patterns
+const BLINK: Duration = Duration::from_millis(500);
+const FAST_BLINK: Duration = Duration::from_millis(125);
+
+/// LED blinking for an alert
+enum Flash {
+    Watch,
+    Dxcc,
+}
+
+enum LedCommand {
+    Connected(bool),
+    Flash(Flash),
+}
+
+/// Drives the configured LEDs from a background thread
+pub struct LedIndicators {
+    sender: Sender<LedCommand>,
+    connected: Option<bool>,
+}
+
+impl LedIndicators {
+    /// Open the configured pins, or None when no LED is configured
+    pub fn start(config: &LedConfig) -> Result<Option<Self>, String> {
+        let open = |pin: Option<u32>| pin.map(Led::open).transpose();
+        let leds = Leds {
+            connected: open(config.connected_pin)?,
+            watch: open(config.watch_pin)?,
+            dxcc: open(config.dxcc_pin)?,
+        };
+        if leds.connected.is_none() && leds.watch.is_none() && leds.dxcc.is_none() {
+            return Ok(None);
+        }
+
+        let (sender, receiver) = mpsc::channel();
+        let alert_time = Duration::from_secs(config.alert_seconds as u64);
+        thread::spawn(move || {
+            let mut leds = leds;
+            let mut connected = false;
+            let mut watch_until: Option<Instant> = None;
+            let mut dxcc_until: Option<Instant> = None;
+            let started = Instant::now();
+            loop {
+                match receiver.recv_timeout(TICK) {
+                    Ok(LedCommand::Connected(state)) => connected = state,
+                    Ok(LedCommand::Flash(Flash::Watch)) => {
+                        watch_until = Some(Instant::now() + alert_time);
+                    }
+                    Ok(LedCommand::Flash(Flash::Dxcc)) => {
+                        dxcc_until = Some(Instant::now() + alert_time);
+                    }
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+                let now = Instant::now();
+                let blinking = |until: Option<Instant>, half_period: Duration| {
+                    until.is_some_and(|until| now < until)
+                        && (now.duration_since(started).as_millis() / half_period.as_millis())
+                            .is_multiple_of(2)
+                };
+                leds.show(
+                    connected,
+                    blinking(watch_until, BLINK),
+                    blinking(dxcc_until, FAST_BLINK),
+                );
+            }
+            leds.show(false, false, false);
+        });
+
+        Ok(Some(Self {
+            sender,
+            connected: None,
+        }))
+    }
+
+    /// Light the connected LED while the RBN session is up
+    pub fn set_connected(&mut self, connected: bool) {
+        if self.connected != Some(connected) {
+            self.connected = Some(connected);
+            let _ = self.sender.send(LedCommand::Connected(connected));
+        }
+    }
+
+    /// Start blinking the LED for an alert
+    pub fn alert(&self, alert: &Alert) {
+        let flash = match alert {
+            Alert::Watch { .. } => Flash::Watch,
+            Alert::NewDxcc { .. } => Flash::Dxcc,
+            _ => return,
+        };
+        let _ = self.sender.send(LedCommand::Flash(flash));
+    }
+
+    /// Start blinking an LED by name, "watch" or "dxcc", for an alert rule
+    pub fn flash(&self, led: &str) {
+        let flash = match led {
+            "watch" => Flash::Watch,
+            "dxcc" => Flash::Dxcc,
+            _ => return,
+        };
+        let _ = self.sender.send(LedCommand::Flash(flash));
+    }
+}
+
+/// An LED pin and the level last written to it
+struct Led {
+    pin: OutputPin,
+    lit: bool,
+}
+
+impl Led {
+    fn open(pin: u32) -> Result<Self, String> {
+        let mut pin = OutputPin::open(pin)?;
+        let _ = pin.set(false);
+        Ok(Self { pin, lit: false })
+    }
+
+    fn set(&mut self, lit: bool) {
+        if lit != self.lit && self.pin.set(lit).is_ok() {
+            self.lit = lit;
+        }
+    }
+}
+
+struct Leds {
+    connected: Option<Led>,
+    watch: Option<Led>,
+    dxcc: Option<Led>,
+}
+
+impl Leds {
+    fn show(&mut self, connected: bool, watch: bool, dxcc: bool) {
+        for (led, lit) in [
+            (&mut self.connected, connected),
+            (&mut self.watch, watch),
+            (&mut self.dxcc, dxcc),
+        ] {
+            if let Some(led) = led {
+                led.set(lit);
+            }
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Selected spots looked up in a logging program (`[logger]`)
+//!
+//! Picking a spot with the encoder or in the spot list puts its call in
+//! the logger's entry window, so the program looks it up and shows its
+//! worked-before state while you tune. Log4OM takes an XML remote control
+//! request over UDP; DXKeeper, like the other DXLab applications, takes a
+//! directive over TCP. Only a selection left alone for `SETTLE` is sent,
+//! so turning the encoder past a dozen spots doesn't queue a dozen
+//! lookups.
+
+use crate::config::LoggerConfig;
+use crate::models::AggregatedSpot;
+use std::io::Write;
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a selection must stay before it's sent
+const SETTLE: Duration = Duration::from_millis(500);
+/// How long DXKeeper gets to accept the connection
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+/// Log4OM's default remote control port
+const LOG4OM_ADDRESS: &str = "127.0.0.1:2241";
+/// DXKeeper's port with DXLab's default base port (52000)
+const DXKEEPER_ADDRESS: &str = "127.0.0.1:52001";
+
+/// Logging programs spots can be sent to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Program {
+    Log4om,
+    Dxkeeper,
+}
+
+/// Sends selected spots to the logging program
+pub struct LoggerLink {
+    program: Program,
+    target: SocketAddr,
+    socket: UdpSocket,
+    /// Spot selected and when, until it's sent
+    pending: Option<(AggregatedSpot, Instant)>,
+    /// Callsign last sent, so reselecting it doesn't send it again
+    last_sent: String,
+}
+
+impl LoggerLink {
+    /// Resolve the program's address, or None when not enabled or it
+    /// doesn't resolve
+    pub fn start(config: &LoggerConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let (program, default_address) = match config.program.trim().to_lowercase().as_str() {
+            "dxkeeper" => (Program::Dxkeeper, DXKEEPER_ADDRESS),
+            "log4om" => (Program::Log4om, LOG4OM_ADDRESS),
+            other => {
+                log::warn!("Unknown logging program '{}'", other);
+                return None;
+            }
+        };
+        let address = match config.address.trim() {
+            "" => default_address,
+            address => address,
+        };
+        let Some(target) = address
+            .to_socket_addrs()
+            .ok()
+            .and_then(|mut addrs| addrs.next())
+        else {
+            log::warn!("Can't resolve logger address '{}'", address);
+            return None;
+        };
+        let socket = match UdpSocket::bind(("0.0.0.0", 0)) {
+            Ok(socket) => socket,
+            Err(e) => {
+                log::warn!("Failed to open a UDP socket for the logger: {}", e);
+                return None;
+            }
+        };
+        log::info!("Sending selected spots to {:?} at {}", program, target);
+        Some(Self {
+            program,
+            target,
+            socket,
+            pending: None,
+            last_sent: String::new(),
+        })
+    }
+
+    /// Note a spot picked on the device or in the list; it's sent once it
+    /// has stayed selected for a moment
+    pub fn select(&mut self, spot: &AggregatedSpot) {
+        self.pending = Some((spot.clone(), Instant::now()));
+    }
+
+    /// Send the selected spot once it has settled
+    pub fn update(&mut self) {
+        let Some((spot, _)) = self
+            .pending
+            .take_if(|(_, selected)| selected.elapsed() >= SETTLE)
+        else {
+            return;
+        };
+        let call = spot.callsign.to_uppercase();
+        if call == self.last_sent {
+            return;
+        }
+        log::debug!("Looking up {} in {:?}", call, self.program);
+        self.last_sent = call.clone();
+        match self.program {
+            Program::Log4om => {
+                if let Err(e) = self
+                    .socket
+                    .send_to(log4om_request(&call).as_bytes(), self.target)
+                {
+                    log::warn!("Failed to send {} to Log4OM: {}", call, e);
+                }
+            }
+            Program::Dxkeeper => {
+                let directive = dxkeeper_directive(&call, &spot);
+                let target = self.target;
+                // Connecting may take a moment when DXKeeper isn't running
+                thread::spawn(move || {
+                    let result = TcpStream::connect_timeout(&target, CONNECT_TIMEOUT)
+                        .and_then(|mut stream| stream.write_all(directive.as_bytes()));
+                    if let Err(e) = result {
+                        log::warn!("Failed to send {} to DXKeeper at {}: {}", call, target, e);
+                    }
+                });
+            }
+        }
+    }
+}
+
+/// Log4OM remote control request putting a call in the QSO entry
+fn log4om_request(call: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\
+         <RemoteControlRequest>\
+         <MessageId>{:032x}</MessageId>\
+         <RemoteControlMessage>SetCallsign</RemoteControlMessage>\
+         <Callsign>{}</Callsign>\
+         </RemoteControlRequest>",
+        rand::random::<u128>(),
+        call
+    )
+}
+
+/// DXLab directive filling DXKeeper's Capture window: each field is
+/// `<name:length>value`, the parameters wrapped the same way
+/// Example: "<command:11>CaptureCall<parameters:48><callsign:6>DL1ABC<frequency:7>14.0253<mode:2>CW"
+fn dxkeeper_directive(call: &str, spot: &AggregatedSpot) -> String {
+    let field = |name: &str, value: &str| format!("<{}:{}>{}", name, value.len(), value);
+    let parameters = [
+        field("callsign", call),
+        field("frequency", &format!("{:.4}", spot.frequency_khz / 1000.0)),
+        field("mode", &spot.mode.to_uppercase()),
+    ]
+    .concat();
+    [
+        field("command", "CaptureCall"),
+        field("parameters", &parameters),
+    ]
+    .concat()
+}
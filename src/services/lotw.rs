@@ -0,0 +1,139 @@
+//! LoTW users list (`[lotw]`)
+//!
+//! ARRL publishes each LoTW user's last upload date as a CSV. A background
+//! thread loads the copy cached in the cache directory, downloads a fresh
+//! one when it is older than `refresh_hours`, and keeps doing so while the
+//! program runs; lookups read whatever was loaded last.
+
+use crate::config::LotwConfig;
+use crate::paths;
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Users list published by ARRL: "CALL,YYYY-MM-DD,HH:MM:SS" per line
+const USERS_URL: &str = "https://lotw.arrl.org/lotw-user-activity.csv";
+
+/// How long the download may take
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Wait before trying a failed download again
+const RETRY_AFTER: Duration = Duration::from_secs(60 * 60);
+
+/// Last LoTW upload per callsign, kept current on a background thread
+#[derive(Clone, Default)]
+pub struct LotwUsers {
+    uploads: Arc<RwLock<HashMap<String, NaiveDate>>>,
+}
+
+impl LotwUsers {
+    /// Load the cached list and keep it fresh, or None when not enabled
+    pub fn start(config: &LotwConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let Some(path) = paths::lotw_file() else {
+            log::warn!("No cache directory for the LoTW users list");
+            return None;
+        };
+        let users = Self::default();
+        let refresh = Duration::from_secs(u64::from(config.refresh_hours) * 60 * 60);
+        let shared = users.clone();
+        thread::spawn(move || {
+            match fs::read_to_string(&path) {
+                Ok(text) => shared.replace(parse(&text)),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => log::warn!("Failed to read {}: {}", path.display(), e),
+            }
+            // 0 keeps to the cached copy
+            if refresh.is_zero() {
+                return;
+            }
+            loop {
+                let age = fs::metadata(&path)
+                    .and_then(|meta| meta.modified())
+                    .ok()
+                    .and_then(|modified| SystemTime::now().duration_since(modified).ok());
+                match age {
+                    Some(age) if age < refresh => thread::sleep(refresh - age),
+                    _ => match download(&path) {
+                        Ok(uploads) => shared.replace(uploads),
+                        Err(e) => {
+                            log::warn!("LoTW users list download failed: {}", e);
+                            thread::sleep(RETRY_AFTER);
+                        }
+                    },
+                }
+            }
+        });
+        Some(users)
+    }
+
+    /// Date of the callsign's last upload, if it is a LoTW user. Portable
+    /// calls ("W6JSV/P") are found under the plain call.
+    pub fn last_upload(&self, callsign: &str) -> Option<NaiveDate> {
+        let uploads = self.uploads.read().ok()?;
+        let callsign = callsign.trim().to_uppercase();
+        uploads.get(&callsign).copied().or_else(|| {
+            callsign
+                .split('/')
+                .max_by_key(|part| part.len())
+                .and_then(|base| uploads.get(base).copied())
+        })
+    }
+
+    /// Take a newly read list
+    fn replace(&self, uploads: HashMap<String, NaiveDate>) {
+        log::info!("LoTW users list: {} callsigns", uploads.len());
+        if let Ok(mut current) = self.uploads.write() {
+            *current = uploads;
+        }
+    }
+}
+
+/// Fetch the list and cache it at `path`, replacing the old copy only once
+/// the new one is complete
+fn download(path: &Path) -> Result<HashMap<String, NaiveDate>, String> {
+    log::info!("Downloading the LoTW users list");
+    let response = ureq::AgentBuilder::new()
+        .timeout(DOWNLOAD_TIMEOUT)
+        .build()
+        .get(USERS_URL)
+        .call()
+        .map_err(|e| e.to_string())?;
+    // Larger than `into_string` allows
+    let mut text = String::new();
+    response
+        .into_reader()
+        .read_to_string(&mut text)
+        .map_err(|e| e.to_string())?;
+    let uploads = parse(&text);
+    if uploads.is_empty() {
+        return Err("no callsigns in the answer".to_string());
+    }
+    let partial = path.with_extension("csv.part");
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    }
+    fs::write(&partial, &text)
+        .and_then(|()| fs::rename(&partial, path))
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(uploads)
+}
+
+/// Callsigns and last upload dates from the CSV; malformed lines are skipped
+fn parse(text: &str) -> HashMap<String, NaiveDate> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split(',');
+            let call = fields.next()?.trim().to_uppercase();
+            let date = NaiveDate::parse_from_str(fields.next()?.trim(), "%Y-%m-%d").ok()?;
+            (!call.is_empty()).then_some((call, date))
+        })
+        .collect()
+}
@@ -1,8 +1,81 @@
+mod adif;
+mod alert_rules;
+mod alerts;
+mod aprs;
+mod brightness;
+mod callbook;
+mod cloudlog;
+mod clublog;
+pub mod control;
+#[cfg(feature = "email")]
+mod digest;
+pub mod display;
+mod dump;
+#[cfg(target_os = "linux")]
+mod gpio;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(target_os = "linux")]
+mod i2c;
+mod influx;
+pub mod input;
+mod integrations;
+#[cfg(target_os = "linux")]
+mod leds;
+mod logger_link;
+mod lotw;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+mod n1mm;
+mod port_discovery;
 pub mod radio;
 mod rbn_client;
+mod schedule;
+mod self_spot;
+#[cfg(target_os = "linux")]
+mod sound;
+#[cfg(all(
+    target_os = "linux",
+    any(feature = "oled", feature = "epaper", feature = "tft")
+))]
+mod spi;
+mod spot_detail;
 mod spot_store;
+mod telnet;
+mod template;
 mod vfd_display;
+#[cfg(feature = "web")]
+pub mod web;
+mod wsjtx;
 
-pub use rbn_client::{RbnClient, RbnMessage};
+pub use adif::{AdifWatcher, Qso};
+pub use alert_rules::{AlertEngine, RuleMatch};
+pub use alerts::{Alert, SpotAlerts};
+pub use aprs::AprsPublisher;
+pub use callbook::{Callbook, Operator};
+pub use cloudlog::Cloudlog;
+pub use clublog::ClubLog;
+pub use control::{AppCommand, CommandSource, Control};
+#[cfg(feature = "email")]
+pub use digest::Digest;
+pub use dump::ActivityDump;
+pub use influx::InfluxExporter;
+pub use integrations::Integrations;
+#[cfg(target_os = "linux")]
+pub use leds::LedIndicators;
+pub use logger_link::LoggerLink;
+pub use lotw::LotwUsers;
+pub use n1mm::N1mmBroadcaster;
+pub use port_discovery::{
+    display_port, find_usb_port, list_ports, HotplugEvent, HotplugWatcher, UsbId,
+};
+pub use rbn_client::{server_address, RbnClient, RbnMessage};
+pub use schedule::{Cron, DailyRange};
+pub use self_spot::SelfSpotter;
+#[cfg(target_os = "linux")]
+pub use sound::AlertSound;
+pub use spot_detail::detail_lines;
 pub use spot_store::SpotStore;
-pub use vfd_display::VfdDisplay;
+pub use telnet::TelnetServer;
+pub use vfd_display::{DisplayStatus, VfdDisplay};
+pub use wsjtx::WsjtxHighlighter;
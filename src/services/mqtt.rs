@@ -0,0 +1,473 @@
+//! MQTT publishing of spots, display frames and state
+//!
+//! Each new or updated spot is published as JSON to `<prefix>/spots`, and
+//! the lines currently on the display go to `<prefix>/display` (retained)
+//! through a display output, so ESP32 displays or Node-RED dashboards can
+//! mirror the VFD. The radio's frequency and mode (`<prefix>/radio`) and
+//! the app's feed connection, profile and filters (`<prefix>/app`) are
+//! retained as they change, spots per minute and per band go to
+//! `<prefix>/activity` every 30 seconds, and `<prefix>/status` says
+//! "online" while connected, with "offline" left as the last will for the
+//! broker to publish when the connection drops. Text sent to
+//! `<prefix>/notify` comes back as an announcement. Every topic can be set
+//! on its own, and with `discovery` Home Assistant is told about them as a
+//! device with sensors and a notify target.
+
+use crate::config::{Config, MqttConfig};
+use crate::models::{Announcement, AnnouncementKind, Band, SpotRecord};
+use crate::services::display::{DisplayDriver, DisplayResult};
+use crate::services::radio::RadioState;
+use crate::services::SpotStore;
+use rumqttc::{AsyncClient, Event, LastWill, MqttOptions, Packet, QoS, Transport};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc};
+
+/// How often spots per minute and per band are published
+const ACTIVITY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Handle to the MQTT publisher thread
+pub struct MqttPublisher {
+    tx: mpsc::Sender<Outgoing>,
+    /// Messages from the notify topic
+    notify_rx: mpsc::Receiver<String>,
+    /// App and radio state last sent, so only changes are published
+    last_app: String,
+    last_radio: String,
+}
+
+/// Messages for the publisher thread
+enum Outgoing {
+    Frame(Vec<String>),
+    App(String),
+    Radio(String),
+}
+
+/// Where and how each kind of message is published
+#[derive(Clone)]
+struct Topics {
+    spots: String,
+    display: String,
+    radio: String,
+    app: String,
+    activity: String,
+    notify: String,
+    status: String,
+    spot_qos: QoS,
+    state_qos: QoS,
+}
+
+impl Topics {
+    fn new(config: &MqttConfig) -> Self {
+        let prefix = config.topic_prefix.trim_end_matches('/');
+        let topic = |set: &str, name: &str| match set.trim() {
+            "" => format!("{}/{}", prefix, name),
+            set => set.to_string(),
+        };
+        Self {
+            spots: topic(&config.spots_topic, "spots"),
+            display: topic(&config.display_topic, "display"),
+            radio: topic(&config.radio_topic, "radio"),
+            app: topic(&config.app_topic, "app"),
+            activity: topic(&config.activity_topic, "activity"),
+            notify: topic(&config.notify_topic, "notify"),
+            status: topic(&config.status_topic, "status"),
+            spot_qos: qos(config.spot_qos),
+            state_qos: qos(config.state_qos),
+        }
+    }
+}
+
+/// QoS level from the settings; anything above 2 is treated as 2
+fn qos(level: u8) -> QoS {
+    match level {
+        0 => QoS::AtMostOnce,
+        1 => QoS::AtLeastOnce,
+        _ => QoS::ExactlyOnce,
+    }
+}
+
+impl MqttPublisher {
+    /// Connect to the broker and start publishing spots from the store
+    pub fn start(config: &MqttConfig, spot_store: SpotStore) -> Self {
+        let topics = Topics::new(config);
+        let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+        options.set_keep_alive(Duration::from_secs(30));
+        if !config.username.is_empty() {
+            options.set_credentials(&config.username, config.password.expose());
+        }
+        options.set_last_will(LastWill::new(
+            &topics.status,
+            "offline",
+            topics.state_qos,
+            true,
+        ));
+        if config.tls {
+            options.set_transport(transport(&config.ca_file));
+        }
+        let discovery = if config.discovery {
+            discovery(config, &topics)
+        } else {
+            Vec::new()
+        };
+        let (tx, rx) = mpsc::channel(16);
+        let (notify_tx, notify_rx) = mpsc::channel(16);
+        let link = Link {
+            topics,
+            discovery,
+            notify_tx,
+        };
+
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Failed to create tokio runtime");
+            rt.block_on(mqtt_task(options, link, spot_store, rx));
+        });
+
+        Self {
+            tx,
+            notify_rx,
+            last_app: String::new(),
+            last_radio: String::new(),
+        }
+    }
+
+    /// A display driver that publishes each frame it is sent
+    pub fn display_output(&self, columns: usize, rows: usize) -> Box<dyn DisplayDriver> {
+        Box::new(MqttDisplay {
+            tx: self.tx.clone(),
+            columns,
+            lines: vec![String::new(); rows],
+        })
+    }
+
+    /// Publish the app and radio state when either has changed since the
+    /// last call
+    pub fn publish_state(&mut self, config: &Config, connected: bool, radio: &RadioState) {
+        let app = serde_json::json!({
+            "connected": connected,
+            "profile": config.profile_label(),
+            "preset": config.preset_label(),
+            "min_snr": config.min_snr,
+            "max_age_minutes": config.max_age_minutes,
+            "band": config.band_filter.map_or("all", |band| band.name()),
+            "cw_only": config.cw_only,
+            "sort": config.sort.name(),
+        })
+        .to_string();
+        if app != self.last_app && self.tx.try_send(Outgoing::App(app.clone())).is_ok() {
+            self.last_app = app;
+        }
+
+        let radio = serde_json::json!({
+            "name": config.radio.name,
+            "backend": radio.backend,
+            "connected": radio.connected,
+            "frequency_khz": radio.frequency_khz,
+            "mode": radio.mode.map(|mode| mode.to_rigctld_mode()),
+        })
+        .to_string();
+        if radio != self.last_radio && self.tx.try_send(Outgoing::Radio(radio.clone())).is_ok() {
+            self.last_radio = radio;
+        }
+    }
+
+    /// Next message sent to the notify topic, as an announcement from
+    /// "MQTT" (non-blocking)
+    pub fn try_recv(&mut self) -> Option<Announcement> {
+        loop {
+            let text = self.notify_rx.try_recv().ok()?;
+            let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            if !text.is_empty() {
+                return Some(Announcement {
+                    kind: AnnouncementKind::Announce,
+                    from: "MQTT".to_string(),
+                    to: "ALL".to_string(),
+                    text,
+                    received: chrono::Utc::now(),
+                });
+            }
+        }
+    }
+}
+
+/// Home Assistant discovery messages (topic and config) for the spot rate,
+/// spots, busiest band and feed connection sensors and the notify target
+fn discovery(config: &MqttConfig, topics: &Topics) -> Vec<(String, String)> {
+    // Discovery topics and unique IDs only take these characters
+    let node: String = config
+        .client_id
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' => c,
+            _ => '_',
+        })
+        .collect();
+    let prefix = config.discovery_prefix.trim().trim_end_matches('/');
+    let device = serde_json::json!({
+        "identifiers": [node],
+        "name": "RBN VFD Display",
+        "model": "rbn-vfd",
+        "sw_version": env!("CARGO_PKG_VERSION"),
+    });
+    let entities = [
+        (
+            "sensor",
+            "spot_rate",
+            serde_json::json!({
+                "name": "Spots per minute",
+                "state_topic": topics.activity,
+                "value_template": "{{ value_json.spots_per_minute }}",
+                "unit_of_measurement": "spots/min",
+                "state_class": "measurement",
+                "icon": "mdi:radio-tower",
+            }),
+        ),
+        (
+            "sensor",
+            "spots",
+            serde_json::json!({
+                "name": "Spots",
+                "state_topic": topics.activity,
+                "value_template": "{{ value_json.spots }}",
+                "state_class": "measurement",
+                "icon": "mdi:format-list-bulleted",
+            }),
+        ),
+        (
+            "sensor",
+            "band_activity",
+            serde_json::json!({
+                "name": "Busiest band",
+                "state_topic": topics.activity,
+                "value_template": "{{ value_json.busiest_band }}",
+                "json_attributes_topic": topics.activity,
+                "json_attributes_template": "{{ value_json.bands | tojson }}",
+                "icon": "mdi:sine-wave",
+            }),
+        ),
+        (
+            "binary_sensor",
+            "feed",
+            serde_json::json!({
+                "name": "RBN feed",
+                "state_topic": topics.app,
+                "value_template": "{{ 'ON' if value_json.connected else 'OFF' }}",
+                "device_class": "connectivity",
+            }),
+        ),
+        (
+            "notify",
+            "display",
+            serde_json::json!({
+                "name": "Display",
+                "command_topic": topics.notify,
+            }),
+        ),
+    ];
+    entities
+        .into_iter()
+        .map(|(component, object, mut entity)| {
+            entity["unique_id"] = format!("{}_{}", node, object).into();
+            entity["availability_topic"] = topics.status.clone().into();
+            entity["device"] = device.clone();
+            (
+                format!("{}/{}/{}/{}/config", prefix, component, node, object),
+                entity.to_string(),
+            )
+        })
+        .collect()
+}
+
+/// TLS with the system's roots, or only the CA in `ca_file`
+fn transport(ca_file: &str) -> Transport {
+    let ca_file = ca_file.trim();
+    if ca_file.is_empty() {
+        return Transport::tls_with_default_config();
+    }
+    match std::fs::read(ca_file) {
+        Ok(ca) => Transport::tls(ca, None, None),
+        Err(e) => {
+            log::error!(
+                "MQTT: can't read {}: {}; using the system's roots",
+                ca_file,
+                e
+            );
+            Transport::tls_with_default_config()
+        }
+    }
+}
+
+/// Display output that publishes frames to the display topic
+struct MqttDisplay {
+    tx: mpsc::Sender<Outgoing>,
+    columns: usize,
+    lines: Vec<String>,
+}
+
+impl DisplayDriver for MqttDisplay {
+    fn name(&self) -> &'static str {
+        "mqtt"
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        (self.columns, self.lines.len())
+    }
+
+    fn clear(&mut self) -> DisplayResult<()> {
+        self.lines.iter_mut().for_each(String::clear);
+        Ok(())
+    }
+
+    fn shows_char(&self, c: char) -> bool {
+        !c.is_control()
+    }
+
+    fn write_line(&mut self, row: usize, text: &str) -> DisplayResult<()> {
+        if let Some(line) = self.lines.get_mut(row) {
+            *line = text.trim_end().to_string();
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> DisplayResult<()> {
+        // Non-blocking; a full queue just drops this frame
+        let _ = self.tx.try_send(Outgoing::Frame(self.lines.clone()));
+        Ok(())
+    }
+}
+
+/// What the event loop needs to announce itself and pass on notifications
+struct Link {
+    topics: Topics,
+    /// Home Assistant discovery topics and configs, empty when it's off
+    discovery: Vec<(String, String)>,
+    notify_tx: mpsc::Sender<String>,
+}
+
+impl Link {
+    /// Say "online", (re)announce to Home Assistant and listen for
+    /// notifications; not awaited, since the event loop is the one
+    /// draining the queue
+    fn connected(&self, client: &AsyncClient) {
+        let topics = &self.topics;
+        let _ = client.try_publish(&topics.status, topics.state_qos, true, "online");
+        for (topic, config) in &self.discovery {
+            let _ = client.try_publish(topic, topics.state_qos, true, config.as_str());
+        }
+        let _ = client.try_subscribe(&topics.notify, QoS::AtLeastOnce);
+    }
+}
+
+/// Spots per minute, spots and spots per band, with the busiest band
+fn activity(heard: &VecDeque<Instant>, spot_store: &SpotStore) -> String {
+    let spots = spot_store.get_spots_by_frequency();
+    let bands: Vec<(Band, usize)> = Band::ALL
+        .iter()
+        .map(|&band| {
+            let count = spots
+                .iter()
+                .filter(|spot| Band::from_khz(spot.frequency_khz) == Some(band))
+                .count();
+            (band, count)
+        })
+        .filter(|&(_, count)| count > 0)
+        .collect();
+    let busiest = bands
+        .iter()
+        .max_by_key(|&&(_, count)| count)
+        .map_or("none", |(band, _)| band.name());
+    let counts: serde_json::Map<String, serde_json::Value> = bands
+        .iter()
+        .map(|(band, count)| (band.name().to_string(), (*count).into()))
+        .collect();
+    serde_json::json!({
+        "spots_per_minute": heard.len(),
+        "spots": spots.len(),
+        "busiest_band": busiest,
+        "bands": counts,
+    })
+    .to_string()
+}
+
+async fn mqtt_task(
+    options: MqttOptions,
+    link: Link,
+    spot_store: SpotStore,
+    mut rx: mpsc::Receiver<Outgoing>,
+) {
+    let topics = link.topics.clone();
+    let mut updates = spot_store.subscribe();
+    let (client, mut eventloop) = AsyncClient::new(options, 64);
+
+    // The event loop drives the connection and reconnects after errors,
+    // announcing "online" over the last will each time it connects
+    let link_client = client.clone();
+    tokio::spawn(async move {
+        loop {
+            match eventloop.poll().await {
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    log::info!("MQTT connected");
+                    link.connected(&link_client);
+                }
+                Ok(Event::Incoming(Packet::Publish(publish))) => {
+                    if publish.topic == link.topics.notify {
+                        let text = String::from_utf8_lossy(&publish.payload).into_owned();
+                        let _ = link.notify_tx.try_send(text);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    log::warn!("MQTT: {}", e);
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                }
+            }
+        }
+    });
+
+    // Spots heard in the last minute
+    let mut heard: VecDeque<Instant> = VecDeque::new();
+    let mut activity_tick = tokio::time::interval(ACTIVITY_INTERVAL);
+    loop {
+        tokio::select! {
+            update = updates.recv() => match update {
+                Ok(spot) => {
+                    heard.push_back(Instant::now());
+                    if let Ok(json) = serde_json::to_string(&SpotRecord::from(&spot)) {
+                        let _ = client
+                            .publish(&topics.spots, topics.spot_qos, false, json)
+                            .await;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            _ = activity_tick.tick() => {
+                while heard
+                    .front()
+                    .is_some_and(|at| at.elapsed() > Duration::from_secs(60))
+                {
+                    heard.pop_front();
+                }
+                let payload = activity(&heard, &spot_store);
+                let _ = client
+                    .publish(&topics.activity, topics.state_qos, true, payload)
+                    .await;
+            },
+            outgoing = rx.recv() => {
+                let (topic, payload) = match outgoing {
+                    Some(Outgoing::Frame(lines)) => match serde_json::to_string(&lines) {
+                        Ok(json) => (&topics.display, json),
+                        Err(_) => continue,
+                    },
+                    Some(Outgoing::App(json)) => (&topics.app, json),
+                    Some(Outgoing::Radio(json)) => (&topics.radio, json),
+                    None => break,
+                };
+                let _ = client.publish(topic, topics.state_qos, true, payload).await;
+            },
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! N1MM+ spot broadcasts (`[n1mm]`)
+//!
+//! Spots passing the filters are sent as N1MM+ `<spot>` UDP packets, so
+//! contest loggers listening for them (N1MM+, DXLog and others) fill their
+//! band maps from this feed. A spot is announced with action "add" when it
+//! first passes the filters and withdrawn with "delete" once it ages out or
+//! stops passing them.
+
+use crate::config::N1mmConfig;
+use crate::models::AggregatedSpot;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+/// Least time between passes over the spots
+const UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Sends the filtered spots to loggers on the LAN
+pub struct N1mmBroadcaster {
+    socket: UdpSocket,
+    target: SocketAddr,
+    /// Spots announced and not yet withdrawn, by `key`
+    announced: HashMap<String, AggregatedSpot>,
+    last_update: Option<Instant>,
+}
+
+impl N1mmBroadcaster {
+    /// Open a broadcast socket, or None when not enabled or the address
+    /// doesn't resolve
+    pub fn start(config: &N1mmConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let Some(target) = config
+            .address
+            .trim()
+            .to_socket_addrs()
+            .ok()
+            .and_then(|mut addrs| addrs.next())
+        else {
+            log::warn!("Can't resolve N1MM address '{}'", config.address);
+            return None;
+        };
+        let socket = match UdpSocket::bind(("0.0.0.0", 0))
+            .and_then(|socket| socket.set_broadcast(true).map(|()| socket))
+        {
+            Ok(socket) => socket,
+            Err(e) => {
+                log::warn!("Failed to open a UDP socket for N1MM spots: {}", e);
+                return None;
+            }
+        };
+        log::info!("Sending spots to N1MM at {}", target);
+        Some(Self {
+            socket,
+            target,
+            announced: HashMap::new(),
+            last_update: None,
+        })
+    }
+
+    /// Announce spots that newly pass the filters and withdraw ones that
+    /// no longer do; `spots` is everything passing them now
+    pub fn update(&mut self, spots: &[AggregatedSpot]) {
+        if self
+            .last_update
+            .is_some_and(|last| last.elapsed() < UPDATE_INTERVAL)
+        {
+            return;
+        }
+        self.last_update = Some(Instant::now());
+
+        let current: HashMap<String, &AggregatedSpot> =
+            spots.iter().map(|spot| (key(spot), spot)).collect();
+        let gone: Vec<String> = self
+            .announced
+            .keys()
+            .filter(|key| !current.contains_key(*key))
+            .cloned()
+            .collect();
+        for key in gone {
+            if let Some(spot) = self.announced.remove(&key) {
+                self.send(&spot, "delete");
+            }
+        }
+        for (key, spot) in current {
+            if !self.announced.contains_key(&key) {
+                self.send(spot, "add");
+                self.announced.insert(key, spot.clone());
+            }
+        }
+    }
+
+    fn send(&self, spot: &AggregatedSpot, action: &str) {
+        let packet = packet(spot, action);
+        if let Err(e) = self.socket.send_to(packet.as_bytes(), self.target) {
+            log::debug!("N1MM spot to {} failed: {}", self.target, e);
+        }
+    }
+}
+
+/// A spot by callsign and frequency (to 100 Hz)
+fn key(spot: &AggregatedSpot) -> String {
+    format!("{}|{:.1}", spot.callsign.to_uppercase(), spot.frequency_khz)
+}
+
+/// N1MM+ `<spot>` packet
+/// Example: `<spot><app>rbn-vfd</app>...<dxcall>DL1ABC</dxcall>
+/// <frequency>14025.3</frequency><spottercall>DK9IP</spottercall>
+/// <comment>CW 23 dB 28 WPM</comment><action>add</action>...</spot>`
+fn packet(spot: &AggregatedSpot, action: &str) -> String {
+    let spotter = spot
+        .skimmers
+        .iter()
+        .max_by_key(|(_, snr)| *snr)
+        .map_or("", |(skimmer, _)| skimmer.as_str());
+    let comment = format!(
+        "{} {} dB {:.0} WPM",
+        spot.mode, spot.highest_snr, spot.average_speed
+    );
+    format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="utf-8"?>"#,
+            "<spot>",
+            "<app>rbn-vfd</app>",
+            "<StationName>rbn-vfd</StationName>",
+            "<dxcall>{}</dxcall>",
+            "<frequency>{:.1}</frequency>",
+            "<spottercall>{}</spottercall>",
+            "<comment>{}</comment>",
+            "<action>{}</action>",
+            "<mode>{}</mode>",
+            "<status></status>",
+            "<timestamp>{}</timestamp>",
+            "</spot>"
+        ),
+        escape(&spot.callsign.to_uppercase()),
+        spot.frequency_khz,
+        escape(&spotter.to_uppercase()),
+        escape(&comment),
+        action,
+        escape(&spot.mode.to_uppercase()),
+        Utc::now().format("%Y/%m/%d %H:%M:%S"),
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
@@ -0,0 +1,139 @@
+//! USB serial port discovery and hot-plug detection
+
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serialport::SerialPortType;
+use std::fmt;
+use std::str::FromStr;
+
+/// USB vendor/product ID pair used to find an adapter regardless of its path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UsbId {
+    pub vid: u16,
+    pub pid: u16,
+}
+
+impl FromStr for UsbId {
+    type Err = String;
+
+    /// Parse "VID:PID" in hex, e.g. "0403:6001"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (vid, pid) = s
+            .trim()
+            .split_once(':')
+            .ok_or_else(|| format!("Invalid USB ID '{}', expected VID:PID", s))?;
+        let parse = |part: &str| {
+            u16::from_str_radix(part.trim().trim_start_matches("0x"), 16)
+                .map_err(|_| format!("Invalid USB ID '{}', expected hex VID:PID", s))
+        };
+        Ok(Self {
+            vid: parse(vid)?,
+            pid: parse(pid)?,
+        })
+    }
+}
+
+impl fmt::Display for UsbId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04x}:{:04x}", self.vid, self.pid)
+    }
+}
+
+/// Written as "VID:PID" in settings.toml
+impl Serialize for UsbId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for UsbId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+/// Find the first serial port whose USB VID/PID matches
+pub fn find_usb_port(id: UsbId) -> Option<String> {
+    serialport::available_ports()
+        .unwrap_or_default()
+        .into_iter()
+        .find(|port| match &port.port_type {
+            SerialPortType::UsbPort(info) => info.vid == id.vid && info.pid == id.pid,
+            _ => false,
+        })
+        .map(|port| port.port_name)
+}
+
+/// Serial ports present, with the VID/PID of USB adapters
+pub fn list_ports() -> Vec<(String, Option<UsbId>)> {
+    serialport::available_ports()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|port| {
+            let id = match &port.port_type {
+                SerialPortType::UsbPort(info) => Some(UsbId {
+                    vid: info.vid,
+                    pid: info.pid,
+                }),
+                _ => None,
+            };
+            (port.port_name, id)
+        })
+        .collect()
+}
+
+/// The configured serial port, or else wherever the configured USB adapter
+/// is plugged in (empty if neither)
+pub fn display_port(serial_port: &str, usb_id: Option<UsbId>) -> String {
+    if !serial_port.is_empty() {
+        return serial_port.to_string();
+    }
+    usb_id.and_then(find_usb_port).unwrap_or_default()
+}
+
+/// Change in presence of a watched USB adapter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HotplugEvent {
+    /// Adapter appeared at the given port path
+    Arrived(String),
+    /// Adapter previously at the given port path disappeared
+    Removed(String),
+}
+
+/// Tracks whether a USB adapter is plugged in across periodic polls
+pub struct HotplugWatcher {
+    id: UsbId,
+    present: Option<String>,
+}
+
+impl HotplugWatcher {
+    /// Create a watcher, recording the adapter's current location
+    pub fn new(id: UsbId) -> Self {
+        Self {
+            id,
+            present: find_usb_port(id),
+        }
+    }
+
+    /// Current port path of the adapter, if plugged in
+    pub fn port(&self) -> Option<&str> {
+        self.present.as_deref()
+    }
+
+    /// Re-scan ports and report arrival, removal, or a path change
+    pub fn poll(&mut self) -> Option<HotplugEvent> {
+        let current = find_usb_port(self.id);
+        if current == self.present {
+            return None;
+        }
+
+        let previous = std::mem::replace(&mut self.present, current.clone());
+        match (previous, current) {
+            (_, Some(path)) => Some(HotplugEvent::Arrived(path)),
+            (Some(path), None) => Some(HotplugEvent::Removed(path)),
+            (None, None) => None,
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Shell hooks run before and after each tune
+//!
+//! Hooks receive the target in the environment (`TUNE_PHASE`,
+//! `TUNE_FREQ_KHZ`, `TUNE_MODE`) so scripts can mute audio, key a
+//! sequencer or log the QSY.
+
+use super::RadioMode;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Longest a hook may run before it is killed (tuning waits on it)
+const HOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// When a hook runs relative to the tune
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookPhase {
+    Pre,
+    Post,
+}
+
+impl HookPhase {
+    pub fn name(self) -> &'static str {
+        match self {
+            HookPhase::Pre => "pre",
+            HookPhase::Post => "post",
+        }
+    }
+}
+
+/// Run a shell hook and wait for it to finish
+pub fn run_shell_hook(
+    command: &str,
+    phase: HookPhase,
+    frequency_khz: f64,
+    mode: RadioMode,
+) -> Result<(), String> {
+    let mut shell = if cfg!(target_os = "windows") {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+
+    let mut child = shell
+        .arg(command)
+        .env("TUNE_PHASE", phase.name())
+        .env("TUNE_FREQ_KHZ", format!("{:.1}", frequency_khz))
+        .env("TUNE_MODE", mode.to_rigctld_mode())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run {}-tune hook: {}", phase.name(), e))?;
+
+    let deadline = Instant::now() + HOOK_TIMEOUT;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return Ok(()),
+            Ok(Some(status)) => {
+                return Err(format!("{}-tune hook failed ({})", phase.name(), status));
+            }
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("{}-tune hook timed out", phase.name()));
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(20)),
+            Err(e) => return Err(format!("{}-tune hook: {}", phase.name(), e)),
+        }
+    }
+}
@@ -0,0 +1,619 @@
+//! Background radio worker that owns the controller and caches rig state
+//!
+//! All CAT traffic happens on the worker thread. The UI sends commands and
+//! reads a `RadioState` snapshot, so rendering never waits on the rig.
+
+use super::hooks::{run_shell_hook, HookPhase};
+use super::{
+    create_controller, RadioController, RadioError, RadioMode, RadioResult, RigctldLaunch,
+    RigctldProcess,
+};
+use crate::config::RadioConfig;
+use crate::models::Band;
+use crate::services::find_usb_port;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// How often the worker reads frequency/mode back from the rig
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Number of previous frequencies kept for undo
+const MAX_QSY_HISTORY: usize = 10;
+
+/// Upper bound on intermediate steps for a smooth QSY
+const MAX_SMOOTH_STEPS: usize = 100;
+
+/// Last known radio state, kept current by the worker
+#[derive(Debug, Clone)]
+pub struct RadioState {
+    /// Name of the active backend
+    pub backend: &'static str,
+    pub connected: bool,
+    pub frequency_khz: Option<f64>,
+    pub mode: Option<RadioMode>,
+    pub split: Option<bool>,
+    /// Most recent polling error, cleared on the next successful poll
+    pub last_error: Option<String>,
+    /// When the state was last refreshed from the rig
+    pub updated: Option<Instant>,
+    /// Last frequency sent to the sub receiver
+    pub sub_frequency_khz: Option<f64>,
+    /// Number of previous frequencies available to `undo_qsy`
+    pub qsy_history_len: usize,
+}
+
+impl RadioState {
+    fn new(backend: &'static str) -> Self {
+        Self {
+            backend,
+            connected: false,
+            frequency_khz: None,
+            mode: None,
+            split: None,
+            last_error: None,
+            updated: None,
+            sub_frequency_khz: None,
+            qsy_history_len: 0,
+        }
+    }
+
+    /// Short state for the display footer: "14025.0 CW", the backend name
+    /// before the first poll, or "NO RIG"
+    pub fn summary(&self) -> String {
+        match (self.connected, self.frequency_khz, self.mode) {
+            (true, Some(freq), Some(mode)) => format!("{:.1} {}", freq, mode.to_rigctld_mode()),
+            (true, _, _) => self.backend.to_string(),
+            (false, _, _) => "NO RIG".to_string(),
+        }
+    }
+}
+
+/// Commands sent from the UI to the radio worker
+enum RadioCommand {
+    Connect,
+    Disconnect,
+    Tune { frequency_khz: f64, mode: RadioMode },
+    TuneSub { frequency_khz: f64, mode: RadioMode },
+    UndoQsy,
+    Reconfigure(Box<RadioConfig>),
+    Shutdown,
+}
+
+/// Messages sent from the radio worker to the UI
+#[derive(Debug, Clone)]
+pub enum RadioEvent {
+    Status(String),
+    Error(String),
+    /// The rig took a main VFO tune (the sub receiver's aren't reported)
+    Tuned {
+        frequency_khz: f64,
+        mode: RadioMode,
+    },
+}
+
+/// Handle to the radio worker thread
+pub struct RadioManager {
+    cmd_tx: mpsc::Sender<RadioCommand>,
+    event_rx: mpsc::Receiver<RadioEvent>,
+    state: Arc<Mutex<RadioState>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl RadioManager {
+    /// Spawn the worker with a controller built from the given config
+    pub fn new(config: RadioConfig) -> Self {
+        let (cmd_tx, cmd_rx) = mpsc::channel();
+        let (event_tx, event_rx) = mpsc::channel();
+        let state = Arc::new(Mutex::new(RadioState::new("None")));
+
+        let worker_state = Arc::clone(&state);
+        let thread = std::thread::spawn(move || {
+            RadioWorker::new(config, worker_state, event_tx).run(cmd_rx);
+        });
+
+        Self {
+            cmd_tx,
+            event_rx,
+            state,
+            thread: Some(thread),
+        }
+    }
+
+    /// Connect (or reconnect) to the radio
+    pub fn connect(&self) {
+        let _ = self.cmd_tx.send(RadioCommand::Connect);
+    }
+
+    /// Disconnect from the radio
+    pub fn disconnect(&self) {
+        let _ = self.cmd_tx.send(RadioCommand::Disconnect);
+    }
+
+    /// Tune to a frequency (in kHz) and mode
+    pub fn tune(&self, frequency_khz: f64, mode: RadioMode) {
+        let _ = self.cmd_tx.send(RadioCommand::Tune {
+            frequency_khz,
+            mode,
+        });
+    }
+
+    /// Tune the sub receiver, leaving the main VFO where it is
+    pub fn tune_sub(&self, frequency_khz: f64, mode: RadioMode) {
+        let _ = self.cmd_tx.send(RadioCommand::TuneSub {
+            frequency_khz,
+            mode,
+        });
+    }
+
+    /// Return to the frequency and mode in use before the last tune
+    pub fn undo_qsy(&self) {
+        let _ = self.cmd_tx.send(RadioCommand::UndoQsy);
+    }
+
+    /// Replace the controller with one built from new settings
+    pub fn reconfigure(&self, config: RadioConfig) {
+        let _ = self
+            .cmd_tx
+            .send(RadioCommand::Reconfigure(Box::new(config)));
+    }
+
+    /// Snapshot of the cached radio state (never touches the rig)
+    pub fn state(&self) -> RadioState {
+        self.state
+            .lock()
+            .map(|s| s.clone())
+            .unwrap_or_else(|_| RadioState::new("None"))
+    }
+
+    /// Try to receive an event (non-blocking), logging it
+    pub fn try_recv(&self) -> Option<RadioEvent> {
+        let event = self.event_rx.try_recv().ok()?;
+        match &event {
+            RadioEvent::Status(status) => log::info!("Radio: {}", status),
+            RadioEvent::Error(e) => log::warn!("Radio: {}", e),
+            RadioEvent::Tuned { .. } => {}
+        }
+        Some(event)
+    }
+}
+
+impl Drop for RadioManager {
+    fn drop(&mut self) {
+        let _ = self.cmd_tx.send(RadioCommand::Shutdown);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Worker-side state, lives on the radio thread
+struct RadioWorker {
+    config: RadioConfig,
+    controller: Box<dyn RadioController>,
+    state: Arc<Mutex<RadioState>>,
+    event_tx: mpsc::Sender<RadioEvent>,
+    last_poll: Instant,
+    /// Previous (frequency_khz, mode) pairs, most recent last
+    qsy_history: Vec<(f64, RadioMode)>,
+    /// rigctld launched by the app, if configured
+    rigctld: Option<RigctldProcess>,
+}
+
+/// Launch settings for a managed rigctld, if enabled in the config
+pub fn rigctld_launch(config: &RadioConfig) -> Option<RigctldLaunch> {
+    let uses_rigctld = !cfg!(target_os = "windows") || config.backend == "rigctld";
+    if !(config.enabled && config.manage_rigctld && uses_rigctld) {
+        return None;
+    }
+
+    // Fall back to wherever the CAT adapter currently enumerates
+    let device = if config.rigctld_device.is_empty() {
+        config
+            .cat_usb_id
+            .and_then(find_usb_port)
+            .unwrap_or_default()
+    } else {
+        config.rigctld_device.clone()
+    };
+
+    Some(RigctldLaunch {
+        program: config.rigctld_path.clone(),
+        model: config.rigctld_model,
+        device,
+        port: config.rigctld_port,
+        extra_args: config.rigctld_args.clone(),
+    })
+}
+
+impl RadioWorker {
+    fn new(
+        config: RadioConfig,
+        state: Arc<Mutex<RadioState>>,
+        event_tx: mpsc::Sender<RadioEvent>,
+    ) -> Self {
+        let controller = create_controller(&config);
+        let mut worker = Self {
+            config,
+            controller,
+            state,
+            event_tx,
+            last_poll: Instant::now(),
+            qsy_history: Vec::new(),
+            rigctld: None,
+        };
+        worker.ensure_rigctld();
+        worker.reset_state();
+        worker
+    }
+
+    fn run(mut self, cmd_rx: mpsc::Receiver<RadioCommand>) {
+        loop {
+            match cmd_rx.recv_timeout(POLL_INTERVAL) {
+                Ok(RadioCommand::Shutdown) | Err(RecvTimeoutError::Disconnected) => {
+                    self.controller.disconnect();
+                    self.rigctld = None;
+                    return;
+                }
+                Ok(RadioCommand::Connect) => self.connect(),
+                Ok(RadioCommand::Disconnect) => {
+                    self.controller.disconnect();
+                    self.reset_state();
+                }
+                Ok(RadioCommand::Tune {
+                    frequency_khz,
+                    mode,
+                }) => {
+                    let previous = self.current_tuning();
+                    if self.tune(frequency_khz, mode, previous) {
+                        if let Some(previous) = previous {
+                            self.record_qsy(previous);
+                        }
+                    }
+                }
+                Ok(RadioCommand::TuneSub {
+                    frequency_khz,
+                    mode,
+                }) => self.tune_sub(frequency_khz, mode),
+                Ok(RadioCommand::UndoQsy) => self.undo_qsy(),
+                Ok(RadioCommand::Reconfigure(config)) => {
+                    self.controller.disconnect();
+                    self.controller = create_controller(&config);
+                    self.config = *config;
+                    self.qsy_history.clear();
+                    self.ensure_rigctld();
+                    self.reset_state();
+                    if self.config.enabled {
+                        self.connect();
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+            }
+
+            if self.last_poll.elapsed() >= POLL_INTERVAL {
+                self.supervise_rigctld();
+                self.poll();
+            }
+        }
+    }
+
+    fn send(&self, event: RadioEvent) {
+        let _ = self.event_tx.send(event);
+    }
+
+    fn update_state(&self, f: impl FnOnce(&mut RadioState)) {
+        if let Ok(mut state) = self.state.lock() {
+            f(&mut state);
+        }
+    }
+
+    /// Clear cached rig values after the controller changes or disconnects
+    fn reset_state(&mut self) {
+        let fresh = RadioState {
+            connected: self.controller.is_connected(),
+            qsy_history_len: self.qsy_history.len(),
+            ..RadioState::new(self.controller.backend_name())
+        };
+        self.update_state(|state| *state = fresh);
+    }
+
+    /// Start, restart or stop the managed rigctld to match the config
+    fn ensure_rigctld(&mut self) {
+        let launch = rigctld_launch(&self.config);
+        if self.rigctld.as_ref().map(|p| p.launch()) == launch.as_ref() {
+            return;
+        }
+
+        // Dropping the old process kills it
+        self.rigctld = None;
+        if let Some(launch) = launch {
+            let mut process = RigctldProcess::new(launch);
+            match process.start() {
+                Ok(()) => self.send(RadioEvent::Status("Started rigctld".to_string())),
+                Err(e) => self.send(RadioEvent::Error(e)),
+            }
+            self.rigctld = Some(process);
+        }
+    }
+
+    /// Restart the managed rigctld if it died, then reconnect
+    fn supervise_rigctld(&mut self) {
+        let Some(process) = self.rigctld.as_mut() else {
+            return;
+        };
+
+        match process.supervise() {
+            Some(Ok(message)) => {
+                self.send(RadioEvent::Status(message));
+                if self.controller.is_connected() {
+                    self.connect();
+                }
+            }
+            // Reported as status rather than a popup, this repeats until it recovers
+            Some(Err(e)) => self.send(RadioEvent::Status(e)),
+            None => {}
+        }
+    }
+
+    fn connect(&mut self) {
+        self.ensure_rigctld();
+        self.controller.disconnect();
+        match self.controller.connect() {
+            Ok(()) => {
+                self.send(RadioEvent::Status(format!(
+                    "{} connected",
+                    self.controller.backend_name()
+                )));
+            }
+            Err(e) => self.send(RadioEvent::Error(e.to_string())),
+        }
+        self.reset_state();
+        self.poll();
+    }
+
+    /// Refresh frequency, mode and split from the rig
+    fn poll(&mut self) {
+        self.last_poll = Instant::now();
+        if !self.controller.is_connected() {
+            return;
+        }
+
+        let frequency = self.read_frequency();
+        let mode = self.controller.get_mode();
+        let split = self.controller.get_split();
+
+        let error = [
+            frequency.as_ref().err(),
+            mode.as_ref().err(),
+            split.as_ref().err(),
+        ]
+        .into_iter()
+        .flatten()
+        .find(|e| !matches!(e, RadioError::NotSupported))
+        .map(|e| e.to_string());
+
+        self.update_state(|state| {
+            state.connected = true;
+            if let Ok(frequency_khz) = frequency {
+                state.frequency_khz = Some(frequency_khz);
+            }
+            if let Ok(mode) = mode {
+                state.mode = Some(mode);
+            }
+            if let Ok(split) = split {
+                state.split = Some(split);
+            }
+            state.last_error = error;
+            state.updated = Some(Instant::now());
+        });
+    }
+
+    /// kHz from a spot frequency to the rig's dial: the calibration offset
+    /// (positive when the rig reads high) less any transverter offset
+    fn rig_offset_khz(&self) -> f64 {
+        self.config.calibration_offset_hz as f64 / 1000.0 - self.config.transverter_offset_khz
+    }
+
+    /// Read the rig frequency with the calibration and transverter offsets
+    /// removed
+    fn read_frequency(&mut self) -> RadioResult<f64> {
+        let offset_khz = self.rig_offset_khz();
+        self.controller
+            .get_frequency()
+            .map(|frequency_khz| frequency_khz - offset_khz)
+    }
+
+    /// The rig's current frequency and mode, when connected and known
+    fn current_tuning(&mut self) -> Option<(f64, RadioMode)> {
+        if !self.controller.is_connected() {
+            return None;
+        }
+
+        // Prefer a fresh read-back, fall back to the cached state
+        let cached = self.state.lock().ok().map(|s| (s.frequency_khz, s.mode));
+        let (cached_freq, cached_mode) = cached.unwrap_or((None, None));
+        let frequency = self.read_frequency().ok().or(cached_freq);
+        let mode = self.controller.get_mode().ok().or(cached_mode);
+        frequency.zip(mode)
+    }
+
+    /// Push the frequency and mode tuned away from onto the undo history
+    fn record_qsy(&mut self, previous: (f64, RadioMode)) {
+        self.qsy_history.push(previous);
+        if self.qsy_history.len() > MAX_QSY_HISTORY {
+            self.qsy_history.remove(0);
+        }
+        let len = self.qsy_history.len();
+        self.update_state(|state| state.qsy_history_len = len);
+    }
+
+    /// Tune back to the most recent history entry, dropping it only once
+    /// the rig has taken it
+    fn undo_qsy(&mut self) {
+        let Some(&(frequency_khz, mode)) = self.qsy_history.last() else {
+            self.send(RadioEvent::Status("No previous frequency".to_string()));
+            return;
+        };
+        let previous = self.current_tuning();
+        if !self.tune(frequency_khz, mode, previous) {
+            return;
+        }
+        self.qsy_history.pop();
+        let len = self.qsy_history.len();
+        self.update_state(|state| state.qsy_history_len = len);
+    }
+
+    /// Run the configured shell and CAT hooks for one phase of a tune
+    fn run_hooks(&mut self, phase: HookPhase, frequency_khz: f64, mode: RadioMode) {
+        let (shell, cat) = match phase {
+            HookPhase::Pre => (&self.config.pre_tune_command, &self.config.pre_tune_cat),
+            HookPhase::Post => (&self.config.post_tune_command, &self.config.post_tune_cat),
+        };
+        let shell = shell.trim().to_string();
+        let cat = cat.clone();
+
+        if !shell.is_empty() {
+            if let Err(e) = run_shell_hook(&shell, phase, frequency_khz, mode) {
+                self.send(RadioEvent::Error(e));
+            }
+        }
+
+        // CAT hooks are ';'-separated raw backend commands
+        for command in cat.split(';').map(str::trim).filter(|c| !c.is_empty()) {
+            if let Err(e) = self.controller.send_raw(command) {
+                self.send(RadioEvent::Error(format!(
+                    "{}-tune CAT hook '{}' failed: {}",
+                    phase.name(),
+                    command,
+                    e
+                )));
+                break;
+            }
+        }
+    }
+
+    /// Walk the VFO toward the target in small steps when smooth QSY is on
+    ///
+    /// Works with any backend that can set frequency alone; the final
+    /// `tune` call lands exactly on the target and sets the mode.
+    fn step_toward(&mut self, rig_frequency_khz: f64) {
+        if !self.config.smooth_qsy {
+            return;
+        }
+        let Ok(start_khz) = self.controller.get_frequency() else {
+            return;
+        };
+
+        let distance_khz = rig_frequency_khz - start_khz;
+        let step_khz = (self.config.smooth_qsy_step_hz.max(1) as f64) / 1000.0;
+        let steps = ((distance_khz.abs() / step_khz).ceil() as usize).min(MAX_SMOOTH_STEPS);
+        let delay = Duration::from_millis(self.config.smooth_qsy_delay_ms as u64);
+
+        for i in 1..steps {
+            let frequency_khz = start_khz + distance_khz * i as f64 / steps as f64;
+            if self.controller.set_frequency(frequency_khz).is_err() {
+                return;
+            }
+            std::thread::sleep(delay);
+        }
+    }
+
+    /// Tune the main VFO; false when the rig didn't take it. `previous` is
+    /// the rig's tuning beforehand, for the power preset.
+    fn tune(
+        &mut self,
+        frequency_khz: f64,
+        mode: RadioMode,
+        previous: Option<(f64, RadioMode)>,
+    ) -> bool {
+        self.run_hooks(HookPhase::Pre, frequency_khz, mode);
+
+        let rig_frequency_khz = frequency_khz + self.rig_offset_khz();
+        self.step_toward(rig_frequency_khz);
+        if let Err(e) = self.controller.tune(rig_frequency_khz, mode) {
+            self.send(RadioEvent::Error(e.to_string()));
+            // Post hooks still run so e.g. muted audio is restored
+            self.run_hooks(HookPhase::Post, frequency_khz, mode);
+            return false;
+        }
+
+        self.update_state(|state| {
+            state.frequency_khz = Some(frequency_khz);
+            state.mode = Some(mode);
+        });
+        self.send(RadioEvent::Status(format!(
+            "Tuned to {:.1} kHz {}",
+            frequency_khz,
+            mode.to_rigctld_mode()
+        )));
+        self.send(RadioEvent::Tuned {
+            frequency_khz,
+            mode,
+        });
+
+        self.apply_filter_width(mode);
+        let from_band = previous.and_then(|(previous_khz, _)| Band::from_khz(previous_khz));
+        self.apply_band_power(frequency_khz, from_band);
+        self.run_hooks(HookPhase::Post, frequency_khz, mode);
+        true
+    }
+
+    /// Tune the sub receiver; main VFO history, power and filter are untouched
+    fn tune_sub(&mut self, frequency_khz: f64, mode: RadioMode) {
+        let rig_frequency_khz = frequency_khz + self.rig_offset_khz();
+        match self.controller.tune_sub(rig_frequency_khz, mode) {
+            Ok(()) => {
+                self.update_state(|state| state.sub_frequency_khz = Some(frequency_khz));
+                self.send(RadioEvent::Status(format!(
+                    "Sub RX on {:.1} kHz {}",
+                    frequency_khz,
+                    mode.to_rigctld_mode()
+                )));
+            }
+            Err(e) => self.send(RadioEvent::Error(format!("Sub RX: {}", e))),
+        }
+    }
+
+    /// Apply the configured filter width for the tuned mode
+    fn apply_filter_width(&mut self, mode: RadioMode) {
+        let Some(width_hz) = self.config.filter_width_hz(mode) else {
+            return;
+        };
+
+        match self.controller.set_filter_width(mode, width_hz) {
+            Ok(()) | Err(RadioError::NotSupported) => {}
+            Err(e) => {
+                self.send(RadioEvent::Error(format!(
+                    "Failed to set {} Hz filter: {}",
+                    width_hz, e
+                )));
+            }
+        }
+    }
+
+    /// Apply the configured TX power preset after tuning onto a new band.
+    /// `from_band` is the band the rig read back before the tune; when it's
+    /// unknown the preset is applied anyway.
+    fn apply_band_power(&mut self, frequency_khz: f64, from_band: Option<Band>) {
+        let Some(band) = Band::from_khz(frequency_khz) else {
+            return;
+        };
+        if from_band == Some(band) {
+            return;
+        }
+        let Some(&percent) = self.config.band_power.get(&band) else {
+            return;
+        };
+
+        match self.controller.set_power(percent) {
+            Ok(()) | Err(RadioError::NotSupported) => {}
+            Err(e) => {
+                self.send(RadioEvent::Error(format!(
+                    "Failed to set {} power to {}%: {}",
+                    band, percent, e
+                )));
+            }
+        }
+    }
+}
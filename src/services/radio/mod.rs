@@ -1,13 +1,22 @@
 //! Radio controller abstraction for CAT control
 
+use crate::config::RadioConfig;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+
+mod hooks;
+mod manager;
 mod noop;
 mod rigctld;
+mod rigctld_process;
 
 #[cfg(target_os = "windows")]
 mod omnirig;
 
+pub use manager::{rigctld_launch, RadioEvent, RadioManager, RadioState};
 pub use noop::NoOpController;
 pub use rigctld::RigctldController;
+pub use rigctld_process::{RigctldLaunch, RigctldProcess};
 
 #[cfg(target_os = "windows")]
 pub use omnirig::OmniRigController;
@@ -39,6 +48,22 @@ impl RadioMode {
         }
     }
 
+    /// Parse a rigctld mode string
+    pub fn from_rigctld_mode(mode: &str) -> Option<Self> {
+        match mode {
+            "CW" => Some(RadioMode::Cw),
+            "CWR" => Some(RadioMode::CwReverse),
+            "USB" => Some(RadioMode::Usb),
+            "LSB" => Some(RadioMode::Lsb),
+            "RTTY" => Some(RadioMode::Rtty),
+            "RTTYR" => Some(RadioMode::RttyReverse),
+            "AM" => Some(RadioMode::Am),
+            "FM" => Some(RadioMode::Fm),
+            "PKTUSB" | "PKTLSB" => Some(RadioMode::Data),
+            _ => None,
+        }
+    }
+
     /// Convert to rigctld mode string
     pub fn to_rigctld_mode(self) -> &'static str {
         match self {
@@ -67,6 +92,7 @@ pub enum RadioError {
     CommandFailed(String),
     Timeout,
     NotConfigured,
+    NotSupported,
 }
 
 impl std::fmt::Display for RadioError {
@@ -77,6 +103,7 @@ impl std::fmt::Display for RadioError {
             RadioError::CommandFailed(msg) => write!(f, "Command failed: {}", msg),
             RadioError::Timeout => write!(f, "Radio not responding"),
             RadioError::NotConfigured => write!(f, "Radio not configured"),
+            RadioError::NotSupported => write!(f, "Not supported by this radio backend"),
         }
     }
 }
@@ -98,33 +125,115 @@ pub trait RadioController: Send {
     /// Tune to a frequency (in kHz) and mode
     fn tune(&mut self, frequency_khz: f64, mode: RadioMode) -> RadioResult<()>;
 
+    /// Tune the sub receiver (dual-receive rigs), leaving the main VFO alone
+    fn tune_sub(&mut self, _frequency_khz: f64, _mode: RadioMode) -> RadioResult<()> {
+        Err(RadioError::NotSupported)
+    }
+
+    /// Set only the VFO frequency (in kHz), leaving the mode alone
+    fn set_frequency(&mut self, _frequency_khz: f64) -> RadioResult<()> {
+        Err(RadioError::NotSupported)
+    }
+
+    /// Set RF output power as a percentage of the rig's maximum (0-100)
+    fn set_power(&mut self, _percent: u8) -> RadioResult<()> {
+        Err(RadioError::NotSupported)
+    }
+
+    /// Set the receive filter width (in Hz) for the given mode
+    fn set_filter_width(&mut self, _mode: RadioMode, _width_hz: u32) -> RadioResult<()> {
+        Err(RadioError::NotSupported)
+    }
+
+    /// Send a backend-specific raw command (e.g. a rigctld set command)
+    fn send_raw(&mut self, _command: &str) -> RadioResult<String> {
+        Err(RadioError::NotSupported)
+    }
+
+    /// Read the current VFO frequency (in kHz)
+    fn get_frequency(&mut self) -> RadioResult<f64> {
+        Err(RadioError::NotSupported)
+    }
+
+    /// Read the current operating mode
+    fn get_mode(&mut self) -> RadioResult<RadioMode> {
+        Err(RadioError::NotSupported)
+    }
+
+    /// Read whether split operation is enabled
+    fn get_split(&mut self) -> RadioResult<bool> {
+        Err(RadioError::NotSupported)
+    }
+
     /// Get a description of the backend
     fn backend_name(&self) -> &'static str;
 }
 
+/// Constructor for a radio backend, given the radio settings
+pub type BackendFactory = Box<dyn Fn(&RadioConfig) -> Box<dyn RadioController> + Send + Sync>;
+
+/// Registered backends by name, built-ins included
+fn registry() -> &'static Mutex<BTreeMap<String, BackendFactory>> {
+    static REGISTRY: OnceLock<Mutex<BTreeMap<String, BackendFactory>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let mut backends: BTreeMap<String, BackendFactory> = BTreeMap::new();
+        backends.insert(
+            "rigctld".to_string(),
+            Box::new(|config: &RadioConfig| {
+                Box::new(RigctldController::new(
+                    config.rigctld_host.clone(),
+                    config.rigctld_port,
+                ))
+            }),
+        );
+        #[cfg(target_os = "windows")]
+        backends.insert(
+            "omnirig".to_string(),
+            Box::new(|config: &RadioConfig| Box::new(OmniRigController::new(config.omnirig_rig))),
+        );
+        Mutex::new(backends)
+    })
+}
+
+/// Register a radio backend selectable by name via `[radio] backend = <name>`
+///
+/// Call before the app starts; registering an existing name replaces it.
+pub fn register_backend<F>(name: &str, factory: F)
+where
+    F: Fn(&RadioConfig) -> Box<dyn RadioController> + Send + Sync + 'static,
+{
+    if let Ok(mut backends) = registry().lock() {
+        backends.insert(name.to_string(), Box::new(factory));
+    }
+}
+
+/// Names of all registered backends
+pub fn registered_backends() -> Vec<String> {
+    registry()
+        .lock()
+        .map(|backends| backends.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
 /// Factory function to create the appropriate controller
-#[cfg(target_os = "windows")]
-pub fn create_controller(config: &crate::config::RadioConfig) -> Box<dyn RadioController> {
+pub fn create_controller(config: &RadioConfig) -> Box<dyn RadioController> {
     if !config.enabled {
         return Box::new(NoOpController::new());
     }
-    match config.backend.as_str() {
-        "omnirig" => Box::new(OmniRigController::new(config.omnirig_rig)),
-        "rigctld" => Box::new(RigctldController::new(
-            config.rigctld_host.clone(),
-            config.rigctld_port,
-        )),
-        _ => Box::new(NoOpController::new()),
+
+    if let Ok(backends) = registry().lock() {
+        if let Some(factory) = backends.get(&config.backend) {
+            return factory(config);
+        }
     }
-}
 
-#[cfg(not(target_os = "windows"))]
-pub fn create_controller(config: &crate::config::RadioConfig) -> Box<dyn RadioController> {
-    if !config.enabled {
-        return Box::new(NoOpController::new());
+    // Unknown backend: rigctld is the only choice outside Windows
+    if cfg!(target_os = "windows") {
+        Box::new(NoOpController::new())
+    } else {
+        Box::new(RigctldController::new(
+            config.rigctld_host.clone(),
+            config.rigctld_port,
+        ))
     }
-    Box::new(RigctldController::new(
-        config.rigctld_host.clone(),
-        config.rigctld_port,
-    ))
 }
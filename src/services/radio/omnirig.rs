@@ -16,6 +16,7 @@ mod omnirig_modes {
     pub const PM_DIG_L: i32 = 0x10000000;
     pub const PM_AM: i32 = 0x20000000;
     pub const PM_FM: i32 = 0x40000000;
+    pub const PM_SPLITON: i32 = 0x00008000;
 }
 
 /// Controller for OmniRig (Windows COM server)
@@ -49,6 +50,36 @@ impl OmniRigController {
         }
     }
 
+    /// Convert OmniRig mode constant to RadioMode
+    fn mode_from_omnirig(value: i32) -> Option<RadioMode> {
+        match value {
+            omnirig_modes::PM_CW_U => Some(RadioMode::Cw),
+            omnirig_modes::PM_CW_L => Some(RadioMode::CwReverse),
+            omnirig_modes::PM_SSB_U => Some(RadioMode::Usb),
+            omnirig_modes::PM_SSB_L => Some(RadioMode::Lsb),
+            omnirig_modes::PM_AM => Some(RadioMode::Am),
+            omnirig_modes::PM_FM => Some(RadioMode::Fm),
+            omnirig_modes::PM_DIG_U | omnirig_modes::PM_DIG_L => Some(RadioMode::Data),
+            _ => None,
+        }
+    }
+
+    /// Read an integer property from the rig object
+    fn get_i32(&self, property: &str) -> RadioResult<i32> {
+        let rig = self.rig.as_ref().ok_or(RadioError::NotConnected)?;
+        match rig.invoke_get(property, &[]) {
+            Ok(w::Variant::I4(value)) => Ok(value),
+            Ok(_) => Err(RadioError::CommandFailed(format!(
+                "Unexpected type for {}",
+                property
+            ))),
+            Err(e) => Err(RadioError::CommandFailed(format!(
+                "Failed to read {}: {}",
+                property, e
+            ))),
+        }
+    }
+
     /// Get the rig property name based on rig number
     fn rig_property_name(&self) -> &'static str {
         if self.rig_number == 2 {
@@ -139,6 +170,28 @@ impl RadioController for OmniRigController {
         Ok(())
     }
 
+    fn set_frequency(&mut self, frequency_khz: f64) -> RadioResult<()> {
+        let rig = self.rig.as_ref().ok_or(RadioError::NotConnected)?;
+        let freq_variant = w::Variant::I4((frequency_khz * 1000.0) as i32);
+        rig.invoke_put("FreqA", &freq_variant)
+            .map_err(|e| RadioError::CommandFailed(format!("Failed to set frequency: {}", e)))?;
+        Ok(())
+    }
+
+    fn get_frequency(&mut self) -> RadioResult<f64> {
+        Ok(self.get_i32("Freq")? as f64 / 1000.0)
+    }
+
+    fn get_mode(&mut self) -> RadioResult<RadioMode> {
+        let value = self.get_i32("Mode")?;
+        Self::mode_from_omnirig(value)
+            .ok_or_else(|| RadioError::CommandFailed(format!("Unknown mode: {:#x}", value)))
+    }
+
+    fn get_split(&mut self) -> RadioResult<bool> {
+        Ok(self.get_i32("Split")? == omnirig_modes::PM_SPLITON)
+    }
+
     fn backend_name(&self) -> &'static str {
         "OmniRig"
     }
@@ -10,6 +10,7 @@ pub struct RigctldController {
     host: String,
     port: u16,
     stream: Option<TcpStream>,
+    reader: Option<BufReader<TcpStream>>,
 }
 
 impl RigctldController {
@@ -18,10 +19,16 @@ impl RigctldController {
             host,
             port,
             stream: None,
+            reader: None,
         }
     }
 
     fn send_command(&mut self, command: &str) -> RadioResult<String> {
+        Ok(self.query(command, 1)?.remove(0))
+    }
+
+    /// Send a command and read the given number of response lines
+    fn query(&mut self, command: &str, lines: usize) -> RadioResult<Vec<String>> {
         let stream = self.stream.as_mut().ok_or(RadioError::NotConnected)?;
 
         // Send command
@@ -30,31 +37,32 @@ impl RigctldController {
             .flush()
             .map_err(|e| RadioError::CommandFailed(e.to_string()))?;
 
-        // Read response
-        let mut reader =
-            BufReader::new(stream.try_clone().map_err(|e| {
-                RadioError::CommandFailed(format!("Failed to clone stream: {}", e))
-            })?);
-        let mut response = String::new();
-        reader
-            .read_line(&mut response)
-            .map_err(|e| RadioError::CommandFailed(e.to_string()))?;
-
-        let response = response.trim().to_string();
-
-        // Check for error response (rigctld returns "RPRT <error_code>" on failure)
-        if response.starts_with("RPRT") {
-            let parts: Vec<&str> = response.split_whitespace().collect();
-            if parts.len() >= 2 {
-                if let Ok(code) = parts[1].parse::<i32>() {
-                    if code != 0 {
-                        return Err(RadioError::CommandFailed(format!(
-                            "rigctld error code: {}",
-                            code
-                        )));
+        // Read response through the persistent reader so no buffered lines are lost
+        let reader = self.reader.as_mut().ok_or(RadioError::NotConnected)?;
+        let mut response = Vec::with_capacity(lines);
+        for _ in 0..lines {
+            let mut line = String::new();
+            reader
+                .read_line(&mut line)
+                .map_err(|e| RadioError::CommandFailed(e.to_string()))?;
+            let line = line.trim().to_string();
+
+            // Check for error response (rigctld returns "RPRT <error_code>" on failure)
+            if line.starts_with("RPRT") {
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                if parts.len() >= 2 {
+                    if let Ok(code) = parts[1].parse::<i32>() {
+                        if code != 0 {
+                            return Err(RadioError::CommandFailed(format!(
+                                "rigctld error code: {}",
+                                code
+                            )));
+                        }
                     }
                 }
             }
+
+            response.push(line);
         }
 
         Ok(response)
@@ -88,11 +96,17 @@ impl RadioController for RigctldController {
             .set_write_timeout(Some(Duration::from_secs(3)))
             .map_err(|e| RadioError::ConnectionFailed(e.to_string()))?;
 
+        let reader = stream
+            .try_clone()
+            .map_err(|e| RadioError::ConnectionFailed(format!("Failed to clone stream: {}", e)))?;
+
+        self.reader = Some(BufReader::new(reader));
         self.stream = Some(stream);
         Ok(())
     }
 
     fn disconnect(&mut self) {
+        self.reader = None;
         self.stream = None;
     }
 
@@ -114,6 +128,74 @@ impl RadioController for RigctldController {
         Ok(())
     }
 
+    fn tune_sub(&mut self, frequency_khz: f64, mode: RadioMode) -> RadioResult<()> {
+        if self.stream.is_none() {
+            return Err(RadioError::NotConnected);
+        }
+
+        // Select the sub receiver, tune it, then hand control back to main
+        self.send_command("V Sub")?;
+        let result = self.tune(frequency_khz, mode);
+        self.send_command("V Main")?;
+        result
+    }
+
+    fn set_frequency(&mut self, frequency_khz: f64) -> RadioResult<()> {
+        let frequency_hz = (frequency_khz * 1000.0) as u64;
+        self.send_command(&format!("F {}", frequency_hz))?;
+        Ok(())
+    }
+
+    fn set_power(&mut self, percent: u8) -> RadioResult<()> {
+        if self.stream.is_none() {
+            return Err(RadioError::NotConnected);
+        }
+
+        // Set level: L RFPOWER <0.0-1.0>
+        let level = percent.min(100) as f64 / 100.0;
+        self.send_command(&format!("L RFPOWER {:.2}", level))?;
+
+        Ok(())
+    }
+
+    fn set_filter_width(&mut self, mode: RadioMode, width_hz: u32) -> RadioResult<()> {
+        if self.stream.is_none() {
+            return Err(RadioError::NotConnected);
+        }
+
+        // Passband is set together with the mode: M <mode> <passband_hz>
+        self.send_command(&format!("M {} {}", mode.to_rigctld_mode(), width_hz))?;
+
+        Ok(())
+    }
+
+    fn send_raw(&mut self, command: &str) -> RadioResult<String> {
+        // Only single-line replies are read, so hooks should use set commands
+        self.send_command(command)
+    }
+
+    fn get_frequency(&mut self) -> RadioResult<f64> {
+        // Get frequency: f -> <freq_hz>
+        let response = self.send_command("f")?;
+        let frequency_hz: f64 = response
+            .parse()
+            .map_err(|_| RadioError::CommandFailed(format!("Bad frequency: {}", response)))?;
+        Ok(frequency_hz / 1000.0)
+    }
+
+    fn get_mode(&mut self) -> RadioResult<RadioMode> {
+        // Get mode: m -> <mode>\n<passband>
+        let response = self.query("m", 2)?;
+        RadioMode::from_rigctld_mode(&response[0])
+            .ok_or_else(|| RadioError::CommandFailed(format!("Unknown mode: {}", response[0])))
+    }
+
+    fn get_split(&mut self) -> RadioResult<bool> {
+        // Get split: s -> <split 0|1>\n<tx_vfo>
+        let response = self.query("s", 2)?;
+        Ok(response[0] == "1")
+    }
+
     fn backend_name(&self) -> &'static str {
         "rigctld"
     }
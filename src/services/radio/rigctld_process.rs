@@ -0,0 +1,136 @@
+//! Supervised rigctld child process
+//!
+//! When enabled the app launches rigctld itself, restarts it if it exits,
+//! and kills it on shutdown, so only one service needs configuring.
+
+use std::net::TcpStream;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Minimum time between restart attempts
+const RESTART_DELAY: Duration = Duration::from_secs(5);
+
+/// How long to wait for a freshly started rigctld to accept connections
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Launch settings for a managed rigctld
+#[derive(Debug, Clone, PartialEq)]
+pub struct RigctldLaunch {
+    /// Path to the rigctld binary
+    pub program: String,
+    /// Hamlib rig model number (-m)
+    pub model: u32,
+    /// Serial device of the rig (-r), omitted if empty
+    pub device: String,
+    /// TCP port to listen on (-t)
+    pub port: u16,
+    /// Extra arguments, whitespace separated
+    pub extra_args: String,
+}
+
+/// A rigctld process owned by the app
+pub struct RigctldProcess {
+    launch: RigctldLaunch,
+    child: Option<Child>,
+    last_start: Option<Instant>,
+}
+
+impl RigctldProcess {
+    pub fn new(launch: RigctldLaunch) -> Self {
+        Self {
+            launch,
+            child: None,
+            last_start: None,
+        }
+    }
+
+    /// Settings this process was launched with
+    pub fn launch(&self) -> &RigctldLaunch {
+        &self.launch
+    }
+
+    /// Start rigctld and wait briefly until it accepts connections
+    pub fn start(&mut self) -> Result<(), String> {
+        self.stop();
+        self.last_start = Some(Instant::now());
+
+        let mut command = Command::new(&self.launch.program);
+        command
+            .arg("-m")
+            .arg(self.launch.model.to_string())
+            .arg("-t")
+            .arg(self.launch.port.to_string());
+        if !self.launch.device.is_empty() {
+            command.arg("-r").arg(&self.launch.device);
+        }
+        command
+            .args(self.launch.extra_args.split_whitespace())
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+
+        let child = command
+            .spawn()
+            .map_err(|e| format!("Failed to start {}: {}", self.launch.program, e))?;
+        self.child = Some(child);
+
+        let deadline = Instant::now() + STARTUP_TIMEOUT;
+        while Instant::now() < deadline {
+            if TcpStream::connect(("127.0.0.1", self.launch.port)).is_ok() {
+                return Ok(());
+            }
+            if let Some(status) = self.exit_status() {
+                self.child = None;
+                return Err(format!("rigctld {} during startup", status));
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+
+        Err("rigctld did not start listening in time".to_string())
+    }
+
+    /// Restart rigctld if it has exited; returns a description of what happened
+    pub fn supervise(&mut self) -> Option<Result<String, String>> {
+        let exited = match self.child {
+            Some(_) => self.exit_status()?,
+            None => "not running".to_string(),
+        };
+
+        if self
+            .last_start
+            .is_some_and(|started| started.elapsed() < RESTART_DELAY)
+        {
+            return None;
+        }
+
+        self.child = None;
+        Some(
+            self.start()
+                .map(|()| format!("rigctld {}, restarted", exited)),
+        )
+    }
+
+    /// Kill rigctld and reap it
+    pub fn stop(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+
+    /// Exit status text if the child has exited
+    fn exit_status(&mut self) -> Option<String> {
+        let child = self.child.as_mut()?;
+        match child.try_wait() {
+            Ok(Some(status)) => Some(format!("exited ({})", status)),
+            Ok(None) => None,
+            Err(e) => Some(format!("could not be checked ({})", e)),
+        }
+    }
+}
+
+impl Drop for RigctldProcess {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
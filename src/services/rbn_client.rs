@@ -1,17 +1,26 @@
-use crate::models::RawSpot;
+use crate::logging::RAW_TARGET;
+use crate::models::{Announcement, RawSpot};
 use regex::Regex;
-use tokio::io::{AsyncReadExt, AsyncWriteExt, BufReader};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
 use tokio::net::TcpStream;
 use tokio::sync::mpsc;
 
 const RBN_HOST: &str = "rbn.telegraphy.de";
+/// Also the port of a `[feed] server` given without one
 const RBN_PORT: u16 = 7000;
 
+/// Pause between lines when playing back a recorded feed
+const REPLAY_INTERVAL: Duration = Duration::from_millis(200);
+
 /// Messages sent from the RBN client to the main app
 #[derive(Debug, Clone)]
 pub enum RbnMessage {
     Status(String),
     Spot(RawSpot),
+    /// WWV/WCY report, announcement or talk message
+    Announcement(Announcement),
     Disconnected,
     /// Raw data for debugging (direction: true = received, false = sent)
     RawData {
@@ -23,7 +32,13 @@ pub enum RbnMessage {
 /// Commands sent to the RBN client
 #[derive(Debug)]
 pub enum RbnCommand {
-    Connect(String),
+    Connect {
+        callsign: String,
+        /// "host[:port]", "" for the RBN server
+        server: String,
+    },
+    /// Play back a recorded feed instead of connecting
+    Replay(PathBuf),
     Disconnect,
 }
 
@@ -50,10 +65,18 @@ impl RbnClient {
         Self { cmd_tx, msg_rx }
     }
 
-    /// Send a connect command (non-blocking from UI)
-    pub fn connect(&self, callsign: String) {
+    /// Send a connect command (non-blocking from UI); `server` is
+    /// `[feed] server`
+    pub fn connect(&self, callsign: String, server: String) {
+        let tx = self.cmd_tx.clone();
+        let _ = tx.blocking_send(RbnCommand::Connect { callsign, server });
+    }
+
+    /// Play back a recorded feed (one telnet line per line, e.g. a saved
+    /// session log) instead of connecting, for trying settings offline
+    pub fn replay(&self, path: PathBuf) {
         let tx = self.cmd_tx.clone();
-        let _ = tx.blocking_send(RbnCommand::Connect(callsign));
+        let _ = tx.blocking_send(RbnCommand::Replay(path));
     }
 
     /// Send a disconnect command (non-blocking from UI)
@@ -62,9 +85,27 @@ impl RbnClient {
         let _ = tx.blocking_send(RbnCommand::Disconnect);
     }
 
-    /// Try to receive a message (non-blocking)
+    /// Try to receive a message (non-blocking); status changes are logged,
+    /// and raw lines too with `[logging] raw_lines`
     pub fn try_recv(&mut self) -> Option<RbnMessage> {
-        self.msg_rx.try_recv().ok()
+        let msg = self.msg_rx.try_recv().ok()?;
+        match &msg {
+            RbnMessage::Status(status) => log::info!("RBN: {}", status),
+            RbnMessage::RawData { data, received } => log::info!(
+                target: RAW_TARGET,
+                "{} {}",
+                if *received { "<<" } else { ">>" },
+                data.trim_end()
+            ),
+            _ => {}
+        }
+        Some(msg)
+    }
+}
+
+impl Default for RbnClient {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -75,23 +116,25 @@ async fn rbn_task(mut cmd_rx: mpsc::Receiver<RbnCommand>, msg_tx: mpsc::Sender<R
 
     loop {
         // Wait for a connect command
-        let callsign = loop {
+        let (callsign, server) = loop {
             match cmd_rx.recv().await {
-                Some(RbnCommand::Connect(cs)) => break cs,
+                Some(RbnCommand::Connect { callsign, server }) => break (callsign, server),
+                Some(RbnCommand::Replay(path)) => {
+                    replay(&path, &mut cmd_rx, &msg_tx, &spot_regex).await;
+                    let _ = msg_tx.send(RbnMessage::Disconnected).await;
+                }
                 Some(RbnCommand::Disconnect) => continue,
                 None => return, // Channel closed
             }
         };
 
+        let address = server_address(&server);
         let _ = msg_tx
-            .send(RbnMessage::Status(format!(
-                "Connecting to {}:{}...",
-                RBN_HOST, RBN_PORT
-            )))
+            .send(RbnMessage::Status(format!("Connecting to {}...", address)))
             .await;
 
         // Try to connect
-        let stream = match TcpStream::connect((RBN_HOST, RBN_PORT)).await {
+        let stream = match TcpStream::connect(address.as_str()).await {
             Ok(s) => s,
             Err(e) => {
                 let _ = msg_tx
@@ -137,7 +180,7 @@ async fn handle_connection(
                         let _ = msg_tx.send(RbnMessage::Status("Disconnected".to_string())).await;
                         return;
                     }
-                    Some(RbnCommand::Connect(_)) => {
+                    Some(RbnCommand::Connect { .. }) | Some(RbnCommand::Replay(_)) => {
                         // Already connected, ignore
                     }
                 }
@@ -159,25 +202,11 @@ async fn handle_connection(
                         // Process complete lines (ending with \n)
                         while let Some(newline_pos) = buffer.find('\n') {
                             let line: String = buffer.drain(..=newline_pos).collect();
-
-                            // Send raw received data for debugging
-                            let _ = msg_tx
-                                .send(RbnMessage::RawData {
-                                    data: line.clone(),
-                                    received: true,
-                                })
-                                .await;
-
-                            // Parse spots from complete lines
-                            if line.starts_with("DX de") {
-                                if let Some(spot) = parse_spot_line(&line, spot_regex) {
-                                    let _ = msg_tx.send(RbnMessage::Spot(spot)).await;
-                                }
-                            }
+                            forward_line(&line, msg_tx, spot_regex).await;
                         }
 
                         // Check for login prompt in remaining buffer (may not end with newline)
-                        if !logged_in && buffer.to_lowercase().contains("please enter your callsign") {
+                        if !logged_in && is_login_prompt(&buffer) {
                             // Send remaining buffer as raw data for debugging
                             if !buffer.is_empty() {
                                 let _ = msg_tx
@@ -215,6 +244,97 @@ async fn handle_connection(
     }
 }
 
+/// RBN's "Please enter your callsign:", or a cluster node's "login:"
+fn is_login_prompt(text: &str) -> bool {
+    let text = text.to_lowercase();
+    text.contains("please enter your call") || text.trim_end().ends_with("login:")
+}
+
+/// "host:port" to connect to for a `[feed] server` setting
+pub fn server_address(server: &str) -> String {
+    match server.trim() {
+        "" => format!("{}:{}", RBN_HOST, RBN_PORT),
+        server if server.contains(':') => server.to_string(),
+        host => format!("{}:{}", host, RBN_PORT),
+    }
+}
+
+/// Play back a recorded feed line by line until it ends or a disconnect
+/// arrives
+async fn replay(
+    path: &Path,
+    cmd_rx: &mut mpsc::Receiver<RbnCommand>,
+    msg_tx: &mpsc::Sender<RbnMessage>,
+    spot_regex: &Regex,
+) {
+    let file = match tokio::fs::File::open(path).await {
+        Ok(file) => file,
+        Err(e) => {
+            let _ = msg_tx
+                .send(RbnMessage::Status(format!(
+                    "Failed to open {}: {}",
+                    path.display(),
+                    e
+                )))
+                .await;
+            return;
+        }
+    };
+    let _ = msg_tx
+        .send(RbnMessage::Status(format!("Replaying {}", path.display())))
+        .await;
+
+    let mut lines = BufReader::new(file).lines();
+    loop {
+        tokio::select! {
+            cmd = cmd_rx.recv() => {
+                if let Some(RbnCommand::Disconnect) | None = cmd {
+                    let _ = msg_tx.send(RbnMessage::Status("Disconnected".to_string())).await;
+                    return;
+                }
+            }
+
+            line = lines.next_line() => {
+                match line {
+                    Ok(Some(line)) => {
+                        forward_line(&format!("{}\n", line), msg_tx, spot_regex).await;
+                        tokio::time::sleep(REPLAY_INTERVAL).await;
+                    }
+                    Ok(None) => {
+                        let _ = msg_tx.send(RbnMessage::Status("Replay finished".to_string())).await;
+                        return;
+                    }
+                    Err(e) => {
+                        let _ = msg_tx.send(RbnMessage::Status(format!("Read error: {}", e))).await;
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Send a complete line on as raw data, then as a spot or announcement if
+/// it is one
+async fn forward_line(line: &str, msg_tx: &mpsc::Sender<RbnMessage>, spot_regex: &Regex) {
+    // Send raw received data for debugging
+    let _ = msg_tx
+        .send(RbnMessage::RawData {
+            data: line.to_string(),
+            received: true,
+        })
+        .await;
+
+    // Parse spots from complete lines
+    if line.starts_with("DX de") {
+        if let Some(spot) = parse_spot_line(line, spot_regex) {
+            let _ = msg_tx.send(RbnMessage::Spot(spot)).await;
+        }
+    } else if let Some(announcement) = Announcement::parse(line) {
+        let _ = msg_tx.send(RbnMessage::Announcement(announcement)).await;
+    }
+}
+
 fn parse_spot_line(line: &str, regex: &Regex) -> Option<RawSpot> {
     let caps = regex.captures(line)?;
 
@@ -0,0 +1,121 @@
+//! Daily local-time ranges, as used by `[sound] quiet_hours` and
+//! `[display] off_hours`, and cron-style schedules (`[dump] schedule`)
+
+use crate::models::local_minute_of_day;
+use chrono::{DateTime, Datelike, Local, NaiveDateTime, Timelike, Utc};
+use chrono_tz::Tz;
+
+/// "HH:MM-HH:MM" in local time, wrapping past midnight when the end is
+/// earlier than the start
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DailyRange {
+    /// Minutes of the day
+    start: u32,
+    end: u32,
+}
+
+impl DailyRange {
+    /// Parse "HH:MM-HH:MM" (None when empty or malformed)
+    pub fn parse(range: &str) -> Option<Self> {
+        let minute_of_day = |time: &str| -> Option<u32> {
+            let (hour, minute) = time.trim().split_once(':')?;
+            let hour: u32 = hour.parse().ok().filter(|h| *h < 24)?;
+            let minute: u32 = minute.parse().ok().filter(|m| *m < 60)?;
+            Some(hour * 60 + minute)
+        };
+        let (start, end) = range.split_once('-')?;
+        Some(Self {
+            start: minute_of_day(start)?,
+            end: minute_of_day(end)?,
+        })
+    }
+
+    /// Whether the time in `zone` (None = the system's, see
+    /// `[units] timezone`) is inside the range
+    pub fn contains_now(&self, zone: Option<Tz>) -> bool {
+        let minute = local_minute_of_day(Utc::now(), zone);
+        if self.start <= self.end {
+            (self.start..self.end).contains(&minute)
+        } else {
+            minute >= self.start || minute < self.end
+        }
+    }
+}
+
+/// Cron-style "minute hour day-of-month month day-of-week" in local time.
+/// Each field is `*`, a number, a range "8-18", a list "0,30" or a step
+/// "*/15" or "8-18/2"; days of the week run 0-6 from Sunday (7 is Sunday
+/// too). As in cron, when both day fields are set either one matching is
+/// enough.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cron {
+    /// A bit per allowed value of each field
+    fields: [u64; 5],
+    /// Whether the day-of-month and day-of-week fields were `*`
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl Cron {
+    /// Lowest and highest value of each field
+    const LIMITS: [(u32, u32); 5] = [(0, 59), (0, 23), (1, 31), (1, 12), (0, 7)];
+
+    /// Parse "0 */6 * * *" and the like (None when malformed)
+    pub fn parse(spec: &str) -> Option<Self> {
+        let parts: Vec<&str> = spec.split_whitespace().collect();
+        if parts.len() != 5 {
+            return None;
+        }
+        let mut fields = [0u64; 5];
+        for ((field, part), (low, high)) in fields.iter_mut().zip(&parts).zip(Self::LIMITS) {
+            for item in part.split(',') {
+                let (range, step) = match item.split_once('/') {
+                    Some((range, step)) => (range, step.parse::<u32>().ok().filter(|s| *s > 0)?),
+                    None => (item, 1),
+                };
+                let (start, end) = match range.split_once('-') {
+                    _ if range == "*" => (low, high),
+                    Some((start, end)) => (start.parse().ok()?, end.parse().ok()?),
+                    // "5/10" runs from 5 to the end
+                    None if step > 1 => (range.parse().ok()?, high),
+                    None => {
+                        let value = range.parse().ok()?;
+                        (value, value)
+                    }
+                };
+                if start < low || end > high || start > end {
+                    return None;
+                }
+                for value in (start..=end).step_by(step as usize) {
+                    *field |= 1 << value;
+                }
+            }
+        }
+        // Sunday as 7
+        if fields[4] & 1 << 7 != 0 {
+            fields[4] |= 1;
+        }
+        Some(Self {
+            fields,
+            any_day: parts[2] == "*",
+            any_weekday: parts[4] == "*",
+        })
+    }
+
+    /// Whether the minute `time` falls in, in `zone` (None = the system's),
+    /// is one the schedule runs at
+    pub fn matches(&self, time: DateTime<Utc>, zone: Option<Tz>) -> bool {
+        let local: NaiveDateTime = match zone {
+            Some(zone) => time.with_timezone(&zone).naive_local(),
+            None => time.with_timezone(&Local).naive_local(),
+        };
+        let has = |field: usize, value: u32| self.fields[field] & 1 << value != 0;
+        let day = has(2, local.day());
+        let weekday = has(4, local.weekday().num_days_from_sunday());
+        let day_matches = match (self.any_day, self.any_weekday) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        };
+        has(0, local.minute()) && has(1, local.hour()) && has(3, local.month()) && day_matches
+    }
+}
@@ -0,0 +1,200 @@
+//! SOTA and POTA self-spots (`[activation]`)
+//!
+//! The `self_spot` button posts the activation being worked at the radio's
+//! frequency and mode: POTA spots go to api.pota.app, SOTA spots to
+//! SOTAwatch with `[activation] sota_token`. Posting happens on a thread
+//! and the outcome comes back through `try_recv` as a status line.
+
+use crate::config::{ActivationConfig, Config};
+use crate::services::radio::{RadioMode, RadioState};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const POTA_URL: &str = "https://api.pota.app/spot";
+const SOTA_URL: &str = "https://api2.sota.org.uk/api/spots";
+/// How long the spotting site gets to answer
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Least time between spots, so a double press isn't posted twice
+const MIN_INTERVAL: Duration = Duration::from_secs(60);
+/// Sent as the spot's source
+const SOURCE: &str = "RBN VFD Display";
+
+/// Posts self-spots and reports how they went
+pub struct SelfSpotter {
+    tx: Sender<Result<String, String>>,
+    results: Receiver<Result<String, String>>,
+    last_post: Option<Instant>,
+}
+
+impl Default for SelfSpotter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SelfSpotter {
+    pub fn new() -> Self {
+        let (tx, results) = mpsc::channel();
+        Self {
+            tx,
+            results,
+            last_post: None,
+        }
+    }
+
+    /// Spot the `[activation]` at the radio's frequency, returning a status
+    /// line; the outcome follows through `try_recv`
+    pub fn post(&mut self, config: &Config, radio: &RadioState) -> Result<String, String> {
+        let spot = Spot::new(&config.activation, &config.callsign, radio)?;
+        if self
+            .last_post
+            .is_some_and(|last| last.elapsed() < MIN_INTERVAL)
+        {
+            return Err("Self-spot already posted in the last minute".to_string());
+        }
+        // Set while the post is under way too, so a second press can't
+        // double it
+        self.last_post = Some(Instant::now());
+
+        let status = format!(
+            "Self-spotting {} at {}...",
+            spot.reference,
+            config.units.frequency_with_unit(spot.frequency_khz)
+        );
+        log::info!(
+            "Self-spotting {} {} at {:.1} kHz {}",
+            spot.activator,
+            spot.reference,
+            spot.frequency_khz,
+            spot.mode
+        );
+        let token = config.activation.sota_token.expose().trim().to_string();
+        let tx = self.tx.clone();
+        thread::spawn(move || {
+            let result = spot
+                .send(&token)
+                .map(|()| format!("Spotted {} on {}", spot.reference, spot.program))
+                .map_err(|e| format!("Self-spot failed: {}", e));
+            if let Err(ref e) = result {
+                log::warn!("{}", e);
+            }
+            let _ = tx.send(result);
+        });
+        Ok(status)
+    }
+
+    /// Outcome of a posted spot, if one has finished; a failed one can be
+    /// tried again straight away
+    pub fn try_recv(&mut self) -> Option<Result<String, String>> {
+        let result = self.results.try_recv().ok()?;
+        if result.is_err() {
+            self.last_post = None;
+        }
+        Some(result)
+    }
+}
+
+/// A self-spot ready to post
+struct Spot {
+    /// "POTA" or "SOTA"
+    program: &'static str,
+    reference: String,
+    activator: String,
+    frequency_khz: f64,
+    /// Mode as the spotting sites name it, e.g. "CW" or "SSB"
+    mode: &'static str,
+    comment: String,
+}
+
+impl Spot {
+    fn new(
+        activation: &ActivationConfig,
+        callsign: &str,
+        radio: &RadioState,
+    ) -> Result<Self, String> {
+        let reference = activation.reference.trim().to_uppercase();
+        if reference.is_empty() {
+            return Err("No [activation] reference to spot".to_string());
+        }
+        let program = match activation.program.trim().to_lowercase().as_str() {
+            "pota" => "POTA",
+            "sota" => "SOTA",
+            other => return Err(format!("Unknown activation program '{}'", other)),
+        };
+        let activator = match activation.callsign.trim() {
+            "" => callsign.trim().to_uppercase(),
+            call => call.to_uppercase(),
+        };
+        if activator.is_empty() {
+            return Err("No callsign to self-spot".to_string());
+        }
+        let Some(frequency_khz) = radio.frequency_khz.filter(|_| radio.connected) else {
+            return Err("Self-spot needs the radio's frequency".to_string());
+        };
+        Ok(Self {
+            program,
+            reference,
+            activator,
+            frequency_khz,
+            mode: radio.mode.map_or("CW", mode_name),
+            comment: activation.comment.trim().to_string(),
+        })
+    }
+
+    fn send(&self, sota_token: &str) -> Result<(), String> {
+        let agent = ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build();
+        let (url, request, body) = match self.program {
+            "SOTA" => {
+                let (association, summit) = self
+                    .reference
+                    .split_once('/')
+                    .ok_or_else(|| format!("'{}' is not a summit reference", self.reference))?;
+                let body = serde_json::json!({
+                    "activatorCallsign": self.activator,
+                    "callsign": self.activator,
+                    "associationCode": association,
+                    "summitCode": summit,
+                    "frequency": format!("{:.4}", self.frequency_khz / 1000.0),
+                    "mode": self.mode.to_lowercase(),
+                    "comments": self.comment,
+                    "type": "NORMAL",
+                });
+                let request = agent
+                    .post(SOTA_URL)
+                    .set("Authorization", &format!("Bearer {}", sota_token));
+                (SOTA_URL, request, body)
+            }
+            _ => {
+                let body = serde_json::json!({
+                    "activator": self.activator,
+                    "spotter": self.activator,
+                    "reference": self.reference,
+                    "frequency": format!("{:.1}", self.frequency_khz),
+                    "mode": self.mode,
+                    "comments": self.comment,
+                    "source": SOURCE,
+                });
+                (POTA_URL, agent.post(POTA_URL), body)
+            }
+        };
+        // The URL holds no secrets, so errors can name it
+        request
+            .set("Content-Type", "application/json")
+            .send_string(&body.to_string())
+            .map(|_| ())
+            .map_err(|e| format!("{}: {}", url, e))
+    }
+}
+
+/// Mode name the spotting sites use for a radio mode
+fn mode_name(mode: RadioMode) -> &'static str {
+    match mode {
+        RadioMode::Cw | RadioMode::CwReverse => "CW",
+        RadioMode::Usb | RadioMode::Lsb => "SSB",
+        RadioMode::Rtty | RadioMode::RttyReverse => "RTTY",
+        RadioMode::Am => "AM",
+        RadioMode::Fm => "FM",
+        RadioMode::Data => "DATA",
+    }
+}
@@ -0,0 +1,266 @@
+//! Audible alerts: beep patterns, or with `[sound] morse` the callsign
+//! sent in Morse, on an active piezo buzzer (GPIO) or a tone through the
+//! sound card (ALSA, by piping raw samples to `aplay`)
+
+use crate::config::SoundConfig;
+use crate::services::alerts::Alert;
+use crate::services::gpio::OutputPin;
+use crate::services::schedule::DailyRange;
+use chrono_tz::Tz;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, SyncSender};
+use std::thread;
+use std::time::Duration;
+
+/// Sample rate of the generated tone
+const SAMPLE_RATE: u32 = 8000;
+
+/// Rise and fall of each tone, so keying doesn't click
+const RAMP_MS: u64 = 4;
+
+/// Sounds waiting to play; more are dropped rather than heard late
+const QUEUE: usize = 4;
+
+/// Beep patterns as alternating on/off times in milliseconds
+const WATCH_PATTERN: &[u64] = &[120, 100, 120];
+const DXCC_PATTERN: &[u64] = &[80, 80, 80, 80, 80];
+const SLOT_PATTERN: &[u64] = &[400];
+const SELF_PATTERN: &[u64] = &[40, 60, 40, 60, 40];
+const RULE_PATTERN: &[u64] = &[200, 100, 200, 100, 200];
+
+enum Output {
+    Piezo(OutputPin),
+    Alsa { device: String, tone_hz: u32 },
+}
+
+impl Output {
+    fn play(&mut self, pattern: &[u64]) {
+        match self {
+            Output::Piezo(pin) => {
+                for (i, ms) in pattern.iter().enumerate() {
+                    let _ = pin.set(i % 2 == 0);
+                    thread::sleep(Duration::from_millis(*ms));
+                }
+                let _ = pin.set(false);
+            }
+            Output::Alsa { device, tone_hz } => {
+                // Nowhere to report to from here; `start` checked aplay runs
+                let _ = play_tone(device, *tone_hz, pattern);
+            }
+        }
+    }
+}
+
+/// Plays alert sounds on a background thread
+pub struct AlertSound {
+    sender: SyncSender<Vec<u64>>,
+    /// Morse dot length in milliseconds, when callsigns are sent
+    morse_dot: Option<u64>,
+    /// Alert kinds that sound
+    kinds: Vec<String>,
+    /// Quiet hours as minutes of the day (start, end)
+    quiet: Option<DailyRange>,
+    /// Zone of the quiet hours (None = the system's)
+    timezone: Option<Tz>,
+}
+
+impl AlertSound {
+    /// Open the configured output, or None with `[sound] output = none`;
+    /// quiet hours are read in `timezone` (`[units] timezone`)
+    pub fn start(config: &SoundConfig, timezone: Option<Tz>) -> Result<Option<Self>, String> {
+        let mut output = match config.output.trim().to_lowercase().as_str() {
+            "" | "none" => return Ok(None),
+            "gpio" => Output::Piezo(OutputPin::open(config.piezo_pin)?),
+            "alsa" => {
+                Command::new("aplay")
+                    .arg("--version")
+                    .stdout(Stdio::null())
+                    .status()
+                    .map_err(|e| format!("Failed to run aplay: {}", e))?;
+                Output::Alsa {
+                    device: config.alsa_device.clone(),
+                    tone_hz: config.tone_hz,
+                }
+            }
+            other => return Err(format!("Unknown sound output '{}'", other)),
+        };
+        let (sender, receiver) = mpsc::sync_channel::<Vec<u64>>(QUEUE);
+        thread::spawn(move || {
+            for pattern in receiver {
+                output.play(&pattern);
+            }
+        });
+        Ok(Some(Self {
+            sender,
+            // PARIS timing: a dot is 1200 ms / WPM
+            morse_dot: config
+                .morse
+                .then(|| 1200 / u64::from(config.morse_wpm.max(1))),
+            kinds: config
+                .alerts
+                .split(',')
+                .map(|kind| kind.trim().to_lowercase())
+                .collect(),
+            quiet: DailyRange::parse(&config.quiet_hours),
+            timezone,
+        }))
+    }
+
+    /// Change which alert kinds sound, as a comma separated list
+    pub fn set_alerts(&mut self, kinds: &str) {
+        self.kinds = kinds
+            .split(',')
+            .map(|kind| kind.trim().to_lowercase())
+            .collect();
+    }
+
+    /// Sound an alert unless its kind is off or it's quiet hours
+    pub fn alert(&self, alert: &Alert) {
+        if !self.kinds.iter().any(|kind| kind == alert.kind())
+            || self
+                .quiet
+                .is_some_and(|quiet| quiet.contains_now(self.timezone))
+        {
+            return;
+        }
+        let pattern = match alert {
+            Alert::SelfSpot { .. } => SELF_PATTERN,
+            Alert::Watch { .. } => WATCH_PATTERN,
+            Alert::NewDxcc { .. } => DXCC_PATTERN,
+            Alert::NewSlot { .. } => SLOT_PATTERN,
+            Alert::Rule { .. } => RULE_PATTERN,
+        };
+        self.play(alert, pattern);
+    }
+
+    /// Beep for an alert rule, whatever the alert kinds, unless it's quiet
+    /// hours
+    pub fn beep(&self, alert: &Alert) {
+        if !self
+            .quiet
+            .is_some_and(|quiet| quiet.contains_now(self.timezone))
+        {
+            self.play(alert, RULE_PATTERN);
+        }
+    }
+
+    /// Queue the alert's callsign in Morse, or `pattern` without `morse`
+    fn play(&self, alert: &Alert, pattern: &[u64]) {
+        let pattern = match self.morse_dot {
+            Some(dot) => {
+                let call = match alert {
+                    // Who heard you
+                    Alert::SelfSpot { spotter, .. } => spotter,
+                    Alert::Watch { callsign }
+                    | Alert::NewDxcc { callsign, .. }
+                    | Alert::NewSlot { callsign, .. }
+                    | Alert::Rule { callsign, .. } => callsign,
+                };
+                morse_pattern(call, dot)
+            }
+            None => pattern.to_vec(),
+        };
+        if self.sender.try_send(pattern).is_err() {
+            log::debug!("Alert sound dropped, {} already waiting", QUEUE);
+        }
+    }
+}
+
+/// On/off times for a callsign in Morse: a dash is three dots, and the
+/// gaps are one dot within a letter and three between letters. Characters
+/// without a code are left out.
+/// Example: "K1" at 20 WPM (60 ms dots) -> [180, 60, 60, 60, 180, 180, 60, 60, 180, 60, 180, 60, 180, 60, 180]
+fn morse_pattern(callsign: &str, dot: u64) -> Vec<u64> {
+    let mut pattern: Vec<u64> = Vec::new();
+    for c in callsign.chars() {
+        let Some(code) = morse_code(c) else {
+            continue;
+        };
+        for (i, element) in code.chars().enumerate() {
+            pattern.push(if element == '-' { 3 * dot } else { dot });
+            pattern.push(if i + 1 == code.len() { 3 * dot } else { dot });
+        }
+    }
+    // No trailing gap
+    pattern.pop();
+    pattern
+}
+
+/// Dots and dashes for a letter, digit or "/"
+fn morse_code(c: char) -> Option<&'static str> {
+    Some(match c.to_ascii_uppercase() {
+        'A' => ".-",
+        'B' => "-...",
+        'C' => "-.-.",
+        'D' => "-..",
+        'E' => ".",
+        'F' => "..-.",
+        'G' => "--.",
+        'H' => "....",
+        'I' => "..",
+        'J' => ".---",
+        'K' => "-.-",
+        'L' => ".-..",
+        'M' => "--",
+        'N' => "-.",
+        'O' => "---",
+        'P' => ".--.",
+        'Q' => "--.-",
+        'R' => ".-.",
+        'S' => "...",
+        'T' => "-",
+        'U' => "..-",
+        'V' => "...-",
+        'W' => ".--",
+        'X' => "-..-",
+        'Y' => "-.--",
+        'Z' => "--..",
+        '0' => "-----",
+        '1' => ".----",
+        '2' => "..---",
+        '3' => "...--",
+        '4' => "....-",
+        '5' => ".....",
+        '6' => "-....",
+        '7' => "--...",
+        '8' => "---..",
+        '9' => "----.",
+        '/' => "-..-.",
+        _ => return None,
+    })
+}
+
+/// Pipe the pattern as 16-bit mono samples (tone for "on", silence for
+/// "off") into `aplay`
+fn play_tone(device: &str, tone_hz: u32, pattern: &[u64]) -> std::io::Result<()> {
+    let mut samples = Vec::new();
+    for (i, ms) in pattern.iter().enumerate() {
+        let count = SAMPLE_RATE as u64 * ms / 1000;
+        let ramp = (SAMPLE_RATE as u64 * RAMP_MS / 1000).min(count / 2).max(1);
+        for n in 0..count {
+            let sample = if i % 2 == 0 {
+                let phase = n as f64 * tone_hz as f64 / SAMPLE_RATE as f64;
+                // Raised-cosine edges
+                let edge = n.min(count - 1 - n).min(ramp) as f64 / ramp as f64;
+                let envelope = 0.5 - 0.5 * (edge * std::f64::consts::PI).cos();
+                ((phase * std::f64::consts::TAU).sin() * i16::MAX as f64 * 0.5 * envelope) as i16
+            } else {
+                0
+            };
+            samples.extend_from_slice(&sample.to_le_bytes());
+        }
+    }
+
+    let mut child = Command::new("aplay")
+        .args(["-q", "-D", device, "-t", "raw", "-f", "S16_LE", "-c", "1"])
+        .arg(format!("-r{}", SAMPLE_RATE))
+        .stdin(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(&samples)?;
+    }
+    child.wait()?;
+    Ok(())
+}
@@ -0,0 +1,52 @@
+//! Minimal Linux spidev access for display hardware
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::os::unix::io::AsRawFd;
+
+/// ioctl to set the maximum clock speed, _IOW('k', 4, u32)
+const SPI_IOC_WR_MAX_SPEED_HZ: u64 = 0x4004_6B04;
+
+/// An open SPI device at /dev/spidevB.C, written half-duplex
+pub struct SpiDevice {
+    file: File,
+}
+
+impl SpiDevice {
+    /// Open /dev/spidevB.C, clocked at `speed_hz` (0 keeps the default)
+    pub fn open(bus: u8, chip_select: u8, speed_hz: u32) -> Result<Self, String> {
+        let path = format!("/dev/spidev{}.{}", bus, chip_select);
+        let file = OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to open {}: {}", path, e))?;
+
+        if speed_hz > 0 {
+            // SAFETY: fd is a valid open spidev handle and the request reads a u32
+            let result = unsafe {
+                libc::ioctl(
+                    file.as_raw_fd(),
+                    SPI_IOC_WR_MAX_SPEED_HZ as _,
+                    &speed_hz as *const u32,
+                )
+            };
+            if result < 0 {
+                return Err(format!(
+                    "Failed to set {} to {} Hz: {}",
+                    path,
+                    speed_hz,
+                    std::io::Error::last_os_error()
+                ));
+            }
+        }
+        Ok(Self { file })
+    }
+
+    /// Write bytes, split to stay under the default 4 KiB spidev buffer
+    pub fn write(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        for chunk in bytes.chunks(4096) {
+            self.file.write_all(chunk)?;
+        }
+        Ok(())
+    }
+}
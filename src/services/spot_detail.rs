@@ -0,0 +1,105 @@
+//! Detail page for a selected spot: the skimmers that heard it, when, where
+//! it is, who operates it (from QRZ or HamQTH), whether its entity has been
+//! worked, how wanted it is on Club Log and when it last uploaded to LoTW
+
+use crate::models::{AggregatedSpot, Band, LatLon, Units};
+use crate::services::alerts::SpotAlerts;
+use crate::services::callbook::Operator;
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use std::time::Instant;
+
+/// Lines for the display's detail page, ending with the tune prompt, in
+/// the `[units]` formats; `operator` is the callbook answer, once there is
+/// one, and `lotw` the last LoTW upload
+/// Example: "DL1ABC 14025.3" / "Heard 12:03-12:10Z" / "Hans Mueller" /
+/// "JO62QM Germany" / "DL Germany CQ14" / "8123km 032deg" /
+/// "Sun DX 0512-1844" / "Worked" / "Most wanted #140" / "LoTW 2024-03-17" /
+/// "Sun QTH 1302-0110 GL" / "3 skimmers" / "DK9IP 23dB" / ... /
+/// "Press to tune"
+pub fn detail_lines(
+    spot: &AggregatedSpot,
+    alerts: &SpotAlerts,
+    operator: Option<&Operator>,
+    lotw: Option<NaiveDate>,
+    home: Option<LatLon>,
+    units: &Units,
+) -> Vec<String> {
+    let mut lines = vec![
+        format!("{} {}", spot.callsign, units.frequency(spot.frequency_khz)),
+        format!(
+            "Heard {}-{}{}",
+            units.time(wall_clock(spot.first_spotted)),
+            units.time(wall_clock(spot.last_spotted)),
+            units.zone()
+        ),
+    ];
+    if let Some(operator) = operator {
+        lines.extend(Some(operator.name.clone()).filter(|name| !name.is_empty()));
+        let place = format!("{} {}", operator.grid, operator.country);
+        lines.extend(Some(place.trim().to_string()).filter(|place| !place.is_empty()));
+    }
+
+    let dxcc = alerts.dxcc();
+    if let Some(entity) = dxcc.entity(&spot.callsign) {
+        match dxcc.details(entity) {
+            Some(details) => {
+                lines.push(format!("{} {} CQ{}", entity, details.name, details.cq_zone));
+                if let Some(home) = home {
+                    let (km, bearing) = home.distance_bearing(&details.location);
+                    lines.push(format!("{} {:03.0}deg", units.distance(km), bearing));
+                }
+                lines.extend(sun_line("DX", details.location, units));
+            }
+            None => lines.push(entity.to_string()),
+        }
+        let band = Band::from_khz(spot.frequency_khz);
+        lines.push(alerts.worked_status(entity, band).to_string());
+        if let Some(rank) = alerts.rank(&spot.callsign) {
+            lines.push(format!("Most wanted #{}", rank));
+        }
+    }
+
+    if let Some(uploaded) = lotw.and_then(|date| date.and_hms_opt(12, 0, 0)) {
+        lines.push(format!("LoTW {}", units.date(uploaded.and_utc())));
+    }
+
+    if let Some(home) = home {
+        lines.extend(sun_line("QTH", home, units));
+    }
+
+    let mut skimmers = spot.skimmers.clone();
+    skimmers.sort_by_key(|(_, snr)| std::cmp::Reverse(*snr));
+    lines.push(match skimmers.len() {
+        1 => "1 skimmer".to_string(),
+        count => format!("{} skimmers", count),
+    });
+    lines.extend(
+        skimmers
+            .iter()
+            .map(|(skimmer, snr)| format!("{} {}dB", skimmer, snr)),
+    );
+    lines.push("Press to tune".to_string());
+    lines
+}
+
+/// Today's sunrise and sunset at one end of the path, with "GL" when it is
+/// on the grey line now
+/// Example: "Sun DX 0512-1844 GL"
+fn sun_line(end: &str, location: LatLon, units: &Units) -> Option<String> {
+    let now = Utc::now();
+    let (rise, set) = location.sun_times(now.date_naive())?;
+    let greyline = if location.in_greyline(now) { " GL" } else { "" };
+    Some(format!(
+        "Sun {} {}-{}{}",
+        end,
+        units.time_compact(rise),
+        units.time_compact(set),
+        greyline
+    ))
+}
+
+/// Wall clock time of an instant
+fn wall_clock(instant: Instant) -> DateTime<Utc> {
+    let ago = TimeDelta::from_std(instant.elapsed()).unwrap_or(TimeDelta::zero());
+    Utc::now() - ago
+}
@@ -2,32 +2,44 @@ use crate::models::{AggregatedSpot, RawSpot};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+/// Buffered updates per subscriber before the slowest one starts missing some
+const UPDATE_CHANNEL_CAPACITY: usize = 256;
 
 /// Thread-safe store for aggregated spots
 #[derive(Clone)]
 pub struct SpotStore {
     spots: Arc<Mutex<HashMap<String, AggregatedSpot>>>,
+    updates: broadcast::Sender<AggregatedSpot>,
 }
 
 impl SpotStore {
     pub fn new() -> Self {
+        let (updates, _) = broadcast::channel(UPDATE_CHANNEL_CAPACITY);
         Self {
             spots: Arc::new(Mutex::new(HashMap::new())),
+            updates,
         }
     }
 
+    /// Subscribe to new and updated spots (unfiltered)
+    pub fn subscribe(&self) -> broadcast::Receiver<AggregatedSpot> {
+        self.updates.subscribe()
+    }
+
     /// Add or update a spot (stores all spots, filtering happens at retrieval)
     pub fn add_spot(&self, raw: RawSpot) {
         let center_freq = raw.frequency_khz.round();
         let key = format!("{}|{:.0}", raw.spotted_callsign, center_freq);
 
         if let Ok(mut spots) = self.spots.lock() {
-            if let Some(existing) = spots.get_mut(&key) {
-                existing.update(&raw);
-            } else {
-                let spot = AggregatedSpot::from_raw(&raw);
-                spots.insert(key, spot);
-            }
+            let spot = spots
+                .entry(key)
+                .and_modify(|existing| existing.update(&raw))
+                .or_insert_with(|| AggregatedSpot::from_raw(&raw));
+            // No subscribers is fine
+            let _ = self.updates.send(spot.clone());
         }
     }
 
@@ -94,3 +106,9 @@ impl SpotStore {
         }
     }
 }
+
+impl Default for SpotStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,290 @@
+//! Cluster server re-sending the filtered spots (`[telnet]`)
+//!
+//! Loggers and band-map programs connect as they would to any DX cluster:
+//! they're asked for a callsign, then get a "DX de" line for each spot when
+//! it first passes the filters, so one RBN login feeds every program in the
+//! shack. `SH/DX [n]` lists the last lines sent and `BYE` disconnects;
+//! other commands just get the prompt back.
+
+use crate::config::TelnetConfig;
+use crate::models::AggregatedSpot;
+use chrono::Utc;
+use std::collections::{HashSet, VecDeque};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+
+/// Least time between passes over the spots
+const UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+/// Longest wait for spots before the server thread looks at its clients
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Lines kept for `SH/DX`
+const RECENT_KEPT: usize = 100;
+/// Lines `SH/DX` lists without a count
+const SHOW_DEFAULT: usize = 10;
+/// Unfinished input a client may send before it's dropped
+const MAX_INPUT: usize = 1024;
+/// Name in the greeting and prompt
+const NODE: &str = "RBN-VFD";
+
+/// Handle to the cluster server thread
+pub struct TelnetServer {
+    address: String,
+    /// "DX de" lines for the server thread
+    tx: mpsc::Sender<String>,
+    /// Clients connected now, kept up by the server thread
+    clients: Arc<AtomicUsize>,
+    /// Spots sent and still passing the filters, by `key`
+    announced: HashSet<String>,
+    last_update: Option<Instant>,
+}
+
+impl TelnetServer {
+    /// Start listening on `[telnet] bind:port`
+    pub fn start(config: &TelnetConfig) -> Result<Self, String> {
+        let address = format!("{}:{}", config.bind, config.port);
+        let listener = TcpListener::bind(&address)
+            .map_err(|e| format!("Failed to listen on {}: {}", address, e))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| format!("Failed to listen on {}: {}", address, e))?;
+
+        let (tx, rx) = mpsc::channel();
+        let clients = Arc::new(AtomicUsize::new(0));
+        let count = clients.clone();
+        std::thread::spawn(move || serve(listener, rx, count));
+        log::info!("Cluster server listening on {}", address);
+
+        Ok(Self {
+            address,
+            tx,
+            clients,
+            announced: HashSet::new(),
+            last_update: None,
+        })
+    }
+
+    /// Address the server is listening on
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Clients connected now
+    pub fn clients(&self) -> usize {
+        self.clients.load(Ordering::Relaxed)
+    }
+
+    /// Send a "DX de" line for each spot that newly passes the filters;
+    /// `spots` is everything passing them now
+    pub fn update(&mut self, spots: &[AggregatedSpot]) {
+        if self
+            .last_update
+            .is_some_and(|last| last.elapsed() < UPDATE_INTERVAL)
+        {
+            return;
+        }
+        self.last_update = Some(Instant::now());
+
+        // Spots that stop passing are sent again if they come back
+        let current: HashSet<String> = spots.iter().map(key).collect();
+        self.announced.retain(|key| current.contains(key));
+        for spot in spots {
+            if self.announced.insert(key(spot)) {
+                let _ = self.tx.send(dx_line(spot));
+            }
+        }
+    }
+}
+
+/// A spot by callsign and frequency (to 100 Hz)
+fn key(spot: &AggregatedSpot) -> String {
+    format!("{}|{:.1}", spot.callsign.to_uppercase(), spot.frequency_khz)
+}
+
+/// Cluster spot line, heard by the skimmer with the best SNR
+/// Example: "DX de DK9IP-#:    14025.3  DL1ABC       CW 23 dB 28 WPM                1423Z"
+fn dx_line(spot: &AggregatedSpot) -> String {
+    let spotter = spot
+        .skimmers
+        .iter()
+        .max_by_key(|(_, snr)| *snr)
+        .map_or("", |(skimmer, _)| skimmer.as_str());
+    let comment = format!(
+        "{} {} dB {:.0} WPM",
+        spot.mode.to_uppercase(),
+        spot.highest_snr,
+        spot.average_speed
+    );
+    format!(
+        "{:<16}{:>8.1}  {:<12} {:<30} {}",
+        format!("DX de {}:", spotter.to_uppercase()),
+        spot.frequency_khz,
+        spot.callsign.to_uppercase(),
+        comment,
+        Utc::now().format("%H%MZ")
+    )
+}
+
+/// Accept clients, answer their commands and pass lines from `rx` to the
+/// ones logged in, until the handle is dropped
+fn serve(listener: TcpListener, rx: mpsc::Receiver<String>, count: Arc<AtomicUsize>) {
+    let mut clients: Vec<Client> = Vec::new();
+    let mut recent: VecDeque<String> = VecDeque::new();
+    loop {
+        loop {
+            match listener.accept() {
+                Ok((stream, peer)) => {
+                    if let Some(client) = Client::new(stream, peer) {
+                        clients.push(client);
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    log::warn!("Cluster server: {}", e);
+                    break;
+                }
+            }
+        }
+        clients.retain_mut(|client| client.read(&recent));
+
+        let first = match rx.recv_timeout(POLL_INTERVAL) {
+            Ok(line) => Some(line),
+            Err(mpsc::RecvTimeoutError::Timeout) => None,
+            Err(mpsc::RecvTimeoutError::Disconnected) => return,
+        };
+        for line in first
+            .into_iter()
+            .chain(std::iter::from_fn(|| rx.try_recv().ok()))
+        {
+            clients.retain_mut(|client| client.spot(&line));
+            recent.push_front(line);
+            recent.truncate(RECENT_KEPT);
+        }
+        count.store(clients.len(), Ordering::Relaxed);
+    }
+}
+
+/// A connected program
+struct Client {
+    stream: TcpStream,
+    peer: SocketAddr,
+    /// Callsign it logged in with; None until then
+    call: Option<String>,
+    /// Bytes read since the last full line
+    input: Vec<u8>,
+}
+
+impl Client {
+    /// Ask a new connection for its callsign
+    fn new(stream: TcpStream, peer: SocketAddr) -> Option<Self> {
+        if let Err(e) = stream.set_nonblocking(true) {
+            log::warn!("Cluster client {}: {}", peer, e);
+            return None;
+        }
+        let mut client = Self {
+            stream,
+            peer,
+            call: None,
+            input: Vec::new(),
+        };
+        client.write("login: ").then_some(client)
+    }
+
+    /// Take in what the client sent and answer any full lines; false once
+    /// it has gone
+    fn read(&mut self, recent: &VecDeque<String>) -> bool {
+        let mut buf = [0u8; 512];
+        loop {
+            match self.stream.read(&mut buf) {
+                Ok(0) => return self.gone(),
+                Ok(n) => self.input.extend_from_slice(&buf[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(_) => return self.gone(),
+            }
+        }
+        while let Some(end) = self.input.iter().position(|&b| b == b'\r' || b == b'\n') {
+            // Printable ASCII only, which also drops telnet negotiation
+            let line: String = self
+                .input
+                .drain(..=end)
+                .filter(|b| (0x20..0x7f).contains(b))
+                .map(char::from)
+                .collect();
+            let line = line.trim();
+            if !line.is_empty() && !self.command(line, recent) {
+                return self.gone();
+            }
+        }
+        self.input.len() <= MAX_INPUT || self.gone()
+    }
+
+    /// Answer one line; false to disconnect
+    fn command(&mut self, line: &str, recent: &VecDeque<String>) -> bool {
+        let Some(call) = self.call.clone() else {
+            let call = line
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .to_uppercase();
+            log::info!("Cluster client {} logged in as {}", self.peer, call);
+            let greeting = format!(
+                "Hello {}, this is {} passing on filtered RBN spots\r\n",
+                call, NODE
+            );
+            self.call = Some(call.clone());
+            return self.write(&greeting) && self.prompt(&call);
+        };
+
+        let line = line.to_ascii_uppercase();
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or_default();
+        match command {
+            "B" | "BYE" | "Q" | "QUIT" => {
+                let _ = self.write("73\r\n");
+                false
+            }
+            _ if command.starts_with("SH/DX") || command.starts_with("SHOW/DX") => {
+                // "SH/DX/30" or "SH/DX 30"
+                let shown = command
+                    .rsplit('/')
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .or_else(|| words.next().and_then(|n| n.parse().ok()))
+                    .unwrap_or(SHOW_DEFAULT);
+                let lines: String = recent
+                    .iter()
+                    .take(shown)
+                    .map(|line| format!("{}\r\n", line))
+                    .collect();
+                self.write(&lines) && self.prompt(&call)
+            }
+            _ => self.prompt(&call),
+        }
+    }
+
+    fn prompt(&mut self, call: &str) -> bool {
+        self.write(&format!("{} de {} >\r\n", call, NODE))
+    }
+
+    /// Send a spot line once logged in; false once the client has gone or
+    /// can't keep up
+    fn spot(&mut self, line: &str) -> bool {
+        self.call.is_none() || self.write(&format!("{}\r\n", line)) || self.gone()
+    }
+
+    /// false when the write fails, including when the client's buffer is
+    /// full
+    fn write(&mut self, text: &str) -> bool {
+        self.stream.write_all(text.as_bytes()).is_ok()
+    }
+
+    fn gone(&self) -> bool {
+        match self.call {
+            Some(ref call) => log::info!("Cluster client {} ({}) left", self.peer, call),
+            None => log::debug!("Cluster client {} left", self.peer),
+        }
+        false
+    }
+}
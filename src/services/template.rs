@@ -0,0 +1,315 @@
+//! User-defined display line templates (`layout = template`)
+//!
+//! A template is text with `{field}` or `{field:spec}` placeholders, where
+//! spec is an optional `<` or `>` alignment and a width, e.g.
+//! `{mhz:>6} {call:<8} {snr}dB`. Lines are separated by `|`; a single line
+//! shows one spot per row, several lines make a page per spot. A trailing
+//! placeholder without a width scrolls when it doesn't fit.
+//!
+//! In a page, a line starting with `^` is shown at double size on displays
+//! that can, taking the row below as well, e.g. `^{call}|{freq} {snr}dB`.
+//!
+//! `{utc}`, `{local}`, `{session}` and `{received}` place the clock view's
+//! times and session counters, e.g. `{call}|{freq}|{utc}Z {session}`.
+
+use crate::models::{format_offset, AggregatedSpot, Band, Units};
+use crate::services::display::glyphs::Glyph;
+use chrono::{DateTime, Utc};
+
+/// A spot attribute a placeholder can show
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    /// Frequency as `[units]` says, "14025.0" or "14.025"
+    Freq,
+    /// Frequency in MHz with three decimals, "14.025"
+    Mhz,
+    /// Whole kHz, "14025"
+    Khz,
+    Call,
+    Snr,
+    Wpm,
+    Mode,
+    Band,
+    /// Minutes and seconds since last spotted, "1m05s"
+    Age,
+    /// Number of skimmer reports
+    Count,
+    /// Offset from the VFO for the nearest spot, blank otherwise
+    Offset,
+    BandGlyph,
+    ModeGlyph,
+    /// UTC time of day, "14:25"
+    Utc,
+    /// Local time of day, "08:25" or "8:25am"
+    Local,
+    /// Time since start, "1:23:45"
+    Session,
+    /// Spots received since start
+    Received,
+}
+
+/// Time and session counters for the clock placeholders
+#[derive(Debug, Clone, Copy)]
+pub struct Clock {
+    pub now: DateTime<Utc>,
+    /// Seconds since start
+    pub session_secs: u64,
+    /// Spots received since start
+    pub received: u64,
+}
+
+impl Clock {
+    /// Session length as "1:23:45"
+    pub fn session(&self) -> String {
+        let elapsed = self.session_secs;
+        format!(
+            "{}:{:02}:{:02}",
+            elapsed / 3600,
+            elapsed / 60 % 60,
+            elapsed % 60
+        )
+    }
+}
+
+impl Field {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "freq" => Field::Freq,
+            "mhz" => Field::Mhz,
+            "khz" => Field::Khz,
+            "call" => Field::Call,
+            "snr" => Field::Snr,
+            "wpm" => Field::Wpm,
+            "mode" => Field::Mode,
+            "band" => Field::Band,
+            "age" => Field::Age,
+            "count" => Field::Count,
+            "offset" => Field::Offset,
+            "bandglyph" => Field::BandGlyph,
+            "modeglyph" => Field::ModeGlyph,
+            "utc" => Field::Utc,
+            "local" => Field::Local,
+            "session" => Field::Session,
+            "received" => Field::Received,
+            _ => return None,
+        })
+    }
+
+    /// Numbers line up on the right unless the template says otherwise
+    fn right_aligned(self) -> bool {
+        matches!(
+            self,
+            Field::Freq
+                | Field::Mhz
+                | Field::Khz
+                | Field::Snr
+                | Field::Wpm
+                | Field::Count
+                | Field::Received
+        )
+    }
+
+    fn value(
+        self,
+        spot: &AggregatedSpot,
+        vfo_offset: Option<f64>,
+        units: &Units,
+        clock: &Clock,
+    ) -> String {
+        match self {
+            Field::Freq => units.frequency(spot.frequency_khz),
+            Field::Mhz => format!("{:.3}", spot.frequency_khz / 1000.0),
+            Field::Khz => format!("{:.0}", spot.frequency_khz),
+            Field::Call => spot.callsign.clone(),
+            Field::Snr => spot.highest_snr.to_string(),
+            Field::Wpm => (spot.average_speed.round() as i32).to_string(),
+            Field::Mode => spot.mode.clone(),
+            Field::Band => Band::from_khz(spot.frequency_khz)
+                .map(Band::name)
+                .unwrap_or_default()
+                .to_string(),
+            Field::Age => {
+                let age = spot.age_seconds();
+                format!("{}m{:02}s", age / 60, age % 60)
+            }
+            Field::Count => spot.spot_count.to_string(),
+            Field::Offset => vfo_offset.map(format_offset).unwrap_or_default(),
+            Field::BandGlyph => Band::from_khz(spot.frequency_khz)
+                .map_or(' ', |band| Glyph::Band(band).marker())
+                .to_string(),
+            Field::ModeGlyph => Glyph::for_mode(&spot.mode).marker().to_string(),
+            Field::Utc => Units {
+                local_time: false,
+                ..*units
+            }
+            .time(clock.now),
+            Field::Local => Units {
+                local_time: true,
+                ..*units
+            }
+            .time(clock.now),
+            Field::Session => clock.session(),
+            Field::Received => clock.received.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Part {
+    Text(String),
+    Field {
+        field: Field,
+        width: Option<usize>,
+        /// None uses the field's natural alignment
+        right: Option<bool>,
+    },
+}
+
+/// One rendered template line: fixed text and a trailing scrolling field
+pub struct TemplateLine {
+    pub fixed: String,
+    pub scroll: String,
+    /// Marked `^` for double size
+    pub large: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Line {
+    parts: Vec<Part>,
+    large: bool,
+}
+
+/// A parsed display template
+#[derive(Debug, Clone)]
+pub struct LineTemplate {
+    lines: Vec<Line>,
+}
+
+impl LineTemplate {
+    /// Parse a template; unknown placeholders are kept as literal text so
+    /// mistakes show up on the display
+    pub fn parse(template: &str) -> Self {
+        let lines = template
+            .split('|')
+            .map(|line| Line {
+                parts: parse_line(line.strip_prefix('^').unwrap_or(line)),
+                large: line.starts_with('^'),
+            })
+            .collect();
+        Self { lines }
+    }
+
+    /// Whether each spot gets a page of several lines rather than one row
+    pub fn is_paged(&self) -> bool {
+        self.lines.len() > 1
+    }
+
+    /// Render every template line for a spot
+    pub fn render(
+        &self,
+        spot: &AggregatedSpot,
+        vfo_offset: Option<f64>,
+        units: &Units,
+        clock: &Clock,
+    ) -> Vec<TemplateLine> {
+        self.lines
+            .iter()
+            .map(|line| TemplateLine {
+                large: line.large,
+                ..render_line(&line.parts, spot, vfo_offset, units, clock)
+            })
+            .collect()
+    }
+}
+
+fn parse_line(line: &str) -> Vec<Part> {
+    let mut parts = Vec::new();
+    let mut rest = line;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        let placeholder = &rest[start + 1..start + len];
+        match parse_placeholder(placeholder) {
+            Some(part) => {
+                if start > 0 {
+                    parts.push(Part::Text(rest[..start].to_string()));
+                }
+                parts.push(part);
+            }
+            None => parts.push(Part::Text(rest[..start + len + 1].to_string())),
+        }
+        rest = &rest[start + len + 1..];
+    }
+    if !rest.is_empty() {
+        parts.push(Part::Text(rest.to_string()));
+    }
+    parts
+}
+
+fn parse_placeholder(placeholder: &str) -> Option<Part> {
+    let (name, spec) = placeholder.split_once(':').unwrap_or((placeholder, ""));
+    let field = Field::parse(name.trim())?;
+    let spec = spec.trim();
+    let (right, digits) = match spec.chars().next() {
+        Some('<') => (Some(false), &spec[1..]),
+        Some('>') => (Some(true), &spec[1..]),
+        _ => (None, spec),
+    };
+    let width = if digits.is_empty() {
+        None
+    } else {
+        Some(digits.parse().ok()?)
+    };
+    Some(Part::Field {
+        field,
+        width,
+        right,
+    })
+}
+
+fn render_line(
+    parts: &[Part],
+    spot: &AggregatedSpot,
+    vfo_offset: Option<f64>,
+    units: &Units,
+    clock: &Clock,
+) -> TemplateLine {
+    let mut fixed = String::new();
+    for (index, part) in parts.iter().enumerate() {
+        match part {
+            Part::Text(text) => fixed.push_str(text),
+            Part::Field {
+                field, width: None, ..
+            } if index == parts.len() - 1 => {
+                return TemplateLine {
+                    fixed,
+                    scroll: field.value(spot, vfo_offset, units, clock),
+                    large: false,
+                };
+            }
+            Part::Field {
+                field,
+                width,
+                right,
+            } => {
+                let value = field.value(spot, vfo_offset, units, clock);
+                let Some(width) = *width else {
+                    fixed.push_str(&value);
+                    continue;
+                };
+                let value: String = value.chars().take(width).collect();
+                if right.unwrap_or(field.right_aligned()) {
+                    fixed.push_str(&format!("{:>width$}", value, width = width));
+                } else {
+                    fixed.push_str(&format!("{:<width$}", value, width = width));
+                }
+            }
+        }
+    }
+    TemplateLine {
+        fixed,
+        scroll: String::new(),
+        large: false,
+    }
+}
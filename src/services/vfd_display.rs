@@ -1,26 +1,117 @@
-use crate::models::AggregatedSpot;
+use crate::config::DisplayConfig;
+use crate::models::{
+    find_spot, format_offset, nearest_spot, AggregatedSpot, Announcement, AnnouncementKind, Band,
+    BandMap, LatLon, Propagation, Region, Units,
+};
+use crate::services::alerts::Alert;
+use crate::services::brightness::BrightnessControl;
+use crate::services::display::charset::Charset;
+use crate::services::display::color::{band_color, ColorSpan, Rgb, OFFLINE};
+use crate::services::display::glyphs::{strip_glyphs, Glyph, GlyphSlots};
+use crate::services::display::{create_driver, DisplayDriver};
+use crate::services::schedule::DailyRange;
+use crate::services::template::{Clock, LineTemplate, TemplateLine};
+use chrono::{DateTime, Utc};
 use rand::Rng;
-use serialport::SerialPort;
-use std::io::Write;
+use std::collections::{HashMap, VecDeque};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-const DISPLAY_WIDTH: usize = 20;
-const DISPLAY_LINES: usize = 2;
-
-// VFD commands - simple protocol without ANSI escape sequences
-const CLEAR_DISPLAY: &[u8] = &[0x0C]; // Form feed - clear and home cursor
+/// Feed, filter and radio state shown in the status footer
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DisplayStatus {
+    /// Connected to the RBN server
+    pub connected: bool,
+    /// Active filters (see `Config::filter_summary`)
+    pub filters: String,
+    /// Radio state (see `RadioState::summary`), empty without radio control
+    pub radio: String,
+}
 
 /// VFD Display controller
 pub struct VfdDisplay {
-    port: Option<Box<dyn SerialPort>>,
+    /// Hardware display opened on a port
+    primary: Option<DisplayOutput>,
+    /// Extra outputs (web, MQTT, ...) fed the same frames
+    mirrors: Vec<DisplayOutput>,
+    driver_config: DisplayConfig,
     port_name: String,
+    width: usize,
     scroll_index: usize,
     scroll_interval: Duration,
     last_update: Instant,
     force_random_mode: bool,
     random_char_percent: u32,
     random_state: RandomCharState,
-    current_lines: [String; 2],
+    current_lines: Vec<String>,
+    /// Color hints for `current_lines` (none in random mode)
+    current_colors: Vec<Vec<ColorSpan>>,
+    /// Rows of `current_lines` to show at double size where supported
+    current_large: Vec<bool>,
+    /// Callsign colors from the alert rules (watch list, worked)
+    tints: HashMap<String, Rgb>,
+    /// Station location and spotted callsigns' entity locations, for the
+    /// grey-line marker
+    home: Option<LatLon>,
+    locations: HashMap<String, LatLon>,
+    /// IARU region for band map edges (`[station] region`)
+    region: Option<Region>,
+    /// How frequencies and times are written (`[units]`)
+    units: Units,
+    /// Spot page being shown, re-rendered each update for marquee fields
+    page: Vec<PageLine>,
+    /// When the current page went up (marquee time base)
+    marquee_start: Instant,
+    /// Views rotated through while spots are shown
+    views: Vec<View>,
+    view_index: usize,
+    view_started: Instant,
+    /// Rotation is paused until then after a manual view change
+    view_hold_until: Option<Instant>,
+    /// Where the current view is in a list longer than the display
+    position: Option<ListPosition>,
+    /// Automatic page flips pause until then after a manual page step
+    page_hold_until: Option<Instant>,
+    /// Radio frequency from CAT read-back, for the band map
+    vfo_khz: Option<f64>,
+    /// Last state sent to the activity GPO
+    activity_shown: Option<bool>,
+    /// Scheduled / ambient brightness
+    brightness: BrightnessControl,
+    /// Parsed `[display] template` for the template layout
+    template: LineTemplate,
+    /// Spot picked with the front-panel encoder (callsign, kHz) and when
+    /// the selection list gives way to the normal views again
+    selection: Option<(String, f64)>,
+    selection_until: Option<Instant>,
+    /// Detail page for the selected spot, empty when not shown
+    detail: Vec<String>,
+    /// Session start and spots received since, for the clock view
+    session_started: Instant,
+    session_spots: u64,
+    /// Items of the status footer, none for no footer
+    footer: Vec<FooterItem>,
+    status: DisplayStatus,
+    /// When the last spot arrived from RBN
+    last_spot: Option<Instant>,
+    /// Recent WWV lines and announcements from the feed, newest first
+    announcements: VecDeque<Announcement>,
+    /// Callsigns that hit the watch list, newest first
+    watched: VecDeque<String>,
+    /// Indices merged from the WWV/WCY reports so far, the latest report,
+    /// and which way the solar flux went in it
+    propagation: Option<Propagation>,
+    propagation_report: Option<Announcement>,
+    sfi_trend: Option<Glyph>,
+    /// When the attention animation for a rare spot started, and whether
+    /// its highlighted step is showing
+    attention_started: Option<Instant>,
+    attention_shown: bool,
+    /// Parsed `[display] off_hours`
+    off_hours: Option<DailyRange>,
+    /// Kept on through the off hours until then after a wake-up
+    awake_until: Option<Instant>,
+    /// The hardware display is switched off for the off hours
+    asleep: bool,
 }
 
 struct RandomCharState {
@@ -45,16 +136,57 @@ impl Default for RandomCharState {
 
 impl VfdDisplay {
     pub fn new() -> Self {
+        let driver_config = DisplayConfig::default();
         Self {
-            port: None,
+            primary: None,
+            mirrors: Vec::new(),
             port_name: String::new(),
+            width: driver_config.columns,
+            current_lines: vec![String::new(); driver_config.rows],
+            current_colors: Vec::new(),
+            current_large: Vec::new(),
+            tints: HashMap::new(),
+            home: None,
+            locations: HashMap::new(),
+            region: None,
+            units: Units::default(),
+            brightness: BrightnessControl::new(&driver_config),
+            template: LineTemplate::parse(&driver_config.template),
+            driver_config,
             scroll_index: 0,
             scroll_interval: Duration::from_secs(3),
             last_update: Instant::now(),
+            page: Vec::new(),
+            marquee_start: Instant::now(),
+            views: vec![View::Spots],
+            view_index: 0,
+            view_started: Instant::now(),
+            view_hold_until: None,
+            position: None,
+            page_hold_until: None,
+            vfo_khz: None,
             force_random_mode: false,
             random_char_percent: 20,
             random_state: RandomCharState::default(),
-            current_lines: [String::new(), String::new()],
+            activity_shown: None,
+            selection: None,
+            selection_until: None,
+            detail: Vec::new(),
+            session_started: Instant::now(),
+            session_spots: 0,
+            footer: Vec::new(),
+            status: DisplayStatus::default(),
+            last_spot: None,
+            announcements: VecDeque::new(),
+            watched: VecDeque::new(),
+            propagation: None,
+            propagation_report: None,
+            sfi_trend: None,
+            attention_started: None,
+            attention_shown: false,
+            off_hours: None,
+            awake_until: None,
+            asleep: false,
         }
     }
 
@@ -67,36 +199,412 @@ impl VfdDisplay {
             .collect()
     }
 
-    /// Open a serial port
+    /// Set the driver and grid size used by the next `open` (and the preview)
+    pub fn set_driver_config(&mut self, config: &DisplayConfig) {
+        self.driver_config = config.clone();
+        self.views = View::parse_list(&config.views);
+        self.brightness = BrightnessControl::new(config);
+        self.brightness.set_timezone(self.units.timezone);
+        self.template = LineTemplate::parse(&config.template);
+        self.footer = FooterItem::parse_list(&config.footer);
+        self.off_hours = DailyRange::parse(&config.off_hours);
+        self.view_index = 0;
+        self.page.clear();
+        self.resize(config.columns, config.rows);
+    }
+
+    /// Set the radio's VFO frequency (None when unknown)
+    pub fn set_vfo(&mut self, frequency_khz: Option<f64>) {
+        self.vfo_khz = frequency_khz;
+    }
+
+    /// Name of the view being shown
+    pub fn view_name(&self) -> &'static str {
+        self.views[self.view_index].name()
+    }
+
+    /// Step to the next (+1) or previous (-1) view and pause rotation
+    pub fn step_view(&mut self, step: isize) {
+        let count = self.views.len() as isize;
+        self.view_index = (self.view_index as isize + step).rem_euclid(count) as usize;
+        let now = Instant::now();
+        self.view_started = now;
+        self.view_hold_until = Some(now + MANUAL_VIEW_HOLD);
+        self.scroll_index = 0;
+        self.page.clear();
+    }
+
+    /// Step a list longer than the display a screenful forward (+1) or
+    /// back (-1), and hold it there for a while
+    pub fn step_page(&mut self, step: isize) {
+        let Some(position) = self.position.as_mut() else {
+            return;
+        };
+        let target = position.shown as isize + step * position.stride as isize;
+        position.shown = target.rem_euclid(position.count as isize) as usize;
+        self.scroll_index = position.shown;
+        let now = Instant::now();
+        self.page_hold_until = Some(now + MANUAL_VIEW_HOLD);
+        self.view_hold_until = Some(now + MANUAL_VIEW_HOLD);
+        self.page.clear();
+    }
+
+    /// Page being shown of a long list, e.g. "2/5" ("1/1" when it fits)
+    pub fn page_label(&self) -> String {
+        self.position
+            .map_or("1/1".to_string(), |position| position.label())
+    }
+
+    /// Note what a view stepping through a long list shows, and move on by
+    /// one unless a manual page step is holding it
+    fn show_position(&mut self, shown: usize, count: usize, stride: usize) {
+        self.position = Some(ListPosition {
+            shown,
+            count,
+            stride,
+        });
+        if self
+            .page_hold_until
+            .is_none_or(|until| Instant::now() >= until)
+        {
+            self.scroll_index = (shown + 1) % count;
+        }
+    }
+
+    /// Show the spot list with `spot` marked for a while (None to go back
+    /// to the normal views)
+    pub fn select_spot(&mut self, spot: Option<&AggregatedSpot>) {
+        self.selection = spot.map(|spot| (spot.callsign.clone(), spot.frequency_khz));
+        self.selection_until = spot.map(|_| Instant::now() + SELECTION_HOLD);
+        self.detail.clear();
+        self.page.clear();
+    }
+
+    /// Show a detail page (see `detail_lines`) for `spot` for a while
+    pub fn show_detail(&mut self, spot: &AggregatedSpot, lines: Vec<String>) {
+        self.select_spot(Some(spot));
+        self.detail = lines;
+        self.scroll_index = 0;
+    }
+
+    /// Replace the detail page's lines if it is still up for `callsign`
+    /// (e.g. once a callbook answer arrives)
+    pub fn update_detail(&mut self, callsign: &str, lines: Vec<String>) {
+        let showing = self
+            .selection
+            .as_ref()
+            .is_some_and(|(call, _)| call.eq_ignore_ascii_case(callsign));
+        if showing && self.showing_detail() {
+            self.detail = lines;
+        }
+    }
+
+    /// Whether the detail page is up, so a press tunes rather than opens it
+    pub fn showing_detail(&self) -> bool {
+        !self.detail.is_empty()
+    }
+
+    /// Set callsign color hints (see `SpotAlerts::tint`)
+    pub fn set_tints(&mut self, tints: HashMap<String, Rgb>) {
+        self.tints = tints;
+    }
+
+    /// Set the station location and the entity location of each spotted
+    /// callsign (see `SpotAlerts::location`)
+    pub fn set_locations(&mut self, home: Option<LatLon>, locations: HashMap<String, LatLon>) {
+        self.home = home;
+        self.locations = locations;
+    }
+
+    /// Set the station's IARU region, for the band map's band edges
+    pub fn set_region(&mut self, region: Option<Region>) {
+        self.region = region;
+    }
+
+    /// Set how frequencies, times and dates are written, and the zone of
+    /// the off hours and brightness schedule
+    pub fn set_units(&mut self, units: Units) {
+        self.units = units;
+        self.brightness.set_timezone(units.timezone);
+    }
+
+    /// Whether either end of the path to a spot is near sunrise or sunset
+    fn greyline(&self, spot: &AggregatedSpot) -> bool {
+        let now = Utc::now();
+        self.home
+            .iter()
+            .chain(self.locations.get(&spot.callsign))
+            .any(|location| location.in_greyline(now))
+    }
+
+    /// Mark a highlighted spot's line when its path is on the grey line,
+    /// in place of the space before the callsign
+    fn mark_greyline(&self, line: &mut PageLine, spot: &AggregatedSpot) {
+        let marker = Glyph::GreyLine.marker();
+        if line.fixed.contains(marker) || !self.greyline(spot) {
+            return;
+        }
+        if line.fixed.ends_with(' ') {
+            line.fixed.pop();
+        }
+        line.fixed.push(marker);
+    }
+
+    /// Count a spot received from RBN toward the session total
+    pub fn count_spot(&mut self) {
+        self.session_spots += 1;
+        self.last_spot = Some(Instant::now());
+    }
+
+    /// Keep a WWV line or announcement from the feed for the cluster view
+    pub fn add_announcement(&mut self, announcement: Announcement) {
+        if let Some(report) = announcement.propagation() {
+            let earlier = self.propagation.unwrap_or_default();
+            self.sfi_trend = match (earlier.sfi, report.sfi) {
+                (Some(old), Some(new)) if new > old => Some(Glyph::TrendUp),
+                (Some(old), Some(new)) if new < old => Some(Glyph::TrendDown),
+                (_, Some(_)) => None,
+                (_, None) => self.sfi_trend,
+            };
+            self.propagation = Some(report.or(earlier));
+            self.propagation_report = Some(announcement.clone());
+        }
+        self.announcements.push_front(announcement);
+        self.announcements.truncate(ANNOUNCEMENTS_KEPT);
+        // Show it now rather than at the next page flip
+        if matches!(
+            self.views[self.view_index],
+            View::Cluster | View::Propagation
+        ) {
+            self.page.clear();
+        }
+    }
+
+    /// Latest indices with an up/down arrow glyph after the solar flux when
+    /// it changed, e.g. "SFI 142^ A 8 K 2"
+    fn propagation_widget(&self) -> Option<String> {
+        let propagation = self.propagation?;
+        let mut widget = propagation.summary();
+        if let (Some(trend), Some(sfi)) = (self.sfi_trend, propagation.sfi) {
+            widget.insert(format!("SFI {}", sfi).len(), trend.marker());
+        }
+        Some(widget)
+    }
+
+    /// Propagation view: the indices, then where and when they came from
+    /// Example: "SFI 142^ A 8 K 2" / "WWV 14:23Z W0MU"
+    fn propagation_lines(&self) -> Vec<PageLine> {
+        let (Some(widget), Some(report)) = (self.propagation_widget(), &self.propagation_report)
+        else {
+            return vec![PageLine::scrolling("No WWV report yet".to_string())];
+        };
+        let source = if report.kind == AnnouncementKind::Wcy {
+            "WCY"
+        } else {
+            "WWV"
+        };
+        vec![
+            PageLine::scrolling(widget),
+            PageLine::scrolling(format!(
+                "{} {}{} {}",
+                source,
+                self.units.time(report.received),
+                self.units.zone(),
+                report.from
+            )),
+        ]
+    }
+
+    /// Change which alert kinds draw attention (`[display] attention`)
+    pub fn set_attention(&mut self, kinds: &str) {
+        self.driver_config.attention = kinds.to_string();
+    }
+
+    /// Draw the eye to a rare spot when its alert kind is in
+    /// `[display] attention`: outputs with notices of their own get one,
+    /// the rest flash (see `animate_attention`)
+    pub fn alert(&mut self, alert: &Alert) {
+        if let Alert::Watch { callsign } = alert {
+            self.wake();
+            let callsign = callsign.to_uppercase();
+            self.watched.retain(|watched| *watched != callsign);
+            self.watched.push_front(callsign);
+            self.watched.truncate(WATCHED_KEPT);
+        }
+        if !self
+            .driver_config
+            .attention
+            .split(',')
+            .any(|kind| kind.trim().eq_ignore_ascii_case(alert.kind()))
+        {
+            return;
+        }
+        let text = alert.to_string();
+        for output in self.primary.iter_mut().chain(self.mirrors.iter_mut()) {
+            if output.driver.capabilities().notify {
+                let _ = output.driver.notify(&text);
+            }
+        }
+        self.attention_started = Some(Instant::now());
+    }
+
+    /// Step the attention animation, ending in the normal state
+    fn animate_attention(&mut self, now: Instant) {
+        let Some(started) = self.attention_started else {
+            return;
+        };
+        let elapsed = now.duration_since(started);
+        if elapsed >= ATTENTION_LENGTH {
+            self.attention_started = None;
+        }
+        let highlight = elapsed < ATTENTION_LENGTH
+            && (elapsed.as_millis() / ATTENTION_STEP.as_millis()).is_multiple_of(2);
+        if highlight == self.attention_shown {
+            return;
+        }
+        self.attention_shown = highlight;
+        // Only the hardware display follows the brightness control
+        let level = self.brightness.level();
+        if let Some(primary) = self.primary.as_mut().filter(|_| !self.asleep) {
+            primary.flash(highlight, Some(level));
+        }
+        for output in &mut self.mirrors {
+            output.flash(highlight, None);
+        }
+    }
+
+    /// Keep the display on for `[display] wake_minutes` during the off
+    /// hours. Returns whether it was asleep, so the press that woke it can
+    /// be ignored.
+    pub fn wake(&mut self) -> bool {
+        let hold = Duration::from_secs(self.driver_config.wake_minutes as u64 * 60);
+        self.awake_until = Some(Instant::now() + hold);
+        self.asleep
+    }
+
+    /// Whether the display is switched off for the off hours
+    pub fn is_asleep(&self) -> bool {
+        self.asleep
+    }
+
+    /// Switch the hardware display off when the off hours start (unless
+    /// woken) and back on when they end
+    fn update_power(&mut self, now: Instant) {
+        let asleep = self
+            .off_hours
+            .is_some_and(|hours| hours.contains_now(self.units.timezone))
+            && self.awake_until.is_none_or(|until| now >= until);
+        if asleep == self.asleep {
+            return;
+        }
+        self.asleep = asleep;
+        let level = self.brightness.level();
+        if let Some(ref mut primary) = self.primary {
+            primary.set_asleep(asleep, level);
+        }
+    }
+
+    /// Set the feed and radio state for the status footer
+    pub fn set_status(&mut self, status: DisplayStatus) {
+        self.status = status;
+    }
+
+    /// Rows left for pages once the footer has the last one
+    fn page_rows(&self) -> usize {
+        let rows = self.current_lines.len();
+        if self.footer.is_empty() || rows < 2 {
+            rows
+        } else {
+            rows - 1
+        }
+    }
+
+    /// Advance the view rotation when the current view's time is up
+    fn rotate_view(&mut self, now: Instant) {
+        if self.view_hold_until.is_some_and(|until| now < until) || self.views.len() < 2 {
+            return;
+        }
+        self.view_hold_until = None;
+        let dwell = Duration::from_secs(self.driver_config.view_seconds.max(1) as u64);
+        if now.duration_since(self.view_started) >= dwell {
+            self.view_index = (self.view_index + 1) % self.views.len();
+            self.view_started = now;
+            self.scroll_index = 0;
+            self.page.clear();
+        }
+    }
+
+    fn resize(&mut self, width: usize, rows: usize) {
+        if width != self.width || rows != self.current_lines.len() {
+            self.width = width;
+            self.current_lines = vec![String::new(); rows];
+            self.page.clear();
+            self.scroll_index = 0;
+        }
+    }
+
+    /// Open the configured driver on a serial port
     pub fn open(&mut self, port_name: &str) -> Result<(), String> {
         self.close();
 
-        let port = serialport::new(port_name, 9600)
-            .data_bits(serialport::DataBits::Eight)
-            .parity(serialport::Parity::None)
-            .stop_bits(serialport::StopBits::One)
-            .timeout(Duration::from_millis(1000))
-            .open()
-            .map_err(|e| format!("Failed to open {}: {}", port_name, e))?;
+        let driver = create_driver(&self.driver_config, port_name).map_err(|e| e.to_string())?;
+        let (width, rows) = driver.dimensions();
+        self.resize(width, rows);
+        if driver.capabilities().brightness {
+            if let Err(e) = self.brightness.open_sensor(&self.driver_config) {
+                log::warn!("Light sensor: {}", e);
+            }
+        }
 
-        self.port = Some(port);
+        let mut primary = DisplayOutput::new(driver, Duration::ZERO);
+        primary.charset = Charset::parse(&self.driver_config.charset_map);
+        self.primary = Some(primary);
         self.port_name = port_name.to_string();
+        self.activity_shown = None;
+        // Switched off again by the next update during the off hours
+        self.asleep = false;
         self.clear();
         Ok(())
     }
 
     /// Close the serial port
     pub fn close(&mut self) {
-        if self.port.is_some() {
-            self.clear();
+        if let Some(ref mut primary) = self.primary {
+            primary.clear();
         }
-        self.port = None;
+        self.primary = None;
         self.port_name.clear();
     }
 
     /// Check if port is open
     pub fn is_open(&self) -> bool {
-        self.port.is_some()
+        self.primary.is_some()
+    }
+
+    /// Feed rendered frames to another driver as well as the hardware
+    /// display, writing at most once per `min_interval`
+    pub fn add_output(&mut self, driver: Box<dyn DisplayDriver>, min_interval: Duration) {
+        self.mirrors.push(DisplayOutput::new(driver, min_interval));
+    }
+
+    /// Open another display that shows the same frames, such as a
+    /// `[[display]]` entry after the first
+    pub fn open_mirror(&mut self, config: &DisplayConfig, port_name: &str) -> Result<(), String> {
+        let driver = create_driver(config, port_name).map_err(|e| e.to_string())?;
+        let mut output = DisplayOutput::new(driver, Duration::ZERO);
+        output.charset = Charset::parse(&config.charset_map);
+        self.mirrors.push(output);
+        Ok(())
+    }
+
+    /// Text grid size as (columns, rows)
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.current_lines.len())
+    }
+
+    /// Name of the active driver, if open
+    pub fn driver_name(&self) -> Option<&'static str> {
+        self.primary.as_ref().map(|primary| primary.driver.name())
     }
 
     /// Get current port name
@@ -127,72 +635,488 @@ impl VfdDisplay {
 
     /// Clear the display
     pub fn clear(&mut self) {
-        self.current_lines = [String::new(), String::new()];
-        if let Some(ref mut port) = self.port {
-            let _ = port.write_all(CLEAR_DISPLAY);
-        }
-    }
-
-    /// Pad or truncate text to exactly DISPLAY_WIDTH characters
-    fn format_line(text: &str) -> String {
-        format!("{:width$}", text, width = DISPLAY_WIDTH)
-            .chars()
-            .take(DISPLAY_WIDTH)
-            .collect()
+        self.current_lines.iter_mut().for_each(String::clear);
+        self.current_colors.clear();
+        self.current_large.clear();
+        self.primary
+            .iter_mut()
+            .chain(self.mirrors.iter_mut())
+            .for_each(DisplayOutput::clear);
     }
 
     /// Update display state with spots (always runs, even without serial connection)
     pub fn update(&mut self, spots: &[AggregatedSpot]) {
+        self.update_power(Instant::now());
+        if let Some(primary) = self.primary.as_mut().filter(|_| !self.asleep) {
+            if primary.driver.capabilities().brightness {
+                if let Some(level) = self.brightness.poll() {
+                    let _ = primary.driver.set_brightness(level);
+                }
+            }
+        }
+        // Frames held back by an output's refresh rate
+        let now = Instant::now();
+        self.primary
+            .iter_mut()
+            .chain(self.mirrors.iter_mut())
+            .for_each(|output| output.write_pending(now));
+        self.animate_attention(now);
+        self.update_activity_output(!spots.is_empty() && !self.force_random_mode);
+
         // Random mode updates on its own timing (duty cycle within each second)
         if self.force_random_mode || spots.is_empty() {
+            self.page.clear();
+            self.current_colors.clear();
+            self.current_large.clear();
             self.update_random_mode_state();
+            if let Some(footer) = self.footer_line(0) {
+                let row = self.current_lines.len() - 1;
+                self.current_lines[row] = self.render_line(&footer, now);
+            }
             self.write_to_port();
             return;
         }
 
         // Spot display uses scroll interval
         let now = Instant::now();
-        if now.duration_since(self.last_update) < self.scroll_interval {
-            return;
+        if self.selection_until.is_some_and(|until| now >= until) {
+            self.select_spot(None);
+        }
+        self.rotate_view(now);
+        if now.duration_since(self.last_update) >= self.scroll_interval || self.page.is_empty() {
+            self.last_update = now;
+            if !self.detail.is_empty() {
+                let lines = self
+                    .detail
+                    .iter()
+                    .cloned()
+                    .map(PageLine::scrolling)
+                    .collect();
+                self.list_page(lines);
+            } else if self.selection.is_some() {
+                self.selection_page(spots);
+            } else {
+                self.next_page(spots);
+            }
+            self.marquee_start = now;
+        } else if self.selection.is_none() && self.views[self.view_index] == View::Clock {
+            // The time moves between page flips
+            self.clock_page(false);
         }
-        self.last_update = now;
 
-        // Update current_lines based on spots
-        match spots.len() {
-            1 => {
-                self.current_lines[0] = spots[0].to_display_string();
-                self.current_lines[1] = String::new();
+        // Marquee fields move between page flips and the footer follows the
+        // feed, so re-render every update
+        let footer = self.footer_line(spots.len());
+        let rows: Vec<&PageLine> = self.page.iter().chain(footer.as_ref()).collect();
+        let lines: Vec<String> = rows
+            .iter()
+            .map(|line| self.render_line(line, now))
+            .collect();
+        let colors: Vec<Vec<ColorSpan>> = rows.iter().map(|line| self.line_colors(line)).collect();
+        let large: Vec<bool> = rows.iter().map(|line| line.large).collect();
+        if lines != self.current_lines
+            || colors != self.current_colors
+            || large != self.current_large
+        {
+            self.current_lines = lines;
+            self.current_colors = colors;
+            self.current_large = large;
+            self.write_to_port();
+        }
+    }
+
+    /// Lay out the next page of the current view
+    fn next_page(&mut self, spots: &[AggregatedSpot]) {
+        let nearest = self.vfo_khz.and_then(|vfo| nearest_spot(spots, vfo));
+        self.position = None;
+        match self.views[self.view_index] {
+            View::Spots if self.driver_config.layout == "compact" => {
+                // One spot per page, flipping each interval
+                let rows = self.page_rows();
+                let index = self.scroll_index % spots.len();
+                let spot = &spots[index];
+                let mut page = spot.to_compact_lines(&self.units);
+                if let Some(offset) = offset_if_nearest(spot, nearest) {
+                    page[1] = format!("{} {}", page[1], format_offset(offset));
+                }
+                self.page = (0..rows)
+                    .map(|row| PageLine::scrolling(page.get(row).cloned().unwrap_or_default()))
+                    .collect();
+                self.show_position(index, spots.len(), 1);
+            }
+            View::Spots if self.driver_config.layout == "template" && self.template.is_paged() => {
+                // Multi-line template: one spot per page
+                let rows = self.page_rows();
+                let index = self.scroll_index % spots.len();
+                let spot = &spots[index];
+                let mut page = Vec::new();
+                let clock = self.clock();
+                for line in self.template.render(
+                    spot,
+                    offset_if_nearest(spot, nearest),
+                    &self.units,
+                    &clock,
+                ) {
+                    let line = PageLine::from(line);
+                    // A double size line covers the row below it
+                    let spacer = line.large;
+                    page.push(line);
+                    if spacer {
+                        page.push(PageLine::default());
+                    }
+                }
+                page.resize(rows, PageLine::default());
+                self.page = page;
+                self.show_position(index, spots.len(), 1);
+            }
+            View::Spots => {
+                let lines = spots
+                    .iter()
+                    .map(|spot| self.spot_line(spot, offset_if_nearest(spot, nearest)))
+                    .collect();
+                self.list_page(lines);
             }
-            2 => {
-                self.current_lines[0] = spots[0].to_display_string();
-                self.current_lines[1] = spots[1].to_display_string();
+            View::Bands => self.list_page(band_lines(spots, nearest)),
+            View::Stats => self.list_page(stats_lines(spots, nearest)),
+            View::Watch => {
+                // Spots on hand from the watch list, latest hit first
+                let lines: Vec<PageLine> = self
+                    .watched
+                    .iter()
+                    .filter_map(|call| {
+                        spots
+                            .iter()
+                            .find(|spot| spot.callsign.eq_ignore_ascii_case(call))
+                    })
+                    .map(|spot| self.spot_line(spot, offset_if_nearest(spot, nearest)))
+                    .collect();
+                if lines.is_empty() {
+                    self.list_page(vec![PageLine::scrolling("No watch-list spots".to_string())]);
+                } else {
+                    self.list_page(lines);
+                }
             }
-            _ => {
-                // Scroll through spots
-                let idx1 = self.scroll_index % spots.len();
-                let idx2 = (self.scroll_index + 1) % spots.len();
-                self.current_lines[0] = spots[idx1].to_display_string();
-                self.current_lines[1] = spots[idx2].to_display_string();
-                self.scroll_index = (self.scroll_index + 1) % spots.len();
+            View::Cluster => self.list_page(cluster_lines(&self.announcements, &self.units)),
+            View::Propagation => {
+                let lines = self.propagation_lines();
+                self.list_page(lines);
+            }
+            View::Ladder => {
+                let rows = self.page_rows();
+                let mut lines = ladder_lines(
+                    spots,
+                    self.vfo_khz,
+                    self.region,
+                    nearest,
+                    self.width,
+                    &self.units,
+                );
+                lines.resize(rows, PageLine::default());
+                self.page = lines;
             }
+            View::Clock => self.clock_page(true),
         }
+    }
 
-        self.write_to_port();
+    /// UTC and/or local time (`[units] clocks`, local labelled with the
+    /// zone's abbreviation when one is set), the date, session length and
+    /// spots received, scrolling one line per page flip (`advance`) when
+    /// they don't fit
+    /// Example: "UTC   14:25:03" / "MDT   08:25:03" / "Date  2024-03-17" /
+    /// "Session 1:23:45" / "Spots 1234"
+    fn clock_page(&mut self, advance: bool) {
+        let clock = self.clock();
+        let now = clock.now;
+        let utc = Units {
+            local_time: false,
+            ..self.units
+        };
+        let local = Units {
+            local_time: true,
+            ..self.units
+        };
+        let mut lines = Vec::new();
+        if self.units.clocks.utc() {
+            lines.push(format!("UTC   {}", utc.time_seconds(now)));
+        }
+        if self.units.clocks.local() {
+            lines.push(format!(
+                "{:<5} {}",
+                local.local_label(now),
+                local.time_seconds(now)
+            ));
+        }
+        lines.extend([
+            format!("Date  {}", self.units.date(now)),
+            format!("Session {}", clock.session()),
+            format!("Spots {}", clock.received),
+        ]);
+        let rows = self.page_rows();
+        let fits = lines.len() <= rows;
+        if !fits && advance && !self.page.is_empty() {
+            self.scroll_index = (self.scroll_index + 1) % lines.len();
+        }
+        self.page = (0..rows)
+            .map(|row| {
+                let index = if fits {
+                    row
+                } else {
+                    (self.scroll_index + row) % lines.len()
+                };
+                lines
+                    .get(index)
+                    .cloned()
+                    .map(PageLine::scrolling)
+                    .unwrap_or_default()
+            })
+            .collect();
     }
 
-    /// Write current_lines to serial port if connected
-    fn write_to_port(&mut self) {
-        if let Some(ref mut port) = self.port {
-            // Clear and home cursor
-            let _ = port.write_all(CLEAR_DISPLAY);
+    /// The time now with the session counters, for the clock view, footer
+    /// and template placeholders
+    fn clock(&self) -> Clock {
+        Clock {
+            now: Utc::now(),
+            session_secs: self.session_started.elapsed().as_secs(),
+            received: self.session_spots,
+        }
+    }
+
+    /// UTC and/or local time of day as `[units] clocks` says, e.g.
+    /// "14:25Z 08:25"
+    fn clock_widget(&self, now: DateTime<Utc>) -> String {
+        let mut times = Vec::new();
+        if self.units.clocks.utc() {
+            let utc = Units {
+                local_time: false,
+                ..self.units
+            };
+            times.push(format!("{}Z", utc.time(now)));
+        }
+        if self.units.clocks.local() {
+            let local = Units {
+                local_time: true,
+                ..self.units
+            };
+            times.push(local.time(now));
+        }
+        times.join(" ")
+    }
+
+    /// Spot list starting at the selected spot, marked with '*'
+    fn selection_page(&mut self, spots: &[AggregatedSpot]) {
+        let rows = self.page_rows();
+        self.position = None;
+        let Some(index) = self
+            .selection
+            .as_ref()
+            .and_then(|(callsign, khz)| find_spot(spots, callsign, *khz))
+        else {
+            // Selected spot aged out
+            self.select_spot(None);
+            self.next_page(spots);
+            return;
+        };
+        let nearest = self.vfo_khz.and_then(|vfo| nearest_spot(spots, vfo));
+        // The selected spot can take two rows at double size
+        let large = self.driver_config.large_highlight && rows > 1;
+        let mut page: Vec<PageLine> = spots[index..]
+            .iter()
+            .take(rows)
+            .enumerate()
+            .map(|(row, spot)| {
+                let mut line = self.spot_line(spot, offset_if_nearest(spot, nearest));
+                let marker = if row == 0 { '*' } else { ' ' };
+                line.fixed = format!("{}{}", marker, line.fixed);
+                if row == 0 {
+                    self.mark_greyline(&mut line, spot);
+                }
+                line
+            })
+            .collect();
+        if large {
+            page[0].large = true;
+            page.insert(1, PageLine::default());
+        }
+        page.resize(rows, PageLine::default());
+        self.page = page;
+    }
+
+    /// Show a list of lines; when it doesn't fit, scroll one line per page
+    /// flip or, with `list_mode = page`, flip a screenful at a time
+    fn list_page(&mut self, lines: Vec<PageLine>) {
+        let rows = self.page_rows();
+        if lines.len() <= rows {
+            // Everything fits, no scrolling
+            self.position = None;
+            self.page = (0..rows)
+                .map(|row| lines.get(row).cloned().unwrap_or_default())
+                .collect();
+        } else if self.driver_config.list_mode == "page" {
+            // The last row says which page this is, unless the footer does
+            let indicator = rows == self.current_lines.len() && rows > 1;
+            let per_page = if indicator { rows - 1 } else { rows };
+            let pages = lines.len().div_ceil(per_page);
+            let index = self.scroll_index % pages;
+            let mut page: Vec<PageLine> = lines
+                .into_iter()
+                .skip(index * per_page)
+                .take(per_page)
+                .collect();
+            page.resize(per_page, PageLine::default());
+            if indicator {
+                page.push(PageLine::scrolling(format!("Page {}/{}", index + 1, pages)));
+            }
+            self.page = page;
+            self.show_position(index, pages, 1);
+        } else {
+            // Scroll through the list one line at a time
+            let index = self.scroll_index % lines.len();
+            self.page = (0..rows)
+                .map(|row| lines[(index + row) % lines.len()].clone())
+                .collect();
+            self.show_position(index, lines.len(), rows);
+        }
+    }
+
+    /// One spot per line in the list layouts, the fixed fields in the band's
+    /// color and the callsign tinted by the alert rules
+    fn spot_line(&self, spot: &AggregatedSpot, vfo_offset: Option<f64>) -> PageLine {
+        let mut line = match self.driver_config.layout.as_str() {
+            "icons" => icon_line(spot, vfo_offset, &self.units),
+            "template" => self
+                .template
+                .render(spot, vfo_offset, &self.units, &self.clock())
+                .into_iter()
+                .next()
+                .map(PageLine::from)
+                .unwrap_or_default(),
+            _ => list_line(spot, vfo_offset, &self.units),
+        };
+        // A list row has no room to grow into
+        line.large = false;
+        // The spot being listened to
+        if vfo_offset.is_some() {
+            self.mark_greyline(&mut line, spot);
+        }
+        line.fixed_color = Band::from_khz(spot.frequency_khz).map(band_color);
+        line.scroll_color = self.tints.get(&spot.callsign).copied();
+        line
+    }
+
+    /// Status footer for the last row, when configured and there's room
+    /// Example: "LIVE 42sp CW ≥10dB 20m 14025.0 CW"
+    fn footer_line(&self, spot_count: usize) -> Option<PageLine> {
+        if self.page_rows() == self.current_lines.len() {
+            return None;
+        }
+        let quiet = self.last_spot.is_none_or(|at| at.elapsed() >= FEED_QUIET);
+        let clock = self.clock();
+        // Paged lists show where they are ahead of the configured items
+        let page = self
+            .position
+            .filter(|_| self.driver_config.list_mode == "page")
+            .map(|position| position.label());
+        let items: Vec<String> = page
+            .into_iter()
+            .chain(self.footer.iter().map(|item| match item {
+                FooterItem::Feed => match (self.status.connected, quiet) {
+                    (false, _) => "DOWN".to_string(),
+                    (true, true) => "IDLE".to_string(),
+                    (true, false) => "LIVE".to_string(),
+                },
+                FooterItem::Spots => format!("{}sp", spot_count),
+                FooterItem::Filters => self.status.filters.clone(),
+                FooterItem::Radio => self.status.radio.clone(),
+                FooterItem::Propagation => self.propagation_widget().unwrap_or_default(),
+                FooterItem::Clock => self.clock_widget(clock.now),
+                FooterItem::Session => format!("{} {}rx", clock.session(), clock.received),
+            }))
+            .filter(|item| !item.is_empty())
+            .collect();
+        Some(PageLine {
+            scroll: items.join(" "),
+            scroll_color: (!self.status.connected).then_some(OFFLINE),
+            ..PageLine::default()
+        })
+    }
+
+    /// Color hints for a rendered line: the fixed part, then the scrolling
+    /// field through the end of the row
+    fn line_colors(&self, line: &PageLine) -> Vec<ColorSpan> {
+        let width = line.width(self.width);
+        let fixed_width = line.fixed.chars().count().min(width);
+        let mut spans = Vec::new();
+        if let Some(color) = line.fixed_color.filter(|_| fixed_width > 0) {
+            spans.push(ColorSpan {
+                start: 0,
+                end: fixed_width,
+                color,
+            });
+        }
+        if let Some(color) = line.scroll_color.filter(|_| fixed_width < width) {
+            spans.push(ColorSpan {
+                start: fixed_width,
+                end: width,
+                color,
+            });
+        }
+        spans
+    }
 
-            // Write line 1 (exactly 20 chars)
-            let padded1 = Self::format_line(&self.current_lines[0]);
-            let _ = port.write_all(padded1.as_bytes());
+    /// Fixed part of a line followed by its scrolling field, cut to the width
+    fn render_line(&self, line: &PageLine, now: Instant) -> String {
+        let fixed_width = line.fixed.chars().count();
+        let field_width = line.width(self.width).saturating_sub(fixed_width);
+        let field = marquee(
+            &line.scroll,
+            field_width,
+            now.duration_since(self.marquee_start),
+            self.driver_config.marquee_speed,
+            Duration::from_millis(self.driver_config.marquee_pause_ms as u64),
+        );
+        format!("{}{}", line.fixed, field)
+    }
+
+    /// Switch the activity GPO when spot display starts or stops
+    fn update_activity_output(&mut self, active: bool) {
+        let output = self.driver_config.activity_output;
+        if output == 0 || self.activity_shown == Some(active) {
+            return;
+        }
+        if let Some(ref mut primary) = self.primary {
+            if primary.driver.capabilities().outputs {
+                let _ = primary.driver.set_output(output, active);
+            }
+            self.activity_shown = Some(active);
+        }
+    }
+
+    /// Key codes pressed on the display's keypad since the last poll; a
+    /// press while the display is asleep only wakes it
+    pub fn poll_keys(&mut self) -> Vec<u8> {
+        let keys = match self.primary {
+            Some(ref mut primary) if primary.driver.capabilities().keypad => {
+                primary.driver.read_keys().unwrap_or_default()
+            }
+            _ => Vec::new(),
+        };
+        if !keys.is_empty() && self.wake() {
+            return Vec::new();
+        }
+        keys
+    }
 
-            // Write line 2 (exactly 20 chars)
-            let padded2 = Self::format_line(&self.current_lines[1]);
-            let _ = port.write_all(padded2.as_bytes());
+    /// Send current_lines to every connected output
+    fn write_to_port(&mut self) {
+        let now = Instant::now();
+        for output in self.primary.iter_mut().chain(self.mirrors.iter_mut()) {
+            output.show(
+                &self.current_lines,
+                &self.current_colors,
+                &self.current_large,
+                now,
+            );
         }
     }
 
@@ -218,8 +1142,8 @@ impl VfdDisplay {
             } else {
                 rng.gen_range(b'0'..=b'9') as char
             };
-            self.random_state.char_col = rng.gen_range(0..DISPLAY_WIDTH);
-            self.random_state.char_row = rng.gen_range(0..DISPLAY_LINES);
+            self.random_state.char_col = rng.gen_range(0..self.width);
+            self.random_state.char_row = rng.gen_range(0..self.page_rows());
         }
 
         // Update current_lines based on random state
@@ -227,33 +1151,25 @@ impl VfdDisplay {
             self.random_state.showing_char = true;
 
             // Create display with single character
-            let mut line0 = " ".repeat(DISPLAY_WIDTH);
-            let mut line1 = " ".repeat(DISPLAY_WIDTH);
-
-            if self.random_state.char_row == 0 {
-                line0.replace_range(
-                    self.random_state.char_col..self.random_state.char_col + 1,
-                    &self.random_state.character.to_string(),
-                );
-            } else {
-                line1.replace_range(
-                    self.random_state.char_col..self.random_state.char_col + 1,
-                    &self.random_state.character.to_string(),
-                );
+            let col = self.random_state.char_col.min(self.width - 1);
+            for (row, line) in self.current_lines.iter_mut().enumerate() {
+                *line = " ".repeat(self.width);
+                if row == self.random_state.char_row {
+                    line.replace_range(col..col + 1, &self.random_state.character.to_string());
+                }
             }
-
-            self.current_lines[0] = line0;
-            self.current_lines[1] = line1;
         } else if !should_show && self.random_state.showing_char {
             self.random_state.showing_char = false;
-            self.current_lines[0] = String::new();
-            self.current_lines[1] = String::new();
+            self.current_lines.iter_mut().for_each(String::clear);
         }
     }
 
     /// Get current display lines for preview
-    pub fn get_preview(&self) -> [String; 2] {
-        self.current_lines.clone()
+    pub fn get_preview(&self) -> Vec<String> {
+        self.current_lines
+            .iter()
+            .map(|line| strip_glyphs(line))
+            .collect()
     }
 
     /// Get random mode state for preview
@@ -261,3 +1177,614 @@ impl VfdDisplay {
         self.force_random_mode
     }
 }
+
+/// A driver frames are written to, with its own character set, glyph slots
+/// and refresh rate
+struct DisplayOutput {
+    driver: Box<dyn DisplayDriver>,
+    charset: Charset,
+    glyph_slots: GlyphSlots,
+    /// Shortest time between writes
+    min_interval: Duration,
+    last_write: Option<Instant>,
+    /// Latest frame and its color hints, and whether it still has to be
+    /// written
+    frame: Vec<String>,
+    frame_colors: Vec<Vec<ColorSpan>>,
+    frame_large: Vec<bool>,
+    pending: bool,
+    /// Rows (and colors) as last written to the driver, so only changes
+    /// are sent
+    shown: Vec<String>,
+    shown_colors: Vec<Vec<ColorSpan>>,
+    /// Switched off; frames wait until it's back on
+    asleep: bool,
+}
+
+impl DisplayOutput {
+    fn new(driver: Box<dyn DisplayDriver>, min_interval: Duration) -> Self {
+        Self {
+            driver,
+            charset: Charset::default(),
+            glyph_slots: GlyphSlots::default(),
+            min_interval,
+            last_write: None,
+            frame: Vec::new(),
+            frame_colors: Vec::new(),
+            frame_large: Vec::new(),
+            pending: false,
+            shown: Vec::new(),
+            shown_colors: Vec::new(),
+            asleep: false,
+        }
+    }
+
+    fn clear(&mut self) {
+        let _ = self.driver.clear();
+        self.frame.clear();
+        self.frame_colors.clear();
+        self.frame_large.clear();
+        self.pending = false;
+        self.shown.clear();
+        self.shown_colors.clear();
+    }
+
+    /// Queue a frame and write it if the refresh rate allows
+    fn show(&mut self, lines: &[String], colors: &[Vec<ColorSpan>], large: &[bool], now: Instant) {
+        if lines == self.frame.as_slice()
+            && colors == self.frame_colors.as_slice()
+            && large == self.frame_large.as_slice()
+        {
+            return;
+        }
+        self.frame = lines.to_vec();
+        self.frame_colors = colors.to_vec();
+        self.frame_large = large.to_vec();
+        self.pending = true;
+        self.write_pending(now);
+    }
+
+    /// Switch the display off, or blank it where it can't be switched off,
+    /// and back on at `level` brightness with the latest frame redrawn
+    fn set_asleep(&mut self, asleep: bool, level: u8) {
+        self.asleep = asleep;
+        let capabilities = self.driver.capabilities();
+        if capabilities.power {
+            let _ = self.driver.set_power(!asleep);
+        } else if asleep {
+            let _ = self.driver.clear();
+        }
+        if capabilities.brightness {
+            let _ = self.driver.set_brightness(if asleep { 0 } else { level });
+        }
+        if !asleep {
+            self.shown.clear();
+            self.shown_colors.clear();
+            self.pending = true;
+        }
+    }
+
+    /// One step of the attention animation: the screen inverted, or where
+    /// it can't invert dimmed from `level` when the output follows the
+    /// brightness control
+    fn flash(&mut self, highlight: bool, level: Option<u8>) {
+        let capabilities = self.driver.capabilities();
+        if capabilities.notify {
+            return;
+        }
+        if capabilities.invert {
+            let _ = self.driver.set_inverted(highlight);
+        } else if let Some(level) = level.filter(|_| capabilities.brightness) {
+            let level = if highlight { level / 5 } else { level };
+            let _ = self.driver.set_brightness(level);
+        }
+    }
+
+    fn write_pending(&mut self, now: Instant) {
+        if !self.pending
+            || self.asleep
+            || self
+                .last_write
+                .is_some_and(|last| now.duration_since(last) < self.min_interval)
+        {
+            return;
+        }
+        self.pending = false;
+        self.last_write = Some(now);
+
+        // Fit the frame to this output's own character set and grid; double
+        // size rows get half the width where the driver can show them
+        let (width, rows) = self.driver.dimensions();
+        let capabilities = self.driver.capabilities();
+        let large: Vec<bool> = (0..rows)
+            .map(|row| {
+                capabilities.large_text
+                    && row + 1 < rows
+                    && self.frame_large.get(row).copied().unwrap_or(false)
+            })
+            .collect();
+        let lines: Vec<String> = self
+            .frame
+            .iter()
+            .take(rows)
+            .enumerate()
+            .map(|(row, line)| {
+                let width = if large[row] { width / 2 } else { width };
+                let line = self.charset.translate(line, self.driver.as_ref());
+                format!("{:width$}", line, width = width)
+                    .chars()
+                    .take(width)
+                    .collect()
+            })
+            .collect();
+        let lines = self.glyph_slots.prepare(self.driver.as_mut(), &lines);
+
+        // Serial displays take tens of milliseconds per full redraw, so send
+        // only the rows that changed, and only the changed cells where the
+        // driver can position its cursor. Color drivers get whole rows with
+        // their color hints.
+        self.shown.resize(lines.len(), String::new());
+        self.shown_colors.resize(lines.len(), Vec::new());
+        let mut changed = false;
+        for (row, line) in lines.into_iter().enumerate() {
+            if row > 0 && large[row - 1] {
+                // Covered by the double size row above; rewritten in full
+                // once that goes
+                self.shown[row].clear();
+                self.shown_colors[row].clear();
+                continue;
+            }
+            let colors = match self.frame_colors.get(row) {
+                Some(colors) if capabilities.color => colors.clone(),
+                _ => Vec::new(),
+            };
+            let shown = &mut self.shown[row];
+            if *shown == line && self.shown_colors[row] == colors {
+                continue;
+            }
+            changed = true;
+            let written = if large[row] {
+                self.driver.write_large_line(row, &line, &colors)
+            } else if capabilities.color {
+                self.driver.write_colored_line(row, &line, &colors)
+            } else {
+                match changed_span(shown, &line).filter(|_| capabilities.partial_writes) {
+                    Some((column, text)) => self.driver.write_at(row, column, &text),
+                    None => self.driver.write_line(row, &line),
+                }
+            };
+            // A failed row is rewritten in full with the next frame
+            if written.is_ok() {
+                *shown = line;
+                self.shown_colors[row] = colors;
+            } else {
+                shown.clear();
+            }
+        }
+        if changed {
+            let _ = self.driver.flush();
+        }
+    }
+}
+
+/// First changed column and the text from there through the last changed
+/// cell, for two rows of the same width
+fn changed_span(old: &str, new: &str) -> Option<(usize, String)> {
+    let old: Vec<char> = old.chars().collect();
+    let new: Vec<char> = new.chars().collect();
+    if old.len() != new.len() {
+        return None;
+    }
+    let first = old.iter().zip(&new).position(|(a, b)| a != b)?;
+    let last = old.iter().zip(&new).rposition(|(a, b)| a != b)?;
+    Some((first, new[first..=last].iter().collect()))
+}
+
+/// How long a manual view change pauses the rotation
+const MANUAL_VIEW_HOLD: Duration = Duration::from_secs(60);
+
+/// How long the encoder's selection list stays up after the last turn
+const SELECTION_HOLD: Duration = Duration::from_secs(15);
+
+/// How long the attention animation for a rare spot runs, and how long
+/// each of its steps lasts
+const ATTENTION_LENGTH: Duration = Duration::from_millis(1600);
+const ATTENTION_STEP: Duration = Duration::from_millis(200);
+
+/// The footer shows the feed as idle when connected but no spot has come
+/// in for this long
+const FEED_QUIET: Duration = Duration::from_secs(120);
+
+/// WWV lines and announcements kept for the cluster view
+const ANNOUNCEMENTS_KEPT: usize = 20;
+
+/// Watch-list callsigns remembered for the watch view
+const WATCHED_KEPT: usize = 50;
+
+/// Information shown on the display, rotated on a timer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum View {
+    /// Spots in the configured layout
+    Spots,
+    /// One line per active band: spot count and strongest station
+    Bands,
+    /// Totals over the filtered spots
+    Stats,
+    /// Spots on hand that hit the watch list, latest first
+    Watch,
+    /// Horizontal band map of the current band with the VFO marked
+    Ladder,
+    /// UTC and local time with session length and spot count
+    Clock,
+    /// WWV lines and announcements captured from the feed
+    Cluster,
+    /// Solar flux and A/K indices from the latest WWV/WCY report
+    Propagation,
+}
+
+impl View {
+    fn name(self) -> &'static str {
+        match self {
+            View::Spots => "spots",
+            View::Bands => "bands",
+            View::Stats => "stats",
+            View::Watch => "watch",
+            View::Ladder => "ladder",
+            View::Clock => "clock",
+            View::Cluster => "cluster",
+            View::Propagation => "prop",
+        }
+    }
+
+    /// Parse a comma separated view list, skipping unknown names
+    fn parse_list(list: &str) -> Vec<View> {
+        let views: Vec<View> = list
+            .split(',')
+            .filter_map(|name| {
+                [
+                    View::Spots,
+                    View::Bands,
+                    View::Stats,
+                    View::Watch,
+                    View::Ladder,
+                    View::Clock,
+                    View::Cluster,
+                    View::Propagation,
+                ]
+                .into_iter()
+                .find(|view| view.name() == name.trim().to_lowercase())
+            })
+            .collect();
+        if views.is_empty() {
+            vec![View::Spots]
+        } else {
+            views
+        }
+    }
+}
+
+/// One item of the status footer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FooterItem {
+    /// RBN connection: LIVE, IDLE (no recent spots) or DOWN
+    Feed,
+    /// Spots passing the filters
+    Spots,
+    /// Active filters
+    Filters,
+    /// Radio frequency and mode
+    Radio,
+    /// Solar flux and A/K indices with the flux trend
+    Propagation,
+    /// UTC and/or local time of day
+    Clock,
+    /// Session length and spots received
+    Session,
+}
+
+impl FooterItem {
+    fn name(self) -> &'static str {
+        match self {
+            FooterItem::Feed => "feed",
+            FooterItem::Spots => "spots",
+            FooterItem::Filters => "filters",
+            FooterItem::Radio => "radio",
+            FooterItem::Propagation => "prop",
+            FooterItem::Clock => "clock",
+            FooterItem::Session => "session",
+        }
+    }
+
+    /// Parse a comma separated item list, skipping unknown names
+    fn parse_list(list: &str) -> Vec<FooterItem> {
+        list.split(',')
+            .filter_map(|name| {
+                [
+                    FooterItem::Feed,
+                    FooterItem::Spots,
+                    FooterItem::Filters,
+                    FooterItem::Radio,
+                    FooterItem::Propagation,
+                    FooterItem::Clock,
+                    FooterItem::Session,
+                ]
+                .into_iter()
+                .find(|item| item.name() == name.trim().to_lowercase())
+            })
+            .collect()
+    }
+}
+
+/// Spot nearest the VFO, with its offset in kHz
+type Nearest<'a> = Option<(&'a AggregatedSpot, f64)>;
+
+/// The VFO offset when `spot` is the one nearest the VFO
+fn offset_if_nearest(spot: &AggregatedSpot, nearest: Nearest) -> Option<f64> {
+    nearest
+        .filter(|(near, _)| std::ptr::eq(*near, spot))
+        .map(|(_, offset)| offset)
+}
+
+/// By-band summary, lowest band first; the VFO's band shows the nearest
+/// spot instead of the strongest
+/// Example: "20m  12 WO6W 23dB" / "20m  12>WO6W +0.3"
+fn band_lines(spots: &[AggregatedSpot], nearest: Nearest) -> Vec<PageLine> {
+    Band::ALL
+        .iter()
+        .filter_map(|band| {
+            let on_band: Vec<&AggregatedSpot> = spots
+                .iter()
+                .filter(|spot| Band::from_khz(spot.frequency_khz) == Some(*band))
+                .collect();
+            let best = on_band.iter().max_by_key(|spot| spot.highest_snr)?;
+            if let Some((near, offset)) =
+                nearest.filter(|(near, _)| Band::from_khz(near.frequency_khz) == Some(*band))
+            {
+                return Some(PageLine {
+                    fixed: format!("{:<4}{:3}>", band.name(), on_band.len()),
+                    scroll: format!("{} {}", near.callsign, format_offset(offset)),
+                    fixed_color: Some(band_color(*band)),
+                    ..PageLine::default()
+                });
+            }
+            Some(PageLine {
+                fixed: format!("{:<4}{:3} ", band.name(), on_band.len()),
+                scroll: format!("{} {}dB", best.callsign, best.highest_snr),
+                fixed_color: Some(band_color(*band)),
+                ..PageLine::default()
+            })
+        })
+        .collect()
+}
+
+/// Band map ladder: spot marks across the line with 'V' at the VFO, the
+/// span's edges and band below, then spots in frequency order
+/// Example: ". | .. + .V. | ...|." / "14015  20m     14040"
+fn ladder_lines(
+    spots: &[AggregatedSpot],
+    vfo_khz: Option<f64>,
+    region: Option<Region>,
+    nearest: Nearest,
+    width: usize,
+    units: &Units,
+) -> Vec<PageLine> {
+    let Some(band) = BandMap::current_band(spots, vfo_khz) else {
+        return vec![PageLine::scrolling("No band activity".to_string())];
+    };
+    let map = BandMap::fit(band, spots, vfo_khz, region);
+
+    let mut counts = vec![0u32; width];
+    for spot in map.spots(spots) {
+        if let Some(cell) = map.cell(spot.frequency_khz, width) {
+            counts[cell] += 1;
+        }
+    }
+    let mut marks: Vec<char> = counts
+        .iter()
+        .map(|count| match count {
+            0 => '.',
+            1 => '|',
+            _ => '+',
+        })
+        .collect();
+    if let Some(cell) = vfo_khz.and_then(|khz| map.cell(khz, width)) {
+        marks[cell] = 'V';
+    }
+
+    let low = format!("{:.0}", map.low_khz);
+    let high = format!("{:.0}", map.high_khz);
+    let gap = width.saturating_sub(low.len() + high.len());
+    let scale = format!("{}{:^gap$}{}", low, band.name(), high, gap = gap);
+
+    let mut on_band: Vec<&AggregatedSpot> = map.spots(spots).collect();
+    on_band.sort_by(|a, b| a.frequency_khz.total_cmp(&b.frequency_khz));
+    let mut lines = vec![
+        PageLine {
+            fixed: marks.into_iter().collect(),
+            ..PageLine::default()
+        },
+        PageLine::scrolling(scale),
+    ];
+    lines.extend(
+        on_band
+            .into_iter()
+            .map(|spot| list_line(spot, offset_if_nearest(spot, nearest), units)),
+    );
+    lines
+}
+
+/// Totals over the filtered spots
+fn stats_lines(spots: &[AggregatedSpot], nearest: Nearest) -> Vec<PageLine> {
+    let mut lines = Vec::new();
+    if let Some((near, offset)) = nearest {
+        lines.push(PageLine::scrolling(format!(
+            "Near {} {}",
+            near.callsign,
+            format_offset(offset)
+        )));
+    }
+    lines.push(PageLine::scrolling(format!(
+        "{} stations {} skims",
+        spots.len(),
+        spots.iter().map(|spot| spot.spot_count).sum::<u32>()
+    )));
+    let busiest = Band::ALL
+        .iter()
+        .map(|band| {
+            let count = spots
+                .iter()
+                .filter(|spot| Band::from_khz(spot.frequency_khz) == Some(*band))
+                .count();
+            (count, *band)
+        })
+        .max_by_key(|(count, _)| *count);
+    if let Some((count, band)) = busiest.filter(|(count, _)| *count > 0) {
+        lines.push(PageLine::scrolling(format!("Busiest {} ({})", band, count)));
+    }
+    if let Some(best) = spots.iter().max_by_key(|spot| spot.highest_snr) {
+        lines.push(PageLine::scrolling(format!(
+            "Best {} {}dB",
+            best.callsign, best.highest_snr
+        )));
+    }
+    if !spots.is_empty() {
+        let wpm = spots.iter().map(|spot| spot.average_speed).sum::<f64>() / spots.len() as f64;
+        lines.push(PageLine::scrolling(format!("Avg {:.0} WPM", wpm)));
+    }
+    lines
+}
+
+/// Cluster view: newest first, time then the message (scrolling)
+/// Example: "1423 WWV SFI=150, A=8, K=2" / "1410 K1ABC: QRV 20m"
+fn cluster_lines(announcements: &VecDeque<Announcement>, units: &Units) -> Vec<PageLine> {
+    if announcements.is_empty() {
+        return vec![PageLine::scrolling("No announcements".to_string())];
+    }
+    announcements
+        .iter()
+        .map(|announcement| PageLine {
+            fixed: format!("{} ", units.time_compact(announcement.received)),
+            scroll: announcement.to_string(),
+            ..PageLine::default()
+        })
+        .collect()
+}
+
+/// Place in a view stepping through more than fits: pages of a paged
+/// list, lines of a scrolling one, or spots of a one-spot-per-page layout
+#[derive(Debug, Clone, Copy)]
+struct ListPosition {
+    /// First line (or page, or spot) shown
+    shown: usize,
+    /// Lines, pages or spots stepped through
+    count: usize,
+    /// Positions one page step moves
+    stride: usize,
+}
+
+impl ListPosition {
+    /// Page number out of the total, e.g. "2/5"
+    fn label(self) -> String {
+        format!(
+            "{}/{}",
+            self.shown / self.stride + 1,
+            self.count.div_ceil(self.stride)
+        )
+    }
+}
+
+/// One display line: a fixed prefix and a field that scrolls when too long,
+/// each with an optional color hint
+#[derive(Debug, Clone, Default)]
+struct PageLine {
+    fixed: String,
+    scroll: String,
+    fixed_color: Option<Rgb>,
+    scroll_color: Option<Rgb>,
+    /// Double size, covering the row below (which the page leaves blank)
+    large: bool,
+}
+
+impl PageLine {
+    fn scrolling(text: String) -> Self {
+        Self {
+            scroll: text,
+            ..Self::default()
+        }
+    }
+
+    /// Characters the line has on a display `columns` wide
+    fn width(&self, columns: usize) -> usize {
+        if self.large {
+            columns / 2
+        } else {
+            columns
+        }
+    }
+}
+
+impl From<TemplateLine> for PageLine {
+    fn from(line: TemplateLine) -> Self {
+        Self {
+            fixed: line.fixed,
+            scroll: line.scroll,
+            large: line.large,
+            ..Self::default()
+        }
+    }
+}
+
+/// List line: frequency and WPM, then the callsign; the spot nearest the
+/// VFO shows its offset in place of the WPM
+/// Example: "14033.0 22 VP8/SQ1SGB/MM" (callsign scrolls) / "14033.0>+0.3 WO6W"
+fn list_line(spot: &AggregatedSpot, vfo_offset: Option<f64>, units: &Units) -> PageLine {
+    let middle = match vfo_offset {
+        Some(offset) => format!(">{}", format_offset(offset)),
+        None => format!(" {:2}", spot.average_speed.round() as i32),
+    };
+    PageLine {
+        fixed: format!("{:>7}{} ", units.frequency(spot.frequency_khz), middle),
+        scroll: spot.callsign.clone(),
+        ..PageLine::default()
+    }
+}
+
+/// List line with band and mode glyphs in front of the frequency
+/// Example: "[20][cw]14033.0 22 WO6W"
+fn icon_line(spot: &AggregatedSpot, vfo_offset: Option<f64>, units: &Units) -> PageLine {
+    let band = Band::from_khz(spot.frequency_khz).map_or(' ', |band| Glyph::Band(band).marker());
+    let mut line = list_line(spot, vfo_offset, units);
+    line.fixed = format!(
+        "{}{}{}",
+        band,
+        Glyph::for_mode(&spot.mode).marker(),
+        line.fixed
+    );
+    line
+}
+
+/// Window of `text` to show in a field `width` characters wide
+///
+/// Text that fits is padded. Longer text holds at the start for `pause`,
+/// moves `speed` characters per second until its end is visible, holds
+/// again and jumps back. A speed of 0 just truncates.
+fn marquee(text: &str, width: usize, elapsed: Duration, speed: u32, pause: Duration) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let overflow = chars.len().saturating_sub(width);
+    let offset = if overflow == 0 || speed == 0 {
+        0
+    } else {
+        let travel = overflow as f64 / speed as f64;
+        let cycle = 2.0 * pause.as_secs_f64() + travel;
+        let t = elapsed.as_secs_f64() % cycle - pause.as_secs_f64();
+        ((t.max(0.0) * speed as f64) as usize).min(overflow)
+    };
+    let window: String = chars.iter().skip(offset).take(width).collect();
+    format!("{:width$}", window, width = width)
+}
+
+impl Default for VfdDisplay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
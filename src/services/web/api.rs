@@ -0,0 +1,288 @@
+//! REST API under /api for scripts and apps
+//!
+//! Every request needs `Authorization: Bearer <[web] api_token>`, as does
+//! the dashboard's `/ws` (browsers can't set headers on a websocket, so a
+//! `token` query parameter works as well). Reads
+//! answer from the spot store and the status the app hands over each tick;
+//! changes go into the same command queue as the dashboard's and answer
+//! 202 Accepted, since the app applies them on its next tick.
+//!
+//! - `GET /api/status`: feed connection, profile, preset, radio and filters
+//! - `GET /api/spots?band=20m&mode=CW&call=DL*&min_snr=10&sort=recency&limit=20`
+//! - `GET /api/filters`, `PUT /api/filters` with any of `{"min_snr": 12,
+//!   "max_age_minutes": 10, "band": "20m" (or "all"), "cw_only": true,
+//!   "sort": "recency"}`
+//! - `POST /api/tune` with `{"callsign": "DL1ABC"}` or `{"frequency_khz":
+//!   14025.3, "mode": "CW"}`
+//! - `POST /api/profile` with `{"name": "contest"}` ("" for the main settings)
+
+use super::{current_filter, ServerState, WebCommand, WebFilters, WebStatus};
+use crate::config::same_token;
+use crate::models::{Band, SpotRecord, SpotSort};
+use crate::services::alerts::glob_match;
+use crate::services::control::AppCommand;
+use axum::extract::{Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+
+/// Routes under /api, all behind the token check
+pub(super) fn router(state: ServerState) -> Router<ServerState> {
+    let routes = Router::new()
+        .route("/api/status", get(status))
+        .route("/api/spots", get(spots))
+        .route("/api/filters", get(filters).put(set_filters))
+        .route("/api/tune", post(tune))
+        .route("/api/profile", post(profile));
+    guard(routes, state)
+}
+
+/// Put `routes` behind the token check
+pub(super) fn guard(routes: Router<ServerState>, state: ServerState) -> Router<ServerState> {
+    routes.route_layer(middleware::from_fn_with_state(state, require_token))
+}
+
+#[derive(Serialize)]
+struct ApiError {
+    error: String,
+}
+
+/// Error answer with a JSON body
+fn error(status: StatusCode, message: String) -> Response {
+    (status, Json(ApiError { error: message })).into_response()
+}
+
+#[derive(Deserialize)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
+async fn require_token(
+    State(state): State<ServerState>,
+    Query(query): Query<TokenQuery>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or(query.token.as_deref());
+    match token {
+        Some(token) if same_token(token.trim(), &state.api_token) => next.run(request).await,
+        _ => error(
+            StatusCode::UNAUTHORIZED,
+            "missing or wrong bearer token".to_string(),
+        ),
+    }
+}
+
+fn current_status(state: &ServerState) -> WebStatus {
+    state
+        .status
+        .lock()
+        .map(|status| status.clone())
+        .unwrap_or_default()
+}
+
+/// Queue commands for the app
+async fn queue(state: &ServerState, commands: Vec<WebCommand>) -> Response {
+    for command in commands {
+        if state.cmd_tx.send(command).await.is_err() {
+            return error(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "the app isn't taking commands".to_string(),
+            );
+        }
+    }
+    StatusCode::ACCEPTED.into_response()
+}
+
+async fn status(State(state): State<ServerState>) -> Json<WebStatus> {
+    Json(current_status(&state))
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct SpotQuery {
+    band: Option<String>,
+    mode: Option<String>,
+    /// Callsign, `*` matching any characters
+    call: Option<String>,
+    min_snr: Option<i32>,
+    /// "frequency" (default), "recency" or "snr"
+    sort: Option<String>,
+    limit: Option<usize>,
+}
+
+/// Spots passing the app's SNR and age limits, narrowed by the query
+async fn spots(State(state): State<ServerState>, Query(query): Query<SpotQuery>) -> Response {
+    let band = match query.band.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(name) => match Band::from_name(name) {
+            Some(band) => Some(band),
+            None => return error(StatusCode::BAD_REQUEST, format!("unknown band '{}'", name)),
+        },
+    };
+    let sort = match query.sort.as_deref().map(str::trim) {
+        None | Some("") => SpotSort::Frequency,
+        Some(name) => match SpotSort::from_name(name) {
+            Some(sort) => sort,
+            None => {
+                return error(
+                    StatusCode::BAD_REQUEST,
+                    format!("unknown sort '{}' (frequency, recency or snr)", name),
+                )
+            }
+        },
+    };
+    let mut spots = state.spot_store.get_spots_by_frequency();
+    sort.sort(&mut spots);
+    let filter = current_filter(&state);
+    let call = query.call.map(|call| call.trim().to_uppercase());
+    let records: Vec<SpotRecord> = spots
+        .iter()
+        .filter(|spot| filter.allows(spot))
+        .filter(|spot| band.is_none() || Band::from_khz(spot.frequency_khz) == band)
+        .filter(|spot| {
+            query
+                .mode
+                .as_deref()
+                .is_none_or(|mode| spot.mode.eq_ignore_ascii_case(mode.trim()))
+        })
+        .filter(|spot| {
+            call.as_deref()
+                .is_none_or(|call| glob_match(call, &spot.callsign.to_uppercase()))
+        })
+        .filter(|spot| query.min_snr.is_none_or(|snr| spot.highest_snr >= snr))
+        .take(query.limit.unwrap_or(usize::MAX))
+        .map(SpotRecord::from)
+        .collect();
+    Json(records).into_response()
+}
+
+async fn filters(State(state): State<ServerState>) -> Json<WebFilters> {
+    Json(current_status(&state).filters)
+}
+
+#[derive(Deserialize)]
+struct FilterChange {
+    min_snr: Option<i32>,
+    max_age_minutes: Option<u32>,
+    /// Band name, or "all"
+    band: Option<String>,
+    cw_only: Option<bool>,
+    /// "frequency", "recency" or "snr"
+    sort: Option<String>,
+}
+
+async fn set_filters(
+    State(state): State<ServerState>,
+    Json(change): Json<FilterChange>,
+) -> Response {
+    let mut commands = Vec::new();
+    if let Some(snr) = change.min_snr {
+        commands.push(AppCommand::SetMinSnr(snr));
+    }
+    if let Some(minutes) = change.max_age_minutes {
+        commands.push(AppCommand::SetMaxAge(minutes));
+    }
+    if let Some(name) = change.band {
+        let name = name.trim();
+        let band = if name.eq_ignore_ascii_case("all") {
+            None
+        } else {
+            match Band::from_name(name) {
+                Some(band) => Some(band),
+                None => return error(StatusCode::BAD_REQUEST, format!("unknown band '{}'", name)),
+            }
+        };
+        commands.push(AppCommand::SetBand(band));
+    }
+    if let Some(on) = change.cw_only {
+        commands.push(AppCommand::SetCwOnly(on));
+    }
+    if let Some(name) = change.sort {
+        match SpotSort::from_name(&name) {
+            Some(sort) => commands.push(AppCommand::SetSort(sort)),
+            None => {
+                return error(
+                    StatusCode::BAD_REQUEST,
+                    format!("unknown sort '{}'", name.trim()),
+                )
+            }
+        }
+    }
+    queue(
+        &state,
+        commands.into_iter().map(WebCommand::Control).collect(),
+    )
+    .await
+}
+
+#[derive(Deserialize)]
+struct TuneRequest {
+    /// Tune to this station's latest spot
+    callsign: Option<String>,
+    frequency_khz: Option<f64>,
+    mode: Option<String>,
+}
+
+async fn tune(State(state): State<ServerState>, Json(request): Json<TuneRequest>) -> Response {
+    let (frequency_khz, mode) = match (request.callsign, request.frequency_khz) {
+        (Some(callsign), _) => {
+            let callsign = callsign.trim();
+            let spot = state
+                .spot_store
+                .get_spots_by_recency()
+                .into_iter()
+                .find(|spot| spot.callsign.eq_ignore_ascii_case(callsign));
+            match spot {
+                Some(spot) => (spot.frequency_khz, spot.mode),
+                None => return error(StatusCode::NOT_FOUND, format!("no spot of {}", callsign)),
+            }
+        }
+        (None, Some(frequency_khz)) => (frequency_khz, request.mode.unwrap_or_default()),
+        (None, None) => {
+            return error(
+                StatusCode::BAD_REQUEST,
+                "needs a callsign or frequency_khz".to_string(),
+            )
+        }
+    };
+    queue(
+        &state,
+        vec![WebCommand::Tune {
+            frequency_khz,
+            mode,
+        }],
+    )
+    .await
+}
+
+#[derive(Deserialize)]
+struct ProfileRequest {
+    name: String,
+}
+
+async fn profile(
+    State(state): State<ServerState>,
+    Json(request): Json<ProfileRequest>,
+) -> Response {
+    let name = request.name.trim().to_string();
+    if !name.is_empty() && !current_status(&state).profiles.contains(&name) {
+        return error(
+            StatusCode::NOT_FOUND,
+            format!("no profile named '{}'", name),
+        );
+    }
+    queue(
+        &state,
+        vec![WebCommand::Control(AppCommand::SetProfile(name))],
+    )
+    .await
+}
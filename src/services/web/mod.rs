@@ -0,0 +1,442 @@
+//! Embedded web dashboard: a live band map over WebSockets
+//!
+//! The server runs on its own tokio runtime thread. Browsers get a filtered
+//! snapshot on connect, then each new or updated spot from the SpotStore
+//! subscription, with a fresh snapshot every few seconds so expired spots
+//! drop off. Clicking a spot sends a tune request back to the app, and
+//! clients can change the filters and profile with `AppCommand`s. The
+//! dashboard can also mirror the hardware display through a display output.
+//! With `[web] api_token` set, `api` serves a REST API alongside it and
+//! `/ws` needs the token too.
+
+mod api;
+
+use crate::config::{Config, SpotLimits};
+use crate::models::SpotRecord;
+use crate::services::control::AppCommand;
+use crate::services::display::color::ColorSpan;
+use crate::services::display::{Capabilities, DisplayDriver, DisplayResult};
+use crate::services::SpotStore;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::response::{Html, IntoResponse};
+use axum::routing::get;
+use axum::Router;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+
+const DASHBOARD_HTML: &str = include_str!("dashboard.html");
+
+/// How often browsers get a full snapshot
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Requests from dashboard clients to the app
+#[derive(Debug, Clone)]
+pub enum WebCommand {
+    Tune {
+        frequency_khz: f64,
+        mode: String,
+    },
+    /// Settings change for the app's control queue
+    Control(AppCommand),
+}
+
+/// App state for the REST API, handed over every tick
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WebStatus {
+    /// Connected (or connecting) to the spot feed
+    pub connected: bool,
+    pub profile: String,
+    /// `[profile.<name>]` names to switch to
+    pub profiles: Vec<String>,
+    pub preset: String,
+    pub radio: String,
+    pub filters: WebFilters,
+}
+
+/// Main filter settings, as the API shows them
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WebFilters {
+    pub min_snr: i32,
+    pub max_age_minutes: u32,
+    /// Band shown, or "all"
+    pub band: &'static str,
+    pub cw_only: bool,
+    /// "frequency", "recency" or "snr"
+    pub sort: &'static str,
+}
+
+impl WebStatus {
+    pub fn new(config: &Config, connected: bool) -> Self {
+        Self {
+            connected,
+            profile: config.profile_label().to_string(),
+            profiles: config.profiles.keys().cloned().collect(),
+            preset: config.preset_label().to_string(),
+            radio: config.radio.name.clone(),
+            filters: WebFilters {
+                min_snr: config.min_snr,
+                max_age_minutes: config.max_age_minutes,
+                band: config.band_filter.map_or("all", |band| band.name()),
+                cw_only: config.cw_only,
+                sort: config.sort.name(),
+            },
+        }
+    }
+}
+
+#[derive(Clone)]
+struct ServerState {
+    spot_store: SpotStore,
+    /// SNR and age limits applied to everything sent to browsers
+    filter: Arc<Mutex<SpotLimits>>,
+    status: Arc<Mutex<WebStatus>>,
+    /// Bearer token for /api
+    api_token: String,
+    cmd_tx: mpsc::Sender<WebCommand>,
+    display: DisplayFrames,
+}
+
+/// Latest display frame plus channels announcing new ones and rare spot
+/// notices
+#[derive(Clone)]
+struct DisplayFrames {
+    latest: Arc<Mutex<Frame>>,
+    tx: broadcast::Sender<Frame>,
+    notices: broadcast::Sender<String>,
+}
+
+/// Display rows with their color hints
+#[derive(Debug, Clone, Default)]
+struct Frame {
+    lines: Vec<String>,
+    colors: Vec<Vec<ColorSpan>>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ServerMessage {
+    Snapshot {
+        spots: Vec<SpotRecord>,
+    },
+    Spot {
+        spot: SpotRecord,
+    },
+    Display {
+        lines: Vec<String>,
+        colors: Vec<Vec<ColorSpan>>,
+    },
+    Notice {
+        text: String,
+    },
+}
+
+impl From<Frame> for ServerMessage {
+    fn from(frame: Frame) -> Self {
+        ServerMessage::Display {
+            lines: frame.lines,
+            colors: frame.colors,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ClientMessage {
+    Tune {
+        frequency_khz: f64,
+        mode: String,
+    },
+    /// e.g. `{"type": "command", "command": {"set_min_snr": 12}}`
+    Command {
+        command: AppCommand,
+    },
+}
+
+/// Handle to the web server thread
+pub struct WebServer {
+    address: String,
+    filter: Arc<Mutex<SpotLimits>>,
+    status: Arc<Mutex<WebStatus>>,
+    cmd_rx: mpsc::Receiver<WebCommand>,
+    display: DisplayFrames,
+}
+
+impl WebServer {
+    /// Start serving the dashboard on `bind:port`, and the REST API when
+    /// `api_token` isn't empty
+    pub fn start(
+        bind: &str,
+        port: u16,
+        api_token: &str,
+        spot_store: SpotStore,
+        limits: SpotLimits,
+    ) -> Result<Self, String> {
+        let address = format!("{}:{}", bind, port);
+        let listener = std::net::TcpListener::bind(&address)
+            .map_err(|e| format!("Failed to listen on {}: {}", address, e))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| format!("Failed to listen on {}: {}", address, e))?;
+
+        let filter = Arc::new(Mutex::new(limits));
+        let status = Arc::new(Mutex::new(WebStatus::default()));
+        let (cmd_tx, cmd_rx) = mpsc::channel(16);
+        let display = DisplayFrames {
+            latest: Arc::new(Mutex::new(Frame::default())),
+            tx: broadcast::channel(16).0,
+            notices: broadcast::channel(16).0,
+        };
+        let state = ServerState {
+            spot_store,
+            filter: filter.clone(),
+            status: status.clone(),
+            api_token: api_token.trim().to_string(),
+            cmd_tx,
+            display: display.clone(),
+        };
+
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Failed to create tokio runtime");
+            rt.block_on(async move {
+                let mut app = Router::new().route("/", get(dashboard));
+                let mut live = Router::new().route("/ws", get(websocket));
+                if !state.api_token.is_empty() {
+                    app = app.merge(api::router(state.clone()));
+                    live = api::guard(live, state.clone());
+                }
+                let app = app.merge(live).with_state(state);
+                match tokio::net::TcpListener::from_std(listener) {
+                    Ok(listener) => {
+                        if let Err(e) = axum::serve(listener, app).await {
+                            log::error!("Web server stopped: {}", e);
+                        }
+                    }
+                    Err(e) => log::error!("Web server failed to start: {}", e),
+                }
+            });
+        });
+
+        Ok(Self {
+            address,
+            filter,
+            status,
+            cmd_rx,
+            display,
+        })
+    }
+
+    /// Address the server is listening on
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Update the spot filter used for browsers and the status the API
+    /// reports
+    pub fn set_state(&self, config: &Config, connected: bool) {
+        if let Ok(mut filter) = self.filter.lock() {
+            *filter = config.spot_limits();
+        }
+        if let Ok(mut status) = self.status.lock() {
+            *status = WebStatus::new(config, connected);
+        }
+    }
+
+    /// Try to receive a command from a browser (non-blocking)
+    pub fn try_recv(&mut self) -> Option<WebCommand> {
+        self.cmd_rx.try_recv().ok()
+    }
+
+    /// A display driver that mirrors frames onto the dashboard
+    pub fn display_output(&self, columns: usize, rows: usize) -> Box<dyn DisplayDriver> {
+        Box::new(WebDisplay {
+            frames: self.display.clone(),
+            columns,
+            frame: Frame {
+                lines: vec![String::new(); rows],
+                colors: vec![Vec::new(); rows],
+            },
+        })
+    }
+}
+
+/// Display output that pushes frames to connected browsers
+struct WebDisplay {
+    frames: DisplayFrames,
+    columns: usize,
+    frame: Frame,
+}
+
+impl DisplayDriver for WebDisplay {
+    fn name(&self) -> &'static str {
+        "web"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            color: true,
+            notify: true,
+            ..Capabilities::default()
+        }
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        (self.columns, self.frame.lines.len())
+    }
+
+    fn clear(&mut self) -> DisplayResult<()> {
+        self.frame.lines.iter_mut().for_each(String::clear);
+        self.frame.colors.iter_mut().for_each(Vec::clear);
+        Ok(())
+    }
+
+    fn shows_char(&self, c: char) -> bool {
+        !c.is_control()
+    }
+
+    fn write_line(&mut self, row: usize, text: &str) -> DisplayResult<()> {
+        self.write_colored_line(row, text, &[])
+    }
+
+    fn write_colored_line(
+        &mut self,
+        row: usize,
+        text: &str,
+        colors: &[ColorSpan],
+    ) -> DisplayResult<()> {
+        if let Some(line) = self.frame.lines.get_mut(row) {
+            *line = text.to_string();
+            self.frame.colors[row] = colors.to_vec();
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> DisplayResult<()> {
+        if let Ok(mut latest) = self.frames.latest.lock() {
+            *latest = self.frame.clone();
+        }
+        // No receivers just means no browser is connected
+        let _ = self.frames.tx.send(self.frame.clone());
+        Ok(())
+    }
+
+    fn notify(&mut self, text: &str) -> DisplayResult<()> {
+        let _ = self.frames.notices.send(text.to_string());
+        Ok(())
+    }
+}
+
+async fn dashboard() -> Html<&'static str> {
+    Html(DASHBOARD_HTML)
+}
+
+async fn websocket(ws: WebSocketUpgrade, State(state): State<ServerState>) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| client_session(socket, state))
+}
+
+fn current_filter(state: &ServerState) -> SpotLimits {
+    state
+        .filter
+        .lock()
+        .map(|filter| filter.clone())
+        .unwrap_or(SpotLimits {
+            min_snr: 0,
+            max_age: Duration::from_secs(600),
+            bands: Default::default(),
+        })
+}
+
+fn snapshot(state: &ServerState) -> ServerMessage {
+    let filter = current_filter(state);
+    let (min_snr, max_age) = filter.loosest();
+    let mut spots = state.spot_store.get_filtered_spots(min_snr, max_age);
+    spots.retain(|spot| filter.allows(spot));
+    ServerMessage::Snapshot {
+        spots: spots.iter().map(SpotRecord::from).collect(),
+    }
+}
+
+async fn send(socket: &mut WebSocket, message: &ServerMessage) -> bool {
+    let Ok(json) = serde_json::to_string(message) else {
+        return true;
+    };
+    socket.send(Message::Text(json.into())).await.is_ok()
+}
+
+async fn client_session(mut socket: WebSocket, state: ServerState) {
+    let mut updates = state.spot_store.subscribe();
+    let mut frames = state.display.tx.subscribe();
+    let mut notices = state.display.notices.subscribe();
+    let mut snapshots = tokio::time::interval(SNAPSHOT_INTERVAL);
+
+    let latest = state
+        .display
+        .latest
+        .lock()
+        .map(|frame| frame.clone())
+        .unwrap_or_default();
+    if !latest.lines.is_empty() && !send(&mut socket, &latest.into()).await {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            _ = snapshots.tick() => {
+                if !send(&mut socket, &snapshot(&state)).await {
+                    break;
+                }
+            }
+            update = updates.recv() => match update {
+                Ok(spot) => {
+                    if !current_filter(&state).allows(&spot) {
+                        continue;
+                    }
+                    let message = ServerMessage::Spot { spot: SpotRecord::from(&spot) };
+                    if !send(&mut socket, &message).await {
+                        break;
+                    }
+                }
+                // Fell behind; the next snapshot catches up
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            frame = frames.recv() => match frame {
+                Ok(frame) => {
+                    if !send(&mut socket, &frame.into()).await {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            notice = notices.recv() => match notice {
+                Ok(text) => {
+                    if !send(&mut socket, &ServerMessage::Notice { text }).await {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Text(text))) => {
+                    let command = match serde_json::from_str(&text) {
+                        Ok(ClientMessage::Tune { frequency_khz, mode }) => {
+                            WebCommand::Tune { frequency_khz, mode }
+                        }
+                        Ok(ClientMessage::Command { command }) => WebCommand::Control(command),
+                        Err(_) => continue,
+                    };
+                    let _ = state.cmd_tx.send(command).await;
+                }
+                Some(Ok(_)) => {}
+                Some(Err(_)) | None => break,
+            },
+        }
+    }
+}
@@ -0,0 +1,277 @@
+//! WSJT-X call highlighting (`[wsjtx]`)
+//!
+//! WSJT-X sends its UDP messages to the "UDP Server" set in its Reporting
+//! settings and takes requests back on the port they came from. Listening
+//! on that address, this learns each running instance from its heartbeats
+//! and status messages (which carry the dial frequency), then sends
+//! HighlightCallsign requests for watched or needed stations the RBN heard
+//! on FT8 or FT4 on that instance's band, and clears them once the spot is
+//! gone.
+
+use crate::config::WsjtxConfig;
+use crate::models::{AggregatedSpot, Band};
+use crate::services::display::color::Rgb;
+use crate::services::{SpotAlerts, SpotStore};
+use std::collections::HashMap;
+use std::net::{SocketAddr, UdpSocket};
+use std::time::{Duration, Instant};
+
+/// First field of every message
+const MAGIC: u32 = 0xadbc_cbda;
+
+/// Message schema: Qt 5.4 stream layout, understood by WSJT-X 1.5 and up
+const SCHEMA: u32 = 2;
+
+/// Message types used here
+const STATUS: u32 = 1;
+const CLOSE: u32 = 6;
+const HIGHLIGHT_CALLSIGN: u32 = 13;
+
+/// WSJT-X sends a heartbeat every 15 s; an instance silent this long has
+/// gone away
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Least time between passes over the spots
+const UPDATE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Digital modes WSJT-X shows in its band activity window
+const MODES: [&str; 2] = ["FT8", "FT4"];
+
+/// Highlights spotted stations in running WSJT-X instances
+pub struct WsjtxHighlighter {
+    socket: UdpSocket,
+    spot_store: SpotStore,
+    watch_color: Rgb,
+    needed_color: Rgb,
+    /// Running instances by their id ("WSJT-X", or the `--rig-name`)
+    clients: HashMap<String, Client>,
+    last_update: Option<Instant>,
+}
+
+/// One WSJT-X instance
+struct Client {
+    addr: SocketAddr,
+    /// Band of its dial frequency, once a status message has told
+    band: Option<Band>,
+    last_heard: Instant,
+    /// Calls highlighted in it, with their color
+    highlighted: HashMap<String, Rgb>,
+}
+
+impl WsjtxHighlighter {
+    /// Listen for WSJT-X, or None when not enabled or the address can't
+    /// be bound
+    pub fn start(config: &WsjtxConfig, spot_store: &SpotStore) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let socket = match UdpSocket::bind(config.address.trim())
+            .and_then(|socket| socket.set_nonblocking(true).map(|()| socket))
+        {
+            Ok(socket) => socket,
+            Err(e) => {
+                log::warn!("Can't listen for WSJT-X on {}: {}", config.address, e);
+                return None;
+            }
+        };
+        log::info!("Listening for WSJT-X on {}", config.address.trim());
+        Some(Self {
+            socket,
+            spot_store: spot_store.clone(),
+            watch_color: config.watch_color,
+            needed_color: config.needed_color,
+            clients: HashMap::new(),
+            last_update: None,
+        })
+    }
+
+    /// Take messages from WSJT-X and bring each instance's highlights up
+    /// to date with the spots
+    pub fn update(&mut self, alerts: &SpotAlerts) {
+        self.receive();
+        if self
+            .last_update
+            .is_some_and(|last| last.elapsed() < UPDATE_INTERVAL)
+        {
+            return;
+        }
+        self.last_update = Some(Instant::now());
+
+        self.clients
+            .retain(|_, client| client.last_heard.elapsed() < CLIENT_TIMEOUT);
+        if self.clients.is_empty() {
+            return;
+        }
+        let wanted: Vec<(String, Option<Band>, Rgb)> = self
+            .spot_store
+            .get_spots_by_recency()
+            .iter()
+            .filter(|spot| MODES.contains(&spot.mode.to_uppercase().as_str()))
+            .filter_map(|spot| {
+                let color = self.color(spot, alerts)?;
+                let band = Band::from_khz(spot.frequency_khz);
+                Some((spot.callsign.to_uppercase(), band, color))
+            })
+            .collect();
+
+        for (id, client) in &mut self.clients {
+            let mut calls: HashMap<String, Rgb> = HashMap::new();
+            for (call, band, color) in &wanted {
+                if client.band.is_none() || *band == client.band {
+                    calls.entry(call.clone()).or_insert(*color);
+                }
+            }
+            let gone: Vec<String> = client
+                .highlighted
+                .keys()
+                .filter(|call| !calls.contains_key(*call))
+                .cloned()
+                .collect();
+            for call in gone {
+                client.highlighted.remove(&call);
+                send(&self.socket, client.addr, &highlight(id, &call, None));
+            }
+            for (call, color) in calls {
+                if client.highlighted.get(&call) != Some(&color) {
+                    send(
+                        &self.socket,
+                        client.addr,
+                        &highlight(id, &call, Some(color)),
+                    );
+                    client.highlighted.insert(call, color);
+                }
+            }
+        }
+    }
+
+    /// Highlight for a spot: watch-list hits first, then new entities or
+    /// slots
+    fn color(&self, spot: &AggregatedSpot, alerts: &SpotAlerts) -> Option<Rgb> {
+        if alerts.watched(&spot.callsign.to_uppercase()) {
+            Some(self.watch_color)
+        } else if alerts.is_new(spot) {
+            Some(self.needed_color)
+        } else {
+            None
+        }
+    }
+
+    /// Read waiting datagrams, keeping track of the instances sending them
+    fn receive(&mut self) {
+        let mut buf = [0u8; 2048];
+        while let Ok((len, addr)) = self.socket.recv_from(&mut buf) {
+            let mut reader = Reader(&buf[..len]);
+            let Some((kind, id)) = reader.header() else {
+                continue;
+            };
+            if kind == CLOSE {
+                self.clients.remove(&id);
+                continue;
+            }
+            let client = self.clients.entry(id.clone()).or_insert_with(|| {
+                log::info!("WSJT-X instance '{}' at {}", id, addr);
+                Client {
+                    addr,
+                    band: None,
+                    last_heard: Instant::now(),
+                    highlighted: HashMap::new(),
+                }
+            });
+            if client.addr != addr {
+                // Restarted on a new port: it has forgotten the highlights
+                client.addr = addr;
+                client.highlighted.clear();
+            }
+            client.last_heard = Instant::now();
+            if kind == STATUS {
+                if let Some(dial_hz) = reader.u64() {
+                    client.band = Band::from_khz(dial_hz as f64 / 1000.0);
+                }
+            }
+        }
+    }
+}
+
+fn send(socket: &UdpSocket, addr: SocketAddr, message: &[u8]) {
+    if let Err(e) = socket.send_to(message, addr) {
+        log::debug!("WSJT-X message to {} failed: {}", addr, e);
+    }
+}
+
+/// HighlightCallsign message; no color clears the highlight
+fn highlight(id: &str, call: &str, background: Option<Rgb>) -> Vec<u8> {
+    let mut message = Vec::new();
+    message.extend_from_slice(&MAGIC.to_be_bytes());
+    message.extend_from_slice(&SCHEMA.to_be_bytes());
+    message.extend_from_slice(&HIGHLIGHT_CALLSIGN.to_be_bytes());
+    put_utf8(&mut message, id);
+    put_utf8(&mut message, call);
+    put_color(&mut message, background);
+    // Foreground left as WSJT-X draws it
+    put_color(&mut message, None);
+    // Every instance of the call, not only the last one
+    message.push(0);
+    message
+}
+
+/// Qt utf8 string: byte length, then the bytes
+fn put_utf8(message: &mut Vec<u8>, text: &str) {
+    message.extend_from_slice(&(text.len() as u32).to_be_bytes());
+    message.extend_from_slice(text.as_bytes());
+}
+
+/// QColor: spec (1 = RGB, 0 = invalid), then 16-bit alpha, red, green,
+/// blue and padding
+fn put_color(message: &mut Vec<u8>, color: Option<Rgb>) {
+    let (spec, channels) = match color {
+        Some(Rgb(r, g, b)) => (1u8, [0xff, r, g, b]),
+        None => (0, [0xff, 0, 0, 0]),
+    };
+    message.push(spec);
+    for channel in channels {
+        message.extend_from_slice(&(u16::from(channel) * 0x101).to_be_bytes());
+    }
+    message.extend_from_slice(&0u16.to_be_bytes());
+}
+
+/// Big-endian fields from a received message
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    /// Message type and instance id, if this is a WSJT-X message
+    fn header(&mut self) -> Option<(u32, String)> {
+        if self.u32()? != MAGIC {
+            return None;
+        }
+        let _schema = self.u32()?;
+        let kind = self.u32()?;
+        let id = self.utf8()?;
+        Some((kind, id))
+    }
+
+    fn take(&mut self, len: usize) -> Option<&[u8]> {
+        if self.0.len() < len {
+            return None;
+        }
+        let (head, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Some(head)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_be_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_be_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    fn utf8(&mut self) -> Option<String> {
+        let len = self.u32()?;
+        // 0xffffffff is a null string
+        if len == u32::MAX {
+            return Some(String::new());
+        }
+        Some(String::from_utf8_lossy(self.take(len as usize)?).into_owned())
+    }
+}
@@ -0,0 +1,343 @@
+//! First-run setup (`rbn-vfd setup`)
+//!
+//! Asks at the terminal for what a working device needs: callsign,
+//! locator, where spots come from, the radio and the display. It probes what
+//! it can (rigctld on its port, the serial ports present) and puts a test
+//! pattern on the chosen display before writing settings.toml. Everything
+//! else keeps its default, or its current value when run over an existing
+//! file. Starts by itself when there is no settings file and someone at a
+//! terminal to answer.
+
+use crate::config::Config;
+use crate::models::{normalize_callsign, LatLon};
+use crate::services::display::{self, DisplayDriver};
+use crate::services::{list_ports, radio, UsbId};
+use std::fmt::Display;
+use std::io::{self, IsTerminal, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// How long rigctld gets to accept a connection
+const PROBE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Whether to run setup before starting: no settings file yet (nor a
+/// settings.ini to move over) and a terminal to ask at
+pub fn wanted() -> bool {
+    let Some(path) = Config::config_path() else {
+        return false;
+    };
+    !path.exists()
+        && !path.with_file_name("settings.ini").exists()
+        && io::stdin().is_terminal()
+        && io::stdout().is_terminal()
+}
+
+/// Ask the questions and save the answers; `first_run` offers to skip
+pub fn run(first_run: bool) -> Result<(), String> {
+    let path = Config::config_path().ok_or("Could not determine config path")?;
+    let mut config = Config::load();
+    if first_run {
+        println!("No settings yet ({})", path.display());
+        if !confirm("Set up now", true)? {
+            println!("Skipped; `rbn-vfd setup` asks again any time");
+            return Ok(());
+        }
+    }
+    println!("Enter keeps the value in [brackets]\n");
+    ask_station(&mut config)?;
+    ask_feed(&mut config)?;
+    ask_radio(&mut config)?;
+    ask_display(&mut config)?;
+    config.save()?;
+    println!("\nSaved {}", path.display());
+    Ok(())
+}
+
+fn ask_station(config: &mut Config) -> Result<(), String> {
+    loop {
+        match normalize_callsign(&ask("Your callsign", &config.callsign)?) {
+            Ok(callsign) => {
+                config.callsign = callsign;
+                break;
+            }
+            Err(e) => println!("  {}", e),
+        }
+    }
+    loop {
+        let grid = ask(
+            "Maidenhead locator, e.g. CM87wj (blank to skip)",
+            &config.grid,
+        )?;
+        if grid.is_empty() || LatLon::from_grid(&grid).is_some() {
+            config.grid = grid;
+            return Ok(());
+        }
+        println!("  '{}' isn't a 4 or 6 character locator", grid);
+    }
+}
+
+fn ask_feed(config: &mut Config) -> Result<(), String> {
+    let options = [
+        "Reverse Beacon Network (rbn.telegraphy.de)".to_string(),
+        "A DX cluster node passing on skimmer spots".to_string(),
+    ];
+    let current = usize::from(!config.feed.server.trim().is_empty());
+    if choose("Spot source", &options, current)? == 0 {
+        config.feed.server.clear();
+        return Ok(());
+    }
+    loop {
+        let server = ask("Cluster node host[:port]", &config.feed.server)?;
+        if !server.is_empty() {
+            config.feed.server = server;
+            return Ok(());
+        }
+    }
+}
+
+fn ask_radio(config: &mut Config) -> Result<(), String> {
+    let rigctld = format!(
+        "{}:{}",
+        config.radio.rigctld_host, config.radio.rigctld_port
+    );
+    let found = probe(&rigctld);
+    if found {
+        println!("Found rigctld on {}", rigctld);
+    }
+    let backends = radio::registered_backends();
+    let mut options = vec!["None".to_string()];
+    options.extend(backends.iter().cloned());
+    let current = if config.radio.enabled || found {
+        let backend = if found && !config.radio.enabled {
+            "rigctld"
+        } else {
+            config.radio.backend.as_str()
+        };
+        backends
+            .iter()
+            .position(|name| name == backend)
+            .map_or(0, |index| index + 1)
+    } else {
+        0
+    };
+    let choice = choose("Radio control", &options, current)?;
+    if choice == 0 {
+        config.radio.enabled = false;
+        return Ok(());
+    }
+
+    let radio = &mut config.radio;
+    radio.enabled = true;
+    radio.backend = backends[choice - 1].clone();
+    match radio.backend.as_str() {
+        "rigctld" => {
+            radio.rigctld_host = ask("rigctld host", &radio.rigctld_host)?;
+            radio.rigctld_port = ask_number("rigctld port", radio.rigctld_port)?;
+            let address = format!("{}:{}", radio.rigctld_host, radio.rigctld_port);
+            if probe(&address) {
+                println!("  rigctld answers on {}", address);
+                radio.manage_rigctld = false;
+            } else if confirm(
+                "rigctld isn't running there. Start it from this app for a rig on a serial port",
+                true,
+            )? {
+                radio.manage_rigctld = true;
+                if let Some((port, usb_id)) = pick_port("Rig's serial port", &radio.rigctld_device)?
+                {
+                    radio.rigctld_device = port;
+                    radio.cat_usb_id = usb_id.or(radio.cat_usb_id);
+                }
+                radio.rigctld_model = ask_number(
+                    "Hamlib rig model (`rigctl -l` lists them)",
+                    radio.rigctld_model,
+                )?;
+            }
+        }
+        "omnirig" => radio.omnirig_rig = ask_number("OmniRig rig, 1 or 2", radio.omnirig_rig)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Whether something accepts connections at "host:port"
+fn probe(address: &str) -> bool {
+    address
+        .to_socket_addrs()
+        .map(|mut addrs| addrs.any(|addr| TcpStream::connect_timeout(&addr, PROBE_TIMEOUT).is_ok()))
+        .unwrap_or(false)
+}
+
+fn ask_display(config: &mut Config) -> Result<(), String> {
+    let drivers = display::registered_drivers();
+    loop {
+        let current = drivers
+            .iter()
+            .position(|name| *name == config.display.driver)
+            .unwrap_or(0);
+        let name = drivers[choose("Display", &drivers, current)?].clone();
+        config.display.driver = name.clone();
+
+        let serial = display::uses_serial(&name);
+        let mut port = String::new();
+        if serial {
+            if let Some((path, usb_id)) = pick_port("Display's serial port", &config.serial_port)? {
+                config.serial_port = path.clone();
+                config.display_usb_id = usb_id;
+                port = path;
+            }
+        }
+        // Pixel displays work out their own text grid
+        if serial || name.starts_with("hd44780") {
+            let (columns, rows) = ask_size(config.display.columns, config.display.rows)?;
+            config.display.columns = columns;
+            config.display.rows = rows;
+        }
+
+        match test_pattern(config, &port) {
+            Ok(mut driver) => {
+                let shown = confirm("Does the display show the test pattern", true)?;
+                let _ = driver.clear();
+                if shown {
+                    return Ok(());
+                }
+            }
+            Err(e) => println!("  Couldn't open the display: {}", e),
+        }
+        if !confirm("Try another driver or port", true)? {
+            println!("  Keeping {}; settings.toml has its other options", name);
+            return Ok(());
+        }
+    }
+}
+
+/// Open the display and fill it: a title, the callsign, then rulers
+fn test_pattern(config: &Config, port: &str) -> Result<Box<dyn DisplayDriver>, String> {
+    let mut driver = display::create_driver(&config.display, port).map_err(|e| e.to_string())?;
+    let (columns, rows) = driver.dimensions();
+    for row in 0..rows {
+        let text = match row {
+            0 => "RBN VFD setup".to_string(),
+            1 => config.callsign.clone(),
+            _ => "#".repeat(columns),
+        };
+        driver
+            .write_line(
+                row,
+                &format!("{:columns$.columns$}", text, columns = columns),
+            )
+            .map_err(|e| e.to_string())?;
+    }
+    driver.flush().map_err(|e| e.to_string())?;
+    Ok(driver)
+}
+
+/// A serial port from the ones present (with its USB ID), or one typed in
+fn pick_port(question: &str, current: &str) -> Result<Option<(String, Option<UsbId>)>, String> {
+    let ports = list_ports();
+    if ports.is_empty() {
+        println!("No serial ports found");
+        let path = ask(&format!("{} (blank for none)", question), current)?;
+        return Ok((!path.is_empty()).then_some((path, None)));
+    }
+    let mut options: Vec<String> = ports
+        .iter()
+        .map(|(port, id)| match id {
+            Some(id) => format!("{}  {}", port, id),
+            None => port.clone(),
+        })
+        .collect();
+    options.push("Other".to_string());
+    let listed = ports
+        .iter()
+        .position(|(port, _)| port == current)
+        .unwrap_or(if current.is_empty() { 0 } else { ports.len() });
+    match ports.get(choose(question, &options, listed)?) {
+        Some((port, id)) => Ok(Some((port.clone(), *id))),
+        None => {
+            let path = ask("Port", current)?;
+            Ok((!path.is_empty()).then_some((path, None)))
+        }
+    }
+}
+
+/// "columns x rows", e.g. "20x2"
+fn ask_size(columns: usize, rows: usize) -> Result<(usize, usize), String> {
+    loop {
+        let answer = ask(
+            "Size in characters, columns x rows",
+            &format!("{}x{}", columns, rows),
+        )?;
+        let size = answer.split_once(['x', 'X']).and_then(|(columns, rows)| {
+            Some((columns.trim().parse().ok()?, rows.trim().parse().ok()?))
+        });
+        match size {
+            Some((columns, rows)) if columns > 0 && rows > 0 => return Ok((columns, rows)),
+            _ => println!("  '{}' isn't a size like 20x2", answer),
+        }
+    }
+}
+
+/// Print the question, with `hint` in brackets, and read the answer. End
+/// of input cancels setup.
+fn read_answer(question: &str, hint: &str) -> Result<String, String> {
+    if hint.is_empty() {
+        print!("{}: ", question);
+    } else {
+        print!("{} [{}]: ", question, hint);
+    }
+    let _ = io::stdout().flush();
+    let mut line = String::new();
+    match io::stdin().read_line(&mut line) {
+        Ok(0) => Err("Setup cancelled".to_string()),
+        Ok(_) => Ok(line.trim().to_string()),
+        Err(e) => Err(format!("Failed to read the answer: {}", e)),
+    }
+}
+
+/// Free text answer; blank gives `default`
+fn ask(question: &str, default: &str) -> Result<String, String> {
+    let answer = read_answer(question, default)?;
+    Ok(if answer.is_empty() {
+        default.to_string()
+    } else {
+        answer
+    })
+}
+
+fn ask_number<T: FromStr + Display>(question: &str, default: T) -> Result<T, String> {
+    loop {
+        let answer = ask(question, &default.to_string())?;
+        match answer.parse() {
+            Ok(value) => return Ok(value),
+            Err(_) => println!("  '{}' isn't a number", answer),
+        }
+    }
+}
+
+fn confirm(question: &str, default: bool) -> Result<bool, String> {
+    loop {
+        let answer = read_answer(question, if default { "Y/n" } else { "y/N" })?;
+        match answer.to_lowercase().as_str() {
+            "" => return Ok(default),
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => println!("  y or n"),
+        }
+    }
+}
+
+/// Numbered choice; returns the index of the option picked
+fn choose(question: &str, options: &[String], current: usize) -> Result<usize, String> {
+    println!("{}:", question);
+    for (index, option) in options.iter().enumerate() {
+        println!("  {}) {}", index + 1, option);
+    }
+    loop {
+        let answer = ask("Number", &(current + 1).to_string())?;
+        match answer.parse::<usize>() {
+            Ok(number) if (1..=options.len()).contains(&number) => return Ok(number - 1),
+            _ => println!("  1 to {}", options.len()),
+        }
+    }
+}
@@ -0,0 +1,767 @@
+//! Full-screen terminal interface (`--tui`)
+//!
+//! Runs the same services as the GUI: RBN client, spot store, radio
+//! manager and the configured display, with a spot table and keybindings
+//! instead of egui widgets.
+
+use crate::config::Config;
+use crate::logging;
+use crate::models::{
+    format_offset, nearest_spot, normalize_callsign, AggregatedSpot, Band, BandMap, LatLon,
+};
+use crate::services::display::color::{band_color, Rgb};
+#[cfg(target_os = "linux")]
+use crate::services::input::{ButtonAction, InputEvent, InputHandler};
+#[cfg(feature = "mqtt")]
+use crate::services::mqtt::MqttPublisher;
+use crate::services::radio::{RadioEvent, RadioManager, RadioMode, RadioState};
+use crate::services::{
+    detail_lines, AppCommand, Callbook, CommandSource, Control, DisplayStatus, Integrations,
+    LoggerLink, LotwUsers, RbnClient, RbnMessage, SelfSpotter, SpotAlerts, SpotStore, VfdDisplay,
+};
+#[cfg(target_os = "linux")]
+use crate::services::{AlertSound, LedIndicators};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState, Tabs};
+use ratatui::{DefaultTerminal, Frame};
+use std::io;
+use std::time::{Duration, Instant};
+
+/// How long to wait for a key before refreshing
+const TICK: Duration = Duration::from_millis(250);
+
+const AGE_OPTIONS: [u32; 5] = [1, 5, 10, 15, 30];
+
+const HELP: &str = "q quit  ↑↓ select  ←→ band  Enter tune  d details  +/- SNR  a age  m CW  S sort  \
+                    p profile  f preset  r radio  s self-spot  c connect  o display  v view  PgUp/PgDn display page";
+
+/// Terminal UI state
+struct TuiApp {
+    config: Config,
+    /// Settings changes from keys and buttons
+    control: Control,
+    spot_store: SpotStore,
+    rbn_client: Option<RbnClient>,
+    /// Set on connect, cleared when the client reports a disconnect
+    is_connected: bool,
+    radio: RadioManager,
+    radio_state: RadioState,
+    vfd_display: VfdDisplay,
+    table: TableState,
+    status_message: String,
+    last_purge: Instant,
+    /// Front-panel rotary encoder, when configured
+    #[cfg(target_os = "linux")]
+    input: Option<InputHandler>,
+    /// Watch-list and new-DXCC checks on incoming spots
+    alerts: SpotAlerts,
+    /// Alert rules, logbooks and spot sharing
+    integrations: Integrations,
+    /// QRZ or HamQTH operator lookups for the detail page, when enabled
+    callbook: Option<Callbook>,
+    /// LoTW users list, when enabled
+    lotw: Option<LotwUsers>,
+    /// Logging program selected spots are looked up in, when enabled
+    logger: Option<LoggerLink>,
+    /// SOTA/POTA self-spots of the `[activation]`
+    self_spotter: SelfSpotter,
+    /// MQTT spot and state publisher, when enabled
+    #[cfg(feature = "mqtt")]
+    mqtt: Option<MqttPublisher>,
+    /// Spot whose detail page was last put up, refreshed by callbook answers
+    detail_spot: Option<AggregatedSpot>,
+    /// Alert LEDs, when configured
+    #[cfg(target_os = "linux")]
+    leds: Option<LedIndicators>,
+    /// Piezo or sound card alerts, when configured
+    #[cfg(target_os = "linux")]
+    sound: Option<AlertSound>,
+    quit: bool,
+}
+
+impl TuiApp {
+    fn new() -> Self {
+        let config = Config::load();
+        // stderr would draw over the screen; other outputs still log
+        logging::init(&config.logging, false);
+        let radio = RadioManager::new(config.radio.clone());
+        let radio_state = radio.state();
+        let mut vfd_display = VfdDisplay::new();
+        vfd_display.set_scroll_interval(config.scroll_interval_seconds);
+        vfd_display.set_random_char_percent(config.random_char_percent);
+        vfd_display.set_driver_config(&config.display);
+        vfd_display.set_region(config.region);
+        vfd_display.set_units(config.units);
+        // Settings problems and hardware that fails to start are reported in
+        // the status line
+        let mut errors: Vec<String> = config.issues.iter().map(|i| i.to_string()).collect();
+        for extra in &config.extra_displays {
+            if let Err(e) = vfd_display.open_mirror(&extra.settings, &extra.port()) {
+                errors.push(format!("Display {}: {}", extra.settings.driver, e));
+            }
+        }
+        #[cfg(target_os = "linux")]
+        let input = InputHandler::start(&config.input).unwrap_or_else(|e| {
+            errors.push(e);
+            None
+        });
+        let mut alerts = SpotAlerts::new(&config.alerts);
+        alerts.set_callsign(&config.callsign);
+        if let Err(e) = alerts.load_dxcc(&config.alerts) {
+            errors.push(e);
+        }
+        let spot_store = SpotStore::new();
+        let (integrations, integration_errors) = Integrations::start(&config, &spot_store, &alerts);
+        errors.extend(integration_errors);
+        let callbook = Callbook::start(&config.qrz, &config.hamqth);
+        let lotw = LotwUsers::start(&config.lotw);
+        let logger = LoggerLink::start(&config.logger);
+        #[cfg(feature = "mqtt")]
+        let mqtt = if config.mqtt.enabled {
+            let mqtt = MqttPublisher::start(&config.mqtt, spot_store.clone());
+            let (columns, rows) = vfd_display.dimensions();
+            vfd_display.add_output(mqtt.display_output(columns, rows), Duration::from_secs(1));
+            Some(mqtt)
+        } else {
+            None
+        };
+        #[cfg(target_os = "linux")]
+        let leds = LedIndicators::start(&config.leds).unwrap_or_else(|e| {
+            errors.push(e);
+            None
+        });
+        #[cfg(target_os = "linux")]
+        let sound = AlertSound::start(&config.sound, config.units.timezone).unwrap_or_else(|e| {
+            errors.push(e);
+            None
+        });
+
+        let mut app = Self {
+            config,
+            control: Control::new(),
+            spot_store,
+            rbn_client: None,
+            is_connected: false,
+            radio,
+            radio_state,
+            vfd_display,
+            table: TableState::default().with_selected(Some(0)),
+            status_message: "Ready".to_string(),
+            last_purge: Instant::now(),
+            #[cfg(target_os = "linux")]
+            input,
+            alerts,
+            integrations,
+            callbook,
+            lotw,
+            logger,
+            self_spotter: SelfSpotter::new(),
+            #[cfg(feature = "mqtt")]
+            mqtt,
+            detail_spot: None,
+            #[cfg(target_os = "linux")]
+            leds,
+            #[cfg(target_os = "linux")]
+            sound,
+            quit: false,
+        };
+        app.connect_rbn();
+        if !errors.is_empty() {
+            app.status_message = errors.join("; ");
+        }
+        app
+    }
+
+    fn connect_rbn(&mut self) {
+        let callsign = match normalize_callsign(&self.config.callsign) {
+            Ok(callsign) => callsign,
+            Err(e) => {
+                self.status_message = format!("{}; set one in settings.toml to connect", e);
+                return;
+            }
+        };
+        let client = RbnClient::new();
+        match &self.config.replay_file {
+            Some(path) => client.replay(path.clone()),
+            None => client.connect(callsign, self.config.feed.server.clone()),
+        }
+        self.rbn_client = Some(client);
+        self.is_connected = true;
+        self.status_message = "Connecting...".to_string();
+    }
+
+    fn disconnect_rbn(&mut self) {
+        if let Some(ref client) = self.rbn_client {
+            client.disconnect();
+        }
+        self.rbn_client = None;
+        self.is_connected = false;
+        self.status_message = "Disconnected".to_string();
+    }
+
+    fn toggle_display(&mut self) {
+        if self.vfd_display.is_open() {
+            self.vfd_display.close();
+            self.status_message = "Display closed".to_string();
+            return;
+        }
+        let port = self.config.serial_port.clone();
+        self.status_message = match self.vfd_display.open(&port) {
+            Ok(()) => format!("Display opened on {}", port),
+            Err(e) => format!("Failed to open display: {}", e),
+        };
+    }
+
+    /// Spots shown in the table and on the display: filtered, on the
+    /// selected band
+    fn visible_spots(&self) -> Vec<AggregatedSpot> {
+        self.config.filter_spots(&self.spot_store, &self.alerts)
+    }
+
+    fn tune_selected(&mut self) {
+        let spots = self.visible_spots();
+        let Some(spot) = self.table.selected().and_then(|i| spots.get(i)) else {
+            return;
+        };
+        let mode = RadioMode::from_rbn_mode(&spot.mode);
+        if self.config.radio.use_sub_receiver {
+            self.radio.tune_sub(spot.frequency_khz, mode);
+        } else {
+            self.radio.tune(spot.frequency_khz, mode);
+        }
+    }
+
+    /// Put the selected spot's detail page up on the display
+    /// Look the selected spot up in the logging program, when enabled
+    fn selection_to_logger(&mut self) {
+        let spots = self.visible_spots();
+        if let (Some(logger), Some(spot)) = (
+            self.logger.as_mut(),
+            self.table.selected().and_then(|i| spots.get(i)),
+        ) {
+            logger.select(spot);
+        }
+    }
+
+    fn show_detail(&mut self) {
+        let spots = self.visible_spots();
+        let Some(spot) = self.table.selected().and_then(|i| spots.get(i)) else {
+            return;
+        };
+        let home = LatLon::from_grid(&self.config.grid);
+        let operator = self
+            .callbook
+            .as_mut()
+            .and_then(|callbook| callbook.get(&spot.callsign));
+        let lotw = self
+            .lotw
+            .as_ref()
+            .and_then(|lotw| lotw.last_upload(&spot.callsign));
+        let lines = detail_lines(spot, &self.alerts, operator, lotw, home, &self.config.units);
+        self.vfd_display.show_detail(spot, lines);
+        self.detail_spot = Some(spot.clone());
+    }
+
+    /// Encoder turns move the table selection (mirrored on the display),
+    /// a press opens the selected spot's detail page and another tunes it
+    #[cfg(target_os = "linux")]
+    fn process_input(&mut self) {
+        let Some(ref input) = self.input else {
+            return;
+        };
+        let events: Vec<InputEvent> = std::iter::from_fn(|| input.try_recv()).collect();
+        for event in events {
+            // Input while the display is off only wakes it
+            if self.vfd_display.wake() {
+                continue;
+            }
+            match event {
+                InputEvent::Turn(detents) => {
+                    let spots = self.visible_spots();
+                    if spots.is_empty() {
+                        continue;
+                    }
+                    let current = self.table.selected().unwrap_or(0) as i64;
+                    let index =
+                        (current + detents as i64).clamp(0, spots.len() as i64 - 1) as usize;
+                    self.table.select(Some(index));
+                    self.vfd_display.select_spot(Some(&spots[index]));
+                    if let Some(ref mut logger) = self.logger {
+                        logger.select(&spots[index]);
+                    }
+                }
+                InputEvent::Press if self.vfd_display.showing_detail() => {
+                    self.tune_selected();
+                    self.vfd_display.select_spot(None);
+                }
+                InputEvent::Press => self.show_detail(),
+                InputEvent::Action(action) => match action.command() {
+                    Some(command) => self.control.send(CommandSource::Button, command),
+                    // Display buttons do what their keybinding does
+                    None => self.handle_key(match action {
+                        ButtonAction::PageUp => KeyCode::PageUp,
+                        ButtonAction::PageDown => KeyCode::PageDown,
+                        ButtonAction::SelfSpot => KeyCode::Char('s'),
+                        _ => KeyCode::Char('v'),
+                    }),
+                },
+            }
+        }
+    }
+
+    fn handle_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.table.select_next();
+                self.selection_to_logger();
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.table.select_previous();
+                self.selection_to_logger();
+            }
+            KeyCode::Right | KeyCode::Tab => self.command(AppCommand::StepBand(1)),
+            KeyCode::Left | KeyCode::BackTab => self.command(AppCommand::StepBand(-1)),
+            KeyCode::Enter | KeyCode::Char('t') => self.tune_selected(),
+            KeyCode::Char('d') => self.show_detail(),
+            KeyCode::Char('+') | KeyCode::Char('=') => self.command(AppCommand::StepMinSnr(1)),
+            KeyCode::Char('-') => self.command(AppCommand::StepMinSnr(-1)),
+            KeyCode::Char('m') => self.command(AppCommand::ToggleCwOnly),
+            KeyCode::Char('S') => self.command(AppCommand::NextSort),
+            KeyCode::Char('a') => {
+                let index = AGE_OPTIONS
+                    .iter()
+                    .position(|age| *age == self.config.max_age_minutes)
+                    .map_or(0, |i| (i + 1) % AGE_OPTIONS.len());
+                self.command(AppCommand::SetMaxAge(AGE_OPTIONS[index]));
+            }
+            KeyCode::Char('c') => {
+                if self.rbn_client.is_some() {
+                    self.disconnect_rbn();
+                } else {
+                    self.connect_rbn();
+                }
+            }
+            KeyCode::Char('o') => self.toggle_display(),
+            KeyCode::Char('v') => {
+                self.vfd_display.step_view(1);
+                self.status_message = format!("Display view: {}", self.vfd_display.view_name());
+            }
+            KeyCode::PageUp | KeyCode::PageDown => {
+                let step = if code == KeyCode::PageDown { 1 } else { -1 };
+                self.vfd_display.step_page(step);
+                self.status_message = format!("Display page: {}", self.vfd_display.page_label());
+            }
+            KeyCode::Char('p') => self.command(AppCommand::NextProfile),
+            KeyCode::Char('f') => self.command(AppCommand::NextPreset),
+            KeyCode::Char('r') => self.command(AppCommand::NextRadio),
+            KeyCode::Char('s') => {
+                self.status_message = self
+                    .self_spotter
+                    .post(&self.config, &self.radio_state)
+                    .unwrap_or_else(|e| e);
+            }
+            _ => {}
+        }
+    }
+
+    /// Queue a settings change from the keyboard
+    fn command(&self, command: AppCommand) {
+        self.control.send(CommandSource::Keyboard, command);
+    }
+
+    /// Apply queued settings changes, starting the table over at the top
+    fn process_commands(&mut self) {
+        while let Some((command, result)) = self.control.try_apply(&mut self.config) {
+            match result {
+                Ok(status) => {
+                    if command.changes_profile() {
+                        self.alerts.set_lists(&self.config.alerts);
+                        self.integrations.set_lists(&self.config.alerts);
+                        self.vfd_display
+                            .set_attention(&self.config.display.attention);
+                        #[cfg(target_os = "linux")]
+                        if let Some(ref mut sound) = self.sound {
+                            sound.set_alerts(&self.config.sound.alerts);
+                        }
+                    }
+                    if command.changes_radio() {
+                        self.radio.reconfigure(self.config.radio.clone());
+                    }
+                    self.table.select(Some(0));
+                    self.status_message = status;
+                }
+                Err(e) => self.status_message = e,
+            }
+        }
+    }
+
+    /// Drain RBN and radio events and run periodic housekeeping
+    fn tick(&mut self) {
+        let answered = self
+            .callbook
+            .as_mut()
+            .map(Callbook::poll)
+            .unwrap_or_default();
+        if let Some(spot) = self.detail_spot.clone().filter(|spot| {
+            answered
+                .iter()
+                .any(|call| call.eq_ignore_ascii_case(&spot.callsign))
+        }) {
+            let home = LatLon::from_grid(&self.config.grid);
+            let operator = self
+                .callbook
+                .as_mut()
+                .and_then(|callbook| callbook.get(&spot.callsign));
+            let lotw = self
+                .lotw
+                .as_ref()
+                .and_then(|lotw| lotw.last_upload(&spot.callsign));
+            let lines = detail_lines(
+                &spot,
+                &self.alerts,
+                operator,
+                lotw,
+                home,
+                &self.config.units,
+            );
+            self.vfd_display.update_detail(&spot.callsign, lines);
+        }
+        self.integrations.poll(&mut self.alerts);
+        let mut disconnected = false;
+        if let Some(ref mut client) = self.rbn_client {
+            while let Some(msg) = client.try_recv() {
+                match msg {
+                    RbnMessage::Status(s) => self.status_message = s,
+                    RbnMessage::Spot(raw) => {
+                        if let Some(alert) = self.integrations.spot(&raw, &mut self.alerts) {
+                            self.status_message = alert.to_string();
+                            #[cfg(target_os = "linux")]
+                            if let Some(ref leds) = self.leds {
+                                leds.alert(&alert);
+                            }
+                            #[cfg(target_os = "linux")]
+                            if let Some(ref sound) = self.sound {
+                                sound.alert(&alert);
+                            }
+                            self.vfd_display.alert(&alert);
+                        }
+                        self.vfd_display.count_spot();
+                        self.spot_store.add_spot(raw);
+                    }
+                    RbnMessage::Announcement(announcement) => {
+                        self.integrations.announcement(&announcement);
+                        self.vfd_display.add_announcement(announcement);
+                    }
+                    RbnMessage::Disconnected => disconnected = true,
+                    RbnMessage::RawData { .. } => {}
+                }
+            }
+        }
+        if disconnected {
+            self.rbn_client = None;
+            self.is_connected = false;
+        }
+        while let Some(fired) = self.integrations.try_recv_match() {
+            self.status_message = fired.alert.to_string();
+            #[cfg(target_os = "linux")]
+            if let Some(ref leds) = self.leds {
+                leds.flash(&fired.led);
+            }
+            #[cfg(target_os = "linux")]
+            if let Some(sound) = self.sound.as_ref().filter(|_| fired.beep) {
+                sound.beep(&fired.alert);
+            }
+            self.vfd_display.alert(&fired.alert);
+        }
+        #[cfg(target_os = "linux")]
+        if let Some(ref mut leds) = self.leds {
+            leds.set_connected(self.is_connected);
+        }
+
+        while let Some(event) = self.radio.try_recv() {
+            match event {
+                RadioEvent::Status(s) | RadioEvent::Error(s) => self.status_message = s,
+                RadioEvent::Tuned {
+                    frequency_khz,
+                    mode,
+                } => self.integrations.tuned(frequency_khz, mode),
+            }
+        }
+        self.radio_state = self.radio.state();
+        if let Some(result) = self.self_spotter.try_recv() {
+            self.status_message = result.unwrap_or_else(|e| e);
+        }
+        #[cfg(target_os = "linux")]
+        self.process_input();
+
+        if self.last_purge.elapsed() >= Duration::from_secs(5) {
+            self.spot_store.purge_old_spots();
+            self.last_purge = Instant::now();
+        }
+
+        let spots = self.visible_spots();
+        self.vfd_display.set_vfo(self.radio_state.frequency_khz);
+        let tints = spots
+            .iter()
+            .filter_map(|spot| Some((spot.callsign.clone(), self.alerts.tint(spot)?)))
+            .collect();
+        self.vfd_display.set_tints(tints);
+        let locations = spots
+            .iter()
+            .filter_map(|spot| Some((spot.callsign.clone(), self.alerts.location(&spot.callsign)?)))
+            .collect();
+        self.vfd_display
+            .set_locations(LatLon::from_grid(&self.config.grid), locations);
+        self.vfd_display.set_status(DisplayStatus {
+            connected: self.is_connected,
+            filters: self.config.filter_summary(),
+            radio: if self.config.radio.enabled {
+                self.radio_state.summary()
+            } else {
+                String::new()
+            },
+        });
+        self.vfd_display.update(&spots);
+        self.integrations.update(&spots, &self.alerts);
+        if let Some(ref mut logger) = self.logger {
+            logger.update();
+        }
+        #[cfg(feature = "mqtt")]
+        if let Some(ref mut mqtt) = self.mqtt {
+            mqtt.publish_state(&self.config, self.is_connected, &self.radio_state);
+            while let Some(announcement) = mqtt.try_recv() {
+                self.status_message = announcement.to_string();
+                self.vfd_display.add_announcement(announcement);
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [tabs_area, table_area, status_area, help_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(3),
+            Constraint::Length(3),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let titles = std::iter::once("All").chain(Band::ALL.iter().map(|band| band.name()));
+        let selected_tab = self
+            .config
+            .band_filter
+            .and_then(|band| Band::ALL.iter().position(|b| *b == band))
+            .map_or(0, |i| i + 1);
+        let tabs = Tabs::new(titles)
+            .block(Block::default().borders(Borders::ALL).title("Band"))
+            .select(selected_tab)
+            .highlight_style(
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            );
+        frame.render_widget(tabs, tabs_area);
+
+        let spots = self.visible_spots();
+        if self.table.selected().is_some_and(|i| i >= spots.len()) {
+            self.table.select(spots.len().checked_sub(1));
+        }
+        let nearest = self
+            .radio_state
+            .frequency_khz
+            .and_then(|vfo| nearest_spot(&spots, vfo));
+        // Frequencies in band colors, callsigns tinted by the alert rules
+        let tinted = |color: Option<Rgb>| {
+            color.map_or(Style::default(), |Rgb(r, g, b)| {
+                Style::default().fg(Color::Rgb(r, g, b))
+            })
+        };
+        let rows = spots.iter().map(|spot| {
+            let age = spot.age_seconds();
+            let offset = nearest
+                .filter(|(near, _)| std::ptr::eq(*near, spot))
+                .map(|(_, offset)| offset);
+            let band = Band::from_khz(spot.frequency_khz).map(band_color);
+            let row = Row::new(vec![
+                Cell::from(self.config.units.frequency(spot.frequency_khz)).style(tinted(band)),
+                Cell::from(spot.callsign.clone()).style(tinted(self.alerts.tint(spot))),
+                Cell::from(format!("{}", spot.highest_snr)),
+                Cell::from(format!("{}", spot.average_speed.round() as i32)),
+                Cell::from(spot.mode.clone()),
+                Cell::from(format!("{}:{:02}", age / 60, age % 60)),
+                Cell::from(format!("{}", spot.spot_count)),
+                Cell::from(self.alerts.want_label(spot)),
+                Cell::from(offset.map(format_offset).unwrap_or_default()),
+            ]);
+            // Highlight the spot the radio is tuned to
+            if offset.is_some() {
+                row.style(Style::default().fg(Color::Yellow))
+            } else {
+                row
+            }
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(9),
+                Constraint::Length(10),
+                Constraint::Length(4),
+                Constraint::Length(4),
+                Constraint::Length(6),
+                Constraint::Length(6),
+                Constraint::Length(5),
+                Constraint::Length(5),
+                Constraint::Length(5),
+            ],
+        )
+        .header(
+            Row::new(vec![
+                "Freq", "Call", "SNR", "WPM", "Mode", "Age", "Spots", "Want", "Off",
+            ])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Spots ({})", spots.len())),
+        )
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let [table_area, ladder_area] =
+            Layout::horizontal([Constraint::Min(40), Constraint::Length(28)]).areas(table_area);
+        frame.render_stateful_widget(table, table_area, &mut self.table);
+        self.draw_ladder(frame, ladder_area);
+
+        let rbn = if self.rbn_client.is_some() {
+            format!("RBN {}", self.config.callsign)
+        } else {
+            "RBN off".to_string()
+        };
+        let radio = match (self.radio_state.frequency_khz, self.radio_state.mode) {
+            (Some(freq), Some(mode)) if self.radio_state.connected => {
+                format!(
+                    "{} {}",
+                    self.config.units.frequency_with_unit(freq),
+                    mode.to_rigctld_mode()
+                )
+            }
+            _ if self.radio_state.connected => format!("{} connected", self.radio_state.backend),
+            _ => "Radio off".to_string(),
+        };
+        let display = if self.vfd_display.is_open() {
+            format!("Display {}", self.vfd_display.port_name())
+        } else {
+            "Display closed".to_string()
+        };
+        let status = Paragraph::new(vec![
+            Line::from(format!(
+                "{} | {} | {} | SNR ≥ {} dB | Age ≤ {} min | {}",
+                rbn,
+                radio,
+                display,
+                self.config.min_snr,
+                self.config.max_age_minutes,
+                if self.config.cw_only {
+                    "CW"
+                } else {
+                    "All modes"
+                }
+            )),
+            Line::from(self.status_message.as_str()),
+        ])
+        .block(Block::default().borders(Borders::TOP));
+        frame.render_widget(status, status_area);
+
+        frame.render_widget(
+            Paragraph::new(HELP).style(Style::default().fg(Color::DarkGray)),
+            help_area,
+        );
+    }
+
+    /// Vertical band map: high frequencies at the top, VFO row highlighted
+    fn draw_ladder(&self, frame: &mut Frame, area: Rect) {
+        let spots = self.visible_spots();
+        let vfo = self.radio_state.frequency_khz;
+        let block = Block::default().borders(Borders::ALL);
+        let Some(band) = self
+            .config
+            .band_filter
+            .or_else(|| BandMap::current_band(&spots, vfo))
+        else {
+            frame.render_widget(
+                Paragraph::new("No activity").block(block.title("Band map")),
+                area,
+            );
+            return;
+        };
+
+        let map = BandMap::fit(band, &spots, vfo, self.config.region);
+        let cells = area.height.saturating_sub(2) as usize;
+        let mut calls = vec![Vec::new(); cells];
+        for spot in map.spots(&spots) {
+            if let Some(cell) = map.cell(spot.frequency_khz, cells) {
+                calls[cell].push(spot.callsign.as_str());
+            }
+        }
+        let vfo_cell = vfo.and_then(|khz| map.cell(khz, cells));
+        let lines: Vec<Line> = (0..cells)
+            .rev()
+            .map(|cell| {
+                let marker = if Some(cell) == vfo_cell { '▶' } else { '┤' };
+                let text = format!(
+                    "{:8.1}{}{}",
+                    map.frequency_at(cell, cells),
+                    marker,
+                    calls[cell].join(" ")
+                );
+                if Some(cell) == vfo_cell {
+                    Line::styled(text, Style::default().fg(Color::Yellow))
+                } else {
+                    Line::from(text)
+                }
+            })
+            .collect();
+        frame.render_widget(
+            Paragraph::new(lines).block(block.title(format!("{} map", band))),
+            area,
+        );
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        while !self.quit {
+            self.process_commands();
+            self.tick();
+            terminal.draw(|frame| self.draw(frame))?;
+            if event::poll(TICK)? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key.code);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn shutdown(&mut self) {
+        if self.rbn_client.is_some() {
+            self.disconnect_rbn();
+        }
+        self.vfd_display.close();
+        if let Err(e) = self.config.save() {
+            eprintln!("Failed to save config: {}", e);
+        }
+    }
+}
+
+/// Run the terminal UI until the user quits
+pub fn run() -> io::Result<()> {
+    let mut app = TuiApp::new();
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+    app.shutdown();
+    result
+}